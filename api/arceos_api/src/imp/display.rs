@@ -1,4 +1,4 @@
-pub use axdisplay::DisplayInfo as AxDisplayInfo;
+pub use axdisplay::{DisplayInfo as AxDisplayInfo, PixelFormat as AxPixelFormat};
 
 /// Gets the framebuffer information.
 pub fn ax_framebuffer_info() -> AxDisplayInfo {
@@ -2,6 +2,7 @@ use alloc::string::String;
 use axerrno::AxResult;
 use axfs::fops::{Directory, File};
 
+pub use axfs::fops::BlockCacheStats as AxBlockCacheStats;
 pub use axfs::fops::DirEntry as AxDirEntry;
 pub use axfs::fops::FileAttr as AxFileAttr;
 pub use axfs::fops::FilePerm as AxFilePerm;
@@ -9,6 +10,8 @@ pub use axfs::fops::FileType as AxFileType;
 pub use axfs::fops::OpenOptions as AxOpenOptions;
 pub use axio::SeekFrom as AxSeekFrom;
 
+#[cfg(feature = "serial")]
+pub use axfs::api::{Parity as AxParity, SerialConfig as AxSerialConfig, StopBits as AxStopBits};
 #[cfg(feature = "myfs")]
 pub use axfs::fops::{Disk as AxDisk, MyFileSystemIf};
 
@@ -78,6 +81,22 @@ pub fn ax_rename(old: &str, new: &str) -> AxResult {
     axfs::api::rename(old, new)
 }
 
+pub fn ax_symlink_attr(path: &str) -> AxResult<AxFileAttr> {
+    Ok(*axfs::api::symlink_metadata(path)?.raw_metadata())
+}
+
+pub fn ax_read_link(path: &str) -> AxResult<String> {
+    axfs::api::read_link(path)
+}
+
+pub fn ax_symlink(original: &str, link: &str) -> AxResult {
+    axfs::api::symlink(original, link)
+}
+
+pub fn ax_hard_link(original: &str, link: &str) -> AxResult {
+    axfs::api::hard_link(original, link)
+}
+
 pub fn ax_current_dir() -> AxResult<String> {
     axfs::api::current_dir()
 }
@@ -85,3 +104,29 @@ pub fn ax_current_dir() -> AxResult<String> {
 pub fn ax_set_current_dir(path: &str) -> AxResult {
     axfs::api::set_current_dir(path)
 }
+
+pub fn ax_mount(source: &str, target: &str, fstype: &str) -> AxResult {
+    axfs::api::mount(source, target, fstype)
+}
+
+pub fn ax_umount(target: &str) -> AxResult {
+    axfs::api::umount(target)
+}
+
+pub fn ax_sync() -> AxResult {
+    axfs::api::sync()
+}
+
+pub fn ax_block_cache_stats() -> AxBlockCacheStats {
+    axfs::api::block_cache_stats()
+}
+
+#[cfg(feature = "serial")]
+pub fn ax_serial_config(path: &str) -> AxResult<AxSerialConfig> {
+    axfs::api::serial_config(path)
+}
+
+#[cfg(feature = "serial")]
+pub fn ax_set_serial_config(path: &str, config: &AxSerialConfig) -> AxResult {
+    axfs::api::set_serial_config(path, config)
+}
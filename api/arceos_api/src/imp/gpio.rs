@@ -0,0 +1,35 @@
+use axerrno::AxResult;
+
+pub use axfs::api::{Edge as AxEdge, PinFunction as AxPinFunction, Pull as AxPull};
+
+pub fn ax_gpio_function(path: &str, pin: usize) -> AxResult<AxPinFunction> {
+    axfs::api::gpio_function(path, pin)
+}
+
+pub fn ax_gpio_set_function(path: &str, pin: usize, func: AxPinFunction) -> AxResult {
+    axfs::api::gpio_set_function(path, pin, func)
+}
+
+pub fn ax_gpio_level(path: &str, pin: usize) -> AxResult<bool> {
+    axfs::api::gpio_level(path, pin)
+}
+
+pub fn ax_gpio_set_level(path: &str, pin: usize, high: bool) -> AxResult {
+    axfs::api::gpio_set_level(path, pin, high)
+}
+
+pub fn ax_gpio_pull(path: &str, pin: usize) -> AxResult<AxPull> {
+    axfs::api::gpio_pull(path, pin)
+}
+
+pub fn ax_gpio_set_pull(path: &str, pin: usize, pull: AxPull) -> AxResult {
+    axfs::api::gpio_set_pull(path, pin, pull)
+}
+
+pub fn ax_gpio_set_edge_interrupt(path: &str, pin: usize, edge: AxEdge) -> AxResult {
+    axfs::api::gpio_set_edge_interrupt(path, pin, edge)
+}
+
+pub fn ax_gpio_wait_event(path: &str) -> AxResult<usize> {
+    axfs::api::gpio_wait_event(path)
+}
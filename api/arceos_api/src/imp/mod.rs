@@ -16,6 +16,11 @@ cfg_display! {
     pub use display::*;
 }
 
+cfg_gpio! {
+    mod gpio;
+    pub use gpio::*;
+}
+
 mod stdio {
     use core::fmt;
 
@@ -1,7 +1,8 @@
 use crate::io::AxPollState;
 use axerrno::AxResult;
-use axnet::{UdpSocket, TcpSocket};
+use axnet::{IcmpSocket, TcpSocket, UdpSocket};
 use core::net::{IpAddr, SocketAddr};
+use core::time::Duration;
 
 /// A handle to a TCP socket.
 pub struct AxTcpSocketHandle(TcpSocket);
@@ -9,6 +10,14 @@ pub struct AxTcpSocketHandle(TcpSocket);
 /// A handle to a UDP socket.
 pub struct AxUdpSocketHandle(UdpSocket);
 
+/// A handle to an ICMP socket.
+pub struct AxIcmpSocketHandle(IcmpSocket);
+
+/// The configuration of a network interface.
+pub use axnet::IfaceConfig as AxIfaceConfig;
+/// The information of a network interface.
+pub use axnet::IfaceInfo as AxIfaceInfo;
+
 ////////////////////////////////////////////////////////////////////////////////
 // TCP socket
 ////////////////////////////////////////////////////////////////////////////////
@@ -64,6 +73,76 @@ pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult {
     socket.0.shutdown()
 }
 
+pub fn ax_tcp_set_nodelay(socket: &AxTcpSocketHandle, nodelay: bool) -> AxResult {
+    socket.0.set_nodelay(nodelay);
+    Ok(())
+}
+
+pub fn ax_tcp_nodelay(socket: &AxTcpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.nodelay())
+}
+
+pub fn ax_tcp_set_keepalive(socket: &AxTcpSocketHandle, keepalive: bool) -> AxResult {
+    socket.0.set_keepalive(keepalive);
+    Ok(())
+}
+
+pub fn ax_tcp_keepalive(socket: &AxTcpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.keepalive())
+}
+
+pub fn ax_tcp_set_recv_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_recv_buffer_size(size);
+    Ok(())
+}
+
+pub fn ax_tcp_recv_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.recv_buffer_size())
+}
+
+pub fn ax_tcp_set_send_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_send_buffer_size(size);
+    Ok(())
+}
+
+pub fn ax_tcp_send_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.send_buffer_size())
+}
+
+pub fn ax_tcp_set_reuse_address(socket: &AxTcpSocketHandle, reuse: bool) -> AxResult {
+    socket.0.set_reuse_address(reuse);
+    Ok(())
+}
+
+pub fn ax_tcp_reuse_address(socket: &AxTcpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.reuse_address())
+}
+
+pub fn ax_tcp_set_read_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_read_timeout(timeout)
+}
+
+pub fn ax_tcp_read_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.read_timeout())
+}
+
+pub fn ax_tcp_set_write_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_write_timeout(timeout)
+}
+
+pub fn ax_tcp_write_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.write_timeout())
+}
+
+pub fn ax_tcp_set_linger(socket: &AxTcpSocketHandle, linger: Option<Duration>) -> AxResult {
+    socket.0.set_linger(linger);
+    Ok(())
+}
+
+pub fn ax_tcp_linger(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.linger())
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // UDP socket
 ////////////////////////////////////////////////////////////////////////////////
@@ -117,6 +196,104 @@ pub fn ax_udp_poll(socket: &AxUdpSocketHandle) -> AxResult<AxPollState> {
     socket.0.poll()
 }
 
+pub fn ax_udp_set_recv_buffer_size(socket: &AxUdpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_recv_buffer_size(size);
+    Ok(())
+}
+
+pub fn ax_udp_recv_buffer_size(socket: &AxUdpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.recv_buffer_size())
+}
+
+pub fn ax_udp_set_send_buffer_size(socket: &AxUdpSocketHandle, size: usize) -> AxResult {
+    socket.0.set_send_buffer_size(size);
+    Ok(())
+}
+
+pub fn ax_udp_send_buffer_size(socket: &AxUdpSocketHandle) -> AxResult<usize> {
+    Ok(socket.0.send_buffer_size())
+}
+
+pub fn ax_udp_set_reuse_address(socket: &AxUdpSocketHandle, reuse: bool) -> AxResult {
+    socket.0.set_reuse_address(reuse);
+    Ok(())
+}
+
+pub fn ax_udp_reuse_address(socket: &AxUdpSocketHandle) -> AxResult<bool> {
+    Ok(socket.0.reuse_address())
+}
+
+pub fn ax_udp_set_read_timeout(socket: &AxUdpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_read_timeout(timeout)
+}
+
+pub fn ax_udp_read_timeout(socket: &AxUdpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.read_timeout())
+}
+
+pub fn ax_udp_set_write_timeout(socket: &AxUdpSocketHandle, timeout: Option<Duration>) -> AxResult {
+    socket.0.set_write_timeout(timeout)
+}
+
+pub fn ax_udp_write_timeout(socket: &AxUdpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.write_timeout())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// ICMP socket
+////////////////////////////////////////////////////////////////////////////////
+
+pub fn ax_icmp_socket() -> AxIcmpSocketHandle {
+    AxIcmpSocketHandle(IcmpSocket::new())
+}
+
+pub fn ax_icmp_ident(socket: &AxIcmpSocketHandle) -> AxResult<u16> {
+    socket.0.ident()
+}
+
+pub fn ax_icmp_set_nonblocking(socket: &AxIcmpSocketHandle, nonblocking: bool) -> AxResult {
+    socket.0.set_nonblocking(nonblocking);
+    Ok(())
+}
+
+pub fn ax_icmp_bind(socket: &AxIcmpSocketHandle, ident: u16) -> AxResult {
+    socket.0.bind(ident)
+}
+
+pub fn ax_icmp_send_to(socket: &AxIcmpSocketHandle, buf: &[u8], addr: IpAddr) -> AxResult<usize> {
+    socket.0.send_to(buf, addr)
+}
+
+pub fn ax_icmp_recv_from(socket: &AxIcmpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, IpAddr)> {
+    socket.0.recv_from(buf)
+}
+
+pub fn ax_icmp_poll(socket: &AxIcmpSocketHandle) -> AxResult<AxPollState> {
+    socket.0.poll()
+}
+
+pub fn ax_icmp_set_read_timeout(
+    socket: &AxIcmpSocketHandle,
+    timeout: Option<Duration>,
+) -> AxResult {
+    socket.0.set_read_timeout(timeout)
+}
+
+pub fn ax_icmp_read_timeout(socket: &AxIcmpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.read_timeout())
+}
+
+pub fn ax_icmp_set_write_timeout(
+    socket: &AxIcmpSocketHandle,
+    timeout: Option<Duration>,
+) -> AxResult {
+    socket.0.set_write_timeout(timeout)
+}
+
+pub fn ax_icmp_write_timeout(socket: &AxIcmpSocketHandle) -> AxResult<Option<Duration>> {
+    Ok(socket.0.write_timeout())
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Miscellaneous
 ////////////////////////////////////////////////////////////////////////////////
@@ -129,3 +306,15 @@ pub fn ax_poll_interfaces() -> AxResult {
     axnet::poll_interfaces();
     Ok(())
 }
+
+pub fn ax_iface_list() -> alloc::vec::Vec<AxIfaceInfo> {
+    axnet::iface_list()
+}
+
+pub fn ax_iface_config(name: &str) -> AxResult<AxIfaceConfig> {
+    axnet::iface_config(name)
+}
+
+pub fn ax_set_iface_config(name: &str, config: &AxIfaceConfig) -> AxResult {
+    axnet::set_iface_config(name, config)
+}
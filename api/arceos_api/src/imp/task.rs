@@ -108,4 +108,29 @@ cfg_task! {
             }
         }
     }
+
+    #[cfg(feature = "sched_edf")]
+    pub use axtask::RtParams as AxRtParams;
+
+    #[cfg(feature = "sched_edf")]
+    pub fn ax_spawn_periodic<F>(
+        f: F,
+        name: alloc::string::String,
+        stack_size: usize,
+        params: AxRtParams,
+    ) -> Option<AxTaskHandle>
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        let inner = axtask::spawn_periodic(f, name, stack_size, params)?;
+        Some(AxTaskHandle {
+            id: inner.id().as_u64(),
+            inner,
+        })
+    }
+
+    #[cfg(feature = "sched_edf")]
+    pub fn ax_deadline_misses(task: &AxTaskHandle) -> usize {
+        task.inner.deadline_misses()
+    }
 }
@@ -134,6 +134,30 @@ pub mod task {
         /// `count` is `u32::MAX`, it will wake up all tasks in the wait queue.
         pub fn ax_wait_queue_wake(wq: &AxWaitQueueHandle, count: u32);
     }
+
+    define_api_type! {
+        @cfg "sched_edf";
+        pub type AxRtParams;
+    }
+
+    define_api! {
+        @cfg "sched_edf";
+
+        /// Spawns a periodic real-time task, which calls `f` once per period
+        /// until it returns `false`.
+        ///
+        /// Returns [`None`] if `params` is invalid, or the task does not fit in
+        /// any CPU allowed by the affinity of the current task.
+        pub fn ax_spawn_periodic(
+            f: impl FnMut() -> bool + Send + 'static,
+            name: alloc::string::String,
+            stack_size: usize,
+            params: AxRtParams
+        ) -> Option<AxTaskHandle>;
+        /// Returns the number of jobs of the given periodic task that completed
+        /// after their deadlines.
+        pub fn ax_deadline_misses(task: &AxTaskHandle) -> usize;
+    }
 }
 
 /// Filesystem manipulation operations.
@@ -150,10 +174,17 @@ pub mod fs {
         pub type AxFilePerm;
         pub type AxDirEntry;
         pub type AxSeekFrom;
+        pub type AxBlockCacheStats;
         #[cfg(feature = "myfs")]
         pub type AxDisk;
         #[cfg(feature = "myfs")]
         pub type MyFileSystemIf;
+        #[cfg(feature = "serial")]
+        pub type AxSerialConfig;
+        #[cfg(feature = "serial")]
+        pub type AxParity;
+        #[cfg(feature = "serial")]
+        pub type AxStopBits;
     }
 
     define_api! {
@@ -213,23 +244,54 @@ pub mod fs {
         ///
         /// It will delete the original file if `old` already exists.
         pub fn ax_rename(old: &str, new: &str) -> AxResult;
+        /// Returns attributes of the file at the given path, without following
+        /// the symbolic link if it is one.
+        pub fn ax_symlink_attr(path: &str) -> AxResult<AxFileAttr>;
+        /// Reads the target path of the symbolic link.
+        pub fn ax_read_link(path: &str) -> AxResult<alloc::string::String>;
+        /// Creates a symbolic link at `link` which points to `original`.
+        pub fn ax_symlink(original: &str, link: &str) -> AxResult;
+        /// Creates a hard link at `link` which refers to the same file as
+        /// `original`.
+        pub fn ax_hard_link(original: &str, link: &str) -> AxResult;
 
         /// Returns the current working directory.
         pub fn ax_current_dir() -> AxResult<alloc::string::String>;
         /// Changes the current working directory to the specified path.
         pub fn ax_set_current_dir(path: &str) -> AxResult;
+
+        /// Mounts the filesystem of type `fstype` from `source` on the
+        /// directory `target`.
+        pub fn ax_mount(source: &str, target: &str, fstype: &str) -> AxResult;
+        /// Unmounts the filesystem mounted on the directory `target`.
+        pub fn ax_umount(target: &str) -> AxResult;
+        /// Writes the cached data of all block devices back to the devices.
+        pub fn ax_sync() -> AxResult;
+        /// Returns the hit and miss counters of the block caches.
+        pub fn ax_block_cache_stats() -> AxBlockCacheStats;
+
+        /// Returns the line configuration of the serial port at `path`.
+        #[cfg(feature = "serial")]
+        pub fn ax_serial_config(path: &str) -> AxResult<AxSerialConfig>;
+        /// Sets the line configuration of the serial port at `path`.
+        #[cfg(feature = "serial")]
+        pub fn ax_set_serial_config(path: &str, config: &AxSerialConfig) -> AxResult;
     }
 }
 
-/// Networking primitives for TCP/UDP communication.
+/// Networking primitives for TCP/UDP/ICMP communication.
 pub mod net {
     use crate::{io::AxPollState, AxResult};
     use core::net::{IpAddr, SocketAddr};
+    use core::time::Duration;
 
     define_api_type! {
         @cfg "net";
         pub type AxTcpSocketHandle;
         pub type AxUdpSocketHandle;
+        pub type AxIcmpSocketHandle;
+        pub type AxIfaceConfig;
+        pub type AxIfaceInfo;
     }
 
     define_api! {
@@ -268,6 +330,39 @@ pub mod net {
         /// Closes the connection on the TCP socket.
         pub fn ax_tcp_shutdown(socket: &AxTcpSocketHandle) -> AxResult;
 
+        /// Disables or enables the Nagle's algorithm (`TCP_NODELAY`).
+        pub fn ax_tcp_set_nodelay(socket: &AxTcpSocketHandle, nodelay: bool) -> AxResult;
+        /// Returns whether the Nagle's algorithm is disabled (`TCP_NODELAY`).
+        pub fn ax_tcp_nodelay(socket: &AxTcpSocketHandle) -> AxResult<bool>;
+        /// Enables or disables keep-alive packets (`SO_KEEPALIVE`).
+        pub fn ax_tcp_set_keepalive(socket: &AxTcpSocketHandle, keepalive: bool) -> AxResult;
+        /// Returns whether keep-alive packets are sent (`SO_KEEPALIVE`).
+        pub fn ax_tcp_keepalive(socket: &AxTcpSocketHandle) -> AxResult<bool>;
+        /// Sets the receive buffer size of new connections (`SO_RCVBUF`).
+        pub fn ax_tcp_set_recv_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult;
+        /// Returns the receive buffer size (`SO_RCVBUF`).
+        pub fn ax_tcp_recv_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize>;
+        /// Sets the send buffer size of new connections (`SO_SNDBUF`).
+        pub fn ax_tcp_set_send_buffer_size(socket: &AxTcpSocketHandle, size: usize) -> AxResult;
+        /// Returns the send buffer size (`SO_SNDBUF`).
+        pub fn ax_tcp_send_buffer_size(socket: &AxTcpSocketHandle) -> AxResult<usize>;
+        /// Allows or disallows reusing the local address (`SO_REUSEADDR`).
+        pub fn ax_tcp_set_reuse_address(socket: &AxTcpSocketHandle, reuse: bool) -> AxResult;
+        /// Returns whether the local address can be reused (`SO_REUSEADDR`).
+        pub fn ax_tcp_reuse_address(socket: &AxTcpSocketHandle) -> AxResult<bool>;
+        /// Sets the timeout of receiving and accepting (`SO_RCVTIMEO`).
+        pub fn ax_tcp_set_read_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of receiving and accepting (`SO_RCVTIMEO`).
+        pub fn ax_tcp_read_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_tcp_set_write_timeout(socket: &AxTcpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_tcp_write_timeout(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the linger timeout of closing the connection (`SO_LINGER`).
+        pub fn ax_tcp_set_linger(socket: &AxTcpSocketHandle, linger: Option<Duration>) -> AxResult;
+        /// Returns the linger timeout of closing the connection (`SO_LINGER`).
+        pub fn ax_tcp_linger(socket: &AxTcpSocketHandle) -> AxResult<Option<Duration>>;
+
         // UDP socket
 
         /// Creates a new UDP socket.
@@ -304,6 +399,57 @@ pub mod net {
         /// Returns whether the UDP socket is readable or writable.
         pub fn ax_udp_poll(socket: &AxUdpSocketHandle) -> AxResult<AxPollState>;
 
+        /// Sets the receive buffer size, which takes effect on binding
+        /// (`SO_RCVBUF`).
+        pub fn ax_udp_set_recv_buffer_size(socket: &AxUdpSocketHandle, size: usize) -> AxResult;
+        /// Returns the receive buffer size (`SO_RCVBUF`).
+        pub fn ax_udp_recv_buffer_size(socket: &AxUdpSocketHandle) -> AxResult<usize>;
+        /// Sets the send buffer size, which takes effect on binding
+        /// (`SO_SNDBUF`).
+        pub fn ax_udp_set_send_buffer_size(socket: &AxUdpSocketHandle, size: usize) -> AxResult;
+        /// Returns the send buffer size (`SO_SNDBUF`).
+        pub fn ax_udp_send_buffer_size(socket: &AxUdpSocketHandle) -> AxResult<usize>;
+        /// Allows or disallows reusing the local address (`SO_REUSEADDR`).
+        pub fn ax_udp_set_reuse_address(socket: &AxUdpSocketHandle, reuse: bool) -> AxResult;
+        /// Returns whether the local address can be reused (`SO_REUSEADDR`).
+        pub fn ax_udp_reuse_address(socket: &AxUdpSocketHandle) -> AxResult<bool>;
+        /// Sets the timeout of receiving (`SO_RCVTIMEO`).
+        pub fn ax_udp_set_read_timeout(socket: &AxUdpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of receiving (`SO_RCVTIMEO`).
+        pub fn ax_udp_read_timeout(socket: &AxUdpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_udp_set_write_timeout(socket: &AxUdpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_udp_write_timeout(socket: &AxUdpSocketHandle) -> AxResult<Option<Duration>>;
+
+        // ICMP socket
+
+        /// Creates a new ICMP socket for echo requests and replies.
+        pub fn ax_icmp_socket() -> AxIcmpSocketHandle;
+        /// Returns the echo identifier that the ICMP socket is bound to.
+        pub fn ax_icmp_ident(socket: &AxIcmpSocketHandle) -> AxResult<u16>;
+        /// Moves this ICMP socket into or out of nonblocking mode.
+        pub fn ax_icmp_set_nonblocking(socket: &AxIcmpSocketHandle, nonblocking: bool) -> AxResult;
+        /// Binds the ICMP socket to the given echo identifier, or an unused
+        /// one if it's zero.
+        pub fn ax_icmp_bind(socket: &AxIcmpSocketHandle, ident: u16) -> AxResult;
+        /// Sends an echo request to the given address. On success, returns
+        /// the number of bytes written.
+        pub fn ax_icmp_send_to(socket: &AxIcmpSocketHandle, buf: &[u8], addr: IpAddr) -> AxResult<usize>;
+        /// Receives a single ICMP message on the ICMP socket. On success,
+        /// returns the number of bytes read and the origin.
+        pub fn ax_icmp_recv_from(socket: &AxIcmpSocketHandle, buf: &mut [u8]) -> AxResult<(usize, IpAddr)>;
+        /// Returns whether the ICMP socket is readable or writable.
+        pub fn ax_icmp_poll(socket: &AxIcmpSocketHandle) -> AxResult<AxPollState>;
+        /// Sets the timeout of receiving (`SO_RCVTIMEO`).
+        pub fn ax_icmp_set_read_timeout(socket: &AxIcmpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of receiving (`SO_RCVTIMEO`).
+        pub fn ax_icmp_read_timeout(socket: &AxIcmpSocketHandle) -> AxResult<Option<Duration>>;
+        /// Sets the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_icmp_set_write_timeout(socket: &AxIcmpSocketHandle, timeout: Option<Duration>) -> AxResult;
+        /// Returns the timeout of sending (`SO_SNDTIMEO`).
+        pub fn ax_icmp_write_timeout(socket: &AxIcmpSocketHandle) -> AxResult<Option<Duration>>;
+
         // Miscellaneous
 
         /// Resolves the host name to a list of IP addresses.
@@ -313,6 +459,16 @@ pub mod net {
         /// It may receive packets from the NIC and process them, and transmit queued
         /// packets to the NIC.
         pub fn ax_poll_interfaces() -> AxResult;
+        /// Returns the information of all network interfaces, e.g., names and
+        /// addresses.
+        pub fn ax_iface_list() -> alloc::vec::Vec<AxIfaceInfo>;
+        /// Returns the current configuration of the network interface with
+        /// the given name.
+        pub fn ax_iface_config(name: &str) -> AxResult<AxIfaceConfig>;
+        /// Changes the configuration of the network interface with the given
+        /// name, e.g., the IPv4 address, the gateway, DNS servers or whether to
+        /// use DHCP.
+        pub fn ax_set_iface_config(name: &str, config: &AxIfaceConfig) -> AxResult;
     }
 }
 
@@ -321,6 +477,7 @@ pub mod display {
     define_api_type! {
         @cfg "display";
         pub type AxDisplayInfo;
+        pub type AxPixelFormat;
     }
 
     define_api! {
@@ -332,6 +489,40 @@ pub mod display {
     }
 }
 
+/// GPIO operations on the controllers `/dev/gpiochip0`, `/dev/gpiochip1`, etc.
+pub mod gpio {
+    use crate::AxResult;
+
+    define_api_type! {
+        @cfg "gpio";
+        pub type AxPinFunction;
+        pub type AxPull;
+        pub type AxEdge;
+    }
+
+    define_api! {
+        @cfg "gpio";
+        /// Returns the function of the pin of the GPIO controller at `path`.
+        pub fn ax_gpio_function(path: &str, pin: usize) -> AxResult<AxPinFunction>;
+        /// Sets the function of the pin, i.e., makes it an input, an output, or
+        /// selects an alternative function.
+        pub fn ax_gpio_set_function(path: &str, pin: usize, func: AxPinFunction) -> AxResult;
+        /// Returns the level of the pin, `true` for high.
+        pub fn ax_gpio_level(path: &str, pin: usize) -> AxResult<bool>;
+        /// Drives the output pin to high or low.
+        pub fn ax_gpio_set_level(path: &str, pin: usize, high: bool) -> AxResult;
+        /// Returns the pull resistor of the pin.
+        pub fn ax_gpio_pull(path: &str, pin: usize) -> AxResult<AxPull>;
+        /// Sets the pull resistor of the pin.
+        pub fn ax_gpio_set_pull(path: &str, pin: usize, pull: AxPull) -> AxResult;
+        /// Selects the edges of the pin that are reported by
+        /// [`ax_gpio_wait_event`].
+        pub fn ax_gpio_set_edge_interrupt(path: &str, pin: usize, edge: AxEdge) -> AxResult;
+        /// Blocks until a selected edge is detected, and returns its pin.
+        pub fn ax_gpio_wait_event(path: &str) -> AxResult<usize>;
+    }
+}
+
 /// Input/output operations.
 pub mod io {
     define_api_type! {
@@ -3,6 +3,8 @@ fn main() {
 
     fn gen_pthread_mutex(out_file: &str) -> std::io::Result<()> {
         // TODO: generate size and initial content automatically.
+        // `pthread_cond_t` and `pthread_rwlock_t` share the layout of `pthread_mutex_t`: a wait
+        // queue followed by a word of state (see `axsync::{Condvar, RwLock}`).
         let (mutex_size, mutex_init) = if cfg!(feature = "multitask") {
             if cfg!(feature = "smp") {
                 (6, "{0, 8, 0, 0, 0, 0}") // core::mem::transmute::<_, [usize; 6]>(axsync::Mutex::new(()))
@@ -26,6 +28,18 @@ typedef struct {{
 }} pthread_mutex_t;
 
 #define PTHREAD_MUTEX_INITIALIZER {{ .__l = {mutex_init}}}
+
+typedef struct {{
+    long __l[{mutex_size}];
+}} pthread_cond_t;
+
+#define PTHREAD_COND_INITIALIZER {{ .__l = {mutex_init}}}
+
+typedef struct {{
+    long __l[{mutex_size}];
+}} pthread_rwlock_t;
+
+#define PTHREAD_RWLOCK_INITIALIZER {{ .__l = {mutex_init}}}
 "#
         )?;
         std::fs::write(out_file, output)?;
@@ -42,31 +56,55 @@ typedef struct {{
             "off_t",
             "mode_t",
             "sock.*",
+            "linger",
             "fd_set",
+            "pollfd",
+            "nfds_t",
             "timeval",
             "pthread_t",
             "pthread_attr_t",
             "pthread_mutex_t",
             "pthread_mutexattr_t",
+            "pthread_cond_t",
+            "pthread_condattr_t",
+            "pthread_rwlock_t",
+            "pthread_rwlockattr_t",
+            "sem_t",
             "epoll_event",
             "iovec",
             "clockid_t",
             "rlimit",
             "aibuf",
+            "cpu_set_t",
+            "sigaction",
+            "sigset_t",
+            "siginfo_t",
+            "itimerval",
         ];
         let allow_vars = [
             "O_.*",
             "AF_.*",
             "SOCK_.*",
             "IPPROTO_.*",
+            "SOL_.*",
+            "SO_.*",
+            "TCP_.*",
             "FD_.*",
             "F_.*",
             "_SC_.*",
             "EPOLL_CTL_.*",
             "EPOLL.*",
+            "POLL.*",
             "RLIMIT_.*",
             "EAI_.*",
             "MAXADDRS",
+            "SIG.*",
+            "SA_.*",
+            "ITIMER_.*",
+            "PROT_.*",
+            "MAP_.*",
+            "MREMAP_.*",
+            "MADV_.*",
         ];
 
         #[derive(Debug)]
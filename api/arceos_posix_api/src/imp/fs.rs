@@ -1,8 +1,8 @@
 use alloc::sync::Arc;
-use core::ffi::{c_char, c_int};
+use core::ffi::{c_char, c_int, c_ulong, c_void};
 
 use axerrno::{LinuxError, LinuxResult};
-use axfs::fops::OpenOptions;
+use axfs::fops::{FileAttr, OpenOptions};
 use axio::{PollState, SeekFrom};
 use axsync::Mutex;
 
@@ -24,7 +24,7 @@ impl File {
         super::fd_ops::add_file_like(Arc::new(self))
     }
 
-    fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
+    pub(crate) fn from_fd(fd: c_int) -> LinuxResult<Arc<Self>> {
         let f = super::fd_ops::get_file_like(fd)?;
         f.into_any()
             .downcast::<Self>()
@@ -42,21 +42,7 @@ impl FileLike for File {
     }
 
     fn stat(&self) -> LinuxResult<ctypes::stat> {
-        let metadata = self.inner.lock().get_attr()?;
-        let ty = metadata.file_type() as u8;
-        let perm = metadata.perm().bits() as u32;
-        let st_mode = ((ty as u32) << 12) | perm;
-        Ok(ctypes::stat {
-            st_ino: 1,
-            st_nlink: 1,
-            st_mode,
-            st_uid: 1000,
-            st_gid: 1000,
-            st_size: metadata.size() as _,
-            st_blocks: metadata.blocks() as _,
-            st_blksize: 512,
-            ..Default::default()
-        })
+        Ok(attr_to_stat(&self.inner.lock().get_attr()?))
     }
 
     fn into_any(self: Arc<Self>) -> Arc<dyn core::any::Any + Send + Sync> {
@@ -67,6 +53,8 @@ impl FileLike for File {
         Ok(PollState {
             readable: true,
             writable: true,
+            hangup: false,
+            error: false,
         })
     }
 
@@ -75,6 +63,39 @@ impl FileLike for File {
     }
 }
 
+#[cfg(feature = "mmap")]
+impl axmm::MappedFile for File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> axerrno::AxResult<usize> {
+        self.inner.lock().read_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> axerrno::AxResult<usize> {
+        self.inner.lock().write_at(offset, buf)
+    }
+
+    fn size(&self) -> axerrno::AxResult<u64> {
+        Ok(self.inner.lock().get_attr()?.size())
+    }
+}
+
+/// Convert file attributes to [`ctypes::stat`].
+fn attr_to_stat(metadata: &FileAttr) -> ctypes::stat {
+    let ty = metadata.file_type() as u8;
+    let perm = metadata.perm().bits() as u32;
+    let st_mode = ((ty as u32) << 12) | perm;
+    ctypes::stat {
+        st_ino: 1,
+        st_nlink: 1,
+        st_mode,
+        st_uid: 1000,
+        st_gid: 1000,
+        st_size: metadata.size() as _,
+        st_blocks: metadata.blocks() as _,
+        st_blksize: 512,
+        ..Default::default()
+    }
+}
+
 /// Convert open flags to [`OpenOptions`].
 fn flags_to_options(flags: c_int, _mode: ctypes::mode_t) -> OpenOptions {
     let flags = flags as u32;
@@ -177,7 +198,57 @@ pub unsafe fn sys_lstat(path: *const c_char, buf: *mut ctypes::stat) -> ctypes::
         if buf.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        unsafe { *buf = Default::default() }; // TODO
+        let metadata = axfs::api::symlink_metadata(path?)?;
+        unsafe { *buf = attr_to_stat(metadata.raw_metadata()) };
+        Ok(0)
+    })
+}
+
+/// Read the target of the symbolic link `path` into `buf`, which is not
+/// null-terminated.
+///
+/// Return the number of bytes placed in `buf`.
+pub unsafe fn sys_readlink(
+    path: *const c_char,
+    buf: *mut c_char,
+    bufsize: usize,
+) -> ctypes::ssize_t {
+    let path = char_ptr_to_str(path);
+    debug!("sys_readlink <= {:?} {:#x} {}", path, buf as usize, bufsize);
+    syscall_body!(sys_readlink, {
+        if buf.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let target = axfs::api::read_link(path?)?;
+        let len = target.len().min(bufsize);
+        let dst = unsafe { core::slice::from_raw_parts_mut(buf as *mut u8, len) };
+        dst.copy_from_slice(&target.as_bytes()[..len]);
+        Ok(len as ctypes::ssize_t)
+    })
+}
+
+/// Create a symbolic link `linkpath` which contains the string `target`.
+///
+/// Return 0 if success.
+pub fn sys_symlink(target: *const c_char, linkpath: *const c_char) -> c_int {
+    syscall_body!(sys_symlink, {
+        let target = char_ptr_to_str(target)?;
+        let linkpath = char_ptr_to_str(linkpath)?;
+        debug!("sys_symlink <= {:?} {:?}", target, linkpath);
+        axfs::api::symlink(target, linkpath)?;
+        Ok(0)
+    })
+}
+
+/// Create a hard link `newpath` to the existing file `oldpath`.
+///
+/// Return 0 if success.
+pub fn sys_link(oldpath: *const c_char, newpath: *const c_char) -> c_int {
+    syscall_body!(sys_link, {
+        let oldpath = char_ptr_to_str(oldpath)?;
+        let newpath = char_ptr_to_str(newpath)?;
+        debug!("sys_link <= {:?} {:?}", oldpath, newpath);
+        axfs::api::hard_link(oldpath, newpath)?;
         Ok(0)
     })
 }
@@ -215,3 +286,58 @@ pub fn sys_rename(old: *const c_char, new: *const c_char) -> c_int {
         Ok(0)
     })
 }
+
+/// Mount the filesystem of type `fstype` from `source` on the directory
+/// `target`.
+///
+/// `mountflags` and `data` are ignored currently. Return 0 if success.
+pub fn sys_mount(
+    source: *const c_char,
+    target: *const c_char,
+    fstype: *const c_char,
+    mountflags: c_ulong,
+    data: *const c_void,
+) -> c_int {
+    syscall_body!(sys_mount, {
+        let source = char_ptr_to_str(source)?;
+        let target = char_ptr_to_str(target)?;
+        let fstype = char_ptr_to_str(fstype)?;
+        debug!(
+            "sys_mount <= {:?} {:?} {:?} {:#x} {:#x}",
+            source, target, fstype, mountflags, data as usize
+        );
+        axfs::api::mount(source, target, fstype)?;
+        Ok(0)
+    })
+}
+
+/// Synchronize the data of the file `fd` to the disk.
+///
+/// Return 0 if success.
+pub fn sys_fsync(fd: c_int) -> c_int {
+    syscall_body!(sys_fsync, {
+        debug!("sys_fsync <= {}", fd);
+        File::from_fd(fd)?.inner.lock().flush()?;
+        Ok(0)
+    })
+}
+
+/// Write all cached data of the filesystems to the disks.
+pub fn sys_sync() {
+    debug!("sys_sync");
+    if let Err(e) = axfs::api::sync() {
+        warn!("sys_sync failed: {:?}", e);
+    }
+}
+
+/// Unmount the filesystem mounted on the directory `target`.
+///
+/// Return 0 if success.
+pub fn sys_umount(target: *const c_char) -> c_int {
+    syscall_body!(sys_umount, {
+        let target = char_ptr_to_str(target)?;
+        debug!("sys_umount <= {:?}", target);
+        axfs::api::umount(target)?;
+        Ok(0)
+    })
+}
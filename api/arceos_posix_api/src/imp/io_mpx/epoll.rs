@@ -11,6 +11,7 @@ use axerrno::{LinuxError, LinuxResult};
 use axhal::time::current_time;
 use axsync::Mutex;
 
+use super::{notifies_events, wait_events};
 use crate::ctypes;
 use crate::imp::fd_ops::{add_file_like, get_file_like, FileLike};
 
@@ -73,6 +74,12 @@ impl EpollInstance {
         Ok(0)
     }
 
+    /// Whether the events of all interested files are notified.
+    fn notifies_events(&self) -> bool {
+        let interest_list = self.events.lock();
+        interest_list.keys().all(|&fd| notifies_events(fd as c_int))
+    }
+
     fn poll_all(&self, events: &mut [ctypes::epoll_event]) -> LinuxResult<usize> {
         let ready_list = self.events.lock();
         let mut events_num = 0;
@@ -190,6 +197,7 @@ pub unsafe fn sys_epoll_wait(
         loop {
             #[cfg(feature = "net")]
             axnet::poll_interfaces();
+            let seen = super::events();
             let events_num = epoll_instance.poll_all(events)?;
             if events_num > 0 {
                 return Ok(events_num as c_int);
@@ -199,7 +207,7 @@ pub unsafe fn sys_epoll_wait(
                 debug!("    timeout!");
                 return Ok(0);
             }
-            crate::sys_sched_yield();
+            wait_events(seen, epoll_instance.notifies_events(), deadline);
         }
     })
 }
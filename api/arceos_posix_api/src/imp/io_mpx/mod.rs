@@ -1,16 +1,67 @@
 //! I/O multiplexing:
 //!
 //! * [`select`](select::sys_select)
+//! * [`poll`](poll::sys_poll)
+//! * [`ppoll`](poll::sys_ppoll)
 //! * [`epoll_create`](epoll::sys_epoll_create)
 //! * [`epoll_ctl`](epoll::sys_epoll_ctl)
 //! * [`epoll_wait`](epoll::sys_epoll_wait)
 
 #[cfg(feature = "epoll")]
 mod epoll;
+#[cfg(feature = "poll")]
+mod poll;
 #[cfg(feature = "select")]
 mod select;
 
 #[cfg(feature = "epoll")]
 pub use self::epoll::{sys_epoll_create, sys_epoll_ctl, sys_epoll_wait};
+#[cfg(feature = "poll")]
+pub use self::poll::{sys_poll, sys_ppoll};
 #[cfg(feature = "select")]
 pub use self::select::sys_select;
+
+use core::ffi::c_int;
+
+use axhal::time::TimeValue;
+
+/// Returns the number of notified events so far, which must be read before
+/// polling files and be passed to the following [`wait_events`].
+fn events() -> usize {
+    #[cfg(feature = "net")]
+    return axnet::socket_events();
+    #[cfg(not(feature = "net"))]
+    0
+}
+
+/// Whether the readiness changes of the file `fd` are notified, so that
+/// [`wait_events`] can sleep on it.
+///
+/// Only network sockets notify their events, other files are polled in a
+/// loop.
+#[cfg_attr(not(feature = "net"), allow(unused_variables))]
+fn notifies_events(fd: c_int) -> bool {
+    #[cfg(feature = "net")]
+    return super::net::notifies_events(fd);
+    #[cfg(not(feature = "net"))]
+    false
+}
+
+/// Blocks the current task until an event is notified after `seen` is read
+/// by [`events`], or the `deadline` has passed.
+///
+/// `notified` is whether all polled files notify their events, otherwise it
+/// only yields the CPU. Pending signals are not handled here, so that the
+/// caller can see them and return `EINTR`.
+#[cfg_attr(not(feature = "net"), allow(unused_variables))]
+fn wait_events(seen: usize, notified: bool, deadline: Option<TimeValue>) {
+    #[cfg(feature = "net")]
+    if notified {
+        axnet::wait_socket_events(seen, deadline);
+        return;
+    }
+    #[cfg(feature = "multitask")]
+    axtask::yield_now();
+    #[cfg(not(feature = "multitask"))]
+    crate::sys_sched_yield();
+}
@@ -0,0 +1,164 @@
+use core::ffi::{c_int, c_void};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::{current_time, TimeValue};
+#[cfg(feature = "multitask")]
+use axtask::signal;
+
+use super::{events, notifies_events, wait_events};
+use crate::ctypes;
+use crate::imp::fd_ops::{get_file_like, AX_FILE_LIMIT};
+
+/// Polls all file descriptors once, fills `revents` and returns the number of
+/// ready ones.
+fn poll_all(fds: &mut [ctypes::pollfd]) -> usize {
+    let mut res_num = 0;
+    for pfd in fds.iter_mut() {
+        pfd.revents = 0;
+        // negative fds are ignored
+        if pfd.fd < 0 {
+            continue;
+        }
+        let events = pfd.events as u32;
+        let revents = match get_file_like(pfd.fd).and_then(|f| f.poll()) {
+            Ok(state) => {
+                let mut revents = 0;
+                if state.readable {
+                    revents |= events & ctypes::POLLIN;
+                }
+                if state.writable {
+                    revents |= events & ctypes::POLLOUT;
+                }
+                // `POLLHUP` and `POLLERR` are always reported.
+                if state.hangup {
+                    revents |= ctypes::POLLHUP;
+                }
+                if state.error {
+                    revents |= ctypes::POLLERR;
+                }
+                revents
+            }
+            Err(LinuxError::EBADF) => ctypes::POLLNVAL,
+            Err(e) => {
+                debug!("    error: {} {:?}", pfd.fd, e);
+                ctypes::POLLERR
+            }
+        };
+        if revents != 0 {
+            pfd.revents = revents as _;
+            res_num += 1;
+        }
+    }
+    res_num
+}
+
+unsafe fn poll_impl(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    deadline: Option<TimeValue>,
+) -> LinuxResult<c_int> {
+    if nfds as usize > AX_FILE_LIMIT {
+        return Err(LinuxError::EINVAL);
+    }
+    let fds = if nfds == 0 {
+        &mut []
+    } else if fds.is_null() {
+        return Err(LinuxError::EFAULT);
+    } else {
+        unsafe { core::slice::from_raw_parts_mut(fds, nfds as usize) }
+    };
+
+    // Wake up `wait_events` sleeping on socket events when a signal arrives.
+    #[cfg(all(feature = "multitask", feature = "net"))]
+    {
+        let old_waker = signal::set_signal_waker(Some(axnet::socket_events_waker()));
+        let res = poll_until(fds, deadline);
+        signal::set_signal_waker(old_waker);
+        res
+    }
+    #[cfg(not(all(feature = "multitask", feature = "net")))]
+    poll_until(fds, deadline)
+}
+
+/// Polls the file descriptors until some of them are ready, the `deadline`
+/// has passed, or a signal that is not blocked is pending.
+fn poll_until(fds: &mut [ctypes::pollfd], deadline: Option<TimeValue>) -> LinuxResult<c_int> {
+    loop {
+        #[cfg(feature = "net")]
+        axnet::poll_interfaces();
+        let seen = events();
+        let res = poll_all(fds);
+        if res > 0 {
+            return Ok(res as c_int);
+        }
+        #[cfg(feature = "multitask")]
+        if signal::signal_pending() {
+            return Err(LinuxError::EINTR);
+        }
+
+        if deadline.map_or(false, |ddl| current_time() >= ddl) {
+            debug!("    timeout!");
+            return Ok(0);
+        }
+        let notified = fds
+            .iter()
+            .filter(|pfd| pfd.fd >= 0)
+            .all(|pfd| notifies_events(pfd.fd));
+        wait_events(seen, notified, deadline);
+    }
+}
+
+/// Wait for some event on the file descriptors, with a timeout in
+/// milliseconds. A negative timeout means an infinite timeout.
+///
+/// Return the number of file descriptors with events or errors, or 0 if
+/// timed out. Return `-EINTR` if a signal that is not blocked arrives before
+/// any event.
+pub unsafe fn sys_poll(fds: *mut ctypes::pollfd, nfds: ctypes::nfds_t, timeout: c_int) -> c_int {
+    debug!("sys_poll <= {:#x} {} {}", fds as usize, nfds, timeout);
+    syscall_body!(sys_poll, {
+        let deadline = (!timeout.is_negative())
+            .then(|| current_time() + Duration::from_millis(timeout as u64));
+        unsafe { poll_impl(fds, nfds, deadline) }
+    })
+}
+
+/// Wait for some event on the file descriptors, like [`sys_poll`] but with a
+/// `timespec` timeout. A null timeout means an infinite timeout.
+///
+/// If `sigmask` is not null, it replaces the blocked signals during the wait,
+/// and the signal that interrupts the wait is handled before the blocked
+/// signals are restored, like `sigsuspend`.
+#[cfg_attr(not(feature = "multitask"), allow(unused_variables))]
+pub unsafe fn sys_ppoll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    timeout: *const ctypes::timespec,
+    sigmask: *const c_void,
+) -> c_int {
+    debug!(
+        "sys_ppoll <= {:#x} {} {:#x}",
+        fds as usize, nfds, timeout as usize
+    );
+    syscall_body!(sys_ppoll, {
+        let deadline = match unsafe { timeout.as_ref() } {
+            Some(ts) => {
+                if ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+                    return Err(LinuxError::EINVAL);
+                }
+                Some(current_time() + Duration::from(*ts))
+            }
+            None => None,
+        };
+        #[cfg(feature = "multitask")]
+        if let Some(mask) = unsafe { sigmask.cast::<ctypes::sigset_t>().as_ref() } {
+            let old_mask = signal::set_signal_mask((*mask).into());
+            let res = unsafe { poll_impl(fds, nfds, deadline) };
+            signal::handle_signals();
+            signal::set_signal_mask(old_mask);
+            return res;
+        }
+        unsafe { poll_impl(fds, nfds, deadline) }
+    })
+}
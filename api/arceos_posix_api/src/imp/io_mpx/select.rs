@@ -3,6 +3,7 @@ use core::ffi::c_int;
 use axerrno::{LinuxError, LinuxResult};
 use axhal::time::current_time;
 
+use super::{events, notifies_events, wait_events};
 use crate::{ctypes, imp::fd_ops::get_file_like};
 
 const FD_SETSIZE: usize = 1024;
@@ -46,6 +47,19 @@ impl FdSets {
         Self { nfds, bits }
     }
 
+    /// Returns all file descriptors in the sets.
+    fn fds(&self) -> impl Iterator<Item = c_int> + '_ {
+        (0..self.nfds)
+            .filter(|&fd| {
+                let (idx, bit) = (fd / BITS_PER_USIZE, 1 << (fd % BITS_PER_USIZE));
+                let all_bits = self.bits[idx]
+                    | self.bits[idx + FD_SETSIZE_USIZES]
+                    | self.bits[idx + FD_SETSIZE_USIZES * 2];
+                all_bits & bit != 0
+            })
+            .map(|fd| fd as c_int)
+    }
+
     fn poll_all(
         &self,
         res_read_fds: *mut ctypes::fd_set,
@@ -136,6 +150,7 @@ pub unsafe fn sys_select(
         loop {
             #[cfg(feature = "net")]
             axnet::poll_interfaces();
+            let seen = events();
             let res = fd_sets.poll_all(readfds, writefds, exceptfds)?;
             if res > 0 {
                 return Ok(res);
@@ -145,7 +160,7 @@ pub unsafe fn sys_select(
                 debug!("    timeout!");
                 return Ok(0);
             }
-            crate::sys_sched_yield();
+            wait_events(seen, fd_sets.fds().all(notifies_events), deadline);
         }
     })
 }
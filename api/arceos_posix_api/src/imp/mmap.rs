@@ -0,0 +1,210 @@
+use core::ffi::{c_int, c_void};
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::mem::{VirtAddr, PAGE_SIZE_4K};
+use axhal::paging::MappingFlags;
+use axmm::{kernel_aspace, Backend};
+
+use crate::ctypes;
+
+// When pages are unmapped or their flags are changed, TLB entries are only
+// flushed on the current CPU, so other CPUs may access the freed frames.
+#[cfg(feature = "smp")]
+compile_error!("`mmap` can't be used with `smp`, as there is no TLB shootdown");
+
+/// Converts `prot` to mapping flags.
+///
+/// Writable or executable pages are also readable, as on most architectures.
+fn prot_to_flags(prot: c_int) -> LinuxResult<MappingFlags> {
+    let prot = prot as u32;
+    if prot & !(ctypes::PROT_READ | ctypes::PROT_WRITE | ctypes::PROT_EXEC) != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let mut flags = MappingFlags::empty();
+    if prot != ctypes::PROT_NONE {
+        flags |= MappingFlags::READ;
+    }
+    if prot & ctypes::PROT_WRITE != 0 {
+        flags |= MappingFlags::WRITE;
+    }
+    if prot & ctypes::PROT_EXEC != 0 {
+        flags |= MappingFlags::EXECUTE;
+    }
+    Ok(flags)
+}
+
+/// Checks that `addr` is page aligned and `len` is not zero, and returns the
+/// start address and the size rounded up to pages.
+fn page_range(addr: *mut c_void, len: ctypes::size_t) -> LinuxResult<(VirtAddr, usize)> {
+    let start = VirtAddr::from(addr as usize);
+    if !start.is_aligned_4k() || len == 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let size = len
+        .checked_add(PAGE_SIZE_4K - 1)
+        .ok_or(LinuxError::ENOMEM)?
+        & !(PAGE_SIZE_4K - 1);
+    Ok((start, size))
+}
+
+#[cfg(feature = "fs")]
+fn file_backend(fd: c_int, offset: u64, shared: bool) -> LinuxResult<Backend> {
+    let file = super::fs::File::from_fd(fd).map_err(|e| match e {
+        LinuxError::EINVAL => LinuxError::ENODEV, // not a regular file
+        e => e,
+    })?;
+    Ok(Backend::File {
+        file,
+        offset,
+        shared,
+    })
+}
+
+#[cfg(not(feature = "fs"))]
+fn file_backend(_fd: c_int, _offset: u64, _shared: bool) -> LinuxResult<Backend> {
+    Err(LinuxError::ENODEV) // no regular files
+}
+
+/// Map files or devices into memory.
+///
+/// Anonymous mappings are filled with zeros, and file mappings are filled
+/// with the file contents when mapped. Changes to `MAP_SHARED` file mappings
+/// are written back to the file when unmapped.
+///
+/// All mappings are in a dedicated area of the kernel address space, so
+/// `MAP_FIXED` addresses must be in it.
+pub unsafe fn sys_mmap(
+    addr: *mut c_void,
+    len: ctypes::size_t,
+    prot: c_int,
+    flags: c_int,
+    fd: c_int,
+    off: ctypes::off_t,
+) -> *mut c_void {
+    debug!(
+        "sys_mmap <= {:#x} {:#x} {:#x} {:#x} {} {:#x}",
+        addr as usize, len, prot, flags, fd, off
+    );
+    syscall_body!(sys_mmap, {
+        let map_flags = prot_to_flags(prot)?;
+        let flags = flags as u32;
+        let shared = match flags & ctypes::MAP_TYPE {
+            ctypes::MAP_SHARED | ctypes::MAP_SHARED_VALIDATE => true,
+            ctypes::MAP_PRIVATE => false,
+            _ => return Err(LinuxError::EINVAL),
+        };
+        if len == 0 || off < 0 || off as usize % PAGE_SIZE_4K != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let fixed = flags & ctypes::MAP_FIXED != 0;
+        let (hint, size) = if fixed {
+            page_range(addr, len)?
+        } else {
+            page_range(core::ptr::null_mut(), len)?
+        };
+        let backend = if flags & ctypes::MAP_ANONYMOUS != 0 {
+            Backend::Anonymous
+        } else {
+            file_backend(fd, off as u64, shared)?
+        };
+
+        let mut aspace = kernel_aspace().lock();
+        let start = if fixed {
+            aspace.unmap(hint, size)?;
+            hint
+        } else {
+            aspace
+                .find_free_area(VirtAddr::from(addr as usize), size)
+                .ok_or(LinuxError::ENOMEM)?
+        };
+        aspace.map(start, size, map_flags, backend)?;
+        Ok(start.as_mut_ptr() as *mut c_void)
+    })
+}
+
+/// Unmap the pages in the range `[addr, addr + len)`.
+///
+/// Changes to `MAP_SHARED` file mappings are written back to the file.
+pub unsafe fn sys_munmap(addr: *mut c_void, len: ctypes::size_t) -> c_int {
+    debug!("sys_munmap <= {:#x} {:#x}", addr as usize, len);
+    syscall_body!(sys_munmap, {
+        let (start, size) = page_range(addr, len)?;
+        kernel_aspace().lock().unmap(start, size)?;
+        Ok(0)
+    })
+}
+
+/// Set the protection of the pages in the range `[addr, addr + len)`.
+pub unsafe fn sys_mprotect(addr: *mut c_void, len: ctypes::size_t, prot: c_int) -> c_int {
+    debug!(
+        "sys_mprotect <= {:#x} {:#x} {:#x}",
+        addr as usize, len, prot
+    );
+    syscall_body!(sys_mprotect, {
+        let flags = prot_to_flags(prot)?;
+        if len == 0 {
+            return Ok(0);
+        }
+        let (start, size) = page_range(addr, len)?;
+        kernel_aspace().lock().protect(start, size, flags)?;
+        Ok(0)
+    })
+}
+
+/// Expand or shrink the mapping `[old_address, old_address + old_size)`,
+/// which must be within a single mapping.
+///
+/// It may be moved only if `MREMAP_MAYMOVE` is set. `MREMAP_FIXED` and
+/// `MREMAP_DONTUNMAP` are not supported, so `new_address` is ignored.
+pub unsafe fn sys_mremap(
+    old_address: *mut c_void,
+    old_size: ctypes::size_t,
+    new_size: ctypes::size_t,
+    flags: c_int,
+    new_address: *mut c_void,
+) -> *mut c_void {
+    debug!(
+        "sys_mremap <= {:#x} {:#x} {:#x} {:#x} {:#x}",
+        old_address as usize, old_size, new_size, flags, new_address as usize
+    );
+    syscall_body!(sys_mremap, {
+        let flags = flags as u32;
+        if flags & !ctypes::MREMAP_MAYMOVE != 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        let (old_start, old_size) = page_range(old_address, old_size)?;
+        let (_, new_size) = page_range(core::ptr::null_mut(), new_size)?;
+        let may_move = flags & ctypes::MREMAP_MAYMOVE != 0;
+        let new_start = kernel_aspace()
+            .lock()
+            .remap(old_start, old_size, new_size, may_move)?;
+        Ok(new_start.as_mut_ptr() as *mut c_void)
+    })
+}
+
+/// Give advice about the use of memory in the range `[addr, addr + len)`.
+///
+/// `MADV_DONTNEED` refills the pages from their backends, i.e., anonymous
+/// pages are zeroed and file pages are read again. Other advice has no
+/// effect.
+pub unsafe fn sys_madvise(addr: *mut c_void, len: ctypes::size_t, advice: c_int) -> c_int {
+    debug!("sys_madvise <= {:#x} {:#x} {}", addr as usize, len, advice);
+    syscall_body!(sys_madvise, {
+        match advice as u32 {
+            ctypes::MADV_NORMAL
+            | ctypes::MADV_RANDOM
+            | ctypes::MADV_SEQUENTIAL
+            | ctypes::MADV_WILLNEED
+            | ctypes::MADV_DONTNEED => {}
+            _ => return Err(LinuxError::EINVAL),
+        }
+        if len == 0 {
+            return Ok(0);
+        }
+        let (start, size) = page_range(addr, len)?;
+        if advice as u32 == ctypes::MADV_DONTNEED {
+            kernel_aspace().lock().discard(start, size)?;
+        }
+        Ok(0)
+    })
+}
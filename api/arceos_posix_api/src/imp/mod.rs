@@ -10,11 +10,15 @@ pub mod time;
 pub mod fd_ops;
 #[cfg(feature = "fs")]
 pub mod fs;
-#[cfg(any(feature = "select", feature = "epoll"))]
+#[cfg(any(feature = "select", feature = "poll", feature = "epoll"))]
 pub mod io_mpx;
 #[cfg(feature = "net")]
 pub mod net;
 #[cfg(feature = "pipe")]
 pub mod pipe;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 #[cfg(feature = "multitask")]
 pub mod pthread;
+#[cfg(feature = "multitask")]
+pub mod signal;
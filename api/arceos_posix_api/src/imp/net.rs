@@ -1,20 +1,88 @@
 use alloc::{sync::Arc, vec, vec::Vec};
 use core::ffi::{c_char, c_int, c_void};
 use core::mem::size_of;
-use core::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::time::Duration;
 
 use axerrno::{LinuxError, LinuxResult};
 use axio::PollState;
-use axnet::{TcpSocket, UdpSocket};
+use axnet::{IcmpSocket, RawSocket, TcpSocket, UdpSocket};
 use axsync::Mutex;
 
+use self::unix::{UnixAddr, UnixSocket};
 use super::fd_ops::FileLike;
 use crate::ctypes;
 use crate::utils::char_ptr_to_str;
 
+mod unix;
+
 pub enum Socket {
     Udp(Mutex<UdpSocket>),
     Tcp(Mutex<TcpSocket>),
+    Unix(UnixSocket),
+    Raw(Mutex<RawSocket>),
+    Icmp {
+        socket: Mutex<IcmpSocket>,
+        ipv6: bool,
+    },
+}
+
+/// Whether `fd` is a socket, whose readiness changes are notified by
+/// [`axnet::socket_events`].
+pub(crate) fn notifies_events(fd: c_int) -> bool {
+    Socket::from_fd(fd).is_ok()
+}
+
+/// The length of the IPv6 header, which is not delivered to IPv6 raw sockets.
+const IPV6_HEADER_LEN: usize = 40;
+
+/// A socket address of any supported family.
+enum SockAddr {
+    Inet(SocketAddr),
+    Unix(UnixAddr),
+}
+
+impl SockAddr {
+    fn from_sockaddr(
+        addr: *const ctypes::sockaddr,
+        addrlen: ctypes::socklen_t,
+    ) -> LinuxResult<Self> {
+        if addr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        if (addrlen as usize) < size_of::<ctypes::sa_family_t>() {
+            return Err(LinuxError::EINVAL);
+        }
+        match unsafe { (*addr).sa_family } as u32 {
+            ctypes::AF_UNIX => Ok(Self::Unix(UnixAddr::from_sockaddr(addr, addrlen)?)),
+            _ => Ok(Self::Inet(from_sockaddr(addr, addrlen)?)),
+        }
+    }
+
+    unsafe fn write_to(
+        &self,
+        dst: *mut ctypes::sockaddr,
+        addrlen: *mut ctypes::socklen_t,
+    ) -> LinuxResult {
+        match self {
+            Self::Inet(addr) => unsafe { write_sockaddr(*addr, dst, addrlen) },
+            Self::Unix(addr) => unsafe { addr.write_to(dst, addrlen) },
+        }
+    }
+
+    fn inet(self) -> LinuxResult<SocketAddr> {
+        match self {
+            Self::Inet(addr) => Ok(addr),
+            Self::Unix(_) => Err(LinuxError::EAFNOSUPPORT),
+        }
+    }
+
+    fn unix(self) -> LinuxResult<UnixAddr> {
+        match self {
+            Self::Inet(_) => Err(LinuxError::EINVAL),
+            Self::Unix(addr) => Ok(addr),
+        }
+    }
 }
 
 impl Socket {
@@ -33,6 +101,8 @@ impl Socket {
         match self {
             Socket::Udp(udpsocket) => Ok(udpsocket.lock().send(buf)?),
             Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().send(buf)?),
+            Socket::Unix(unixsocket) => unixsocket.send(buf),
+            Socket::Raw(_) | Socket::Icmp { .. } => Err(LinuxError::EDESTADDRREQ),
         }
     }
 
@@ -40,6 +110,8 @@ impl Socket {
         match self {
             Socket::Udp(udpsocket) => Ok(udpsocket.lock().recv_from(buf).map(|e| e.0)?),
             Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf)?),
+            Socket::Unix(unixsocket) => unixsocket.recv_from(buf).map(|e| e.0),
+            Socket::Raw(_) | Socket::Icmp { .. } => self.recvfrom(buf).map(|e| e.0),
         }
     }
 
@@ -47,67 +119,138 @@ impl Socket {
         match self {
             Socket::Udp(udpsocket) => Ok(udpsocket.lock().poll()?),
             Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().poll()?),
+            Socket::Unix(unixsocket) => Ok(unixsocket.poll()),
+            Socket::Raw(rawsocket) => Ok(rawsocket.lock().poll()?),
+            Socket::Icmp { socket, .. } => Ok(socket.lock().poll()?),
         }
     }
 
-    fn local_addr(&self) -> LinuxResult<SocketAddr> {
+    fn local_addr(&self) -> LinuxResult<SockAddr> {
         match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().local_addr()?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().local_addr()?),
+            Socket::Udp(udpsocket) => Ok(SockAddr::Inet(udpsocket.lock().local_addr()?)),
+            Socket::Tcp(tcpsocket) => Ok(SockAddr::Inet(tcpsocket.lock().local_addr()?)),
+            Socket::Unix(unixsocket) => Ok(SockAddr::Unix(unixsocket.local_addr())),
+            Socket::Raw(rawsocket) => Ok(SockAddr::Inet(unspecified_addr(
+                rawsocket.lock().is_ipv6(),
+                0,
+            ))),
+            // The port is the echo identifier, as in Linux.
+            Socket::Icmp { socket, ipv6 } => Ok(SockAddr::Inet(unspecified_addr(
+                *ipv6,
+                socket.lock().ident().unwrap_or(0),
+            ))),
         }
     }
 
-    fn peer_addr(&self) -> LinuxResult<SocketAddr> {
+    fn peer_addr(&self) -> LinuxResult<SockAddr> {
         match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().peer_addr()?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().peer_addr()?),
+            Socket::Udp(udpsocket) => Ok(SockAddr::Inet(udpsocket.lock().peer_addr()?)),
+            Socket::Tcp(tcpsocket) => Ok(SockAddr::Inet(tcpsocket.lock().peer_addr()?)),
+            Socket::Unix(unixsocket) => Ok(SockAddr::Unix(unixsocket.peer_addr()?)),
+            Socket::Raw(_) | Socket::Icmp { .. } => Err(LinuxError::ENOTCONN),
         }
     }
 
-    fn bind(&self, addr: SocketAddr) -> LinuxResult {
+    fn bind(&self, addr: SockAddr) -> LinuxResult {
         match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().bind(addr)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().bind(addr)?),
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().bind(addr.inet()?)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().bind(addr.inet()?)?),
+            Socket::Unix(unixsocket) => unixsocket.bind(addr.unix()?),
+            // The source address of raw sockets is chosen by routing, so the
+            // address is only checked.
+            Socket::Raw(rawsocket) => {
+                check_family(addr.inet()?, rawsocket.lock().is_ipv6())?;
+                Ok(())
+            }
+            Socket::Icmp { socket, ipv6 } => {
+                let addr = addr.inet()?;
+                check_family(addr, *ipv6)?;
+                Ok(socket.lock().bind(addr.port())?)
+            }
         }
     }
 
-    fn connect(&self, addr: SocketAddr) -> LinuxResult {
+    fn connect(&self, addr: SockAddr) -> LinuxResult {
         match self {
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().connect(addr)?),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().connect(addr)?),
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().connect(addr.inet()?)?),
+            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().connect(addr.inet()?)?),
+            Socket::Unix(unixsocket) => unixsocket.connect(addr.unix()?),
+            Socket::Raw(_) | Socket::Icmp { .. } => Err(LinuxError::EOPNOTSUPP),
         }
     }
 
-    fn sendto(&self, buf: &[u8], addr: SocketAddr) -> LinuxResult<usize> {
+    fn sendto(&self, buf: &[u8], addr: SockAddr) -> LinuxResult<usize> {
         match self {
             // diff: must bind before sendto
-            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send_to(buf, addr)?),
+            Socket::Udp(udpsocket) => Ok(udpsocket.lock().send_to(buf, addr.inet()?)?),
             Socket::Tcp(_) => Err(LinuxError::EISCONN),
+            Socket::Unix(unixsocket) => unixsocket.send_to(buf, addr.unix()?),
+            Socket::Raw(rawsocket) => {
+                let rawsocket = rawsocket.lock();
+                let addr = addr.inet()?;
+                check_family(addr, rawsocket.is_ipv6())?;
+                Ok(rawsocket.send_to(buf, addr.ip())?)
+            }
+            Socket::Icmp { socket, ipv6 } => {
+                let addr = addr.inet()?;
+                check_family(addr, *ipv6)?;
+                Ok(socket.lock().send_to(buf, addr.ip())?)
+            }
         }
     }
 
-    fn recvfrom(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<SocketAddr>)> {
+    fn recvfrom(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<SockAddr>)> {
         match self {
             // diff: must bind before recvfrom
             Socket::Udp(udpsocket) => Ok(udpsocket
                 .lock()
                 .recv_from(buf)
-                .map(|res| (res.0, Some(res.1)))?),
+                .map(|res| (res.0, Some(SockAddr::Inet(res.1))))?),
             Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().recv(buf).map(|res| (res, None))?),
+            Socket::Unix(unixsocket) => unixsocket
+                .recv_from(buf)
+                .map(|res| (res.0, res.1.map(SockAddr::Unix))),
+            // The IP header is included for IPv4 but not for IPv6, as in Linux.
+            Socket::Raw(rawsocket) => {
+                let rawsocket = rawsocket.lock();
+                let (len, addr) = if rawsocket.is_ipv6() {
+                    let mut packet = vec![0; IPV6_HEADER_LEN + buf.len()];
+                    let (len, addr) = rawsocket.recv_from(&mut packet)?;
+                    let len = len.saturating_sub(IPV6_HEADER_LEN);
+                    buf[..len].copy_from_slice(&packet[IPV6_HEADER_LEN..IPV6_HEADER_LEN + len]);
+                    (len, addr)
+                } else {
+                    rawsocket.recv_from(buf)?
+                };
+                Ok((len, Some(SockAddr::Inet(SocketAddr::new(addr, 0)))))
+            }
+            Socket::Icmp { socket, .. } => {
+                let (len, addr) = socket.lock().recv_from(buf)?;
+                Ok((len, Some(SockAddr::Inet(SocketAddr::new(addr, 0)))))
+            }
         }
     }
 
     fn listen(&self) -> LinuxResult {
         match self {
-            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
+            Socket::Udp(_) | Socket::Raw(_) | Socket::Icmp { .. } => Err(LinuxError::EOPNOTSUPP),
             Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().listen()?),
+            Socket::Unix(unixsocket) => unixsocket.listen(),
         }
     }
 
-    fn accept(&self) -> LinuxResult<TcpSocket> {
+    fn accept(&self) -> LinuxResult<(Socket, SockAddr)> {
         match self {
-            Socket::Udp(_) => Err(LinuxError::EOPNOTSUPP),
-            Socket::Tcp(tcpsocket) => Ok(tcpsocket.lock().accept()?),
+            Socket::Udp(_) | Socket::Raw(_) | Socket::Icmp { .. } => Err(LinuxError::EOPNOTSUPP),
+            Socket::Tcp(tcpsocket) => {
+                let new_socket = tcpsocket.lock().accept()?;
+                let addr = new_socket.peer_addr()?;
+                Ok((Socket::Tcp(Mutex::new(new_socket)), SockAddr::Inet(addr)))
+            }
+            Socket::Unix(unixsocket) => {
+                let (new_socket, addr) = unixsocket.accept()?;
+                Ok((Socket::Unix(new_socket), SockAddr::Unix(addr)))
+            }
         }
     }
 
@@ -126,10 +269,200 @@ impl Socket {
                 tcpsocket.shutdown()?;
                 Ok(())
             }
+
+            Socket::Unix(unixsocket) => unixsocket.shutdown(),
+            Socket::Raw(_) => Err(LinuxError::ENOTCONN),
+            Socket::Icmp { socket, .. } => Ok(socket.lock().shutdown()?),
+        }
+    }
+
+    fn set_option(&self, level: u32, name: u32, val: SockOptVal) -> LinuxResult {
+        match (self, level, name) {
+            (Socket::Udp(udpsocket), ctypes::SOL_SOCKET, _) => {
+                let udpsocket = udpsocket.lock();
+                match name {
+                    ctypes::SO_REUSEADDR => udpsocket.set_reuse_address(val.flag()?),
+                    ctypes::SO_RCVBUF => udpsocket.set_recv_buffer_size(val.size()?),
+                    ctypes::SO_SNDBUF => udpsocket.set_send_buffer_size(val.size()?),
+                    ctypes::SO_RCVTIMEO => udpsocket.set_read_timeout(val.timeout()?)?,
+                    ctypes::SO_SNDTIMEO => udpsocket.set_write_timeout(val.timeout()?)?,
+                    _ => return Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Tcp(tcpsocket), ctypes::SOL_SOCKET, _) => {
+                let tcpsocket = tcpsocket.lock();
+                match name {
+                    ctypes::SO_REUSEADDR => tcpsocket.set_reuse_address(val.flag()?),
+                    ctypes::SO_KEEPALIVE => tcpsocket.set_keepalive(val.flag()?),
+                    ctypes::SO_RCVBUF => tcpsocket.set_recv_buffer_size(val.size()?),
+                    ctypes::SO_SNDBUF => tcpsocket.set_send_buffer_size(val.size()?),
+                    ctypes::SO_RCVTIMEO => tcpsocket.set_read_timeout(val.timeout()?)?,
+                    ctypes::SO_SNDTIMEO => tcpsocket.set_write_timeout(val.timeout()?)?,
+                    ctypes::SO_LINGER => tcpsocket.set_linger(val.linger()?),
+                    _ => return Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Tcp(tcpsocket), ctypes::IPPROTO_TCP, ctypes::TCP_NODELAY) => {
+                tcpsocket.lock().set_nodelay(val.flag()?)
+            }
+            (Socket::Raw(rawsocket), ctypes::SOL_SOCKET, _) => {
+                let rawsocket = rawsocket.lock();
+                match name {
+                    ctypes::SO_RCVTIMEO => rawsocket.set_read_timeout(val.timeout()?)?,
+                    ctypes::SO_SNDTIMEO => rawsocket.set_write_timeout(val.timeout()?)?,
+                    _ => return Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Icmp { socket, .. }, ctypes::SOL_SOCKET, _) => {
+                let socket = socket.lock();
+                match name {
+                    ctypes::SO_RCVTIMEO => socket.set_read_timeout(val.timeout()?)?,
+                    ctypes::SO_SNDTIMEO => socket.set_write_timeout(val.timeout()?)?,
+                    _ => return Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            _ => return Err(LinuxError::ENOPROTOOPT),
+        }
+        Ok(())
+    }
+
+    fn get_option(&self, level: u32, name: u32, val: &mut SockOptBuf) -> LinuxResult {
+        match (self, level, name) {
+            (Socket::Udp(udpsocket), ctypes::SOL_SOCKET, _) => {
+                let udpsocket = udpsocket.lock();
+                match name {
+                    ctypes::SO_REUSEADDR => val.put_flag(udpsocket.reuse_address()),
+                    ctypes::SO_RCVBUF => val.put_size(udpsocket.recv_buffer_size()),
+                    ctypes::SO_SNDBUF => val.put_size(udpsocket.send_buffer_size()),
+                    ctypes::SO_RCVTIMEO => val.put_timeout(udpsocket.read_timeout()),
+                    ctypes::SO_SNDTIMEO => val.put_timeout(udpsocket.write_timeout()),
+                    _ => Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Tcp(tcpsocket), ctypes::SOL_SOCKET, _) => {
+                let tcpsocket = tcpsocket.lock();
+                match name {
+                    ctypes::SO_REUSEADDR => val.put_flag(tcpsocket.reuse_address()),
+                    ctypes::SO_KEEPALIVE => val.put_flag(tcpsocket.keepalive()),
+                    ctypes::SO_RCVBUF => val.put_size(tcpsocket.recv_buffer_size()),
+                    ctypes::SO_SNDBUF => val.put_size(tcpsocket.send_buffer_size()),
+                    ctypes::SO_RCVTIMEO => val.put_timeout(tcpsocket.read_timeout()),
+                    ctypes::SO_SNDTIMEO => val.put_timeout(tcpsocket.write_timeout()),
+                    ctypes::SO_LINGER => val.put_linger(tcpsocket.linger()),
+                    _ => Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Tcp(tcpsocket), ctypes::IPPROTO_TCP, ctypes::TCP_NODELAY) => {
+                val.put_flag(tcpsocket.lock().nodelay())
+            }
+            (Socket::Raw(rawsocket), ctypes::SOL_SOCKET, _) => {
+                let rawsocket = rawsocket.lock();
+                match name {
+                    ctypes::SO_RCVTIMEO => val.put_timeout(rawsocket.read_timeout()),
+                    ctypes::SO_SNDTIMEO => val.put_timeout(rawsocket.write_timeout()),
+                    _ => Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            (Socket::Icmp { socket, .. }, ctypes::SOL_SOCKET, _) => {
+                let socket = socket.lock();
+                match name {
+                    ctypes::SO_RCVTIMEO => val.put_timeout(socket.read_timeout()),
+                    ctypes::SO_SNDTIMEO => val.put_timeout(socket.write_timeout()),
+                    _ => Err(LinuxError::ENOPROTOOPT),
+                }
+            }
+            _ => Err(LinuxError::ENOPROTOOPT),
         }
     }
 }
 
+/// The value passed to `setsockopt`.
+struct SockOptVal {
+    ptr: *const c_void,
+    len: usize,
+}
+
+impl SockOptVal {
+    fn read<T: Copy>(&self) -> LinuxResult<T> {
+        if self.ptr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        if self.len < size_of::<T>() {
+            return Err(LinuxError::EINVAL);
+        }
+        // SAFETY: the caller of `sys_setsockopt` guarantees that the buffer
+        // is valid for `len` bytes.
+        Ok(unsafe { (self.ptr as *const T).read_unaligned() })
+    }
+
+    fn flag(&self) -> LinuxResult<bool> {
+        Ok(self.read::<c_int>()? != 0)
+    }
+
+    /// Negative sizes are treated as 0 and then clamped by the socket.
+    fn size(&self) -> LinuxResult<usize> {
+        Ok(self.read::<c_int>()?.max(0) as usize)
+    }
+
+    /// A zero `timeval` means no timeout.
+    fn timeout(&self) -> LinuxResult<Option<Duration>> {
+        let tv = self.read::<ctypes::timeval>()?;
+        if tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec) {
+            return Err(LinuxError::EDOM);
+        }
+        let dur = Duration::from(tv);
+        Ok((!dur.is_zero()).then_some(dur))
+    }
+
+    fn linger(&self) -> LinuxResult<Option<Duration>> {
+        let linger = self.read::<ctypes::linger>()?;
+        Ok((linger.l_onoff != 0).then(|| Duration::from_secs(linger.l_linger.max(0) as u64)))
+    }
+}
+
+/// The buffer passed to `getsockopt`.
+struct SockOptBuf {
+    ptr: *mut c_void,
+    len: *mut ctypes::socklen_t,
+}
+
+impl SockOptBuf {
+    /// Writes the value and its length. The value is truncated if the buffer
+    /// is too small.
+    fn write<T>(&mut self, val: &T) -> LinuxResult {
+        if self.ptr.is_null() || self.len.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        // SAFETY: the caller of `sys_getsockopt` guarantees that the length
+        // is valid, and the buffer is valid for that many bytes.
+        unsafe {
+            let len = size_of::<T>().min(*self.len as usize);
+            core::ptr::copy_nonoverlapping(val as *const T as *const u8, self.ptr as *mut u8, len);
+            *self.len = len as _;
+        }
+        Ok(())
+    }
+
+    fn put_flag(&mut self, flag: bool) -> LinuxResult {
+        self.write(&(flag as c_int))
+    }
+
+    fn put_size(&mut self, size: usize) -> LinuxResult {
+        self.write(&(size.min(c_int::MAX as usize) as c_int))
+    }
+
+    fn put_timeout(&mut self, timeout: Option<Duration>) -> LinuxResult {
+        self.write(&ctypes::timeval::from(timeout.unwrap_or(Duration::ZERO)))
+    }
+
+    fn put_linger(&mut self, linger: Option<Duration>) -> LinuxResult {
+        self.write(&ctypes::linger {
+            l_onoff: linger.is_some() as c_int,
+            l_linger: linger.map_or(0, |d| d.as_secs().min(c_int::MAX as u64) as c_int),
+        })
+    }
+}
+
 impl FileLike for Socket {
     fn read(&self, buf: &mut [u8]) -> LinuxResult<usize> {
         self.recv(buf)
@@ -165,6 +498,9 @@ impl FileLike for Socket {
         match self {
             Socket::Udp(udpsocket) => udpsocket.lock().set_nonblocking(nonblock),
             Socket::Tcp(tcpsocket) => tcpsocket.lock().set_nonblocking(nonblock),
+            Socket::Unix(unixsocket) => unixsocket.set_nonblocking(nonblock),
+            Socket::Raw(rawsocket) => rawsocket.lock().set_nonblocking(nonblock),
+            Socket::Icmp { socket, .. } => socket.lock().set_nonblocking(nonblock),
         }
         Ok(())
     }
@@ -194,14 +530,86 @@ impl From<ctypes::sockaddr_in> for SocketAddrV4 {
     }
 }
 
-fn into_sockaddr(addr: SocketAddr) -> (ctypes::sockaddr, ctypes::socklen_t) {
+impl From<SocketAddrV6> for ctypes::sockaddr_in6 {
+    fn from(addr: SocketAddrV6) -> ctypes::sockaddr_in6 {
+        ctypes::sockaddr_in6 {
+            sin6_family: ctypes::AF_INET6 as u16,
+            sin6_port: addr.port().to_be(),
+            sin6_flowinfo: addr.flowinfo().to_be(),
+            sin6_addr: ctypes::in6_addr {
+                __in6_union: ctypes::in6_addr__bindgen_ty_1 {
+                    __s6_addr: addr.ip().octets(),
+                },
+            },
+            sin6_scope_id: addr.scope_id(),
+        }
+    }
+}
+
+impl From<ctypes::sockaddr_in6> for SocketAddrV6 {
+    fn from(addr: ctypes::sockaddr_in6) -> SocketAddrV6 {
+        SocketAddrV6::new(
+            Ipv6Addr::from(unsafe { addr.sin6_addr.__in6_union.__s6_addr }),
+            u16::from_be(addr.sin6_port),
+            u32::from_be(addr.sin6_flowinfo),
+            addr.sin6_scope_id,
+        )
+    }
+}
+
+/// Store `addr` to the buffer `dst` of `*addrlen` bytes, and set `*addrlen`
+/// to the actual size of the address.
+///
+/// The address is truncated if the buffer is too small.
+unsafe fn write_sockaddr(
+    addr: SocketAddr,
+    dst: *mut ctypes::sockaddr,
+    addrlen: *mut ctypes::socklen_t,
+) -> LinuxResult {
     debug!("    Sockaddr: {}", addr);
-    match addr {
-        SocketAddr::V4(addr) => (
-            unsafe { *(&ctypes::sockaddr_in::from(addr) as *const _ as *const ctypes::sockaddr) },
-            size_of::<ctypes::sockaddr>() as _,
-        ),
-        SocketAddr::V6(_) => panic!("IPv6 is not supported"),
+    if dst.is_null() || addrlen.is_null() {
+        return Err(LinuxError::EFAULT);
+    }
+    let (sin, sin6);
+    let (src, len) = match addr {
+        SocketAddr::V4(addr) => {
+            sin = ctypes::sockaddr_in::from(addr);
+            (
+                &sin as *const _ as *const u8,
+                size_of::<ctypes::sockaddr_in>(),
+            )
+        }
+        SocketAddr::V6(addr) => {
+            sin6 = ctypes::sockaddr_in6::from(addr);
+            (
+                &sin6 as *const _ as *const u8,
+                size_of::<ctypes::sockaddr_in6>(),
+            )
+        }
+    };
+    unsafe {
+        let copy_len = len.min(*addrlen as usize);
+        core::ptr::copy_nonoverlapping(src, dst as *mut u8, copy_len);
+        *addrlen = len as _;
+    }
+    Ok(())
+}
+
+/// Returns the unspecified address of the given family with the given port.
+fn unspecified_addr(ipv6: bool, port: u16) -> SocketAddr {
+    if ipv6 {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port)
+    } else {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)
+    }
+}
+
+/// Checks that `addr` is of the family of a socket.
+fn check_family(addr: SocketAddr, ipv6: bool) -> LinuxResult {
+    if addr.is_ipv6() == ipv6 {
+        Ok(())
+    } else {
+        Err(LinuxError::EAFNOSUPPORT)
     }
 }
 
@@ -212,16 +620,25 @@ fn from_sockaddr(
     if addr.is_null() {
         return Err(LinuxError::EFAULT);
     }
-    if addrlen != size_of::<ctypes::sockaddr>() as _ {
-        return Err(LinuxError::EINVAL);
-    }
-
-    let mid = unsafe { *(addr as *const ctypes::sockaddr_in) };
-    if mid.sin_family != ctypes::AF_INET as u16 {
+    if (addrlen as usize) < size_of::<ctypes::sa_family_t>() {
         return Err(LinuxError::EINVAL);
     }
 
-    let res = SocketAddr::V4(mid.into());
+    let res = match unsafe { (*addr).sa_family } as u32 {
+        ctypes::AF_INET => {
+            if (addrlen as usize) < size_of::<ctypes::sockaddr_in>() {
+                return Err(LinuxError::EINVAL);
+            }
+            SocketAddr::V4(unsafe { *(addr as *const ctypes::sockaddr_in) }.into())
+        }
+        ctypes::AF_INET6 => {
+            if (addrlen as usize) < size_of::<ctypes::sockaddr_in6>() {
+                return Err(LinuxError::EINVAL);
+            }
+            SocketAddr::V6(unsafe { *(addr as *const ctypes::sockaddr_in6) }.into())
+        }
+        _ => return Err(LinuxError::EAFNOSUPPORT),
+    };
     debug!("    load sockaddr:{:#x} => {:?}", addr as usize, res);
     Ok(res)
 }
@@ -234,19 +651,75 @@ pub fn sys_socket(domain: c_int, socktype: c_int, protocol: c_int) -> c_int {
     let (domain, socktype, protocol) = (domain as u32, socktype as u32, protocol as u32);
     syscall_body!(sys_socket, {
         match (domain, socktype, protocol) {
-            (ctypes::AF_INET, ctypes::SOCK_STREAM, ctypes::IPPROTO_TCP)
-            | (ctypes::AF_INET, ctypes::SOCK_STREAM, 0) => {
+            (ctypes::AF_INET | ctypes::AF_INET6, ctypes::SOCK_STREAM, ctypes::IPPROTO_TCP)
+            | (ctypes::AF_INET | ctypes::AF_INET6, ctypes::SOCK_STREAM, 0) => {
                 Socket::Tcp(Mutex::new(TcpSocket::new())).add_to_fd_table()
             }
-            (ctypes::AF_INET, ctypes::SOCK_DGRAM, ctypes::IPPROTO_UDP)
-            | (ctypes::AF_INET, ctypes::SOCK_DGRAM, 0) => {
+            (ctypes::AF_INET | ctypes::AF_INET6, ctypes::SOCK_DGRAM, ctypes::IPPROTO_UDP)
+            | (ctypes::AF_INET | ctypes::AF_INET6, ctypes::SOCK_DGRAM, 0) => {
                 Socket::Udp(Mutex::new(UdpSocket::new())).add_to_fd_table()
             }
+            (ctypes::AF_INET, ctypes::SOCK_DGRAM, ctypes::IPPROTO_ICMP)
+            | (ctypes::AF_INET6, ctypes::SOCK_DGRAM, ctypes::IPPROTO_ICMPV6) => Socket::Icmp {
+                socket: Mutex::new(IcmpSocket::new()),
+                ipv6: domain == ctypes::AF_INET6,
+            }
+            .add_to_fd_table(),
+            // Sending IP headers from users (`IPPROTO_RAW`) is not supported.
+            (ctypes::AF_INET | ctypes::AF_INET6, ctypes::SOCK_RAW, protocol)
+                if protocol != 0 && protocol < ctypes::IPPROTO_RAW =>
+            {
+                let socket = RawSocket::new(domain == ctypes::AF_INET6, protocol as u8);
+                Socket::Raw(Mutex::new(socket)).add_to_fd_table()
+            }
+            (ctypes::AF_UNIX, ctypes::SOCK_STREAM, 0) => {
+                Socket::Unix(UnixSocket::new_stream()).add_to_fd_table()
+            }
+            (ctypes::AF_UNIX, ctypes::SOCK_DGRAM, 0) => {
+                Socket::Unix(UnixSocket::new_dgram()).add_to_fd_table()
+            }
             _ => Err(LinuxError::EINVAL),
         }
     })
 }
 
+/// Create a pair of connected sockets.
+///
+/// Only `AF_UNIX` sockets are supported.
+///
+/// Return 0 if success.
+pub fn sys_socketpair(domain: c_int, socktype: c_int, protocol: c_int, fds: &mut [c_int]) -> c_int {
+    debug!(
+        "sys_socketpair <= {} {} {} {:#x}",
+        domain,
+        socktype,
+        protocol,
+        fds.as_ptr() as usize
+    );
+    let (domain, socktype, protocol) = (domain as u32, socktype as u32, protocol as u32);
+    syscall_body!(sys_socketpair, {
+        if fds.len() != 2 {
+            return Err(LinuxError::EFAULT);
+        }
+        let (socket0, socket1) = match (domain, socktype, protocol) {
+            (ctypes::AF_UNIX, ctypes::SOCK_STREAM, 0) => UnixSocket::new_pair(true),
+            (ctypes::AF_UNIX, ctypes::SOCK_DGRAM, 0) => UnixSocket::new_pair(false),
+            (ctypes::AF_UNIX, _, _) => return Err(LinuxError::EINVAL),
+            _ => return Err(LinuxError::EOPNOTSUPP),
+        };
+
+        let fd0 = Socket::Unix(socket0).add_to_fd_table()?;
+        let fd1 = Socket::Unix(socket1).add_to_fd_table().inspect_err(|_| {
+            super::fd_ops::close_file_like(fd0).ok();
+        })?;
+
+        fds[0] = fd0;
+        fds[1] = fd1;
+
+        Ok(0)
+    })
+}
+
 /// Bind a address to a socket.
 ///
 /// Return 0 if success.
@@ -260,7 +733,7 @@ pub fn sys_bind(
         socket_fd, socket_addr as usize, addrlen
     );
     syscall_body!(sys_bind, {
-        let addr = from_sockaddr(socket_addr, addrlen)?;
+        let addr = SockAddr::from_sockaddr(socket_addr, addrlen)?;
         Socket::from_fd(socket_fd)?.bind(addr)?;
         Ok(0)
     })
@@ -279,7 +752,7 @@ pub fn sys_connect(
         socket_fd, socket_addr as usize, addrlen
     );
     syscall_body!(sys_connect, {
-        let addr = from_sockaddr(socket_addr, addrlen)?;
+        let addr = SockAddr::from_sockaddr(socket_addr, addrlen)?;
         Socket::from_fd(socket_fd)?.connect(addr)?;
         Ok(0)
     })
@@ -304,7 +777,7 @@ pub fn sys_sendto(
         if buf_ptr.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        let addr = from_sockaddr(socket_addr, addrlen)?;
+        let addr = SockAddr::from_sockaddr(socket_addr, addrlen)?;
         let buf = unsafe { core::slice::from_raw_parts(buf_ptr as *const u8, len) };
         Socket::from_fd(socket_fd)?.sendto(buf, addr)
     })
@@ -356,9 +829,7 @@ pub unsafe fn sys_recvfrom(
 
         let res = socket.recvfrom(buf)?;
         if let Some(addr) = res.1 {
-            unsafe {
-                (*socket_addr, *addrlen) = into_sockaddr(addr);
-            }
+            unsafe { addr.write_to(socket_addr, addrlen)? };
         }
         Ok(res.0)
     })
@@ -417,12 +888,9 @@ pub unsafe fn sys_accept(
             return Err(LinuxError::EFAULT);
         }
         let socket = Socket::from_fd(socket_fd)?;
-        let new_socket = socket.accept()?;
-        let addr = new_socket.peer_addr()?;
-        let new_fd = Socket::add_to_fd_table(Socket::Tcp(Mutex::new(new_socket)))?;
-        unsafe {
-            (*socket_addr, *socket_len) = into_sockaddr(addr);
-        }
+        let (new_socket, addr) = socket.accept()?;
+        let new_fd = Socket::add_to_fd_table(new_socket)?;
+        unsafe { addr.write_to(socket_addr, socket_len)? };
         Ok(new_fd)
     })
 }
@@ -443,7 +911,7 @@ pub fn sys_shutdown(
 
 /// Query addresses for a domain name.
 ///
-/// Only IPv4. Ports are always 0. Ignore servname and hint.
+/// Ports are always 0. Ignore servname and hint.
 /// Results' ai_flags and ai_canonname are 0 or NULL.
 ///
 /// Return address number if success.
@@ -502,11 +970,27 @@ pub unsafe fn sys_getaddrinfo(
                     lock: [0],
                     ref_: 0,
                 },
-                _ => panic!("IPv6 is not supported"),
+                IpAddr::V6(ip) => ctypes::aibuf {
+                    ai: ctypes::addrinfo {
+                        ai_family: ctypes::AF_INET6 as _,
+                        ai_socktype: ctypes::SOCK_STREAM as _,
+                        ai_protocol: ctypes::IPPROTO_TCP as _,
+                        ai_addrlen: size_of::<ctypes::sockaddr_in6>() as _,
+                        ai_addr: core::ptr::null_mut(),
+                        ai_canonname: core::ptr::null_mut(),
+                        ai_next: core::ptr::null_mut(),
+                        ai_flags: 0,
+                    },
+                    sa: ctypes::aibuf_sa {
+                        sin6: SocketAddrV6::new(ip, port, 0, 0).into(),
+                    },
+                    slot: i as i16,
+                    lock: [0],
+                    ref_: 0,
+                },
             };
             out.push(buf);
-            out[i].ai.ai_addr =
-                unsafe { core::ptr::addr_of_mut!(out[i].sa.sin) as *mut ctypes::sockaddr };
+            out[i].ai.ai_addr = core::ptr::addr_of_mut!(out[i].sa) as *mut ctypes::sockaddr;
             if i > 0 {
                 out[i - 1].ai.ai_next = core::ptr::addr_of_mut!(out[i].ai);
             }
@@ -546,12 +1030,8 @@ pub unsafe fn sys_getsockname(
         if addr.is_null() || addrlen.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        if unsafe { *addrlen } < size_of::<ctypes::sockaddr>() as u32 {
-            return Err(LinuxError::EINVAL);
-        }
-        unsafe {
-            (*addr, *addrlen) = into_sockaddr(Socket::from_fd(sock_fd)?.local_addr()?);
-        }
+        let sock_addr = Socket::from_fd(sock_fd)?.local_addr()?;
+        unsafe { sock_addr.write_to(addr, addrlen)? };
         Ok(0)
     })
 }
@@ -570,12 +1050,62 @@ pub unsafe fn sys_getpeername(
         if addr.is_null() || addrlen.is_null() {
             return Err(LinuxError::EFAULT);
         }
-        if unsafe { *addrlen } < size_of::<ctypes::sockaddr>() as u32 {
-            return Err(LinuxError::EINVAL);
-        }
-        unsafe {
-            (*addr, *addrlen) = into_sockaddr(Socket::from_fd(sock_fd)?.peer_addr()?);
-        }
+        let sock_addr = Socket::from_fd(sock_fd)?.peer_addr()?;
+        unsafe { sock_addr.write_to(addr, addrlen)? };
+        Ok(0)
+    })
+}
+
+/// Set options on sockets.
+///
+/// Supported options are `SO_REUSEADDR`, `SO_KEEPALIVE`, `SO_RCVBUF`,
+/// `SO_SNDBUF`, `SO_RCVTIMEO`, `SO_SNDTIMEO`, `SO_LINGER` at the `SOL_SOCKET`
+/// level and `TCP_NODELAY` at the `IPPROTO_TCP` level.
+///
+/// Return 0 if success.
+pub unsafe fn sys_setsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *const c_void,
+    optlen: ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_setsockopt <= {} {} {} {:#x} {}",
+        socket_fd, level, optname, optval as usize, optlen
+    );
+    syscall_body!(sys_setsockopt, {
+        let val = SockOptVal {
+            ptr: optval,
+            len: optlen as usize,
+        };
+        Socket::from_fd(socket_fd)?.set_option(level as u32, optname as u32, val)?;
+        Ok(0)
+    })
+}
+
+/// Get options on sockets.
+///
+/// See [`sys_setsockopt`] for supported options.
+///
+/// Return 0 if success.
+pub unsafe fn sys_getsockopt(
+    socket_fd: c_int,
+    level: c_int,
+    optname: c_int,
+    optval: *mut c_void,
+    optlen: *mut ctypes::socklen_t,
+) -> c_int {
+    debug!(
+        "sys_getsockopt <= {} {} {} {:#x} {:#x}",
+        socket_fd, level, optname, optval as usize, optlen as usize
+    );
+    syscall_body!(sys_getsockopt, {
+        let mut buf = SockOptBuf {
+            ptr: optval,
+            len: optlen,
+        };
+        Socket::from_fd(socket_fd)?.get_option(level as u32, optname as u32, &mut buf)?;
         Ok(0)
     })
 }
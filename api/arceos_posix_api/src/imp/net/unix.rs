@@ -0,0 +1,719 @@
+//! Unix domain sockets (`AF_UNIX`) for local IPC.
+//!
+//! They are implemented in memory and do not rely on any NIC or the network
+//! stack. A stream connection is a pair of byte buffers, one for each
+//! direction, and a datagram is pushed to the receiving queue of the peer
+//! directly.
+//!
+//! A socket can be bound to a path, which is also created as a file if the
+//! `fs` feature is enabled, or to a name in the abstract namespace (starting
+//! with a NUL byte). Like Linux, the file is not removed when the socket is
+//! closed, so it must be unlinked before binding the path again. Unlinking
+//! the file also unbinds the path, even if the socket is still open.
+//!
+//! Blocked tasks sleep on the [`SocketWaiter`] of the connection, listener or
+//! receiving queue, which is notified on every change of them. So they also
+//! notify [`axnet::socket_events`] like other sockets.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axio::PollState;
+use axnet::SocketWaiter;
+use axsync::Mutex;
+
+use crate::ctypes;
+
+/// The capacity of each direction of a stream connection, and of the
+/// receiving queue of a datagram socket.
+const UNIX_BUF_LEN: usize = 64 * 1024;
+/// The maximum number of pending connections of a listening socket.
+const UNIX_LISTEN_QUEUE_SIZE: usize = 128;
+
+/// The offset of `sun_path` in `sockaddr_un`.
+const SUN_PATH_OFFSET: usize = size_of::<ctypes::sa_family_t>();
+
+/// The address of a Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UnixAddr {
+    /// Not bound to any name.
+    Unnamed,
+    /// A path in the file system.
+    Path(String),
+    /// A name in the abstract namespace, without the leading NUL byte.
+    Abstract(Vec<u8>),
+}
+
+impl UnixAddr {
+    /// Loads the address from a `sockaddr_un` of `addrlen` bytes.
+    pub fn from_sockaddr(
+        addr: *const ctypes::sockaddr,
+        addrlen: ctypes::socklen_t,
+    ) -> LinuxResult<Self> {
+        if addr.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let addrlen = addrlen as usize;
+        if !(SUN_PATH_OFFSET..=size_of::<ctypes::sockaddr_un>()).contains(&addrlen) {
+            return Err(LinuxError::EINVAL);
+        }
+        if unsafe { (*addr).sa_family } as u32 != ctypes::AF_UNIX {
+            return Err(LinuxError::EINVAL);
+        }
+
+        let name = unsafe {
+            core::slice::from_raw_parts(
+                (addr as *const u8).add(SUN_PATH_OFFSET),
+                addrlen - SUN_PATH_OFFSET,
+            )
+        };
+        Ok(match name {
+            [] => Self::Unnamed,
+            [0, name @ ..] => Self::Abstract(name.to_vec()),
+            _ => {
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                let path = core::str::from_utf8(&name[..len]).map_err(|_| LinuxError::EINVAL)?;
+                Self::Path(path.into())
+            }
+        })
+    }
+
+    /// Stores the address to the buffer `dst` of `*addrlen` bytes, and sets
+    /// `*addrlen` to the actual size of the address.
+    pub unsafe fn write_to(
+        &self,
+        dst: *mut ctypes::sockaddr,
+        addrlen: *mut ctypes::socklen_t,
+    ) -> LinuxResult {
+        if dst.is_null() || addrlen.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let mut name = Vec::with_capacity(size_of::<ctypes::sockaddr_un>());
+        name.extend_from_slice(&(ctypes::AF_UNIX as ctypes::sa_family_t).to_ne_bytes());
+        match self {
+            Self::Unnamed => {}
+            Self::Path(path) => {
+                name.extend_from_slice(path.as_bytes());
+                name.push(0);
+            }
+            Self::Abstract(abs) => {
+                name.push(0);
+                name.extend_from_slice(abs);
+            }
+        }
+        name.truncate(size_of::<ctypes::sockaddr_un>());
+        unsafe {
+            let copy_len = name.len().min(*addrlen as usize);
+            core::ptr::copy_nonoverlapping(name.as_ptr(), dst as *mut u8, copy_len);
+            *addrlen = name.len() as _;
+        }
+        Ok(())
+    }
+
+    /// Converts a relative path to the absolute one, so that it always refers
+    /// to the same socket.
+    fn resolve(self) -> LinuxResult<Self> {
+        match self {
+            #[cfg(feature = "fs")]
+            Self::Path(path) => Ok(Self::Path(axfs::api::canonicalize(&path)?)),
+            addr => Ok(addr),
+        }
+    }
+}
+
+/// The object that a name is bound to.
+#[derive(Clone)]
+enum Binding {
+    Stream(Weak<Listener>),
+    Datagram(Weak<DgramQueue>),
+}
+
+impl Binding {
+    fn is_alive(&self) -> bool {
+        match self {
+            Binding::Stream(listener) => listener.strong_count() > 0,
+            Binding::Datagram(queue) => queue.strong_count() > 0,
+        }
+    }
+}
+
+/// All bound names. An entry is removed lazily after its socket is closed or
+/// its file is unlinked.
+static BOUND: Mutex<BTreeMap<UnixAddr, Binding>> = Mutex::new(BTreeMap::new());
+
+/// Returns whether `addr` is a path whose file no longer exists, i.e., it has
+/// been unlinked after the socket was bound.
+fn is_unlinked(addr: &UnixAddr) -> bool {
+    match addr {
+        #[cfg(feature = "fs")]
+        UnixAddr::Path(path) => axfs::api::metadata(path).is_err(),
+        _ => false,
+    }
+}
+
+fn bind_name(addr: &UnixAddr, binding: Binding) -> LinuxResult {
+    let mut bound = BOUND.lock();
+    bound.retain(|addr, b| b.is_alive() && !is_unlinked(addr));
+    if bound.contains_key(addr) {
+        return Err(LinuxError::EADDRINUSE);
+    }
+    #[cfg(feature = "fs")]
+    if let UnixAddr::Path(path) = addr {
+        let mut opts = axfs::fops::OpenOptions::new();
+        opts.write(true);
+        opts.create_new(true);
+        axfs::fops::File::open(path, &opts).map_err(|e| match e {
+            axerrno::AxError::AlreadyExists => LinuxError::EADDRINUSE,
+            e => e.into(),
+        })?;
+    }
+    bound.insert(addr.clone(), binding);
+    Ok(())
+}
+
+fn lookup_name(addr: &UnixAddr) -> LinuxResult<Binding> {
+    let mut bound = BOUND.lock();
+    if is_unlinked(addr) {
+        bound.remove(addr);
+        return Err(LinuxError::ENOENT);
+    }
+    match bound.get(addr) {
+        Some(binding) if binding.is_alive() => Ok(binding.clone()),
+        Some(_) => Err(LinuxError::ECONNREFUSED),
+        None => match addr {
+            #[cfg(feature = "fs")]
+            UnixAddr::Path(path) if axfs::api::metadata(path).is_ok() => {
+                Err(LinuxError::ECONNREFUSED)
+            }
+            UnixAddr::Path(_) => Err(LinuxError::ENOENT),
+            _ => Err(LinuxError::ECONNREFUSED),
+        },
+    }
+}
+
+/// Generates a unique name in the abstract namespace for a socket that is
+/// bound without a name, like the autobind of Linux.
+fn autobind_name() -> UnixAddr {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    UnixAddr::Abstract(format!("{:05x}", id & 0xfffff).into_bytes())
+}
+
+/// One direction of a stream connection.
+#[derive(Default)]
+struct StreamBuf {
+    data: VecDeque<u8>,
+    /// The reading end is closed, so writing fails with `EPIPE`.
+    reader_closed: bool,
+    /// The writing end is closed, so reading returns EOF after the remaining
+    /// data is consumed.
+    writer_closed: bool,
+}
+
+/// An end of a stream connection.
+struct Connection {
+    rx: Arc<Mutex<StreamBuf>>,
+    tx: Arc<Mutex<StreamBuf>>,
+    peer: UnixAddr,
+    /// Shared by both ends, notified when data is sent or received, or
+    /// either end is shut down.
+    waiter: Arc<SocketWaiter>,
+}
+
+impl Connection {
+    /// Creates both ends of a connection, the peers of them are `peer0` and
+    /// `peer1` respectively.
+    fn pair(peer0: UnixAddr, peer1: UnixAddr) -> (Self, Self) {
+        let buf0 = Arc::new(Mutex::new(StreamBuf::default()));
+        let buf1 = Arc::new(Mutex::new(StreamBuf::default()));
+        let waiter = SocketWaiter::new();
+        let end0 = Self {
+            rx: buf0.clone(),
+            tx: buf1.clone(),
+            peer: peer0,
+            waiter: waiter.clone(),
+        };
+        let end1 = Self {
+            rx: buf1,
+            tx: buf0,
+            peer: peer1,
+            waiter,
+        };
+        (end0, end1)
+    }
+
+    fn send(&self, buf: &[u8]) -> LinuxResult<usize> {
+        let mut tx = self.tx.lock();
+        if tx.reader_closed || tx.writer_closed {
+            return Err(LinuxError::EPIPE);
+        }
+        let len = buf.len().min(UNIX_BUF_LEN - tx.data.len());
+        if len == 0 && !buf.is_empty() {
+            return Err(LinuxError::EAGAIN);
+        }
+        tx.data.extend(&buf[..len]);
+        drop(tx);
+        self.waiter.notify();
+        Ok(len)
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> LinuxResult<usize> {
+        let mut rx = self.rx.lock();
+        if rx.data.is_empty() && !buf.is_empty() {
+            return if rx.writer_closed || rx.reader_closed {
+                Ok(0)
+            } else {
+                Err(LinuxError::EAGAIN)
+            };
+        }
+        let len = buf.len().min(rx.data.len());
+        for (dst, src) in buf.iter_mut().zip(rx.data.drain(..len)) {
+            *dst = src;
+        }
+        drop(rx);
+        self.waiter.notify();
+        Ok(len)
+    }
+
+    fn shutdown(&self) {
+        self.rx.lock().reader_closed = true;
+        self.tx.lock().writer_closed = true;
+        self.waiter.notify();
+    }
+
+    fn poll(&self) -> PollState {
+        let rx = self.rx.lock();
+        let tx = self.tx.lock();
+        PollState {
+            readable: !rx.data.is_empty() || rx.writer_closed || rx.reader_closed,
+            writable: tx.data.len() < UNIX_BUF_LEN || tx.reader_closed || tx.writer_closed,
+            hangup: (rx.writer_closed || rx.reader_closed)
+                && (tx.reader_closed || tx.writer_closed),
+            error: false,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The bound name of a stream socket, which accepts connections after
+/// `listen`.
+struct Listener {
+    addr: UnixAddr,
+    listening: AtomicBool,
+    queue: Mutex<VecDeque<Connection>>,
+    /// Notified when a connection is queued or accepted, or the listener is
+    /// closed.
+    waiter: Arc<SocketWaiter>,
+}
+
+/// The receiving queue of a datagram socket.
+struct DgramQueue {
+    inner: Mutex<DgramBuf>,
+    /// Notified when a datagram is pushed or popped.
+    waiter: Arc<SocketWaiter>,
+}
+
+#[derive(Default)]
+struct DgramBuf {
+    msgs: VecDeque<(Vec<u8>, UnixAddr)>,
+    len: usize,
+}
+
+impl DgramQueue {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(DgramBuf::default()),
+            waiter: SocketWaiter::new(),
+        }
+    }
+
+    fn push(&self, buf: &[u8], src: UnixAddr) -> LinuxResult<usize> {
+        if buf.len() > UNIX_BUF_LEN {
+            return Err(LinuxError::EMSGSIZE);
+        }
+        let mut inner = self.inner.lock();
+        if inner.len + buf.len() > UNIX_BUF_LEN {
+            return Err(LinuxError::EAGAIN);
+        }
+        inner.msgs.push_back((buf.to_vec(), src));
+        inner.len += buf.len();
+        drop(inner);
+        self.waiter.notify();
+        Ok(buf.len())
+    }
+
+    /// Receives a datagram, and the excess bytes are discarded if `buf` is
+    /// too small.
+    fn pop(&self, buf: &mut [u8]) -> LinuxResult<(usize, UnixAddr)> {
+        let mut inner = self.inner.lock();
+        let (msg, src) = inner.msgs.pop_front().ok_or(LinuxError::EAGAIN)?;
+        inner.len -= msg.len();
+        drop(inner);
+        self.waiter.notify();
+        let len = buf.len().min(msg.len());
+        buf[..len].copy_from_slice(&msg[..len]);
+        Ok((len, src))
+    }
+
+    fn can_recv(&self) -> bool {
+        !self.inner.lock().msgs.is_empty()
+    }
+
+    fn can_send(&self) -> bool {
+        self.inner.lock().len < UNIX_BUF_LEN
+    }
+}
+
+enum State {
+    /// A stream socket that is neither connected nor listening. It holds the
+    /// listener if it's bound.
+    Idle(Option<Arc<Listener>>),
+    Listening(Arc<Listener>),
+    Connected(Arc<Connection>),
+    Datagram {
+        queue: Arc<DgramQueue>,
+        peer: Option<(UnixAddr, Weak<DgramQueue>)>,
+    },
+}
+
+struct Inner {
+    local_addr: UnixAddr,
+    state: State,
+}
+
+/// A Unix domain socket in stream (`SOCK_STREAM`) or datagram (`SOCK_DGRAM`)
+/// mode.
+pub struct UnixSocket {
+    nonblock: AtomicBool,
+    inner: Mutex<Inner>,
+}
+
+impl UnixSocket {
+    const fn with_state(local_addr: UnixAddr, state: State) -> Self {
+        Self {
+            nonblock: AtomicBool::new(false),
+            inner: Mutex::new(Inner { local_addr, state }),
+        }
+    }
+
+    /// Creates a new stream socket.
+    pub fn new_stream() -> Self {
+        Self::with_state(UnixAddr::Unnamed, State::Idle(None))
+    }
+
+    /// Creates a new datagram socket.
+    pub fn new_dgram() -> Self {
+        Self::with_state(
+            UnixAddr::Unnamed,
+            State::Datagram {
+                queue: Arc::new(DgramQueue::new()),
+                peer: None,
+            },
+        )
+    }
+
+    /// Creates a pair of connected unnamed sockets, like `socketpair`.
+    pub fn new_pair(stream: bool) -> (Self, Self) {
+        let (state0, state1) = if stream {
+            let (conn0, conn1) = Connection::pair(UnixAddr::Unnamed, UnixAddr::Unnamed);
+            (
+                State::Connected(Arc::new(conn0)),
+                State::Connected(Arc::new(conn1)),
+            )
+        } else {
+            let queue0 = Arc::new(DgramQueue::new());
+            let queue1 = Arc::new(DgramQueue::new());
+            let peer0 = Some((UnixAddr::Unnamed, Arc::downgrade(&queue1)));
+            let peer1 = Some((UnixAddr::Unnamed, Arc::downgrade(&queue0)));
+            (
+                State::Datagram {
+                    queue: queue0,
+                    peer: peer0,
+                },
+                State::Datagram {
+                    queue: queue1,
+                    peer: peer1,
+                },
+            )
+        };
+        (
+            Self::with_state(UnixAddr::Unnamed, state0),
+            Self::with_state(UnixAddr::Unnamed, state1),
+        )
+    }
+
+    /// Returns the address that the socket is bound to.
+    pub fn local_addr(&self) -> UnixAddr {
+        self.inner.lock().local_addr.clone()
+    }
+
+    /// Returns the address of the peer, or `ENOTCONN` if not connected.
+    pub fn peer_addr(&self) -> LinuxResult<UnixAddr> {
+        match &self.inner.lock().state {
+            State::Connected(conn) => Ok(conn.peer.clone()),
+            State::Datagram {
+                peer: Some((addr, _)),
+                ..
+            } => Ok(addr.clone()),
+            _ => Err(LinuxError::ENOTCONN),
+        }
+    }
+
+    pub fn is_nonblocking(&self) -> bool {
+        self.nonblock.load(Ordering::Acquire)
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.nonblock.store(nonblocking, Ordering::Release);
+    }
+
+    /// Binds the socket to the given address. An unnamed address means a
+    /// unique name in the abstract namespace.
+    pub fn bind(&self, addr: UnixAddr) -> LinuxResult {
+        let addr = match addr {
+            UnixAddr::Unnamed => autobind_name(),
+            addr => addr.resolve()?,
+        };
+        let mut inner = self.inner.lock();
+        if inner.local_addr != UnixAddr::Unnamed {
+            return Err(LinuxError::EINVAL);
+        }
+        match &inner.state {
+            State::Idle(None) => {
+                let listener = Arc::new(Listener {
+                    addr: addr.clone(),
+                    listening: AtomicBool::new(false),
+                    queue: Mutex::new(VecDeque::new()),
+                    waiter: SocketWaiter::new(),
+                });
+                bind_name(&addr, Binding::Stream(Arc::downgrade(&listener)))?;
+                inner.state = State::Idle(Some(listener));
+            }
+            State::Datagram { queue, .. } => {
+                bind_name(&addr, Binding::Datagram(Arc::downgrade(queue)))?;
+            }
+            _ => return Err(LinuxError::EINVAL),
+        }
+        debug!("Unix socket bound on {:?}", addr);
+        inner.local_addr = addr;
+        Ok(())
+    }
+
+    /// Starts listening on the bound address, or on a unique name in the
+    /// abstract namespace if it's not bound.
+    pub fn listen(&self) -> LinuxResult {
+        if matches!(self.inner.lock().state, State::Idle(None)) {
+            self.bind(UnixAddr::Unnamed)?;
+        }
+        let mut inner = self.inner.lock();
+        match &inner.state {
+            State::Idle(Some(listener)) => {
+                listener.listening.store(true, Ordering::Release);
+                inner.state = State::Listening(listener.clone());
+                Ok(())
+            }
+            State::Listening(_) => Ok(()),
+            State::Datagram { .. } => Err(LinuxError::EOPNOTSUPP),
+            _ => Err(LinuxError::EINVAL),
+        }
+    }
+
+    /// Accepts a new connection. Returns the connected socket and the address
+    /// of the peer.
+    pub fn accept(&self) -> LinuxResult<(UnixSocket, UnixAddr)> {
+        let listener = match &self.inner.lock().state {
+            State::Listening(listener) => listener.clone(),
+            State::Datagram { .. } => return Err(LinuxError::EOPNOTSUPP),
+            _ => return Err(LinuxError::EINVAL),
+        };
+        let conn = self.block_on(&listener.waiter, || {
+            let conn = listener.queue.lock().pop_front();
+            let conn = conn.ok_or(LinuxError::EAGAIN)?;
+            listener.waiter.notify();
+            Ok(conn)
+        })?;
+        let peer = conn.peer.clone();
+        debug!("Unix socket {:?} accepted a connection", listener.addr);
+        let socket = Self::with_state(listener.addr.clone(), State::Connected(Arc::new(conn)));
+        Ok((socket, peer))
+    }
+
+    /// Connects a stream socket to a listening socket, or sets the default
+    /// destination of a datagram socket.
+    pub fn connect(&self, addr: UnixAddr) -> LinuxResult {
+        let addr = addr.resolve()?;
+        let binding = lookup_name(&addr)?;
+        let mut inner = self.inner.lock();
+        let listener = match (&mut inner.state, binding) {
+            (State::Datagram { peer, .. }, Binding::Datagram(queue)) => {
+                *peer = Some((addr, queue));
+                return Ok(());
+            }
+            (State::Idle(_), Binding::Stream(listener)) => {
+                listener.upgrade().ok_or(LinuxError::ECONNREFUSED)?
+            }
+            (State::Connected(_), _) => return Err(LinuxError::EISCONN),
+            (State::Listening(_), _) => return Err(LinuxError::EINVAL),
+            _ => return Err(LinuxError::EPROTOTYPE),
+        };
+        if !listener.listening.load(Ordering::Acquire) {
+            return Err(LinuxError::ECONNREFUSED);
+        }
+        let (conn, server_conn) = Connection::pair(addr, inner.local_addr.clone());
+        drop(inner);
+
+        let mut server_conn = Some(server_conn);
+        self.block_on(&listener.waiter, || {
+            let mut queue = listener.queue.lock();
+            if !listener.listening.load(Ordering::Acquire) {
+                return Err(LinuxError::ECONNREFUSED);
+            }
+            if queue.len() >= UNIX_LISTEN_QUEUE_SIZE {
+                return Err(LinuxError::EAGAIN);
+            }
+            queue.extend(server_conn.take());
+            drop(queue);
+            listener.waiter.notify();
+            Ok(())
+        })?;
+        debug!("Unix socket connected to {:?}", listener.addr);
+        let waiter = conn.waiter.clone();
+        self.inner.lock().state = State::Connected(Arc::new(conn));
+        // the socket becomes writable
+        waiter.notify();
+        Ok(())
+    }
+
+    /// Sends data to the connected peer.
+    pub fn send(&self, buf: &[u8]) -> LinuxResult<usize> {
+        let inner = self.inner.lock();
+        match &inner.state {
+            State::Connected(conn) => {
+                let conn = conn.clone();
+                drop(inner);
+                self.block_on(&conn.waiter, || conn.send(buf))
+            }
+            State::Datagram {
+                peer: Some((_, peer)),
+                ..
+            } => {
+                let peer = peer.upgrade().ok_or(LinuxError::ECONNREFUSED)?;
+                let local_addr = inner.local_addr.clone();
+                drop(inner);
+                self.block_on(&peer.waiter, || peer.push(buf, local_addr.clone()))
+            }
+            _ => Err(LinuxError::ENOTCONN),
+        }
+    }
+
+    /// Sends a datagram to the given address.
+    pub fn send_to(&self, buf: &[u8], addr: UnixAddr) -> LinuxResult<usize> {
+        if !matches!(self.inner.lock().state, State::Datagram { .. }) {
+            return Err(LinuxError::EISCONN);
+        }
+        let queue = match lookup_name(&addr.resolve()?)? {
+            Binding::Datagram(queue) => queue.upgrade().ok_or(LinuxError::ECONNREFUSED)?,
+            Binding::Stream(_) => return Err(LinuxError::EPROTOTYPE),
+        };
+        let local_addr = self.local_addr();
+        self.block_on(&queue.waiter, || queue.push(buf, local_addr.clone()))
+    }
+
+    /// Receives data, and the address of the sender if it's a datagram
+    /// socket.
+    pub fn recv_from(&self, buf: &mut [u8]) -> LinuxResult<(usize, Option<UnixAddr>)> {
+        let inner = self.inner.lock();
+        match &inner.state {
+            State::Connected(conn) => {
+                let conn = conn.clone();
+                drop(inner);
+                self.block_on(&conn.waiter, || Ok((conn.recv(buf)?, None)))
+            }
+            State::Datagram { queue, .. } => {
+                let queue = queue.clone();
+                drop(inner);
+                self.block_on(&queue.waiter, || {
+                    let (len, src) = queue.pop(buf)?;
+                    Ok((len, Some(src)))
+                })
+            }
+            _ => Err(LinuxError::ENOTCONN),
+        }
+    }
+
+    /// Shuts down both directions of a stream connection.
+    pub fn shutdown(&self) -> LinuxResult {
+        match &self.inner.lock().state {
+            State::Connected(conn) => {
+                conn.shutdown();
+                Ok(())
+            }
+            State::Datagram { peer: Some(_), .. } => Ok(()),
+            _ => Err(LinuxError::ENOTCONN),
+        }
+    }
+
+    pub fn poll(&self) -> PollState {
+        match &self.inner.lock().state {
+            State::Idle(_) => PollState {
+                readable: false,
+                writable: false,
+                hangup: false,
+                error: false,
+            },
+            State::Listening(listener) => PollState {
+                readable: !listener.queue.lock().is_empty(),
+                writable: false,
+                hangup: false,
+                error: false,
+            },
+            State::Connected(conn) => conn.poll(),
+            State::Datagram { queue, peer } => PollState {
+                readable: queue.can_recv(),
+                writable: peer
+                    .as_ref()
+                    .and_then(|(_, peer)| peer.upgrade())
+                    .map_or(true, |peer| peer.can_send()),
+                hangup: false,
+                error: false,
+            },
+        }
+    }
+
+    /// Calls `f` until it does not return `EAGAIN`, sleeping on `waiter`
+    /// between the calls, or calls it only once if the socket is nonblocking.
+    fn block_on<F, T>(&self, waiter: &SocketWaiter, mut f: F) -> LinuxResult<T>
+    where
+        F: FnMut() -> LinuxResult<T>,
+    {
+        loop {
+            let seen = waiter.events();
+            match f() {
+                Err(LinuxError::EAGAIN) if !self.is_nonblocking() => waiter.wait(seen, None),
+                res => return res,
+            }
+        }
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        if let State::Listening(listener) = &self.inner.get_mut().state {
+            // refuse new connections and drop the pending ones
+            listener.listening.store(false, Ordering::Release);
+            listener.queue.lock().clear();
+            listener.waiter.notify();
+        }
+    }
+}
@@ -101,7 +101,8 @@ impl Pipe {
         !self.readable
     }
 
-    pub fn write_end_close(&self) -> bool {
+    /// Whether the other end of the pipe is closed.
+    pub fn peer_closed(&self) -> bool {
         Arc::strong_count(&self.buffer) == 1
     }
 }
@@ -117,7 +118,7 @@ impl FileLike for Pipe {
             let mut ring_buffer = self.buffer.lock();
             let loop_read = ring_buffer.available_read();
             if loop_read == 0 {
-                if self.write_end_close() {
+                if self.peer_closed() {
                     return Ok(read_size);
                 }
                 drop(ring_buffer);
@@ -142,6 +143,14 @@ impl FileLike for Pipe {
         let mut write_size = 0usize;
         let max_len = buf.len();
         loop {
+            if self.peer_closed() {
+                // The read end is closed, no one will consume the data.
+                return if write_size > 0 {
+                    Ok(write_size)
+                } else {
+                    Err(LinuxError::EPIPE)
+                };
+            }
             let mut ring_buffer = self.buffer.lock();
             let loop_write = ring_buffer.available_write();
             if loop_write == 0 {
@@ -179,9 +188,14 @@ impl FileLike for Pipe {
 
     fn poll(&self) -> LinuxResult<PollState> {
         let buf = self.buffer.lock();
+        let peer_closed = self.peer_closed();
+        // Like Linux, the read end hangs up without writers, and the write end
+        // gets an error without readers.
         Ok(PollState {
-            readable: self.readable() && buf.available_read() > 0,
-            writable: self.writable() && buf.available_write() > 0,
+            readable: self.readable() && (buf.available_read() > 0 || peer_closed),
+            writable: self.writable() && (buf.available_write() > 0 || peer_closed),
+            hangup: self.readable() && peer_closed,
+            error: self.writable() && peer_closed,
         })
     }
 
@@ -0,0 +1,150 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axsync::Condvar;
+
+use core::ffi::c_int;
+use core::mem::{size_of, ManuallyDrop};
+use core::time::Duration;
+
+use super::mutex::PthreadMutex;
+
+static_assertions::const_assert_eq!(
+    size_of::<PthreadCond>(),
+    size_of::<ctypes::pthread_cond_t>()
+);
+
+#[repr(C)]
+pub struct PthreadCond(Condvar);
+
+impl PthreadCond {
+    const fn new() -> Self {
+        Self(Condvar::new())
+    }
+
+    fn wait(&self, mutex: &PthreadMutex) -> LinuxResult {
+        // the mutex is locked by the caller, and remains locked after waking up
+        let guard = unsafe { mutex.0.make_guard_unchecked() };
+        let _guard = ManuallyDrop::new(self.0.wait(guard));
+        Ok(())
+    }
+
+    fn timedwait(&self, mutex: &PthreadMutex, abstime: Duration) -> LinuxResult {
+        let dur = abstime.saturating_sub(axhal::time::current_time());
+        let guard = unsafe { mutex.0.make_guard_unchecked() };
+        let (guard, res) = self.0.wait_timeout(guard, dur);
+        let _guard = ManuallyDrop::new(guard);
+        if res.timed_out() {
+            Err(LinuxError::ETIMEDOUT)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn signal(&self) -> LinuxResult {
+        self.0.notify_one();
+        Ok(())
+    }
+
+    fn broadcast(&self) -> LinuxResult {
+        self.0.notify_all();
+        Ok(())
+    }
+}
+
+/// Initialize a condition variable.
+pub fn sys_pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    _attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    debug!("sys_pthread_cond_init <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_init, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            cond.cast::<PthreadCond>().write(PthreadCond::new());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy a condition variable.
+pub fn sys_pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_destroy <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_destroy, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            cond.cast::<PthreadCond>().drop_in_place();
+        }
+        Ok(0)
+    })
+}
+
+/// Atomically unlock the given mutex and wait on the condition variable. The
+/// mutex is locked again before returning.
+pub fn sys_pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_wait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_wait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).wait(&*mutex.cast::<PthreadMutex>())?;
+        }
+        Ok(0)
+    })
+}
+
+/// Like [`sys_pthread_cond_wait`], but fails with `ETIMEDOUT` if the
+/// condition variable is not signaled before the absolute time `abstime`.
+pub unsafe fn sys_pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_timedwait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_timedwait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        let abstime = unsafe { abstime.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        if abstime.tv_nsec < 0 || abstime.tv_nsec > 999_999_999 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            (*cond.cast::<PthreadCond>())
+                .timedwait(&*mutex.cast::<PthreadMutex>(), Duration::from(*abstime))?;
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up one task waiting on the condition variable.
+pub fn sys_pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_signal <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_signal, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).signal()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Wake up all tasks waiting on the condition variable.
+pub fn sys_pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_broadcast <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_broadcast, {
+        check_null_mut_ptr(cond)?;
+        unsafe {
+            (*cond.cast::<PthreadCond>()).broadcast()?;
+        }
+        Ok(0)
+    })
+}
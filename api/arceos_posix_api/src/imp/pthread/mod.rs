@@ -8,7 +8,10 @@ use spin::RwLock;
 
 use crate::ctypes;
 
+pub mod condvar;
 pub mod mutex;
+pub mod rwlock;
+pub mod semaphore;
 
 lazy_static::lazy_static! {
     static ref TID_TO_PTHREAD: RwLock<BTreeMap<u64, ForceSendSync<ctypes::pthread_t>>> = {
@@ -147,6 +150,27 @@ pub unsafe fn sys_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_vo
     })
 }
 
+/// Sends the signal `sig` to the given thread. If `sig` is 0, only checks
+/// whether the thread exists.
+///
+/// Returns `-ESRCH` if `thread` is not a thread created by
+/// [`sys_pthread_create`] or the main thread, or it has been joined.
+pub fn sys_pthread_kill(thread: ctypes::pthread_t, sig: c_int) -> c_int {
+    debug!("sys_pthread_kill <= {:#x} {}", thread as usize, sig);
+    syscall_body!(sys_pthread_kill, {
+        // Only threads in the table are valid, and they can't be joined and
+        // freed while the table is locked.
+        let threads = TID_TO_PTHREAD.read();
+        let ptr = threads
+            .values()
+            .find(|ptr| core::ptr::eq(ptr.0, thread))
+            .ok_or(LinuxError::ESRCH)?;
+        let thread = unsafe { &*(ptr.0 as *const Pthread) };
+        super::signal::send_signal(&thread.inner, sig, axtask::signal::SI_TKILL)?;
+        Ok(0)
+    })
+}
+
 #[derive(Clone, Copy)]
 struct ForceSendSync<T>(T);
 
@@ -0,0 +1,158 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axsync::Semaphore;
+
+use core::ffi::{c_int, c_uint};
+use core::mem::size_of;
+use core::time::Duration;
+
+/// The maximum value of a semaphore, the same as `SEM_VALUE_MAX` in `<semaphore.h>`.
+const SEM_VALUE_MAX: c_uint = c_int::MAX as c_uint;
+
+static_assertions::const_assert!(size_of::<PosixSemaphore>() <= size_of::<ctypes::sem_t>());
+
+#[repr(C)]
+pub struct PosixSemaphore(Semaphore);
+
+impl PosixSemaphore {
+    const fn new(value: usize) -> Self {
+        Self(Semaphore::new(value))
+    }
+
+    fn wait(&self) -> LinuxResult {
+        self.0.acquire();
+        Ok(())
+    }
+
+    fn trywait(&self) -> LinuxResult {
+        if self.0.try_acquire() {
+            Ok(())
+        } else {
+            Err(LinuxError::EAGAIN)
+        }
+    }
+
+    fn timedwait(&self, abstime: Duration) -> LinuxResult {
+        let dur = abstime.saturating_sub(axhal::time::current_time());
+        if self.0.acquire_timeout(dur) {
+            Ok(())
+        } else {
+            Err(LinuxError::ETIMEDOUT)
+        }
+    }
+
+    fn post(&self) -> LinuxResult {
+        if self.0.value() >= SEM_VALUE_MAX as usize {
+            return Err(LinuxError::EOVERFLOW);
+        }
+        self.0.release();
+        Ok(())
+    }
+}
+
+/// Initialize an unnamed semaphore with the given value.
+///
+/// Semaphores shared between processes (`pshared != 0`) are not supported.
+pub fn sys_sem_init(sem: *mut ctypes::sem_t, pshared: c_int, value: c_uint) -> c_int {
+    debug!(
+        "sys_sem_init <= {:#x}, {}, {}",
+        sem as usize, pshared, value
+    );
+    syscall_body!(sys_sem_init, {
+        check_null_mut_ptr(sem)?;
+        if value > SEM_VALUE_MAX {
+            return Err(LinuxError::EINVAL);
+        }
+        if pshared != 0 {
+            return Err(LinuxError::ENOSYS);
+        }
+        unsafe {
+            sem.cast::<PosixSemaphore>()
+                .write(PosixSemaphore::new(value as usize));
+        }
+        Ok(0)
+    })
+}
+
+/// Destroy an unnamed semaphore.
+pub fn sys_sem_destroy(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_destroy <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_destroy, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            sem.cast::<PosixSemaphore>().drop_in_place();
+        }
+        Ok(0)
+    })
+}
+
+/// Decrement the semaphore, blocking until its value is greater than zero.
+pub fn sys_sem_wait(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_wait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_wait, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            (*sem.cast::<PosixSemaphore>()).wait()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Decrement the semaphore if its value is greater than zero, otherwise fails
+/// with `EAGAIN`.
+pub fn sys_sem_trywait(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_trywait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_trywait, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            (*sem.cast::<PosixSemaphore>()).trywait()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Like [`sys_sem_wait`], but fails with `ETIMEDOUT` if the semaphore cannot
+/// be decremented before the absolute time `abstime`.
+pub unsafe fn sys_sem_timedwait(
+    sem: *mut ctypes::sem_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!("sys_sem_timedwait <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_timedwait, {
+        check_null_mut_ptr(sem)?;
+        let abstime = unsafe { abstime.as_ref() }.ok_or(LinuxError::EFAULT)?;
+        if abstime.tv_nsec < 0 || abstime.tv_nsec > 999_999_999 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe {
+            (*sem.cast::<PosixSemaphore>()).timedwait(Duration::from(*abstime))?;
+        }
+        Ok(0)
+    })
+}
+
+/// Increment the semaphore, waking up a task blocked on it.
+pub fn sys_sem_post(sem: *mut ctypes::sem_t) -> c_int {
+    debug!("sys_sem_post <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_post, {
+        check_null_mut_ptr(sem)?;
+        unsafe {
+            (*sem.cast::<PosixSemaphore>()).post()?;
+        }
+        Ok(0)
+    })
+}
+
+/// Store the current value of the semaphore in `sval`.
+pub unsafe fn sys_sem_getvalue(sem: *mut ctypes::sem_t, sval: *mut c_int) -> c_int {
+    debug!("sys_sem_getvalue <= {:#x}", sem as usize);
+    syscall_body!(sys_sem_getvalue, {
+        check_null_mut_ptr(sem)?;
+        check_null_mut_ptr(sval)?;
+        unsafe {
+            *sval = (*sem.cast::<PosixSemaphore>()).0.value() as c_int;
+        }
+        Ok(0)
+    })
+}
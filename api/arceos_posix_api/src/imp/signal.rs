@@ -0,0 +1,258 @@
+use core::ffi::c_int;
+use core::mem::{size_of, transmute};
+use core::time::Duration;
+
+use axerrno::{LinuxError, LinuxResult};
+use axtask::signal::{self, SignalAction, SignalHandler, SignalInfo, SignalSet};
+use axtask::{AxTaskRef, TaskState};
+
+use crate::ctypes;
+use crate::imp::task::find_task;
+
+static_assertions::const_assert_eq!(size_of::<SignalInfo>(), size_of::<ctypes::siginfo_t>());
+
+impl From<ctypes::sigset_t> for SignalSet {
+    fn from(set: ctypes::sigset_t) -> Self {
+        // only the first word is used, as there are only 64 signals
+        SignalSet::from_bits(set.__bits[0])
+    }
+}
+
+impl From<SignalSet> for ctypes::sigset_t {
+    fn from(set: SignalSet) -> Self {
+        let mut res = Self::default();
+        res.__bits[0] = set.bits();
+        res
+    }
+}
+
+impl From<&ctypes::sigaction> for SignalAction {
+    fn from(act: &ctypes::sigaction) -> Self {
+        let flags = act.sa_flags as u32;
+        // Both members of the union are function pointers.
+        let (handler, sigaction) =
+            unsafe { (act.__sa_handler.sa_handler, act.__sa_handler.sa_sigaction) };
+        let handler = match handler.map_or(0, |f| f as usize) {
+            0 => SignalHandler::Default, // SIG_DFL
+            1 => SignalHandler::Ignore,  // SIG_IGN
+            _ if flags & ctypes::SA_SIGINFO != 0 => {
+                // `siginfo_t` has the same layout as `SignalInfo`
+                SignalHandler::SigAction(unsafe { transmute(sigaction.unwrap()) })
+            }
+            _ => SignalHandler::Handler(handler.unwrap()),
+        };
+        Self {
+            handler,
+            mask: act.sa_mask.into(),
+            flags,
+        }
+    }
+}
+
+impl From<SignalAction> for ctypes::sigaction {
+    fn from(action: SignalAction) -> Self {
+        let mut act = Self {
+            sa_mask: action.mask.into(),
+            sa_flags: action.flags as c_int,
+            ..Default::default()
+        };
+        match action.handler {
+            SignalHandler::Default => act.__sa_handler.sa_handler = None,
+            SignalHandler::Ignore => act.__sa_handler.sa_handler = unsafe { transmute(1usize) },
+            SignalHandler::Handler(f) => act.__sa_handler.sa_handler = Some(f),
+            SignalHandler::SigAction(f) => {
+                act.__sa_handler.sa_sigaction = Some(unsafe { transmute(f) })
+            }
+        }
+        act
+    }
+}
+
+/// Converts `sig` to a signal number, or returns `EINVAL` if it's invalid.
+fn signal_number(sig: c_int) -> LinuxResult<usize> {
+    usize::try_from(sig)
+        .ok()
+        .filter(|&sig| signal::is_valid(sig))
+        .ok_or(LinuxError::EINVAL)
+}
+
+/// Sends the signal `sig` to the task, where `code` is the origin of the
+/// signal. If `sig` is 0, only checks whether the task exists.
+pub(crate) fn send_signal(task: &AxTaskRef, sig: c_int, code: i32) -> LinuxResult {
+    let sig = if sig == 0 { 0 } else { signal_number(sig)? };
+    if task.state() == TaskState::Exited {
+        return Err(LinuxError::ESRCH);
+    }
+    if sig != 0 {
+        signal::send_signal(task, sig, code);
+    }
+    Ok(())
+}
+
+fn timeval_to_duration(tv: &ctypes::timeval) -> LinuxResult<Duration> {
+    if tv.tv_sec < 0 || !(0..1_000_000).contains(&tv.tv_usec) {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(Duration::from(*tv))
+}
+
+/// Examine and change the action of the signal `signum`.
+///
+/// Actions are shared by all threads. Those of `SIGKILL` and `SIGSTOP` can't
+/// be changed.
+pub unsafe fn sys_sigaction(
+    signum: c_int,
+    act: *const ctypes::sigaction,
+    oldact: *mut ctypes::sigaction,
+) -> c_int {
+    debug!(
+        "sys_sigaction <= {} {:#x} {:#x}",
+        signum, act as usize, oldact as usize
+    );
+    syscall_body!(sys_sigaction, {
+        let sig = signal_number(signum)?;
+        let old = if act.is_null() {
+            signal::signal_action(sig)
+        } else {
+            if sig == signal::SIGKILL || sig == signal::SIGSTOP {
+                return Err(LinuxError::EINVAL);
+            }
+            signal::set_signal_action(sig, unsafe { &*act }.into())
+        };
+        if !oldact.is_null() {
+            unsafe { *oldact = old.into() };
+        }
+        Ok(0)
+    })
+}
+
+/// Examine and change the blocked signals of the current thread.
+///
+/// `how` is one of `SIG_BLOCK`, `SIG_UNBLOCK` and `SIG_SETMASK`. `SIGKILL`
+/// and `SIGSTOP` can't be blocked.
+pub unsafe fn sys_sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    debug!(
+        "sys_sigprocmask <= {} {:#x} {:#x}",
+        how, set as usize, oldset as usize
+    );
+    syscall_body!(sys_sigprocmask, {
+        let old = signal::signal_mask();
+        if !set.is_null() {
+            let set = SignalSet::from(unsafe { *set });
+            let mask = match how as u32 {
+                ctypes::SIG_BLOCK => old.bits() | set.bits(),
+                ctypes::SIG_UNBLOCK => old.bits() & !set.bits(),
+                ctypes::SIG_SETMASK => set.bits(),
+                _ => return Err(LinuxError::EINVAL),
+            };
+            signal::set_signal_mask(SignalSet::from_bits(mask));
+        }
+        if !oldset.is_null() {
+            unsafe { *oldset = old.into() };
+        }
+        Ok(0)
+    })
+}
+
+/// Get the pending signals of the current thread.
+pub unsafe fn sys_sigpending(set: *mut ctypes::sigset_t) -> c_int {
+    debug!("sys_sigpending <= {:#x}", set as usize);
+    syscall_body!(sys_sigpending, {
+        if set.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        unsafe { *set = signal::pending_signals().into() };
+        Ok(0)
+    })
+}
+
+/// Replace the blocked signals of the current thread with `mask`, and wait
+/// for a signal that is not blocked to be handled.
+///
+/// Always returns `-EINTR`.
+pub unsafe fn sys_sigsuspend(mask: *const ctypes::sigset_t) -> c_int {
+    debug!("sys_sigsuspend <= {:#x}", mask as usize);
+    syscall_body!(sys_sigsuspend, {
+        if mask.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        signal::suspend(unsafe { *mask }.into());
+        Err::<c_int, _>(LinuxError::EINTR)
+    })
+}
+
+/// Send the signal `sig` to the thread `pid` (0 for the current thread).
+///
+/// Every thread is a process here, as returned by `getpid`.
+pub fn sys_kill(pid: c_int, sig: c_int) -> c_int {
+    debug!("sys_kill <= {} {}", pid, sig);
+    syscall_body!(sys_kill, {
+        send_signal(&find_task(pid)?, sig, signal::SI_USER)?;
+        Ok(0)
+    })
+}
+
+/// Get the value of the interval timer `which`.
+///
+/// Only `ITIMER_REAL` is supported, which requires the `irq` feature.
+pub unsafe fn sys_getitimer(which: c_int, curr_value: *mut ctypes::itimerval) -> c_int {
+    debug!("sys_getitimer <= {} {:#x}", which, curr_value as usize);
+    syscall_body!(sys_getitimer, {
+        if which as u32 != ctypes::ITIMER_REAL {
+            return Err(LinuxError::EINVAL);
+        }
+        if curr_value.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let (value, interval) = signal::real_timer().ok_or(LinuxError::ENOSYS)?;
+        unsafe {
+            *curr_value = ctypes::itimerval {
+                it_interval: interval.into(),
+                it_value: value.into(),
+            }
+        };
+        Ok(0)
+    })
+}
+
+/// Set the interval timer `which`, and store the old value in `old_value` if
+/// it's not null.
+///
+/// Only `ITIMER_REAL` is supported, which requires the `irq` feature. It
+/// sends `SIGALRM` to the current thread when it expires.
+pub unsafe fn sys_setitimer(
+    which: c_int,
+    new_value: *const ctypes::itimerval,
+    old_value: *mut ctypes::itimerval,
+) -> c_int {
+    debug!(
+        "sys_setitimer <= {} {:#x} {:#x}",
+        which, new_value as usize, old_value as usize
+    );
+    syscall_body!(sys_setitimer, {
+        if which as u32 != ctypes::ITIMER_REAL {
+            return Err(LinuxError::EINVAL);
+        }
+        if new_value.is_null() {
+            return Err(LinuxError::EFAULT);
+        }
+        let new_value = unsafe { &*new_value };
+        let value = timeval_to_duration(&new_value.it_value)?;
+        let interval = timeval_to_duration(&new_value.it_interval)?;
+        let (old, old_interval) =
+            signal::set_real_timer(value, interval).ok_or(LinuxError::ENOSYS)?;
+        if !old_value.is_null() {
+            unsafe {
+                *old_value = ctypes::itimerval {
+                    it_interval: old_interval.into(),
+                    it_value: old.into(),
+                }
+            };
+        }
+        Ok(0)
+    })
+}
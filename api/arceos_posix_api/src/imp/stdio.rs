@@ -125,6 +125,8 @@ impl super::fd_ops::FileLike for Stdin {
         Ok(PollState {
             readable: true,
             writable: true,
+            hangup: false,
+            error: false,
         })
     }
 
@@ -161,6 +163,8 @@ impl super::fd_ops::FileLike for Stdout {
         Ok(PollState {
             readable: true,
             writable: true,
+            hangup: false,
+            error: false,
         })
     }
 
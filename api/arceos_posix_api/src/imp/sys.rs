@@ -14,9 +14,9 @@ pub fn sys_sysconf(name: c_int) -> c_long {
             // Page size
             ctypes::_SC_PAGE_SIZE => Ok(PAGE_SIZE_4K),
             // Total physical pages
-            ctypes::_SC_PHYS_PAGES => Ok(axconfig::PHYS_MEMORY_SIZE / PAGE_SIZE_4K),
+            ctypes::_SC_PHYS_PAGES => Ok(axhal::mem::total_memory_size() / PAGE_SIZE_4K),
             // Number of processors in use
-            ctypes::_SC_NPROCESSORS_ONLN => Ok(axconfig::SMP),
+            ctypes::_SC_NPROCESSORS_ONLN => Ok(axhal::cpu::cpu_count()),
             // Avaliable physical pages
             #[cfg(feature = "alloc")]
             ctypes::_SC_AVPHYS_PAGES => Ok(axalloc::global_allocator().available_pages()),
@@ -1,12 +1,22 @@
-use core::ffi::c_int;
+use core::ffi::{c_int, c_ulong};
+
+use axerrno::LinuxError;
+
+use crate::ctypes;
+use crate::utils::{check_null_mut_ptr, check_null_ptr};
 
 /// Relinquish the CPU, and switches to another task.
 ///
+/// Pending signals are handled after the current task is switched back.
+///
 /// For single-threaded configuration (`multitask` feature is disabled), we just
 /// relax the CPU and wait for incoming interrupts.
 pub fn sys_sched_yield() -> c_int {
     #[cfg(feature = "multitask")]
-    axtask::yield_now();
+    {
+        axtask::yield_now();
+        axtask::signal::handle_signals();
+    }
     #[cfg(not(feature = "multitask"))]
     if cfg!(feature = "irq") {
         axhal::arch::wait_for_irqs();
@@ -38,3 +48,90 @@ pub fn sys_exit(exit_code: c_int) -> ! {
     #[cfg(not(feature = "multitask"))]
     axhal::misc::terminate();
 }
+
+/// Find the task by the thread ID, or the current task if `pid` is 0.
+#[cfg(feature = "multitask")]
+pub(crate) fn find_task(pid: c_int) -> axerrno::LinuxResult<axtask::AxTaskRef> {
+    if pid == 0 {
+        return Ok(axtask::current().as_task_ref().clone());
+    }
+    axtask::all_tasks()
+        .into_iter()
+        .find(|task| task.id().as_u64() == pid as u64)
+        .ok_or(LinuxError::ESRCH)
+}
+
+/// Set the CPU affinity of the thread `pid` (0 for the current thread), i.e.,
+/// the CPUs it is allowed to run on.
+///
+/// Return 0 if success.
+pub unsafe fn sys_sched_setaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *const ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_sched_setaffinity <= {} {} {:#x}",
+        pid, cpusetsize, mask as usize
+    );
+    syscall_body!(sys_sched_setaffinity, {
+        check_null_ptr(mask)?;
+        if cpusetsize < core::mem::size_of::<c_ulong>() {
+            return Err(LinuxError::EINVAL);
+        }
+        // only the first word is used, as there are no more CPUs
+        let bits = unsafe { (*mask).__bits[0] } as usize;
+        #[cfg(feature = "multitask")]
+        {
+            let task = find_task(pid)?;
+            if !axtask::set_affinity(&task, axtask::CpuMask::from_bits(bits)) {
+                return Err(LinuxError::EINVAL);
+            }
+        }
+        #[cfg(not(feature = "multitask"))]
+        {
+            if pid != 0 && pid != sys_getpid() {
+                return Err(LinuxError::ESRCH);
+            }
+            if bits & 1 == 0 {
+                return Err(LinuxError::EINVAL);
+            }
+        }
+        Ok(0)
+    })
+}
+
+/// Get the CPU affinity of the thread `pid` (0 for the current thread), and
+/// write into `mask`.
+///
+/// Return 0 if success.
+pub unsafe fn sys_sched_getaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *mut ctypes::cpu_set_t,
+) -> c_int {
+    debug!(
+        "sys_sched_getaffinity <= {} {} {:#x}",
+        pid, cpusetsize, mask as usize
+    );
+    syscall_body!(sys_sched_getaffinity, {
+        check_null_mut_ptr(mask)?;
+        if cpusetsize < core::mem::size_of::<c_ulong>() {
+            return Err(LinuxError::EINVAL);
+        }
+        #[cfg(feature = "multitask")]
+        let bits = find_task(pid)?.cpumask().bits();
+        #[cfg(not(feature = "multitask"))]
+        let bits = if pid == 0 || pid == sys_getpid() {
+            1
+        } else {
+            return Err(LinuxError::ESRCH);
+        };
+        let size = cpusetsize.min(core::mem::size_of::<ctypes::cpu_set_t>());
+        unsafe {
+            core::ptr::write_bytes(mask as *mut u8, 0, size);
+            (*mask).__bits[0] = bits as c_ulong;
+        }
+        Ok(0)
+    })
+}
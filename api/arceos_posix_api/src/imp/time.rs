@@ -49,7 +49,8 @@ pub unsafe fn sys_clock_gettime(_clk: ctypes::clockid_t, ts: *mut ctypes::timesp
 
 /// Sleep some nanoseconds
 ///
-/// TODO: should be woken by signals, and set errno
+/// Returns `-EINTR` and stores the remaining time in `rem` if it's woken by
+/// a signal.
 pub unsafe fn sys_nanosleep(req: *const ctypes::timespec, rem: *mut ctypes::timespec) -> c_int {
     syscall_body!(sys_nanosleep, {
         unsafe {
@@ -66,7 +67,7 @@ pub unsafe fn sys_nanosleep(req: *const ctypes::timespec, rem: *mut ctypes::time
         let now = axhal::time::current_time();
 
         #[cfg(feature = "multitask")]
-        axtask::sleep(dur);
+        axtask::signal::interruptible_sleep_until(now + dur);
         #[cfg(not(feature = "multitask"))]
         axhal::time::busy_wait(dur);
 
@@ -0,0 +1,77 @@
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+
+use axerrno::LinuxError;
+use axtask::signal::{self, SignalAction, SignalHandler};
+
+use crate::{ctypes, sys_pthread_kill, sys_pthread_self};
+
+static INIT: Once = Once::new();
+static SERIAL: Mutex<()> = Mutex::new(());
+
+static HANDLED_SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn count_signal(_sig: c_int) {
+    HANDLED_SIGNALS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn set_handler(sig: usize, handler: SignalHandler) {
+    signal::set_signal_action(
+        sig,
+        SignalAction {
+            handler,
+            ..SignalAction::DEFAULT
+        },
+    );
+}
+
+#[test]
+fn test_pthread_kill() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    assert_eq!(sys_pthread_kill(sys_pthread_self(), 0), 0);
+    let unknown = 0x1000 as ctypes::pthread_t;
+    assert_eq!(sys_pthread_kill(unknown, 0), -LinuxError::ESRCH.code());
+    assert_eq!(
+        sys_pthread_kill(unknown, signal::SIGUSR1 as _),
+        -LinuxError::ESRCH.code()
+    );
+}
+
+#[cfg(all(feature = "poll", feature = "pipe"))]
+#[test]
+fn test_poll_pipe_interrupted() {
+    use crate::{sys_close, sys_pipe, sys_poll};
+
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    set_handler(signal::SIGUSR1, SignalHandler::Handler(count_signal));
+    let mut fds = [0; 2];
+    assert_eq!(sys_pipe(&mut fds), 0);
+
+    let handled = HANDLED_SIGNALS.load(Ordering::Relaxed);
+    let main_task = axtask::current().as_task_ref().clone();
+    let task = axtask::spawn(move || {
+        axtask::yield_now();
+        signal::send_signal(&main_task, signal::SIGUSR1, signal::SI_TKILL);
+    });
+    // nothing is written to the pipe, so only the signal ends the wait
+    let mut pfd = ctypes::pollfd {
+        fd: fds[0],
+        events: ctypes::POLLIN as _,
+        revents: 0,
+    };
+    let res = unsafe { sys_poll(&mut pfd, 1, -1) };
+    assert_eq!(res, -LinuxError::EINTR.code());
+    assert_eq!(pfd.revents, 0);
+    // handled before returning from the syscall
+    assert_eq!(HANDLED_SIGNALS.load(Ordering::Relaxed), handled + 1);
+    assert_eq!(task.join(), Some(0));
+
+    assert_eq!(sys_close(fds[0]), 0);
+    assert_eq!(sys_close(fds[1]), 0);
+    set_handler(signal::SIGUSR1, SignalHandler::Default);
+}
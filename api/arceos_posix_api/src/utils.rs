@@ -34,6 +34,8 @@ macro_rules! syscall_body {
     ($fn: ident, $($stmt: tt)*) => {{
         #[allow(clippy::redundant_closure_call)]
         let res = (|| -> axerrno::LinuxResult<_> { $($stmt)* })();
+        #[cfg(feature = "multitask")]
+        axtask::signal::handle_signals();
         match res {
             Ok(_) | Err(axerrno::LinuxError::EAGAIN) => debug!(concat!(stringify!($fn), " => {:?}"),  res),
             Err(_) => info!(concat!(stringify!($fn), " => {:?}"), res),
@@ -51,6 +53,8 @@ macro_rules! syscall_body_no_debug {
     ($($stmt: tt)*) => {{
         #[allow(clippy::redundant_closure_call)]
         let res = (|| -> axerrno::LinuxResult<_> { $($stmt)* })();
+        #[cfg(feature = "multitask")]
+        axtask::signal::handle_signals();
         match res {
             Ok(v) => v as _,
             Err(e) => {
@@ -4,6 +4,7 @@
 use arm_pl011::pl011::Pl011Uart;
 #[cfg(feature = "axstd")]
 use axstd::println;
+use chassis_protocol::{Command, Direction};
 
 #[cfg_attr(feature = "axstd", no_mangle)]
 fn main() {
@@ -31,161 +32,44 @@ fn main() {
     let mut uart = Pl011Uart::new(uart_base);
 
     println!("start");
-    {
-        // 鸣笛：0xFF_FC_05_02_60_00_67
-        uart.putchar(0xff);
-        uart.putchar(0xfc);
-        uart.putchar(0x05);
-        uart.putchar(0x02);
-        uart.putchar(0x60);
-        uart.putchar(0x00);
-        uart.putchar(0x67);
-    }
+    send(&mut uart, Command::Buzzer(96));
     delay(1);
 
     // loop {
     //     println!("forward");
-    //     {
-    //         // 前进：0xff_fc_07_11_01_01_64_00_7e
-    //         uart.putchar(0xff);
-    //         uart.putchar(0xfc);
-    //         uart.putchar(0x07);
-    //         uart.putchar(0x11);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x64);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x7e);
-    //     }
+    //     send(&mut uart, Command::Drive(Direction::Forward, 100));
     //     delay(6);
-
-    //     println!("stop");
-    //     {
-    //         // 停止：0xff_fc_07_11_01_00_00_00_19
-    //         uart.putchar(0xff);
-    //         uart.putchar(0xfc);
-    //         uart.putchar(0x07);
-    //         uart.putchar(0x11);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x19);
-    //     }
-    //     delay(1);
-
-    //     println!("turn right");
-    //     {
-    //         // // 左转：0xff_fc_07_11_01_05_64_00_82
-    //         // uart.putchar(0xff);
-    //         // uart.putchar(0xfc);
-    //         // uart.putchar(0x07);
-    //         // uart.putchar(0x11);
-    //         // uart.putchar(0x01);
-    //         // uart.putchar(0x05);
-    //         // uart.putchar(0x64);
-    //         // uart.putchar(0x00);
-    //         // uart.putchar(0x82);
-
-    //         // 右转：0xff_fc_07_11_01_06_64_00_83
-    //         uart.putchar(0xff);
-    //         uart.putchar(0xfc);
-    //         uart.putchar(0x07);
-    //         uart.putchar(0x11);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x06);
-    //         uart.putchar(0x64);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x83);
-    //     }
-    //     delay(1);
-    //     // println!("forward");
-    //     // {
-    //     //     // 前进：0xff_fc_07_11_01_01_64_00_7e
-    //     //     uart.putchar(0xff);
-    //     //     uart.putchar(0xfc);
-    //     //     uart.putchar(0x07);
-    //     //     uart.putchar(0x11);
-    //     //     uart.putchar(0x01);
-    //     //     uart.putchar(0x01);
-    //     //     uart.putchar(0x64);
-    //     //     uart.putchar(0x00);
-    //     //     uart.putchar(0x7e);
-    //     // }
-    //     // delay(4);
-
+    //
     //     println!("stop");
-    //     {
-    //         // 停止：0xff_fc_07_11_01_00_00_00_19
-    //         uart.putchar(0xff);
-    //         uart.putchar(0xfc);
-    //         uart.putchar(0x07);
-    //         uart.putchar(0x11);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x19);
-    //     }
+    //     send(&mut uart, Command::Stop);
     //     delay(1);
-
+    //
     //     println!("turn right");
-    //     {
-    //         // // 左转：0xff_fc_07_11_01_05_64_00_82
-    //         // uart.putchar(0xff);
-    //         // uart.putchar(0xfc);
-    //         // uart.putchar(0x07);
-    //         // uart.putchar(0x11);
-    //         // uart.putchar(0x01);
-    //         // uart.putchar(0x05);
-    //         // uart.putchar(0x64);
-    //         // uart.putchar(0x00);
-    //         // uart.putchar(0x82);
-
-    //         // 右转：0xff_fc_07_11_01_06_64_00_83
-    //         uart.putchar(0xff);
-    //         uart.putchar(0xfc);
-    //         uart.putchar(0x07);
-    //         uart.putchar(0x11);
-    //         uart.putchar(0x01);
-    //         uart.putchar(0x06);
-    //         uart.putchar(0x64);
-    //         uart.putchar(0x00);
-    //         uart.putchar(0x83);
-    //     }
+    //     // send(&mut uart, Command::Turn(Rotation::Left, 100));
+    //     send(&mut uart, Command::Turn(Rotation::Right, 100));
     //     delay(1);
     // }
 
     loop {
         println!("forward");
-        {
-            //前进
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x01);
-            uart.putchar(0x32);
-            uart.putchar(0x00);
-            uart.putchar(0x4c);
-        }
+        send(&mut uart, Command::Drive(Direction::Forward, 50));
         delay(4);
-        {
-            //偏航角PID设置
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x0a);
-            uart.putchar(0x14);
-            uart.putchar(0x20);
-            uart.putchar(0x00);
-            uart.putchar(0x20);
-            uart.putchar(0x00);
-            uart.putchar(0x20);
-            uart.putchar(0x00);
-            uart.putchar(0x00);
-            uart.putchar(0x7e);
-        }
+        // yaw PID, to keep going straight
+        send(
+            &mut uart,
+            Command::SetYawPid {
+                kp: 32,
+                ki: 32,
+                kd: 32,
+            },
+        );
         delay(1);
     }
 }
+
+/// Sends a command to the chassis controller.
+fn send(uart: &mut Pl011Uart, cmd: Command) {
+    for &b in cmd.to_frame().as_bytes() {
+        uart.putchar(b);
+    }
+}
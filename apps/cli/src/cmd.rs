@@ -1,4 +1,5 @@
-use arm_pl011::pl011::Pl011Uart;
+use chassis_protocol::{Chassis, Command, Direction, Rotation};
+use std::fs::{self, File, OpenOptions, PinFunction, Pull, SerialConfig};
 use std::io::{self};
 #[cfg(feature = "axalloc")]
 #[cfg(feature = "axstd")]
@@ -167,134 +168,93 @@ fn split_whitespace(str: &str) -> (&str, &str) {
 }
 
 fn do_go(args: &str) {
-    let str_addr1 = "ffff0000fe200004 246c0";
-    let str_addr2 = "ffff0000fe2000e4 55000000";
-    let str_addr3 = "ffff0000fe201a24 1A";
-    let str_addr4 = "ffff0000fe201a28 3";
-    let str_addr5 = "ffff0000fe201a2c 70";
-    let str_addr6 = "ffff0000fe201a30 301";
-
-    do_str(str_addr1);
-    do_str(str_addr2);
-    do_str(str_addr3);
-    do_str(str_addr4);
-    do_str(str_addr5);
-    do_str(str_addr6);
-
-    let uart_base = 0xffff_0000_fe20_1a00 as *mut u8;
-    let mut uart = Pl011Uart::new(uart_base);
+    let mut chassis = match open_chassis() {
+        Ok(chassis) => chassis,
+        Err(e) => {
+            println!("go: {}", e);
+            return;
+        }
+    };
 
     match args {
         "f" => {
             //前进
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x01);
-            uart.putchar(0x64);
-            uart.putchar(0x00);
-            uart.putchar(0x7e);
+            send(&mut chassis, Command::Drive(Direction::Forward, 100));
         }
         "b" => {
             //后退
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x02);
-            uart.putchar(0x64);
-            uart.putchar(0x00);
-            uart.putchar(0x7f);
+            send(&mut chassis, Command::Drive(Direction::Backward, 100));
         }
         "s" => {
             //停止
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x00);
-            uart.putchar(0x00);
-            uart.putchar(0x00);
-            uart.putchar(0x19);
+            send(&mut chassis, Command::Stop);
         }
         "r" => {
             //右转
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x06);
-            uart.putchar(0x64);
-            uart.putchar(0x00);
-            uart.putchar(0x83);
+            send(&mut chassis, Command::Turn(Rotation::Right, 100));
         }
         "l" => {
             //左转
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x07);
-            uart.putchar(0x11);
-            uart.putchar(0x01);
-            uart.putchar(0x05);
-            uart.putchar(0x64);
-            uart.putchar(0x00);
-            uart.putchar(0x82);
+            send(&mut chassis, Command::Turn(Rotation::Left, 100));
         }
         "w" => {
             //鸣笛
-            uart.putchar(0xff);
-            uart.putchar(0xfc);
-            uart.putchar(0x05);
-            uart.putchar(0x02);
-            uart.putchar(0x60);
-            uart.putchar(0x00);
-            uart.putchar(0x67);
+            send(&mut chassis, Command::Buzzer(96));
         }
         _ => {}
     }
 }
+
+/// The serial port of the chassis controller, i.e., UART5 of the Raspberry Pi
+/// 4, which is the only serial port other than the console.
+const CHASSIS_PORT: &str = "/dev/ttyS0";
+/// The GPIO controller of the Raspberry Pi 4.
+const GPIO_CHIP: &str = "/dev/gpiochip0";
+
+/// Routes UART5 to GPIO 12 (TXD) and 13 (RXD), and sets it to 115200 8N1.
+fn setup_uart5() -> io::Result<()> {
+    for pin in [12, 13] {
+        fs::gpio_set_function(GPIO_CHIP, pin, PinFunction::Alt(4))?;
+        fs::gpio_set_pull(GPIO_CHIP, pin, Pull::Up)?;
+    }
+    fs::set_serial_config(CHASSIS_PORT, &SerialConfig::default())
+}
+
+/// Opens the chassis controller on UART5.
+fn open_chassis() -> io::Result<Chassis<File>> {
+    setup_uart5()?;
+    let port = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(CHASSIS_PORT)?;
+    Ok(Chassis::new(port))
+}
+
+/// Sends a command to the chassis controller.
+fn send(chassis: &mut Chassis<File>, cmd: Command) {
+    if let Err(e) = chassis.send(&cmd) {
+        println!("chassis: {}", e);
+    }
+}
+
 fn do_uart(args: &str) {
     match args {
         "5" => {
-            let str_addr1 = "ffff0000fe200004 246c0";
-            let str_addr2 = "ffff0000fe2000e4 55000000";
-            let str_addr3 = "ffff0000fe201a24 1A";
-            let str_addr4 = "ffff0000fe201a28 3";
-            let str_addr5 = "ffff0000fe201a2c 70";
-            let str_addr6 = "ffff0000fe201a30 301";
-
-            do_str(str_addr1);
-            do_str(str_addr2);
-            do_str(str_addr3);
-            do_str(str_addr4);
-            do_str(str_addr5);
-            do_str(str_addr6);
+            if let Err(e) = setup_uart5() {
+                println!("uart: {}", e);
+            }
         }
         _ => {}
     }
 }
 fn do_moves(args: &str) {
-    let str_addr1 = "ffff0000fe200004 246c0";
-    let str_addr2 = "ffff0000fe2000e4 55000000";
-    let str_addr3 = "ffff0000fe201a24 1A";
-    let str_addr4 = "ffff0000fe201a28 3";
-    let str_addr5 = "ffff0000fe201a2c 70";
-    let str_addr6 = "ffff0000fe201a30 301";
-
-    do_str(str_addr1);
-    do_str(str_addr2);
-    do_str(str_addr3);
-    do_str(str_addr4);
-    do_str(str_addr5);
-    do_str(str_addr6);
-
-    let uart_base = 0xffff_0000_fe20_1a00 as *mut u8;
-    let mut uart = Pl011Uart::new(uart_base);
+    let mut chassis = match open_chassis() {
+        Ok(chassis) => chassis,
+        Err(e) => {
+            println!("moves: {}", e);
+            return;
+        }
+    };
 
     let mut iter = args.split_whitespace();
     if let Some(shape) = iter.next() {
@@ -328,72 +288,32 @@ fn do_moves(args: &str) {
                         println!("forward");
                         {
                             // 前进
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x07);
-                            uart.putchar(0x11);
-                            uart.putchar(0x01);
-                            uart.putchar(0x01);
-                            uart.putchar(0x64);
-                            uart.putchar(0x00);
-                            uart.putchar(0x7e);
+                            send(&mut chassis, Command::Drive(Direction::Forward, 100));
                         }
                         delay(4);
                         println!("stop");
                         {
                             // 停止
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x07);
-                            uart.putchar(0x11);
-                            uart.putchar(0x01);
-                            uart.putchar(0x00);
-                            uart.putchar(0x00);
-                            uart.putchar(0x00);
-                            uart.putchar(0x19);
+                            send(&mut chassis, Command::Stop);
                         }
                         delay(1);
                         println!("turn right");
                         {
                             // 右转
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x07);
-                            uart.putchar(0x11);
-                            uart.putchar(0x01);
-                            uart.putchar(0x06);
-                            uart.putchar(0x64);
-                            uart.putchar(0x00);
-                            uart.putchar(0x83);
+                            send(&mut chassis, Command::Turn(Rotation::Right, 100));
                         }
                         delay(1);
                         println!("stop");
                         {
                             // 停止
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x07);
-                            uart.putchar(0x11);
-                            uart.putchar(0x01);
-                            uart.putchar(0x00);
-                            uart.putchar(0x00);
-                            uart.putchar(0x00);
-                            uart.putchar(0x19);
+                            send(&mut chassis, Command::Stop);
                         }
                         delay(1);
                     }
                     delay(1);
                     {
                         // 停止
-                        uart.putchar(0xff);
-                        uart.putchar(0xfc);
-                        uart.putchar(0x07);
-                        uart.putchar(0x11);
-                        uart.putchar(0x01);
-                        uart.putchar(0x00);
-                        uart.putchar(0x00);
-                        uart.putchar(0x00);
-                        uart.putchar(0x19);
+                        send(&mut chassis, Command::Stop);
                     }
                 }
                 "c" => {
@@ -402,32 +322,20 @@ fn do_moves(args: &str) {
                         println!("前进");
                         {
                             //前进
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x07);
-                            uart.putchar(0x11);
-                            uart.putchar(0x01);
-                            uart.putchar(0x01);
-                            uart.putchar(0x32);
-                            uart.putchar(0x00);
-                            uart.putchar(0x4c);
+                            send(&mut chassis, Command::Drive(Direction::Forward, 50));
                         }
                         delay(1);
                         println!("偏移");
                         {
                             //偏航角PID设置
-                            uart.putchar(0xff);
-                            uart.putchar(0xfc);
-                            uart.putchar(0x0a);
-                            uart.putchar(0x14);
-                            uart.putchar(0x20);
-                            uart.putchar(0x00);
-                            uart.putchar(0x20);
-                            uart.putchar(0x00);
-                            uart.putchar(0x20);
-                            uart.putchar(0x00);
-                            uart.putchar(0x00);
-                            uart.putchar(0x7e);
+                            send(
+                                &mut chassis,
+                                Command::SetYawPid {
+                                    kp: 32,
+                                    ki: 32,
+                                    kd: 32,
+                                },
+                            );
                         }
                         delay(5);
                     }
@@ -435,13 +343,7 @@ fn do_moves(args: &str) {
 
                 "w" => {
                     for _ in 0..mount {
-                        uart.putchar(0xff);
-                        uart.putchar(0xfc);
-                        uart.putchar(0x05);
-                        uart.putchar(0x02);
-                        uart.putchar(0x60);
-                        uart.putchar(0x00);
-                        uart.putchar(0x67);
+                        send(&mut chassis, Command::Buzzer(96));
                         println!("鸣笛");
                     }
                 }
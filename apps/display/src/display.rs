@@ -2,10 +2,11 @@ use embedded_graphics::pixelcolor::Rgb888;
 use embedded_graphics::prelude::{RgbColor, Size};
 use embedded_graphics::{draw_target::DrawTarget, prelude::OriginDimensions};
 
-use std::os::arceos::api::display as api;
+use std::os::arceos::api::display::{self as api, AxPixelFormat};
 
 pub struct Display {
     size: Size,
+    format: AxPixelFormat,
     fb: &'static mut [u8],
 }
 
@@ -15,7 +16,11 @@ impl Display {
         let fb =
             unsafe { core::slice::from_raw_parts_mut(info.fb_base_vaddr as *mut u8, info.fb_size) };
         let size = Size::new(info.width, info.height);
-        Self { size, fb }
+        Self {
+            size,
+            format: info.format,
+            fb,
+        }
     }
 
     pub fn flush(&self) {
@@ -37,15 +42,31 @@ impl DrawTarget for Display {
     where
         I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>>,
     {
-        pixels.into_iter().for_each(|px| {
-            let idx = (px.0.y * self.size.width as i32 + px.0.x) as usize * 4;
-            if idx + 2 >= self.fb.len() {
-                return;
+        let (width, height) = (self.size.width as i32, self.size.height as i32);
+        for px in pixels {
+            let (x, y) = (px.0.x, px.0.y);
+            if x < 0 || y < 0 || x >= width || y >= height {
+                continue;
             }
-            self.fb[idx] = px.1.b();
-            self.fb[idx + 1] = px.1.g();
-            self.fb[idx + 2] = px.1.r();
-        });
+            match self.format {
+                AxPixelFormat::Bgra8888 => {
+                    let idx = (y * width + x) as usize * 4;
+                    self.fb[idx] = px.1.b();
+                    self.fb[idx + 1] = px.1.g();
+                    self.fb[idx + 2] = px.1.r();
+                }
+                AxPixelFormat::Mono1 => {
+                    // any color other than black turns the pixel on
+                    let idx = (y * ((width + 7) / 8) + x / 8) as usize;
+                    let bit = 0x80 >> (x % 8);
+                    if px.1 == Rgb888::BLACK {
+                        self.fb[idx] &= !bit;
+                    } else {
+                        self.fb[idx] |= bit;
+                    }
+                }
+            }
+        }
         Ok(())
     }
 }
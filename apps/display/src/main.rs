@@ -77,9 +77,38 @@ fn test_gpu() {
     }
 }
 
+/// Draws on a small screen, e.g., a 128 x 64 OLED.
+fn test_small_screen() {
+    let mut disp = Display::new();
+    let size = disp.size();
+    let center_y = size.height as i32 / 2;
+    disp.clear(Rgb888::BLACK).unwrap();
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_stroke(Rgb888::WHITE, 1))
+        .draw(&mut disp)
+        .ok();
+    Circle::with_center(Point::new(20, center_y), 24)
+        .into_styled(PrimitiveStyle::with_fill(Rgb888::WHITE))
+        .draw(&mut disp)
+        .ok();
+    Text::with_alignment(
+        "ArceOS",
+        Point::new(80, center_y + 5),
+        MonoTextStyle::new(&FONT_10X20, Rgb888::WHITE),
+        Alignment::Center,
+    )
+    .draw(&mut disp)
+    .ok();
+    disp.flush();
+}
+
 #[cfg_attr(feature = "axstd", no_mangle)]
 fn main() -> ! {
-    test_gpu();
+    if Display::new().size().height < INIT_Y as u32 {
+        test_small_screen();
+    } else {
+        test_gpu();
+    }
     loop {
         core::hint::spin_loop();
     }
@@ -0,0 +1,135 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate axstd as std;
+
+use core::time::Duration;
+use std::io;
+use std::net::{IcmpSocket, IpAddr, ToSocketAddrs};
+use std::thread;
+use std::time::Instant;
+
+macro_rules! env_or_default {
+    ($key:literal, $default:expr) => {
+        match option_env!($key) {
+            Some(val) => val,
+            None => $default,
+        }
+    };
+}
+
+/// The host to ping, the gateway of QEMU user networking by default.
+const DEST: &str = env_or_default!("PING_DEST", "10.0.2.2");
+/// The number of echo requests to send.
+const COUNT: &str = env_or_default!("PING_COUNT", "4");
+
+const PAYLOAD_LEN: usize = 56;
+const ICMP_HEADER_LEN: usize = 8;
+const INTERVAL: Duration = Duration::from_secs(1);
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Builds an echo request. The identifier and the checksum are filled by the
+/// network stack.
+fn echo_request(ipv6: bool, seq: u16, buf: &mut [u8]) {
+    buf[0] = if ipv6 { 128 } else { 8 };
+    buf[1] = 0;
+    buf[2..4].fill(0);
+    buf[4..6].fill(0);
+    buf[6..8].copy_from_slice(&seq.to_be_bytes());
+    for (i, b) in buf[ICMP_HEADER_LEN..].iter_mut().enumerate() {
+        *b = i as u8;
+    }
+}
+
+/// Returns the sequence number if `buf` is an echo reply.
+fn echo_reply_seq(ipv6: bool, buf: &[u8]) -> Option<u16> {
+    let reply = if ipv6 { 129 } else { 0 };
+    (buf.len() >= ICMP_HEADER_LEN && buf[0] == reply && buf[1] == 0)
+        .then(|| u16::from_be_bytes([buf[6], buf[7]]))
+}
+
+/// Waits for the echo reply of `seq` until timeout, returning its length.
+fn wait_reply(socket: &IcmpSocket, dest: IpAddr, seq: u16) -> io::Result<Option<usize>> {
+    let start = Instant::now();
+    let mut buf = [0; ICMP_HEADER_LEN + PAYLOAD_LEN];
+    while start.elapsed() < TIMEOUT {
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(res) => res,
+            Err(io::Error::WouldBlock) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if from == dest && echo_reply_seq(dest.is_ipv6(), &buf[..len]) == Some(seq) {
+            return Ok(Some(len));
+        }
+    }
+    Ok(None)
+}
+
+fn ping() -> io::Result<()> {
+    let count: u16 = COUNT.parse().unwrap_or(4);
+    let dest = match (DEST, 0).to_socket_addrs()?.next() {
+        Some(addr) => addr.ip(),
+        None => return Err(io::Error::InvalidInput),
+    };
+    let ipv6 = dest.is_ipv6();
+
+    let socket = IcmpSocket::bind(0)?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    println!("PING {} ({}): {} data bytes", DEST, dest, PAYLOAD_LEN);
+
+    let mut received = 0;
+    let mut rtt_sum = Duration::ZERO;
+    let (mut rtt_min, mut rtt_max) = (Duration::MAX, Duration::ZERO);
+    for seq in 0..count {
+        let mut request = [0; ICMP_HEADER_LEN + PAYLOAD_LEN];
+        echo_request(ipv6, seq, &mut request);
+        let start = Instant::now();
+        socket.send_to(&request, dest)?;
+
+        match wait_reply(&socket, dest, seq)? {
+            Some(len) => {
+                let rtt = start.elapsed();
+                println!(
+                    "{} bytes from {}: icmp_seq={} time={}.{:03} ms",
+                    len,
+                    dest,
+                    seq,
+                    rtt.as_millis(),
+                    rtt.as_micros() % 1000,
+                );
+                received += 1;
+                rtt_sum += rtt;
+                rtt_min = rtt_min.min(rtt);
+                rtt_max = rtt_max.max(rtt);
+            }
+            None => println!("Request timeout for icmp_seq {}", seq),
+        }
+        if seq + 1 < count {
+            thread::sleep(INTERVAL.saturating_sub(start.elapsed()));
+        }
+    }
+
+    println!("--- {} ping statistics ---", DEST);
+    println!(
+        "{} packets transmitted, {} packets received, {}% packet loss",
+        count,
+        received,
+        (count - received) as u32 * 100 / count.max(1) as u32,
+    );
+    if received > 0 {
+        let rtt_avg = rtt_sum / received as u32;
+        println!(
+            "round-trip min/avg/max = {}/{}/{} us",
+            rtt_min.as_micros(),
+            rtt_avg.as_micros(),
+            rtt_max.as_micros(),
+        );
+    }
+    Ok(())
+}
+
+#[no_mangle]
+fn main() {
+    ping().expect("ping failed");
+}
@@ -19,6 +19,12 @@ register_structs! {
         /// Flag Register.
         (0x18 => fr: ReadOnly<u32>),
         (0x1c => _reserved1),
+        /// Integer Baud Rate Register.
+        (0x24 => ibrd: ReadWrite<u32>),
+        /// Fractional Baud Rate Register.
+        (0x28 => fbrd: ReadWrite<u32>),
+        /// Line Control Register.
+        (0x2c => lcr_h: ReadWrite<u32>),
         /// Control register.
         (0x30 => cr: ReadWrite<u32>),
         /// Interrupt FIFO Level Select Register.
@@ -31,10 +37,24 @@ register_structs! {
         (0x40 => mis: ReadOnly<u32>),
         /// Interrupt Clear Register.
         (0x44 => icr: WriteOnly<u32>),
-        (0x48 => @END),
+        (0x48 => _reserved2),
+        /// Peripheral Identification Registers.
+        (0xfe0 => periph_id: [ReadOnly<u32>; 4]),
+        (0xff0 => @END),
     }
 }
 
+/// The parity bit of the frame format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    /// No parity bit.
+    None,
+    /// The number of 1s in the data and parity bits is odd.
+    Odd,
+    /// The number of 1s in the data and parity bits is even.
+    Even,
+}
+
 /// The Pl011 Uart
 ///
 /// The Pl011 Uart provides a programing interface for:
@@ -73,12 +93,55 @@ impl Pl011Uart {
         self.regs().ifls.set(0); // 1/8 rxfifo, 1/8 txfifo.
 
         // enable rx interrupt
-        self.regs().imsc.set(1 << 4); // rxim
+        self.regs().imsc.set((1 << 4) | (1 << 6)); // rxim, rtim
 
         // enable receive
         self.regs().cr.set((1 << 0) | (1 << 8) | (1 << 9)); // tx enable, rx enable, uart enable
     }
 
+    /// Returns the peripheral ID, which is `0x0014_1011` or `0x0034_1011` for
+    /// a PL011 (the revision is in bits 20..24).
+    pub fn periph_id(&self) -> u32 {
+        let id = &self.regs().periph_id;
+        (0..4).fold(0, |acc, i| acc | (id[i].get() & 0xff) << (i * 8))
+    }
+
+    /// Sets the baud rate and the frame format.
+    ///
+    /// `clock` is the frequency of the reference clock (`UARTCLK`) in Hz, and
+    /// `data_bits` must be 5 to 8. FIFOs are enabled, and the UART is disabled
+    /// while the registers are updated, after the pending data is sent.
+    pub fn set_line_config(
+        &mut self,
+        clock: u32,
+        baud_rate: u32,
+        data_bits: u8,
+        parity: Parity,
+        two_stop_bits: bool,
+    ) {
+        let cr = self.regs().cr.get();
+        self.regs().cr.set(0);
+        while self.regs().fr.get() & (1 << 3) != 0 {} // busy
+
+        // The divisor is `clock / (16 * baud_rate)` with a 6-bit fraction.
+        let divisor = (clock as u64 * 4 + baud_rate as u64 / 2) / baud_rate as u64;
+        self.regs().ibrd.set((divisor >> 6) as u32 & 0xffff);
+        self.regs().fbrd.set(divisor as u32 & 0x3f);
+
+        let mut lcr_h = (1 << 4) | ((data_bits.clamp(5, 8) as u32 - 5) << 5); // fen, wlen
+        match parity {
+            Parity::None => {}
+            Parity::Odd => lcr_h |= 1 << 1,               // pen
+            Parity::Even => lcr_h |= (1 << 1) | (1 << 2), // pen, eps
+        }
+        if two_stop_bits {
+            lcr_h |= 1 << 3; // stp2
+        }
+        // the baud rate registers are updated only when LCR_H is written
+        self.regs().lcr_h.set(lcr_h);
+        self.regs().cr.set(cr);
+    }
+
     /// Output a char c to data register
     pub fn putchar(&mut self, c: u8) {
         while self.regs().fr.get() & (1 << 5) != 0 {}
@@ -94,10 +157,11 @@ impl Pl011Uart {
         }
     }
 
-    /// Return true if pl011 has received an interrupt
+    /// Return true if pl011 has received an interrupt, or the receive
+    /// timeout interrupt if FIFOs are enabled
     pub fn is_receive_interrupt(&self) -> bool {
         let pending = self.regs().mis.get();
-        pending & (1 << 4) != 0
+        pending & ((1 << 4) | (1 << 6)) != 0
     }
 
     /// Clear all interrupts
@@ -0,0 +1,924 @@
+use alloc::collections::BTreeMap;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+use axfs_vfs::{VfsError, VfsResult};
+
+use crate::layout::*;
+use crate::BlockDevice;
+
+/// Maximum depth of nested directories when checking whether a directory is
+/// moved into itself.
+const MAX_DEPTH: usize = 1024;
+
+/// An entry read from a directory.
+pub struct DirEntry {
+    pub ino: u32,
+    pub file_type: u8,
+    pub name: String,
+}
+
+/// The state of a mounted ext2 filesystem.
+///
+/// All metadata is written through to the disk as soon as it is changed, so
+/// the filesystem is consistent whenever no operation is in progress.
+pub struct Ext2Fs {
+    disk: Box<dyn BlockDevice>,
+    sb: Superblock,
+    groups: Vec<GroupDesc>,
+    block_size: usize,
+    writable: bool,
+    /// Number of live nodes of each inode. An unlinked inode is not freed
+    /// until all of its nodes are dropped.
+    open_count: BTreeMap<u32, usize>,
+}
+
+impl Ext2Fs {
+    pub fn open(mut disk: Box<dyn BlockDevice>) -> VfsResult<Self> {
+        let mut sb = Superblock::new_zeroed();
+        disk.read_at(SUPERBLOCK_OFFSET, &mut sb.raw)?;
+        if sb.magic() != EXT2_MAGIC {
+            warn!("ext2: bad magic number {:#x}", sb.magic());
+            return Err(VfsError::InvalidData);
+        }
+        let incompat = sb.feature_incompat();
+        if incompat & !INCOMPAT_SUPPORTED != 0 {
+            warn!(
+                "ext2: unsupported incompatible features {:#x}",
+                incompat & !INCOMPAT_SUPPORTED
+            );
+            return Err(VfsError::Unsupported);
+        }
+        if sb.log_block_size() > 2
+            || sb.blocks_per_group() == 0
+            || sb.inodes_per_group() == 0
+            || sb.inode_size() < GOOD_OLD_INODE_SIZE
+            || !(32..=64).contains(&sb.desc_size())
+        {
+            warn!("ext2: invalid superblock");
+            return Err(VfsError::InvalidData);
+        }
+
+        let writable =
+            incompat & !INCOMPAT_WRITABLE == 0 && sb.feature_ro_compat() & !RO_COMPAT_WRITABLE == 0;
+        if !writable {
+            info!(
+                "ext2: features {:#x} (incompat) {:#x} (ro_compat) are read-only",
+                incompat,
+                sb.feature_ro_compat()
+            );
+        }
+
+        let block_size = sb.block_size();
+        let desc_size = sb.desc_size();
+        let mut gdt = vec![0; sb.group_count() as usize * desc_size];
+        let gdt_start = (sb.first_data_block() as u64 + 1) * block_size as u64;
+        disk.read_at(gdt_start, &mut gdt)?;
+        let groups = gdt.chunks(desc_size).map(GroupDesc::from_bytes).collect();
+
+        Ok(Self {
+            disk,
+            sb,
+            groups,
+            block_size,
+            writable,
+            open_count: BTreeMap::new(),
+        })
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    pub fn flush(&mut self) -> VfsResult {
+        self.disk.flush()
+    }
+
+    fn check_writable(&self) -> VfsResult {
+        if self.writable {
+            Ok(())
+        } else {
+            Err(VfsError::PermissionDenied)
+        }
+    }
+
+    fn read_block(&mut self, block: u64, buf: &mut [u8]) -> VfsResult {
+        self.disk.read_at(block * self.block_size as u64, buf)
+    }
+
+    fn write_block(&mut self, block: u64, buf: &[u8]) -> VfsResult {
+        self.disk.write_at(block * self.block_size as u64, buf)
+    }
+
+    fn read_ptr(&mut self, block: u64, idx: u64) -> VfsResult<u64> {
+        let mut buf = [0; 4];
+        let offset = block * self.block_size as u64 + idx * 4;
+        self.disk.read_at(offset, &mut buf)?;
+        Ok(u32::from_le_bytes(buf) as u64)
+    }
+
+    fn write_ptr(&mut self, block: u64, idx: u64, ptr: u64) -> VfsResult {
+        let offset = block * self.block_size as u64 + idx * 4;
+        self.disk.write_at(offset, &(ptr as u32).to_le_bytes())
+    }
+
+    fn write_super(&mut self) -> VfsResult {
+        self.disk.write_at(SUPERBLOCK_OFFSET, &self.sb.raw)
+    }
+
+    fn write_group(&mut self, group: usize) -> VfsResult {
+        let desc_size = self.sb.desc_size();
+        let gdt_start = (self.sb.first_data_block() as u64 + 1) * self.block_size as u64;
+        let offset = gdt_start + (group * desc_size) as u64;
+        self.disk
+            .write_at(offset, &self.groups[group].raw[..desc_size])
+    }
+
+    fn blocks_in_group(&self, group: usize) -> u32 {
+        let start =
+            self.sb.first_data_block() as u64 + group as u64 * self.sb.blocks_per_group() as u64;
+        (self.sb.blocks_count() - start).min(self.sb.blocks_per_group() as u64) as u32
+    }
+
+    fn inode_group(&self, ino: u32) -> usize {
+        ((ino - 1) / self.sb.inodes_per_group()) as usize
+    }
+
+    /// Allocates a zeroed block, trying the block group `goal` first.
+    fn alloc_block(&mut self, goal: usize) -> VfsResult<u64> {
+        let mut bitmap = vec![0; self.block_size];
+        for i in 0..self.groups.len() {
+            let group = (goal + i) % self.groups.len();
+            if self.groups[group].free_blocks_count() == 0 {
+                continue;
+            }
+            let bitmap_block = self.groups[group].block_bitmap();
+            self.read_block(bitmap_block, &mut bitmap)?;
+            let Some(bit) = find_zero_bit(&bitmap, self.blocks_in_group(group)) else {
+                continue;
+            };
+            bitmap[bit / 8] |= 1 << (bit % 8);
+            self.write_block(bitmap_block, &bitmap)?;
+
+            let desc = &mut self.groups[group];
+            desc.set_free_blocks_count(desc.free_blocks_count() - 1);
+            self.write_group(group)?;
+            self.sb
+                .set_free_blocks_count(self.sb.free_blocks_count() - 1);
+            self.write_super()?;
+
+            let block = self.sb.first_data_block() as u64
+                + group as u64 * self.sb.blocks_per_group() as u64
+                + bit as u64;
+            bitmap.fill(0);
+            self.write_block(block, &bitmap)?;
+            return Ok(block);
+        }
+        Err(VfsError::StorageFull)
+    }
+
+    fn free_block(&mut self, block: u64) -> VfsResult {
+        let rel = block - self.sb.first_data_block() as u64;
+        let group = (rel / self.sb.blocks_per_group() as u64) as usize;
+        let bit = (rel % self.sb.blocks_per_group() as u64) as usize;
+        let bitmap_block = self.groups[group].block_bitmap();
+        let mut bitmap = vec![0; self.block_size];
+        self.read_block(bitmap_block, &mut bitmap)?;
+        if bitmap[bit / 8] & (1 << (bit % 8)) == 0 {
+            warn!("ext2: freeing free block {}", block);
+            return Ok(());
+        }
+        bitmap[bit / 8] &= !(1 << (bit % 8));
+        self.write_block(bitmap_block, &bitmap)?;
+
+        let desc = &mut self.groups[group];
+        desc.set_free_blocks_count(desc.free_blocks_count() + 1);
+        self.write_group(group)?;
+        self.sb
+            .set_free_blocks_count(self.sb.free_blocks_count() + 1);
+        self.write_super()
+    }
+
+    /// Allocates an inode and clears it on the disk, trying the block group
+    /// `goal` first.
+    fn alloc_inode(&mut self, goal: usize, is_dir: bool) -> VfsResult<u32> {
+        let inodes_per_group = self.sb.inodes_per_group();
+        let mut bitmap = vec![0; self.block_size];
+        for i in 0..self.groups.len() {
+            let group = (goal + i) % self.groups.len();
+            if self.groups[group].free_inodes_count() == 0 {
+                continue;
+            }
+            let bitmap_block = self.groups[group].inode_bitmap();
+            self.read_block(bitmap_block, &mut bitmap)?;
+            let Some(bit) = find_zero_bit(&bitmap, inodes_per_group) else {
+                continue;
+            };
+            let ino = group as u32 * inodes_per_group + bit as u32 + 1;
+            if ino < self.sb.first_ino() {
+                warn!("ext2: reserved inode {} is not in use", ino);
+                continue;
+            }
+            bitmap[bit / 8] |= 1 << (bit % 8);
+            self.write_block(bitmap_block, &bitmap)?;
+
+            let desc = &mut self.groups[group];
+            desc.set_free_inodes_count(desc.free_inodes_count() - 1);
+            if is_dir {
+                desc.set_used_dirs_count(desc.used_dirs_count() + 1);
+            }
+            self.write_group(group)?;
+            self.sb
+                .set_free_inodes_count(self.sb.free_inodes_count() - 1);
+            self.write_super()?;
+
+            let zeros = vec![0; self.sb.inode_size()];
+            let pos = self.inode_pos(ino)?;
+            self.disk.write_at(pos, &zeros)?;
+            return Ok(ino);
+        }
+        Err(VfsError::StorageFull)
+    }
+
+    fn free_inode(&mut self, ino: u32, is_dir: bool) -> VfsResult {
+        let group = self.inode_group(ino);
+        let bit = ((ino - 1) % self.sb.inodes_per_group()) as usize;
+        let bitmap_block = self.groups[group].inode_bitmap();
+        let mut bitmap = vec![0; self.block_size];
+        self.read_block(bitmap_block, &mut bitmap)?;
+        bitmap[bit / 8] &= !(1 << (bit % 8));
+        self.write_block(bitmap_block, &bitmap)?;
+
+        let desc = &mut self.groups[group];
+        desc.set_free_inodes_count(desc.free_inodes_count() + 1);
+        if is_dir {
+            desc.set_used_dirs_count(desc.used_dirs_count() - 1);
+        }
+        self.write_group(group)?;
+        self.sb
+            .set_free_inodes_count(self.sb.free_inodes_count() + 1);
+        self.write_super()
+    }
+
+    fn inode_pos(&self, ino: u32) -> VfsResult<u64> {
+        if ino == 0 || ino > self.sb.inodes_count() {
+            return Err(VfsError::InvalidData);
+        }
+        let group = self.inode_group(ino);
+        let idx = ((ino - 1) % self.sb.inodes_per_group()) as u64;
+        let table = self.groups[group].inode_table();
+        Ok(table * self.block_size as u64 + idx * self.sb.inode_size() as u64)
+    }
+
+    pub fn read_inode(&mut self, ino: u32) -> VfsResult<Inode> {
+        let mut inode = Inode::new_zeroed();
+        let pos = self.inode_pos(ino)?;
+        self.disk.read_at(pos, &mut inode.raw)?;
+        Ok(inode)
+    }
+
+    fn write_inode(&mut self, ino: u32, inode: &Inode) -> VfsResult {
+        let pos = self.inode_pos(ino)?;
+        self.disk.write_at(pos, &inode.raw)
+    }
+
+    /// Registers a live node of the inode.
+    pub fn get_inode(&mut self, ino: u32) {
+        *self.open_count.entry(ino).or_insert(0) += 1;
+    }
+
+    /// Unregisters a live node of the inode, and frees the inode if it is
+    /// the last one and the inode has been unlinked.
+    pub fn put_inode(&mut self, ino: u32) -> VfsResult {
+        let count = self.open_count.get_mut(&ino).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.open_count.remove(&ino);
+            if self.writable && self.read_inode(ino)?.links_count() == 0 {
+                self.release_inode(ino)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Frees the inode and all of its blocks.
+    fn release_inode(&mut self, ino: u32) -> VfsResult {
+        debug!("ext2: release inode {}", ino);
+        let mut inode = self.read_inode(ino)?;
+        if !inode.is_fast_symlink(self.block_size) {
+            self.free_blocks_from(&mut inode, 0)?;
+        }
+        if inode.file_acl() != 0 {
+            warn!("ext2: extended attributes of inode {} are leaked", ino);
+        }
+        // a zeroed inode is unused, and `e2fsck` treats a small `i_dtime`
+        // as a link in the orphan list, so it is not set.
+        self.write_inode(ino, &Inode::new_zeroed())?;
+        self.free_inode(ino, inode.is_dir())
+    }
+
+    /// Splits the index of a file block into the slot in `i_block`, the
+    /// levels of indirection and the index within the slot.
+    fn block_path(&self, idx: u64) -> VfsResult<(usize, u32, u64)> {
+        let ptrs = (self.block_size / 4) as u64;
+        let mut rel = idx;
+        if rel < NDIR_BLOCKS as u64 {
+            return Ok((rel as usize, 0, 0));
+        }
+        rel -= NDIR_BLOCKS as u64;
+        for depth in 1..=3 {
+            let span = ptrs.pow(depth);
+            if rel < span {
+                return Ok((NDIR_BLOCKS + depth as usize - 1, depth, rel));
+            }
+            rel -= span;
+        }
+        Err(VfsError::InvalidInput) // file too large
+    }
+
+    /// Returns the disk block of the file block `idx`, or 0 for a hole.
+    fn bmap(&mut self, inode: &Inode, idx: u64) -> VfsResult<u64> {
+        if inode.uses_extents() {
+            return self.extent_bmap(inode, idx);
+        }
+        let ptrs = (self.block_size / 4) as u64;
+        let (slot, depth, mut rel) = self.block_path(idx)?;
+        let mut block = inode.block_ptr(slot) as u64;
+        for level in (0..depth).rev() {
+            if block == 0 {
+                break;
+            }
+            let span = ptrs.pow(level);
+            block = self.read_ptr(block, rel / span)?;
+            rel %= span;
+        }
+        Ok(block)
+    }
+
+    /// Like [`Self::bmap`], but allocates the data block and the indirect
+    /// blocks if they do not exist.
+    fn bmap_alloc(&mut self, ino: u32, inode: &mut Inode, idx: u64) -> VfsResult<u64> {
+        let goal = self.inode_group(ino);
+        let sectors_per_block = (self.block_size / 512) as u64;
+        let ptrs = (self.block_size / 4) as u64;
+        let (slot, depth, mut rel) = self.block_path(idx)?;
+        let mut block = inode.block_ptr(slot) as u64;
+        if block == 0 {
+            block = self.alloc_block(goal)?;
+            inode.set_block_ptr(slot, block as u32);
+            inode.set_sectors(inode.sectors() + sectors_per_block);
+        }
+        for level in (0..depth).rev() {
+            let span = ptrs.pow(level);
+            let mut next = self.read_ptr(block, rel / span)?;
+            if next == 0 {
+                next = self.alloc_block(goal)?;
+                self.write_ptr(block, rel / span, next)?;
+                inode.set_sectors(inode.sectors() + sectors_per_block);
+            }
+            block = next;
+            rel %= span;
+        }
+        Ok(block)
+    }
+
+    /// Looks up the file block `idx` in the extent tree (ext4).
+    fn extent_bmap(&mut self, inode: &Inode, idx: u64) -> VfsResult<u64> {
+        let mut node = inode.i_block().to_vec();
+        loop {
+            if node.len() < 12 || read_u16(&node, 0) != EXTENT_MAGIC {
+                return Err(VfsError::InvalidData);
+            }
+            let entries = read_u16(&node, 2) as usize;
+            let depth = read_u16(&node, 6);
+            if 12 * (entries + 1) > node.len() {
+                return Err(VfsError::InvalidData);
+            }
+            // the last entry that starts at or before `idx`
+            let Some(entry) = (0..entries)
+                .map(|i| 12 * (i + 1))
+                .take_while(|&e| read_u32(&node, e) as u64 <= idx)
+                .last()
+            else {
+                return Ok(0);
+            };
+
+            if depth == 0 {
+                let first = read_u32(&node, entry) as u64;
+                let len = read_u16(&node, entry + 4) as u64;
+                let start =
+                    ((read_u16(&node, entry + 6) as u64) << 32) | read_u32(&node, entry + 8) as u64;
+                // uninitialized extents (length > 32768) are read as zeros
+                if len > 32768 || idx >= first + len {
+                    return Ok(0);
+                }
+                return Ok(start + idx - first);
+            }
+            let leaf =
+                ((read_u16(&node, entry + 8) as u64) << 32) | read_u32(&node, entry + 4) as u64;
+            node = vec![0; self.block_size];
+            self.read_block(leaf, &mut node)?;
+        }
+    }
+
+    /// Frees all blocks of the file starting from the file block `keep`.
+    fn free_blocks_from(&mut self, inode: &mut Inode, keep: u64) -> VfsResult {
+        if inode.uses_extents() {
+            return Err(VfsError::Unsupported);
+        }
+        let ptrs = (self.block_size / 4) as u64;
+        let mut freed = 0;
+        let mut base = 0;
+        for slot in 0..NDIR_BLOCKS + 3 {
+            let depth = slot.saturating_sub(NDIR_BLOCKS - 1) as u32;
+            let block = inode.block_ptr(slot) as u64;
+            if block != 0 && self.truncate_tree(block, depth, base, keep, &mut freed)? {
+                inode.set_block_ptr(slot, 0);
+            }
+            base += ptrs.pow(depth);
+        }
+        let sectors = freed * (self.block_size / 512) as u64;
+        inode.set_sectors(inode.sectors().saturating_sub(sectors));
+        Ok(())
+    }
+
+    /// Frees the file blocks from `keep` in the tree of `block`, which maps
+    /// the file blocks starting from `base` with `depth` levels of
+    /// indirection. Returns whether `block` itself is freed.
+    fn truncate_tree(
+        &mut self,
+        block: u64,
+        depth: u32,
+        base: u64,
+        keep: u64,
+        freed: &mut u64,
+    ) -> VfsResult<bool> {
+        if base >= keep {
+            self.free_tree(block, depth, freed)?;
+            return Ok(true);
+        }
+        if depth == 0 {
+            return Ok(false);
+        }
+        let ptrs = self.block_size / 4;
+        let span = (ptrs as u64).pow(depth - 1);
+        let mut buf = vec![0; self.block_size];
+        self.read_block(block, &mut buf)?;
+        let mut changed = false;
+        for i in 0..ptrs {
+            let child_base = base + i as u64 * span;
+            let child = read_u32(&buf, i * 4) as u64;
+            if child == 0 || child_base + span <= keep {
+                continue;
+            }
+            if self.truncate_tree(child, depth - 1, child_base, keep, freed)? {
+                write_u32(&mut buf, i * 4, 0);
+                changed = true;
+            }
+        }
+        if changed {
+            self.write_block(block, &buf)?;
+        }
+        Ok(false)
+    }
+
+    fn free_tree(&mut self, block: u64, depth: u32, freed: &mut u64) -> VfsResult {
+        if depth > 0 {
+            let mut buf = vec![0; self.block_size];
+            self.read_block(block, &mut buf)?;
+            for ptr in buf.chunks(4) {
+                let child = read_u32(ptr, 0) as u64;
+                if child != 0 {
+                    self.free_tree(child, depth - 1, freed)?;
+                }
+            }
+        }
+        *freed += 1;
+        self.free_block(block)
+    }
+
+    pub fn read_data(&mut self, inode: &Inode, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let size = inode.size();
+        if offset >= size {
+            return Ok(0);
+        }
+        let len = buf.len().min((size - offset) as usize);
+        let block_size = self.block_size as u64;
+        let mut done = 0;
+        while done < len {
+            let pos = offset + done as u64;
+            let off = (pos % block_size) as usize;
+            let n = (len - done).min(self.block_size - off);
+            let block = self.bmap(inode, pos / block_size)?;
+            let chunk = &mut buf[done..done + n];
+            if block == 0 {
+                chunk.fill(0);
+            } else {
+                self.disk.read_at(block * block_size + off as u64, chunk)?;
+            }
+            done += n;
+        }
+        Ok(len)
+    }
+
+    pub fn write_data(&mut self, ino: u32, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        self.check_writable()?;
+        let mut inode = self.read_inode(ino)?;
+        let block_size = self.block_size as u64;
+        let mut done = 0;
+        let mut res = Ok(());
+        while done < buf.len() {
+            let pos = offset + done as u64;
+            let off = (pos % block_size) as usize;
+            let n = (buf.len() - done).min(self.block_size - off);
+            let block = match self.bmap_alloc(ino, &mut inode, pos / block_size) {
+                Ok(block) => block,
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            };
+            self.disk
+                .write_at(block * block_size + off as u64, &buf[done..done + n])?;
+            done += n;
+        }
+        if offset + done as u64 > inode.size() {
+            inode.set_size(offset + done as u64);
+        }
+        self.write_inode(ino, &inode)?;
+        match res {
+            Err(e) if done == 0 => Err(e),
+            _ => Ok(done),
+        }
+    }
+
+    pub fn truncate(&mut self, ino: u32, size: u64) -> VfsResult {
+        self.check_writable()?;
+        let mut inode = self.read_inode(ino)?;
+        if size < inode.size() {
+            let block_size = self.block_size as u64;
+            self.free_blocks_from(&mut inode, size.div_ceil(block_size))?;
+            // clear the rest of the last block, in case the file grows again
+            let off = (size % block_size) as usize;
+            if off != 0 {
+                let block = self.bmap(&inode, size / block_size)?;
+                if block != 0 {
+                    let zeros = vec![0; self.block_size - off];
+                    self.disk
+                        .write_at(block * block_size + off as u64, &zeros)?;
+                }
+            }
+        }
+        inode.set_size(size);
+        self.write_inode(ino, &inode)
+    }
+
+    pub fn dir_entries(&mut self, dir: &Inode) -> VfsResult<Vec<DirEntry>> {
+        let has_filetype = self.sb.feature_incompat() & INCOMPAT_FILETYPE != 0;
+        let mut entries = Vec::new();
+        let mut buf = vec![0; self.block_size];
+        for idx in 0..dir.size().div_ceil(self.block_size as u64) {
+            let block = self.bmap(dir, idx)?;
+            if block == 0 {
+                continue;
+            }
+            self.read_block(block, &mut buf)?;
+            let mut off = 0;
+            while off + 8 <= buf.len() {
+                let (ino, rec_len, name_len) = parse_dirent(&buf, off)?;
+                if ino != 0 {
+                    let name = &buf[off + 8..off + 8 + name_len];
+                    entries.push(DirEntry {
+                        ino,
+                        file_type: if has_filetype {
+                            buf[off + 7]
+                        } else {
+                            FT_UNKNOWN
+                        },
+                        name: String::from_utf8_lossy(name).into(),
+                    });
+                }
+                off += rec_len;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn find_entry(&mut self, dir: &Inode, name: &str) -> VfsResult<Option<DirEntry>> {
+        Ok(self.dir_entries(dir)?.into_iter().find(|e| e.name == name))
+    }
+
+    fn is_dir_empty(&mut self, dir: &Inode) -> VfsResult<bool> {
+        let entries = self.dir_entries(dir)?;
+        Ok(entries.iter().all(|e| e.name == "." || e.name == ".."))
+    }
+
+    fn write_dirent(&self, buf: &mut [u8], off: usize, rec_len: usize, ent: (&str, u32, u8)) {
+        let (name, ino, file_type) = ent;
+        let has_filetype = self.sb.feature_incompat() & INCOMPAT_FILETYPE != 0;
+        write_u32(buf, off, ino);
+        write_u16(buf, off + 4, rec_len as u16);
+        buf[off + 6] = name.len() as u8;
+        buf[off + 7] = if has_filetype { file_type } else { 0 };
+        buf[off + 8..off + 8 + name.len()].copy_from_slice(name.as_bytes());
+    }
+
+    /// Adds an entry to the directory, and writes the directory inode.
+    fn add_entry(&mut self, dir_ino: u32, dir: &mut Inode, ent: (&str, u32, u8)) -> VfsResult {
+        let need = dirent_len(ent.0.len());
+        let mut buf = vec![0; self.block_size];
+        // htree indexes are not maintained, so they must be dropped
+        dir.set_flags(dir.flags() & !INDEX_FL);
+
+        let nblocks = dir.size() / self.block_size as u64;
+        for idx in 0..nblocks {
+            let block = self.bmap(dir, idx)?;
+            if block == 0 {
+                continue;
+            }
+            self.read_block(block, &mut buf)?;
+            let mut off = 0;
+            while off + 8 <= buf.len() {
+                let (ino, rec_len, name_len) = parse_dirent(&buf, off)?;
+                let used = if ino == 0 { 0 } else { dirent_len(name_len) };
+                if rec_len >= used + need {
+                    if used != 0 {
+                        write_u16(&mut buf, off + 4, used as u16);
+                    }
+                    self.write_dirent(&mut buf, off + used, rec_len - used, ent);
+                    self.write_block(block, &buf)?;
+                    return self.write_inode(dir_ino, dir);
+                }
+                off += rec_len;
+            }
+        }
+
+        // no space in existing blocks, append a new one
+        let block = self.bmap_alloc(dir_ino, dir, nblocks)?;
+        buf.fill(0);
+        self.write_dirent(&mut buf, 0, self.block_size, ent);
+        self.write_block(block, &buf)?;
+        dir.set_size((nblocks + 1) * self.block_size as u64);
+        self.write_inode(dir_ino, dir)
+    }
+
+    /// Removes the entry from the directory, and writes the directory inode.
+    fn remove_entry(&mut self, dir_ino: u32, dir: &mut Inode, name: &str) -> VfsResult {
+        let mut buf = vec![0; self.block_size];
+        dir.set_flags(dir.flags() & !INDEX_FL);
+        for idx in 0..dir.size().div_ceil(self.block_size as u64) {
+            let block = self.bmap(dir, idx)?;
+            if block == 0 {
+                continue;
+            }
+            self.read_block(block, &mut buf)?;
+            let mut prev = None;
+            let mut off = 0;
+            while off + 8 <= buf.len() {
+                let (ino, rec_len, name_len) = parse_dirent(&buf, off)?;
+                if ino != 0 && &buf[off + 8..off + 8 + name_len] == name.as_bytes() {
+                    if let Some(prev) = prev {
+                        // merge into the previous entry
+                        let prev_len = read_u16(&buf, prev + 4);
+                        write_u16(&mut buf, prev + 4, prev_len + rec_len as u16);
+                    } else {
+                        write_u32(&mut buf, off, 0);
+                    }
+                    self.write_block(block, &buf)?;
+                    return self.write_inode(dir_ino, dir);
+                }
+                prev = Some(off);
+                off += rec_len;
+            }
+        }
+        Err(VfsError::NotFound)
+    }
+
+    /// Points the `..` entry of the directory to `parent`.
+    fn set_dotdot(&mut self, dir: &Inode, parent: u32) -> VfsResult {
+        let mut buf = vec![0; self.block_size];
+        let block = self.bmap(dir, 0)?;
+        self.read_block(block, &mut buf)?;
+        let (_, rec_len, _) = parse_dirent(&buf, 0)?;
+        let (_, _, name_len) = parse_dirent(&buf, rec_len)?;
+        if &buf[rec_len + 8..rec_len + 8 + name_len] != b".." {
+            return Err(VfsError::InvalidData);
+        }
+        write_u32(&mut buf, rec_len, parent);
+        self.write_block(block, &buf)
+    }
+
+    pub fn lookup(&mut self, dir_ino: u32, name: &str) -> VfsResult<u32> {
+        let dir = self.read_inode(dir_ino)?;
+        if !dir.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        let entry = self.find_entry(&dir, name)?;
+        entry.map(|e| e.ino).ok_or(VfsError::NotFound)
+    }
+
+    /// Creates an inode with `mode`, and links it to the directory.
+    pub fn create(&mut self, dir_ino: u32, name: &str, mode: u16) -> VfsResult<u32> {
+        self.check_writable()?;
+        if name.len() > 255 {
+            return Err(VfsError::InvalidInput);
+        }
+        let mut dir = self.read_inode(dir_ino)?;
+        if !dir.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        if self.find_entry(&dir, name)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+
+        let is_dir = mode & S_IFMT == S_IFDIR;
+        let ino = self.alloc_inode(self.inode_group(dir_ino), is_dir)?;
+        let mut inode = Inode::new_zeroed();
+        inode.set_mode(mode);
+        inode.set_links_count(1);
+        let res = self.init_inode(ino, &mut inode, dir_ino).and_then(|_| {
+            self.add_entry(dir_ino, &mut dir, (name, ino, mode_to_dirent_type(mode)))
+        });
+        if let Err(e) = res {
+            inode.set_links_count(0);
+            self.write_inode(ino, &inode)?;
+            self.release_inode(ino)?;
+            return Err(e);
+        }
+        if is_dir {
+            dir.set_links_count(dir.links_count() + 1);
+            self.write_inode(dir_ino, &dir)?;
+        }
+        Ok(ino)
+    }
+
+    /// Writes a new inode, with the `.` and `..` entries if it is a
+    /// directory.
+    fn init_inode(&mut self, ino: u32, inode: &mut Inode, parent: u32) -> VfsResult {
+        if inode.is_dir() {
+            let block = self.bmap_alloc(ino, inode, 0)?;
+            let mut buf = vec![0; self.block_size];
+            self.write_dirent(&mut buf, 0, 12, (".", ino, FT_DIR));
+            self.write_dirent(&mut buf, 12, self.block_size - 12, ("..", parent, FT_DIR));
+            self.write_block(block, &buf)?;
+            inode.set_links_count(2);
+            inode.set_size(self.block_size as u64);
+        }
+        self.write_inode(ino, inode)
+    }
+
+    pub fn symlink(&mut self, dir_ino: u32, name: &str, target: &str) -> VfsResult {
+        if target.is_empty() || target.len() >= self.block_size {
+            return Err(VfsError::InvalidInput);
+        }
+        let ino = self.create(dir_ino, name, S_IFLNK | 0o777)?;
+        if target.len() < INLINE_SIZE {
+            let mut inode = self.read_inode(ino)?;
+            inode.i_block_mut()[..target.len()].copy_from_slice(target.as_bytes());
+            inode.set_size(target.len() as u64);
+            self.write_inode(ino, &inode)
+        } else {
+            self.write_data(ino, 0, target.as_bytes())?;
+            Ok(())
+        }
+    }
+
+    pub fn readlink(&mut self, ino: u32, buf: &mut [u8]) -> VfsResult<usize> {
+        let inode = self.read_inode(ino)?;
+        if !inode.is_symlink() {
+            return Err(VfsError::InvalidInput);
+        }
+        if inode.is_fast_symlink(self.block_size) {
+            let len = buf.len().min(inode.size() as usize).min(INLINE_SIZE);
+            buf[..len].copy_from_slice(&inode.i_block()[..len]);
+            Ok(len)
+        } else {
+            self.read_data(&inode, 0, buf)
+        }
+    }
+
+    pub fn link(&mut self, dir_ino: u32, name: &str, ino: u32) -> VfsResult {
+        self.check_writable()?;
+        let mut inode = self.read_inode(ino)?;
+        if inode.is_dir() {
+            return Err(VfsError::PermissionDenied);
+        }
+        let mut dir = self.read_inode(dir_ino)?;
+        if !dir.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        if self.find_entry(&dir, name)?.is_some() {
+            return Err(VfsError::AlreadyExists);
+        }
+        let file_type = mode_to_dirent_type(inode.mode());
+        self.add_entry(dir_ino, &mut dir, (name, ino, file_type))?;
+        inode.set_links_count(inode.links_count() + 1);
+        self.write_inode(ino, &inode)
+    }
+
+    /// Removes the entry from the directory, and frees the inode if it is no
+    /// longer used. Directories must be empty.
+    pub fn unlink(&mut self, dir_ino: u32, name: &str) -> VfsResult {
+        self.check_writable()?;
+        let mut dir = self.read_inode(dir_ino)?;
+        if !dir.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        let entry = self.find_entry(&dir, name)?.ok_or(VfsError::NotFound)?;
+        let mut inode = self.read_inode(entry.ino)?;
+        if inode.is_dir() {
+            if !self.is_dir_empty(&inode)? {
+                return Err(VfsError::DirectoryNotEmpty);
+            }
+            dir.set_links_count(dir.links_count() - 1);
+            inode.set_links_count(0);
+        } else {
+            inode.set_links_count(inode.links_count().saturating_sub(1));
+        }
+        self.remove_entry(dir_ino, &mut dir, name)?;
+        self.write_inode(entry.ino, &inode)?;
+        if inode.links_count() == 0 && !self.open_count.contains_key(&entry.ino) {
+            self.release_inode(entry.ino)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `ino` is `dir_ino` or one of its ancestors.
+    fn is_ancestor(&mut self, ino: u32, dir_ino: u32) -> VfsResult<bool> {
+        let mut cur = dir_ino;
+        for _ in 0..MAX_DEPTH {
+            if cur == ino {
+                return Ok(true);
+            } else if cur == ROOT_INO {
+                return Ok(false);
+            }
+            cur = self.lookup(cur, "..")?;
+        }
+        Err(VfsError::InvalidData)
+    }
+
+    pub fn rename(&mut self, src: (u32, &str), dst: (u32, &str)) -> VfsResult {
+        self.check_writable()?;
+        let ((src_dir, src_name), (dst_dir, dst_name)) = (src, dst);
+        if dst_name.len() > 255 {
+            return Err(VfsError::InvalidInput);
+        }
+        let ino = self.lookup(src_dir, src_name)?;
+        let inode = self.read_inode(ino)?;
+        if inode.is_dir() && self.is_ancestor(ino, dst_dir)? {
+            return Err(VfsError::InvalidInput); // move a directory into itself
+        }
+        match self.lookup(dst_dir, dst_name) {
+            Ok(old) if old == ino => return Ok(()),
+            Ok(old) => {
+                let old_is_dir = self.read_inode(old)?.is_dir();
+                if old_is_dir && !inode.is_dir() {
+                    return Err(VfsError::IsADirectory);
+                } else if !old_is_dir && inode.is_dir() {
+                    return Err(VfsError::NotADirectory);
+                }
+                self.unlink(dst_dir, dst_name)?;
+            }
+            Err(VfsError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut dir = self.read_inode(dst_dir)?;
+        let file_type = mode_to_dirent_type(inode.mode());
+        self.add_entry(dst_dir, &mut dir, (dst_name, ino, file_type))?;
+        let mut dir = self.read_inode(src_dir)?;
+        self.remove_entry(src_dir, &mut dir, src_name)?;
+
+        if inode.is_dir() && src_dir != dst_dir {
+            self.set_dotdot(&inode, dst_dir)?;
+            dir.set_links_count(dir.links_count() - 1);
+            self.write_inode(src_dir, &dir)?;
+            let mut dir = self.read_inode(dst_dir)?;
+            dir.set_links_count(dir.links_count() + 1);
+            self.write_inode(dst_dir, &dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the inode number, record length and name length of the directory
+/// entry at `off`.
+fn parse_dirent(buf: &[u8], off: usize) -> VfsResult<(u32, usize, usize)> {
+    let ino = read_u32(buf, off);
+    let rec_len = read_u16(buf, off + 4) as usize;
+    let name_len = buf[off + 6] as usize;
+    if rec_len < 8 || rec_len % 4 != 0 || off + rec_len > buf.len() || 8 + name_len > rec_len {
+        warn!("ext2: corrupted directory entry");
+        return Err(VfsError::InvalidData);
+    }
+    Ok((ino, rec_len, name_len))
+}
+
+/// Finds the first zero bit in the first `nbits` bits of the bitmap.
+fn find_zero_bit(bitmap: &[u8], nbits: u32) -> Option<usize> {
+    let nbits = (nbits as usize).min(bitmap.len() * 8);
+    let byte = bitmap[..nbits.div_ceil(8)]
+        .iter()
+        .position(|&b| b != 0xff)?;
+    let bit = byte * 8 + bitmap[byte].trailing_ones() as usize;
+    (bit < nbits).then_some(bit)
+}
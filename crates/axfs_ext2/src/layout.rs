@@ -0,0 +1,331 @@
+//! On-disk structures of ext2 (and the parts of ext4 that can be read).
+//!
+//! All structures are kept as raw little-endian bytes, so fields unknown to
+//! this implementation are preserved when they are written back.
+
+pub const SUPERBLOCK_OFFSET: u64 = 1024;
+pub const SUPERBLOCK_SIZE: usize = 1024;
+pub const EXT2_MAGIC: u16 = 0xef53;
+
+pub const ROOT_INO: u32 = 2;
+pub const GOOD_OLD_FIRST_INO: u32 = 11;
+pub const GOOD_OLD_INODE_SIZE: usize = 128;
+
+/// Number of direct block pointers in an inode.
+pub const NDIR_BLOCKS: usize = 12;
+/// Size of `i_block`, where fast symlinks store their targets.
+pub const INLINE_SIZE: usize = 60;
+
+pub const INCOMPAT_FILETYPE: u32 = 0x2;
+pub const INCOMPAT_RECOVER: u32 = 0x4;
+pub const INCOMPAT_EXTENTS: u32 = 0x40;
+pub const INCOMPAT_64BIT: u32 = 0x80;
+pub const INCOMPAT_FLEX_BG: u32 = 0x200;
+/// Incompatible features that can be read.
+pub const INCOMPAT_SUPPORTED: u32 =
+    INCOMPAT_FILETYPE | INCOMPAT_RECOVER | INCOMPAT_EXTENTS | INCOMPAT_64BIT | INCOMPAT_FLEX_BG;
+/// Incompatible features that can be written.
+pub const INCOMPAT_WRITABLE: u32 = INCOMPAT_FILETYPE;
+
+pub const RO_COMPAT_SPARSE_SUPER: u32 = 0x1;
+pub const RO_COMPAT_LARGE_FILE: u32 = 0x2;
+/// Read-only compatible features that can be written.
+pub const RO_COMPAT_WRITABLE: u32 = RO_COMPAT_SPARSE_SUPER | RO_COMPAT_LARGE_FILE;
+
+pub const S_IFMT: u16 = 0o170000;
+pub const S_IFSOCK: u16 = 0o140000;
+pub const S_IFLNK: u16 = 0o120000;
+pub const S_IFREG: u16 = 0o100000;
+pub const S_IFBLK: u16 = 0o060000;
+pub const S_IFDIR: u16 = 0o040000;
+pub const S_IFCHR: u16 = 0o020000;
+pub const S_IFIFO: u16 = 0o010000;
+
+pub const INDEX_FL: u32 = 0x1000;
+pub const EXTENTS_FL: u32 = 0x80000;
+
+pub const EXTENT_MAGIC: u16 = 0xf30a;
+
+pub const FT_UNKNOWN: u8 = 0;
+pub const FT_REG_FILE: u8 = 1;
+pub const FT_DIR: u8 = 2;
+pub const FT_CHRDEV: u8 = 3;
+pub const FT_BLKDEV: u8 = 4;
+pub const FT_FIFO: u8 = 5;
+pub const FT_SOCK: u8 = 6;
+pub const FT_SYMLINK: u8 = 7;
+
+pub fn read_u16(buf: &[u8], off: usize) -> u16 {
+    u16::from_le_bytes([buf[off], buf[off + 1]])
+}
+
+pub fn read_u32(buf: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(buf[off..off + 4].try_into().unwrap())
+}
+
+pub fn write_u16(buf: &mut [u8], off: usize, val: u16) {
+    buf[off..off + 2].copy_from_slice(&val.to_le_bytes());
+}
+
+pub fn write_u32(buf: &mut [u8], off: usize, val: u32) {
+    buf[off..off + 4].copy_from_slice(&val.to_le_bytes());
+}
+
+/// Defines getters (and optionally setters) of little-endian fields.
+macro_rules! le_fields {
+    ($($($get:ident)? $(/ $set:ident)?: $ty:ident @ $off:expr;)*) => {
+        $(
+            $(
+                pub fn $get(&self) -> $ty {
+                    le_fields!(@read $ty, self.raw, $off)
+                }
+            )?
+            $(
+                pub fn $set(&mut self, val: $ty) {
+                    le_fields!(@write $ty, self.raw, $off, val)
+                }
+            )?
+        )*
+    };
+    (@read u16, $raw:expr, $off:expr) => { read_u16(&$raw, $off) };
+    (@read u32, $raw:expr, $off:expr) => { read_u32(&$raw, $off) };
+    (@write u16, $raw:expr, $off:expr, $val:expr) => { write_u16(&mut $raw, $off, $val) };
+    (@write u32, $raw:expr, $off:expr, $val:expr) => { write_u32(&mut $raw, $off, $val) };
+}
+
+/// The superblock, located at byte 1024 of the device.
+pub struct Superblock {
+    pub raw: [u8; SUPERBLOCK_SIZE],
+}
+
+impl Superblock {
+    le_fields! {
+        inodes_count / set_inodes_count: u32 @ 0;
+        blocks_count_lo / set_blocks_count_lo: u32 @ 4;
+        free_blocks_count / set_free_blocks_count: u32 @ 12;
+        free_inodes_count / set_free_inodes_count: u32 @ 16;
+        first_data_block / set_first_data_block: u32 @ 20;
+        log_block_size / set_log_block_size: u32 @ 24;
+        / set_log_frag_size: u32 @ 28;
+        blocks_per_group / set_blocks_per_group: u32 @ 32;
+        / set_frags_per_group: u32 @ 36;
+        inodes_per_group / set_inodes_per_group: u32 @ 40;
+        / set_max_mnt_count: u16 @ 54;
+        magic / set_magic: u16 @ 56;
+        / set_state: u16 @ 58;
+        / set_errors: u16 @ 60;
+        rev_level / set_rev_level: u32 @ 76;
+        first_ino_raw / set_first_ino: u32 @ 84;
+        inode_size_raw / set_inode_size: u16 @ 88;
+        / set_block_group_nr: u16 @ 90;
+        feature_incompat / set_feature_incompat: u32 @ 96;
+        feature_ro_compat / set_feature_ro_compat: u32 @ 100;
+        desc_size_raw: u16 @ 254;
+        blocks_count_hi: u32 @ 336;
+    }
+
+    pub const fn new_zeroed() -> Self {
+        Self {
+            raw: [0; SUPERBLOCK_SIZE],
+        }
+    }
+
+    pub fn is_64bit(&self) -> bool {
+        self.feature_incompat() & INCOMPAT_64BIT != 0
+    }
+
+    pub fn blocks_count(&self) -> u64 {
+        let hi = if self.is_64bit() {
+            self.blocks_count_hi() as u64
+        } else {
+            0
+        };
+        (hi << 32) | self.blocks_count_lo() as u64
+    }
+
+    pub fn block_size(&self) -> usize {
+        1024 << self.log_block_size()
+    }
+
+    pub fn first_ino(&self) -> u32 {
+        if self.rev_level() == 0 {
+            GOOD_OLD_FIRST_INO
+        } else {
+            self.first_ino_raw()
+        }
+    }
+
+    pub fn inode_size(&self) -> usize {
+        if self.rev_level() == 0 {
+            GOOD_OLD_INODE_SIZE
+        } else {
+            self.inode_size_raw() as usize
+        }
+    }
+
+    pub fn desc_size(&self) -> usize {
+        if self.is_64bit() {
+            self.desc_size_raw() as usize
+        } else {
+            32
+        }
+    }
+
+    pub fn group_count(&self) -> u32 {
+        let data_blocks = self.blocks_count() - self.first_data_block() as u64;
+        data_blocks.div_ceil(self.blocks_per_group() as u64) as u32
+    }
+}
+
+/// A block group descriptor.
+pub struct GroupDesc {
+    pub raw: [u8; 64],
+}
+
+impl GroupDesc {
+    le_fields! {
+        block_bitmap_lo / set_block_bitmap: u32 @ 0;
+        inode_bitmap_lo / set_inode_bitmap: u32 @ 4;
+        inode_table_lo / set_inode_table: u32 @ 8;
+        free_blocks_count / set_free_blocks_count: u16 @ 12;
+        free_inodes_count / set_free_inodes_count: u16 @ 14;
+        used_dirs_count / set_used_dirs_count: u16 @ 16;
+        block_bitmap_hi: u32 @ 32;
+        inode_bitmap_hi: u32 @ 36;
+        inode_table_hi: u32 @ 40;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut raw = [0; 64];
+        raw[..bytes.len()].copy_from_slice(bytes);
+        Self { raw }
+    }
+
+    // The high 32 bits of block numbers only exist if the descriptor is
+    // larger than 32 bytes, otherwise they are zeros.
+
+    pub fn block_bitmap(&self) -> u64 {
+        ((self.block_bitmap_hi() as u64) << 32) | self.block_bitmap_lo() as u64
+    }
+
+    pub fn inode_bitmap(&self) -> u64 {
+        ((self.inode_bitmap_hi() as u64) << 32) | self.inode_bitmap_lo() as u64
+    }
+
+    pub fn inode_table(&self) -> u64 {
+        ((self.inode_table_hi() as u64) << 32) | self.inode_table_lo() as u64
+    }
+}
+
+/// The first 128 bytes of an inode, which are the same in all revisions.
+#[derive(Clone)]
+pub struct Inode {
+    pub raw: [u8; GOOD_OLD_INODE_SIZE],
+}
+
+impl Inode {
+    le_fields! {
+        mode / set_mode: u16 @ 0;
+        size_lo / set_size_lo: u32 @ 4;
+        links_count / set_links_count: u16 @ 26;
+        blocks_lo / set_blocks_lo: u32 @ 28;
+        flags / set_flags: u32 @ 32;
+        file_acl: u32 @ 104;
+        size_high / set_size_high: u32 @ 108;
+    }
+
+    pub const fn new_zeroed() -> Self {
+        Self {
+            raw: [0; GOOD_OLD_INODE_SIZE],
+        }
+    }
+
+    pub fn file_type(&self) -> u16 {
+        self.mode() & S_IFMT
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type() == S_IFDIR
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.file_type() == S_IFLNK
+    }
+
+    pub fn uses_extents(&self) -> bool {
+        self.flags() & EXTENTS_FL != 0
+    }
+
+    pub fn size(&self) -> u64 {
+        if self.file_type() == S_IFREG {
+            ((self.size_high() as u64) << 32) | self.size_lo() as u64
+        } else {
+            self.size_lo() as u64
+        }
+    }
+
+    pub fn set_size(&mut self, size: u64) {
+        self.set_size_lo(size as u32);
+        if self.file_type() == S_IFREG {
+            self.set_size_high((size >> 32) as u32);
+        }
+    }
+
+    /// Number of 512-byte sectors occupied by the inode, including the
+    /// indirect blocks.
+    pub fn sectors(&self) -> u64 {
+        self.blocks_lo() as u64
+    }
+
+    pub fn set_sectors(&mut self, sectors: u64) {
+        self.set_blocks_lo(sectors as u32);
+    }
+
+    /// The `i_block` area, which contains block pointers, an extent tree or
+    /// the target of a fast symlink.
+    pub fn i_block(&self) -> &[u8] {
+        &self.raw[40..40 + INLINE_SIZE]
+    }
+
+    pub fn i_block_mut(&mut self) -> &mut [u8] {
+        &mut self.raw[40..40 + INLINE_SIZE]
+    }
+
+    pub fn block_ptr(&self, idx: usize) -> u32 {
+        read_u32(self.i_block(), idx * 4)
+    }
+
+    pub fn set_block_ptr(&mut self, idx: usize, block: u32) {
+        write_u32(self.i_block_mut(), idx * 4, block)
+    }
+
+    /// Whether the symlink target is stored in `i_block` rather than a data
+    /// block.
+    pub fn is_fast_symlink(&self, block_size: usize) -> bool {
+        let acl_sectors = if self.file_acl() != 0 {
+            block_size as u64 / 512
+        } else {
+            0
+        };
+        self.is_symlink() && !self.uses_extents() && self.sectors() == acl_sectors
+    }
+}
+
+/// Converts the file type in `i_mode` to the one in directory entries.
+pub fn mode_to_dirent_type(mode: u16) -> u8 {
+    match mode & S_IFMT {
+        S_IFREG => FT_REG_FILE,
+        S_IFDIR => FT_DIR,
+        S_IFCHR => FT_CHRDEV,
+        S_IFBLK => FT_BLKDEV,
+        S_IFIFO => FT_FIFO,
+        S_IFSOCK => FT_SOCK,
+        S_IFLNK => FT_SYMLINK,
+        _ => FT_UNKNOWN,
+    }
+}
+
+/// Length of a directory entry with a name of `name_len` bytes.
+pub const fn dirent_len(name_len: usize) -> usize {
+    (8 + name_len + 3) & !3
+}
@@ -0,0 +1,124 @@
+//! Ext2 filesystem used by [ArceOS](https://github.com/rcore-os/arceos).
+//!
+//! It supports permissions, hard links, symbolic links and sparse files on
+//! the disks formatted by `mkfs.ext2`, or by [`Ext2FileSystem::format`].
+//!
+//! Ext4 filesystems can also be mounted, but only for reading, as well as
+//! ext2 filesystems with other features that are not supported. The files
+//! in ext4 are read through their extent trees.
+//!
+//! The implementation is based on [`axfs_vfs`].
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate log;
+
+mod fs;
+mod layout;
+mod mkfs;
+mod node;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::node::Ext2Node;
+
+use alloc::{boxed::Box, sync::Arc};
+use axfs_vfs::{VfsNodeOps, VfsNodeRef, VfsOps, VfsResult};
+use spin::{Mutex, RwLock};
+
+use self::fs::Ext2Fs;
+use self::layout::ROOT_INO;
+use self::node::Volume;
+
+/// The disk that stores an ext2 filesystem.
+pub trait BlockDevice: Send {
+    /// Returns the size of the disk in bytes.
+    fn size(&self) -> u64;
+
+    /// Reads exactly `buf.len()` bytes at the byte offset `offset`.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> VfsResult;
+
+    /// Writes all bytes in `buf` at the byte offset `offset`.
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> VfsResult;
+
+    /// Writes the cached data to the disk.
+    fn flush(&mut self) -> VfsResult {
+        Ok(())
+    }
+}
+
+/// An ext2 filesystem that implements [`axfs_vfs::VfsOps`].
+pub struct Ext2FileSystem {
+    vol: Arc<Volume>,
+    root: Arc<Ext2Node>,
+}
+
+impl Ext2FileSystem {
+    /// Opens the filesystem on the disk.
+    ///
+    /// If the filesystem uses features that cannot be written (e.g., ext4),
+    /// it is opened read-only, and all modifications fail with
+    /// [`PermissionDenied`](axfs_vfs::VfsError::PermissionDenied).
+    pub fn new<D: BlockDevice + 'static>(disk: D) -> VfsResult<Self> {
+        let vol = Arc::new(Volume {
+            fs: Mutex::new(Ext2Fs::open(Box::new(disk))?),
+            parent: RwLock::new(None),
+        });
+        let root = Ext2Node::new(&vol, ROOT_INO);
+        if !root.get_attr()?.is_dir() {
+            return Err(axfs_vfs::VfsError::InvalidData);
+        }
+        Ok(Self { vol, root })
+    }
+
+    /// Formats the whole disk as an empty ext2 filesystem, and opens it.
+    ///
+    /// The root directory contains an empty `lost+found` directory, as
+    /// `mkfs.ext2` creates.
+    pub fn format<D: BlockDevice + 'static>(mut disk: D) -> VfsResult<Self> {
+        mkfs::format(&mut disk)?;
+        let fs = Self::new(disk)?;
+        fs.vol
+            .fs
+            .lock()
+            .create(ROOT_INO, "lost+found", layout::S_IFDIR | 0o700)?;
+        Ok(fs)
+    }
+
+    /// Whether the filesystem can be modified.
+    pub fn is_writable(&self) -> bool {
+        self.vol.fs.lock().is_writable()
+    }
+
+    /// Returns the root directory node in [`Arc<Ext2Node>`](Ext2Node).
+    pub fn root_dir_node(&self) -> Arc<Ext2Node> {
+        self.root.clone()
+    }
+}
+
+impl VfsOps for Ext2FileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        *self.vol.parent.write() = mount_point.parent();
+        Ok(())
+    }
+
+    fn umount(&self) -> VfsResult {
+        self.vol.fs.lock().flush()
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+}
+
+impl Drop for Ext2FileSystem {
+    fn drop(&mut self) {
+        if let Err(e) = self.vol.fs.lock().flush() {
+            warn!("ext2: failed to flush the disk: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use alloc::vec;
+use axfs_vfs::{VfsError, VfsResult};
+
+use crate::layout::*;
+use crate::BlockDevice;
+
+const BLOCK_SIZE: usize = 1024;
+const INODE_SIZE: usize = GOOD_OLD_INODE_SIZE;
+const DESC_SIZE: usize = 32;
+const BLOCKS_PER_GROUP: u32 = BLOCK_SIZE as u32 * 8;
+const INODES_PER_BLOCK: u32 = (BLOCK_SIZE / INODE_SIZE) as u32;
+/// One inode for every 4 KiB of disk space, like `mkfs.ext2` does.
+const BYTES_PER_INODE: u64 = 4096;
+/// The smallest disk that can be formatted.
+const MIN_BLOCKS: u64 = 64;
+
+/// Creates an empty ext2 filesystem that occupies the whole disk.
+///
+/// The filesystem uses 1 KiB blocks, 128-byte inodes and only the
+/// `filetype` and `large_file` features. Every block group has a copy of the
+/// superblock and the group descriptors.
+pub fn format(disk: &mut dyn BlockDevice) -> VfsResult {
+    let mut blocks = (disk.size() / BLOCK_SIZE as u64).min(u32::MAX as u64);
+    if blocks < MIN_BLOCKS {
+        return Err(VfsError::InvalidInput);
+    }
+
+    let mut groups = (blocks - 1).div_ceil(BLOCKS_PER_GROUP as u64) as u32;
+    let inodes_per_group = ((blocks * BLOCK_SIZE as u64 / BYTES_PER_INODE) as u32)
+        .div_ceil(groups)
+        .next_multiple_of(INODES_PER_BLOCK)
+        .clamp(16, BLOCKS_PER_GROUP);
+    let gdt_blocks = (groups as usize * DESC_SIZE).div_ceil(BLOCK_SIZE) as u32;
+    let itable_blocks = inodes_per_group / INODES_PER_BLOCK;
+    // superblock, descriptors, bitmaps and the inode table
+    let overhead = 1 + gdt_blocks + 2 + itable_blocks;
+
+    // drop the last group if it cannot hold its own metadata
+    let last_blocks = (blocks - 1) as u32 - (groups - 1) * BLOCKS_PER_GROUP;
+    if last_blocks < overhead + 8 {
+        if groups == 1 {
+            return Err(VfsError::InvalidInput);
+        }
+        groups -= 1;
+        blocks = 1 + (groups * BLOCKS_PER_GROUP) as u64;
+    }
+
+    let mut sb = Superblock::new_zeroed();
+    sb.set_inodes_count(inodes_per_group * groups);
+    sb.set_blocks_count_lo(blocks as u32);
+    sb.set_first_data_block(1);
+    sb.set_log_block_size(0);
+    sb.set_log_frag_size(0);
+    sb.set_blocks_per_group(BLOCKS_PER_GROUP);
+    sb.set_frags_per_group(BLOCKS_PER_GROUP);
+    sb.set_inodes_per_group(inodes_per_group);
+    sb.set_max_mnt_count(u16::MAX);
+    sb.set_magic(EXT2_MAGIC);
+    sb.set_state(1); // cleanly unmounted
+    sb.set_errors(1); // continue on errors
+    sb.set_rev_level(1);
+    sb.set_first_ino(GOOD_OLD_FIRST_INO);
+    sb.set_inode_size(INODE_SIZE as u16);
+    sb.set_feature_incompat(INCOMPAT_FILETYPE);
+    sb.set_feature_ro_compat(RO_COMPAT_LARGE_FILE);
+
+    let mut descs = vec![0; gdt_blocks as usize * BLOCK_SIZE];
+    let mut free_blocks = 0;
+    let mut buf = vec![0; BLOCK_SIZE];
+    for group in 0..groups {
+        let start = 1 + group * BLOCKS_PER_GROUP;
+        let group_blocks = (blocks as u32 - start).min(BLOCKS_PER_GROUP);
+        let block_bitmap = start + 1 + gdt_blocks;
+        let inode_bitmap = block_bitmap + 1;
+        let inode_table = inode_bitmap + 1;
+        // the root directory takes the first data block
+        let used_blocks = if group == 0 { overhead + 1 } else { overhead };
+        let used_inodes = if group == 0 {
+            GOOD_OLD_FIRST_INO - 1
+        } else {
+            0
+        };
+
+        // block bitmap, with the bits beyond the group set
+        buf.fill(0);
+        set_bits(&mut buf, 0..used_blocks as usize);
+        set_bits(&mut buf, group_blocks as usize..BLOCK_SIZE * 8);
+        write_block(disk, block_bitmap, &buf)?;
+
+        // inode bitmap, with the reserved inodes set
+        buf.fill(0);
+        set_bits(&mut buf, 0..used_inodes as usize);
+        set_bits(&mut buf, inodes_per_group as usize..BLOCK_SIZE * 8);
+        write_block(disk, inode_bitmap, &buf)?;
+
+        buf.fill(0);
+        for i in 0..itable_blocks {
+            write_block(disk, inode_table + i, &buf)?;
+        }
+
+        let mut desc = GroupDesc::from_bytes(&[0; DESC_SIZE]);
+        desc.set_block_bitmap(block_bitmap);
+        desc.set_inode_bitmap(inode_bitmap);
+        desc.set_inode_table(inode_table);
+        desc.set_free_blocks_count((group_blocks - used_blocks) as u16);
+        desc.set_free_inodes_count((inodes_per_group - used_inodes) as u16);
+        desc.set_used_dirs_count(if group == 0 { 1 } else { 0 });
+        let off = group as usize * DESC_SIZE;
+        descs[off..off + DESC_SIZE].copy_from_slice(&desc.raw[..DESC_SIZE]);
+        free_blocks += group_blocks - used_blocks;
+    }
+    sb.set_free_blocks_count(free_blocks);
+    sb.set_free_inodes_count(inodes_per_group * groups - (GOOD_OLD_FIRST_INO - 1));
+
+    // the root directory, with only `.` and `..`
+    let root_block = 1 + overhead;
+    buf.fill(0);
+    write_u32(&mut buf, 0, ROOT_INO);
+    write_u16(&mut buf, 4, 12);
+    buf[6] = 1;
+    buf[7] = FT_DIR;
+    buf[8] = b'.';
+    write_u32(&mut buf, 12, ROOT_INO);
+    write_u16(&mut buf, 16, (BLOCK_SIZE - 12) as u16);
+    buf[18] = 2;
+    buf[19] = FT_DIR;
+    buf[20..22].copy_from_slice(b"..");
+    write_block(disk, root_block, &buf)?;
+
+    let mut root = Inode::new_zeroed();
+    root.set_mode(S_IFDIR | 0o755);
+    root.set_links_count(2);
+    root.set_size(BLOCK_SIZE as u64);
+    root.set_sectors((BLOCK_SIZE / 512) as u64);
+    root.set_block_ptr(0, root_block);
+    let root_pos = (1 + overhead - itable_blocks) as u64 * BLOCK_SIZE as u64
+        + (ROOT_INO as u64 - 1) * INODE_SIZE as u64;
+    disk.write_at(root_pos, &root.raw)?;
+
+    // the superblock and the descriptors in every group
+    for group in 0..groups {
+        let start = 1 + group * BLOCKS_PER_GROUP;
+        sb.set_block_group_nr(group as u16);
+        disk.write_at(start as u64 * BLOCK_SIZE as u64, &sb.raw)?;
+        disk.write_at((start + 1) as u64 * BLOCK_SIZE as u64, &descs)?;
+    }
+    disk.flush()
+}
+
+fn write_block(disk: &mut dyn BlockDevice, block: u32, buf: &[u8]) -> VfsResult {
+    disk.write_at(block as u64 * BLOCK_SIZE as u64, buf)
+}
+
+fn set_bits(bitmap: &mut [u8], bits: core::ops::Range<usize>) {
+    for bit in bits {
+        bitmap[bit / 8] |= 1 << (bit % 8);
+    }
+}
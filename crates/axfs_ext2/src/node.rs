@@ -0,0 +1,258 @@
+use alloc::sync::Arc;
+
+use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsError, VfsResult};
+use spin::{Mutex, RwLock};
+
+use crate::fs::Ext2Fs;
+use crate::layout::*;
+
+/// A mounted ext2 filesystem shared by all of its nodes.
+pub(crate) struct Volume {
+    pub fs: Mutex<Ext2Fs>,
+    /// Parent of the root directory, i.e., the parent of the mount point.
+    pub parent: RwLock<Option<VfsNodeRef>>,
+}
+
+/// A file, directory or symbolic link in the ext2 filesystem.
+///
+/// It implements [`axfs_vfs::VfsNodeOps`].
+pub struct Ext2Node {
+    vol: Arc<Volume>,
+    ino: u32,
+}
+
+impl Ext2Node {
+    pub(crate) fn new(vol: &Arc<Volume>, ino: u32) -> Arc<Self> {
+        vol.fs.lock().get_inode(ino);
+        Arc::new(Self {
+            vol: vol.clone(),
+            ino,
+        })
+    }
+
+    /// Returns the inode number.
+    pub fn ino(&self) -> u32 {
+        self.ino
+    }
+
+    /// Resolves `path` relative to this directory, without following
+    /// symbolic links.
+    fn resolve(&self, path: &str) -> VfsResult<u32> {
+        let mut fs = self.vol.fs.lock();
+        let mut ino = self.ino;
+        for name in path.split('/') {
+            match name {
+                "" | "." => {
+                    if !fs.read_inode(ino)?.is_dir() {
+                        return Err(VfsError::NotADirectory);
+                    }
+                }
+                _ => ino = fs.lookup(ino, name)?,
+            }
+        }
+        Ok(ino)
+    }
+
+    /// Resolves the parent directory of `path`, and returns its inode number
+    /// and the last component of `path`.
+    fn resolve_parent<'a>(&self, path: &'a str) -> VfsResult<(u32, &'a str)> {
+        let path = path.trim_end_matches('/');
+        match path.rsplit_once('/') {
+            Some((dir, name)) => Ok((self.resolve(dir)?, name)),
+            None => Ok((self.resolve("")?, path)),
+        }
+    }
+}
+
+impl Drop for Ext2Node {
+    fn drop(&mut self) {
+        if let Err(e) = self.vol.fs.lock().put_inode(self.ino) {
+            warn!("ext2: failed to release inode {}: {:?}", self.ino, e);
+        }
+    }
+}
+
+impl VfsNodeOps for Ext2Node {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let inode = self.vol.fs.lock().read_inode(self.ino)?;
+        let ty = match inode.file_type() {
+            S_IFREG => VfsNodeType::File,
+            S_IFDIR => VfsNodeType::Dir,
+            S_IFLNK => VfsNodeType::SymLink,
+            S_IFCHR => VfsNodeType::CharDevice,
+            S_IFBLK => VfsNodeType::BlockDevice,
+            S_IFIFO => VfsNodeType::Fifo,
+            S_IFSOCK => VfsNodeType::Socket,
+            _ => return Err(VfsError::InvalidData),
+        };
+        let perm = VfsNodePerm::from_bits_truncate(inode.mode() & 0o777);
+        Ok(VfsNodeAttr::new(perm, ty, inode.size(), inode.sectors()))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut fs = self.vol.fs.lock();
+        let inode = fs.read_inode(self.ino)?;
+        match inode.file_type() {
+            S_IFREG => fs.read_data(&inode, offset, buf),
+            S_IFDIR => Err(VfsError::IsADirectory),
+            _ => Err(VfsError::InvalidInput),
+        }
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        let mut fs = self.vol.fs.lock();
+        match fs.read_inode(self.ino)?.file_type() {
+            S_IFREG => fs.write_data(self.ino, offset, buf),
+            S_IFDIR => Err(VfsError::IsADirectory),
+            _ => Err(VfsError::InvalidInput),
+        }
+    }
+
+    fn fsync(&self) -> VfsResult {
+        self.vol.fs.lock().flush()
+    }
+
+    fn truncate(&self, size: u64) -> VfsResult {
+        let mut fs = self.vol.fs.lock();
+        match fs.read_inode(self.ino)?.file_type() {
+            S_IFREG => fs.truncate(self.ino, size),
+            S_IFDIR => Err(VfsError::IsADirectory),
+            _ => Err(VfsError::InvalidInput),
+        }
+    }
+
+    fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+        self.vol.fs.lock().readlink(self.ino, buf)
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        if self.ino == ROOT_INO {
+            return self.vol.parent.read().clone();
+        }
+        let ino = self.vol.fs.lock().lookup(self.ino, "..").ok()?;
+        Some(Self::new(&self.vol, ino))
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        debug!("lookup at ext2: {}", path);
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => {
+                if !self.get_attr()?.is_dir() {
+                    return Err(VfsError::NotADirectory);
+                }
+                self.clone() as VfsNodeRef
+            }
+            ".." if self.ino == ROOT_INO => self.parent().ok_or(VfsError::NotFound)?,
+            _ => {
+                let ino = self.vol.fs.lock().lookup(self.ino, name)?;
+                Self::new(&self.vol, ino)
+            }
+        };
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
+        debug!("create {:?} at ext2: {}", ty, path);
+        let (dir, name) = self.resolve_parent(path)?;
+        if name.is_empty() || name == "." || name == ".." {
+            return Ok(()); // already exists
+        }
+        let mode = match ty {
+            VfsNodeType::File => S_IFREG | VfsNodePerm::default_file().bits(),
+            VfsNodeType::Dir => S_IFDIR | VfsNodePerm::default_dir().bits(),
+            _ => return Err(VfsError::Unsupported),
+        };
+        self.vol.fs.lock().create(dir, name, mode)?;
+        Ok(())
+    }
+
+    fn remove(&self, path: &str) -> VfsResult {
+        debug!("remove at ext2: {}", path);
+        let (dir, name) = self.resolve_parent(path)?;
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(VfsError::InvalidInput); // remove '.' or '..'
+        }
+        self.vol.fs.lock().unlink(dir, name)
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        debug!("symlink {} -> {} at ext2", path, target);
+        let (dir, name) = self.resolve_parent(path)?;
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(VfsError::AlreadyExists);
+        }
+        self.vol.fs.lock().symlink(dir, name, target)
+    }
+
+    fn link(&self, path: &str, node: &VfsNodeRef) -> VfsResult {
+        debug!("link at ext2: {}", path);
+        let node = match node.as_any().downcast_ref::<Self>() {
+            Some(node) if Arc::ptr_eq(&node.vol, &self.vol) => node,
+            _ => return Err(VfsError::InvalidInput), // not in this filesystem
+        };
+        let (dir, name) = self.resolve_parent(path)?;
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(VfsError::AlreadyExists);
+        }
+        self.vol.fs.lock().link(dir, name, node.ino)
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        let mut fs = self.vol.fs.lock();
+        let inode = fs.read_inode(self.ino)?;
+        if !inode.is_dir() {
+            return Err(VfsError::NotADirectory);
+        }
+        let entries = fs.dir_entries(&inode)?;
+        let mut count = 0;
+        for (ent, out) in entries.iter().skip(start_idx).zip(dirents.iter_mut()) {
+            let file_type = if ent.file_type == FT_UNKNOWN {
+                mode_to_dirent_type(fs.read_inode(ent.ino)?.mode())
+            } else {
+                ent.file_type
+            };
+            let ty = match file_type {
+                FT_DIR => VfsNodeType::Dir,
+                FT_SYMLINK => VfsNodeType::SymLink,
+                FT_CHRDEV => VfsNodeType::CharDevice,
+                FT_BLKDEV => VfsNodeType::BlockDevice,
+                FT_FIFO => VfsNodeType::Fifo,
+                FT_SOCK => VfsNodeType::Socket,
+                _ => VfsNodeType::File,
+            };
+            *out = VfsDirEntry::new(&ent.name, ty);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        debug!("rename at ext2: {} -> {}", src_path, dst_path);
+        let (src_dir, src_name) = self.resolve_parent(src_path)?;
+        let (dst_dir, dst_name) = self.resolve_parent(dst_path)?;
+        for name in [src_name, dst_name] {
+            if name.is_empty() || name == "." || name == ".." {
+                return Err(VfsError::InvalidInput);
+            }
+        }
+        let mut fs = self.vol.fs.lock();
+        fs.rename((src_dir, src_name), (dst_dir, dst_name))
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    let trimmed_path = path.trim_start_matches('/');
+    trimmed_path.find('/').map_or((trimmed_path, None), |n| {
+        (&trimmed_path[..n], Some(&trimmed_path[n + 1..]))
+    })
+}
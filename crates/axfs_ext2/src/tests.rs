@@ -0,0 +1,270 @@
+use std::sync::{Arc, Mutex};
+
+use axfs_vfs::{VfsError, VfsNodeType, VfsOps, VfsResult};
+
+use crate::*;
+
+const DISK_SIZE: usize = 4 * 1024 * 1024;
+
+/// A disk in memory, which can be shared by several filesystem instances to
+/// check that the data is persisted.
+#[derive(Clone)]
+struct MemDisk(Arc<Mutex<Vec<u8>>>);
+
+impl MemDisk {
+    fn new(size: usize) -> Self {
+        Self(Arc::new(Mutex::new(vec![0xcc; size])))
+    }
+}
+
+impl BlockDevice for MemDisk {
+    fn size(&self) -> u64 {
+        self.0.lock().unwrap().len() as u64
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> VfsResult {
+        let data = self.0.lock().unwrap();
+        let start = offset as usize;
+        let src = data.get(start..start + buf.len()).ok_or(VfsError::Io)?;
+        buf.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> VfsResult {
+        let mut data = self.0.lock().unwrap();
+        let start = offset as usize;
+        let dst = data.get_mut(start..start + buf.len()).ok_or(VfsError::Io)?;
+        dst.copy_from_slice(buf);
+        Ok(())
+    }
+}
+
+fn entries(fs: &Ext2FileSystem, path: &str) -> VfsResult<Vec<String>> {
+    let dir = fs.root_dir().lookup(path)?;
+    let mut dirents = [(); 128].map(|_| axfs_vfs::VfsDirEntry::default());
+    let n = dir.read_dir(0, &mut dirents)?;
+    let mut names = dirents[..n]
+        .iter()
+        .map(|e| String::from_utf8(e.name_as_bytes().to_vec()).unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+fn ino(node: &axfs_vfs::VfsNodeRef) -> u32 {
+    node.as_any().downcast_ref::<Ext2Node>().unwrap().ino()
+}
+
+fn test_file_ops(fs: &Ext2FileSystem) -> VfsResult {
+    let root = fs.root_dir();
+    let mut buf = [1; 32];
+
+    root.create("f1", VfsNodeType::File)?;
+    let f1 = root.clone().lookup("f1")?;
+    let attr = f1.get_attr()?;
+    assert_eq!(attr.file_type(), VfsNodeType::File);
+    assert_eq!(attr.perm().mode(), 0o666);
+    assert_eq!(attr.size(), 0);
+    assert_eq!(f1.read_at(0, &mut buf)?, 0);
+    assert_eq!(f1.write_at(16, &buf[..16])?, 16);
+    assert_eq!(f1.read_at(0, &mut buf)?, 32);
+    assert_eq!(buf[..16], [0; 16]);
+    assert_eq!(buf[16..], [1; 16]);
+    assert_eq!(f1.lookup("/").err(), Some(VfsError::NotADirectory));
+
+    // a file that uses the double indirect blocks
+    let data = (0..300 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    root.create("big", VfsNodeType::File)?;
+    let big = root.clone().lookup("big")?;
+    assert_eq!(big.write_at(0, &data)?, data.len());
+    let mut read = vec![0; data.len() + 100];
+    assert_eq!(big.read_at(0, &mut read)?, data.len());
+    assert_eq!(read[..data.len()], data[..]);
+    let blocks = big.get_attr()?.blocks();
+    assert!(blocks > data.len() as u64 / 512);
+
+    // shrink and grow again
+    big.truncate(20000)?;
+    assert!(big.get_attr()?.blocks() < blocks);
+    big.truncate(30000)?;
+    assert_eq!(big.read_at(19990, &mut buf)?, 32);
+    assert_eq!(buf[..10], data[19990..20000]);
+    assert_eq!(buf[10..], [0; 22]);
+
+    // sparse file
+    root.create("sparse", VfsNodeType::File)?;
+    let sparse = root.clone().lookup("sparse")?;
+    assert_eq!(sparse.write_at(1 << 20, b"end")?, 3);
+    assert_eq!(sparse.get_attr()?.size(), (1 << 20) + 3);
+    assert!(sparse.get_attr()?.blocks() <= 4 * 2); // data and 2 indirect blocks
+    assert_eq!(sparse.read_at(4096, &mut buf)?, 32);
+    assert_eq!(buf, [0; 32]);
+
+    // directories
+    root.create("foo", VfsNodeType::Dir)?;
+    root.create("foo/bar", VfsNodeType::Dir)?;
+    root.create("foo/bar/f2", VfsNodeType::File)?;
+    let foo = root.clone().lookup(".//foo/")?;
+    assert_eq!(foo.get_attr()?.perm().mode(), 0o755);
+    assert_eq!(foo.read_at(0, &mut buf).err(), Some(VfsError::IsADirectory));
+    assert_eq!(
+        root.create("foo", VfsNodeType::File).err(),
+        Some(VfsError::AlreadyExists)
+    );
+    assert_eq!(
+        root.create("f1/f3", VfsNodeType::File).err(),
+        Some(VfsError::NotADirectory)
+    );
+    assert_eq!(entries(fs, "foo/bar")?, [".", "..", "f2"]);
+    let bar = foo.clone().lookup("bar")?;
+    assert_eq!(ino(&bar.parent().unwrap()), ino(&foo));
+    assert!(bar.lookup("../../f1").is_ok());
+
+    // many entries that take more than one block
+    for i in 0..100 {
+        root.create(&format!("foo/file-with-a-long-name-{i}"), VfsNodeType::File)?;
+    }
+    assert!(foo.get_attr()?.size() > 1024);
+    assert_eq!(entries(fs, "foo")?.len(), 103);
+    for i in 0..100 {
+        root.remove(&format!("foo/file-with-a-long-name-{i}"))?;
+    }
+    assert_eq!(entries(fs, "foo")?, [".", "..", "bar"]);
+    Ok(())
+}
+
+fn test_links(fs: &Ext2FileSystem) -> VfsResult {
+    let root = fs.root_dir();
+    let mut buf = [0; 128];
+    let long_target = "../".repeat(30) + "f1";
+
+    root.symlink("foo/l1", "../f1")?;
+    root.symlink("l2", &long_target)?;
+    assert_eq!(
+        root.symlink("foo/l1", "f2").err(),
+        Some(VfsError::AlreadyExists)
+    );
+    let l1 = root.clone().lookup("foo/l1")?;
+    assert_eq!(l1.get_attr()?.file_type(), VfsNodeType::SymLink);
+    assert_eq!(l1.get_attr()?.size(), 5);
+    assert_eq!(l1.readlink(&mut buf)?, 5);
+    assert_eq!(&buf[..5], b"../f1");
+    let l2 = root.clone().lookup("l2")?;
+    assert_eq!(l2.readlink(&mut buf)?, long_target.len());
+    assert_eq!(&buf[..long_target.len()], long_target.as_bytes());
+    assert_eq!(l2.read_at(0, &mut buf).err(), Some(VfsError::InvalidInput));
+
+    let f1 = root.clone().lookup("f1")?;
+    root.link("foo/bar/h1", &f1)?;
+    let h1 = root.clone().lookup("foo/bar/h1")?;
+    assert_eq!(h1.write_at(0, b"hard")?, 4);
+    assert_eq!(f1.read_at(0, &mut buf)?, 32);
+    assert_eq!(&buf[..4], b"hard");
+    assert_eq!(
+        root.link("f3", &root.clone().lookup("foo")?).err(),
+        Some(VfsError::PermissionDenied)
+    );
+    assert_eq!(root.link("big", &f1).err(), Some(VfsError::AlreadyExists));
+
+    // the inode is kept until the last link and node are dropped
+    root.remove("f1")?;
+    root.remove("foo/bar/h1")?;
+    assert_eq!(root.clone().lookup("f1").err(), Some(VfsError::NotFound));
+    assert_eq!(h1.read_at(0, &mut buf)?, 32);
+    assert_eq!(&buf[..4], b"hard");
+    root.remove("foo/l1")?;
+    root.remove("l2")?;
+    Ok(())
+}
+
+fn test_rename(fs: &Ext2FileSystem) -> VfsResult {
+    let root = fs.root_dir();
+    let mut buf = [0; 8];
+
+    root.create("r1", VfsNodeType::File)?;
+    root.clone().lookup("r1")?.write_at(0, b"rename")?;
+    root.rename("r1", "foo/bar/r2")?;
+    assert_eq!(root.clone().lookup("r1").err(), Some(VfsError::NotFound));
+    assert_eq!(root.clone().lookup("foo/bar/r2")?.read_at(0, &mut buf)?, 6);
+
+    // replace an existing file
+    root.rename("foo/bar/r2", "foo/bar/f2")?;
+    assert_eq!(entries(fs, "foo/bar")?, [".", "..", "f2"]);
+    assert_eq!(root.clone().lookup("foo/bar/f2")?.read_at(0, &mut buf)?, 6);
+
+    // move a directory
+    root.rename("foo/bar", "bar")?;
+    assert!(root.clone().lookup("bar/f2").is_ok());
+    let bar = root.clone().lookup("bar")?;
+    assert_eq!(ino(&bar.parent().unwrap()), ino(&root));
+    assert_eq!(
+        root.rename("bar", "bar/baz").err(),
+        Some(VfsError::InvalidInput)
+    );
+    assert_eq!(
+        root.rename("bar", "big").err(),
+        Some(VfsError::NotADirectory)
+    );
+    assert_eq!(
+        root.rename("big", "foo").err(),
+        Some(VfsError::IsADirectory)
+    );
+    assert_eq!(
+        root.rename("foo", "bar").err(),
+        Some(VfsError::DirectoryNotEmpty)
+    );
+    root.rename("bar", "foo")?;
+    assert_eq!(entries(fs, "foo")?, [".", "..", "f2"]);
+    Ok(())
+}
+
+fn test_remove(fs: &Ext2FileSystem) -> VfsResult {
+    let root = fs.root_dir();
+    assert_eq!(root.remove("f3").err(), Some(VfsError::NotFound));
+    assert_eq!(root.remove("foo").err(), Some(VfsError::DirectoryNotEmpty));
+    assert_eq!(root.remove("foo/..").err(), Some(VfsError::InvalidInput));
+    root.remove("foo/f2")?;
+    root.remove("./foo")?;
+    root.remove("big")?;
+    root.remove("sparse")?;
+    assert_eq!(entries(fs, "")?, [".", "..", "lost+found"]);
+    Ok(())
+}
+
+#[test]
+fn test_ext2() {
+    let disk = MemDisk::new(DISK_SIZE);
+    let fs = Ext2FileSystem::format(disk.clone()).unwrap();
+    assert!(fs.is_writable());
+    assert_eq!(entries(&fs, "/").unwrap(), [".", "..", "lost+found"]);
+    let root = fs.root_dir();
+    assert!(root.get_attr().unwrap().is_dir());
+    assert!(root.parent().is_none());
+
+    test_file_ops(&fs).unwrap();
+    test_links(&fs).unwrap();
+
+    // the data is persisted after the filesystem is opened again
+    drop(root);
+    drop(fs);
+    let fs = Ext2FileSystem::new(disk.clone()).unwrap();
+    let mut buf = [0; 3];
+    let sparse = fs.root_dir().lookup("sparse").unwrap();
+    assert_eq!(sparse.read_at(1 << 20, &mut buf), Ok(3));
+    assert_eq!(&buf, b"end");
+    drop(sparse);
+
+    test_rename(&fs).unwrap();
+    test_remove(&fs).unwrap();
+}
+
+#[test]
+fn test_invalid_disk() {
+    let disk = MemDisk::new(DISK_SIZE);
+    assert_eq!(Ext2FileSystem::new(disk).err(), Some(VfsError::InvalidData));
+    let disk = MemDisk::new(16 * 1024);
+    assert_eq!(
+        Ext2FileSystem::format(disk).err(),
+        Some(VfsError::InvalidInput)
+    );
+}
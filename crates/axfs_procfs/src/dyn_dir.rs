@@ -0,0 +1,134 @@
+use alloc::sync::{Arc, Weak};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsError, VfsResult};
+use spin::RwLock;
+
+use crate::dir::{split_path, DirNode};
+
+type ListFn = Box<dyn Fn() -> Vec<String> + Send + Sync>;
+type GenerateFn = Box<dyn Fn(&str) -> Option<VfsNodeRef> + Send + Sync>;
+
+/// A directory whose entries are generated when it is accessed.
+///
+/// The names of entries are listed by a closure, and each entry is generated
+/// by another closure from its name. It is useful for the entries that come
+/// and go, such as one directory per task.
+pub struct DynDirNode {
+    this: Weak<DynDirNode>,
+    parent: RwLock<Weak<dyn VfsNodeOps>>,
+    list: ListFn,
+    generate: GenerateFn,
+}
+
+impl DynDirNode {
+    /// Create a new dynamic directory whose parent is `parent`.
+    ///
+    /// `list` returns the names of all entries, and `generate` returns the
+    /// entry with the given name, or `None` if it does not exist.
+    pub fn new<L, G>(parent: Option<&VfsNodeRef>, list: L, generate: G) -> Arc<Self>
+    where
+        L: Fn() -> Vec<String> + Send + Sync + 'static,
+        G: Fn(&str) -> Option<VfsNodeRef> + Send + Sync + 'static,
+    {
+        let parent = parent.map_or(Weak::<Self>::new() as _, Arc::downgrade);
+        Arc::new_cyclic(|this| Self {
+            this: this.clone(),
+            parent: RwLock::new(parent),
+            list: Box::new(list),
+            generate: Box::new(generate),
+        })
+    }
+
+    /// Generates the entry with the given name.
+    ///
+    /// If the entry is a [`DirNode`], its parent is set to this directory.
+    fn entry(&self, name: &str) -> Option<VfsNodeRef> {
+        let node = (self.generate)(name)?;
+        if let Some(dir) = node.as_any().downcast_ref::<DirNode>() {
+            let this = self.this.upgrade().map(|this| this as VfsNodeRef);
+            dir.set_parent(this.as_ref());
+        }
+        Some(node)
+    }
+}
+
+impl VfsNodeOps for DynDirNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new_dir(4096, 0))
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        self.parent.read().upgrade()
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => Ok(self.clone() as VfsNodeRef),
+            ".." => self.parent().ok_or(VfsError::NotFound),
+            _ => self.entry(name).ok_or(VfsError::NotFound),
+        }?;
+
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        let names = (self.list)();
+        let mut children = names.iter().skip(start_idx.max(2) - 2);
+        for (i, ent) in dirents.iter_mut().enumerate() {
+            match i + start_idx {
+                0 => *ent = VfsDirEntry::new(".", VfsNodeType::Dir),
+                1 => *ent = VfsDirEntry::new("..", VfsNodeType::Dir),
+                _ => {
+                    if let Some(name) = children.next() {
+                        let ty = match self.entry(name) {
+                            Some(node) => node.get_attr()?.file_type(),
+                            None => VfsNodeType::Dir, // removed after listing
+                        };
+                        *ent = VfsDirEntry::new(name, ty);
+                    } else {
+                        return Ok(i);
+                    }
+                }
+            }
+        }
+        Ok(dirents.len())
+    }
+
+    fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
+        log::debug!("create {:?} at procfs: {}", ty, path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.create(rest, ty),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.create(rest, ty),
+                _ => self.entry(name).ok_or(VfsError::NotFound)?.create(rest, ty),
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Ok(()) // already exists
+        } else {
+            Err(VfsError::PermissionDenied) // do not support to create nodes dynamically
+        }
+    }
+
+    fn remove(&self, path: &str) -> VfsResult {
+        log::debug!("remove at procfs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.remove(rest),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.remove(rest),
+                _ => self.entry(name).ok_or(VfsError::NotFound)?.remove(rest),
+            }
+        } else {
+            Err(VfsError::PermissionDenied) // do not support to remove nodes dynamically
+        }
+    }
+
+    axfs_vfs::impl_vfs_dir_default! {}
+}
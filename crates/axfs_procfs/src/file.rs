@@ -0,0 +1,64 @@
+use alloc::{boxed::Box, string::String};
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+use spin::Mutex;
+
+/// A read-only file whose content is generated when it is read.
+///
+/// The content is generated on the read at offset 0, and the following reads
+/// at other offsets return the same snapshot, so that a file read in chunks
+/// is consistent.
+///
+/// Like the files in Linux procfs, its size is always reported as 0.
+pub struct ProcFile {
+    generate: Box<dyn Fn() -> String + Send + Sync>,
+    snapshot: Mutex<Option<String>>,
+}
+
+impl ProcFile {
+    /// Create a new file whose content is generated by `generate`.
+    pub fn new<F>(generate: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self {
+            generate: Box::new(generate),
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    /// Create a new file with the fixed `content`.
+    pub fn new_static(content: &'static str) -> Self {
+        Self::new(|| content.into())
+    }
+
+    /// Generates the current content of the file.
+    pub fn content(&self) -> String {
+        (self.generate)()
+    }
+}
+
+impl VfsNodeOps for ProcFile {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(0o444),
+            VfsNodeType::File,
+            0,
+            0,
+        ))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut snapshot = self.snapshot.lock();
+        if offset == 0 || snapshot.is_none() {
+            *snapshot = Some(self.content());
+        }
+        let content = snapshot.as_ref().unwrap();
+        let start = content.len().min(offset as usize);
+        let end = content.len().min(offset as usize + buf.len());
+        let src = &content.as_bytes()[start..end];
+        buf[..src.len()].copy_from_slice(src);
+        Ok(src.len())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
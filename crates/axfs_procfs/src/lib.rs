@@ -0,0 +1,77 @@
+//! Process information pseudo-filesystem used by [ArceOS](https://github.com/rcore-os/arceos).
+//!
+//! The contents of its files are not stored anywhere, but generated by the
+//! given closures every time they are read from the beginning, so they always
+//! reflect the current kernel state. Directories can also be generated dynamically (e.g.,
+//! one directory for each task) by [`DynDirNode`].
+//!
+//! The implementation is based on [`axfs_vfs`].
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+mod dir;
+mod dyn_dir;
+mod file;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::dir::DirNode;
+pub use self::dyn_dir::DynDirNode;
+pub use self::file::ProcFile;
+
+use alloc::sync::Arc;
+use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use spin::RwLock;
+
+/// A process information filesystem that implements [`axfs_vfs::VfsOps`].
+pub struct ProcFileSystem {
+    /// Parent of the mount point, kept alive as the root only holds a weak
+    /// reference to it.
+    parent: RwLock<Option<VfsNodeRef>>,
+    root: Arc<DirNode>,
+}
+
+impl ProcFileSystem {
+    /// Create a new instance.
+    pub fn new() -> Self {
+        Self {
+            parent: RwLock::new(None),
+            root: DirNode::new(None),
+        }
+    }
+
+    /// Create a subdirectory at the root directory.
+    pub fn mkdir(&self, name: &'static str) -> Arc<DirNode> {
+        self.root.mkdir(name)
+    }
+
+    /// Add a node to the root directory.
+    ///
+    /// The node must implement [`axfs_vfs::VfsNodeOps`], and be wrapped in [`Arc`].
+    pub fn add(&self, name: &'static str, node: VfsNodeRef) {
+        self.root.add(name, node);
+    }
+}
+
+impl VfsOps for ProcFileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        // it may be remounted somewhere else after unmounting
+        let mut parent = self.parent.write();
+        *parent = mount_point.parent();
+        self.root.set_parent(parent.as_ref());
+        Ok(())
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+}
+
+impl Default for ProcFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axfs_vfs::{VfsDirEntry, VfsError, VfsNodeRef, VfsNodeType, VfsResult};
+
+use crate::*;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+static NUM_TASKS: AtomicUsize = AtomicUsize::new(2);
+
+fn read_to_string(node: &VfsNodeRef) -> VfsResult<String> {
+    let mut buf = [0; 64];
+    let mut content = Vec::new();
+    loop {
+        let n = node.read_at(content.len() as _, &mut buf[..7])?;
+        if n == 0 {
+            break;
+        }
+        content.extend_from_slice(&buf[..n]);
+    }
+    Ok(String::from_utf8(content).unwrap())
+}
+
+fn entry_names(dir: &VfsNodeRef) -> VfsResult<Vec<String>> {
+    let mut dirents: Vec<_> = (0..8).map(|_| VfsDirEntry::default()).collect();
+    let n = dir.read_dir(0, &mut dirents)?;
+    Ok(dirents[..n]
+        .iter()
+        .map(|e| String::from_utf8(e.name_as_bytes().to_vec()).unwrap())
+        .collect())
+}
+
+fn test_procfs_ops(procfs: &ProcFileSystem) -> VfsResult {
+    let mut buf = [0; 16];
+    let root = procfs.root_dir();
+    assert!(root.get_attr()?.is_dir());
+    assert_eq!(
+        root.clone().lookup("urandom").err(),
+        Some(VfsError::NotFound)
+    );
+
+    let version = root.clone().lookup("version")?;
+    assert_eq!(version.get_attr()?.file_type(), VfsNodeType::File);
+    assert_eq!(version.get_attr()?.size(), 0);
+    assert!(!version.get_attr()?.perm().owner_writable());
+    assert_eq!(read_to_string(&version)?, "ArceOS\n");
+    assert_eq!(version.read_at(3, &mut buf)?, 4);
+    assert_eq!(&buf[..4], b"eOS\n");
+    assert_eq!(version.read_at(100, &mut buf)?, 0);
+
+    // generated on every read
+    let counter = root.clone().lookup("./sys//counter")?;
+    assert_eq!(counter.read_at(0, &mut buf)?, 1);
+    assert_eq!(&buf[..1], b"1");
+    assert_eq!(counter.read_at(0, &mut buf)?, 1);
+    assert_eq!(&buf[..1], b"2");
+    // but only at offset 0, so that a file read in chunks is consistent
+    assert_eq!(counter.read_at(1, &mut buf)?, 0);
+    assert_eq!(counter.read_at(0, &mut buf)?, 1);
+    assert_eq!(&buf[..1], b"3");
+    assert_eq!(COUNTER.load(Ordering::SeqCst), 3);
+
+    assert_eq!(
+        root.create("sys/new", VfsNodeType::File).err(),
+        Some(VfsError::PermissionDenied)
+    );
+    assert_eq!(
+        root.remove("version").err(),
+        Some(VfsError::PermissionDenied)
+    );
+    Ok(())
+}
+
+fn test_dyn_dir(procfs: &ProcFileSystem) -> VfsResult {
+    let root = procfs.root_dir();
+    let tasks = root.clone().lookup("tasks")?;
+    assert!(tasks.get_attr()?.is_dir());
+    assert_eq!(entry_names(&tasks)?, [".", "..", "0", "1"]);
+
+    let status = root.clone().lookup("tasks/1/status")?;
+    assert_eq!(read_to_string(&status)?, "task 1\n");
+    assert_eq!(
+        read_to_string(&root.clone().lookup("tasks/0/../1/./status")?)?,
+        "task 1\n"
+    );
+    assert!(Arc::ptr_eq(&root.clone().lookup("tasks/1/../..")?, &root));
+    assert_eq!(
+        root.clone().lookup("tasks/2").err(),
+        Some(VfsError::NotFound)
+    );
+
+    NUM_TASKS.store(3, Ordering::SeqCst);
+    assert_eq!(entry_names(&tasks)?, [".", "..", "0", "1", "2"]);
+    assert_eq!(
+        read_to_string(&root.clone().lookup("tasks/2/status")?)?,
+        "task 2\n"
+    );
+    assert_eq!(
+        tasks.create("3", VfsNodeType::Dir).err(),
+        Some(VfsError::PermissionDenied)
+    );
+    assert_eq!(tasks.remove("1").err(), Some(VfsError::PermissionDenied));
+    Ok(())
+}
+
+fn test_remount(procfs: &ProcFileSystem) -> VfsResult {
+    let root = procfs.root_dir();
+    for _ in 0..2 {
+        // `..` of the root is the parent of the current mount point
+        let parent: VfsNodeRef = DirNode::new(None);
+        procfs.mount("/proc", DirNode::new(Some(&parent)))?;
+        assert!(Arc::ptr_eq(&root.clone().lookup("..")?, &parent));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_procfs() {
+    // .
+    // ├── sys
+    // │   └── counter
+    // ├── tasks
+    // │   ├── 0
+    // │   │   └── status
+    // │   └── 1
+    // │       └── status
+    // └── version
+
+    let procfs = ProcFileSystem::new();
+    procfs.add("version", Arc::new(ProcFile::new_static("ArceOS\n")));
+    let sys = procfs.mkdir("sys");
+    sys.add(
+        "counter",
+        Arc::new(ProcFile::new(|| {
+            (COUNTER.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+        })),
+    );
+    let root = procfs.root_dir();
+    let tasks = DynDirNode::new(
+        Some(&root),
+        || {
+            (0..NUM_TASKS.load(Ordering::SeqCst))
+                .map(|id| id.to_string())
+                .collect()
+        },
+        |name| {
+            let id: usize = name.parse().ok()?;
+            if id >= NUM_TASKS.load(Ordering::SeqCst) {
+                return None;
+            }
+            let dir = DirNode::new(None);
+            dir.add(
+                "status",
+                Arc::new(ProcFile::new(move || format!("task {id}\n"))),
+            );
+            Some(dir)
+        },
+    );
+    procfs.add("tasks", tasks);
+
+    test_procfs_ops(&procfs).expect("test_procfs_ops failed");
+    test_dyn_dir(&procfs).expect("test_dyn_dir failed");
+    test_remount(&procfs).expect("test_remount failed");
+}
@@ -88,6 +88,58 @@ fn test_get_parent(devfs: &RamFileSystem) -> VfsResult {
     Ok(())
 }
 
+fn test_links(ramfs: &RamFileSystem) -> VfsResult {
+    let root = ramfs.root_dir();
+    let mut buf = [0; 16];
+
+    root.symlink("foo/l1", "../f1")?;
+    root.symlink("l2", "/not/exist")?;
+    assert_eq!(
+        root.symlink("foo/l1", "f2").err(),
+        Some(VfsError::AlreadyExists)
+    );
+    assert_eq!(
+        root.symlink("f1/l3", "f2").err(),
+        Some(VfsError::NotADirectory)
+    );
+
+    let l1 = root.clone().lookup("foo/l1")?;
+    assert_eq!(l1.get_attr()?.file_type(), VfsNodeType::SymLink);
+    assert_eq!(l1.get_attr()?.size(), 5);
+    assert_eq!(l1.readlink(&mut buf)?, 5);
+    assert_eq!(&buf[..5], b"../f1");
+    assert_eq!(l1.readlink(&mut buf[..2])?, 2);
+    assert_eq!(&buf[..2], b"..");
+    assert_eq!(l1.read_at(0, &mut buf).err(), Some(VfsError::InvalidInput));
+    assert_eq!(
+        root.clone().lookup("f1")?.readlink(&mut buf).err(),
+        Some(VfsError::InvalidInput)
+    );
+
+    let f1 = root.clone().lookup("f1")?;
+    root.link("foo/bar/h1", &f1)?;
+    let h1 = root.clone().lookup("foo/bar/h1")?;
+    assert!(Arc::ptr_eq(&f1, &h1));
+    assert_eq!(h1.write_at(0, b"hard")?, 4);
+    assert_eq!(f1.read_at(0, &mut buf)?, buf.len());
+    assert_eq!(&buf[..4], b"hard");
+    assert_eq!(
+        root.link("f3", &root.clone().lookup("foo")?).err(),
+        Some(VfsError::PermissionDenied)
+    );
+    assert_eq!(root.link("f2", &f1).err(), Some(VfsError::AlreadyExists));
+
+    let mut entries = ramfs.root_dir_node().get_entries();
+    entries.sort();
+    assert_eq!(entries, ["f1", "f2", "foo", "l2"]);
+
+    root.remove("foo/l1")?;
+    root.remove("l2")?;
+    root.remove("foo/bar/h1")?;
+    assert!(root.lookup("f1").is_ok());
+    Ok(())
+}
+
 #[test]
 fn test_ramfs() {
     // .
@@ -117,6 +169,7 @@ fn test_ramfs() {
 
     test_ramfs_ops(&ramfs).unwrap();
     test_get_parent(&ramfs).unwrap();
+    test_links(&ramfs).unwrap();
 
     let root = ramfs.root_dir();
     assert_eq!(root.remove("f1"), Ok(()));
@@ -229,6 +229,17 @@ impl VfsNodeAttr {
         }
     }
 
+    /// Creates a new `VfsNodeAttr` for a symbolic link, whose size is the
+    /// length of the target path.
+    pub const fn new_symlink(size: u64) -> Self {
+        Self {
+            mode: VfsNodePerm::from_bits_truncate(0o777),
+            ty: VfsNodeType::SymLink,
+            size,
+            blocks: 0,
+        }
+    }
+
     /// Returns the size of the node.
     pub const fn size(&self) -> u64 {
         self.size
@@ -263,6 +274,11 @@ impl VfsNodeAttr {
     pub const fn is_dir(&self) -> bool {
         self.ty.is_dir()
     }
+
+    /// Whether the node is a symbolic link.
+    pub const fn is_symlink(&self) -> bool {
+        self.ty.is_symlink()
+    }
 }
 
 impl VfsDirEntry {
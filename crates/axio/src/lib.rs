@@ -257,4 +257,10 @@ pub struct PollState {
     pub readable: bool,
     /// Object can be writen now.
     pub writable: bool,
+    /// The other end of the object is closed, e.g., a pipe without writers
+    /// or a stream socket closed in both directions.
+    pub hangup: bool,
+    /// An error is pending on the object, e.g., a pipe without readers or a
+    /// reset connection.
+    pub error: bool,
 }
@@ -0,0 +1,80 @@
+use crate::{func, Frame};
+
+/// The type of the car in motion commands, which must match the
+/// configuration of the controller.
+pub const CAR_TYPE: u8 = 0x01;
+
+/// The maximum speed in motion commands, in percent.
+const MAX_SPEED: u8 = 100;
+
+/// The directions that the car moves in without rotating.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Forward.
+    Forward = 1,
+    /// Backward.
+    Backward = 2,
+    /// Sideways to the left, for cars with mecanum wheels.
+    Left = 3,
+    /// Sideways to the right, for cars with mecanum wheels.
+    Right = 4,
+}
+
+/// The directions that the car rotates in place.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Counterclockwise.
+    Left = 5,
+    /// Clockwise.
+    Right = 6,
+}
+
+/// Commands to the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Moves in the direction, at the speed in percent (at most 100).
+    Drive(Direction, u8),
+    /// Stops the motors.
+    Stop,
+    /// Rotates in place, at the speed in percent (at most 100).
+    Turn(Rotation, u8),
+    /// Turns on the buzzer for the time in milliseconds. 0 turns it off, and
+    /// 1 keeps it on.
+    Buzzer(u16),
+    /// Sets the PID parameters of the yaw angle, in thousandths, which keep
+    /// the car moving straight.
+    SetYawPid {
+        /// The proportional gain.
+        kp: u16,
+        /// The integral gain.
+        ki: u16,
+        /// The derivative gain.
+        kd: u16,
+    },
+}
+
+impl Command {
+    /// Builds the frame of the command.
+    pub fn to_frame(&self) -> Frame {
+        let frame = match *self {
+            Self::Drive(dir, speed) => car_run(dir as u8, speed),
+            Self::Stop => car_run(0, 0),
+            Self::Turn(rot, speed) => car_run(rot as u8, speed),
+            Self::Buzzer(ms) => Frame::command(func::BUZZER, &ms.to_le_bytes()),
+            Self::SetYawPid { kp, ki, kd } => {
+                let (kp, ki, kd) = (kp.to_le_bytes(), ki.to_le_bytes(), kd.to_le_bytes());
+                // the last byte is 0 not to save the parameters to flash
+                let payload = [kp[0], kp[1], ki[0], ki[1], kd[0], kd[1], 0];
+                Frame::command(func::SET_YAW_PID, &payload)
+            }
+        };
+        frame.unwrap()
+    }
+}
+
+fn car_run(state: u8, speed: u8) -> Result<Frame, crate::FrameError> {
+    let speed = (speed.min(MAX_SPEED) as i16).to_le_bytes();
+    Frame::command(func::CAR_RUN, &[CAR_TYPE, state, speed[0], speed[1]])
+}
@@ -0,0 +1,188 @@
+use core::fmt;
+
+use crate::{DEVICE_COMMAND, DEVICE_REPLY, HEADER};
+
+/// The maximum length of a frame in bytes.
+pub const MAX_FRAME_LEN: usize = 2 + u8::MAX as usize;
+/// The maximum length of the payload of a frame in bytes.
+pub const MAX_PAYLOAD_LEN: usize = MAX_FRAME_LEN - 5;
+
+/// Offsets of the fields in a frame.
+const DEVICE: usize = 1;
+const LENGTH: usize = 2;
+const FUNC: usize = 3;
+const PAYLOAD: usize = 4;
+
+/// The error type of building or parsing frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The payload is longer than [`MAX_PAYLOAD_LEN`].
+    TooLong,
+    /// The length byte is less than 3.
+    InvalidLength,
+    /// The checksum doesn't match the content.
+    Checksum,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::TooLong => "payload too long",
+            Self::InvalidLength => "invalid length",
+            Self::Checksum => "checksum mismatch",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Computes the checksum of `bytes`, i.e., the sum of them modulo 256.
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, &b| sum.wrapping_add(b))
+}
+
+/// A complete frame, including the header and the checksum.
+#[derive(Clone)]
+pub struct Frame {
+    buf: [u8; MAX_FRAME_LEN],
+}
+
+impl Frame {
+    /// Builds a frame with the device byte, function ID and payload, and
+    /// computes the length and checksum.
+    pub fn new(device: u8, func: u8, payload: &[u8]) -> Result<Self, FrameError> {
+        if payload.len() > MAX_PAYLOAD_LEN {
+            return Err(FrameError::TooLong);
+        }
+        let mut buf = [0; MAX_FRAME_LEN];
+        buf[0] = HEADER;
+        buf[DEVICE] = device;
+        buf[LENGTH] = (payload.len() + 3) as u8;
+        buf[FUNC] = func;
+        buf[PAYLOAD..PAYLOAD + payload.len()].copy_from_slice(payload);
+        let end = PAYLOAD + payload.len();
+        buf[end] = checksum(&buf[LENGTH..end]);
+        Ok(Self { buf })
+    }
+
+    /// Builds a frame sent to the controller.
+    pub fn command(func: u8, payload: &[u8]) -> Result<Self, FrameError> {
+        Self::new(DEVICE_COMMAND, func, payload)
+    }
+
+    /// Builds a frame sent by the controller.
+    pub fn reply(func: u8, payload: &[u8]) -> Result<Self, FrameError> {
+        Self::new(DEVICE_REPLY, func, payload)
+    }
+
+    /// Returns the device byte, i.e., [`DEVICE_COMMAND`] or [`DEVICE_REPLY`].
+    pub const fn device(&self) -> u8 {
+        self.buf[DEVICE]
+    }
+
+    /// Returns the function ID.
+    pub const fn func(&self) -> u8 {
+        self.buf[FUNC]
+    }
+
+    /// Returns the payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.buf[PAYLOAD..self.len() - 1]
+    }
+
+    /// Returns the checksum.
+    pub const fn checksum(&self) -> u8 {
+        self.buf[self.len() - 1]
+    }
+
+    /// Returns the length of the whole frame in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> usize {
+        self.buf[LENGTH] as usize + 2
+    }
+
+    /// Returns the bytes of the whole frame to send.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len()]
+    }
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Frame")
+            .field("device", &self.device())
+            .field("func", &self.func())
+            .field("payload", &self.payload())
+            .finish()
+    }
+}
+
+impl PartialEq for Frame {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for Frame {}
+
+/// A parser that extracts frames from a stream of bytes.
+///
+/// Bytes before a header, e.g., the rest of a corrupted frame, are skipped.
+pub struct Parser {
+    frame: Frame,
+    pos: usize,
+}
+
+impl Parser {
+    /// Creates a new parser.
+    pub const fn new() -> Self {
+        Self {
+            frame: Frame {
+                buf: [0; MAX_FRAME_LEN],
+            },
+            pos: 0,
+        }
+    }
+
+    /// Pushes a received byte. Returns the frame if it's the last byte of a
+    /// frame, or an error if the frame is invalid.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Frame, FrameError>> {
+        let buf = &mut self.frame.buf;
+        match self.pos {
+            0 if byte != HEADER => return None,
+            DEVICE if byte != DEVICE_COMMAND && byte != DEVICE_REPLY => {
+                // may be the header of the next frame
+                self.pos = (byte == HEADER) as usize;
+                return None;
+            }
+            LENGTH if byte < 3 => {
+                self.pos = 0;
+                return Some(Err(FrameError::InvalidLength));
+            }
+            _ => {}
+        }
+        buf[self.pos] = byte;
+        self.pos += 1;
+        if self.pos <= LENGTH || self.pos < self.frame.len() {
+            return None;
+        }
+
+        self.pos = 0;
+        let frame = &self.frame;
+        if checksum(&frame.buf[LENGTH..frame.len() - 1]) == frame.checksum() {
+            Some(Ok(frame.clone()))
+        } else {
+            Some(Err(FrameError::Checksum))
+        }
+    }
+
+    /// Drops the bytes of the incomplete frame.
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,67 @@
+//! Frames, commands and replies of the serial protocol of robot chassis
+//! (motor) controllers.
+//!
+//! Every frame on the serial line has the following format:
+//!
+//! | Header | Device | Length | Function | Payload | Checksum |
+//! |-|-|-|-|-|-|
+//! | `0xFF` | `0xFC` (commands) or `0xFB` (replies) | 1 byte | 1 byte | 0 ~ 252 bytes | 1 byte |
+//!
+//! The length counts the bytes from itself to the checksum, i.e., the length
+//! of the payload plus 3. The checksum is the sum of the bytes from the length
+//! to the end of the payload, modulo 256. Multi-byte values in the payload are
+//! in little endian.
+//!
+//! # Examples
+//!
+//! ```
+//! use chassis_protocol::{Command, Direction};
+//!
+//! let frame = Command::Drive(Direction::Forward, 100).to_frame();
+//! assert_eq!(
+//!     frame.as_bytes(),
+//!     [0xff, 0xfc, 0x07, 0x11, 0x01, 0x01, 0x64, 0x00, 0x7e]
+//! );
+//! ```
+//!
+//! [`Chassis`] sends commands and receives replies over any serial port that
+//! implements [`axio::Read`] and [`axio::Write`], e.g., a `/dev/ttyS*` file
+//! opened by an `axstd` app.
+
+#![cfg_attr(not(test), no_std)]
+
+mod command;
+mod frame;
+mod reply;
+mod transport;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::command::{Command, Direction, Rotation, CAR_TYPE};
+pub use self::frame::{checksum, Frame, FrameError, Parser, MAX_FRAME_LEN, MAX_PAYLOAD_LEN};
+pub use self::reply::Reply;
+pub use self::transport::Chassis;
+
+/// The first byte of all frames.
+pub const HEADER: u8 = 0xff;
+/// The device byte of the frames sent to the controller.
+pub const DEVICE_COMMAND: u8 = 0xfc;
+/// The device byte of the frames sent by the controller.
+pub const DEVICE_REPLY: u8 = 0xfb;
+
+/// Function IDs, i.e., the types of frames.
+pub mod func {
+    /// Turns on the buzzer for a while.
+    pub const BUZZER: u8 = 0x02;
+    /// Reports the speed of the car and the battery voltage.
+    pub const REPORT_SPEED: u8 = 0x0a;
+    /// Reports the attitude angles of the car.
+    pub const REPORT_ATTITUDE: u8 = 0x0c;
+    /// Reports the counts of the encoders of the 4 motors.
+    pub const REPORT_ENCODER: u8 = 0x0d;
+    /// Moves the car in a direction.
+    pub const CAR_RUN: u8 = 0x11;
+    /// Sets the PID parameters of the yaw angle.
+    pub const SET_YAW_PID: u8 = 0x14;
+}
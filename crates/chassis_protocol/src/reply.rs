@@ -0,0 +1,57 @@
+use crate::{func, Frame};
+
+/// Replies (reports) from the controller.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// The speed of the car.
+    Speed {
+        /// The forward speed in mm/s.
+        vx: i16,
+        /// The speed to the left in mm/s.
+        vy: i16,
+        /// The angular speed in mrad/s, counterclockwise.
+        vz: i16,
+        /// The voltage of the battery in 0.1 V.
+        battery: u8,
+    },
+    /// The attitude angles of the car, in 0.1 mrad.
+    Attitude {
+        /// The roll angle.
+        roll: i16,
+        /// The pitch angle.
+        pitch: i16,
+        /// The yaw angle.
+        yaw: i16,
+    },
+    /// The counts of the encoders of the 4 motors.
+    Encoder([i32; 4]),
+    /// A frame of other functions, or with an unexpected payload length.
+    Other(Frame),
+}
+
+impl Reply {
+    /// Parses a frame from the controller.
+    pub fn parse(frame: &Frame) -> Self {
+        let p = frame.payload();
+        let i16_at = |i: usize| i16::from_le_bytes([p[i], p[i + 1]]);
+        let i32_at = |i: usize| i32::from_le_bytes([p[i], p[i + 1], p[i + 2], p[i + 3]]);
+        match (frame.func(), p.len()) {
+            (func::REPORT_SPEED, 7) => Self::Speed {
+                vx: i16_at(0),
+                vy: i16_at(2),
+                vz: i16_at(4),
+                battery: p[6],
+            },
+            (func::REPORT_ATTITUDE, 6) => Self::Attitude {
+                roll: i16_at(0),
+                pitch: i16_at(2),
+                yaw: i16_at(4),
+            },
+            (func::REPORT_ENCODER, 16) => {
+                Self::Encoder([i32_at(0), i32_at(4), i32_at(8), i32_at(12)])
+            }
+            _ => Self::Other(frame.clone()),
+        }
+    }
+}
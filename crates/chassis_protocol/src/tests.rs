@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+#[test]
+fn test_commands() {
+    // the frames sent by hand in the apps
+    let cases = [
+        (
+            Command::Drive(Direction::Forward, 100),
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x01, 0x64, 0x00, 0x7e][..],
+        ),
+        (
+            Command::Drive(Direction::Forward, 50),
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x01, 0x32, 0x00, 0x4c],
+        ),
+        (
+            Command::Drive(Direction::Backward, 100),
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x02, 0x64, 0x00, 0x7f],
+        ),
+        (
+            Command::Stop,
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x00, 0x00, 0x00, 0x19],
+        ),
+        (
+            Command::Turn(Rotation::Left, 100),
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x05, 0x64, 0x00, 0x82],
+        ),
+        (
+            Command::Turn(Rotation::Right, 100),
+            &[0xff, 0xfc, 0x07, 0x11, 0x01, 0x06, 0x64, 0x00, 0x83],
+        ),
+        (
+            Command::Buzzer(96),
+            &[0xff, 0xfc, 0x05, 0x02, 0x60, 0x00, 0x67],
+        ),
+        (
+            Command::SetYawPid {
+                kp: 32,
+                ki: 32,
+                kd: 32,
+            },
+            &[
+                0xff, 0xfc, 0x0a, 0x14, 0x20, 0x00, 0x20, 0x00, 0x20, 0x00, 0x00, 0x7e,
+            ],
+        ),
+    ];
+    for (cmd, bytes) in cases {
+        assert_eq!(cmd.to_frame().as_bytes(), bytes, "{:?}", cmd);
+    }
+
+    // the speed is capped
+    assert_eq!(
+        Command::Drive(Direction::Left, 200).to_frame(),
+        Command::Drive(Direction::Left, 100).to_frame()
+    );
+}
+
+#[test]
+fn test_frame() {
+    let frame = Frame::reply(0x51, &[1, 2, 3]).unwrap();
+    assert_eq!(frame.device(), DEVICE_REPLY);
+    assert_eq!(frame.func(), 0x51);
+    assert_eq!(frame.payload(), [1, 2, 3]);
+    assert_eq!(frame.len(), 8);
+    assert_eq!(frame.checksum(), 6 + 0x51 + 6);
+
+    let empty = Frame::command(0x01, &[]).unwrap();
+    assert_eq!(empty.as_bytes(), [0xff, 0xfc, 0x03, 0x01, 0x04]);
+
+    let max = Frame::command(0x01, &[0xff; MAX_PAYLOAD_LEN]).unwrap();
+    assert_eq!(max.len(), MAX_FRAME_LEN);
+    assert_eq!(max.as_bytes()[2], 0xff);
+    assert_eq!(
+        Frame::command(0x01, &[0; MAX_PAYLOAD_LEN + 1]),
+        Err(FrameError::TooLong)
+    );
+}
+
+fn parse_all(parser: &mut Parser, bytes: &[u8]) -> Vec<Result<Frame, FrameError>> {
+    bytes.iter().filter_map(|&b| parser.push(b)).collect()
+}
+
+#[test]
+fn test_parser() {
+    let a = Frame::reply(0x0a, &[1, 0, 2, 0, 3, 0, 120]).unwrap();
+    let b = Command::Stop.to_frame();
+    let mut parser = Parser::new();
+
+    // garbage before and between the frames
+    let mut stream = vec![0x00, 0x12, 0xff, 0x34];
+    stream.extend_from_slice(a.as_bytes());
+    stream.extend_from_slice(&[0xff, 0xff]);
+    stream.extend_from_slice(b.as_bytes());
+    assert_eq!(
+        parse_all(&mut parser, &stream),
+        [Ok(a.clone()), Ok(b.clone())]
+    );
+
+    // corrupted checksum, then resynchronized
+    let mut bad = a.as_bytes().to_vec();
+    *bad.last_mut().unwrap() ^= 1;
+    bad.extend_from_slice(b.as_bytes());
+    assert_eq!(
+        parse_all(&mut parser, &bad),
+        [Err(FrameError::Checksum), Ok(b.clone())]
+    );
+
+    // invalid length
+    let mut stream = vec![0xff, 0xfb, 0x02];
+    stream.extend_from_slice(a.as_bytes());
+    assert_eq!(
+        parse_all(&mut parser, &stream),
+        [Err(FrameError::InvalidLength), Ok(a.clone())]
+    );
+
+    // an incomplete frame is dropped by reset
+    parse_all(&mut parser, &a.as_bytes()[..5]);
+    parser.reset();
+    assert_eq!(parse_all(&mut parser, b.as_bytes()), [Ok(b)]);
+}
+
+#[test]
+fn test_replies() {
+    let speed = Frame::reply(func::REPORT_SPEED, &[0xf4, 0x01, 0x0c, 0xfe, 0, 0, 121]).unwrap();
+    assert_eq!(
+        Reply::parse(&speed),
+        Reply::Speed {
+            vx: 500,
+            vy: -500,
+            vz: 0,
+            battery: 121,
+        }
+    );
+
+    let attitude = Frame::reply(func::REPORT_ATTITUDE, &[1, 0, 0xff, 0xff, 0x10, 0x27]).unwrap();
+    assert_eq!(
+        Reply::parse(&attitude),
+        Reply::Attitude {
+            roll: 1,
+            pitch: -1,
+            yaw: 10000,
+        }
+    );
+
+    let mut payload = Vec::new();
+    for count in [1i32, -2, 0x1234_5678, i32::MIN] {
+        payload.extend_from_slice(&count.to_le_bytes());
+    }
+    let encoder = Frame::reply(func::REPORT_ENCODER, &payload).unwrap();
+    assert_eq!(
+        Reply::parse(&encoder),
+        Reply::Encoder([1, -2, 0x1234_5678, i32::MIN])
+    );
+
+    // unknown functions and truncated payloads
+    let other = Frame::reply(0x51, &[0x12]).unwrap();
+    assert_eq!(Reply::parse(&other), Reply::Other(other.clone()));
+    let short = Frame::reply(func::REPORT_SPEED, &[0; 6]).unwrap();
+    assert_eq!(Reply::parse(&short), Reply::Other(short.clone()));
+}
+
+/// A serial port that records the written bytes, and reads the queued bytes.
+#[derive(Default)]
+struct MockPort {
+    tx: Vec<u8>,
+    rx: VecDeque<u8>,
+}
+
+impl axio::Read for MockPort {
+    fn read(&mut self, buf: &mut [u8]) -> axio::Result<usize> {
+        let n = buf.len().min(self.rx.len());
+        for b in &mut buf[..n] {
+            *b = self.rx.pop_front().unwrap();
+        }
+        Ok(n)
+    }
+}
+
+impl axio::Write for MockPort {
+    fn write(&mut self, buf: &[u8]) -> axio::Result<usize> {
+        self.tx.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> axio::Result {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_chassis() {
+    let mut chassis = Chassis::new(MockPort::default());
+    chassis.drive(Direction::Forward, 100).unwrap();
+    chassis.turn(Rotation::Right, 100).unwrap();
+    chassis.stop().unwrap();
+    chassis.buzzer(96).unwrap();
+
+    let mut expected = Vec::new();
+    for cmd in [
+        Command::Drive(Direction::Forward, 100),
+        Command::Turn(Rotation::Right, 100),
+        Command::Stop,
+        Command::Buzzer(96),
+    ] {
+        expected.extend_from_slice(cmd.to_frame().as_bytes());
+    }
+    assert_eq!(chassis.port().tx, expected);
+
+    // echoed commands and corrupted frames are skipped
+    let encoder = Frame::reply(func::REPORT_ENCODER, &[0; 16]).unwrap();
+    let mut corrupted = encoder.as_bytes().to_vec();
+    corrupted[5] = 1;
+    let rx = &mut chassis.port().rx;
+    rx.extend(Command::Stop.to_frame().as_bytes());
+    rx.extend(&corrupted);
+    rx.extend(encoder.as_bytes());
+    rx.extend(&encoder.as_bytes()[..4]);
+    assert_eq!(chassis.recv().unwrap(), Reply::Encoder([0; 4]));
+    assert_eq!(chassis.recv(), Err(axio::Error::UnexpectedEof));
+}
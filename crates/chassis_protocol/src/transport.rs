@@ -0,0 +1,76 @@
+use axio::{Error, Read, Result, Write};
+
+use crate::{Command, Direction, Parser, Reply, Rotation, DEVICE_REPLY};
+
+/// A controller on a serial port, which should be set to 115200 baud, 8 data
+/// bits, no parity and 1 stop bit.
+pub struct Chassis<P> {
+    port: P,
+    parser: Parser,
+}
+
+impl<P: Read + Write> Chassis<P> {
+    /// Creates a new controller on the serial port.
+    pub const fn new(port: P) -> Self {
+        Self {
+            port,
+            parser: Parser::new(),
+        }
+    }
+
+    /// Returns the serial port.
+    pub fn port(&mut self) -> &mut P {
+        &mut self.port
+    }
+
+    /// Returns the serial port, and drops the controller.
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// Sends a command.
+    pub fn send(&mut self, cmd: &Command) -> Result {
+        self.port.write_all(cmd.to_frame().as_bytes())?;
+        self.port.flush()
+    }
+
+    /// Moves in the direction, at the speed in percent.
+    pub fn drive(&mut self, dir: Direction, speed: u8) -> Result {
+        self.send(&Command::Drive(dir, speed))
+    }
+
+    /// Stops the motors.
+    pub fn stop(&mut self) -> Result {
+        self.send(&Command::Stop)
+    }
+
+    /// Rotates in place, at the speed in percent.
+    pub fn turn(&mut self, rot: Rotation, speed: u8) -> Result {
+        self.send(&Command::Turn(rot, speed))
+    }
+
+    /// Turns on the buzzer for the time in milliseconds.
+    pub fn buzzer(&mut self, ms: u16) -> Result {
+        self.send(&Command::Buzzer(ms))
+    }
+
+    /// Receives the next reply, skipping corrupted frames and commands (e.g.,
+    /// echoed by the port).
+    ///
+    /// It blocks if the serial port blocks on reading, and returns
+    /// [`Error::UnexpectedEof`] if the port has no more data.
+    pub fn recv(&mut self) -> Result<Reply> {
+        let mut buf = [0];
+        loop {
+            if self.port.read(&mut buf)? == 0 {
+                return Err(Error::UnexpectedEof);
+            }
+            match self.parser.push(buf[0]) {
+                Some(Ok(frame)) if frame.device() == DEVICE_REPLY => {
+                    return Ok(Reply::parse(&frame));
+                }
+                _ => {}
+            }
+        }
+    }
+}
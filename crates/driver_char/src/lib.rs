@@ -0,0 +1,97 @@
+//! Common traits and types for character device drivers (i.e. serial ports).
+
+#![no_std]
+#![feature(doc_auto_cfg)]
+
+#[cfg(feature = "pl011")]
+pub mod pl011;
+
+#[cfg(feature = "ns16550")]
+pub mod ns16550;
+
+#[doc(no_inline)]
+pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+
+/// The parity bit of the frame format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    /// No parity bit.
+    None,
+    /// The number of 1s in the data and parity bits is odd.
+    Odd,
+    /// The number of 1s in the data and parity bits is even.
+    Even,
+}
+
+/// The number of stop bits of the frame format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    /// One stop bit.
+    One,
+    /// Two stop bits.
+    Two,
+}
+
+/// The line configuration of a serial port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialConfig {
+    /// The baud rate in bits per second.
+    pub baud_rate: u32,
+    /// The number of data bits in a frame, from 5 to 8.
+    pub data_bits: u8,
+    /// The parity bit.
+    pub parity: Parity,
+    /// The number of stop bits.
+    pub stop_bits: StopBits,
+}
+
+impl SerialConfig {
+    /// Returns whether the configuration is valid, i.e., the baud rate is not
+    /// zero and there are 5 to 8 data bits.
+    pub const fn is_valid(&self) -> bool {
+        self.baud_rate != 0 && self.data_bits >= 5 && self.data_bits <= 8
+    }
+}
+
+impl Default for SerialConfig {
+    /// 115200 baud, 8 data bits, no parity and 1 stop bit (`115200 8N1`).
+    fn default() -> Self {
+        Self {
+            baud_rate: 115200,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+}
+
+/// Operations that require a character device driver to implement.
+pub trait CharDriverOps: BaseDriverOps {
+    /// Reads a received byte, or returns `None` if nothing is received.
+    fn read_byte(&mut self) -> Option<u8>;
+
+    /// Writes a byte, waits until the device is ready to send it.
+    fn write_byte(&mut self, byte: u8);
+
+    /// Returns the current line configuration.
+    fn config(&self) -> SerialConfig;
+
+    /// Sets the line configuration, returns [`DevError::InvalidParam`] if it's
+    /// invalid, or [`DevError::Unsupported`] if it can't be changed.
+    fn set_config(&mut self, config: &SerialConfig) -> DevResult;
+
+    /// The IRQ number raised when bytes are received, or `None` if the device
+    /// can only be polled.
+    fn irq_num(&self) -> Option<usize> {
+        None
+    }
+
+    /// Acknowledges the interrupt of the device, returns whether it was
+    /// raised by the device.
+    ///
+    /// Received bytes should be read after it's called, until
+    /// [`read_byte`](CharDriverOps::read_byte) returns `None`.
+    fn ack_interrupt(&mut self) -> bool {
+        false
+    }
+}
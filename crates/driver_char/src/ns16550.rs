@@ -0,0 +1,216 @@
+//! Driver for the NS16550 compatible UARTs, e.g., the PC serial ports and the
+//! Synopsys DesignWare APB UART.
+
+use crate::{BaseDriverOps, CharDriverOps, DevError, DevResult, DeviceType};
+use crate::{Parity, SerialConfig, StopBits};
+
+const RBR: usize = 0; // receive buffer (read)
+const THR: usize = 0; // transmit holding (write)
+const DLL: usize = 0; // divisor latch low (DLAB = 1)
+const IER: usize = 1; // interrupt enable
+const DLM: usize = 1; // divisor latch high (DLAB = 1)
+const IIR: usize = 2; // interrupt identification (read)
+const FCR: usize = 2; // FIFO control (write)
+const LCR: usize = 3; // line control
+const MCR: usize = 4; // modem control
+const LSR: usize = 5; // line status
+const SCR: usize = 7; // scratch
+
+const IER_RX_AVAILABLE: u8 = 1 << 0;
+const IIR_NO_PENDING: u8 = 1 << 0;
+const IIR_LINE_STATUS: u8 = 0b0110;
+const LCR_DLAB: u8 = 1 << 7;
+const LSR_DATA_READY: u8 = 1 << 0;
+const LSR_THR_EMPTY: u8 = 1 << 5;
+const LSR_TX_EMPTY: u8 = 1 << 6;
+
+/// Access to the registers of a 16550 UART.
+pub trait Ns16550Io: Send + Sync {
+    /// Reads the register at index `reg`.
+    fn read(&self, reg: usize) -> u8;
+    /// Writes the register at index `reg`.
+    fn write(&mut self, reg: usize, value: u8);
+}
+
+/// Registers mapped to memory, each of which is `1 << reg_shift` bytes wide.
+pub struct MmioIo {
+    base: usize,
+    reg_shift: u8,
+}
+
+impl MmioIo {
+    /// Creates the register access of the UART mapped at `base`.
+    ///
+    /// # Safety
+    ///
+    /// `base` must be the virtual address of the mapped registers.
+    pub const unsafe fn new(base: usize, reg_shift: u8) -> Self {
+        Self { base, reg_shift }
+    }
+
+    fn addr(&self, reg: usize) -> usize {
+        self.base + (reg << self.reg_shift)
+    }
+}
+
+impl Ns16550Io for MmioIo {
+    fn read(&self, reg: usize) -> u8 {
+        unsafe {
+            match self.reg_shift {
+                0 => (self.addr(reg) as *const u8).read_volatile(),
+                _ => (self.addr(reg) as *const u32).read_volatile() as u8,
+            }
+        }
+    }
+
+    fn write(&mut self, reg: usize, value: u8) {
+        unsafe {
+            match self.reg_shift {
+                0 => (self.addr(reg) as *mut u8).write_volatile(value),
+                _ => (self.addr(reg) as *mut u32).write_volatile(value as u32),
+            }
+        }
+    }
+}
+
+/// Registers accessed through x86 I/O ports.
+#[cfg(target_arch = "x86_64")]
+pub struct PortIo {
+    base: u16,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl PortIo {
+    /// Creates the register access of the UART at I/O port `base`, e.g.,
+    /// `0x2f8` for `COM2`.
+    pub const fn new(base: u16) -> Self {
+        Self { base }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Ns16550Io for PortIo {
+    fn read(&self, reg: usize) -> u8 {
+        unsafe { x86_64::instructions::port::PortReadOnly::new(self.base + reg as u16).read() }
+    }
+
+    fn write(&mut self, reg: usize, value: u8) {
+        unsafe {
+            x86_64::instructions::port::PortWriteOnly::new(self.base + reg as u16).write(value)
+        }
+    }
+}
+
+/// The 16550 UART device.
+pub struct Ns16550<IO: Ns16550Io> {
+    io: IO,
+    clock: u32,
+    config: SerialConfig,
+    irq_num: Option<usize>,
+}
+
+impl<IO: Ns16550Io> Ns16550<IO> {
+    /// Initializes the UART, enables FIFOs and the receive interrupt.
+    ///
+    /// `clock` is the frequency of its input clock in Hz (`1_843_200` for the
+    /// PC serial ports), or 0 if unknown, then the line configuration set by
+    /// the firmware is kept. Otherwise, the default [`SerialConfig`] is used.
+    ///
+    /// It returns [`DevError::Unsupported`] if the scratch register doesn't
+    /// work, e.g., there is no such device.
+    pub fn try_new(mut io: IO, clock: u32, irq_num: Option<usize>) -> DevResult<Self> {
+        for pattern in [0x5a, 0xa5] {
+            io.write(SCR, pattern);
+            if io.read(SCR) != pattern {
+                return Err(DevError::Unsupported);
+            }
+        }
+        io.write(IER, 0);
+        io.write(FCR, 0x07); // enable and clear FIFOs, trigger at 1 byte
+        io.write(MCR, 0x0b); // DTR, RTS, and OUT2 to route the interrupt
+        let mut dev = Self {
+            io,
+            clock,
+            config: SerialConfig::default(),
+            irq_num,
+        };
+        if clock != 0 {
+            dev.set_config(&SerialConfig::default())?;
+        }
+        dev.io.write(IER, IER_RX_AVAILABLE);
+        Ok(dev)
+    }
+}
+
+impl<IO: Ns16550Io> BaseDriverOps for Ns16550<IO> {
+    fn device_name(&self) -> &str {
+        "ns16550"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Char
+    }
+}
+
+impl<IO: Ns16550Io> CharDriverOps for Ns16550<IO> {
+    fn read_byte(&mut self) -> Option<u8> {
+        if self.io.read(LSR) & LSR_DATA_READY != 0 {
+            Some(self.io.read(RBR))
+        } else {
+            None
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        while self.io.read(LSR) & LSR_THR_EMPTY == 0 {}
+        self.io.write(THR, byte);
+    }
+
+    fn config(&self) -> SerialConfig {
+        self.config
+    }
+
+    fn set_config(&mut self, config: &SerialConfig) -> DevResult {
+        if !config.is_valid() {
+            return Err(DevError::InvalidParam);
+        }
+        if self.clock == 0 {
+            return Err(DevError::Unsupported);
+        }
+        let divisor =
+            (self.clock as u64 + config.baud_rate as u64 * 8) / (config.baud_rate as u64 * 16);
+        if divisor == 0 || divisor > 0xffff {
+            return Err(DevError::InvalidParam);
+        }
+        let mut lcr = config.data_bits - 5;
+        if config.stop_bits == StopBits::Two {
+            lcr |= 1 << 2;
+        }
+        match config.parity {
+            Parity::None => {}
+            Parity::Odd => lcr |= 1 << 3,
+            Parity::Even => lcr |= (1 << 3) | (1 << 4),
+        }
+
+        // some UARTs (e.g., the DesignWare one) ignore LCR writes when busy
+        while self.io.read(LSR) & LSR_TX_EMPTY == 0 {}
+        self.io.write(LCR, LCR_DLAB);
+        self.io.write(DLL, divisor as u8);
+        self.io.write(DLM, (divisor >> 8) as u8);
+        self.io.write(LCR, lcr);
+        self.config = *config;
+        Ok(())
+    }
+
+    fn irq_num(&self) -> Option<usize> {
+        self.irq_num
+    }
+
+    fn ack_interrupt(&mut self) -> bool {
+        let iir = self.io.read(IIR);
+        if iir & 0x0f == IIR_LINE_STATUS {
+            self.io.read(LSR); // clear the error
+        }
+        iir & IIR_NO_PENDING == 0
+    }
+}
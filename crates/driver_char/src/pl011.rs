@@ -0,0 +1,99 @@
+//! Driver for the ARM PL011 UART.
+
+use arm_pl011::pl011::{self, Pl011Uart};
+
+use crate::{BaseDriverOps, CharDriverOps, DevError, DevResult, DeviceType};
+use crate::{Parity, SerialConfig, StopBits};
+
+/// The PL011 UART device.
+pub struct Pl011Dev {
+    inner: Pl011Uart,
+    clock: u32,
+    config: SerialConfig,
+    irq_num: Option<usize>,
+}
+
+impl Pl011Dev {
+    /// Initializes the PL011 UART whose registers are mapped at `base`.
+    ///
+    /// `clock` is the frequency of its reference clock in Hz, or 0 if unknown,
+    /// then the line configuration set by the firmware is kept. Otherwise,
+    /// the default [`SerialConfig`] is used.
+    ///
+    /// It returns [`DevError::Unsupported`] if the peripheral ID doesn't
+    /// match, e.g., there is no such device.
+    pub fn try_new(base: *mut u8, clock: u32, irq_num: Option<usize>) -> DevResult<Self> {
+        let mut inner = Pl011Uart::new(base);
+        if inner.periph_id() & 0x000f_ffff != 0x0004_1011 {
+            return Err(DevError::Unsupported);
+        }
+        inner.init();
+        let mut dev = Self {
+            inner,
+            clock,
+            config: SerialConfig::default(),
+            irq_num,
+        };
+        if clock != 0 {
+            dev.set_config(&SerialConfig::default())?;
+        }
+        Ok(dev)
+    }
+}
+
+impl BaseDriverOps for Pl011Dev {
+    fn device_name(&self) -> &str {
+        "pl011"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Char
+    }
+}
+
+impl CharDriverOps for Pl011Dev {
+    fn read_byte(&mut self) -> Option<u8> {
+        self.inner.getchar()
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        self.inner.putchar(byte)
+    }
+
+    fn config(&self) -> SerialConfig {
+        self.config
+    }
+
+    fn set_config(&mut self, config: &SerialConfig) -> DevResult {
+        if !config.is_valid() {
+            return Err(DevError::InvalidParam);
+        }
+        if self.clock == 0 {
+            return Err(DevError::Unsupported);
+        }
+        let parity = match config.parity {
+            Parity::None => pl011::Parity::None,
+            Parity::Odd => pl011::Parity::Odd,
+            Parity::Even => pl011::Parity::Even,
+        };
+        self.inner.set_line_config(
+            self.clock,
+            config.baud_rate,
+            config.data_bits,
+            parity,
+            config.stop_bits == StopBits::Two,
+        );
+        self.config = *config;
+        Ok(())
+    }
+
+    fn irq_num(&self) -> Option<usize> {
+        self.irq_num
+    }
+
+    fn ack_interrupt(&mut self) -> bool {
+        let raised = self.inner.is_receive_interrupt();
+        self.inner.ack_interrupts();
+        raised
+    }
+}
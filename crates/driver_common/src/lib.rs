@@ -7,11 +7,17 @@
 //! - [`driver_block`][2]: Common traits for block storage drivers.
 //! - [`driver_display`][3]: Common traits and types for graphics display drivers.
 //! - [`driver_net`][4]: Common traits and types for network (NIC) drivers.
+//! - [`driver_char`][5]: Common traits and types for character device drivers.
+//! - [`driver_gpio`][6]: Common traits and types for GPIO controller drivers.
+//! - [`driver_i2c`][7]: Common traits and types for I2C bus drivers.
 //!
 //! [1]: https://github.com/rcore-os/arceos
 //! [2]: ../driver_block/index.html
 //! [3]: ../driver_display/index.html
 //! [4]: ../driver_net/index.html
+//! [5]: ../driver_char/index.html
+//! [6]: ../driver_gpio/index.html
+//! [7]: ../driver_i2c/index.html
 
 #![no_std]
 #![feature(const_trait_impl)]
@@ -27,6 +33,10 @@ pub enum DeviceType {
     Net,
     /// Graphic display device (e.g., GPU)
     Display,
+    /// GPIO controller.
+    Gpio,
+    /// I2C bus controller.
+    I2c,
 }
 
 /// The error type for device operation failures.
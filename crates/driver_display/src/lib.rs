@@ -5,6 +5,17 @@
 #[doc(no_inline)]
 pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
 
+/// The format of the pixels in the framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel, in the order of blue, green, red and an unused byte.
+    Bgra8888,
+    /// 1 bit per pixel, which is set if the pixel is on. Each row is packed in
+    /// `(width + 7) / 8` bytes, with the leftmost pixel in the most
+    /// significant bit.
+    Mono1,
+}
+
 /// The information of the graphics device.
 #[derive(Debug, Clone, Copy)]
 pub struct DisplayInfo {
@@ -16,6 +27,8 @@ pub struct DisplayInfo {
     pub fb_base_vaddr: usize,
     /// The size of the framebuffer in bytes.
     pub fb_size: usize,
+    /// The format of the pixels in the framebuffer.
+    pub format: PixelFormat,
 }
 
 /// The framebuffer.
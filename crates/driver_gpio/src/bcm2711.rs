@@ -0,0 +1,200 @@
+//! The GPIO controller of BCM2711 (Raspberry Pi 4).
+//!
+//! The official documentation: <https://datasheets.raspberrypi.com/bcm2711/bcm2711-peripherals.pdf>
+
+use core::ptr::NonNull;
+
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_structs,
+    registers::{ReadOnly, ReadWrite, WriteOnly},
+};
+
+use crate::{BaseDriverOps, DevError, DevResult, DeviceType};
+use crate::{Edge, GpioDriverOps, PinFunction, Pull};
+
+/// The number of pins of BCM2711.
+pub const NUM_PINS: usize = 58;
+
+register_structs! {
+    /// BCM2711 GPIO registers.
+    pub Bcm2711GpioRegs {
+        /// Function Select Registers, 3 bits for each pin.
+        (0x00 => gpfsel: [ReadWrite<u32>; 6]),
+        (0x18 => _reserved0),
+        /// Pin Output Set Registers.
+        (0x1c => gpset: [WriteOnly<u32>; 2]),
+        (0x24 => _reserved1),
+        /// Pin Output Clear Registers.
+        (0x28 => gpclr: [WriteOnly<u32>; 2]),
+        (0x30 => _reserved2),
+        /// Pin Level Registers.
+        (0x34 => gplev: [ReadOnly<u32>; 2]),
+        (0x3c => _reserved3),
+        /// Pin Event Detect Status Registers, write 1 to clear.
+        (0x40 => gpeds: [ReadWrite<u32>; 2]),
+        (0x48 => _reserved4),
+        /// Pin Rising Edge Detect Enable Registers.
+        (0x4c => gpren: [ReadWrite<u32>; 2]),
+        (0x54 => _reserved5),
+        /// Pin Falling Edge Detect Enable Registers.
+        (0x58 => gpfen: [ReadWrite<u32>; 2]),
+        (0x60 => _reserved6),
+        /// Pull-up / Pull-down Registers, 2 bits for each pin.
+        (0xe4 => gpio_pup_pdn_cntrl: [ReadWrite<u32>; 4]),
+        (0xf4 => @END),
+    }
+}
+
+/// Values of the function select field of the alternative functions 0 to 5.
+const ALT_FSEL: [u32; 6] = [0b100, 0b101, 0b110, 0b111, 0b011, 0b010];
+
+/// The GPIO controller of BCM2711, whose registers are at `0xfe20_0000`.
+pub struct Bcm2711Gpio {
+    base: NonNull<Bcm2711GpioRegs>,
+    irq_num: Option<usize>,
+}
+
+unsafe impl Send for Bcm2711Gpio {}
+unsafe impl Sync for Bcm2711Gpio {}
+
+impl Bcm2711Gpio {
+    /// Constructs a new GPIO controller from the base address of its
+    /// registers, and the IRQ number raised by edges on any pin.
+    pub const fn new(base: *mut u8, irq_num: Option<usize>) -> Self {
+        Self {
+            base: NonNull::new(base).unwrap().cast(),
+            irq_num,
+        }
+    }
+
+    const fn regs(&self) -> &Bcm2711GpioRegs {
+        unsafe { self.base.as_ref() }
+    }
+
+    /// Returns the bank (register index) and bit of the pin in 1-bit-per-pin
+    /// registers.
+    fn bank_bit(pin: usize) -> DevResult<(usize, u32)> {
+        if pin < NUM_PINS {
+            Ok((pin / 32, 1 << (pin % 32)))
+        } else {
+            Err(DevError::InvalidParam)
+        }
+    }
+
+    /// Updates the bit of the pin in the 1-bit-per-pin register `regs`.
+    fn update_bit(regs: &[ReadWrite<u32>; 2], pin: usize, set: bool) -> DevResult {
+        let (bank, bit) = Self::bank_bit(pin)?;
+        let val = regs[bank].get();
+        regs[bank].set(if set { val | bit } else { val & !bit });
+        Ok(())
+    }
+}
+
+impl BaseDriverOps for Bcm2711Gpio {
+    fn device_name(&self) -> &str {
+        "bcm2711-gpio"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Gpio
+    }
+}
+
+impl GpioDriverOps for Bcm2711Gpio {
+    fn num_pins(&self) -> usize {
+        NUM_PINS
+    }
+
+    fn function(&self, pin: usize) -> DevResult<PinFunction> {
+        Self::bank_bit(pin)?;
+        let shift = pin % 10 * 3;
+        let fsel = (self.regs().gpfsel[pin / 10].get() >> shift) & 0b111;
+        Ok(match fsel {
+            0b000 => PinFunction::Input,
+            0b001 => PinFunction::Output,
+            _ => {
+                let alt = ALT_FSEL.iter().position(|&f| f == fsel).unwrap();
+                PinFunction::Alt(alt as u8)
+            }
+        })
+    }
+
+    fn set_function(&mut self, pin: usize, func: PinFunction) -> DevResult {
+        Self::bank_bit(pin)?;
+        let fsel = match func {
+            PinFunction::Input => 0b000,
+            PinFunction::Output => 0b001,
+            PinFunction::Alt(alt) => *ALT_FSEL.get(alt as usize).ok_or(DevError::InvalidParam)?,
+        };
+        let shift = pin % 10 * 3;
+        let reg = &self.regs().gpfsel[pin / 10];
+        reg.set((reg.get() & !(0b111 << shift)) | (fsel << shift));
+        Ok(())
+    }
+
+    fn level(&self, pin: usize) -> DevResult<bool> {
+        let (bank, bit) = Self::bank_bit(pin)?;
+        Ok(self.regs().gplev[bank].get() & bit != 0)
+    }
+
+    fn set_level(&mut self, pin: usize, high: bool) -> DevResult {
+        let (bank, bit) = Self::bank_bit(pin)?;
+        if high {
+            self.regs().gpset[bank].set(bit);
+        } else {
+            self.regs().gpclr[bank].set(bit);
+        }
+        Ok(())
+    }
+
+    fn pull(&self, pin: usize) -> DevResult<Pull> {
+        Self::bank_bit(pin)?;
+        let shift = pin % 16 * 2;
+        let val = (self.regs().gpio_pup_pdn_cntrl[pin / 16].get() >> shift) & 0b11;
+        Ok(match val {
+            0b01 => Pull::Up,
+            0b10 => Pull::Down,
+            _ => Pull::None,
+        })
+    }
+
+    fn set_pull(&mut self, pin: usize, pull: Pull) -> DevResult {
+        Self::bank_bit(pin)?;
+        let val = match pull {
+            Pull::None => 0b00,
+            Pull::Up => 0b01,
+            Pull::Down => 0b10,
+        };
+        let shift = pin % 16 * 2;
+        let reg = &self.regs().gpio_pup_pdn_cntrl[pin / 16];
+        reg.set((reg.get() & !(0b11 << shift)) | (val << shift));
+        Ok(())
+    }
+
+    fn set_edge_interrupt(&mut self, pin: usize, edge: Edge) -> DevResult {
+        let (bank, bit) = Self::bank_bit(pin)?;
+        let regs = self.regs();
+        Self::update_bit(&regs.gpren, pin, matches!(edge, Edge::Rising | Edge::Both))?;
+        Self::update_bit(&regs.gpfen, pin, matches!(edge, Edge::Falling | Edge::Both))?;
+        regs.gpeds[bank].set(bit);
+        Ok(())
+    }
+
+    fn irq_num(&self) -> Option<usize> {
+        self.irq_num
+    }
+
+    fn take_event(&mut self) -> Option<usize> {
+        let regs = self.regs();
+        for bank in 0..2 {
+            let pending = regs.gpeds[bank].get();
+            if pending != 0 {
+                let bit = pending.trailing_zeros();
+                regs.gpeds[bank].set(1 << bit);
+                return Some(bank * 32 + bit as usize);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,101 @@
+//! Common traits and types for GPIO (general-purpose input/output) controller
+//! drivers.
+//!
+//! A GPIO controller has a number of pins, numbered from 0. Each pin can be
+//! an input, an output, or be connected to another peripheral (e.g., a UART)
+//! by selecting one of its alternative functions.
+
+#![cfg_attr(not(test), no_std)]
+#![feature(const_ptr_as_ref)]
+#![feature(const_option)]
+#![feature(const_nonnull_new)]
+
+pub mod bcm2711;
+
+#[cfg(test)]
+mod tests;
+
+#[doc(no_inline)]
+pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+
+/// The function of a pin, i.e., its direction or the peripheral it's
+/// connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinFunction {
+    /// The pin is an input.
+    Input,
+    /// The pin is an output.
+    Output,
+    /// The alternative function with the given number, which is
+    /// controller-specific.
+    Alt(u8),
+}
+
+/// The internal pull resistor of a pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pull {
+    /// No pull resistor, the pin is floating if it's not driven.
+    None,
+    /// Pulled up to high.
+    Up,
+    /// Pulled down to low.
+    Down,
+}
+
+/// The edges of the input level that raise interrupts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// No interrupts.
+    None,
+    /// Low to high.
+    Rising,
+    /// High to low.
+    Falling,
+    /// Both rising and falling edges.
+    Both,
+}
+
+/// Operations that require a GPIO controller driver to implement.
+///
+/// All methods taking a pin number return [`DevError::InvalidParam`] if it's
+/// not less than [`num_pins`](GpioDriverOps::num_pins).
+pub trait GpioDriverOps: BaseDriverOps {
+    /// The number of pins.
+    fn num_pins(&self) -> usize;
+
+    /// Returns the current function of the pin.
+    fn function(&self, pin: usize) -> DevResult<PinFunction>;
+
+    /// Sets the function of the pin, returns [`DevError::InvalidParam`] if the
+    /// alternative function doesn't exist.
+    fn set_function(&mut self, pin: usize, func: PinFunction) -> DevResult;
+
+    /// Returns the current level of the pin, `true` for high.
+    fn level(&self, pin: usize) -> DevResult<bool>;
+
+    /// Drives the pin to high or low, which takes effect when it's an output.
+    fn set_level(&mut self, pin: usize, high: bool) -> DevResult;
+
+    /// Returns the pull resistor of the pin.
+    fn pull(&self, pin: usize) -> DevResult<Pull>;
+
+    /// Sets the pull resistor of the pin.
+    fn set_pull(&mut self, pin: usize, pull: Pull) -> DevResult;
+
+    /// Selects the edges of the input level that raise interrupts, and
+    /// clears the pending event of the pin.
+    fn set_edge_interrupt(&mut self, pin: usize, edge: Edge) -> DevResult;
+
+    /// The IRQ number raised when an edge is detected, or `None` if the
+    /// events can only be polled.
+    fn irq_num(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns a pin on which a selected edge has been detected, and clears
+    /// its event, or returns `None` if there's no pending event.
+    ///
+    /// It should be called until it returns `None` to acknowledge the
+    /// interrupt.
+    fn take_event(&mut self) -> Option<usize>;
+}
@@ -0,0 +1,150 @@
+use super::bcm2711::{Bcm2711Gpio, NUM_PINS};
+use super::*;
+
+const GPFSEL1: usize = 0x04 >> 2;
+const GPSET0: usize = 0x1c >> 2;
+const GPCLR0: usize = 0x28 >> 2;
+const GPLEV0: usize = 0x34 >> 2;
+const GPEDS0: usize = 0x40 >> 2;
+const GPREN0: usize = 0x4c >> 2;
+const GPFEN0: usize = 0x58 >> 2;
+const PUP_PDN0: usize = 0xe4 >> 2;
+
+/// A fake register block of BCM2711 GPIO in memory.
+///
+/// Unlike the hardware, writes to `GPSET`/`GPCLR` don't change `GPLEV`, and
+/// writes to `GPEDS` don't clear it.
+struct FakeRegs([u32; 0xf4 / 4]);
+
+impl FakeRegs {
+    fn new() -> Box<Self> {
+        Box::new(Self([0; 0xf4 / 4]))
+    }
+
+    fn gpio(&mut self) -> Bcm2711Gpio {
+        Bcm2711Gpio::new(self.0.as_mut_ptr() as *mut u8, Some(0x94))
+    }
+}
+
+#[test]
+fn test_function() {
+    let mut regs = FakeRegs::new();
+    let mut gpio = regs.gpio();
+    assert_eq!(gpio.num_pins(), NUM_PINS);
+    assert_eq!(gpio.function(12).unwrap(), PinFunction::Input);
+
+    // UART5 on GPIO 12 and 13, UART0 on GPIO 14 and 15
+    gpio.set_function(12, PinFunction::Alt(4)).unwrap();
+    gpio.set_function(13, PinFunction::Alt(4)).unwrap();
+    gpio.set_function(14, PinFunction::Alt(0)).unwrap();
+    gpio.set_function(15, PinFunction::Alt(0)).unwrap();
+    assert_eq!(regs.0[GPFSEL1], 0x246c0);
+
+    let mut gpio = regs.gpio();
+    assert_eq!(gpio.function(12).unwrap(), PinFunction::Alt(4));
+    assert_eq!(gpio.function(15).unwrap(), PinFunction::Alt(0));
+    assert_eq!(gpio.function(16).unwrap(), PinFunction::Input);
+    gpio.set_function(16, PinFunction::Output).unwrap();
+    gpio.set_function(12, PinFunction::Input).unwrap();
+    assert_eq!(gpio.function(16).unwrap(), PinFunction::Output);
+    assert_eq!(regs.0[GPFSEL1], 0x64600);
+
+    for alt in 0..6 {
+        gpio.set_function(57, PinFunction::Alt(alt)).unwrap();
+        assert_eq!(gpio.function(57).unwrap(), PinFunction::Alt(alt));
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let mut regs = FakeRegs::new();
+    let mut gpio = regs.gpio();
+    assert!(matches!(
+        gpio.set_function(0, PinFunction::Alt(6)),
+        Err(DevError::InvalidParam)
+    ));
+    assert!(matches!(
+        gpio.function(NUM_PINS),
+        Err(DevError::InvalidParam)
+    ));
+    assert!(matches!(gpio.level(NUM_PINS), Err(DevError::InvalidParam)));
+    assert!(matches!(
+        gpio.set_pull(NUM_PINS, Pull::Up),
+        Err(DevError::InvalidParam)
+    ));
+    assert!(matches!(
+        gpio.set_edge_interrupt(100, Edge::Both),
+        Err(DevError::InvalidParam)
+    ));
+    assert!(regs.0.iter().all(|&r| r == 0));
+}
+
+#[test]
+fn test_level() {
+    let mut regs = FakeRegs::new();
+    let mut gpio = regs.gpio();
+    gpio.set_level(3, true).unwrap();
+    gpio.set_level(40, false).unwrap();
+    assert_eq!(regs.0[GPSET0], 1 << 3);
+    assert_eq!(regs.0[GPCLR0 + 1], 1 << 8);
+
+    regs.0[GPLEV0] = 1 << 5;
+    regs.0[GPLEV0 + 1] = 1 << 25;
+    let gpio = regs.gpio();
+    assert!(gpio.level(5).unwrap());
+    assert!(!gpio.level(6).unwrap());
+    assert!(gpio.level(57).unwrap());
+}
+
+#[test]
+fn test_pull() {
+    let mut regs = FakeRegs::new();
+    let mut gpio = regs.gpio();
+    gpio.set_pull(12, Pull::Up).unwrap();
+    gpio.set_pull(13, Pull::Up).unwrap();
+    gpio.set_pull(16, Pull::Down).unwrap();
+    assert_eq!(regs.0[PUP_PDN0], 0x0500_0000);
+    assert_eq!(regs.0[PUP_PDN0 + 1], 0b10);
+
+    let mut gpio = regs.gpio();
+    assert_eq!(gpio.pull(12).unwrap(), Pull::Up);
+    assert_eq!(gpio.pull(16).unwrap(), Pull::Down);
+    assert_eq!(gpio.pull(17).unwrap(), Pull::None);
+    gpio.set_pull(12, Pull::None).unwrap();
+    assert_eq!(regs.0[PUP_PDN0], 0x0400_0000);
+}
+
+#[test]
+fn test_edge_interrupt() {
+    let mut regs = FakeRegs::new();
+    let mut gpio = regs.gpio();
+    assert_eq!(gpio.irq_num(), Some(0x94));
+    gpio.set_edge_interrupt(4, Edge::Rising).unwrap();
+    gpio.set_edge_interrupt(5, Edge::Falling).unwrap();
+    gpio.set_edge_interrupt(33, Edge::Both).unwrap();
+    assert_eq!(regs.0[GPREN0], 1 << 4);
+    assert_eq!(regs.0[GPFEN0], 1 << 5);
+    assert_eq!(regs.0[GPREN0 + 1], 1 << 1);
+    assert_eq!(regs.0[GPFEN0 + 1], 1 << 1);
+    // the pending event is cleared
+    assert_eq!(regs.0[GPEDS0 + 1], 1 << 1);
+
+    let mut gpio = regs.gpio();
+    gpio.set_edge_interrupt(33, Edge::None).unwrap();
+    assert_eq!(regs.0[GPREN0 + 1], 0);
+    assert_eq!(regs.0[GPFEN0 + 1], 0);
+}
+
+#[test]
+fn test_take_event() {
+    let mut regs = FakeRegs::new();
+    assert_eq!(regs.gpio().take_event(), None);
+
+    regs.0[GPEDS0] = (1 << 4) | (1 << 5);
+    assert_eq!(regs.gpio().take_event(), Some(4));
+    assert_eq!(regs.0[GPEDS0], 1 << 4); // write 1 to clear
+
+    regs.0[GPEDS0] = 0;
+    regs.0[GPEDS0 + 1] = 1 << 1;
+    assert_eq!(regs.gpio().take_event(), Some(33));
+}
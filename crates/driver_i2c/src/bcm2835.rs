@@ -0,0 +1,221 @@
+//! The Broadcom Serial Controller (BSC) of BCM2835 and later SoCs, e.g.,
+//! BCM2711 (Raspberry Pi 4), which is an I2C master.
+//!
+//! The official documentation: <https://datasheets.raspberrypi.com/bcm2711/bcm2711-peripherals.pdf>
+
+use core::ptr::NonNull;
+
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_structs,
+    registers::ReadWrite,
+};
+
+use crate::{BaseDriverOps, DeviceType, I2cBusOps, I2cError, I2cResult};
+
+register_structs! {
+    /// BSC registers.
+    BscRegs {
+        /// Control Register.
+        (0x00 => c: ReadWrite<u32>),
+        /// Status Register.
+        (0x04 => s: ReadWrite<u32>),
+        /// Data Length Register.
+        (0x08 => dlen: ReadWrite<u32>),
+        /// Slave Address Register.
+        (0x0c => a: ReadWrite<u32>),
+        /// Data FIFO Register.
+        (0x10 => fifo: ReadWrite<u32>),
+        /// Clock Divider Register.
+        (0x14 => div: ReadWrite<u32>),
+        /// Data Delay Register.
+        (0x18 => del: ReadWrite<u32>),
+        /// Clock Stretch Timeout Register.
+        (0x1c => clkt: ReadWrite<u32>),
+        (0x20 => @END),
+    }
+}
+
+const C_I2CEN: u32 = 1 << 15;
+const C_ST: u32 = 1 << 7;
+const C_CLEAR: u32 = 1 << 4;
+const C_READ: u32 = 1 << 0;
+
+const S_CLKT: u32 = 1 << 9;
+const S_ERR: u32 = 1 << 8;
+const S_RXD: u32 = 1 << 5;
+const S_TXD: u32 = 1 << 4;
+const S_DONE: u32 = 1 << 1;
+const S_TA: u32 = 1 << 0;
+
+/// The depth of the data FIFO.
+const FIFO_SIZE: usize = 16;
+
+/// The default frequency of the clock line, i.e., the standard mode.
+const DEFAULT_FREQ: u32 = 100_000;
+
+/// The maximum number of polls of the status register in a transfer, after
+/// which it times out.
+const MAX_POLLS: usize = 0x10_0000;
+
+/// The BSC I2C master.
+pub struct Bcm2835I2c {
+    base: NonNull<BscRegs>,
+    core_clock: u32,
+    freq: u32,
+}
+
+unsafe impl Send for Bcm2835I2c {}
+unsafe impl Sync for Bcm2835I2c {}
+
+impl Bcm2835I2c {
+    /// Creates a new BSC from the base address of its registers, and the
+    /// frequency of the core clock in Hz. The clock line is set to 100 kHz.
+    pub fn new(base: *mut u8, core_clock: u32) -> Self {
+        let mut bsc = Self {
+            base: NonNull::new(base).unwrap().cast(),
+            core_clock,
+            freq: 0,
+        };
+        bsc.reset();
+        let _ = bsc.set_clock_frequency(DEFAULT_FREQ);
+        bsc
+    }
+
+    const fn regs(&self) -> &BscRegs {
+        unsafe { self.base.as_ref() }
+    }
+
+    /// Disables the controller, clears the FIFO and the status flags.
+    fn reset(&self) {
+        self.regs().c.set(C_CLEAR);
+        self.regs().s.set(S_CLKT | S_ERR | S_DONE);
+    }
+
+    /// Starts a transfer of `len` bytes with the device at `addr`.
+    fn start(&self, addr: u8, len: usize, read: bool) -> I2cResult {
+        if addr > 0x7f || len > 0xffff {
+            return Err(I2cError::InvalidParam);
+        }
+        let regs = self.regs();
+        regs.a.set(addr as u32);
+        regs.dlen.set(len as u32);
+        regs.c.set(C_I2CEN | C_ST | if read { C_READ } else { 0 });
+        Ok(())
+    }
+
+    /// Moves the data between the FIFO and the buffers until the transfer is
+    /// done.
+    fn wait_done(&self, mut tx: &[u8], rx: &mut [u8]) -> I2cResult {
+        let regs = self.regs();
+        let mut rx_len = 0;
+        for _ in 0..MAX_POLLS {
+            let status = regs.s.get();
+            if status & (S_ERR | S_CLKT) != 0 {
+                self.reset();
+                return Err(if status & S_ERR != 0 {
+                    I2cError::Nack
+                } else {
+                    I2cError::Timeout
+                });
+            }
+            while !tx.is_empty() && regs.s.get() & S_TXD != 0 {
+                regs.fifo.set(tx[0] as u32);
+                tx = &tx[1..];
+            }
+            while rx_len < rx.len() && regs.s.get() & S_RXD != 0 {
+                rx[rx_len] = regs.fifo.get() as u8;
+                rx_len += 1;
+            }
+            if status & S_DONE != 0 {
+                regs.s.set(S_DONE);
+                return Ok(());
+            }
+        }
+        self.reset();
+        Err(I2cError::Timeout)
+    }
+}
+
+impl BaseDriverOps for Bcm2835I2c {
+    fn device_name(&self) -> &str {
+        "bcm2835-i2c"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::I2c
+    }
+}
+
+impl I2cBusOps for Bcm2835I2c {
+    fn write(&mut self, addr: u8, data: &[u8]) -> I2cResult {
+        self.reset();
+        self.start(addr, data.len(), false)?;
+        self.wait_done(data, &mut [])
+    }
+
+    fn read(&mut self, addr: u8, buf: &mut [u8]) -> I2cResult {
+        self.reset();
+        self.start(addr, buf.len(), true)?;
+        self.wait_done(&[], buf)
+    }
+
+    /// The BSC has no explicit repeated start, so the read is started as
+    /// soon as the write becomes active, before the write is done. `data`
+    /// must fit in the FIFO (16 bytes), or [`I2cError::Unsupported`] is
+    /// returned.
+    fn write_read(&mut self, addr: u8, data: &[u8], buf: &mut [u8]) -> I2cResult {
+        if data.len() > FIFO_SIZE {
+            return Err(I2cError::Unsupported);
+        }
+        self.reset();
+        let regs = self.regs();
+        for &b in data {
+            regs.fifo.set(b as u32);
+        }
+        self.start(addr, data.len(), false)?;
+
+        let mut active = false;
+        for _ in 0..MAX_POLLS {
+            let status = regs.s.get();
+            if status & (S_TA | S_DONE | S_ERR | S_CLKT) != 0 {
+                active = status & S_TA != 0;
+                break;
+            }
+        }
+        if !active {
+            // finished or failed, report the error if any
+            self.wait_done(&[], &mut [])?;
+        }
+        self.start(addr, buf.len(), true)?;
+        self.wait_done(&[], buf)
+    }
+
+    fn clock_frequency(&self) -> u32 {
+        self.freq
+    }
+
+    fn set_clock_frequency(&mut self, freq: u32) -> I2cResult {
+        if freq == 0 {
+            return Err(I2cError::InvalidParam);
+        }
+        // SCL = core clock / CDIV, where CDIV is even
+        let mut div = (self.core_clock + freq - 1) / freq;
+        div += div & 1;
+        if !(2..=0xfffe).contains(&div) {
+            return Err(I2cError::InvalidParam);
+        }
+        // sample and launch data in the middle of the clock phases
+        let fedl = (div / 16).max(1);
+        let redl = (div / 4).max(1);
+        // stretch the clock for at most 35 ms, as SMBus
+        let clkt = (freq as u64 * 35 / 1000).min(0xffff) as u32;
+
+        let regs = self.regs();
+        regs.div.set(div);
+        regs.del.set((fedl << 16) | redl);
+        regs.clkt.set(clkt);
+        self.freq = self.core_clock / div;
+        Ok(())
+    }
+}
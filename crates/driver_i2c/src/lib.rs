@@ -1,7 +1,73 @@
-#![no_std]
+//! Common traits and types for I2C bus controller drivers, and drivers of
+//! the devices on an I2C bus.
+//!
+//! Device drivers (e.g., [`ssd1306::Ssd1306`]) are generic over the bus they
+//! are attached to, i.e., any type that implements [`I2cBusOps`].
+
+#![cfg_attr(not(test), no_std)]
 #![feature(const_ptr_as_ref)]
 #![feature(const_option)]
 #![feature(const_nonnull_new)]
 
-pub mod i2c;
-pub mod ssd1306;
\ No newline at end of file
+pub mod bcm2835;
+pub mod ssd1306;
+
+#[cfg(test)]
+mod tests;
+
+#[doc(no_inline)]
+pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
+
+/// The error type of I2C transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I2cError {
+    /// The address or a data byte is not acknowledged by the device.
+    Nack,
+    /// The transfer isn't finished in time, e.g., the device holds the clock
+    /// line low for too long.
+    Timeout,
+    /// Invalid parameter, e.g., an address wider than 7 bits.
+    InvalidParam,
+    /// The operation is unsupported by the bus controller.
+    Unsupported,
+}
+
+impl From<I2cError> for DevError {
+    fn from(err: I2cError) -> Self {
+        match err {
+            I2cError::Nack | I2cError::Timeout => DevError::Io,
+            I2cError::InvalidParam => DevError::InvalidParam,
+            I2cError::Unsupported => DevError::Unsupported,
+        }
+    }
+}
+
+/// A specialized `Result` type for I2C transfers.
+pub type I2cResult<T = ()> = Result<T, I2cError>;
+
+/// Operations that require an I2C bus controller driver to implement.
+///
+/// The controller is the only master on the bus, and devices are addressed
+/// by their 7-bit addresses.
+pub trait I2cBusOps: BaseDriverOps {
+    /// Writes `data` to the device at `addr`, in a transfer that ends with a
+    /// stop condition.
+    fn write(&mut self, addr: u8, data: &[u8]) -> I2cResult;
+
+    /// Reads `buf.len()` bytes from the device at `addr`, in a transfer that
+    /// ends with a stop condition.
+    fn read(&mut self, addr: u8, buf: &mut [u8]) -> I2cResult;
+
+    /// Writes `data` to the device at `addr`, then reads `buf.len()` bytes
+    /// from it after a repeated start condition, e.g., to read a register.
+    fn write_read(&mut self, addr: u8, data: &[u8], buf: &mut [u8]) -> I2cResult;
+
+    /// Returns the frequency of the clock line (SCL) in Hz.
+    fn clock_frequency(&self) -> u32;
+
+    /// Sets the frequency of the clock line (SCL) in Hz, e.g., 100 kHz for
+    /// the standard mode or 400 kHz for the fast mode.
+    ///
+    /// The actual frequency may be lower if it can't be generated exactly.
+    fn set_clock_frequency(&mut self, freq: u32) -> I2cResult;
+}
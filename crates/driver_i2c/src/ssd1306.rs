@@ -0,0 +1,351 @@
+//! The SSD1306 OLED display controller on an I2C bus, and the compatible
+//! SH1106.
+//!
+//! The official documentation: <https://cdn-shop.adafruit.com/datasheets/SSD1306.pdf>,
+//! and <https://www.displayfuture.com/Display/datasheet/controller/SH1106.pdf>
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::ptr::NonNull;
+
+use driver_display::{DisplayDriverOps, DisplayInfo, FrameBuffer, PixelFormat};
+
+use crate::{BaseDriverOps, DevError, DevResult, DeviceType, I2cBusOps, I2cError, I2cResult};
+
+/// The I2C address of SSD1306 if the `SA0` pin is low, or it's `0x3d`.
+pub const DEFAULT_ADDR: u8 = 0x3c;
+
+/// The control byte before a stream of commands (`Co` = 0, `D/C#` = 0).
+const CONTROL_CMD: u8 = 0x00;
+/// The control byte before a stream of display data (`Co` = 0, `D/C#` = 1).
+const CONTROL_DATA: u8 = 0x40;
+
+/// The maximum number of bytes after the control byte in a transfer.
+const MAX_CHUNK: usize = 32;
+
+/// SH1106 has 132 columns of RAM, and the 128 visible ones start from 2.
+const SH1106_COLUMN_OFFSET: usize = 2;
+
+/// The width of the display in pixels.
+pub const WIDTH: usize = 128;
+
+/// The model of the display controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// SSD1306.
+    Ssd1306,
+    /// SH1106, which has no charge pump and no horizontal addressing mode.
+    Sh1106,
+}
+
+/// The SSD1306 (or SH1106) OLED display controller, driving a display of
+/// 128 x 32 or 128 x 64 pixels.
+///
+/// The display data is organized in pages, each of which is 8 rows of
+/// pixels, and a byte of the data is a column of a page, with the top pixel
+/// in the least significant bit.
+pub struct Ssd1306<B> {
+    bus: B,
+    addr: u8,
+    height: usize,
+    controller: Controller,
+}
+
+impl<B: I2cBusOps> Ssd1306<B> {
+    /// Creates a new SSD1306 at `addr` on the bus, with the height of the
+    /// display. It's not initialized.
+    ///
+    /// Returns [`DevError::InvalidParam`] if the height is not 32 or 64.
+    pub fn new(bus: B, addr: u8, height: usize) -> DevResult<Self> {
+        Self::with_controller(bus, addr, height, Controller::Ssd1306)
+    }
+
+    /// Creates a new SH1106 at `addr` on the bus, similar to
+    /// [`new`](Self::new).
+    pub fn new_sh1106(bus: B, addr: u8, height: usize) -> DevResult<Self> {
+        Self::with_controller(bus, addr, height, Controller::Sh1106)
+    }
+
+    fn with_controller(bus: B, addr: u8, height: usize, controller: Controller) -> DevResult<Self> {
+        if height != 32 && height != 64 {
+            return Err(DevError::InvalidParam);
+        }
+        Ok(Self {
+            bus,
+            addr,
+            height,
+            controller,
+        })
+    }
+
+    /// Returns the model of the controller.
+    pub const fn controller(&self) -> Controller {
+        self.controller
+    }
+
+    /// Returns the height of the display in pixels.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of pages, i.e., 8 rows of pixels.
+    pub const fn num_pages(&self) -> usize {
+        self.height / 8
+    }
+
+    /// Returns the bus, e.g., to change its clock.
+    pub fn bus(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Writes `bytes` with the control byte in chunks.
+    fn write_stream(&mut self, control: u8, bytes: &[u8]) -> I2cResult {
+        let mut buf = [0; MAX_CHUNK + 1];
+        buf[0] = control;
+        for chunk in bytes.chunks(MAX_CHUNK) {
+            buf[1..=chunk.len()].copy_from_slice(chunk);
+            self.bus.write(self.addr, &buf[..=chunk.len()])?;
+        }
+        Ok(())
+    }
+
+    /// Sends raw commands (with their parameters).
+    pub fn write_commands(&mut self, cmds: &[u8]) -> I2cResult {
+        self.write_stream(CONTROL_CMD, cmds)
+    }
+
+    /// Writes raw display data at the current address.
+    pub fn write_data(&mut self, data: &[u8]) -> I2cResult {
+        self.write_stream(CONTROL_DATA, data)
+    }
+
+    /// Initializes the controller with the charge pump (or the DC-DC
+    /// converter of SH1106) enabled, and turns on the display.
+    pub fn init(&mut self) -> I2cResult {
+        let com_pins = if self.height == 64 { 0x12 } else { 0x02 };
+        #[rustfmt::skip]
+        let head = [
+            0xae,                           // display off
+            0xd5, 0x80,                     // clock divide ratio and oscillator frequency
+            0xa8, self.height as u8 - 1,    // multiplex ratio
+            0xd3, 0x00,                     // display offset
+            0x40,                           // start line 0
+        ];
+        #[rustfmt::skip]
+        let model_cmds: &[u8] = match self.controller {
+            Controller::Ssd1306 => &[
+                0x8d, 0x14,                 // enable the charge pump
+                0x20, 0x00,                 // horizontal addressing mode
+                0x2e,                       // deactivate scrolling
+            ],
+            Controller::Sh1106 => &[
+                0xad, 0x8b,                 // enable the DC-DC converter
+            ],
+        };
+        #[rustfmt::skip]
+        let tail = [
+            0xa1,                           // segment remap, column 127 is SEG0
+            0xc8,                           // scan from COM[N-1] to COM0
+            0xda, com_pins,                 // COM pins hardware configuration
+            0x81, 0x8f,                     // contrast
+            0xd9, 0xf1,                     // pre-charge period
+            0xdb, 0x40,                     // VCOMH deselect level
+            0xa4,                           // display the RAM content
+            0xa6,                           // normal, not inverted
+            0xaf,                           // display on
+        ];
+        self.write_commands(&[&head[..], model_cmds, &tail[..]].concat())?;
+        self.clear()
+    }
+
+    /// Turns the display on or off (the sleep mode).
+    pub fn set_display_on(&mut self, on: bool) -> I2cResult {
+        self.write_commands(&[if on { 0xaf } else { 0xae }])
+    }
+
+    /// Sets the area written by [`write_data`](Self::write_data), in columns
+    /// and pages (both inclusive). The address wraps to the next page at the
+    /// end column.
+    ///
+    /// SH1106 has no such addressing mode, and [`I2cError::Unsupported`] is
+    /// returned. Use [`write_area`](Self::write_area) instead.
+    pub fn set_window(&mut self, columns: (u8, u8), pages: (u8, u8)) -> I2cResult {
+        if self.controller != Controller::Ssd1306 {
+            return Err(I2cError::Unsupported);
+        }
+        self.write_commands(&[0x21, columns.0, columns.1, 0x22, pages.0, pages.1])
+    }
+
+    /// Writes the display data of an area, in columns and pages (both
+    /// inclusive). `data` contains the pages one by one.
+    pub fn write_area(&mut self, columns: (u8, u8), pages: (u8, u8), data: &[u8]) -> I2cResult {
+        if columns.0 > columns.1
+            || pages.0 > pages.1
+            || columns.1 as usize >= WIDTH
+            || pages.1 as usize >= self.num_pages()
+        {
+            return Err(I2cError::InvalidParam);
+        }
+        let width = (columns.1 - columns.0) as usize + 1;
+        if data.len() != width * (pages.1 - pages.0 + 1) as usize {
+            return Err(I2cError::InvalidParam);
+        }
+        match self.controller {
+            Controller::Ssd1306 => {
+                self.set_window(columns, pages)?;
+                self.write_data(data)
+            }
+            Controller::Sh1106 => {
+                let column = columns.0 as usize + SH1106_COLUMN_OFFSET;
+                for (page, row) in (pages.0..=pages.1).zip(data.chunks(width)) {
+                    self.write_commands(&[
+                        0xb0 | page,
+                        column as u8 & 0xf,
+                        0x10 | (column >> 4) as u8,
+                    ])?;
+                    self.write_data(row)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Clears the whole display.
+    pub fn clear(&mut self) -> I2cResult {
+        let pages = self.num_pages();
+        let zeros = vec![0; WIDTH * pages];
+        self.write_area((0, WIDTH as u8 - 1), (0, pages as u8 - 1), &zeros)
+    }
+}
+
+/// The number of bytes of a row in the framebuffer of [`Ssd1306Display`].
+const FB_STRIDE: usize = WIDTH / 8;
+
+/// The SSD1306 (or SH1106) as a graphics display device.
+///
+/// The framebuffer is in [`PixelFormat::Mono1`], and [`flush`] only sends the
+/// area that has changed since the last flush, as the bus is slow.
+///
+/// [`flush`]: DisplayDriverOps::flush
+pub struct Ssd1306Display<B> {
+    oled: Ssd1306<B>,
+    fb: NonNull<u8>,
+    fb_size: usize,
+    /// The display data on the screen, in pages.
+    shown: Vec<u8>,
+}
+
+unsafe impl<B: Send> Send for Ssd1306Display<B> {}
+unsafe impl<B: Sync> Sync for Ssd1306Display<B> {}
+
+impl<B: I2cBusOps> Ssd1306Display<B> {
+    /// Initializes the controller, and creates a new display device on it
+    /// with a blank framebuffer, or returns an error if the controller
+    /// doesn't respond.
+    pub fn try_new(mut oled: Ssd1306<B>) -> DevResult<Self> {
+        oled.init()?;
+        let fb_size = FB_STRIDE * oled.height();
+        let fb = Box::leak(vec![0u8; fb_size].into_boxed_slice());
+        let shown = vec![0; WIDTH * oled.num_pages()];
+        Ok(Self {
+            oled,
+            fb: NonNull::new(fb.as_mut_ptr()).unwrap(),
+            fb_size,
+            shown,
+        })
+    }
+
+    /// Returns the controller, e.g., to turn off the display. The data
+    /// written through it is overwritten by the next flush.
+    pub fn oled(&mut self) -> &mut Ssd1306<B> {
+        &mut self.oled
+    }
+
+    /// Converts the framebuffer to the display data in pages.
+    fn fb_to_pages(&self) -> Vec<u8> {
+        let fb = unsafe { core::slice::from_raw_parts(self.fb.as_ptr(), self.fb_size) };
+        let mut pages = vec![0; self.shown.len()];
+        for (i, byte) in pages.iter_mut().enumerate() {
+            let (page, x) = (i / WIDTH, i % WIDTH);
+            for bit in 0..8 {
+                let y = page * 8 + bit;
+                if fb[y * FB_STRIDE + x / 8] & (0x80 >> (x % 8)) != 0 {
+                    *byte |= 1 << bit;
+                }
+            }
+        }
+        pages
+    }
+}
+
+impl<B> Drop for Ssd1306Display<B> {
+    fn drop(&mut self) {
+        let fb = core::ptr::slice_from_raw_parts_mut(self.fb.as_ptr(), self.fb_size);
+        drop(unsafe { Box::from_raw(fb) });
+    }
+}
+
+impl<B: I2cBusOps> BaseDriverOps for Ssd1306Display<B> {
+    fn device_name(&self) -> &str {
+        match self.oled.controller() {
+            Controller::Ssd1306 => "ssd1306",
+            Controller::Sh1106 => "sh1106",
+        }
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Display
+    }
+}
+
+impl<B: I2cBusOps> DisplayDriverOps for Ssd1306Display<B> {
+    fn info(&self) -> DisplayInfo {
+        DisplayInfo {
+            width: WIDTH as u32,
+            height: self.oled.height() as u32,
+            fb_base_vaddr: self.fb.as_ptr() as usize,
+            fb_size: self.fb_size,
+            format: PixelFormat::Mono1,
+        }
+    }
+
+    fn fb(&self) -> FrameBuffer {
+        unsafe { FrameBuffer::from_raw_parts_mut(self.fb.as_ptr(), self.fb_size) }
+    }
+
+    fn need_flush(&self) -> bool {
+        true
+    }
+
+    fn flush(&mut self) -> DevResult {
+        let pages = self.fb_to_pages();
+        // the bounding box of the changed bytes, in columns and pages
+        let mut dirty: Option<((usize, usize), (usize, usize))> = None;
+        for (i, (new, old)) in pages.iter().zip(&self.shown).enumerate() {
+            if new == old {
+                continue;
+            }
+            let (page, x) = (i / WIDTH, i % WIDTH);
+            dirty = Some(match dirty {
+                None => ((x, x), (page, page)),
+                Some((c, p)) => ((c.0.min(x), c.1.max(x)), (p.0, page)),
+            });
+        }
+        let Some((columns, rows)) = dirty else {
+            return Ok(());
+        };
+
+        let data: Vec<u8> = (rows.0..=rows.1)
+            .flat_map(|page| &pages[page * WIDTH + columns.0..=page * WIDTH + columns.1])
+            .copied()
+            .collect();
+        self.oled.write_area(
+            (columns.0 as u8, columns.1 as u8),
+            (rows.0 as u8, rows.1 as u8),
+            &data,
+        )?;
+        self.shown = pages;
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use super::bcm2835::Bcm2835I2c;
+use super::ssd1306::{Ssd1306, Ssd1306Display, DEFAULT_ADDR};
+use super::*;
+use driver_display::{DisplayDriverOps, PixelFormat};
+
+/// A bus that records the written bytes, and fails transfers with the device
+/// at `nack_addr`.
+#[derive(Default)]
+struct MockBus {
+    writes: Vec<(u8, Vec<u8>)>,
+    nack_addr: Option<u8>,
+}
+
+impl MockBus {
+    fn check_addr(&self, addr: u8) -> I2cResult {
+        if addr > 0x7f {
+            Err(I2cError::InvalidParam)
+        } else if Some(addr) == self.nack_addr {
+            Err(I2cError::Nack)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl BaseDriverOps for MockBus {
+    fn device_name(&self) -> &str {
+        "mock-i2c"
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::I2c
+    }
+}
+
+impl I2cBusOps for MockBus {
+    fn write(&mut self, addr: u8, data: &[u8]) -> I2cResult {
+        self.check_addr(addr)?;
+        self.writes.push((addr, data.to_vec()));
+        Ok(())
+    }
+
+    fn read(&mut self, addr: u8, buf: &mut [u8]) -> I2cResult {
+        self.check_addr(addr)?;
+        buf.fill(0xff);
+        Ok(())
+    }
+
+    fn write_read(&mut self, addr: u8, data: &[u8], buf: &mut [u8]) -> I2cResult {
+        self.write(addr, data)?;
+        self.read(addr, buf)
+    }
+
+    fn clock_frequency(&self) -> u32 {
+        100_000
+    }
+
+    fn set_clock_frequency(&mut self, _freq: u32) -> I2cResult {
+        Err(I2cError::Unsupported)
+    }
+}
+
+#[test]
+fn test_ssd1306_init() {
+    let mut oled = Ssd1306::new(MockBus::default(), DEFAULT_ADDR, 32).unwrap();
+    oled.init().unwrap();
+
+    let writes = &oled.bus().writes;
+    assert!(writes.iter().all(|(addr, _)| *addr == DEFAULT_ADDR));
+    let init = &writes[0].1;
+    assert_eq!(init[0], 0x00); // commands
+    assert_eq!(init[1], 0xae);
+    assert_eq!(*init.last().unwrap(), 0xaf);
+    assert!(init.windows(2).any(|w| w == [0xa8, 31]));
+    assert!(init.windows(2).any(|w| w == [0xda, 0x02]));
+
+    // clear: set the window, then 4 pages of 128 zeros in chunks
+    assert_eq!(writes[1].1, [0x00, 0x21, 0, 127, 0x22, 0, 3]);
+    let data = &writes[2..];
+    assert_eq!(data.len(), 4 * 4);
+    for (_, bytes) in data {
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(bytes[0], 0x40);
+        assert!(bytes[1..].iter().all(|&b| b == 0));
+    }
+}
+
+#[test]
+fn test_ssd1306_64_rows() {
+    let mut oled = Ssd1306::new(MockBus::default(), 0x3d, 64).unwrap();
+    assert_eq!(oled.num_pages(), 8);
+    oled.init().unwrap();
+    let init = &oled.bus().writes[0].1;
+    assert!(init.windows(2).any(|w| w == [0xa8, 63]));
+    assert!(init.windows(2).any(|w| w == [0xda, 0x12]));
+    assert_eq!(oled.bus().writes[1].1, [0x00, 0x21, 0, 127, 0x22, 0, 7]);
+}
+
+#[test]
+fn test_ssd1306_invalid_height() {
+    for height in [0, 16, 48, 128] {
+        let oled = Ssd1306::new(MockBus::default(), DEFAULT_ADDR, height);
+        assert!(matches!(oled, Err(DevError::InvalidParam)));
+        let oled = Ssd1306::new_sh1106(MockBus::default(), DEFAULT_ADDR, height);
+        assert!(matches!(oled, Err(DevError::InvalidParam)));
+    }
+}
+
+#[test]
+fn test_ssd1306_raw_writes() {
+    let mut oled = Ssd1306::new(MockBus::default(), DEFAULT_ADDR, 32).unwrap();
+    oled.write_data(&[1, 2, 3]).unwrap();
+    oled.set_display_on(false).unwrap();
+    let data: Vec<u8> = (0..40).collect();
+    oled.write_data(&data).unwrap();
+
+    let writes = &oled.bus().writes;
+    assert_eq!(writes[0].1, [0x40, 1, 2, 3]);
+    assert_eq!(writes[1].1, [0x00, 0xae]);
+    assert_eq!(writes[2].1[1..], data[..32]);
+    assert_eq!(writes[3].1[1..], data[32..]);
+}
+
+#[test]
+fn test_ssd1306_nack() {
+    let bus = MockBus {
+        nack_addr: Some(DEFAULT_ADDR),
+        ..Default::default()
+    };
+    let mut oled = Ssd1306::new(bus, DEFAULT_ADDR, 32).unwrap();
+    assert_eq!(oled.init(), Err(I2cError::Nack));
+    assert!(oled.bus().writes.is_empty());
+    assert!(matches!(DevError::from(I2cError::Nack), DevError::Io));
+}
+
+#[test]
+fn test_sh1106() {
+    let mut oled = Ssd1306::new_sh1106(MockBus::default(), DEFAULT_ADDR, 64).unwrap();
+    oled.init().unwrap();
+    let init = &oled.bus().writes[0].1;
+    assert!(init.windows(2).any(|w| w == [0xad, 0x8b]));
+    assert!(!init
+        .windows(2)
+        .any(|w| w == [0x8d, 0x14] || w == [0x20, 0x00]));
+
+    // clear: each page is addressed from column 2
+    let writes = &oled.bus().writes[1..];
+    assert_eq!(writes.len(), 8 * 5);
+    assert_eq!(writes[0].1, [0x00, 0xb0, 0x02, 0x10]);
+    assert_eq!(writes[5].1, [0x00, 0xb1, 0x02, 0x10]);
+    assert_eq!(oled.set_window((0, 1), (0, 1)), Err(I2cError::Unsupported));
+
+    oled.bus().writes.clear();
+    oled.write_area((126, 127), (6, 7), &[1, 2, 3, 4]).unwrap();
+    let writes = &oled.bus().writes;
+    assert_eq!(writes[0].1, [0x00, 0xb6, 0x00, 0x18]);
+    assert_eq!(writes[1].1, [0x40, 1, 2]);
+    assert_eq!(writes[2].1, [0x00, 0xb7, 0x00, 0x18]);
+    assert_eq!(writes[3].1, [0x40, 3, 4]);
+}
+
+#[test]
+fn test_ssd1306_write_area() {
+    let mut oled = Ssd1306::new(MockBus::default(), DEFAULT_ADDR, 32).unwrap();
+    oled.write_area((8, 9), (1, 2), &[1, 2, 3, 4]).unwrap();
+    let writes = &oled.bus().writes;
+    assert_eq!(writes[0].1, [0x00, 0x21, 8, 9, 0x22, 1, 2]);
+    assert_eq!(writes[1].1, [0x40, 1, 2, 3, 4]);
+
+    let err = Err(I2cError::InvalidParam);
+    assert_eq!(oled.write_area((8, 9), (1, 2), &[1, 2, 3]), err);
+    assert_eq!(oled.write_area((9, 8), (1, 1), &[]), err);
+    assert_eq!(oled.write_area((0, 128), (0, 0), &[0; 129]), err);
+    assert_eq!(oled.write_area((0, 0), (4, 4), &[0]), err);
+}
+
+#[test]
+fn test_ssd1306_display_flush() {
+    let oled = Ssd1306::new(MockBus::default(), DEFAULT_ADDR, 32).unwrap();
+    let mut disp = Ssd1306Display::try_new(oled).unwrap();
+    assert_eq!(disp.device_name(), "ssd1306");
+    let info = disp.info();
+    assert_eq!((info.width, info.height), (128, 32));
+    assert_eq!(info.fb_size, 16 * 32);
+    assert_eq!(info.format, PixelFormat::Mono1);
+    let fb =
+        unsafe { core::slice::from_raw_parts_mut(info.fb_base_vaddr as *mut u8, info.fb_size) };
+
+    // nothing changed since the initialization
+    disp.oled().bus().writes.clear();
+    disp.flush().unwrap();
+    assert!(disp.oled().bus().writes.is_empty());
+
+    // the pixel (10, 9) is bit 1 of column 10 in page 1
+    fb[9 * 16 + 1] = 0x80 >> 2;
+    disp.flush().unwrap();
+    let writes = &disp.oled().bus().writes;
+    assert_eq!(writes[0].1, [0x00, 0x21, 10, 10, 0x22, 1, 1]);
+    assert_eq!(writes[1].1, [0x40, 0x02]);
+
+    // only the bounding box of the changes is sent
+    disp.oled().bus().writes.clear();
+    fb[9 * 16 + 1] = 0;
+    fb[31 * 16 + 2] = 0x01; // (23, 31)
+    disp.flush().unwrap();
+    let writes = &disp.oled().bus().writes;
+    assert_eq!(writes[0].1, [0x00, 0x21, 10, 23, 0x22, 1, 3]);
+    let data: Vec<u8> = writes[1..].iter().flat_map(|w| w.1[1..].to_vec()).collect();
+    assert_eq!(data.len(), 14 * 3);
+    assert!(data[..41].iter().all(|&b| b == 0));
+    assert_eq!(data[41], 0x80);
+
+    disp.oled().bus().writes.clear();
+    disp.flush().unwrap();
+    assert!(disp.oled().bus().writes.is_empty());
+}
+
+#[test]
+fn test_ssd1306_display_nack() {
+    let bus = MockBus {
+        nack_addr: Some(DEFAULT_ADDR),
+        ..Default::default()
+    };
+    let oled = Ssd1306::new_sh1106(bus, DEFAULT_ADDR, 64).unwrap();
+    assert!(matches!(Ssd1306Display::try_new(oled), Err(DevError::Io)));
+}
+
+/// Offsets of the BSC registers, in 32-bit words.
+const DIV: usize = 0x14 >> 2;
+const DEL: usize = 0x18 >> 2;
+const CLKT: usize = 0x1c >> 2;
+
+#[test]
+fn test_bcm2835_clock() {
+    let mut regs = Box::new([0u32; 8]);
+    let mut bsc = Bcm2835I2c::new(regs.as_mut_ptr() as *mut u8, 500_000_000);
+    assert_eq!(bsc.clock_frequency(), 100_000);
+    assert_eq!(regs[DIV], 5000);
+    assert_eq!(regs[DEL], (312 << 16) | 1250);
+    assert_eq!(regs[CLKT], 3500);
+
+    // rounded up to an even divider
+    bsc.set_clock_frequency(400_000).unwrap();
+    assert_eq!(regs[DIV], 1250);
+    bsc.set_clock_frequency(333_333).unwrap();
+    assert_eq!(regs[DIV], 1502);
+    assert_eq!(bsc.clock_frequency(), 332_889);
+
+    assert_eq!(bsc.set_clock_frequency(0), Err(I2cError::InvalidParam));
+    assert_eq!(bsc.set_clock_frequency(1_000), Err(I2cError::InvalidParam));
+    assert_eq!(bsc.write(0x80, &[0]), Err(I2cError::InvalidParam));
+    assert_eq!(
+        bsc.write_read(DEFAULT_ADDR, &[0; 17], &mut [0]),
+        Err(I2cError::Unsupported)
+    );
+}
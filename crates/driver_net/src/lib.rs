@@ -64,6 +64,21 @@ pub trait NetDriverOps: BaseDriverOps {
     /// Allocate a memory buffer of a specified size for network transmission,
     /// returns [`DevResult`]
     fn alloc_tx_buffer(&mut self, size: usize) -> DevResult<NetBufPtr>;
+
+    /// The IRQ number raised when packets are received or transmitted, or
+    /// `None` if the NIC can only be polled.
+    fn irq_num(&self) -> Option<usize> {
+        None
+    }
+
+    /// Acknowledges the interrupt of the NIC, returns whether it was raised
+    /// by the NIC.
+    ///
+    /// It should be called before polling the NIC, when the interrupt is
+    /// handled.
+    fn ack_interrupt(&mut self) -> bool {
+        false
+    }
 }
 
 /// A raw buffer struct for network device.
@@ -2,7 +2,7 @@ extern crate alloc;
 use crate::as_dev_err;
 
 use driver_common::{BaseDriverOps, DevResult, DeviceType};
-use driver_display::{DisplayDriverOps, DisplayInfo, FrameBuffer};
+use driver_display::{DisplayDriverOps, DisplayInfo, FrameBuffer, PixelFormat};
 use virtio_drivers::{device::gpu::VirtIOGpu as InnerDev, transport::Transport, Hal};
 
 /// The VirtIO GPU device driver.
@@ -30,6 +30,7 @@ impl<H: Hal, T: Transport> VirtIoGpuDev<H, T> {
             height,
             fb_base_vaddr,
             fb_size,
+            format: PixelFormat::Bgra8888,
         };
 
         Ok(Self {
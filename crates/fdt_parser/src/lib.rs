@@ -0,0 +1,295 @@
+//! A zero-copy parser of the flattened device tree (FDT), also known as the
+//! device tree blob (DTB).
+//!
+//! It follows the [Devicetree Specification v0.4][spec], and only reads the
+//! blob, so it needs no allocation and can be used before the heap is ready.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # let dtb: &[u8] = &[];
+//! use fdt_parser::Fdt;
+//!
+//! let fdt = Fdt::from_bytes(dtb).unwrap();
+//! for region in fdt.memory() {
+//!     println!("memory: {:#x}..{:#x}", region.address, region.end());
+//! }
+//! if let Some(uart) = fdt.find_compatible(&["arm,pl011"]).next() {
+//!     println!("UART: {:#x?}", uart.reg().next());
+//! }
+//! ```
+//!
+//! [spec]: https://github.com/devicetree-org/devicetree-specification/releases/tag/v0.4
+
+#![cfg_attr(not(test), no_std)]
+
+mod node;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::node::{AllNodes, CellList, Children, Node, Properties, Property, Reg, StrList};
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_HEADER_SIZE: usize = 40;
+/// The oldest version whose structure block is compatible with version 17.
+const FDT_LAST_COMP_VERSION: u32 = 16;
+
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+/// The error type of FDT parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdtError {
+    /// The pointer to the blob is null or not 8-byte aligned.
+    BadPtr,
+    /// The magic number of the header is not `0xd00dfeed`.
+    BadMagic,
+    /// The blob is of an incompatible version.
+    BadVersion,
+    /// The blob is shorter than its header says, or a block is out of it.
+    Truncated,
+}
+
+/// A region of memory, as in the `reg` property or the memory reservation
+/// block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// The start address.
+    pub address: u64,
+    /// The size in bytes.
+    pub size: u64,
+}
+
+impl MemoryRegion {
+    /// Returns the end address (exclusive), saturated at `u64::MAX`.
+    pub const fn end(&self) -> u64 {
+        self.address.saturating_add(self.size)
+    }
+}
+
+/// A parsed flattened device tree.
+#[derive(Clone, Copy)]
+pub struct Fdt<'a> {
+    data: &'a [u8],
+    structs: &'a [u8],
+    strings: &'a [u8],
+    mem_rsvmap: &'a [u8],
+}
+
+fn be32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn be64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Returns the NUL-terminated string at `offset`, without the terminator.
+fn cstr(data: &[u8], offset: usize) -> Option<&str> {
+    let bytes = data.get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0)?;
+    core::str::from_utf8(&bytes[..len]).ok()
+}
+
+const fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+impl<'a> Fdt<'a> {
+    /// Parses the header of the blob in `data`, and checks that all blocks
+    /// are in it.
+    ///
+    /// `data` may be longer than the blob, e.g., when the size is unknown.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, FdtError> {
+        let header = |index: usize| be32(data, index * 4).ok_or(FdtError::Truncated);
+        if header(0)? != FDT_MAGIC {
+            return Err(FdtError::BadMagic);
+        }
+        let total_size = header(1)? as usize;
+        if total_size < FDT_HEADER_SIZE || total_size > data.len() {
+            return Err(FdtError::Truncated);
+        }
+        if header(6)? > FDT_LAST_COMP_VERSION + 1 || header(5)? < FDT_LAST_COMP_VERSION {
+            return Err(FdtError::BadVersion);
+        }
+        let data = &data[..total_size];
+        let block = |offset: u32, size: u32| {
+            let start = offset as usize;
+            let end = start
+                .checked_add(size as usize)
+                .ok_or(FdtError::Truncated)?;
+            data.get(start..end).ok_or(FdtError::Truncated)
+        };
+        let structs = block(header(2)?, header(9)?)?;
+        let strings = block(header(3)?, header(8)?)?;
+        // the size of the memory reservation block is not in the header
+        let mem_rsvmap = data.get(header(4)? as usize..).ok_or(FdtError::Truncated)?;
+        Ok(Self {
+            data,
+            structs,
+            strings,
+            mem_rsvmap,
+        })
+    }
+
+    /// Parses the blob at `ptr`, whose size is read from its header.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a readable blob that lives as long as `'a`.
+    pub unsafe fn from_ptr(ptr: *const u8) -> Result<Self, FdtError> {
+        if ptr.is_null() || ptr as usize % 8 != 0 {
+            return Err(FdtError::BadPtr);
+        }
+        let header = core::slice::from_raw_parts(ptr, 8);
+        if be32(header, 0) != Some(FDT_MAGIC) {
+            return Err(FdtError::BadMagic);
+        }
+        let total_size = be32(header, 4).unwrap() as usize;
+        Self::from_bytes(core::slice::from_raw_parts(ptr, total_size))
+    }
+
+    /// Returns the whole blob.
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the size of the blob in bytes.
+    pub const fn total_size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the physical ID of the boot CPU.
+    pub fn boot_cpuid(&self) -> u32 {
+        be32(self.data, 28).unwrap()
+    }
+
+    /// Returns the regions in the memory reservation block, which must not be
+    /// used as normal memory.
+    pub fn memory_reservations(&self) -> impl Iterator<Item = MemoryRegion> + 'a {
+        let data = self.mem_rsvmap;
+        (0..)
+            .map(move |i| {
+                let address = be64(data, i * 16)?;
+                let size = be64(data, i * 16 + 8)?;
+                Some(MemoryRegion { address, size })
+            })
+            .map_while(|region| region.filter(|r| r.address != 0 || r.size != 0))
+    }
+
+    /// Returns the root node.
+    pub fn root(&self) -> Node<'a> {
+        // malformed blobs have a root without properties and children
+        Node::parse(*self, 0, node::CellSizes::default()).unwrap_or(Node::empty(*self))
+    }
+
+    /// Returns all nodes in depth-first order, starting from the root.
+    pub fn all_nodes(&self) -> AllNodes<'a> {
+        AllNodes::new(*self)
+    }
+
+    /// Finds the node by its path.
+    ///
+    /// The path is either absolute, or starts with an alias in `/aliases`.
+    /// The unit address of a node name can be omitted, then the first node
+    /// with the same base name matches, e.g., `/memory` matches
+    /// `/memory@40000000`.
+    pub fn find_node(&self, path: &str) -> Option<Node<'a>> {
+        if path.starts_with('/') {
+            return self.root().find_descendant(path);
+        }
+        let (alias, rest) = path.split_once('/').unwrap_or((path, ""));
+        let base = self.find_node("/aliases")?.property(alias)?.as_str()?;
+        if !base.starts_with('/') {
+            return None; // aliases must be absolute paths
+        }
+        self.find_node(base)?.find_descendant(rest)
+    }
+
+    /// Returns the nodes that are compatible with any of `compatible`.
+    pub fn find_compatible<'b>(
+        &self,
+        compatible: &'b [&'b str],
+    ) -> impl Iterator<Item = Node<'a>> + 'b
+    where
+        'a: 'b,
+    {
+        self.all_nodes()
+            .filter(move |node| node.is_compatible(compatible))
+    }
+
+    /// Finds the node whose `phandle` is `phandle`.
+    pub fn find_phandle(&self, phandle: u32) -> Option<Node<'a>> {
+        self.all_nodes()
+            .find(|node| node.phandle() == Some(phandle))
+    }
+
+    /// Returns the `/chosen` node.
+    pub fn chosen(&self) -> Option<Node<'a>> {
+        self.find_node("/chosen")
+    }
+
+    /// Returns the `bootargs` in `/chosen`.
+    pub fn bootargs(&self) -> Option<&'a str> {
+        self.chosen()?.property("bootargs")?.as_str()
+    }
+
+    /// Returns the node of the console device, referred by `stdout-path` in
+    /// `/chosen`.
+    pub fn stdout(&self) -> Option<Node<'a>> {
+        let path = self.chosen()?.property("stdout-path")?.as_str()?;
+        // strip options like ":115200n8"
+        let path = path.split(':').next()?;
+        self.find_node(path)
+    }
+
+    /// Returns the regions of physical memory, in the `reg` of all nodes
+    /// whose `device_type` is `"memory"`.
+    pub fn memory(&self) -> impl Iterator<Item = MemoryRegion> + 'a {
+        self.root()
+            .children()
+            .filter(|node| node.device_type() == Some("memory"))
+            .flat_map(|node| node.reg())
+    }
+
+    /// Returns the regions of reserved memory, in the `reg` of the children
+    /// of `/reserved-memory`.
+    ///
+    /// Dynamically allocated regions (with `size` but no `reg`) are not
+    /// included.
+    pub fn reserved_memory(&self) -> impl Iterator<Item = MemoryRegion> + 'a {
+        self.find_node("/reserved-memory")
+            .into_iter()
+            .flat_map(|node| node.children())
+            .filter(|node| node.is_available())
+            .flat_map(|node| node.reg())
+    }
+
+    /// Returns the available CPU nodes, the children of `/cpus` whose
+    /// `device_type` is `"cpu"`.
+    pub fn cpus(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.find_node("/cpus")
+            .into_iter()
+            .flat_map(|node| node.children())
+            .filter(|node| node.device_type() == Some("cpu") && node.is_available())
+    }
+
+    fn string_at(&self, offset: usize) -> Option<&'a str> {
+        cstr(self.strings, offset)
+    }
+}
+
+impl core::fmt::Debug for Fdt<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Fdt")
+            .field("ptr", &self.data.as_ptr())
+            .field("total_size", &self.total_size())
+            .finish()
+    }
+}
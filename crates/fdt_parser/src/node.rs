@@ -0,0 +1,488 @@
+use core::fmt;
+
+use crate::{align4, be32, cstr, Fdt, MemoryRegion};
+use crate::{FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_NOP, FDT_PROP};
+
+/// The maximum depth of nodes visited by [`AllNodes`].
+const MAX_DEPTH: usize = 16;
+
+/// The `#address-cells` and `#size-cells` of a node, which apply to the `reg`
+/// of its children.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CellSizes {
+    address: u32,
+    size: u32,
+}
+
+impl Default for CellSizes {
+    fn default() -> Self {
+        Self {
+            address: 2,
+            size: 1,
+        }
+    }
+}
+
+/// Returns the offset after the node beginning at `offset`, including all
+/// its properties and descendants.
+fn skip_node(structs: &[u8], offset: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut pos = offset;
+    loop {
+        match be32(structs, pos)? {
+            FDT_BEGIN_NODE => {
+                depth += 1;
+                let name = cstr(structs, pos + 4)?;
+                pos = align4(pos + 4 + name.len() + 1);
+            }
+            FDT_END_NODE => {
+                depth = depth.checked_sub(1)?;
+                pos += 4;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            FDT_PROP => pos = align4(pos + 12 + be32(structs, pos + 4)? as usize),
+            FDT_NOP => pos += 4,
+            _ => return None,
+        }
+    }
+}
+
+/// Reads a number of `cells` 32-bit cells, keeping only the lower 64 bits.
+fn read_cells(data: &[u8], cells: u32) -> Option<u64> {
+    (0..cells as usize).try_fold(0u64, |acc, i| {
+        let cell = be32(data, i * 4)?;
+        Some(acc.checked_shl(32).unwrap_or(0) | cell as u64)
+    })
+}
+
+/// A node in the device tree.
+#[derive(Clone, Copy)]
+pub struct Node<'a> {
+    fdt: Fdt<'a>,
+    name: &'a str,
+    /// The offset of the first token after the name in the structure block.
+    props_offset: usize,
+    parent_cells: CellSizes,
+}
+
+impl<'a> Node<'a> {
+    pub(crate) fn parse(fdt: Fdt<'a>, offset: usize, parent_cells: CellSizes) -> Option<Self> {
+        let mut offset = offset;
+        while be32(fdt.structs, offset)? == FDT_NOP {
+            offset += 4;
+        }
+        if be32(fdt.structs, offset)? != FDT_BEGIN_NODE {
+            return None;
+        }
+        let name = cstr(fdt.structs, offset + 4)?;
+        Some(Self {
+            fdt,
+            name,
+            props_offset: align4(offset + 4 + name.len() + 1),
+            parent_cells,
+        })
+    }
+
+    pub(crate) fn empty(fdt: Fdt<'a>) -> Self {
+        Self {
+            fdt,
+            name: "",
+            props_offset: fdt.structs.len(),
+            parent_cells: CellSizes::default(),
+        }
+    }
+
+    /// Returns the full name of the node, e.g., `uart@9000000`. The name of
+    /// the root is empty.
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the name without the unit address, e.g., `uart`.
+    pub fn basename(&self) -> &'a str {
+        self.name.split('@').next().unwrap_or_default()
+    }
+
+    /// Returns the unit address of the name, e.g., `9000000`.
+    pub fn unit_address(&self) -> Option<&'a str> {
+        self.name.split_once('@').map(|(_, addr)| addr)
+    }
+
+    /// Returns all properties of the node.
+    pub fn properties(&self) -> Properties<'a> {
+        Properties {
+            fdt: self.fdt,
+            offset: self.props_offset,
+        }
+    }
+
+    /// Finds the property by its name.
+    pub fn property(&self, name: &str) -> Option<Property<'a>> {
+        self.properties().find(|prop| prop.name == name)
+    }
+
+    /// Returns the direct children of the node.
+    pub fn children(&self) -> Children<'a> {
+        let mut props = self.properties();
+        for _ in props.by_ref() {}
+        Children {
+            fdt: self.fdt,
+            offset: props.offset,
+            cells: self.cell_sizes(),
+        }
+    }
+
+    /// Finds the descendant by its path relative to this node.
+    ///
+    /// The unit address can be omitted, then the first node with the same
+    /// base name matches, e.g., `memory` matches `memory@40000000`.
+    pub fn find_descendant(&self, path: &str) -> Option<Node<'a>> {
+        let mut node = *self;
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            node = node.children().find(|child| {
+                child.name == name || (!name.contains('@') && child.basename() == name)
+            })?;
+        }
+        Some(node)
+    }
+
+    /// Returns the strings in the `compatible` property.
+    pub fn compatible(&self) -> StrList<'a> {
+        self.property("compatible")
+            .map_or(StrList { data: &[] }, |prop| prop.as_str_list())
+    }
+
+    /// Whether the node is compatible with any of `compatible`.
+    pub fn is_compatible(&self, compatible: &[&str]) -> bool {
+        self.compatible().any(|c| compatible.contains(&c))
+    }
+
+    /// Returns the `device_type` property.
+    pub fn device_type(&self) -> Option<&'a str> {
+        self.property("device_type")?.as_str()
+    }
+
+    /// Whether the device is available, i.e., its `status` is `"okay"` or
+    /// missing.
+    pub fn is_available(&self) -> bool {
+        self.property("status")
+            .map_or(true, |prop| matches!(prop.as_str(), Some("okay" | "ok")))
+    }
+
+    /// Returns the `phandle` (or the legacy `linux,phandle`) property.
+    pub fn phandle(&self) -> Option<u32> {
+        self.property("phandle")
+            .or_else(|| self.property("linux,phandle"))?
+            .as_u32()
+    }
+
+    /// Returns the `#address-cells` of the node, which is 2 if missing.
+    pub fn address_cells(&self) -> u32 {
+        self.cell_sizes().address
+    }
+
+    /// Returns the `#size-cells` of the node, which is 1 if missing.
+    pub fn size_cells(&self) -> u32 {
+        self.cell_sizes().size
+    }
+
+    /// Returns the `#interrupt-cells` of the node, if it's an interrupt
+    /// controller.
+    pub fn interrupt_cells(&self) -> Option<u32> {
+        self.property("#interrupt-cells")?.as_u32()
+    }
+
+    /// Returns the regions in the `reg` property, whose cells are given by the
+    /// parent.
+    ///
+    /// The addresses are in the address space of the parent, i.e., they are
+    /// not translated by the `ranges` of the ancestors.
+    pub fn reg(&self) -> Reg<'a> {
+        Reg {
+            data: self.property("reg").map_or(&[], |prop| prop.value),
+            cells: self.parent_cells,
+        }
+    }
+
+    /// Returns the interrupt controller of the node, referred by its
+    /// `interrupt-parent`.
+    ///
+    /// Only the node itself and the root are looked up for the property, which
+    /// is enough for common device trees.
+    pub fn interrupt_parent(&self) -> Option<Node<'a>> {
+        let phandle = self
+            .property("interrupt-parent")
+            .or_else(|| self.fdt.root().property("interrupt-parent"))?
+            .as_u32()?;
+        self.fdt.find_phandle(phandle)
+    }
+
+    /// Returns the interrupt specifiers in the `interrupts` property, whose
+    /// cells are given by the `#interrupt-cells` of the interrupt parent.
+    pub fn interrupts(&self) -> impl Iterator<Item = CellList<'a>> + 'a {
+        let data = self
+            .property("interrupts")
+            .map_or(&[][..], |prop| prop.value);
+        let cells = if data.is_empty() {
+            1
+        } else {
+            self.interrupt_parent()
+                .and_then(|parent| parent.interrupt_cells())
+                .unwrap_or(1)
+        };
+        data.chunks_exact((cells as usize * 4).max(4))
+            .map(|data| CellList { data })
+    }
+
+    fn cell_sizes(&self) -> CellSizes {
+        let mut cells = CellSizes::default();
+        for prop in self.properties() {
+            match prop.name {
+                "#address-cells" => cells.address = prop.as_u32().unwrap_or(cells.address),
+                "#size-cells" => cells.size = prop.as_u32().unwrap_or(cells.size),
+                _ => {}
+            }
+        }
+        cells
+    }
+}
+
+impl fmt::Debug for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node").field("name", &self.name).finish()
+    }
+}
+
+/// A property of a node.
+#[derive(Debug, Clone, Copy)]
+pub struct Property<'a> {
+    /// The name of the property.
+    pub name: &'a str,
+    /// The raw value of the property.
+    pub value: &'a [u8],
+}
+
+impl<'a> Property<'a> {
+    /// Returns the value as a `u32`, if it's of one cell.
+    pub fn as_u32(&self) -> Option<u32> {
+        if self.value.len() != 4 {
+            return None;
+        }
+        be32(self.value, 0)
+    }
+
+    /// Returns the value as a `u64`, if it's of one or two cells.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.value.len() {
+            4 | 8 => read_cells(self.value, self.value.len() as u32 / 4),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string, without the terminating NUL.
+    pub fn as_str(&self) -> Option<&'a str> {
+        let bytes = self.value.strip_suffix(&[0])?;
+        core::str::from_utf8(bytes).ok()
+    }
+
+    /// Returns the value as a list of NUL-terminated strings.
+    pub fn as_str_list(&self) -> StrList<'a> {
+        StrList { data: self.value }
+    }
+
+    /// Returns the value as a list of 32-bit cells.
+    pub fn as_cells(&self) -> CellList<'a> {
+        CellList { data: self.value }
+    }
+}
+
+/// A list of 32-bit big-endian cells, e.g., an interrupt specifier.
+#[derive(Debug, Clone, Copy)]
+pub struct CellList<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CellList<'a> {
+    /// Returns the number of cells.
+    pub fn len(&self) -> usize {
+        self.data.len() / 4
+    }
+
+    /// Whether there are no cells.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the cell at `index`.
+    pub fn get(&self, index: usize) -> Option<u32> {
+        be32(self.data, index.checked_mul(4)?)
+    }
+
+    /// Returns an iterator over the cells.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + 'a {
+        let data = self.data;
+        (0..self.len()).map(move |i| be32(data, i * 4).unwrap())
+    }
+}
+
+/// An iterator over NUL-terminated strings, e.g., the `compatible` property.
+#[derive(Debug, Clone)]
+pub struct StrList<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for StrList<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let len = self.data.iter().position(|&b| b == 0);
+        let (s, rest) = match len {
+            Some(len) => (&self.data[..len], &self.data[len + 1..]),
+            None => (self.data, &[][..]),
+        };
+        self.data = rest;
+        match core::str::from_utf8(s) {
+            Ok(s) => Some(s),
+            Err(_) => {
+                self.data = &[];
+                None
+            }
+        }
+    }
+}
+
+/// An iterator over the regions in a `reg` property.
+#[derive(Debug, Clone)]
+pub struct Reg<'a> {
+    data: &'a [u8],
+    cells: CellSizes,
+}
+
+impl Iterator for Reg<'_> {
+    type Item = MemoryRegion;
+
+    fn next(&mut self) -> Option<MemoryRegion> {
+        let address_len = self.cells.address as usize * 4;
+        let entry_len = address_len + self.cells.size as usize * 4;
+        if entry_len == 0 || self.data.len() < entry_len {
+            return None;
+        }
+        let address = read_cells(self.data, self.cells.address)?;
+        let size = read_cells(&self.data[address_len..], self.cells.size)?;
+        self.data = &self.data[entry_len..];
+        Some(MemoryRegion { address, size })
+    }
+}
+
+/// An iterator over the properties of a node.
+#[derive(Debug, Clone)]
+pub struct Properties<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+}
+
+impl<'a> Iterator for Properties<'a> {
+    type Item = Property<'a>;
+
+    fn next(&mut self) -> Option<Property<'a>> {
+        let structs = self.fdt.structs;
+        loop {
+            match be32(structs, self.offset)? {
+                FDT_NOP => self.offset += 4,
+                FDT_PROP => {
+                    let len = be32(structs, self.offset + 4)? as usize;
+                    let name_offset = be32(structs, self.offset + 8)? as usize;
+                    let start = self.offset + 12;
+                    let value = structs.get(start..start.checked_add(len)?)?;
+                    let name = self.fdt.string_at(name_offset)?;
+                    self.offset = align4(start + len);
+                    return Some(Property { name, value });
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// An iterator over the children of a node.
+#[derive(Debug, Clone)]
+pub struct Children<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+    cells: CellSizes,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let structs = self.fdt.structs;
+        loop {
+            match be32(structs, self.offset)? {
+                FDT_NOP => self.offset += 4,
+                FDT_BEGIN_NODE => {
+                    let child = Node::parse(self.fdt, self.offset, self.cells)?;
+                    // stop at malformed nodes
+                    self.offset = skip_node(structs, self.offset).unwrap_or(usize::MAX);
+                    return Some(child);
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// An iterator over all nodes in depth-first order.
+#[derive(Debug, Clone)]
+pub struct AllNodes<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+    depth: usize,
+    cells: [CellSizes; MAX_DEPTH],
+}
+
+impl<'a> AllNodes<'a> {
+    pub(crate) fn new(fdt: Fdt<'a>) -> Self {
+        Self {
+            fdt,
+            offset: 0,
+            depth: 0,
+            cells: [CellSizes::default(); MAX_DEPTH],
+        }
+    }
+}
+
+impl<'a> Iterator for AllNodes<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let structs = self.fdt.structs;
+        loop {
+            match be32(structs, self.offset)? {
+                FDT_BEGIN_NODE if self.depth + 1 < MAX_DEPTH => {
+                    let node = Node::parse(self.fdt, self.offset, self.cells[self.depth])?;
+                    self.depth += 1;
+                    self.cells[self.depth] = node.cell_sizes();
+                    self.offset = node.props_offset;
+                    return Some(node);
+                }
+                FDT_END_NODE => {
+                    self.depth = self.depth.checked_sub(1)?;
+                    self.offset += 4;
+                }
+                FDT_PROP => {
+                    self.offset =
+                        align4(self.offset + 12 + be32(structs, self.offset + 4)? as usize)
+                }
+                FDT_NOP => self.offset += 4,
+                FDT_END => return None,
+                _ => return None, // malformed, or too deep
+            }
+        }
+    }
+}
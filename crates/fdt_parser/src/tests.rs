@@ -0,0 +1,398 @@
+use super::*;
+
+/// Builds a device tree blob, like `dtc` does.
+#[derive(Default)]
+struct Builder {
+    structs: Vec<u8>,
+    strings: Vec<u8>,
+    reservations: Vec<(u64, u64)>,
+}
+
+impl Builder {
+    fn token(&mut self, token: u32) -> &mut Self {
+        self.structs.extend_from_slice(&token.to_be_bytes());
+        self
+    }
+
+    fn pad(&mut self) {
+        self.structs.resize(align4(self.structs.len()), 0);
+    }
+
+    fn begin(&mut self, name: &str) -> &mut Self {
+        self.token(FDT_BEGIN_NODE);
+        self.structs.extend_from_slice(name.as_bytes());
+        self.structs.push(0);
+        self.pad();
+        self
+    }
+
+    fn end(&mut self) -> &mut Self {
+        self.token(FDT_END_NODE)
+    }
+
+    fn prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+        let name_offset = self.strings.len();
+        self.strings.extend_from_slice(name.as_bytes());
+        self.strings.push(0);
+        self.token(FDT_PROP)
+            .token(value.len() as u32)
+            .token(name_offset as u32);
+        self.structs.extend_from_slice(value);
+        self.pad();
+        self
+    }
+
+    fn prop_cells(&mut self, name: &str, cells: &[u32]) -> &mut Self {
+        let value: Vec<u8> = cells.iter().flat_map(|c| c.to_be_bytes()).collect();
+        self.prop(name, &value)
+    }
+
+    fn prop_str(&mut self, name: &str, s: &str) -> &mut Self {
+        self.prop(name, format!("{}\0", s).as_bytes())
+    }
+
+    fn build(&mut self) -> Vec<u8> {
+        self.token(FDT_END);
+        let rsvmap_offset = FDT_HEADER_SIZE;
+        let structs_offset = rsvmap_offset + (self.reservations.len() + 1) * 16;
+        let strings_offset = structs_offset + self.structs.len();
+        let total_size = strings_offset + self.strings.len();
+        let header = [
+            FDT_MAGIC,
+            total_size as u32,
+            structs_offset as u32,
+            strings_offset as u32,
+            rsvmap_offset as u32,
+            17, // version
+            16, // last compatible version
+            1,  // boot CPU
+            self.strings.len() as u32,
+            self.structs.len() as u32,
+        ];
+        let mut blob: Vec<u8> = header.iter().flat_map(|h| h.to_be_bytes()).collect();
+        for &(address, size) in self.reservations.iter().chain([(0, 0)].iter()) {
+            blob.extend_from_slice(&address.to_be_bytes());
+            blob.extend_from_slice(&size.to_be_bytes());
+        }
+        blob.extend_from_slice(&self.structs);
+        blob.extend_from_slice(&self.strings);
+        blob
+    }
+}
+
+/// A device tree like that of the QEMU `virt` machine.
+fn virt_dtb() -> Vec<u8> {
+    let mut b = Builder {
+        reservations: vec![(0x4800_0000, 0x1000)],
+        ..Default::default()
+    };
+    b.begin("")
+        .prop_cells("#address-cells", &[2])
+        .prop_cells("#size-cells", &[2])
+        .prop_cells("interrupt-parent", &[0x8001])
+        .prop_str("compatible", "linux,dummy-virt");
+
+    b.begin("aliases")
+        .prop_str("serial0", "/pl011@9000000")
+        .end();
+    b.begin("chosen")
+        .prop_str("bootargs", "console=ttyAMA0")
+        .prop_str("stdout-path", "serial0:115200n8")
+        .end();
+    b.begin("memory@40000000")
+        .prop_str("device_type", "memory")
+        .prop_cells(
+            "reg",
+            &[0, 0x4000_0000, 0, 0x800_0000, 1, 0, 0, 0x1000_0000],
+        )
+        .end();
+
+    b.begin("reserved-memory")
+        .prop_cells("#address-cells", &[2])
+        .prop_cells("#size-cells", &[2])
+        .prop("ranges", &[]);
+    b.begin("firmware@44000000")
+        .prop_cells("reg", &[0, 0x4400_0000, 0, 0x10_0000])
+        .end();
+    b.begin("unused@45000000")
+        .prop_str("status", "disabled")
+        .prop_cells("reg", &[0, 0x4500_0000, 0, 0x10_0000])
+        .end();
+    b.end();
+
+    b.begin("cpus")
+        .prop_cells("#address-cells", &[1])
+        .prop_cells("#size-cells", &[0]);
+    for (i, name) in ["cpu@0", "cpu@1", "cpu@2"].iter().enumerate() {
+        b.begin(name)
+            .prop_str("device_type", "cpu")
+            .prop_cells("reg", &[i as u32]);
+        if i == 2 {
+            b.prop_str("status", "disabled");
+        }
+        b.end();
+    }
+    b.begin("cpu-map").end();
+    b.end();
+
+    b.token(FDT_NOP);
+    b.begin("intc@8000000")
+        .prop_str("compatible", "arm,cortex-a15-gic")
+        .prop_cells("#interrupt-cells", &[3])
+        .prop("interrupt-controller", &[])
+        .prop_cells(
+            "reg",
+            &[0, 0x800_0000, 0, 0x1_0000, 0, 0x801_0000, 0, 0x1_0000],
+        )
+        .prop_cells("phandle", &[0x8001])
+        .end();
+    b.begin("pl011@9000000")
+        .prop("compatible", b"arm,pl011\0arm,primecell\0")
+        .prop_cells("reg", &[0, 0x900_0000, 0, 0x1000])
+        .prop_cells("interrupts", &[0, 1, 4])
+        .token(FDT_NOP)
+        .end();
+
+    b.begin("soc")
+        .prop_cells("#address-cells", &[1])
+        .prop_cells("#size-cells", &[1]);
+    for (i, name) in ["virtio_mmio@a000000", "virtio_mmio@a000200"]
+        .iter()
+        .enumerate()
+    {
+        b.begin(name)
+            .prop_str("compatible", "virtio,mmio")
+            .prop_cells("reg", &[0xa00_0000 + 0x200 * i as u32, 0x200])
+            .prop_cells("interrupts", &[0, 0x10 + i as u32, 1])
+            .end();
+    }
+    b.end();
+
+    b.end().build()
+}
+
+#[test]
+fn test_header() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    assert_eq!(fdt.total_size(), dtb.len());
+    assert_eq!(fdt.boot_cpuid(), 1);
+    assert_eq!(
+        fdt.memory_reservations().collect::<Vec<_>>(),
+        [MemoryRegion {
+            address: 0x4800_0000,
+            size: 0x1000
+        }]
+    );
+
+    // trailing bytes are ignored
+    let mut longer = dtb.clone();
+    longer.extend_from_slice(&[0xff; 16]);
+    assert_eq!(Fdt::from_bytes(&longer).unwrap().total_size(), dtb.len());
+
+    // aligned to 8 bytes
+    let words: Vec<u64> = dtb
+        .chunks(8)
+        .map(|c| {
+            let mut word = [0; 8];
+            word[..c.len()].copy_from_slice(c);
+            u64::from_ne_bytes(word)
+        })
+        .collect();
+    let fdt = unsafe { Fdt::from_ptr(words.as_ptr() as *const u8) }.unwrap();
+    assert_eq!(fdt.as_bytes(), &dtb[..]);
+    assert_eq!(
+        unsafe { Fdt::from_ptr(core::ptr::null()) }.unwrap_err(),
+        FdtError::BadPtr
+    );
+}
+
+#[test]
+fn test_bad_header() {
+    let dtb = virt_dtb();
+    assert_eq!(
+        Fdt::from_bytes(&dtb[..dtb.len() - 1]).unwrap_err(),
+        FdtError::Truncated
+    );
+    assert_eq!(Fdt::from_bytes(&dtb[..3]).unwrap_err(), FdtError::Truncated);
+
+    let mut bad = dtb.clone();
+    bad[0] = 0;
+    assert_eq!(Fdt::from_bytes(&bad).unwrap_err(), FdtError::BadMagic);
+
+    let mut bad = dtb.clone();
+    bad[27] = 18; // last compatible version
+    assert_eq!(Fdt::from_bytes(&bad).unwrap_err(), FdtError::BadVersion);
+
+    let mut bad = dtb;
+    bad[35] += 4; // size of the strings block
+    assert_eq!(Fdt::from_bytes(&bad).unwrap_err(), FdtError::Truncated);
+}
+
+#[test]
+fn test_find_node() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    assert_eq!(fdt.root().name(), "");
+    assert_eq!(fdt.find_node("/").unwrap().name(), "");
+    assert_eq!(fdt.find_node("/memory").unwrap().name(), "memory@40000000");
+    assert!(fdt.find_node("/memory@40000000").is_some());
+    assert!(fdt.find_node("/memory@0").is_none());
+    assert!(fdt.find_node("/nonexistent").is_none());
+
+    let node = fdt.find_node("/soc/virtio_mmio@a000200").unwrap();
+    assert_eq!(node.basename(), "virtio_mmio");
+    assert_eq!(node.unit_address(), Some("a000200"));
+    assert_eq!(
+        fdt.find_node("/soc/virtio_mmio").unwrap().name(),
+        "virtio_mmio@a000000"
+    );
+
+    assert_eq!(fdt.find_node("serial0").unwrap().name(), "pl011@9000000");
+    assert!(fdt.find_node("serial1").is_none());
+    assert_eq!(fdt.find_phandle(0x8001).unwrap().name(), "intc@8000000");
+    assert!(fdt.find_phandle(1).is_none());
+}
+
+#[test]
+fn test_all_nodes() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    let names: Vec<_> = fdt.all_nodes().map(|node| node.name()).collect();
+    assert_eq!(
+        names,
+        [
+            "",
+            "aliases",
+            "chosen",
+            "memory@40000000",
+            "reserved-memory",
+            "firmware@44000000",
+            "unused@45000000",
+            "cpus",
+            "cpu@0",
+            "cpu@1",
+            "cpu@2",
+            "cpu-map",
+            "intc@8000000",
+            "pl011@9000000",
+            "soc",
+            "virtio_mmio@a000000",
+            "virtio_mmio@a000200",
+        ]
+    );
+    let children: Vec<_> = fdt.root().children().map(|node| node.name()).collect();
+    assert_eq!(children.len(), 8);
+    assert_eq!(children[7], "soc");
+}
+
+#[test]
+fn test_properties() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    let root = fdt.root();
+    assert_eq!(root.address_cells(), 2);
+    assert_eq!(root.size_cells(), 2);
+    assert_eq!(root.properties().count(), 4);
+    assert_eq!(
+        root.property("compatible").unwrap().as_str(),
+        Some("linux,dummy-virt")
+    );
+    assert_eq!(root.property("#size-cells").unwrap().as_u64(), Some(2));
+    assert!(root.property("model").is_none());
+    assert_eq!(fdt.bootargs(), Some("console=ttyAMA0"));
+
+    let reg = fdt.find_node("/memory").unwrap().property("reg").unwrap();
+    assert_eq!(reg.as_u32(), None);
+    assert_eq!(reg.as_cells().len(), 8);
+    assert_eq!(reg.as_cells().get(1), Some(0x4000_0000));
+    let cpus = fdt.find_node("/cpus").unwrap();
+    assert_eq!((cpus.address_cells(), cpus.size_cells()), (1, 0));
+    // defaults
+    let chosen = fdt.chosen().unwrap();
+    assert_eq!((chosen.address_cells(), chosen.size_cells()), (2, 1));
+    assert!(chosen.is_available());
+    assert!(!fdt.find_node("/cpus/cpu@2").unwrap().is_available());
+}
+
+#[test]
+fn test_memory() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    let memory: Vec<_> = fdt.memory().collect();
+    assert_eq!(
+        memory,
+        [
+            MemoryRegion {
+                address: 0x4000_0000,
+                size: 0x800_0000
+            },
+            MemoryRegion {
+                address: 0x1_0000_0000,
+                size: 0x1000_0000
+            },
+        ]
+    );
+    assert_eq!(memory[0].end(), 0x4800_0000);
+
+    let reserved: Vec<_> = fdt.reserved_memory().collect();
+    assert_eq!(
+        reserved,
+        [MemoryRegion {
+            address: 0x4400_0000,
+            size: 0x10_0000
+        }]
+    );
+}
+
+#[test]
+fn test_cpus() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+    let ids: Vec<_> = fdt
+        .cpus()
+        .map(|cpu| cpu.reg().next().unwrap().address)
+        .collect();
+    assert_eq!(ids, [0, 1]);
+}
+
+#[test]
+fn test_devices() {
+    let dtb = virt_dtb();
+    let fdt = Fdt::from_bytes(&dtb).unwrap();
+
+    let uart = fdt.stdout().unwrap();
+    assert_eq!(
+        uart.compatible().collect::<Vec<_>>(),
+        ["arm,pl011", "arm,primecell"]
+    );
+    assert!(uart.is_compatible(&["ns16550a", "arm,pl011"]));
+    assert!(!uart.is_compatible(&["ns16550a"]));
+    assert_eq!(
+        uart.reg().collect::<Vec<_>>(),
+        [MemoryRegion {
+            address: 0x900_0000,
+            size: 0x1000
+        }]
+    );
+    let intc = uart.interrupt_parent().unwrap();
+    assert_eq!(intc.interrupt_cells(), Some(3));
+    assert_eq!(intc.reg().count(), 2);
+    let irqs: Vec<Vec<u32>> = uart.interrupts().map(|irq| irq.iter().collect()).collect();
+    assert_eq!(irqs, [[0, 1, 4]]);
+    assert_eq!(intc.interrupts().count(), 0);
+
+    let virtio: Vec<_> = fdt
+        .find_compatible(&["virtio,mmio"])
+        .map(|node| {
+            let reg = node.reg().next().unwrap();
+            let irq = node.interrupts().next().unwrap().get(1).unwrap();
+            (reg.address, reg.size, irq)
+        })
+        .collect();
+    assert_eq!(
+        virtio,
+        [(0xa00_0000, 0x200, 0x10), (0xa00_0200, 0x200, 0x11)]
+    );
+    assert_eq!(fdt.find_compatible(&["ns16550a"]).count(), 0);
+}
@@ -25,6 +25,10 @@
 //!    the preemption enable/disable operations will be no-ops. This feature is
 //!    disabled by default.
 //!
+//! In user-mode (not `target_os = "none"`), IRQs are never disabled, but the
+//! preemption is still disabled/enabled through [`KernelGuardIf`] if the
+//! `preempt` feature is enabled.
+//!
 //! # Examples
 //!
 //! ```
@@ -99,6 +103,20 @@ cfg_if::cfg_if! {
         /// first, followed by local IRQs. When leaving the critical section, it
         /// re-enables local IRQs first, followed by kernel preemption.
         pub struct NoPreemptIrqSave(usize);
+    } else if #[cfg(feature = "preempt")] {
+        // IRQs can't be disabled in user-mode, but the preemption of the crate
+        // user is still counted, e.g., to test a preemptive scheduler on the
+        // host.
+
+        /// Alias of [`NoOp`].
+        pub type IrqSave = NoOp;
+
+        /// A guard that disables/enables kernel preemption around the critical
+        /// section.
+        pub struct NoPreempt;
+
+        /// Alias of [`NoPreempt`].
+        pub type NoPreemptIrqSave = NoPreempt;
     } else {
         /// Alias of [`NoOp`].
         pub type IrqSave = NoOp;
@@ -147,20 +165,6 @@ mod imp {
         }
     }
 
-    impl BaseGuard for NoPreempt {
-        type State = ();
-        fn acquire() -> Self::State {
-            // disable preempt
-            #[cfg(feature = "preempt")]
-            crate_interface::call_interface!(KernelGuardIf::disable_preempt);
-        }
-        fn release(_state: Self::State) {
-            // enable preempt
-            #[cfg(feature = "preempt")]
-            crate_interface::call_interface!(KernelGuardIf::enable_preempt);
-        }
-    }
-
     impl BaseGuard for NoPreemptIrqSave {
         type State = usize;
         fn acquire() -> Self::State {
@@ -198,40 +202,59 @@ mod imp {
         }
     }
 
-    impl NoPreempt {
-        /// Creates a new [`NoPreempt`] guard.
+    impl NoPreemptIrqSave {
+        /// Creates a new [`NoPreemptIrqSave`] guard.
         pub fn new() -> Self {
-            Self::acquire();
-            Self
+            Self(Self::acquire())
         }
     }
 
-    impl Drop for NoPreempt {
+    impl Drop for NoPreemptIrqSave {
         fn drop(&mut self) {
-            Self::release(())
+            Self::release(self.0)
         }
     }
 
-    impl Default for NoPreempt {
+    impl Default for NoPreemptIrqSave {
         fn default() -> Self {
             Self::new()
         }
     }
+}
 
-    impl NoPreemptIrqSave {
-        /// Creates a new [`NoPreemptIrqSave`] guard.
+#[cfg(any(target_os = "none", doc, feature = "preempt"))]
+mod imp_preempt {
+    use super::*;
+
+    impl BaseGuard for NoPreempt {
+        type State = ();
+        fn acquire() -> Self::State {
+            // disable preempt
+            #[cfg(feature = "preempt")]
+            crate_interface::call_interface!(KernelGuardIf::disable_preempt);
+        }
+        fn release(_state: Self::State) {
+            // enable preempt
+            #[cfg(feature = "preempt")]
+            crate_interface::call_interface!(KernelGuardIf::enable_preempt);
+        }
+    }
+
+    impl NoPreempt {
+        /// Creates a new [`NoPreempt`] guard.
         pub fn new() -> Self {
-            Self(Self::acquire())
+            Self::acquire();
+            Self
         }
     }
 
-    impl Drop for NoPreemptIrqSave {
+    impl Drop for NoPreempt {
         fn drop(&mut self) {
-            Self::release(self.0)
+            Self::release(())
         }
     }
 
-    impl Default for NoPreemptIrqSave {
+    impl Default for NoPreempt {
         fn default() -> Self {
             Self::new()
         }
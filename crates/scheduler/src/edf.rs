@@ -0,0 +1,301 @@
+use alloc::{collections::BTreeMap, collections::VecDeque, sync::Arc};
+use core::ops::Deref;
+use core::sync::atomic::{AtomicBool, AtomicI64, AtomicIsize, AtomicU64, AtomicUsize, Ordering};
+
+use crate::BaseScheduler;
+
+/// Time slice of best-effort (non real-time) tasks, in ticks.
+const BE_TIME_SLICE: isize = 5;
+
+/// Real-time parameters of a periodic task.
+///
+/// All values are in the same time unit as the ones passed to
+/// [`EDFTask::release_job`], e.g., nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RtParams {
+    /// The interval between two consecutive job releases.
+    pub period: u64,
+    /// The maximum execution time of each job.
+    pub budget: u64,
+    /// The relative deadline of each job since its release.
+    pub deadline: u64,
+}
+
+impl RtParams {
+    /// The utilization of a task that occupies a whole CPU.
+    pub const FULL_UTILIZATION: u64 = 1_000_000;
+
+    /// Creates real-time parameters with an implicit deadline, i.e., the
+    /// relative deadline equals to the period.
+    pub const fn new(period: u64, budget: u64) -> Self {
+        Self {
+            period,
+            budget,
+            deadline: period,
+        }
+    }
+
+    /// Sets a constrained relative deadline which is no more than the period.
+    pub const fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Whether `0 < budget <= deadline <= period` holds.
+    pub const fn is_valid(&self) -> bool {
+        self.budget > 0 && self.budget <= self.deadline && self.deadline <= self.period
+    }
+
+    /// Returns the CPU utilization (`budget / period`) scaled by
+    /// [`RtParams::FULL_UTILIZATION`], rounded up.
+    pub const fn utilization(&self) -> u64 {
+        ((self.budget as u128 * Self::FULL_UTILIZATION as u128).div_ceil(self.period as u128))
+            as u64
+    }
+}
+
+/// A task wrapper for the [`EDFScheduler`].
+///
+/// A task without [`RtParams`] is a best-effort task, which only runs when no
+/// real-time task is ready.
+pub struct EDFTask<T> {
+    inner: T,
+    is_rt: AtomicBool,
+    period: AtomicU64,
+    rel_deadline: AtomicU64,
+    budget: AtomicU64,
+    /// Absolute deadline of the current job, used for scheduling.
+    deadline: AtomicU64,
+    /// Absolute deadline of the current job when it's released, which is not
+    /// postponed by budget overruns.
+    job_deadline: AtomicU64,
+    /// Remaining budget of the current job.
+    remaining: AtomicI64,
+    /// Remaining time slice (in ticks) of a best-effort task.
+    time_slice: AtomicIsize,
+    deadline_misses: AtomicUsize,
+    id: AtomicUsize,
+}
+
+impl<T> EDFTask<T> {
+    /// Creates a new best-effort [`EDFTask`] from the inner task struct.
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            is_rt: AtomicBool::new(false),
+            period: AtomicU64::new(0),
+            rel_deadline: AtomicU64::new(0),
+            budget: AtomicU64::new(0),
+            deadline: AtomicU64::new(0),
+            job_deadline: AtomicU64::new(0),
+            remaining: AtomicI64::new(0),
+            time_slice: AtomicIsize::new(BE_TIME_SLICE),
+            deadline_misses: AtomicUsize::new(0),
+            id: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the real-time parameters, or [`None`] for a best-effort task.
+    pub fn rt_params(&self) -> Option<RtParams> {
+        if self.is_rt() {
+            Some(RtParams {
+                period: self.period.load(Ordering::Acquire),
+                budget: self.budget.load(Ordering::Acquire),
+                deadline: self.rel_deadline.load(Ordering::Acquire),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Sets the real-time parameters, or makes it a best-effort task if
+    /// `params` is [`None`].
+    ///
+    /// It must not be called while the task is in the scheduler, as its
+    /// position in the ready queue depends on these parameters.
+    pub fn set_rt_params(&self, params: Option<RtParams>) {
+        if let Some(params) = params {
+            self.period.store(params.period, Ordering::Release);
+            self.budget.store(params.budget, Ordering::Release);
+            self.rel_deadline.store(params.deadline, Ordering::Release);
+        }
+        self.is_rt.store(params.is_some(), Ordering::Release);
+    }
+
+    /// Releases a new job of a real-time task at time `now`, which sets its
+    /// absolute deadline and replenishes its budget.
+    ///
+    /// Like [`EDFTask::set_rt_params`], it must not be called while the task is
+    /// in the scheduler.
+    pub fn release_job(&self, now: u64) {
+        let deadline = now + self.rel_deadline.load(Ordering::Acquire);
+        self.deadline.store(deadline, Ordering::Release);
+        self.job_deadline.store(deadline, Ordering::Release);
+        let budget = self.budget.load(Ordering::Acquire);
+        self.remaining.store(budget as i64, Ordering::Release);
+    }
+
+    /// Completes the current job at time `now`. Returns `false` and counts a
+    /// deadline miss if its deadline has passed.
+    ///
+    /// The deadline is the one set by [`EDFTask::release_job`], even if it has
+    /// been postponed for a budget overrun.
+    pub fn complete_job(&self, now: u64) -> bool {
+        if now > self.job_deadline.load(Ordering::Acquire) {
+            self.deadline_misses.fetch_add(1, Ordering::Relaxed);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Returns the absolute deadline of the current job used for scheduling,
+    /// which is postponed by a period on each budget overrun.
+    pub fn deadline(&self) -> u64 {
+        self.deadline.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of jobs that completed after their deadlines.
+    pub fn deadline_misses(&self) -> usize {
+        self.deadline_misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn is_rt(&self) -> bool {
+        self.is_rt.load(Ordering::Acquire)
+    }
+
+    fn key(&self) -> (u64, usize) {
+        (self.deadline(), self.id.load(Ordering::Acquire))
+    }
+
+    /// Consumes the budget of a tick. If it's used up, postpones the deadline
+    /// by a period and replenishes the budget (the CBS rule), so an overrunning
+    /// task cannot exceed its reserved bandwidth. Returns `true` in this case.
+    fn consume_budget(&self, tick: u64) -> bool {
+        let remaining = self.remaining.fetch_sub(tick as i64, Ordering::AcqRel) - tick as i64;
+        if remaining <= 0 {
+            let period = self.period.load(Ordering::Acquire);
+            let budget = self.budget.load(Ordering::Acquire);
+            self.deadline.fetch_add(period, Ordering::AcqRel);
+            self.remaining.fetch_add(budget as i64, Ordering::AcqRel);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T> Deref for EDFTask<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// An [Earliest Deadline First][1] (EDF) preemptive real-time scheduler.
+///
+/// Real-time tasks are always picked in the order of their absolute deadlines,
+/// and each job can run at most its budget before its deadline is postponed by
+/// a period ([Constant Bandwidth Server][2]). Best-effort tasks are scheduled
+/// in round-robin when there are no ready real-time tasks.
+///
+/// `TICK` is the duration of a timer tick, in the same time unit as
+/// [`RtParams`].
+///
+/// [1]: https://en.wikipedia.org/wiki/Earliest_deadline_first_scheduling
+/// [2]: https://en.wikipedia.org/wiki/Constant_bandwidth_server
+pub struct EDFScheduler<T, const TICK: u64> {
+    rt_queue: BTreeMap<(u64, usize), Arc<EDFTask<T>>>, // (deadline, taskid)
+    be_queue: VecDeque<Arc<EDFTask<T>>>,
+    id_pool: usize,
+}
+
+impl<T, const TICK: u64> EDFScheduler<T, TICK> {
+    /// Creates a new empty [`EDFScheduler`].
+    pub const fn new() -> Self {
+        Self {
+            rt_queue: BTreeMap::new(),
+            be_queue: VecDeque::new(),
+            id_pool: 0,
+        }
+    }
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Earliest Deadline First"
+    }
+
+    fn insert_rt(&mut self, task: Arc<EDFTask<T>>) {
+        self.id_pool += 1;
+        task.id.store(self.id_pool, Ordering::Release);
+        self.rt_queue.insert(task.key(), task);
+    }
+}
+
+impl<T, const TICK: u64> BaseScheduler for EDFScheduler<T, TICK> {
+    type SchedItem = Arc<EDFTask<T>>;
+
+    fn init(&mut self) {}
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        if task.is_rt() {
+            self.insert_rt(task);
+        } else {
+            self.be_queue.push_back(task);
+        }
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        if task.is_rt() {
+            self.rt_queue.remove(&task.key())
+        } else {
+            self.be_queue
+                .iter()
+                .position(|t| Arc::ptr_eq(t, task))
+                .and_then(|idx| self.be_queue.remove(idx))
+        }
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        if let Some((_, task)) = self.rt_queue.pop_first() {
+            Some(task)
+        } else {
+            self.be_queue.pop_front()
+        }
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        if prev.is_rt() {
+            // the task will be placed after tasks with the same deadline
+            self.insert_rt(prev);
+        } else if prev.time_slice.load(Ordering::Acquire) > 0 && preempt {
+            self.be_queue.push_front(prev);
+        } else {
+            prev.time_slice.store(BE_TIME_SLICE, Ordering::Release);
+            self.be_queue.push_back(prev);
+        }
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        if current.is_rt() {
+            let postponed = current.consume_budget(TICK);
+            postponed
+                || self
+                    .rt_queue
+                    .first_key_value()
+                    .is_some_and(|(&(deadline, _), _)| deadline < current.deadline())
+        } else {
+            let old_slice = current.time_slice.fetch_sub(1, Ordering::Release);
+            old_slice <= 1 || !self.rt_queue.is_empty()
+        }
+    }
+
+    fn set_priority(&mut self, _task: &Self::SchedItem, _prio: isize) -> bool {
+        false
+    }
+}
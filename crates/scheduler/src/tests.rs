@@ -82,3 +82,114 @@ macro_rules! def_test_sched {
 def_test_sched!(fifo, FifoScheduler::<usize>, FifoTask::<usize>);
 def_test_sched!(rr, RRScheduler::<usize, 5>, RRTask::<usize, 5>);
 def_test_sched!(cfs, CFScheduler::<usize>, CFSTask::<usize>);
+def_test_sched!(edf, EDFScheduler::<usize, 1>, EDFTask::<usize>);
+
+mod edf_rt {
+    use crate::*;
+    use alloc::sync::Arc;
+
+    fn rt_task(id: usize, params: RtParams, release: u64) -> Arc<EDFTask<usize>> {
+        let task = Arc::new(EDFTask::new(id));
+        task.set_rt_params(Some(params));
+        task.release_job(release);
+        task
+    }
+
+    #[test]
+    fn test_deadline_order() {
+        let mut scheduler = EDFScheduler::<usize, 1>::new();
+        scheduler.add_task(Arc::new(EDFTask::new(0))); // best-effort
+        scheduler.add_task(rt_task(1, RtParams::new(30, 5), 0));
+        scheduler.add_task(rt_task(2, RtParams::new(10, 5), 0));
+        scheduler.add_task(rt_task(3, RtParams::new(100, 5).with_deadline(20), 0));
+
+        let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+            .map(|t| *t.inner())
+            .collect();
+        assert_eq!(order, [2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn test_preempt() {
+        let mut scheduler = EDFScheduler::<usize, 1>::new();
+        let be = Arc::new(EDFTask::new(0));
+        let rt1 = rt_task(1, RtParams::new(50, 10), 0);
+        let rt2 = rt_task(2, RtParams::new(20, 10), 0);
+
+        // best-effort tasks are preempted once a real-time task is ready
+        assert!(!scheduler.task_tick(&be));
+        scheduler.add_task(rt1.clone());
+        assert!(scheduler.task_tick(&be));
+        scheduler.put_prev_task(be, true);
+
+        // real-time tasks are preempted by ones with earlier deadlines
+        let curr = scheduler.pick_next_task().unwrap();
+        assert!(Arc::ptr_eq(&curr, &rt1));
+        assert!(!scheduler.task_tick(&curr));
+        scheduler.add_task(rt2.clone());
+        assert!(scheduler.task_tick(&curr));
+        scheduler.put_prev_task(curr, true);
+        assert!(Arc::ptr_eq(&scheduler.pick_next_task().unwrap(), &rt2));
+    }
+
+    #[test]
+    fn test_budget_overrun() {
+        let mut scheduler = EDFScheduler::<usize, 1>::new();
+        let rt1 = rt_task(1, RtParams::new(10, 3), 0);
+        let rt2 = rt_task(2, RtParams::new(15, 3), 0);
+        scheduler.add_task(rt2.clone());
+
+        // the budget of rt1 is used up after 3 ticks, then its deadline is
+        // postponed and rt2 becomes the earliest one.
+        assert!(!scheduler.task_tick(&rt1));
+        assert!(!scheduler.task_tick(&rt1));
+        assert!(scheduler.task_tick(&rt1));
+        assert_eq!(rt1.deadline(), 20);
+        scheduler.put_prev_task(rt1.clone(), true);
+        assert!(Arc::ptr_eq(&scheduler.pick_next_task().unwrap(), &rt2));
+
+        // removal still works after the deadline changes
+        assert!(scheduler.remove_task(&rt1).is_some());
+        assert!(scheduler.pick_next_task().is_none());
+    }
+
+    #[test]
+    fn test_deadline_miss() {
+        let task = rt_task(1, RtParams::new(10, 2), 100);
+        assert!(task.complete_job(105));
+        task.release_job(110);
+        assert!(!task.complete_job(121));
+        assert_eq!(task.deadline_misses(), 1);
+    }
+
+    #[test]
+    fn test_deadline_miss_after_overrun() {
+        let mut scheduler = EDFScheduler::<usize, 1>::new();
+        let task = rt_task(1, RtParams::new(10, 2), 0);
+
+        // the overrun postpones the scheduling deadline, but the job is still
+        // late against the deadline it was released with.
+        assert!(!scheduler.task_tick(&task));
+        assert!(scheduler.task_tick(&task));
+        assert_eq!(task.deadline(), 20);
+        assert!(!task.complete_job(15));
+        assert_eq!(task.deadline_misses(), 1);
+
+        task.release_job(20);
+        assert!(task.complete_job(25));
+        assert_eq!(task.deadline_misses(), 1);
+    }
+
+    #[test]
+    fn test_params() {
+        assert!(RtParams::new(10, 3).is_valid());
+        assert!(!RtParams::new(10, 0).is_valid());
+        assert!(!RtParams::new(10, 3).with_deadline(2).is_valid());
+        assert!(!RtParams::new(10, 3).with_deadline(11).is_valid());
+        assert_eq!(RtParams::new(3, 1).utilization(), 333_334);
+        assert_eq!(
+            RtParams::new(10, 10).utilization(),
+            RtParams::FULL_UTILIZATION
+        );
+    }
+}
@@ -8,7 +8,7 @@
 extern crate log;
 
 #[doc(no_inline)]
-pub use driver_display::DisplayInfo;
+pub use driver_display::{DisplayInfo, PixelFormat};
 
 use axdriver::{prelude::*, AxDeviceContainer};
 use axsync::Mutex;
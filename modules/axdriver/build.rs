@@ -1,6 +1,9 @@
 const NET_DEV_FEATURES: &[&str] = &["ixgbe", "virtio-net"];
 const BLOCK_DEV_FEATURES: &[&str] = &["ramdisk", "bcm2835-sdhci", "virtio-blk"];
-const DISPLAY_DEV_FEATURES: &[&str] = &["virtio-gpu"];
+const DISPLAY_DEV_FEATURES: &[&str] = &["ssd1306", "sh1106", "virtio-gpu"];
+const CHAR_DEV_FEATURES: &[&str] = &["pl011", "ns16550"];
+const GPIO_DEV_FEATURES: &[&str] = &["bcm2711-gpio"];
+const I2C_DEV_FEATURES: &[&str] = &["bcm2835-i2c"];
 
 fn has_feature(feature: &str) -> bool {
     std::env::var(format!(
@@ -28,6 +31,9 @@ fn main() {
         ("net", NET_DEV_FEATURES),
         ("block", BLOCK_DEV_FEATURES),
         ("display", DISPLAY_DEV_FEATURES),
+        ("char", CHAR_DEV_FEATURES),
+        ("gpio", GPIO_DEV_FEATURES),
+        ("i2c", I2C_DEV_FEATURES),
     ] {
         if !has_feature(dev_kind) {
             continue;
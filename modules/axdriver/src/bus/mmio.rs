@@ -1,11 +1,12 @@
 #[allow(unused_imports)]
 use crate::{prelude::*, AllDevices};
+#[cfg(feature = "virtio")]
+use alloc::vec::Vec;
 
 impl AllDevices {
     pub(crate) fn probe_bus_devices(&mut self) {
-        // TODO: parse device tree
         #[cfg(feature = "virtio")]
-        for reg in axconfig::VIRTIO_MMIO_REGIONS {
+        for reg in virtio_mmio_regions() {
             for_each_drivers!(type Driver, {
                 if let Some(dev) = Driver::probe_mmio(reg.0, reg.1) {
                     info!(
@@ -21,3 +22,19 @@ impl AllDevices {
         }
     }
 }
+
+/// Returns the VirtIO MMIO regions `(base_paddr, size)` in ascending order.
+///
+/// They are taken from the `virtio,mmio` nodes in the device tree if there is
+/// one, or [`axconfig::VIRTIO_MMIO_REGIONS`].
+#[cfg(feature = "virtio")]
+fn virtio_mmio_regions() -> Vec<(usize, usize)> {
+    if axhal::fdt::fdt().is_none() {
+        return axconfig::VIRTIO_MMIO_REGIONS.to_vec();
+    }
+    let mut regions: Vec<_> = axhal::fdt::find_devices(&["virtio,mmio"])
+        .map(|dev| (dev.paddr.as_usize(), dev.size))
+        .collect();
+    regions.sort_unstable();
+    regions
+}
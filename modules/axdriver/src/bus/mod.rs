@@ -2,3 +2,5 @@
 mod mmio;
 #[cfg(bus = "pci")]
 mod pci;
+#[cfg(any(char_dev = "pl011", char_dev = "ns16550"))]
+mod serial;
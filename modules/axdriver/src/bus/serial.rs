@@ -0,0 +1,23 @@
+#[allow(unused_imports)]
+use crate::{prelude::*, AllDevices};
+
+impl AllDevices {
+    /// Probes the serial ports in [`axconfig::SERIAL_PORTS`].
+    pub(crate) fn probe_serial_ports(&mut self) {
+        for &(base, irq_num) in axconfig::SERIAL_PORTS {
+            let irq_num = (irq_num != 0).then_some(irq_num);
+            for_each_drivers!(type Driver, {
+                if let Some(dev) = Driver::probe_serial(base, irq_num) {
+                    info!(
+                        "registered a new {:?} device at {:#x}: {:?}",
+                        dev.device_type(),
+                        base,
+                        dev.device_name(),
+                    );
+                    self.add_device(dev);
+                    continue; // skip to the next device
+                }
+            });
+        }
+    }
+}
@@ -23,6 +23,12 @@ pub trait DriverProbe {
         None
     }
 
+    /// Probes the serial port at `base` in [`axconfig::SERIAL_PORTS`].
+    #[cfg(feature = "char")]
+    fn probe_serial(_base: usize, _irq_num: Option<usize>) -> Option<AxDeviceEnum> {
+        None
+    }
+
     #[cfg(bus = "pci")]
     fn probe_pci(
         _root: &mut PciRoot,
@@ -128,3 +134,121 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+cfg_if::cfg_if! {
+    if #[cfg(char_dev = "pl011")] {
+        pub struct Pl011Driver;
+        register_char_driver!(Pl011Driver, driver_char::pl011::Pl011Dev);
+
+        impl DriverProbe for Pl011Driver {
+            fn probe_serial(base: usize, irq_num: Option<usize>) -> Option<AxDeviceEnum> {
+                let base = axhal::mem::phys_to_virt(base.into());
+                driver_char::pl011::Pl011Dev::try_new(
+                    base.as_mut_ptr(),
+                    axconfig::SERIAL_CLOCK_FREQUENCY as u32,
+                    irq_num,
+                )
+                .ok()
+                .map(AxDeviceEnum::from_char)
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(char_dev = "ns16550")] {
+        #[cfg(target_arch = "x86_64")]
+        type Ns16550Io = driver_char::ns16550::PortIo;
+        #[cfg(not(target_arch = "x86_64"))]
+        type Ns16550Io = driver_char::ns16550::MmioIo;
+
+        pub struct Ns16550Driver;
+        register_char_driver!(Ns16550Driver, driver_char::ns16550::Ns16550<Ns16550Io>);
+
+        impl DriverProbe for Ns16550Driver {
+            fn probe_serial(base: usize, irq_num: Option<usize>) -> Option<AxDeviceEnum> {
+                #[cfg(target_arch = "x86_64")]
+                let io = Ns16550Io::new(base as u16);
+                // 32-bit registers, as the DesignWare APB UART
+                #[cfg(not(target_arch = "x86_64"))]
+                let io = unsafe {
+                    Ns16550Io::new(axhal::mem::phys_to_virt(base.into()).as_usize(), 2)
+                };
+                driver_char::ns16550::Ns16550::try_new(
+                    io,
+                    axconfig::SERIAL_CLOCK_FREQUENCY as u32,
+                    irq_num,
+                )
+                .ok()
+                .map(AxDeviceEnum::from_char)
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(gpio_dev = "bcm2711-gpio")] {
+        pub struct Bcm2711GpioDriver;
+        register_gpio_driver!(Bcm2711GpioDriver, driver_gpio::bcm2711::Bcm2711Gpio);
+
+        impl DriverProbe for Bcm2711GpioDriver {
+            fn probe_global() -> Option<AxDeviceEnum> {
+                let base = axhal::mem::phys_to_virt(axconfig::GPIO_PADDR.into());
+                let irq_num = (axconfig::GPIO_IRQ != 0).then_some(axconfig::GPIO_IRQ);
+                Some(AxDeviceEnum::from_gpio(
+                    driver_gpio::bcm2711::Bcm2711Gpio::new(base.as_mut_ptr(), irq_num),
+                ))
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(i2c_dev = "bcm2835-i2c")] {
+        pub struct Bcm2835I2cDriver;
+        register_i2c_driver!(Bcm2835I2cDriver, driver_i2c::bcm2835::Bcm2835I2c);
+
+        impl DriverProbe for Bcm2835I2cDriver {
+            fn probe_global() -> Option<AxDeviceEnum> {
+                let base = axhal::mem::phys_to_virt(axconfig::I2C_PADDR.into());
+                Some(AxDeviceEnum::from_i2c(driver_i2c::bcm2835::Bcm2835I2c::new(
+                    base.as_mut_ptr(),
+                    axconfig::I2C_CLOCK_FREQUENCY as u32,
+                )))
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(display_dev = "ssd1306", display_dev = "sh1106"))] {
+        use driver_i2c::{bcm2835::Bcm2835I2c, ssd1306::{Ssd1306, Ssd1306Display}, I2cBusOps};
+
+        pub struct Ssd1306Driver;
+        register_display_driver!(Ssd1306Driver, Ssd1306Display<Bcm2835I2c>);
+
+        impl DriverProbe for Ssd1306Driver {
+            fn probe_global() -> Option<AxDeviceEnum> {
+                let base = axhal::mem::phys_to_virt(axconfig::I2C_PADDR.into());
+                let mut bus =
+                    Bcm2835I2c::new(base.as_mut_ptr(), axconfig::I2C_CLOCK_FREQUENCY as u32);
+                // the fast mode, to flush the whole screen in about 25 ms
+                bus.set_clock_frequency(400_000).ok();
+
+                let (addr, height) = (axconfig::OLED_ADDR as u8, axconfig::OLED_HEIGHT);
+                let oled = if cfg!(display_dev = "sh1106") {
+                    Ssd1306::new_sh1106(bus, addr, height)
+                } else {
+                    Ssd1306::new(bus, addr, height)
+                };
+                match oled.and_then(Ssd1306Display::try_new) {
+                    Ok(dev) => Some(AxDeviceEnum::from_display(dev)),
+                    Err(e) => {
+                        warn!("failed to initialize the OLED display at {:#x}: {:?}", addr, e);
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
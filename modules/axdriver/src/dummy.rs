@@ -100,3 +100,121 @@ cfg_if! {
         }
     }
 }
+
+cfg_if! {
+    if #[cfg(char_dev = "dummy")] {
+        use driver_char::SerialConfig;
+
+        pub struct DummyCharDev;
+        pub struct DummyCharDriver;
+        register_char_driver!(DummyCharDriver, DummyCharDev);
+
+        impl BaseDriverOps for DummyCharDev {
+            fn device_type(&self) -> DeviceType {
+                DeviceType::Char
+            }
+            fn device_name(&self) -> &str {
+                "dummy-char"
+            }
+        }
+
+        impl CharDriverOps for DummyCharDev {
+            fn read_byte(&mut self) -> Option<u8> {
+                None
+            }
+            fn write_byte(&mut self, _: u8) {}
+            fn config(&self) -> SerialConfig {
+                SerialConfig::default()
+            }
+            fn set_config(&mut self, _: &SerialConfig) -> DevResult {
+                Err(DevError::Unsupported)
+            }
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(gpio_dev = "dummy")] {
+        use driver_gpio::{Edge, PinFunction, Pull};
+
+        pub struct DummyGpioDev;
+        pub struct DummyGpioDriver;
+        register_gpio_driver!(DummyGpioDriver, DummyGpioDev);
+
+        impl BaseDriverOps for DummyGpioDev {
+            fn device_type(&self) -> DeviceType {
+                DeviceType::Gpio
+            }
+            fn device_name(&self) -> &str {
+                "dummy-gpio"
+            }
+        }
+
+        impl GpioDriverOps for DummyGpioDev {
+            fn num_pins(&self) -> usize {
+                0
+            }
+            fn function(&self, _: usize) -> DevResult<PinFunction> {
+                Err(DevError::Unsupported)
+            }
+            fn set_function(&mut self, _: usize, _: PinFunction) -> DevResult {
+                Err(DevError::Unsupported)
+            }
+            fn level(&self, _: usize) -> DevResult<bool> {
+                Err(DevError::Unsupported)
+            }
+            fn set_level(&mut self, _: usize, _: bool) -> DevResult {
+                Err(DevError::Unsupported)
+            }
+            fn pull(&self, _: usize) -> DevResult<Pull> {
+                Err(DevError::Unsupported)
+            }
+            fn set_pull(&mut self, _: usize, _: Pull) -> DevResult {
+                Err(DevError::Unsupported)
+            }
+            fn set_edge_interrupt(&mut self, _: usize, _: Edge) -> DevResult {
+                Err(DevError::Unsupported)
+            }
+            fn take_event(&mut self) -> Option<usize> {
+                None
+            }
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(i2c_dev = "dummy")] {
+        use driver_i2c::{I2cError, I2cResult};
+
+        pub struct DummyI2cDev;
+        pub struct DummyI2cDriver;
+        register_i2c_driver!(DummyI2cDriver, DummyI2cDev);
+
+        impl BaseDriverOps for DummyI2cDev {
+            fn device_type(&self) -> DeviceType {
+                DeviceType::I2c
+            }
+            fn device_name(&self) -> &str {
+                "dummy-i2c"
+            }
+        }
+
+        impl I2cBusOps for DummyI2cDev {
+            fn write(&mut self, _: u8, _: &[u8]) -> I2cResult {
+                Err(I2cError::Unsupported)
+            }
+            fn read(&mut self, _: u8, _: &mut [u8]) -> I2cResult {
+                Err(I2cError::Unsupported)
+            }
+            fn write_read(&mut self, _: u8, _: &[u8], _: &mut [u8]) -> I2cResult {
+                Err(I2cError::Unsupported)
+            }
+            fn clock_frequency(&self) -> u32 {
+                0
+            }
+            fn set_clock_frequency(&mut self, _: u32) -> I2cResult {
+                Err(I2cError::Unsupported)
+            }
+        }
+    }
+}
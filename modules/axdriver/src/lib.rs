@@ -8,8 +8,9 @@
 //! driver they want.
 //!
 //! For each device category (i.e., net, block, display, etc.), an unified type
-//! is used to represent all devices in that category. Currently, there are 3
-//! categories: [`AxNetDevice`], [`AxBlockDevice`], and [`AxDisplayDevice`].
+//! is used to represent all devices in that category. Currently, there are 6
+//! categories: [`AxNetDevice`], [`AxBlockDevice`], [`AxDisplayDevice`],
+//! [`AxCharDevice`], [`AxGpioDevice`], and [`AxI2cDevice`].
 //!
 //! # Concepts
 //!
@@ -34,6 +35,12 @@
 //! | Block | `virtio-blk` | VirtIO block device |
 //! | Network | `virtio-net` | VirtIO network device |
 //! | Display | `virtio-gpu` | VirtIO graphics device |
+//! | Display | `ssd1306` | SSD1306 OLED display on the BSC at `I2C_PADDR` |
+//! | Display | `sh1106` | SH1106 OLED display on the BSC at `I2C_PADDR` |
+//! | Char | `pl011` | ARM PL011 UART |
+//! | Char | `ns16550` | NS16550 compatible UART (I/O ports on x86) |
+//! | GPIO | `bcm2711-gpio` | GPIO controller of BCM2711 (Raspberry Pi 4) |
+//! | I2C | `bcm2835-i2c` | Broadcom Serial Controller (BSC) of BCM2835/BCM2711 |
 //!
 //! # Other Cargo Features
 //!
@@ -48,7 +55,15 @@
 //!    features, a dummy struct is used for [`AxNetDevice`].
 //! - `block`: use block storage devices. Similar to the `net` feature.
 //! - `display`: use graphics display devices. Similar to the `net` feature.
+//! - `char`: use character devices, i.e., the serial ports other than the
+//!    console, which are listed in `SERIAL_PORTS` of [axconfig]. Similar to
+//!    the `net` feature.
+//! - `gpio`: use GPIO controllers. Similar to the `net` feature.
+//! - `i2c`: use I2C bus controllers. Similar to the `net` feature. Note that
+//!    the OLED displays (`ssd1306` and `sh1106`) own their bus, which should
+//!    not be used by `bcm2835-i2c` at the same time.
 //!
+//! [axconfig]: https://rcore-os.github.io/arceos/axconfig/index.html
 //! [`VirtioNetDev`]: driver_virtio::VirtIoNetDev
 //! [`Box<dyn NetDriverOps>`]: driver_net::NetDriverOps
 //! [trait objects]: https://doc.rust-lang.org/book/ch17-02-trait-objects.html
@@ -61,7 +76,7 @@
 #[macro_use]
 extern crate log;
 
-#[cfg(feature = "dyn")]
+#[cfg(any(feature = "dyn", feature = "virtio"))]
 extern crate alloc;
 
 #[macro_use]
@@ -86,8 +101,14 @@ pub use self::structs::{AxDeviceContainer, AxDeviceEnum};
 
 #[cfg(feature = "block")]
 pub use self::structs::AxBlockDevice;
+#[cfg(feature = "char")]
+pub use self::structs::AxCharDevice;
 #[cfg(feature = "display")]
 pub use self::structs::AxDisplayDevice;
+#[cfg(feature = "gpio")]
+pub use self::structs::AxGpioDevice;
+#[cfg(feature = "i2c")]
+pub use self::structs::AxI2cDevice;
 #[cfg(feature = "net")]
 pub use self::structs::AxNetDevice;
 
@@ -103,6 +124,15 @@ pub struct AllDevices {
     /// All graphics device drivers.
     #[cfg(feature = "display")]
     pub display: AxDeviceContainer<AxDisplayDevice>,
+    /// All character device drivers.
+    #[cfg(feature = "char")]
+    pub char: AxDeviceContainer<AxCharDevice>,
+    /// All GPIO controller drivers.
+    #[cfg(feature = "gpio")]
+    pub gpio: AxDeviceContainer<AxGpioDevice>,
+    /// All I2C bus controller drivers.
+    #[cfg(feature = "i2c")]
+    pub i2c: AxDeviceContainer<AxI2cDevice>,
 }
 
 impl AllDevices {
@@ -131,6 +161,9 @@ impl AllDevices {
         });
 
         self.probe_bus_devices();
+
+        #[cfg(any(char_dev = "pl011", char_dev = "ns16550"))]
+        self.probe_serial_ports();
     }
 
     /// Adds one device into the corresponding container, according to its device category.
@@ -143,6 +176,12 @@ impl AllDevices {
             AxDeviceEnum::Block(dev) => self.block.push(dev),
             #[cfg(feature = "display")]
             AxDeviceEnum::Display(dev) => self.display.push(dev),
+            #[cfg(feature = "char")]
+            AxDeviceEnum::Char(dev) => self.char.push(dev),
+            #[cfg(feature = "gpio")]
+            AxDeviceEnum::Gpio(dev) => self.gpio.push(dev),
+            #[cfg(feature = "i2c")]
+            AxDeviceEnum::I2c(dev) => self.i2c.push(dev),
         }
     }
 }
@@ -179,6 +218,30 @@ pub fn init_drivers() -> AllDevices {
             debug!("  graphics device {}: {:?}", i, dev.device_name());
         }
     }
+    #[cfg(feature = "char")]
+    {
+        debug!("number of character devices: {}", all_devs.char.len());
+        for (i, dev) in all_devs.char.iter().enumerate() {
+            assert_eq!(dev.device_type(), DeviceType::Char);
+            debug!("  character device {}: {:?}", i, dev.device_name());
+        }
+    }
+    #[cfg(feature = "gpio")]
+    {
+        debug!("number of GPIO controllers: {}", all_devs.gpio.len());
+        for (i, dev) in all_devs.gpio.iter().enumerate() {
+            assert_eq!(dev.device_type(), DeviceType::Gpio);
+            debug!("  GPIO controller {}: {:?}", i, dev.device_name());
+        }
+    }
+    #[cfg(feature = "i2c")]
+    {
+        debug!("number of I2C bus controllers: {}", all_devs.i2c.len());
+        for (i, dev) in all_devs.i2c.iter().enumerate() {
+            assert_eq!(dev.device_type(), DeviceType::I2c);
+            debug!("  I2C bus controller {}: {:?}", i, dev.device_name());
+        }
+    }
 
     all_devs
 }
@@ -26,6 +26,30 @@ macro_rules! register_display_driver {
     };
 }
 
+macro_rules! register_char_driver {
+    ($driver_type:ty, $device_type:ty) => {
+        /// The unified type of the character devices.
+        #[cfg(not(feature = "dyn"))]
+        pub type AxCharDevice = $device_type;
+    };
+}
+
+macro_rules! register_gpio_driver {
+    ($driver_type:ty, $device_type:ty) => {
+        /// The unified type of the GPIO controllers.
+        #[cfg(not(feature = "dyn"))]
+        pub type AxGpioDevice = $device_type;
+    };
+}
+
+macro_rules! register_i2c_driver {
+    ($driver_type:ty, $device_type:ty) => {
+        /// The unified type of the I2C bus controllers.
+        #[cfg(not(feature = "dyn"))]
+        pub type AxI2cDevice = $device_type;
+    };
+}
+
 macro_rules! for_each_drivers {
     (type $drv_type:ident, $code:block) => {{
         #[allow(unused_imports)]
@@ -64,5 +88,30 @@ macro_rules! for_each_drivers {
             type $drv_type = crate::drivers::IxgbeDriver;
             $code
         }
+        #[cfg(char_dev = "pl011")]
+        {
+            type $drv_type = crate::drivers::Pl011Driver;
+            $code
+        }
+        #[cfg(char_dev = "ns16550")]
+        {
+            type $drv_type = crate::drivers::Ns16550Driver;
+            $code
+        }
+        #[cfg(gpio_dev = "bcm2711-gpio")]
+        {
+            type $drv_type = crate::drivers::Bcm2711GpioDriver;
+            $code
+        }
+        #[cfg(any(display_dev = "ssd1306", display_dev = "sh1106"))]
+        {
+            type $drv_type = crate::drivers::Ssd1306Driver;
+            $code
+        }
+        #[cfg(i2c_dev = "bcm2835-i2c")]
+        {
+            type $drv_type = crate::drivers::Bcm2835I2cDriver;
+            $code
+        }
     }};
 }
@@ -4,7 +4,13 @@ pub use driver_common::{BaseDriverOps, DevError, DevResult, DeviceType};
 
 #[cfg(feature = "block")]
 pub use {crate::structs::AxBlockDevice, driver_block::BlockDriverOps};
+#[cfg(feature = "char")]
+pub use {crate::structs::AxCharDevice, driver_char::CharDriverOps};
 #[cfg(feature = "display")]
 pub use {crate::structs::AxDisplayDevice, driver_display::DisplayDriverOps};
+#[cfg(feature = "gpio")]
+pub use {crate::structs::AxGpioDevice, driver_gpio::GpioDriverOps};
+#[cfg(feature = "i2c")]
+pub use {crate::structs::AxI2cDevice, driver_i2c::I2cBusOps};
 #[cfg(feature = "net")]
 pub use {crate::structs::AxNetDevice, driver_net::NetDriverOps};
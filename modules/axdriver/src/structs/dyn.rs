@@ -12,6 +12,15 @@ pub type AxBlockDevice = Box<dyn BlockDriverOps>;
 /// The unified type of the graphics display devices.
 #[cfg(feature = "display")]
 pub type AxDisplayDevice = Box<dyn DisplayDriverOps>;
+/// The unified type of the character devices.
+#[cfg(feature = "char")]
+pub type AxCharDevice = Box<dyn CharDriverOps>;
+/// The unified type of the GPIO controllers.
+#[cfg(feature = "gpio")]
+pub type AxGpioDevice = Box<dyn GpioDriverOps>;
+/// The unified type of the I2C bus controllers.
+#[cfg(feature = "i2c")]
+pub type AxI2cDevice = Box<dyn I2cBusOps>;
 
 impl super::AxDeviceEnum {
     /// Constructs a network device.
@@ -31,6 +40,24 @@ impl super::AxDeviceEnum {
     pub fn from_display(dev: impl DisplayDriverOps + 'static) -> Self {
         Self::Display(Box::new(dev))
     }
+
+    /// Constructs a character device.
+    #[cfg(feature = "char")]
+    pub fn from_char(dev: impl CharDriverOps + 'static) -> Self {
+        Self::Char(Box::new(dev))
+    }
+
+    /// Constructs a GPIO controller.
+    #[cfg(feature = "gpio")]
+    pub fn from_gpio(dev: impl GpioDriverOps + 'static) -> Self {
+        Self::Gpio(Box::new(dev))
+    }
+
+    /// Constructs an I2C bus controller.
+    #[cfg(feature = "i2c")]
+    pub fn from_i2c(dev: impl I2cBusOps + 'static) -> Self {
+        Self::I2c(Box::new(dev))
+    }
 }
 
 /// A structure that contains all device drivers of a certain category.
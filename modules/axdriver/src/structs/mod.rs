@@ -18,6 +18,15 @@ pub enum AxDeviceEnum {
     /// Graphic display device.
     #[cfg(feature = "display")]
     Display(AxDisplayDevice),
+    /// Character device.
+    #[cfg(feature = "char")]
+    Char(AxCharDevice),
+    /// GPIO controller.
+    #[cfg(feature = "gpio")]
+    Gpio(AxGpioDevice),
+    /// I2C bus controller.
+    #[cfg(feature = "i2c")]
+    I2c(AxI2cDevice),
 }
 
 impl BaseDriverOps for AxDeviceEnum {
@@ -31,6 +40,12 @@ impl BaseDriverOps for AxDeviceEnum {
             Self::Block(_) => DeviceType::Block,
             #[cfg(feature = "display")]
             Self::Display(_) => DeviceType::Display,
+            #[cfg(feature = "char")]
+            Self::Char(_) => DeviceType::Char,
+            #[cfg(feature = "gpio")]
+            Self::Gpio(_) => DeviceType::Gpio,
+            #[cfg(feature = "i2c")]
+            Self::I2c(_) => DeviceType::I2c,
             _ => unreachable!(),
         }
     }
@@ -45,6 +60,12 @@ impl BaseDriverOps for AxDeviceEnum {
             Self::Block(dev) => dev.device_name(),
             #[cfg(feature = "display")]
             Self::Display(dev) => dev.device_name(),
+            #[cfg(feature = "char")]
+            Self::Char(dev) => dev.device_name(),
+            #[cfg(feature = "gpio")]
+            Self::Gpio(dev) => dev.device_name(),
+            #[cfg(feature = "i2c")]
+            Self::I2c(dev) => dev.device_name(),
             _ => unreachable!(),
         }
     }
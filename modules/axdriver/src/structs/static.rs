@@ -1,7 +1,13 @@
 #[cfg(feature = "block")]
 pub use crate::drivers::AxBlockDevice;
+#[cfg(feature = "char")]
+pub use crate::drivers::AxCharDevice;
 #[cfg(feature = "display")]
 pub use crate::drivers::AxDisplayDevice;
+#[cfg(feature = "gpio")]
+pub use crate::drivers::AxGpioDevice;
+#[cfg(feature = "i2c")]
+pub use crate::drivers::AxI2cDevice;
 #[cfg(feature = "net")]
 pub use crate::drivers::AxNetDevice;
 
@@ -23,6 +29,24 @@ impl super::AxDeviceEnum {
     pub const fn from_display(dev: AxDisplayDevice) -> Self {
         Self::Display(dev)
     }
+
+    /// Constructs a character device.
+    #[cfg(feature = "char")]
+    pub const fn from_char(dev: AxCharDevice) -> Self {
+        Self::Char(dev)
+    }
+
+    /// Constructs a GPIO controller.
+    #[cfg(feature = "gpio")]
+    pub const fn from_gpio(dev: AxGpioDevice) -> Self {
+        Self::Gpio(dev)
+    }
+
+    /// Constructs an I2C bus controller.
+    #[cfg(feature = "i2c")]
+    pub const fn from_i2c(dev: AxI2cDevice) -> Self {
+        Self::I2c(dev)
+    }
 }
 
 /// A structure that contains all device drivers of a certain category.
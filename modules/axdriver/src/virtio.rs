@@ -25,7 +25,9 @@ pub trait VirtIoDevMeta {
     type Device: BaseDriverOps;
     type Driver = VirtIoDriver<Self>;
 
-    fn try_new(transport: VirtIoTransport) -> DevResult<AxDeviceEnum>;
+    /// Creates the device on the transport. `irq_num` is the IRQ number of
+    /// the device if it's known.
+    fn try_new(transport: VirtIoTransport, irq_num: Option<usize>) -> DevResult<AxDeviceEnum>;
 }
 
 cfg_if! {
@@ -36,8 +38,8 @@ cfg_if! {
             const DEVICE_TYPE: DeviceType = DeviceType::Net;
             type Device = driver_virtio::VirtIoNetDev<VirtIoHalImpl, VirtIoTransport, 64>;
 
-            fn try_new(transport: VirtIoTransport) -> DevResult<AxDeviceEnum> {
-                Ok(AxDeviceEnum::from_net(Self::Device::try_new(transport)?))
+            fn try_new(transport: VirtIoTransport, irq_num: Option<usize>) -> DevResult<AxDeviceEnum> {
+                Ok(AxDeviceEnum::from_net(Self::Device::try_new(transport, irq_num)?))
             }
         }
     }
@@ -51,7 +53,7 @@ cfg_if! {
             const DEVICE_TYPE: DeviceType = DeviceType::Block;
             type Device = driver_virtio::VirtIoBlkDev<VirtIoHalImpl, VirtIoTransport>;
 
-            fn try_new(transport: VirtIoTransport) -> DevResult<AxDeviceEnum> {
+            fn try_new(transport: VirtIoTransport, _irq_num: Option<usize>) -> DevResult<AxDeviceEnum> {
                 Ok(AxDeviceEnum::from_block(Self::Device::try_new(transport)?))
             }
         }
@@ -66,7 +68,7 @@ cfg_if! {
             const DEVICE_TYPE: DeviceType = DeviceType::Display;
             type Device = driver_virtio::VirtIoGpuDev<VirtIoHalImpl, VirtIoTransport>;
 
-            fn try_new(transport: VirtIoTransport) -> DevResult<AxDeviceEnum> {
+            fn try_new(transport: VirtIoTransport, _irq_num: Option<usize>) -> DevResult<AxDeviceEnum> {
                 Ok(AxDeviceEnum::from_display(Self::Device::try_new(transport)?))
             }
         }
@@ -84,7 +86,7 @@ impl<D: VirtIoDevMeta> DriverProbe for VirtIoDriver<D> {
             driver_virtio::probe_mmio_device(base_vaddr.as_mut_ptr(), mmio_size)
         {
             if ty == D::DEVICE_TYPE {
-                match D::try_new(transport) {
+                match D::try_new(transport, mmio_irq_num(mmio_base)) {
                     Ok(dev) => return Some(dev),
                     Err(e) => {
                         warn!(
@@ -121,7 +123,8 @@ impl<D: VirtIoDevMeta> DriverProbe for VirtIoDriver<D> {
             driver_virtio::probe_pci_device::<VirtIoHalImpl>(root, bdf, dev_info)
         {
             if ty == D::DEVICE_TYPE {
-                match D::try_new(transport) {
+                // TODO: route legacy INTx or MSI interrupts
+                match D::try_new(transport, None) {
                     Ok(dev) => return Some(dev),
                     Err(e) => {
                         warn!(
@@ -137,6 +140,27 @@ impl<D: VirtIoDevMeta> DriverProbe for VirtIoDriver<D> {
     }
 }
 
+/// Returns the IRQ number of the VirtIO MMIO device at `mmio_base`.
+///
+/// It's taken from the device tree if there is one. Otherwise, the devices in
+/// [`axconfig::VIRTIO_MMIO_REGIONS`] use consecutive IRQ numbers starting
+/// from [`axconfig::VIRTIO_MMIO_IRQ_BASE`].
+#[cfg(bus = "mmio")]
+fn mmio_irq_num(mmio_base: usize) -> Option<usize> {
+    if axhal::fdt::fdt().is_some() {
+        return axhal::fdt::find_devices(&["virtio,mmio"])
+            .find(|dev| dev.paddr.as_usize() == mmio_base)
+            .and_then(|dev| dev.irq_num);
+    }
+    if axconfig::VIRTIO_MMIO_IRQ_BASE == 0 {
+        return None; // not supported on this platform
+    }
+    axconfig::VIRTIO_MMIO_REGIONS
+        .iter()
+        .position(|reg| reg.0 == mmio_base)
+        .map(|idx| axconfig::VIRTIO_MMIO_IRQ_BASE + idx)
+}
+
 pub struct VirtIoHalImpl;
 
 unsafe impl VirtIoHal for VirtIoHalImpl {
@@ -6,6 +6,18 @@ CUR_DIR=`dirname $0`
 
 echo $OUT_DIR
 
+populate_test_dir() {
+	local dir=$1
+	for i in $(seq 1 1000); do
+	  echo "Rust is cool!" >>"$dir/long.txt"
+	done
+	echo "Rust is cool!" >>"$dir/short.txt"
+	mkdir -p "$dir/very/long/path"
+	echo "Rust is cool!" >>"$dir/very/long/path/test.txt"
+	mkdir -p "$dir/very-long-dir-name"
+	echo "Rust is cool!" >>"$dir/very-long-dir-name/very-long-file-name.txt"
+}
+
 create_test_img() {
 	local name=$1
 	local blkcount=$2
@@ -14,17 +26,42 @@ create_test_img() {
 	mkfs.vfat -s 1 -F $fatSize -n "Test!" -i 12345678 "$name"
 	mkdir -p mnt
 	sudo mount -o loop "$name" mnt -o rw,uid=$USER,gid=$USER
-	for i in $(seq 1 1000); do
-	  echo "Rust is cool!" >>"mnt/long.txt"
-	done
-	echo "Rust is cool!" >>"mnt/short.txt"
-	mkdir -p "mnt/very/long/path"
-	echo "Rust is cool!" >>"mnt/very/long/path/test.txt"
-	mkdir -p "mnt/very-long-dir-name"
-	echo "Rust is cool!" >>"mnt/very-long-dir-name/very-long-file-name.txt"
-
+	populate_test_dir mnt
 	sudo umount mnt
 }
 
-create_test_img "$CUR_DIR/fat16.img" 2500 16
-create_test_img "$CUR_DIR/fat32.img" 34000 32
+# The ext images are populated by `mkfs -d`, so no mounting is needed.
+create_ext_test_img() {
+	local name=$1
+	local blkcount=$2
+	local fstype=$3
+	shift 3
+	local root=$(mktemp -d)
+	populate_test_dir "$root"
+	ln -s "very/long/path/test.txt" "$root/link.txt"
+	chmod 600 "$root/short.txt"
+	# mount points are required if the image is mounted read-only as root
+	mkdir -p "$root/dev" "$root/tmp" "$root/proc" "$root/sys"
+	rm -f "$name"
+	E2FSPROGS_FAKE_TIME=1700000000 mkfs.$fstype -q -b 1024 -L "Test!" \
+	  -U 12345678-1234-1234-1234-123456789abc -E root_owner=0:0 \
+	  "$@" -d "$root" "$name" $blkcount
+	rm -rf "$root"
+}
+
+# The ext images are also created by the tests with the type and path as the
+# arguments, e.g., `create_test_img.sh ext2 ext2.img`.
+case "$1" in
+ext2)
+	create_ext_test_img "$2" 2048 ext2
+	;;
+ext4)
+	create_ext_test_img "$2" 2048 ext4 -O ^has_journal
+	;;
+*)
+	create_test_img "$CUR_DIR/fat16.img" 2500 16
+	create_test_img "$CUR_DIR/fat32.img" 34000 32
+	create_ext_test_img "$CUR_DIR/ext2.img" 2048 ext2
+	create_ext_test_img "$CUR_DIR/ext4.img" 2048 ext4 -O ^has_journal
+	;;
+esac
@@ -9,6 +9,11 @@ pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
 use alloc::{string::String, vec::Vec};
 use axio::{self as io, prelude::*};
 
+#[cfg(feature = "serial")]
+pub use driver_char::{Parity, SerialConfig, StopBits};
+#[cfg(feature = "gpio")]
+pub use driver_gpio::{Edge, PinFunction, Pull};
+
 /// Returns an iterator over the entries within a directory.
 pub fn read_dir(path: &str) -> io::Result<ReadDir> {
     ReadDir::new(path)
@@ -59,6 +64,32 @@ pub fn metadata(path: &str) -> io::Result<Metadata> {
     File::open(path)?.metadata()
 }
 
+/// Query the metadata about a file without following symlinks.
+pub fn symlink_metadata(path: &str) -> io::Result<Metadata> {
+    let attr = crate::root::lookup_no_follow(None, path)?.get_attr()?;
+    Ok(Metadata::new(attr))
+}
+
+/// Reads a symbolic link, returning the file that the link points to.
+pub fn read_link(path: &str) -> io::Result<String> {
+    crate::root::read_link(None, path)
+}
+
+/// Creates a new symbolic link on the filesystem.
+///
+/// The `link` path will be a symbolic link pointing to the `original` path.
+pub fn symlink(original: &str, link: &str) -> io::Result<()> {
+    crate::root::create_symlink(None, original, link)
+}
+
+/// Creates a new hard link on the filesystem.
+///
+/// The `link` path will be a link pointing to the `original` path. Note that
+/// both paths must be in the same mounted filesystem.
+pub fn hard_link(original: &str, link: &str) -> io::Result<()> {
+    crate::root::create_link(original, link)
+}
+
 /// Creates a new, empty directory at the provided path.
 pub fn create_dir(path: &str) -> io::Result<()> {
     DirBuilder::new().create(path)
@@ -87,3 +118,106 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     crate::root::rename(old, new)
 }
+
+/// Mounts the filesystem of type `fstype` from `source` on the directory
+/// `target`.
+///
+/// FAT (`vfat`) and ext2 (`ext2`, `ext3` or `ext4`) filesystems are mounted
+/// from the additional block devices named `/dev/sdb`, `/dev/sdc`, etc. Other
+/// types (`ramfs`, `devfs`, `procfs` and `sysfs`) ignore the `source`.
+pub fn mount(source: &str, target: &str, fstype: &str) -> io::Result<()> {
+    let fs = crate::mounts::new_fs(source, fstype)?;
+    crate::root::mount(source, target, fstype, fs)
+}
+
+/// Unmounts the filesystem mounted on the directory `target`.
+///
+/// The cached data of all block devices is written back afterwards.
+pub fn umount(target: &str) -> io::Result<()> {
+    crate::root::umount(target)
+}
+
+/// Writes the cached data of all block devices back to the devices.
+pub fn sync() -> io::Result<()> {
+    crate::dev::sync_all()
+}
+
+/// Like [`sync`], but does not wait for the caches in use, e.g., when the
+/// system is terminating.
+///
+/// The caches in use are not written back, and an error of
+/// [`ResourceBusy`](io::Error::ResourceBusy) is returned.
+pub fn try_sync() -> io::Result<()> {
+    crate::dev::try_sync_all()
+}
+
+/// Returns the hit and miss counters of the block caches.
+pub fn block_cache_stats() -> crate::fops::BlockCacheStats {
+    crate::dev::block_cache_stats()
+}
+
+/// Returns the line configuration of the serial port at `path`, e.g.,
+/// `/dev/ttyS0`.
+#[cfg(feature = "serial")]
+pub fn serial_config(path: &str) -> io::Result<SerialConfig> {
+    crate::tty::config(path)
+}
+
+/// Sets the baud rate, the parity, etc. of the serial port at `path`.
+#[cfg(feature = "serial")]
+pub fn set_serial_config(path: &str, config: &SerialConfig) -> io::Result<()> {
+    crate::tty::set_config(path, config)
+}
+
+/// Returns the function of the pin of the GPIO controller at `path`, e.g.,
+/// `/dev/gpiochip0`.
+#[cfg(feature = "gpio")]
+pub fn gpio_function(path: &str, pin: usize) -> io::Result<PinFunction> {
+    crate::gpio::function(path, pin)
+}
+
+/// Sets the function of the pin of the GPIO controller at `path`, i.e.,
+/// makes it an input, an output, or selects an alternative function.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_function(path: &str, pin: usize, func: PinFunction) -> io::Result<()> {
+    crate::gpio::set_function(path, pin, func)
+}
+
+/// Returns the level of the pin of the GPIO controller at `path`, `true` for
+/// high.
+#[cfg(feature = "gpio")]
+pub fn gpio_level(path: &str, pin: usize) -> io::Result<bool> {
+    crate::gpio::level(path, pin)
+}
+
+/// Drives the output pin of the GPIO controller at `path` to high or low.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_level(path: &str, pin: usize, high: bool) -> io::Result<()> {
+    crate::gpio::set_level(path, pin, high)
+}
+
+/// Returns the pull resistor of the pin of the GPIO controller at `path`.
+#[cfg(feature = "gpio")]
+pub fn gpio_pull(path: &str, pin: usize) -> io::Result<Pull> {
+    crate::gpio::pull(path, pin)
+}
+
+/// Sets the pull resistor of the pin of the GPIO controller at `path`.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_pull(path: &str, pin: usize, pull: Pull) -> io::Result<()> {
+    crate::gpio::set_pull(path, pin, pull)
+}
+
+/// Selects the edges of the pin of the GPIO controller at `path` that are
+/// reported by [`gpio_wait_event`].
+#[cfg(feature = "gpio")]
+pub fn gpio_set_edge_interrupt(path: &str, pin: usize, edge: Edge) -> io::Result<()> {
+    crate::gpio::set_edge_interrupt(path, pin, edge)
+}
+
+/// Blocks until a selected edge is detected by the GPIO controller at
+/// `path`, and returns its pin.
+#[cfg(feature = "gpio")]
+pub fn gpio_wait_event(path: &str) -> io::Result<usize> {
+    crate::gpio::wait_event(path)
+}
@@ -1,28 +1,238 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
 use axdriver::prelude::*;
+use axerrno::{ax_err, AxResult};
+use axsync::Mutex;
 
 const BLOCK_SIZE: usize = 512;
 
-/// A disk device with a cursor.
+/// Maximum number of blocks cached for each disk.
+const CACHE_SIZE: usize = axconfig::BLOCK_CACHE_SIZE;
+
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+static CACHE_WRITEBACKS: AtomicU64 = AtomicU64::new(0);
+
+/// Caches of all disks, so that they can be flushed by [`sync_all`].
+static CACHES: Mutex<Vec<Weak<Mutex<BlockCache>>>> = Mutex::new(Vec::new());
+
+/// Statistics of the block caches, accumulated over all disks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    /// Number of block accesses served by the cache.
+    pub hits: u64,
+    /// Number of block accesses that had to go to the device.
+    pub misses: u64,
+    /// Number of dirty blocks written back to the device.
+    pub writebacks: u64,
+}
+
+/// Returns the statistics of the block caches.
+pub fn block_cache_stats() -> BlockCacheStats {
+    BlockCacheStats {
+        hits: CACHE_HITS.load(Ordering::Relaxed),
+        misses: CACHE_MISSES.load(Ordering::Relaxed),
+        writebacks: CACHE_WRITEBACKS.load(Ordering::Relaxed),
+    }
+}
+
+/// Writes the dirty blocks of all disks back to their devices.
+pub(crate) fn sync_all() -> AxResult {
+    let mut caches = CACHES.lock();
+    caches.retain(|cache| cache.strong_count() > 0);
+    for cache in caches.iter().filter_map(Weak::upgrade) {
+        if cache.lock().flush().is_err() {
+            return ax_err!(Io, "failed to write back the block cache");
+        }
+    }
+    Ok(())
+}
+
+/// Like [`sync_all`], but skips the caches that are locked by others instead
+/// of waiting for them, so it can be called when terminating the system (e.g.,
+/// from the panic handler).
+///
+/// Returns [`ResourceBusy`](axerrno::AxError::ResourceBusy) if any cache was
+/// skipped.
+pub(crate) fn try_sync_all() -> AxResult {
+    let Some(caches) = CACHES.try_lock() else {
+        return ax_err!(ResourceBusy, "the block caches are in use");
+    };
+    let mut busy = false;
+    for cache in caches.iter().filter_map(Weak::upgrade) {
+        match cache.try_lock() {
+            Some(mut cache) => {
+                if cache.flush().is_err() {
+                    return ax_err!(Io, "failed to write back the block cache");
+                }
+            }
+            None => busy = true,
+        }
+    }
+    if busy {
+        return ax_err!(ResourceBusy, "some block caches are in use");
+    }
+    Ok(())
+}
+
+struct CachedBlock {
+    block_id: u64,
+    dirty: bool,
+    /// Value of [`BlockCache::clock`] when the block was last accessed.
+    last_used: u64,
+    data: [u8; BLOCK_SIZE],
+}
+
+/// A write-back cache of the blocks of a device, which evicts the least
+/// recently used block when it is full.
+struct BlockCache {
+    dev: AxBlockDevice,
+    blocks: Vec<CachedBlock>,
+    /// Maps block IDs to indices in `blocks`.
+    index: BTreeMap<u64, usize>,
+    clock: u64,
+}
+
+impl BlockCache {
+    fn new(dev: AxBlockDevice) -> Self {
+        Self {
+            dev,
+            blocks: Vec::new(),
+            index: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the cached block, which is read from the device on a miss if
+    /// `fill` is true.
+    fn get(&mut self, block_id: u64, fill: bool) -> DevResult<&mut CachedBlock> {
+        // report the error now, rather than when the block is written back
+        if block_id >= self.dev.num_blocks() {
+            return Err(DevError::Io);
+        }
+        self.clock += 1;
+        if let Some(&slot) = self.index.get(&block_id) {
+            CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+            let block = &mut self.blocks[slot];
+            block.last_used = self.clock;
+            return Ok(block);
+        }
+
+        CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
+        let slot = self.alloc_slot()?;
+        let block = &mut self.blocks[slot];
+        if fill {
+            self.dev.read_block(block_id, &mut block.data)?;
+        }
+        block.block_id = block_id;
+        block.last_used = self.clock;
+        self.index.insert(block_id, slot);
+        Ok(block)
+    }
+
+    /// Finds a free slot, evicting the least recently used block if the
+    /// cache is full.
+    fn alloc_slot(&mut self) -> DevResult<usize> {
+        if self.blocks.len() < CACHE_SIZE {
+            self.blocks.push(CachedBlock {
+                block_id: 0,
+                dirty: false,
+                last_used: 0,
+                data: [0; BLOCK_SIZE],
+            });
+            return Ok(self.blocks.len() - 1);
+        }
+        let (slot, _) = self
+            .blocks
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, block)| block.last_used)
+            .unwrap();
+        self.write_back(slot)?;
+        let block = &mut self.blocks[slot];
+        // The slot may not be indexed if reading it from the device failed.
+        if self.index.get(&block.block_id) == Some(&slot) {
+            self.index.remove(&block.block_id);
+        }
+        block.last_used = 0;
+        Ok(slot)
+    }
+
+    fn write_back(&mut self, slot: usize) -> DevResult {
+        let block = &mut self.blocks[slot];
+        if block.dirty {
+            self.dev.write_block(block.block_id, &block.data)?;
+            block.dirty = false;
+            CACHE_WRITEBACKS.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, block_id: u64, offset: usize, buf: &mut [u8]) -> DevResult {
+        let block = self.get(block_id, true)?;
+        buf.copy_from_slice(&block.data[offset..offset + buf.len()]);
+        Ok(())
+    }
+
+    fn write(&mut self, block_id: u64, offset: usize, buf: &[u8]) -> DevResult {
+        // no need to read the block if it is overwritten entirely
+        let block = self.get(block_id, buf.len() < BLOCK_SIZE)?;
+        block.data[offset..offset + buf.len()].copy_from_slice(buf);
+        block.dirty = true;
+        Ok(())
+    }
+
+    /// Writes all dirty blocks back in the order of block IDs, and flushes
+    /// the device.
+    fn flush(&mut self) -> DevResult {
+        let slots = self.index.values().copied().collect::<Vec<_>>();
+        for slot in slots {
+            self.write_back(slot)?;
+        }
+        self.dev.flush()
+    }
+}
+
+impl Drop for BlockCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("failed to flush the block cache: {:?}", e);
+        }
+    }
+}
+
+/// A disk device with a cursor, whose blocks are cached in memory.
+///
+/// Written data is kept in the cache until [`Disk::flush`] is called, the
+/// cache is full, or the disk is dropped.
 pub struct Disk {
     block_id: u64,
     offset: usize,
-    dev: AxBlockDevice,
+    num_blocks: u64,
+    cache: Arc<Mutex<BlockCache>>,
 }
 
 impl Disk {
     /// Create a new disk.
     pub fn new(dev: AxBlockDevice) -> Self {
         assert_eq!(BLOCK_SIZE, dev.block_size());
+        let num_blocks = dev.num_blocks();
+        let cache = Arc::new(Mutex::new(BlockCache::new(dev)));
+        CACHES.lock().push(Arc::downgrade(&cache));
         Self {
             block_id: 0,
             offset: 0,
-            dev,
+            num_blocks,
+            cache,
         }
     }
 
     /// Get the size of the disk.
     pub fn size(&self) -> u64 {
-        self.dev.num_blocks() * BLOCK_SIZE as u64
+        self.num_blocks * BLOCK_SIZE as u64
     }
 
     /// Get the position of the cursor.
@@ -38,55 +248,34 @@ impl Disk {
 
     /// Read within one block, returns the number of bytes read.
     pub fn read_one(&mut self, buf: &mut [u8]) -> DevResult<usize> {
-        let read_size = if self.offset == 0 && buf.len() >= BLOCK_SIZE {
-            // whole block
-            self.dev
-                .read_block(self.block_id, &mut buf[0..BLOCK_SIZE])?;
-            self.block_id += 1;
-            BLOCK_SIZE
-        } else {
-            // partial block
-            let mut data = [0u8; BLOCK_SIZE];
-            let start = self.offset;
-            let count = buf.len().min(BLOCK_SIZE - self.offset);
-
-            self.dev.read_block(self.block_id, &mut data)?;
-            buf[..count].copy_from_slice(&data[start..start + count]);
-
-            self.offset += count;
-            if self.offset >= BLOCK_SIZE {
-                self.block_id += 1;
-                self.offset -= BLOCK_SIZE;
-            }
-            count
-        };
-        Ok(read_size)
+        let count = buf.len().min(BLOCK_SIZE - self.offset);
+        self.cache
+            .lock()
+            .read(self.block_id, self.offset, &mut buf[..count])?;
+        self.advance(count);
+        Ok(count)
     }
 
     /// Write within one block, returns the number of bytes written.
     pub fn write_one(&mut self, buf: &[u8]) -> DevResult<usize> {
-        let write_size = if self.offset == 0 && buf.len() >= BLOCK_SIZE {
-            // whole block
-            self.dev.write_block(self.block_id, &buf[0..BLOCK_SIZE])?;
+        let count = buf.len().min(BLOCK_SIZE - self.offset);
+        self.cache
+            .lock()
+            .write(self.block_id, self.offset, &buf[..count])?;
+        self.advance(count);
+        Ok(count)
+    }
+
+    /// Writes the dirty blocks in the cache to the device.
+    pub fn flush(&mut self) -> DevResult {
+        self.cache.lock().flush()
+    }
+
+    fn advance(&mut self, count: usize) {
+        self.offset += count;
+        if self.offset >= BLOCK_SIZE {
             self.block_id += 1;
-            BLOCK_SIZE
-        } else {
-            // partial block
-            let mut data = [0u8; BLOCK_SIZE];
-            let start = self.offset;
-            let count = buf.len().min(BLOCK_SIZE - self.offset);
-
-            self.dev.read_block(self.block_id, &mut data)?;
-            data[start..start + count].copy_from_slice(&buf[..count]);
-            self.dev.write_block(self.block_id, &data)?;
-
-            self.offset += count;
-            if self.offset >= BLOCK_SIZE {
-                self.block_id += 1;
-                self.offset -= BLOCK_SIZE;
-            }
-            count
-        };
-        Ok(write_size)
+            self.offset -= BLOCK_SIZE;
+        }
     }
 }
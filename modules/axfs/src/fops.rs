@@ -6,6 +6,9 @@ use axio::SeekFrom;
 use capability::{Cap, WithCap};
 use core::fmt;
 
+use crate::root::MountRef;
+
+pub use crate::dev::BlockCacheStats;
 #[cfg(feature = "myfs")]
 pub use crate::dev::Disk;
 #[cfg(feature = "myfs")]
@@ -23,6 +26,8 @@ pub type FilePerm = axfs_vfs::VfsNodePerm;
 /// An opened file object, with open permissions and a cursor.
 pub struct File {
     node: WithCap<VfsNodeRef>,
+    /// Keeps the filesystem of the file mounted.
+    _mount: Option<MountRef>,
     is_append: bool,
     offset: u64,
 }
@@ -31,6 +36,8 @@ pub struct File {
 /// [`read_dir`](Directory::read_dir).
 pub struct Directory {
     node: WithCap<VfsNodeRef>,
+    /// Keeps the filesystem of the directory mounted.
+    mount: Option<MountRef>,
     entry_idx: usize,
 }
 
@@ -112,7 +119,14 @@ impl OpenOptions {
 }
 
 impl File {
-    fn _open_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    /// Opens the file at `path` relative to `dir`, which is on the filesystem
+    /// referenced by `dir_mount`.
+    fn _open_at(
+        dir: Option<&VfsNodeRef>,
+        dir_mount: Option<&MountRef>,
+        path: &str,
+        opts: &OpenOptions,
+    ) -> AxResult<Self> {
         debug!("open file: {} {:?}", path, opts);
         if !opts.is_valid() {
             return ax_err!(InvalidInput);
@@ -148,12 +162,14 @@ impl File {
             return ax_err!(PermissionDenied);
         }
 
+        let mount = crate::root::mount_ref(dir, path)?.or_else(|| dir_mount.cloned());
         node.open()?;
         if opts.truncate {
             node.truncate(0)?;
         }
         Ok(Self {
             node: WithCap::new(node, access_cap),
+            _mount: mount,
             is_append: opts.append,
             offset: 0,
         })
@@ -162,7 +178,7 @@ impl File {
     /// Opens a file at the path relative to the current directory. Returns a
     /// [`File`] object.
     pub fn open(path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_at(None, path, opts)
+        Self::_open_at(None, None, path, opts)
     }
 
     /// Truncates the file to the specified size.
@@ -243,7 +259,14 @@ impl File {
 }
 
 impl Directory {
-    fn _open_dir_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    /// Opens the directory at `path` relative to `dir`, which is on the
+    /// filesystem referenced by `dir_mount`.
+    fn _open_dir_at(
+        dir: Option<&VfsNodeRef>,
+        dir_mount: Option<&MountRef>,
+        path: &str,
+        opts: &OpenOptions,
+    ) -> AxResult<Self> {
         debug!("open dir: {}", path);
         if !opts.read {
             return ax_err!(InvalidInput);
@@ -262,9 +285,11 @@ impl Directory {
             return ax_err!(PermissionDenied);
         }
 
+        let mount = crate::root::mount_ref(dir, path)?.or_else(|| dir_mount.cloned());
         node.open()?;
         Ok(Self {
             node: WithCap::new(node, access_cap),
+            mount,
             entry_idx: 0,
         })
     }
@@ -280,19 +305,19 @@ impl Directory {
     /// Opens a directory at the path relative to the current directory.
     /// Returns a [`Directory`] object.
     pub fn open_dir(path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_dir_at(None, path, opts)
+        Self::_open_dir_at(None, None, path, opts)
     }
 
     /// Opens a directory at the path relative to this directory. Returns a
     /// [`Directory`] object.
     pub fn open_dir_at(&self, path: &str, opts: &OpenOptions) -> AxResult<Self> {
-        Self::_open_dir_at(self.access_at(path)?, path, opts)
+        Self::_open_dir_at(self.access_at(path)?, self.mount.as_ref(), path, opts)
     }
 
     /// Opens a file at the path relative to this directory. Returns a [`File`]
     /// object.
     pub fn open_file_at(&self, path: &str, opts: &OpenOptions) -> AxResult<File> {
-        File::_open_at(self.access_at(path)?, path, opts)
+        File::_open_at(self.access_at(path)?, self.mount.as_ref(), path, opts)
     }
 
     /// Creates an empty file at the path relative to this directory.
@@ -0,0 +1,40 @@
+use axfs_ext2::BlockDevice;
+use axfs_vfs::{VfsError, VfsResult};
+
+pub use axfs_ext2::{Ext2FileSystem, Ext2Node};
+
+use crate::dev::Disk;
+
+impl BlockDevice for Disk {
+    fn size(&self) -> u64 {
+        Disk::size(self)
+    }
+
+    fn read_at(&mut self, offset: u64, mut buf: &mut [u8]) -> VfsResult {
+        self.set_position(offset);
+        while !buf.is_empty() {
+            match self.read_one(buf) {
+                Ok(0) => return Err(VfsError::UnexpectedEof),
+                Ok(n) => buf = &mut buf[n..],
+                Err(_) => return Err(VfsError::Io),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, mut buf: &[u8]) -> VfsResult {
+        self.set_position(offset);
+        while !buf.is_empty() {
+            match self.write_one(buf) {
+                Ok(0) => return Err(VfsError::WriteZero),
+                Ok(n) => buf = &buf[n..],
+                Err(_) => return Err(VfsError::Io),
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> VfsResult {
+        Disk::flush(self).map_err(|_| VfsError::Io)
+    }
+}
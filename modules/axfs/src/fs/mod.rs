@@ -1,7 +1,10 @@
 cfg_if::cfg_if! {
     if #[cfg(feature = "myfs")] {
         pub mod myfs;
-    } else if #[cfg(feature = "fatfs")] {
+    } else {
+        #[cfg(feature = "ext2")]
+        pub mod ext2;
+        #[cfg(feature = "fatfs")]
         pub mod fatfs;
     }
 }
@@ -11,3 +14,6 @@ pub use axfs_devfs as devfs;
 
 #[cfg(feature = "ramfs")]
 pub use axfs_ramfs as ramfs;
+
+#[cfg(feature = "procfs")]
+pub use axfs_procfs as procfs;
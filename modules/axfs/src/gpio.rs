@@ -0,0 +1,225 @@
+//! GPIO controllers, as the devices `/dev/gpiochip0`, `/dev/gpiochip1`, etc.
+//!
+//! Each byte of a device file is the level of the pin at that offset, `b'0'`
+//! for low or `b'1'` for high. Reading it gets the levels of the pins, and
+//! writing it drives the output pins. Other operations, e.g., selecting the
+//! functions of the pins, are in [`crate::api`].
+//!
+//! Edge events are queued by the interrupt handler if the controller has an
+//! IRQ and the `irq` feature is enabled, otherwise the controller is polled
+//! when waiting for them.
+
+use alloc::{collections::VecDeque, format, string::String, sync::Arc, vec::Vec};
+
+use axdriver::{prelude::*, AxDeviceContainer};
+use axerrno::{ax_err, AxError, AxResult};
+use axfs_vfs::{VfsError, VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+use driver_gpio::{Edge, PinFunction, Pull};
+use lazy_init::LazyInit;
+
+use crate::irq_input::{InputBuffer, InputDevice, IrqInput};
+
+/// The maximum number of queued edge events of each controller, new events
+/// are dropped when it's full.
+const MAX_EVENTS: usize = 64;
+
+static CHIPS: LazyInit<Vec<Arc<GpioChip>>> = LazyInit::new();
+
+/// A GPIO controller in devfs.
+pub(crate) struct GpioChip {
+    name: String,
+    /// The controller and the pins of the queued edge events.
+    input: IrqInput<AxGpioDevice, VecDeque<usize>>,
+}
+
+impl InputBuffer<usize> for VecDeque<usize> {
+    fn push(&mut self, pin: usize) {
+        if self.len() < MAX_EVENTS {
+            self.push_back(pin);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        VecDeque::is_empty(self)
+    }
+}
+
+impl InputDevice for AxGpioDevice {
+    type Item = usize;
+
+    fn take_input(&mut self) -> Option<usize> {
+        self.take_event()
+    }
+
+    fn input_irq(&self) -> Option<usize> {
+        self.irq_num()
+    }
+}
+
+fn as_ax_err(err: DevError) -> AxError {
+    match err {
+        DevError::InvalidParam => AxError::InvalidInput,
+        DevError::Unsupported => AxError::Unsupported,
+        _ => AxError::Io,
+    }
+}
+
+impl GpioChip {
+    fn new(name: String, dev: AxGpioDevice) -> Self {
+        Self {
+            name,
+            input: IrqInput::new(dev, VecDeque::new()),
+        }
+    }
+
+    /// Waits until an edge event is detected, and returns its pin.
+    fn wait_event(&self) -> usize {
+        self.input.wait_for(|events| events.pop_front())
+    }
+}
+
+impl VfsNodeOps for GpioChip {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        let num_pins = self.input.dev().lock().num_pins() as u64;
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::default_file(),
+            VfsNodeType::CharDevice,
+            num_pins,
+            0,
+        ))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let dev = self.input.dev().lock();
+        let start = (offset as usize).min(dev.num_pins());
+        let n = buf.len().min(dev.num_pins() - start);
+        for (i, b) in buf[..n].iter_mut().enumerate() {
+            let high = dev.level(start + i).map_err(as_ax_err)?;
+            *b = if high { b'1' } else { b'0' };
+        }
+        Ok(n)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        let mut dev = self.input.dev().lock();
+        let start = offset as usize;
+        if start.saturating_add(buf.len()) > dev.num_pins() {
+            return Err(VfsError::InvalidInput);
+        }
+        for (i, &b) in buf.iter().enumerate() {
+            let high = match b {
+                b'0' => false,
+                b'1' => true,
+                _ => return Err(VfsError::InvalidInput),
+            };
+            dev.set_level(start + i, high).map_err(as_ax_err)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
+
+/// The handler of the interrupts of all GPIO controllers.
+#[cfg(feature = "irq")]
+fn gpio_irq_handler() {
+    for chip in CHIPS.try_get().into_iter().flatten() {
+        chip.input.handle_irq();
+    }
+}
+
+/// Adds the GPIO controllers to devfs as `gpiochip0`, `gpiochip1`, etc.
+pub(crate) fn add_to_devfs(devfs: &crate::fs::devfs::DeviceFileSystem) {
+    for chip in CHIPS.try_get().into_iter().flatten() {
+        devfs.add(&chip.name, chip.clone());
+    }
+}
+
+/// Returns the GPIO controller at `path`.
+fn gpio_chip(path: &str) -> AxResult<&'static GpioChip> {
+    let node = crate::root::lookup(None, path)?;
+    let chip = CHIPS
+        .try_get()
+        .into_iter()
+        .flatten()
+        .find(|chip| core::ptr::eq(Arc::as_ptr(chip) as *const u8, Arc::as_ptr(&node) as _));
+    match chip {
+        Some(chip) => Ok(chip),
+        None => ax_err!(InvalidInput, "not a GPIO controller"),
+    }
+}
+
+/// Calls `f` with the device of the GPIO controller at `path`.
+fn with_dev<T>(path: &str, f: impl FnOnce(&mut AxGpioDevice) -> DevResult<T>) -> AxResult<T> {
+    let chip = gpio_chip(path)?;
+    let mut dev = chip.input.dev().lock();
+    f(&mut dev).map_err(as_ax_err)
+}
+
+pub(crate) fn function(path: &str, pin: usize) -> AxResult<PinFunction> {
+    with_dev(path, |dev| dev.function(pin))
+}
+
+pub(crate) fn set_function(path: &str, pin: usize, func: PinFunction) -> AxResult {
+    with_dev(path, |dev| dev.set_function(pin, func))
+}
+
+pub(crate) fn level(path: &str, pin: usize) -> AxResult<bool> {
+    with_dev(path, |dev| dev.level(pin))
+}
+
+pub(crate) fn set_level(path: &str, pin: usize, high: bool) -> AxResult {
+    with_dev(path, |dev| dev.set_level(pin, high))
+}
+
+pub(crate) fn pull(path: &str, pin: usize) -> AxResult<Pull> {
+    with_dev(path, |dev| dev.pull(pin))
+}
+
+pub(crate) fn set_pull(path: &str, pin: usize, pull: Pull) -> AxResult {
+    with_dev(path, |dev| dev.set_pull(pin, pull))
+}
+
+pub(crate) fn set_edge_interrupt(path: &str, pin: usize, edge: Edge) -> AxResult {
+    let chip = gpio_chip(path)?;
+    chip.input
+        .dev()
+        .lock()
+        .set_edge_interrupt(pin, edge)
+        .map_err(as_ax_err)?;
+    // drop the queued events of the pin, which may be of the old edges
+    chip.input.buf().lock().retain(|&p| p != pin);
+    Ok(())
+}
+
+pub(crate) fn wait_event(path: &str) -> AxResult<usize> {
+    Ok(gpio_chip(path)?.wait_event())
+}
+
+/// Takes all GPIO devices, and registers the handlers of their interrupts.
+pub(crate) fn init(mut gpio_devs: AxDeviceContainer<AxGpioDevice>) {
+    let mut chips = Vec::new();
+    while let Some(dev) = gpio_devs.take_one() {
+        let name = format!("gpiochip{}", chips.len());
+        info!(
+            "  found GPIO controller {:?} with {} pins as {}",
+            dev.device_name(),
+            dev.num_pins(),
+            name
+        );
+        chips.push(GpioChip::new(name, dev));
+    }
+
+    #[cfg(feature = "irq")]
+    crate::irq_input::register_irqs(
+        chips.iter_mut().map(|chip| &mut chip.input),
+        gpio_irq_handler,
+        "GPIO",
+    );
+
+    CHIPS.init_by(chips.into_iter().map(Arc::new).collect());
+}
@@ -0,0 +1,144 @@
+//! Input of character devices in devfs, e.g., bytes received by serial ports.
+//!
+//! The input is moved from the device to a buffer by the interrupt handler if
+//! the device has an IRQ and the `irq` feature is enabled, otherwise the
+//! device is polled when waiting for the input.
+
+use spinlock::SpinNoIrq;
+
+/// A device that produces input items.
+pub(crate) trait InputDevice {
+    /// The type of input items.
+    type Item;
+
+    /// Takes an input item from the device, or returns `None` if there's
+    /// nothing more.
+    fn take_input(&mut self) -> Option<Self::Item>;
+
+    /// The IRQ number raised when there's input, or `None` if the device can
+    /// only be polled.
+    fn input_irq(&self) -> Option<usize>;
+
+    /// Acknowledges the interrupt, and returns whether it's raised by the
+    /// device.
+    fn ack_input_irq(&mut self) -> bool {
+        true
+    }
+}
+
+/// A buffer of input items.
+pub(crate) trait InputBuffer<T> {
+    /// Pushes an item, which may be dropped if the buffer is full.
+    fn push(&mut self, item: T);
+
+    /// Whether there's no item in the buffer.
+    fn is_empty(&self) -> bool;
+}
+
+/// A device with its input buffer, and the tasks waiting for the input.
+pub(crate) struct IrqInput<D, B> {
+    dev: SpinNoIrq<D>,
+    buf: SpinNoIrq<B>,
+    /// Whether the input is buffered by the interrupt handler.
+    irq_driven: bool,
+    #[cfg(feature = "multitask")]
+    wq: axtask::WaitQueue,
+}
+
+impl<D: InputDevice, B: InputBuffer<D::Item>> IrqInput<D, B> {
+    pub fn new(dev: D, buf: B) -> Self {
+        Self {
+            dev: SpinNoIrq::new(dev),
+            buf: SpinNoIrq::new(buf),
+            irq_driven: false,
+            #[cfg(feature = "multitask")]
+            wq: axtask::WaitQueue::new(),
+        }
+    }
+
+    pub fn dev(&self) -> &SpinNoIrq<D> {
+        &self.dev
+    }
+
+    #[cfg_attr(not(feature = "gpio"), allow(dead_code))]
+    pub fn buf(&self) -> &SpinNoIrq<B> {
+        &self.buf
+    }
+
+    /// Moves the input from the device to the buffer.
+    fn receive(&self) {
+        let mut dev = self.dev.lock();
+        let mut buf = self.buf.lock();
+        while let Some(item) = dev.take_input() {
+            buf.push(item);
+        }
+    }
+
+    /// Waits until `take` gets something from the buffer, and returns it.
+    pub fn wait_for<T>(&self, mut take: impl FnMut(&mut B) -> Option<T>) -> T {
+        loop {
+            if !self.irq_driven {
+                self.receive();
+            }
+            if let Some(res) = take(&mut self.buf.lock()) {
+                return res;
+            }
+            #[cfg(feature = "multitask")]
+            if self.irq_driven {
+                self.wq.wait_until(|| !self.buf.lock().is_empty());
+            } else {
+                axtask::yield_now();
+            }
+            #[cfg(not(feature = "multitask"))]
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Receives the input and wakes up the waiting tasks, if the interrupt is
+    /// raised by the device.
+    #[cfg(feature = "irq")]
+    pub fn handle_irq(&self) {
+        if self.irq_driven && self.dev.lock().ack_input_irq() {
+            self.receive();
+            #[cfg(feature = "multitask")]
+            self.wq.notify_all(false);
+        }
+    }
+}
+
+/// Registers `handler` for the IRQs of the devices, which should call
+/// [`IrqInput::handle_irq`] of all of them. It's registered once for the
+/// devices sharing an IRQ.
+///
+/// The input of a device is buffered by the handler only if its IRQ is
+/// registered successfully. `kind` is the kind of the devices in logs.
+#[cfg(feature = "irq")]
+pub(crate) fn register_irqs<'a, D, B>(
+    inputs: impl Iterator<Item = &'a mut IrqInput<D, B>>,
+    handler: axhal::irq::IrqHandler,
+    kind: &str,
+) where
+    D: InputDevice + 'a,
+    B: 'a,
+{
+    // (IRQ number, whether the handler is registered)
+    let mut irqs: alloc::vec::Vec<(usize, bool)> = alloc::vec::Vec::new();
+    for input in inputs {
+        let Some(irq_num) = input.dev.lock().input_irq() else {
+            continue;
+        };
+        input.irq_driven = match irqs.iter().find(|irq| irq.0 == irq_num) {
+            Some(irq) => irq.1,
+            None => {
+                let ok = axhal::irq::register_handler(irq_num, handler);
+                if ok {
+                    info!("  use {} IRQ {}", kind, irq_num);
+                } else {
+                    warn!("  failed to register {} IRQ {}", kind, irq_num);
+                }
+                irqs.push((irq_num, ok));
+                ok
+            }
+        };
+    }
+}
@@ -2,21 +2,47 @@
 //!
 //! It provides unified filesystem operations for various filesystems.
 //!
+//! The root filesystem is created on the first block device. Additional block
+//! devices, as well as other filesystems, can be mounted at runtime by
+//! [`api::mount`].
+//!
+//! Blocks of the devices are cached in memory (`BLOCK_CACHE_SIZE` blocks for
+//! each device, configured by [axconfig]), and modified blocks are written
+//! back when a file is synchronized, a filesystem is unmounted, or
+//! [`api::sync`] is called.
+//!
 //! # Cargo Features
 //!
 //! - `fatfs`: Use [FAT] as the main filesystem and mount it on `/`. This feature
 //!    is **enabled** by default.
+//! - `ext2`: Use [ext2] as the main filesystem and mount it on `/`. It takes
+//!    precedence over `fatfs` if both are enabled. Additional block devices
+//!    formatted as ext2, ext3 or ext4 can also be mounted, where the latter
+//!    two are read-only if they use features that ext2 does not have.
 //! - `devfs`: Mount [`axfs_devfs::DeviceFileSystem`] on `/dev`. This feature is
 //!    **enabled** by default.
 //! - `ramfs`: Mount [`axfs_ramfs::RamFileSystem`] on `/tmp`. This feature is
 //!    **enabled** by default.
+//! - `procfs`: Mount [`axfs_procfs::ProcFileSystem`] on `/proc`, which reports
+//!    the memory usage, CPUs, uptime and mount table. This feature is
+//!    **enabled** by default.
+//! - `multitask`: List the tasks in `/proc/tasks` if `procfs` is enabled.
+//! - `serial`: Add the serial ports to devfs as `/dev/ttyS0`, `/dev/ttyS1`,
+//!    etc., which are taken by [`init_serial_ports`].
+//! - `gpio`: Add the GPIO controllers to devfs as `/dev/gpiochip0`,
+//!    `/dev/gpiochip1`, etc., which are taken by [`init_gpio`].
+//! - `irq`: Buffer the bytes received by the serial ports and the edge events
+//!    of the GPIO controllers in their interrupt handlers, instead of polling
+//!    them.
 //! - `myfs`: Allow users to define their custom filesystems to override the
 //!    default. In this case, [`MyFileSystemIf`] is required to be implemented
 //!    to create and initialize other filesystems. This feature is **disabled** by
 //!    by default, but it will override other filesystem selection features if
 //!    both are enabled.
 //!
+//! [axconfig]: https://rcore-os.github.io/arceos/axconfig/index.html
 //! [FAT]: https://en.wikipedia.org/wiki/File_Allocation_Table
+//! [ext2]: https://en.wikipedia.org/wiki/Ext2
 //! [`MyFileSystemIf`]: fops::MyFileSystemIf
 
 #![cfg_attr(all(not(test), not(doc)), no_std)]
@@ -31,6 +57,13 @@ mod fs;
 mod mounts;
 mod root;
 
+#[cfg(feature = "gpio")]
+mod gpio;
+#[cfg(any(feature = "serial", feature = "gpio"))]
+mod irq_input;
+#[cfg(feature = "serial")]
+mod tty;
+
 pub mod api;
 pub mod fops;
 
@@ -42,5 +75,41 @@ pub fn init_filesystems(mut blk_devs: AxDeviceContainer<AxBlockDevice>) {
 
     let dev = blk_devs.take_one().expect("No block device found!");
     info!("  use block device 0: {:?}", dev.device_name());
-    self::root::init_rootfs(self::dev::Disk::new(dev));
+    let root_disk = self::dev::Disk::new(dev);
+
+    // Other block devices can be mounted later by `api::mount`.
+    let mut idx = 1;
+    while let Some(dev) = blk_devs.take_one() {
+        let name = alloc::format!("sd{}", (b'a' + idx) as char);
+        info!(
+            "  found block device {}: {:?} as {}",
+            idx,
+            dev.device_name(),
+            name
+        );
+        self::mounts::add_disk(name, self::dev::Disk::new(dev));
+        idx += 1;
+    }
+
+    self::root::init_rootfs(root_disk);
+}
+
+/// Takes the character devices as serial ports, which are added to devfs as
+/// `/dev/ttyS0`, `/dev/ttyS1`, etc.
+///
+/// It must be called before [`init_filesystems`].
+#[cfg(feature = "serial")]
+pub fn init_serial_ports(char_devs: AxDeviceContainer<AxCharDevice>) {
+    info!("Initialize serial ports...");
+    self::tty::init(char_devs);
+}
+
+/// Takes the GPIO controllers, which are added to devfs as `/dev/gpiochip0`,
+/// `/dev/gpiochip1`, etc.
+///
+/// It must be called before [`init_filesystems`].
+#[cfg(feature = "gpio")]
+pub fn init_gpio(gpio_devs: AxDeviceContainer<AxGpioDevice>) {
+    info!("Initialize GPIO controllers...");
+    self::gpio::init(gpio_devs);
 }
@@ -1,7 +1,86 @@
-use alloc::sync::Arc;
+use alloc::{collections::BTreeMap, string::String, sync::Arc};
+use axerrno::{ax_err, AxResult};
 use axfs_vfs::{VfsNodeType, VfsOps, VfsResult};
+use axsync::Mutex;
 
-use crate::fs;
+use crate::{dev::Disk, fs};
+
+/// Block devices that are not used by the root filesystem, indexed by their
+/// names (`sdb`, `sdc`, ...).
+static DISKS: Mutex<BTreeMap<String, Disk>> = Mutex::new(BTreeMap::new());
+
+/// FAT filesystems that have been created on the additional block devices.
+///
+/// A filesystem is kept here after it is unmounted, so the same device can
+/// be mounted again later. The filesystems are leaked, as
+/// [`FatFileSystem::init`](fs::fatfs::FatFileSystem::init) borrows them for
+/// the rest of the program.
+#[cfg(all(feature = "fatfs", not(feature = "myfs")))]
+static FAT_VOLUMES: Mutex<BTreeMap<String, &'static Arc<fs::fatfs::FatFileSystem>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Ext2 filesystems that have been created on the additional block devices,
+/// kept for the same reason as `FAT_VOLUMES`.
+#[cfg(all(feature = "ext2", not(feature = "myfs")))]
+static EXT2_VOLUMES: Mutex<BTreeMap<String, Arc<fs::ext2::Ext2FileSystem>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Makes a block device available to be mounted by [`new_fs`].
+pub(crate) fn add_disk(name: String, disk: Disk) {
+    DISKS.lock().insert(name, disk);
+}
+
+/// Creates a filesystem of type `fstype` on `source`, so that it can be
+/// mounted.
+///
+/// Disk-based filesystems take a block device name (e.g., `/dev/sdb` or
+/// `sdb`) as the source, others ignore it.
+pub(crate) fn new_fs(source: &str, fstype: &str) -> AxResult<Arc<dyn VfsOps>> {
+    let _source = source.trim_start_matches("/dev/");
+    match fstype {
+        #[cfg(all(feature = "fatfs", not(feature = "myfs")))]
+        "fat" | "vfat" | "fatfs" => {
+            let mut volumes = FAT_VOLUMES.lock();
+            if let Some(fs) = volumes.get(_source) {
+                return Ok((*fs).clone());
+            }
+            let disk = match DISKS.lock().remove(_source) {
+                Some(disk) => disk,
+                None => return ax_err!(NotFound, "block device not found"),
+            };
+            let fs = Arc::new(fs::fatfs::FatFileSystem::new(disk));
+            let fs: &'static Arc<_> = alloc::boxed::Box::leak(alloc::boxed::Box::new(fs));
+            fs.init();
+            volumes.insert(_source.into(), fs);
+            Ok(fs.clone())
+        }
+        // ext3 and ext4 are mounted read-only unless only ext2 features are
+        // used.
+        #[cfg(all(feature = "ext2", not(feature = "myfs")))]
+        "ext2" | "ext3" | "ext4" => {
+            let mut volumes = EXT2_VOLUMES.lock();
+            if let Some(fs) = volumes.get(_source) {
+                return Ok(fs.clone());
+            }
+            let disk = match DISKS.lock().remove(_source) {
+                Some(disk) => disk,
+                None => return ax_err!(NotFound, "block device not found"),
+            };
+            let fs = Arc::new(fs::ext2::Ext2FileSystem::new(disk)?);
+            volumes.insert(_source.into(), fs.clone());
+            Ok(fs)
+        }
+        #[cfg(feature = "ramfs")]
+        "ramfs" | "tmpfs" => Ok(ramfs()),
+        #[cfg(feature = "devfs")]
+        "devfs" => Ok(devfs()),
+        #[cfg(feature = "procfs")]
+        "proc" | "procfs" => Ok(procfs()),
+        #[cfg(feature = "sysfs")]
+        "sysfs" => Ok(sysfs()?),
+        _ => ax_err!(Unsupported, "unknown filesystem type"),
+    }
+}
 
 #[cfg(feature = "devfs")]
 pub(crate) fn devfs() -> Arc<fs::devfs::DeviceFileSystem> {
@@ -13,6 +92,10 @@ pub(crate) fn devfs() -> Arc<fs::devfs::DeviceFileSystem> {
     devfs.add("null", Arc::new(null));
     devfs.add("zero", Arc::new(zero));
     foo_dir.add("bar", Arc::new(bar));
+    #[cfg(feature = "serial")]
+    crate::tty::add_to_devfs(&devfs);
+    #[cfg(feature = "gpio")]
+    crate::gpio::add_to_devfs(&devfs);
     Arc::new(devfs)
 }
 
@@ -22,29 +105,137 @@ pub(crate) fn ramfs() -> Arc<fs::ramfs::RamFileSystem> {
 }
 
 #[cfg(feature = "procfs")]
-pub(crate) fn procfs() -> VfsResult<Arc<fs::ramfs::RamFileSystem>> {
-    let procfs = fs::ramfs::RamFileSystem::new();
-    let proc_root = procfs.root_dir();
-
-    // Create /proc/sys/net/core/somaxconn
-    proc_root.create("sys", VfsNodeType::Dir)?;
-    proc_root.create("sys/net", VfsNodeType::Dir)?;
-    proc_root.create("sys/net/core", VfsNodeType::Dir)?;
-    proc_root.create("sys/net/core/somaxconn", VfsNodeType::File)?;
-    let file_somaxconn = proc_root.clone().lookup("./sys/net/core/somaxconn")?;
-    file_somaxconn.write_at(0, b"4096\n")?;
-
-    // Create /proc/sys/vm/overcommit_memory
-    proc_root.create("sys/vm", VfsNodeType::Dir)?;
-    proc_root.create("sys/vm/overcommit_memory", VfsNodeType::File)?;
-    let file_over = proc_root.clone().lookup("./sys/vm/overcommit_memory")?;
-    file_over.write_at(0, b"0\n")?;
-
-    // Create /proc/self/stat
-    proc_root.create("self", VfsNodeType::Dir)?;
-    proc_root.create("self/stat", VfsNodeType::File)?;
-
-    Ok(Arc::new(procfs))
+pub(crate) fn procfs() -> Arc<fs::procfs::ProcFileSystem> {
+    use fs::procfs::{ProcFile, ProcFileSystem};
+
+    let procfs = ProcFileSystem::new();
+
+    // /proc/meminfo
+    procfs.add(
+        "meminfo",
+        Arc::new(ProcFile::new(|| {
+            use axhal::mem::PAGE_SIZE_4K;
+            let allocator = axalloc::global_allocator();
+            // The byte allocator takes its memory from the page allocator.
+            let total = (allocator.used_pages() + allocator.available_pages()) * PAGE_SIZE_4K;
+            let free = allocator.available_pages() * PAGE_SIZE_4K + allocator.available_bytes();
+            alloc::format!(
+                "MemTotal: {:>12} kB\nMemFree: {:>13} kB\nMemUsed: {:>13} kB\n",
+                total / 1024,
+                free / 1024,
+                (total - free) / 1024,
+            )
+        })),
+    );
+
+    // /proc/cpuinfo
+    procfs.add(
+        "cpuinfo",
+        Arc::new(ProcFile::new(|| {
+            let mut info = String::new();
+            for cpu in 0..axhal::cpu::cpu_count() {
+                info += &alloc::format!(
+                    "processor\t: {cpu}\narch\t\t: {}\nplatform\t: {}\n\n",
+                    axconfig::ARCH,
+                    axconfig::PLATFORM,
+                );
+            }
+            info
+        })),
+    );
+
+    // /proc/uptime
+    procfs.add(
+        "uptime",
+        Arc::new(ProcFile::new(|| {
+            let now = axhal::time::current_time();
+            alloc::format!("{}.{:02} 0.00\n", now.as_secs(), now.subsec_millis() / 10)
+        })),
+    );
+
+    // /proc/mounts
+    procfs.add(
+        "mounts",
+        Arc::new(ProcFile::new(|| {
+            let mut mounts = String::new();
+            for m in crate::root::mounts() {
+                mounts += &alloc::format!("{} {} {} rw 0 0\n", m.source, m.target, m.fstype);
+            }
+            mounts
+        })),
+    );
+
+    // /proc/tasks/<id>/status
+    #[cfg(feature = "multitask")]
+    procfs.add("tasks", tasks_dir());
+
+    // /proc/sys/net/core/somaxconn
+    let sys = procfs.mkdir("sys");
+    let core = sys.mkdir("net").mkdir("core");
+    core.add("somaxconn", Arc::new(ProcFile::new_static("4096\n")));
+
+    // /proc/sys/vm/overcommit_memory
+    let vm = sys.mkdir("vm");
+    vm.add("overcommit_memory", Arc::new(ProcFile::new_static("0\n")));
+
+    // /proc/self/stat
+    let this = procfs.mkdir("self");
+    this.add("stat", Arc::new(ProcFile::new_static("")));
+
+    Arc::new(procfs)
+}
+
+/// Generates a directory for each task, containing the `status` file.
+#[cfg(all(feature = "procfs", feature = "multitask"))]
+fn tasks_dir() -> axfs_vfs::VfsNodeRef {
+    use axtask::{AxTaskRef, TaskState};
+    use fs::procfs::{DirNode, DynDirNode, ProcFile};
+
+    fn find_task(name: &str) -> Option<AxTaskRef> {
+        let id = name.parse::<u64>().ok()?;
+        axtask::all_tasks()
+            .into_iter()
+            .find(|task| task.id().as_u64() == id)
+    }
+
+    fn status(task: &AxTaskRef) -> String {
+        let state = match task.state() {
+            TaskState::Running => "R (running)",
+            TaskState::Ready => "R (ready)",
+            TaskState::Blocked => "S (sleeping)",
+            TaskState::Exited => "Z (zombie)",
+        };
+        alloc::format!(
+            "Name:\t{}\nTid:\t{}\nState:\t{}\n",
+            task.name(),
+            task.id().as_u64(),
+            state
+        )
+    }
+
+    DynDirNode::new(
+        None,
+        || {
+            let tasks = axtask::all_tasks().into_iter();
+            tasks
+                .map(|task| alloc::format!("{}", task.id().as_u64()))
+                .collect()
+        },
+        |name| {
+            let task = find_task(name)?;
+            let dir = DirNode::new(None);
+            // Only keep a weak reference, so the task can still be dropped.
+            let task = Arc::downgrade(&task);
+            dir.add(
+                "status",
+                Arc::new(ProcFile::new(move || match task.upgrade() {
+                    Some(task) => status(&task),
+                    None => String::new(),
+                })),
+            );
+            Some(dir)
+        },
+    )
 }
 
 #[cfg(feature = "sysfs")]
@@ -1,8 +1,10 @@
 //! Root directory of the filesystem
 //!
-//! TODO: it doesn't work very well if the mount points have containment relationships.
+//! Mounted filesystems are organized as a tree: each filesystem keeps the
+//! filesystems mounted beneath it, so paths are resolved one component at a
+//! time and mount points may be nested.
 
-use alloc::{string::String, sync::Arc, vec::Vec};
+use alloc::{collections::BTreeMap, format, string::String, sync::Arc, vec, vec::Vec};
 use axerrno::{ax_err, AxError, AxResult};
 use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
 use axsync::Mutex;
@@ -11,23 +13,88 @@ use lazy_init::LazyInit;
 use crate::{api::FileType, fs, mounts};
 
 static CURRENT_DIR_PATH: Mutex<String> = Mutex::new(String::new());
-static CURRENT_DIR: LazyInit<Mutex<VfsNodeRef>> = LazyInit::new();
+
+/// A reference to a mounted filesystem held by opened files and directories,
+/// which keeps the filesystem from being unmounted.
+pub(crate) type MountRef = Arc<()>;
 
 struct MountPoint {
-    path: &'static str,
     fs: Arc<dyn VfsOps>,
+    source: String,
+    fstype: String,
+    /// Cloned by the files and directories opened on `fs`.
+    users: MountRef,
+    /// Filesystems mounted beneath this one, keyed by their paths relative to
+    /// the root directory of `fs`.
+    children: BTreeMap<String, MountPoint>,
 }
 
 struct RootDirectory {
-    main_fs: Arc<dyn VfsOps>,
-    mounts: Vec<MountPoint>,
+    root: Mutex<MountPoint>,
+}
+
+/// An entry of the mount table.
+pub(crate) struct MountInfo {
+    pub source: String,
+    pub target: String,
+    pub fstype: String,
 }
 
 static ROOT_DIR: LazyInit<Arc<RootDirectory>> = LazyInit::new();
 
 impl MountPoint {
-    pub fn new(path: &'static str, fs: Arc<dyn VfsOps>) -> Self {
-        Self { path, fs }
+    pub fn new(fs: Arc<dyn VfsOps>, source: &str, fstype: &str) -> Self {
+        Self {
+            fs,
+            source: source.into(),
+            fstype: fstype.into(),
+            users: Arc::new(()),
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Appends the entries of this filesystem and the ones beneath it to
+    /// `mounts`, where `target` is the absolute path of this mount point.
+    fn collect_mounts(&self, target: &str, mounts: &mut Vec<MountInfo>) {
+        mounts.push(MountInfo {
+            source: self.source.clone(),
+            target: target.into(),
+            fstype: self.fstype.clone(),
+        });
+        for (path, child) in &self.children {
+            child.collect_mounts(&format!("{}/{path}", target.trim_end_matches('/')), mounts);
+        }
+    }
+
+    /// Returns the length of the shortest prefix of `path` that is a child
+    /// mount point, if any.
+    fn child_prefix_len(&self, path: &str) -> Option<usize> {
+        path.match_indices('/')
+            .map(|(i, _)| i)
+            .chain(core::iter::once(path.len()))
+            .find(|&end| self.children.contains_key(&path[..end]))
+    }
+
+    /// Finds the innermost filesystem that contains `path`, returns it along
+    /// with the path relative to its root directory.
+    ///
+    /// `path` must be canonical and relative to the root of this filesystem.
+    fn resolve<'a>(&self, path: &'a str) -> (&Self, &'a str) {
+        match self.child_prefix_len(path) {
+            Some(end) => self.children[&path[..end]].resolve(path[end..].trim_start_matches('/')),
+            None => (self, path),
+        }
+    }
+
+    /// The mutable version of [`MountPoint::resolve`].
+    fn resolve_mut<'a>(&mut self, path: &'a str) -> (&mut Self, &'a str) {
+        match self.child_prefix_len(path) {
+            Some(end) => {
+                let child = self.children.get_mut(&path[..end]).unwrap();
+                child.resolve_mut(path[end..].trim_start_matches('/'))
+            }
+            None => (self, path),
+        }
     }
 }
 
@@ -37,37 +104,115 @@ impl Drop for MountPoint {
     }
 }
 
+/// Converts `path` that relative to the root directory to its canonical form,
+/// without the leading `/`.
+fn canonical_in_root(path: &str) -> String {
+    let path = axfs_vfs::path::canonicalize(&(String::from("/") + path));
+    path.trim_start_matches('/').into()
+}
+
 impl RootDirectory {
-    pub const fn new(main_fs: Arc<dyn VfsOps>) -> Self {
+    pub fn new(main_fs: Arc<dyn VfsOps>, source: &str, fstype: &str) -> Self {
         Self {
-            main_fs,
-            mounts: Vec::new(),
+            root: Mutex::new(MountPoint::new(main_fs, source, fstype)),
         }
     }
 
-    pub fn mount(&mut self, path: &'static str, fs: Arc<dyn VfsOps>) -> AxResult {
-        if path == "/" {
-            return ax_err!(InvalidInput, "cannot mount root filesystem");
-        }
+    pub fn mount(&self, source: &str, path: &str, fstype: &str, fs: Arc<dyn VfsOps>) -> AxResult {
         if !path.starts_with('/') {
             return ax_err!(InvalidInput, "mount path must start with '/'");
         }
-        if self.mounts.iter().any(|mp| mp.path == path) {
+        let path = axfs_vfs::path::canonicalize(path);
+        if path == "/" {
+            return ax_err!(InvalidInput, "cannot mount root filesystem");
+        }
+        let mut root = self.root.lock();
+        let (parent, rest) = root.resolve_mut(&path[1..]);
+        if rest.is_empty() {
             return ax_err!(InvalidInput, "mount point already exists");
         }
-        // create the mount point in the main filesystem if it does not exist
-        self.main_fs.root_dir().create(path, FileType::Dir)?;
-        fs.mount(path, self.main_fs.root_dir().lookup(path)?)?;
-        self.mounts.push(MountPoint::new(path, fs));
+        // create the mount point in the parent filesystem if it does not exist
+        let parent_root = parent.fs.root_dir();
+        let mount_point = match parent_root.clone().lookup(rest) {
+            Err(AxError::NotFound) => {
+                parent_root.create(rest, FileType::Dir)?;
+                parent_root.lookup(rest)?
+            }
+            res => res?,
+        };
+        fs.mount(&path, mount_point)?;
+        parent
+            .children
+            .insert(rest.into(), MountPoint::new(fs, source, fstype));
         Ok(())
     }
 
-    pub fn _umount(&mut self, path: &str) {
-        self.mounts.retain(|mp| mp.path != path);
+    pub fn umount(&self, path: &str) -> AxResult {
+        let path = canonical_in_root(path);
+        if path.is_empty() {
+            return ax_err!(InvalidInput, "cannot unmount root filesystem");
+        }
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", &path));
+        let mut root = self.root.lock();
+        let (parent, rest) = root.resolve_mut(dir);
+        let key = if rest.is_empty() {
+            name.into()
+        } else {
+            format!("{rest}/{name}")
+        };
+        match parent.children.get(&key) {
+            None => ax_err!(InvalidInput, "not a mount point"),
+            Some(mp) if !mp.children.is_empty() => {
+                ax_err!(ResourceBusy, "other filesystems are mounted beneath")
+            }
+            Some(mp) if Arc::strong_count(&mp.users) > 1 => {
+                ax_err!(ResourceBusy, "files are open on the filesystem")
+            }
+            Some(_) => {
+                parent.children.remove(&key); // `VfsOps::umount` is called on drop
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the mount table, with parents before their children.
+    pub fn mounts(&self) -> Vec<MountInfo> {
+        let mut mounts = Vec::new();
+        self.root.lock().collect_mounts("/", &mut mounts);
+        mounts
+    }
+
+    /// Returns the reference to the filesystem that contains `path`.
+    pub fn mount_ref(&self, path: &str) -> MountRef {
+        let path = canonical_in_root(path);
+        self.root.lock().resolve(&path).0.users.clone()
     }
 
     pub fn contains(&self, path: &str) -> bool {
-        self.mounts.iter().any(|mp| mp.path == path)
+        let path = canonical_in_root(path);
+        !path.is_empty() && self.root.lock().resolve(&path).1.is_empty()
+    }
+
+    /// Creates a hard link at `dst_path` to the node at `src_path`, both in
+    /// the same mounted filesystem.
+    pub fn hard_link(&self, src_path: &str, dst_path: &str) -> AxResult {
+        let src_path = canonical_in_root(src_path);
+        let dst_path = canonical_in_root(dst_path);
+        let (fs, src_rest, dst_rest) = {
+            let root = self.root.lock();
+            let (src_mp, src_rest) = root.resolve(&src_path);
+            let (dst_mp, dst_rest) = root.resolve(&dst_path);
+            if src_rest.is_empty() {
+                return ax_err!(PermissionDenied); // cannot link directories
+            } else if dst_rest.is_empty() {
+                return ax_err!(AlreadyExists);
+            } else if !core::ptr::eq(src_mp, dst_mp) {
+                return ax_err!(InvalidInput, "cannot link across mount points");
+            }
+            (src_mp.fs.clone(), src_rest, dst_rest)
+        };
+        let node = fs.root_dir().lookup(src_rest)?;
+        fs.root_dir().link(dst_rest, &node)
     }
 
     fn lookup_mounted_fs<F, T>(&self, path: &str, f: F) -> AxResult<T>
@@ -75,29 +220,13 @@ impl RootDirectory {
         F: FnOnce(Arc<dyn VfsOps>, &str) -> AxResult<T>,
     {
         debug!("lookup at root: {}", path);
-        let path = path.trim_matches('/');
-        if let Some(rest) = path.strip_prefix("./") {
-            return self.lookup_mounted_fs(rest, f);
-        }
-
-        let mut idx = 0;
-        let mut max_len = 0;
-
-        // Find the filesystem that has the longest mounted path match
-        // TODO: more efficient, e.g. trie
-        for (i, mp) in self.mounts.iter().enumerate() {
-            // skip the first '/'
-            if path.starts_with(&mp.path[1..]) && mp.path.len() - 1 > max_len {
-                max_len = mp.path.len() - 1;
-                idx = i;
-            }
-        }
-
-        if max_len == 0 {
-            f(self.main_fs.clone(), path) // not matched any mount point
-        } else {
-            f(self.mounts[idx].fs.clone(), &path[max_len..]) // matched at `idx`
-        }
+        let path = canonical_in_root(path);
+        let (fs, rest) = {
+            let root = self.root.lock();
+            let (mp, rest) = root.resolve(&path);
+            (mp.fs.clone(), rest)
+        };
+        f(fs, rest)
     }
 }
 
@@ -105,7 +234,8 @@ impl VfsNodeOps for RootDirectory {
     axfs_vfs::impl_vfs_dir_default! {}
 
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        self.main_fs.root_dir().get_attr()
+        let main_fs = self.root.lock().fs.clone();
+        main_fs.root_dir().get_attr()
     }
 
     fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
@@ -132,64 +262,167 @@ impl VfsNodeOps for RootDirectory {
         })
     }
 
-    fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
-        self.lookup_mounted_fs(src_path, |fs, rest_path| {
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        self.lookup_mounted_fs(path, |fs, rest_path| {
             if rest_path.is_empty() {
-                ax_err!(PermissionDenied) // cannot rename mount points
+                ax_err!(AlreadyExists) // mount points already exist
             } else {
-                fs.root_dir().rename(rest_path, dst_path)
+                fs.root_dir().symlink(rest_path, target)
             }
         })
     }
+
+    fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        let src_path = canonical_in_root(src_path);
+        let dst_path = canonical_in_root(dst_path);
+        let (fs, src_rest, dst_rest) = {
+            let root = self.root.lock();
+            let (src_mp, src_rest) = root.resolve(&src_path);
+            let (dst_mp, dst_rest) = root.resolve(&dst_path);
+            if src_rest.is_empty() || dst_rest.is_empty() {
+                return ax_err!(PermissionDenied); // cannot rename mount points
+            } else if !core::ptr::eq(src_mp, dst_mp) {
+                return ax_err!(InvalidInput, "cannot rename across mount points");
+            }
+            (src_mp.fs.clone(), src_rest, dst_rest)
+        };
+        fs.root_dir().rename(src_rest, dst_rest)
+    }
 }
 
 pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
     cfg_if::cfg_if! {
         if #[cfg(feature = "myfs")] { // override the default filesystem
             let main_fs = fs::myfs::new_myfs(disk);
+            let main_fstype = "myfs";
+        } else if #[cfg(feature = "ext2")] {
+            #[cfg(feature = "use-ramdisk")]
+            let main_fs = fs::ext2::Ext2FileSystem::format(disk);
+            #[cfg(not(feature = "use-ramdisk"))]
+            let main_fs = fs::ext2::Ext2FileSystem::new(disk);
+            let main_fs = Arc::new(main_fs.expect("failed to initialize ext2 filesystem"));
+            let main_fstype = "ext2";
         } else if #[cfg(feature = "fatfs")] {
             static FAT_FS: LazyInit<Arc<fs::fatfs::FatFileSystem>> = LazyInit::new();
             FAT_FS.init_by(Arc::new(fs::fatfs::FatFileSystem::new(disk)));
             FAT_FS.init();
             let main_fs = FAT_FS.clone();
+            let main_fstype = "vfat";
         }
     }
 
-    let mut root_dir = RootDirectory::new(main_fs);
+    let root_dir = RootDirectory::new(main_fs, "/dev/sda", main_fstype);
 
     #[cfg(feature = "devfs")]
     root_dir
-        .mount("/dev", mounts::devfs())
+        .mount("devfs", "/dev", "devfs", mounts::devfs())
         .expect("failed to mount devfs at /dev");
 
     #[cfg(feature = "ramfs")]
     root_dir
-        .mount("/tmp", mounts::ramfs())
+        .mount("ramfs", "/tmp", "ramfs", mounts::ramfs())
         .expect("failed to mount ramfs at /tmp");
 
-    // Mount another ramfs as procfs
     #[cfg(feature = "procfs")]
     root_dir // should not fail
-        .mount("/proc", mounts::procfs().unwrap())
+        .mount("proc", "/proc", "proc", mounts::procfs())
         .expect("fail to mount procfs at /proc");
 
     // Mount another ramfs as sysfs
     #[cfg(feature = "sysfs")]
     root_dir // should not fail
-        .mount("/sys", mounts::sysfs().unwrap())
+        .mount("sysfs", "/sys", "sysfs", mounts::sysfs().unwrap())
         .expect("fail to mount sysfs at /sys");
 
     ROOT_DIR.init_by(Arc::new(root_dir));
-    CURRENT_DIR.init_by(Mutex::new(ROOT_DIR.clone()));
     *CURRENT_DIR_PATH.lock() = "/".into();
 }
 
-fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
-    if path.starts_with('/') {
-        ROOT_DIR.clone()
-    } else {
-        dir.cloned().unwrap_or_else(|| CURRENT_DIR.lock().clone())
+/// Maximum number of symbolic links followed in one path resolution.
+const MAX_SYMLINKS: usize = 40;
+
+/// Returns the directory node to start the lookup of `path`, and the path
+/// relative to that node.
+///
+/// Paths relative to the current directory are resolved from the root
+/// directory, so that `..` crosses mount boundaries correctly.
+fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> (VfsNodeRef, String) {
+    match dir {
+        Some(dir) if !path.starts_with('/') => (dir.clone(), path.into()),
+        _ if path.starts_with('/') => (ROOT_DIR.clone(), path.into()),
+        _ => (ROOT_DIR.clone(), CURRENT_DIR_PATH.lock().clone() + path),
+    }
+}
+
+/// Reads the target path of the symbolic link `node`.
+fn read_link_node(node: &VfsNodeRef) -> AxResult<String> {
+    let mut buf = vec![0; node.get_attr()?.size() as usize];
+    let len = node.readlink(&mut buf)?;
+    buf.truncate(len);
+    String::from_utf8(buf).map_err(|_| AxError::InvalidData)
+}
+
+/// Resolves `path` by following the symbolic links in it, returns the
+/// directory node to start the lookup and the path relative to that node,
+/// which contains no symbolic links except the last component if
+/// `follow_last` is `false`.
+///
+/// The last component is not required to exist. A path with a trailing `/`
+/// always follows its last component.
+fn resolve_path(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    follow_last: bool,
+) -> AxResult<(VfsNodeRef, String)> {
+    let (mut start, mut rest) = parent_node_of(dir, path);
+    let mut resolved = String::new();
+    let mut links = 0;
+    loop {
+        let path = rest.trim_start_matches('/');
+        let (name, next) = path.split_once('/').unwrap_or((path, ""));
+        let is_last = next.trim_start_matches('/').is_empty();
+        match name {
+            "" => break,
+            "." => {}
+            ".." if !resolved.is_empty() && resolved != ".." && !resolved.ends_with("/..") => {
+                resolved.truncate(resolved.rfind('/').unwrap_or(0));
+            }
+            _ => {
+                let candidate = if resolved.is_empty() {
+                    name.into()
+                } else {
+                    format!("{resolved}/{name}")
+                };
+                if is_last && !follow_last && !path.ends_with('/') {
+                    resolved = candidate;
+                    break;
+                }
+                match start.clone().lookup(&candidate) {
+                    Ok(node) if node.get_attr()?.is_symlink() => {
+                        links += 1;
+                        if links > MAX_SYMLINKS {
+                            return ax_err!(FilesystemLoop, "too many levels of symbolic links");
+                        }
+                        let target = read_link_node(&node)?;
+                        if target.starts_with('/') {
+                            start = ROOT_DIR.clone();
+                            resolved.clear();
+                        }
+                        rest = format!("{target}/{next}");
+                        continue;
+                    }
+                    Ok(_) => resolved = candidate,
+                    Err(_) if is_last => resolved = candidate,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        if is_last {
+            break;
+        }
+        rest = next.into();
     }
+    Ok((start, resolved))
 }
 
 pub(crate) fn absolute_path(path: &str) -> AxResult<String> {
@@ -201,11 +434,12 @@ pub(crate) fn absolute_path(path: &str) -> AxResult<String> {
     }
 }
 
-pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+fn lookup_inner(dir: Option<&VfsNodeRef>, path: &str, follow: bool) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     }
-    let node = parent_node_of(dir, path).lookup(path)?;
+    let (parent, rel_path) = resolve_path(dir, path, follow)?;
+    let node = parent.lookup(&rel_path)?;
     if path.ends_with('/') && !node.get_attr()?.is_dir() {
         ax_err!(NotADirectory)
     } else {
@@ -213,34 +447,64 @@ pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRe
     }
 }
 
+pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_inner(dir, path, true)
+}
+
+/// Returns the reference to the mounted filesystem that contains `path`, to
+/// be held by the file or directory opened at `path`.
+///
+/// Returns `None` if `path` is resolved from `dir` rather than the root
+/// directory, where lookups never cross mount points, so the filesystem is
+/// the one of `dir`.
+pub(crate) fn mount_ref(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<Option<MountRef>> {
+    let (start, rel_path) = resolve_path(dir, path, true)?;
+    let root = Arc::as_ptr(&ROOT_DIR) as *const ();
+    if core::ptr::eq(Arc::as_ptr(&start) as *const (), root) {
+        Ok(Some(ROOT_DIR.mount_ref(&rel_path)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Looks up the node at `path`, without following the symbolic link if the
+/// last component is one.
+pub(crate) fn lookup_no_follow(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_inner(dir, path, false)
+}
+
 pub(crate) fn create_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     } else if path.ends_with('/') {
         return ax_err!(NotADirectory);
     }
-    let parent = parent_node_of(dir, path);
-    parent.create(path, VfsNodeType::File)?;
-    parent.lookup(path)
+    let (parent, rel_path) = resolve_path(dir, path, true)?;
+    parent.create(&rel_path, VfsNodeType::File)?;
+    parent.lookup(&rel_path)
 }
 
 pub(crate) fn create_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    match lookup(dir, path) {
+    match lookup_no_follow(dir, path) {
         Ok(_) => ax_err!(AlreadyExists),
-        Err(AxError::NotFound) => parent_node_of(dir, path).create(path, VfsNodeType::Dir),
+        Err(AxError::NotFound) => {
+            let (parent, rel_path) = resolve_path(dir, path, false)?;
+            parent.create(&rel_path, VfsNodeType::Dir)
+        }
         Err(e) => Err(e),
     }
 }
 
 pub(crate) fn remove_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    let node = lookup(dir, path)?;
+    let node = lookup_no_follow(dir, path)?;
     let attr = node.get_attr()?;
     if attr.is_dir() {
         ax_err!(IsADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        let (parent, rel_path) = resolve_path(dir, path, false)?;
+        parent.remove(&rel_path)
     }
 }
 
@@ -262,15 +526,46 @@ pub(crate) fn remove_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
         return ax_err!(PermissionDenied);
     }
 
-    let node = lookup(dir, path)?;
+    let node = lookup_no_follow(dir, path)?;
     let attr = node.get_attr()?;
     if !attr.is_dir() {
         ax_err!(NotADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        let (parent, rel_path) = resolve_path(dir, path, false)?;
+        parent.remove(&rel_path)
+    }
+}
+
+/// Creates a symbolic link at `path` which points to `target`.
+pub(crate) fn create_symlink(dir: Option<&VfsNodeRef>, target: &str, path: &str) -> AxResult {
+    if path.is_empty() || target.is_empty() {
+        return ax_err!(NotFound);
+    } else if path.ends_with('/') {
+        return ax_err!(NotADirectory);
+    }
+    let (parent, rel_path) = resolve_path(dir, path, false)?;
+    parent.symlink(&rel_path, target)
+}
+
+/// Reads the target path of the symbolic link at `path`.
+pub(crate) fn read_link(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<String> {
+    read_link_node(&lookup_no_follow(dir, path)?)
+}
+
+/// Creates a hard link at `new` which refers to the same node as `old`.
+///
+/// Both paths must be in the same mounted filesystem.
+pub(crate) fn create_link(old: &str, new: &str) -> AxResult {
+    if old.is_empty() || new.is_empty() {
+        return ax_err!(NotFound);
+    } else if new.ends_with('/') {
+        return ax_err!(NotADirectory);
     }
+    let (_, old_path) = resolve_path(None, old, false)?;
+    let (_, new_path) = resolve_path(None, new, false)?;
+    ROOT_DIR.hard_link(&old_path, &new_path)
 }
 
 pub(crate) fn current_dir() -> AxResult<String> {
@@ -278,12 +573,12 @@ pub(crate) fn current_dir() -> AxResult<String> {
 }
 
 pub(crate) fn set_current_dir(path: &str) -> AxResult {
-    let mut abs_path = absolute_path(path)?;
+    let (_, rel_path) = resolve_path(None, path, true)?;
+    let mut abs_path = absolute_path(&format!("/{rel_path}"))?;
     if !abs_path.ends_with('/') {
         abs_path += "/";
     }
     if abs_path == "/" {
-        *CURRENT_DIR.lock() = ROOT_DIR.clone();
         *CURRENT_DIR_PATH.lock() = "/".into();
         return Ok(());
     }
@@ -295,16 +590,42 @@ pub(crate) fn set_current_dir(path: &str) -> AxResult {
     } else if !attr.perm().owner_executable() {
         ax_err!(PermissionDenied)
     } else {
-        *CURRENT_DIR.lock() = node;
         *CURRENT_DIR_PATH.lock() = abs_path;
         Ok(())
     }
 }
 
 pub(crate) fn rename(old: &str, new: &str) -> AxResult {
-    if parent_node_of(None, new).lookup(new).is_ok() {
+    if lookup(None, new).is_ok() {
         warn!("dst file already exist, now remove it");
         remove_file(None, new)?;
     }
-    parent_node_of(None, old).rename(old, new)
+    let (_, old_path) = resolve_path(None, old, false)?;
+    let (_, new_path) = resolve_path(None, new, false)?;
+    ROOT_DIR.rename(&old_path, &new_path)
+}
+
+pub(crate) fn mount(source: &str, target: &str, fstype: &str, fs: Arc<dyn VfsOps>) -> AxResult {
+    if !lookup(None, target)?.get_attr()?.is_dir() {
+        return ax_err!(NotADirectory);
+    }
+    let (_, rel_path) = resolve_path(None, target, true)?;
+    ROOT_DIR.mount(source, &format!("/{rel_path}"), fstype, fs)
+}
+
+/// Returns the mount table.
+pub(crate) fn mounts() -> Vec<MountInfo> {
+    ROOT_DIR.mounts()
+}
+
+pub(crate) fn umount(target: &str) -> AxResult {
+    let (_, rel_path) = resolve_path(None, target, true)?;
+    let path = absolute_path(&format!("/{rel_path}"))?;
+    if CURRENT_DIR_PATH.lock().starts_with(&(path.clone() + "/")) {
+        return ax_err!(ResourceBusy, "the current directory is in use");
+    }
+    ROOT_DIR.umount(&path)?;
+    // some filesystems (e.g., FAT) are kept after unmounting, so their data
+    // is written back here.
+    crate::dev::sync_all()
 }
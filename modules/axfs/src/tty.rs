@@ -0,0 +1,200 @@
+//! Serial ports, as the character devices `/dev/ttyS0`, `/dev/ttyS1`, etc.
+//!
+//! Received bytes are buffered by the interrupt handler if the port has an
+//! IRQ and the `irq` feature is enabled, otherwise the port is polled when it
+//! is read. Reads block until some bytes are received.
+
+use alloc::{format, string::String, sync::Arc, vec::Vec};
+
+use axdriver::{prelude::*, AxDeviceContainer};
+use axerrno::{ax_err, AxResult};
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeRef, VfsNodeType, VfsResult};
+use driver_char::SerialConfig;
+use lazy_init::LazyInit;
+
+use crate::irq_input::{InputBuffer, InputDevice, IrqInput};
+
+/// Size of the receive buffer of each port, received bytes are dropped when
+/// it is full.
+const RX_BUF_SIZE: usize = 1024;
+
+static PORTS: LazyInit<Vec<Arc<SerialPort>>> = LazyInit::new();
+
+struct RxBuffer {
+    buf: [u8; RX_BUF_SIZE],
+    head: usize,
+    len: usize,
+}
+
+impl RxBuffer {
+    const fn new() -> Self {
+        Self {
+            buf: [0; RX_BUF_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn pop_slice(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.len);
+        for b in buf[..n].iter_mut() {
+            *b = self.buf[self.head];
+            self.head = (self.head + 1) % RX_BUF_SIZE;
+        }
+        self.len -= n;
+        n
+    }
+}
+
+impl InputBuffer<u8> for RxBuffer {
+    fn push(&mut self, byte: u8) {
+        if self.len < RX_BUF_SIZE {
+            self.buf[(self.head + self.len) % RX_BUF_SIZE] = byte;
+            self.len += 1;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl InputDevice for AxCharDevice {
+    type Item = u8;
+
+    fn take_input(&mut self) -> Option<u8> {
+        self.read_byte()
+    }
+
+    fn input_irq(&self) -> Option<usize> {
+        self.irq_num()
+    }
+
+    fn ack_input_irq(&mut self) -> bool {
+        self.ack_interrupt()
+    }
+}
+
+/// A serial port in devfs.
+pub(crate) struct SerialPort {
+    name: String,
+    input: IrqInput<AxCharDevice, RxBuffer>,
+}
+
+impl SerialPort {
+    fn new(name: String, dev: AxCharDevice) -> Self {
+        Self {
+            name,
+            input: IrqInput::new(dev, RxBuffer::new()),
+        }
+    }
+
+    fn config(&self) -> SerialConfig {
+        self.input.dev().lock().config()
+    }
+
+    fn set_config(&self, config: &SerialConfig) -> AxResult {
+        match self.input.dev().lock().set_config(config) {
+            Ok(()) => Ok(()),
+            Err(DevError::InvalidParam) => ax_err!(InvalidInput, "invalid serial configuration"),
+            Err(DevError::Unsupported) => ax_err!(Unsupported),
+            Err(_) => ax_err!(Io),
+        }
+    }
+}
+
+impl VfsNodeOps for SerialPort {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::default_file(),
+            VfsNodeType::CharDevice,
+            0,
+            0,
+        ))
+    }
+
+    fn read_at(&self, _offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.input.wait_for(|rx_buf| match rx_buf.pop_slice(buf) {
+            0 => None,
+            n => Some(n),
+        }))
+    }
+
+    fn write_at(&self, _offset: u64, buf: &[u8]) -> VfsResult<usize> {
+        for &byte in buf {
+            self.input.dev().lock().write_byte(byte);
+        }
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
+
+/// The handler of the interrupts of all serial ports.
+#[cfg(feature = "irq")]
+fn serial_irq_handler() {
+    for port in PORTS.try_get().into_iter().flatten() {
+        port.input.handle_irq();
+    }
+}
+
+/// Adds the serial ports to devfs as `ttyS0`, `ttyS1`, etc.
+pub(crate) fn add_to_devfs(devfs: &crate::fs::devfs::DeviceFileSystem) {
+    for port in PORTS.try_get().into_iter().flatten() {
+        devfs.add(&port.name, port.clone());
+    }
+}
+
+/// Returns the serial port of the node, or `None` if it isn't one.
+pub(crate) fn serial_port(node: &VfsNodeRef) -> Option<&'static SerialPort> {
+    PORTS
+        .try_get()?
+        .iter()
+        .find(|port| core::ptr::eq(Arc::as_ptr(port) as *const u8, Arc::as_ptr(node) as _))
+        .map(|port| port.as_ref())
+}
+
+/// Returns the line configuration of the serial port at `path`.
+pub(crate) fn config(path: &str) -> AxResult<SerialConfig> {
+    let node = crate::root::lookup(None, path)?;
+    match serial_port(&node) {
+        Some(port) => Ok(port.config()),
+        None => ax_err!(InvalidInput, "not a serial port"),
+    }
+}
+
+/// Sets the line configuration of the serial port at `path`.
+pub(crate) fn set_config(path: &str, config: &SerialConfig) -> AxResult {
+    let node = crate::root::lookup(None, path)?;
+    match serial_port(&node) {
+        Some(port) => port.set_config(config),
+        None => ax_err!(InvalidInput, "not a serial port"),
+    }
+}
+
+/// Takes all character devices as serial ports, and registers the handlers
+/// of their interrupts.
+pub(crate) fn init(mut char_devs: AxDeviceContainer<AxCharDevice>) {
+    let mut ports = Vec::new();
+    while let Some(dev) = char_devs.take_one() {
+        let name = format!("ttyS{}", ports.len());
+        info!("  found serial port {:?} as {}", dev.device_name(), name);
+        ports.push(SerialPort::new(name, dev));
+    }
+
+    #[cfg(feature = "irq")]
+    crate::irq_input::register_irqs(
+        ports.iter_mut().map(|port| &mut port.input),
+        serial_irq_handler,
+        "serial port",
+    );
+
+    PORTS.init_by(ports.into_iter().map(Arc::new).collect());
+}
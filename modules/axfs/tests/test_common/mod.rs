@@ -231,7 +231,10 @@ fn test_devfs_ramfs() -> Result<()> {
     // parent of '/dev'
     assert_eq!(fs::create_dir("///dev//..//233//"), Ok(()));
     assert_eq!(fs::write(".///dev//..//233//.///test.txt", "test"), Ok(()));
-    assert_err!(fs::remove_file("./dev//../..//233//.///test.txt"), NotFound);
+    assert_eq!(
+        fs::read_to_string("./dev//../..//233//.///test.txt"),
+        Ok("test".into())
+    );
     assert_eq!(fs::remove_file("./dev//..//233//../233/./test.txt"), Ok(()));
     assert_eq!(fs::remove_dir("dev//foo/../foo/../.././/233"), Ok(()));
     assert_err!(fs::remove_dir("very/../dev//"), PermissionDenied);
@@ -242,16 +245,207 @@ fn test_devfs_ramfs() -> Result<()> {
     assert_eq!(fs::read_dir("tmp").unwrap().count(), 1);
     assert_eq!(fs::write(".///tmp///dir//.///test.txt", "test"), Ok(()));
     assert_eq!(fs::read("tmp//././/dir//.///test.txt"), Ok("test".into()));
-    // assert_err!(fs::remove_dir("dev/../tmp//dir"), DirectoryNotEmpty); // TODO
+    assert_err!(fs::remove_dir("dev/../tmp//dir"), DirectoryNotEmpty);
     assert_err!(fs::remove_dir("/tmp/dir/../dir"), DirectoryNotEmpty);
     assert_eq!(fs::remove_file("./tmp//dir//test.txt"), Ok(()));
     assert_eq!(fs::remove_dir("tmp/dir/.././dir///"), Ok(()));
     assert_eq!(fs::read_dir("tmp").unwrap().count(), 0);
 
+    // paths that share a prefix with a mount point are not inside it
+    assert_eq!(fs::create_dir("/tmpdata"), Ok(()));
+    assert_eq!(fs::read_dir("tmp").unwrap().count(), 0);
+    assert_eq!(fs::remove_dir("/tmp/../tmpdata"), Ok(()));
+
+    // `..` crosses mount boundaries from inside a mounted filesystem
+    assert_eq!(fs::set_current_dir("/tmp"), Ok(()));
+    assert_eq!(
+        fs::metadata("../dev/null")?.file_type(),
+        FileType::CharDevice
+    );
+    assert_eq!(
+        fs::metadata("../very/long/path")?.file_type(),
+        FileType::Dir
+    );
+    assert_eq!(fs::set_current_dir("/"), Ok(()));
+
     println!("test_devfs_ramfs() OK!");
     Ok(())
 }
 
+fn test_mount() -> Result<()> {
+    println!("test mount and umount:");
+
+    // mount a ramfs beneath another mounted ramfs
+    fs::create_dir("/tmp/mnt")?;
+    fs::mount("none", "/tmp/mnt", "ramfs")?;
+    fs::write("/tmp/mnt/test.txt", "inner")?;
+    assert_eq!(fs::read_dir("/tmp/mnt")?.count(), 1);
+    assert_eq!(fs::read_to_string("/tmp/mnt/../mnt/./test.txt")?, "inner");
+    assert_eq!(
+        fs::read_to_string("/tmp/mnt/../../dev/../tmp/mnt/test.txt")?,
+        "inner"
+    );
+
+    // error cases
+    assert_err!(fs::mount("none", "/tmp/mnt", "ramfs"), InvalidInput);
+    assert_err!(fs::mount("none", "/tmp/not-exist", "ramfs"), NotFound);
+    assert_err!(fs::mount("none", "/short.txt", "ramfs"), NotADirectory);
+    assert_err!(fs::mount("none", "/tmp", "unknownfs"), Unsupported);
+    assert_err!(fs::mount("/dev/sdz", "/tmp", "vfat"));
+    assert_err!(fs::umount("/tmp/mnt/test.txt"), InvalidInput);
+    assert_err!(fs::umount("/tmp"), ResourceBusy); // `/tmp/mnt` is mounted
+    assert_err!(fs::remove_dir("/tmp/mnt"), PermissionDenied);
+
+    fs::set_current_dir("/tmp/mnt")?;
+    assert_err!(fs::umount("/tmp/mnt"), ResourceBusy);
+    fs::set_current_dir("/")?;
+
+    // opened files and directories keep the filesystem mounted
+    let file = File::open("/tmp/mnt/test.txt")?;
+    assert_err!(fs::umount("/tmp/mnt"), ResourceBusy);
+    drop(file);
+    let dir = fs::read_dir("/tmp/mnt")?;
+    assert_err!(fs::umount("/tmp/mnt"), ResourceBusy);
+    drop(dir);
+
+    // the original directory is visible again after unmounting
+    fs::umount("/tmp/./mnt/")?;
+    assert_err!(fs::metadata("/tmp/mnt/test.txt"), NotFound);
+    assert_eq!(fs::read_dir("/tmp/mnt")?.count(), 0);
+    fs::remove_dir("/tmp/mnt")?;
+
+    println!("test_mount() OK!");
+    Ok(())
+}
+
+fn test_links() -> Result<()> {
+    println!("test symbolic and hard links:");
+
+    fs::create_dir("/tmp/links")?;
+    fs::set_current_dir("/tmp/links")?;
+    fs::write("file.txt", "linked")?;
+    fs::symlink("file.txt", "l1")?;
+    fs::symlink("/tmp/links", "self")?;
+    fs::symlink("../../dev", "dev")?;
+    fs::symlink("not-exist", "dangling")?;
+    fs::symlink("loop2", "loop1")?;
+    fs::symlink("loop1", "loop2")?;
+
+    assert_eq!(fs::read_link("l1")?, "file.txt");
+    assert_eq!(fs::read_to_string("l1")?, "linked");
+    assert_eq!(fs::read_to_string("self/self/./self/l1")?, "linked");
+    assert_eq!(fs::read_to_string("/tmp/links/self/../links/l1")?, "linked");
+    assert!(fs::metadata("dev/zero")?.file_type().is_char_device());
+    assert!(fs::metadata("l1")?.is_file());
+    assert!(fs::symlink_metadata("l1")?.is_symlink());
+    assert_eq!(fs::symlink_metadata("l1")?.len(), 8);
+    assert!(fs::symlink_metadata("self/")?.is_dir());
+    assert_err!(fs::read_link("file.txt"), InvalidInput);
+    assert_err!(fs::metadata("dangling"), NotFound);
+    assert_err!(fs::metadata("loop1"), FilesystemLoop);
+    assert_err!(fs::symlink("file.txt", "l1"), AlreadyExists);
+    assert_err!(fs::symlink("file.txt", "l1/l2"), NotADirectory);
+
+    // creating through a dangling link creates its target
+    fs::write("dangling", "created")?;
+    assert_eq!(fs::read_to_string("not-exist")?, "created");
+
+    fs::set_current_dir("self")?;
+    assert_eq!(fs::current_dir()?, "/tmp/links/");
+
+    fs::hard_link("file.txt", "h1")?;
+    fs::write("h1", "changed")?;
+    assert_eq!(fs::read_to_string("file.txt")?, "changed");
+    fs::remove_file("file.txt")?;
+    assert_eq!(fs::read_to_string("h1")?, "changed");
+    assert_err!(fs::metadata("l1"), NotFound);
+    assert_err!(fs::hard_link("/tmp/links", "h2"), PermissionDenied);
+    assert_err!(fs::hard_link("h1", "/tmp/links/h1"), AlreadyExists);
+    assert_err!(fs::hard_link("h1", "/h3"), InvalidInput);
+
+    // removing a link does not remove its target
+    fs::remove_file("self")?;
+    fs::remove_file("dev")?;
+    assert!(fs::metadata("/dev/zero").is_ok());
+
+    fs::set_current_dir("/")?;
+    for name in ["l1", "dangling", "not-exist", "loop1", "loop2", "h1"] {
+        fs::remove_file(&format!("/tmp/links/{name}"))?;
+    }
+    fs::remove_dir("/tmp/links")?;
+
+    println!("test_links() OK!");
+    Ok(())
+}
+
+fn test_procfs() -> Result<()> {
+    println!("test procfs:");
+
+    let names = fs::read_dir("/proc")?
+        .map(|e| e.unwrap().file_name())
+        .collect::<Vec<_>>();
+    for name in ["cpuinfo", "meminfo", "mounts", "self", "sys", "uptime"] {
+        assert!(names.iter().any(|n| n == name), "/proc/{name} not found");
+    }
+    assert_eq!(
+        fs::read_to_string("/proc/sys/net/core/somaxconn")?,
+        "4096\n"
+    );
+    assert!(fs::read_to_string("/proc/meminfo")?.starts_with("MemTotal:"));
+    assert!(fs::read_to_string("/proc/cpuinfo")?.starts_with("processor"));
+    assert!(fs::read_to_string("/proc/uptime")?.ends_with(" 0.00\n"));
+    #[cfg(feature = "multitask")]
+    for entry in fs::read_dir("/proc/tasks")? {
+        let status = fs::read_to_string(&format!("{}/status", entry?.path()))?;
+        assert!(status.starts_with("Name:"));
+    }
+
+    // the mount table is generated every time it is read
+    let mounts = fs::read_to_string("/proc/mounts")?;
+    assert!(mounts.lines().any(|l| l == "proc /proc proc rw 0 0"));
+    assert!(mounts.lines().any(|l| l == "ramfs /tmp ramfs rw 0 0"));
+    fs::create_dir("/tmp/mnt")?;
+    fs::mount("none", "/tmp/mnt", "ramfs")?;
+    let mounts = fs::read_to_string("/proc/mounts")?;
+    assert!(mounts.lines().any(|l| l == "none /tmp/mnt ramfs rw 0 0"));
+    fs::umount("/tmp/mnt")?;
+    fs::remove_dir("/tmp/mnt")?;
+
+    assert_err!(fs::write("/proc/mounts", "foo"), PermissionDenied);
+    assert_err!(fs::create_dir("/proc/foo"), PermissionDenied);
+    assert_err!(fs::remove_file("/proc/uptime"), PermissionDenied);
+
+    println!("test_procfs() OK!");
+    Ok(())
+}
+
+fn test_block_cache() -> Result<()> {
+    println!("test block cache:");
+
+    // the second read is served entirely by the cache
+    let contents = fs::read("/long.txt")?;
+    let stats = fs::block_cache_stats();
+    assert_eq!(fs::read("/long.txt")?, contents);
+    let new_stats = fs::block_cache_stats();
+    assert_eq!(new_stats.misses, stats.misses);
+    #[cfg(not(feature = "myfs"))]
+    assert!(new_stats.hits > stats.hits);
+
+    // a file larger than the cache, so that dirty blocks are evicted
+    let data = (0..200 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    fs::write("/big.bin", &data)?;
+    assert_eq!(fs::read("/big.bin")?, data);
+    fs::remove_file("/big.bin")?;
+    fs::sync()?;
+    let new_stats = fs::block_cache_stats();
+    #[cfg(not(feature = "myfs"))]
+    assert!(new_stats.writebacks > stats.writebacks);
+    println!("block cache stats: {:?}", new_stats);
+
+    println!("test_block_cache() OK!");
+    Ok(())
+}
+
 pub fn test_all() {
     test_read_write_file().expect("test_read_write_file() failed");
     test_read_dir().expect("test_read_dir() failed");
@@ -259,4 +453,8 @@ pub fn test_all() {
     test_create_file_dir().expect("test_create_file_dir() failed");
     test_remove_file_dir().expect("test_remove_file_dir() failed");
     test_devfs_ramfs().expect("test_devfs_ramfs() failed");
+    test_mount().expect("test_mount() failed");
+    test_links().expect("test_links() failed");
+    test_procfs().expect("test_procfs() failed");
+    test_block_cache().expect("test_block_cache() failed");
 }
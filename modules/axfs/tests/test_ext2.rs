@@ -0,0 +1,53 @@
+#![cfg(all(feature = "ext2", not(feature = "myfs")))]
+
+mod test_common;
+
+use axdriver::AxDeviceContainer;
+use axfs::api as fs;
+use driver_block::ramdisk::RamDisk;
+
+const IMG_PATH: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/ext2.img");
+
+/// Creates the disk image by `mkfs.ext2`, and loads it.
+fn make_disk() -> std::io::Result<RamDisk> {
+    println!("Creating disk image {:?} ...", IMG_PATH);
+    let status = std::process::Command::new("resources/create_test_img.sh")
+        .args(["ext2", IMG_PATH])
+        .status()?;
+    assert!(status.success(), "failed to create disk image");
+    let data = std::fs::read(IMG_PATH)?;
+    println!("size = {} bytes", data.len());
+    Ok(RamDisk::from(&data))
+}
+
+fn test_ext2_attrs() -> axio::Result<()> {
+    println!("test ext2 attributes:");
+
+    // permissions and symbolic links created by `mkfs.ext2`
+    assert_eq!(fs::metadata("/short.txt")?.permissions().mode(), 0o600);
+    assert_eq!(fs::metadata("/long.txt")?.permissions().mode(), 0o644);
+    assert_eq!(fs::metadata("/long.txt")?.len(), 14000);
+    assert_eq!(fs::read_link("/link.txt")?, "very/long/path/test.txt");
+    assert_eq!(fs::read_to_string("/link.txt")?, "Rust is cool!\n");
+    assert!(fs::metadata("/lost+found")?.is_dir());
+
+    let mounts = fs::read_to_string("/proc/mounts")?;
+    assert!(mounts.lines().any(|l| l == "/dev/sda / ext2 rw 0 0"));
+
+    println!("test_ext2_attrs() OK!");
+    Ok(())
+}
+
+#[test]
+fn test_ext2() {
+    println!("Testing ext2 with ramdisk ...");
+
+    let disk = make_disk().expect("failed to load disk image");
+    axtask::init_scheduler(); // call this to use `axsync::Mutex`.
+    axfs::init_filesystems(AxDeviceContainer::from_one(disk));
+
+    test_ext2_attrs().expect("test_ext2_attrs() failed");
+    test_common::test_all();
+    // no cache is in use now, so all of them are written back
+    axfs::api::try_sync().expect("failed to sync");
+}
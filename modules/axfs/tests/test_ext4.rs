@@ -0,0 +1,58 @@
+#![cfg(all(feature = "ext2", not(feature = "myfs")))]
+
+use axdriver::AxDeviceContainer;
+use axfs::api as fs;
+use axio::{Error, Result};
+use driver_block::ramdisk::RamDisk;
+
+const IMG_PATH: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/ext4.img");
+
+/// Creates the disk image by `mkfs.ext4`, and loads it.
+fn make_disk() -> std::io::Result<RamDisk> {
+    println!("Creating disk image {:?} ...", IMG_PATH);
+    let status = std::process::Command::new("resources/create_test_img.sh")
+        .args(["ext4", IMG_PATH])
+        .status()?;
+    assert!(status.success(), "failed to create disk image");
+    let data = std::fs::read(IMG_PATH)?;
+    println!("size = {} bytes", data.len());
+    Ok(RamDisk::from(&data))
+}
+
+/// Ext4 is read through the extent trees, but cannot be modified.
+fn test_read_only() -> Result<()> {
+    let contents = fs::read_to_string("/long.txt")?;
+    assert_eq!(contents.len(), 14000);
+    assert!(contents.lines().all(|l| l == "Rust is cool!"));
+    assert_eq!(
+        fs::read_to_string("/very//long/path/../path/test.txt")?,
+        "Rust is cool!\n"
+    );
+    assert_eq!(fs::read_to_string("/link.txt")?, "Rust is cool!\n");
+    assert_eq!(fs::metadata("/short.txt")?.permissions().mode(), 0o600);
+    let mut names = fs::read_dir("/very-long-dir-name")?
+        .map(|e| e.unwrap().file_name())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["very-long-file-name.txt"]);
+
+    let err = Some(Error::PermissionDenied);
+    assert_eq!(fs::write("/short.txt", "foo").err(), err);
+    assert_eq!(fs::write("/new.txt", "foo").err(), err);
+    assert_eq!(fs::create_dir("/new-dir").err(), err);
+    assert_eq!(fs::remove_file("/long.txt").err(), err);
+    assert_eq!(fs::rename("/long.txt", "/long2.txt").err(), err);
+    assert_eq!(fs::read_to_string("/short.txt")?, "Rust is cool!\n");
+    Ok(())
+}
+
+#[test]
+fn test_ext4() {
+    println!("Testing ext4 with ramdisk ...");
+
+    let disk = make_disk().expect("failed to load disk image");
+    axtask::init_scheduler(); // call this to use `axsync::Mutex`.
+    axfs::init_filesystems(AxDeviceContainer::from_one(disk));
+
+    test_read_only().expect("test_read_only() failed");
+}
@@ -1,5 +1,9 @@
 //! CPU-related operations.
 
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+static CPU_COUNT: AtomicUsize = AtomicUsize::new(axconfig::SMP);
+
 #[percpu::def_percpu]
 static CPU_ID: usize = 0;
 
@@ -22,6 +26,20 @@ pub fn this_cpu_is_bsp() -> bool {
     IS_BSP.read_current()
 }
 
+/// Returns the number of CPUs to run on.
+///
+/// It's the number of CPUs in the device tree if there is one, but no more
+/// than [`axconfig::SMP`], the number of CPUs that the kernel is built for.
+#[inline]
+pub fn cpu_count() -> usize {
+    CPU_COUNT.load(Ordering::Relaxed)
+}
+
+#[allow(dead_code)]
+pub(crate) fn set_cpu_count(count: usize) {
+    CPU_COUNT.store(count.clamp(1, axconfig::SMP), Ordering::Relaxed);
+}
+
 /// Gets the pointer to the current task with preemption-safety.
 ///
 /// Preemption may be enabled when calling this function. This function will
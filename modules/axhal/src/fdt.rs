@@ -0,0 +1,304 @@
+//! The device tree passed by the bootloader.
+//!
+//! On platforms booted with a flattened device tree (FDT), e.g., the QEMU
+//! virt machines, it's parsed at the very beginning, and is preferred over
+//! the static configuration in [`axconfig`] for the physical memory, the
+//! console UART, the interrupt controller, the timer frequency and the number
+//! of CPUs. The MMIO regions of the devices found in it are also mapped, in
+//! addition to [`axconfig::MMIO_REGIONS`]. Other platforms have no device
+//! tree, and [`fdt`] returns `None`.
+
+use lazy_init::LazyInit;
+use memory_addr::{align_down_4k, align_up_4k};
+
+use crate::mem::{phys_to_virt, virt_to_phys, MemRegion, MemRegionFlags, PhysAddr};
+
+pub use fdt_parser::{Fdt, MemoryRegion, Node};
+
+/// Compatible strings of interrupt controllers whose interrupt specifiers are
+/// `<type number flags>`, i.e., the ARM GIC.
+const GIC_COMPATIBLE: &[&str] = &[
+    "arm,cortex-a15-gic",
+    "arm,cortex-a9-gic",
+    "arm,gic-400",
+    "arm,gic-v3",
+];
+
+/// Compatible strings of interrupt controllers whose interrupt specifiers are
+/// `<number>`, i.e., the RISC-V PLIC.
+pub(crate) const PLIC_COMPATIBLE: &[&str] = &["riscv,plic0", "sifive,plic-1.0.0"];
+
+/// Compatible strings of VirtIO devices on the MMIO bus.
+const VIRTIO_MMIO_COMPATIBLE: &[&str] = &["virtio,mmio"];
+
+/// The maximum number of free memory ranges taken from the device tree.
+const MAX_FREE_RANGES: usize = 16;
+/// The maximum number of MMIO ranges taken from the device tree.
+const MAX_MMIO_RANGES: usize = 32;
+
+static FDT: LazyInit<Fdt<'static>> = LazyInit::new();
+static FREE_MEMORY: LazyInit<Ranges<MAX_FREE_RANGES>> = LazyInit::new();
+static MMIO: LazyInit<Ranges<MAX_MMIO_RANGES>> = LazyInit::new();
+
+/// A device found in the device tree.
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceInfo {
+    /// The physical address of its first `reg` region.
+    pub paddr: PhysAddr,
+    /// The size in bytes of its first `reg` region.
+    pub size: usize,
+    /// The number of its first interrupt, as used by [`crate::irq`].
+    pub irq_num: Option<usize>,
+}
+
+/// At most `N` physical memory ranges `[start, end)` that are sorted and
+/// disjoint.
+struct Ranges<const N: usize> {
+    ranges: [(usize, usize); N],
+    len: usize,
+}
+
+impl<const N: usize> Ranges<N> {
+    const fn new() -> Self {
+        Self {
+            ranges: [(0, 0); N],
+            len: 0,
+        }
+    }
+
+    fn insert(&mut self, idx: usize, range: (usize, usize)) {
+        if self.len == N {
+            return; // too many ranges, drop the rest
+        }
+        self.ranges.copy_within(idx..self.len, idx + 1);
+        self.ranges[idx] = range;
+        self.len += 1;
+    }
+
+    /// Adds `[start, end)`, which must not overlap with the existing ranges.
+    fn add(&mut self, start: usize, end: usize) {
+        if start < end {
+            let idx = self.ranges[..self.len].partition_point(|r| r.0 < start);
+            self.insert(idx, (start, end));
+        }
+    }
+
+    /// Removes `[start, end)` from all ranges, which may split a range.
+    fn remove(&mut self, start: usize, end: usize) {
+        let mut i = 0;
+        while i < self.len {
+            let (s, e) = self.ranges[i];
+            if e <= start || end <= s {
+                i += 1;
+                continue;
+            }
+            if s < start && end < e {
+                self.ranges[i].1 = start;
+                self.insert(i + 1, (end, e));
+                return;
+            } else if s < start {
+                self.ranges[i].1 = start;
+            } else if end < e {
+                self.ranges[i].0 = end;
+            } else {
+                self.ranges.copy_within(i + 1..self.len, i);
+                self.len -= 1;
+                continue;
+            }
+            i += 1;
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.ranges[..self.len].iter().copied()
+    }
+
+    /// Adds `[start, end)`, which is merged with the overlapping ranges.
+    fn merge(&mut self, mut start: usize, mut end: usize) {
+        for (s, e) in self.iter() {
+            if s < end && start < e {
+                start = start.min(s);
+                end = end.max(e);
+            }
+        }
+        self.remove(start, end);
+        self.add(start, end);
+    }
+
+    fn contains(&self, start: usize, end: usize) -> bool {
+        self.iter().any(|(s, e)| s <= start && end <= e)
+    }
+}
+
+/// Returns the device tree passed by the bootloader, or `None` if there's no
+/// valid one.
+pub fn fdt() -> Option<&'static Fdt<'static>> {
+    FDT.try_get()
+}
+
+/// Returns the available devices that are compatible with any of
+/// `compatible`, in the order of the device tree.
+pub fn find_devices<'a>(compatible: &'a [&'a str]) -> impl Iterator<Item = DeviceInfo> + 'a {
+    fdt()
+        .into_iter()
+        .flat_map(move |fdt| fdt.find_compatible(compatible))
+        .filter(|node| node.is_available())
+        .filter_map(|node| device_info(&node))
+}
+
+/// Returns the first `reg` region and interrupt of the device node.
+pub fn device_info(node: &Node) -> Option<DeviceInfo> {
+    let reg = node.reg().next()?;
+    Some(DeviceInfo {
+        paddr: PhysAddr::from(reg.address as usize),
+        size: reg.size as usize,
+        irq_num: irq_num(node),
+    })
+}
+
+/// Returns the number of the first interrupt of the device node, as used by
+/// [`crate::irq`].
+///
+/// GIC shared and private peripheral interrupts are numbered from 32 and 16
+/// respectively, and PLIC interrupts keep their numbers.
+pub fn irq_num(node: &Node) -> Option<usize> {
+    let parent = node.interrupt_parent()?;
+    let spec = node.interrupts().next()?;
+    if parent.is_compatible(GIC_COMPATIBLE) {
+        let base = match spec.get(0)? {
+            0 => 32, // SPI
+            1 => 16, // PPI
+            _ => return None,
+        };
+        Some(spec.get(1)? as usize + base)
+    } else if parent.is_compatible(PLIC_COMPATIBLE) {
+        Some(spec.get(0)? as usize)
+    } else {
+        None
+    }
+}
+
+/// Returns the total size of physical memory in the device tree.
+pub(crate) fn memory_size() -> Option<usize> {
+    let fdt = fdt()?;
+    Some(fdt.memory().map(|r| r.size as usize).sum())
+}
+
+/// Returns the region that holds the device tree blob itself, which is
+/// reserved but readable.
+pub(crate) fn dtb_region() -> Option<MemRegion> {
+    let (start, end) = dtb_range()?;
+    Some(MemRegion {
+        paddr: start.into(),
+        size: end - start,
+        flags: MemRegionFlags::RESERVED | MemRegionFlags::READ,
+        name: "device tree",
+    })
+}
+
+/// Returns the free memory regions in the device tree, i.e., the physical
+/// memory after the kernel image, without any reserved memory.
+pub(crate) fn free_regions() -> Option<impl Iterator<Item = MemRegion>> {
+    let ranges = FREE_MEMORY.try_get()?;
+    Some(ranges.iter().map(|(start, end)| MemRegion {
+        paddr: start.into(),
+        size: end - start,
+        flags: MemRegionFlags::FREE | MemRegionFlags::READ | MemRegionFlags::WRITE,
+        name: "free memory",
+    }))
+}
+
+/// Returns the MMIO regions of the devices in the device tree that are not in
+/// [`axconfig::MMIO_REGIONS`], e.g., VirtIO devices and interrupt controllers
+/// at other addresses.
+pub(crate) fn mmio_regions() -> impl Iterator<Item = MemRegion> {
+    MMIO.try_get()
+        .into_iter()
+        .flat_map(|ranges| ranges.iter())
+        .map(|(start, end)| MemRegion {
+            paddr: start.into(),
+            size: end - start,
+            flags: MemRegionFlags::RESERVED
+                | MemRegionFlags::DEVICE
+                | MemRegionFlags::READ
+                | MemRegionFlags::WRITE,
+            name: "mmio",
+        })
+}
+
+fn dtb_range() -> Option<(usize, usize)> {
+    let fdt = fdt()?;
+    let start = virt_to_phys((fdt.as_bytes().as_ptr() as usize).into()).as_usize();
+    Some((align_down_4k(start), align_up_4k(start + fdt.total_size())))
+}
+
+fn init_free_memory(fdt: &Fdt, kernel_end: usize, mapped_end: usize) -> Ranges<MAX_FREE_RANGES> {
+    let mut ranges = Ranges::new();
+    for r in fdt.memory() {
+        let start = align_up_4k((r.address as usize).max(kernel_end));
+        let end = align_down_4k((r.end() as usize).min(mapped_end));
+        ranges.add(start, end);
+    }
+    let reserved = fdt.memory_reservations().chain(fdt.reserved_memory());
+    for r in reserved {
+        ranges.remove(
+            align_down_4k(r.address as usize),
+            align_up_4k(r.end() as usize),
+        );
+    }
+    if let Some((start, end)) = dtb_range() {
+        ranges.remove(start, end);
+    }
+    ranges
+}
+
+fn init_mmio(fdt: &Fdt) -> Ranges<MAX_MMIO_RANGES> {
+    let mut config = Ranges::<MAX_MMIO_RANGES>::new();
+    for &(base, size) in axconfig::MMIO_REGIONS {
+        config.merge(base, base + size);
+    }
+    let mut ranges = Ranges::new();
+    for compatible in [VIRTIO_MMIO_COMPATIBLE, PLIC_COMPATIBLE, GIC_COMPATIBLE] {
+        let nodes = fdt.find_compatible(compatible);
+        for reg in nodes
+            .filter(|node| node.is_available())
+            .flat_map(|node| node.reg())
+        {
+            let start = align_down_4k(reg.address as usize);
+            let end = align_up_4k(reg.end() as usize);
+            if !config.contains(start, end) {
+                ranges.merge(start, end);
+            }
+        }
+    }
+    // drop the parts already in the configuration
+    for (start, end) in config.iter() {
+        ranges.remove(start, end);
+    }
+    ranges
+}
+
+/// Parses the device tree blob at the physical address `dtb`.
+///
+/// Free memory is limited to `mapped_end`, the end of physical memory mapped
+/// by the boot page table. It must be called once before any other functions.
+#[allow(dead_code)]
+pub(crate) unsafe fn init(dtb: usize, mapped_end: PhysAddr) {
+    if dtb == 0 {
+        return;
+    }
+    let Ok(fdt) = Fdt::from_ptr(phys_to_virt(dtb.into()).as_ptr()) else {
+        return;
+    };
+    FDT.init_by(fdt);
+    if fdt.memory().next().is_some() {
+        let kernel_end = crate::mem::kernel_end();
+        FREE_MEMORY.init_by(init_free_memory(
+            &fdt,
+            kernel_end.as_usize(),
+            mapped_end.as_usize(),
+        ));
+    }
+    MMIO.init_by(init_mmio(&fdt));
+    crate::cpu::set_cpu_count(fdt.cpus().count());
+}
@@ -39,6 +39,7 @@ mod platform;
 
 pub mod arch;
 pub mod cpu;
+pub mod fdt;
 pub mod mem;
 pub mod time;
 pub mod trap;
@@ -66,7 +67,33 @@ pub mod console {
 
 /// Miscellaneous operation, e.g. terminate the system.
 pub mod misc {
+    // other platform-specific operations, `terminate` is wrapped below
+    #[allow(unused_imports)]
     pub use super::platform::misc::*;
+
+    use spinlock::SpinNoIrq;
+
+    static SHUTDOWN_HOOK: SpinNoIrq<Option<fn()>> = SpinNoIrq::new(None);
+
+    /// Registers a function to be called by [`terminate`] before shutting
+    /// down, e.g., to write back the caches of filesystems.
+    ///
+    /// It replaces the previously registered one.
+    pub fn register_shutdown_hook(hook: fn()) {
+        *SHUTDOWN_HOOK.lock() = Some(hook);
+    }
+
+    /// Runs the shutdown hook, then shuts down the whole system.
+    ///
+    /// The hook is run at most once, even if it panics or calls this
+    /// function again.
+    pub fn terminate() -> ! {
+        let hook = SHUTDOWN_HOOK.lock().take();
+        if let Some(hook) = hook {
+            hook();
+        }
+        super::platform::misc::terminate()
+    }
 }
 
 /// Multi-core operations.
@@ -72,7 +72,15 @@ pub const fn phys_to_virt(paddr: PhysAddr) -> VirtAddr {
 
 /// Returns an iterator over all physical memory regions.
 pub fn memory_regions() -> impl Iterator<Item = MemRegion> {
-    kernel_image_regions().chain(crate::platform::mem::platform_regions())
+    kernel_image_regions()
+        .chain(crate::fdt::dtb_region())
+        .chain(crate::platform::mem::platform_regions())
+}
+
+/// Returns the total size of physical memory in bytes, which is taken from
+/// the device tree if there is one, or [`axconfig::PHYS_MEMORY_SIZE`].
+pub fn total_memory_size() -> usize {
+    crate::fdt::memory_size().unwrap_or(axconfig::PHYS_MEMORY_SIZE)
 }
 
 /// Returns the memory regions of the kernel image (code and data sections).
@@ -113,30 +121,48 @@ fn kernel_image_regions() -> impl Iterator<Item = MemRegion> {
 }
 
 /// Returns the default MMIO memory regions (from [`axconfig::MMIO_REGIONS`]).
+///
+/// The MMIO regions of other devices in the device tree are also included,
+/// see [`crate::fdt`].
 #[allow(dead_code)]
 pub(crate) fn default_mmio_regions() -> impl Iterator<Item = MemRegion> {
-    axconfig::MMIO_REGIONS.iter().map(|reg| MemRegion {
-        paddr: reg.0.into(),
-        size: reg.1,
-        flags: MemRegionFlags::RESERVED
-            | MemRegionFlags::DEVICE
-            | MemRegionFlags::READ
-            | MemRegionFlags::WRITE,
-        name: "mmio",
-    })
+    axconfig::MMIO_REGIONS
+        .iter()
+        .map(|reg| MemRegion {
+            paddr: reg.0.into(),
+            size: reg.1,
+            flags: MemRegionFlags::RESERVED
+                | MemRegionFlags::DEVICE
+                | MemRegionFlags::READ
+                | MemRegionFlags::WRITE,
+            name: "mmio",
+        })
+        .chain(crate::fdt::mmio_regions())
 }
 
 /// Returns the default free memory regions (kernel image end to physical memory end).
+///
+/// If there is a device tree, the regions are taken from it instead, see
+/// [`crate::fdt`].
 #[allow(dead_code)]
 pub(crate) fn default_free_regions() -> impl Iterator<Item = MemRegion> {
-    let start = virt_to_phys((_ekernel as usize).into()).align_up_4k();
-    let end = PhysAddr::from(axconfig::PHYS_MEMORY_END).align_down_4k();
-    core::iter::once(MemRegion {
-        paddr: start,
-        size: end.as_usize() - start.as_usize(),
-        flags: MemRegionFlags::FREE | MemRegionFlags::READ | MemRegionFlags::WRITE,
-        name: "free memory",
-    })
+    let fdt_regions = crate::fdt::free_regions();
+    let config_region = fdt_regions.is_none().then(|| {
+        let start = kernel_end().align_up_4k();
+        let end = PhysAddr::from(axconfig::PHYS_MEMORY_END).align_down_4k();
+        MemRegion {
+            paddr: start,
+            size: end.as_usize() - start.as_usize(),
+            flags: MemRegionFlags::FREE | MemRegionFlags::READ | MemRegionFlags::WRITE,
+            name: "free memory",
+        }
+    });
+    fdt_regions.into_iter().flatten().chain(config_region)
+}
+
+/// Returns the physical address of the end of the kernel image.
+pub(crate) fn kernel_end() -> PhysAddr {
+    virt_to_phys((_ekernel as usize).into())
 }
 
 /// Fills the `.bss` section with zeros.
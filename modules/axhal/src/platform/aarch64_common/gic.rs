@@ -1,6 +1,7 @@
 use crate::{irq::IrqHandler, mem::phys_to_virt};
 use arm_gic::gic_v2::{GicCpuInterface, GicDistributor};
 use arm_gic::{translate_irq, InterruptType};
+use lazy_init::LazyInit;
 use memory_addr::PhysAddr;
 use spinlock::SpinNoIrq;
 
@@ -16,11 +17,13 @@ pub const UART_IRQ_NUM: usize = translate_irq(axconfig::UART_IRQ, InterruptType:
 const GICD_BASE: PhysAddr = PhysAddr::from(axconfig::GICD_PADDR);
 const GICC_BASE: PhysAddr = PhysAddr::from(axconfig::GICC_PADDR);
 
-static GICD: SpinNoIrq<GicDistributor> =
-    SpinNoIrq::new(GicDistributor::new(phys_to_virt(GICD_BASE).as_mut_ptr()));
+/// Compatible strings of GICv2 in the device tree.
+const GICV2_COMPATIBLE: &[&str] = &["arm,cortex-a15-gic", "arm,cortex-a9-gic", "arm,gic-400"];
+
+static GICD: LazyInit<SpinNoIrq<GicDistributor>> = LazyInit::new();
 
 // per-CPU, no lock
-static GICC: GicCpuInterface = GicCpuInterface::new(phys_to_virt(GICC_BASE).as_mut_ptr());
+static GICC: LazyInit<GicCpuInterface> = LazyInit::new();
 
 /// Enables or disables the given IRQ.
 pub fn set_enable(irq_num: usize, enabled: bool) {
@@ -46,9 +49,32 @@ pub fn dispatch_irq(_unused: usize) {
     GICC.handle_irq(|irq_num| crate::irq::dispatch_irq_common(irq_num as _));
 }
 
+/// Returns the base addresses of GICD and GICC, which are the first two `reg`
+/// regions of the GIC in the device tree, or those in [`axconfig`].
+fn gic_bases() -> (PhysAddr, PhysAddr) {
+    let regs = crate::fdt::fdt()
+        .and_then(|fdt| fdt.find_compatible(GICV2_COMPATIBLE).next())
+        .map(|node| {
+            let mut reg = node.reg();
+            (reg.next(), reg.next())
+        });
+    match regs {
+        Some((Some(gicd), Some(gicc))) => (
+            PhysAddr::from(gicd.address as usize),
+            PhysAddr::from(gicc.address as usize),
+        ),
+        _ => (GICD_BASE, GICC_BASE),
+    }
+}
+
 /// Initializes GICD, GICC on the primary CPU.
 pub(crate) fn init_primary() {
-    info!("Initialize GICv2...");
+    let (gicd_base, gicc_base) = gic_bases();
+    info!("Initialize GICv2 at {:#x}, {:#x}...", gicd_base, gicc_base);
+    GICD.init_by(SpinNoIrq::new(GicDistributor::new(
+        phys_to_virt(gicd_base).as_mut_ptr(),
+    )));
+    GICC.init_by(GicCpuInterface::new(phys_to_virt(gicc_base).as_mut_ptr()));
     GICD.lock().init();
     GICC.init();
 }
@@ -1,5 +1,7 @@
 //! PL011 UART.
 
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 use arm_pl011::pl011::Pl011Uart;
 use memory_addr::PhysAddr;
 use spinlock::SpinNoIrq;
@@ -11,6 +13,8 @@ const UART_BASE: PhysAddr = PhysAddr::from(axconfig::UART_PADDR);
 static UART: SpinNoIrq<Pl011Uart> =
     SpinNoIrq::new(Pl011Uart::new(phys_to_virt(UART_BASE).as_mut_ptr()));
 
+static UART_IRQ_NUM: AtomicUsize = AtomicUsize::new(0);
+
 /// Writes a byte to the console.
 pub fn putchar(c: u8) {
     let mut uart = UART.lock();
@@ -29,14 +33,37 @@ pub fn getchar() -> Option<u8> {
 }
 
 /// Initialize the UART
+///
+/// The console UART in the device tree is used if it's a PL011, otherwise the
+/// one at [`axconfig::UART_PADDR`].
 pub fn init_early() {
-    UART.lock().init();
+    let mut uart = UART.lock();
+    let console = crate::fdt::fdt()
+        .and_then(|fdt| fdt.stdout())
+        .filter(|node| node.is_compatible(&["arm,pl011"]))
+        .and_then(|node| crate::fdt::device_info(&node));
+    if let Some(console) = console {
+        *uart = Pl011Uart::new(phys_to_virt(console.paddr).as_mut_ptr());
+        if let Some(irq_num) = console.irq_num {
+            UART_IRQ_NUM.store(irq_num, Ordering::Relaxed);
+        }
+    }
+    uart.init();
 }
 
 /// Set UART IRQ Enable
 pub fn init() {
     #[cfg(feature = "irq")]
-    crate::irq::set_enable(crate::platform::irq::UART_IRQ_NUM, true);
+    crate::irq::set_enable(irq_num(), true);
+}
+
+/// Returns the IRQ number of the UART.
+#[cfg(feature = "irq")]
+fn irq_num() -> usize {
+    match UART_IRQ_NUM.load(Ordering::Relaxed) {
+        0 => crate::platform::irq::UART_IRQ_NUM,
+        irq_num => irq_num,
+    }
 }
 
 /// UART IRQ Handler
@@ -1,6 +1,9 @@
 use crate::mem::{MemRegion, PhysAddr};
 use page_table_entry::{aarch64::A64PTE, GenericPTE, MappingFlags};
 
+/// The end of physical memory mapped by the boot page table.
+pub(crate) const BOOT_MAPPED_END: usize = 0x1_4000_0000;
+
 /// Returns platform-specific memory regions.
 pub(crate) fn platform_regions() -> impl Iterator<Item = MemRegion> {
     crate::mem::default_free_regions().chain(crate::mem::default_mmio_regions())
@@ -18,10 +21,12 @@ pub(crate) unsafe fn init_boot_page_table(
         MappingFlags::READ | MappingFlags::WRITE | MappingFlags::DEVICE,
         true,
     );
-    // 0x0000_4000_0000..0x0000_8000_0000, 1G block, normal memory
-    boot_pt_l1[1] = A64PTE::new_page(
-        PhysAddr::from(0x4000_0000),
-        MappingFlags::READ | MappingFlags::WRITE | MappingFlags::EXECUTE,
-        true,
-    );
+    // 0x0000_4000_0000..0x0001_4000_0000, 1G blocks, normal memory
+    for (i, pte) in boot_pt_l1.iter_mut().enumerate().take(5).skip(1) {
+        *pte = A64PTE::new_page(
+            PhysAddr::from(i * 0x4000_0000),
+            MappingFlags::READ | MappingFlags::WRITE | MappingFlags::EXECUTE,
+            true,
+        );
+    }
 }
@@ -34,9 +34,21 @@ pub mod mem {
 }
 
 pub mod time {
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+
     /// Returns the current clock time in hardware ticks.
+    ///
+    /// The clock does not advance by itself, but only by [`set_current_ticks`].
     pub fn current_ticks() -> u64 {
-        0
+        TICKS.load(Ordering::Acquire)
+    }
+
+    /// Sets the current clock time in hardware ticks, e.g., to let time pass
+    /// in tests on the host.
+    pub fn set_current_ticks(ticks: u64) {
+        TICKS.store(ticks, Ordering::Release);
     }
 
     /// Converts hardware ticks to nanoseconds.
@@ -8,11 +8,19 @@ static mut BOOT_STACK: [u8; TASK_STACK_SIZE] = [0; TASK_STACK_SIZE];
 #[link_section = ".data.boot_page_table"]
 static mut BOOT_PT_SV39: [u64; 512] = [0; 512];
 
+/// The end of physical memory mapped by the boot page table.
+pub(super) const BOOT_MAPPED_END: usize = 0x1_8000_0000;
+
 unsafe fn init_boot_page_table() {
-    // 0x8000_0000..0xc000_0000, VRWX_GAD, 1G block
-    BOOT_PT_SV39[2] = (0x80000 << 10) | 0xef;
-    // 0xffff_ffc0_8000_0000..0xffff_ffc0_c000_0000, VRWX_GAD, 1G block
-    BOOT_PT_SV39[0x102] = (0x80000 << 10) | 0xef;
+    // 0xffff_ffc0_0000_0000..0xffff_ffc0_4000_0000, VRW_GAD, 1G block, MMIO
+    BOOT_PT_SV39[0x100] = 0xe7;
+    for i in 0..4 {
+        let ppn = 0x80000 + i as u64 * 0x40000;
+        // 0x8000_0000..0x1_8000_0000, VRWX_GAD, 1G blocks
+        BOOT_PT_SV39[2 + i] = (ppn << 10) | 0xef;
+        // 0xffff_ffc0_8000_0000..0xffff_ffc1_8000_0000, VRWX_GAD, 1G blocks
+        BOOT_PT_SV39[0x102 + i] = (ppn << 10) | 0xef;
+    }
 }
 
 unsafe fn init_mmu() {
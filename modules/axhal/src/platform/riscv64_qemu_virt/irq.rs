@@ -1,8 +1,11 @@
-//! TODO: PLIC
+//! Supervisor-mode interrupts, and the platform-level interrupt controller
+//! (PLIC) for external interrupts.
 
 use crate::irq::IrqHandler;
+use crate::mem::{phys_to_virt, PhysAddr};
 use lazy_init::LazyInit;
 use riscv::register::sie;
+use spinlock::SpinNoIrq;
 
 /// `Interrupt` bit in `scause`
 pub(super) const INTC_IRQ_BASE: usize = 1 << (usize::BITS - 1);
@@ -19,26 +22,88 @@ pub(super) const S_EXT: usize = INTC_IRQ_BASE + 9;
 
 static TIMER_HANDLER: LazyInit<IrqHandler> = LazyInit::new();
 
+static PLIC: LazyInit<SpinNoIrq<Plic>> = LazyInit::new();
+
 /// The maximum number of IRQs.
 pub const MAX_IRQ_COUNT: usize = 1024;
 
 /// The timer IRQ number (supervisor timer interrupt in `scause`).
 pub const TIMER_IRQ_NUM: usize = S_TIMER;
 
-macro_rules! with_cause {
-    ($cause: expr, @TIMER => $timer_op: expr, @EXT => $ext_op: expr $(,)?) => {
-        match $cause {
-            S_TIMER => $timer_op,
-            S_EXT => $ext_op,
-            _ => panic!("invalid trap cause: {:#x}", $cause),
+/// The PLIC, of which only the supervisor-mode contexts are used.
+///
+/// External IRQ numbers are the PLIC interrupt source numbers, and they are
+/// all routed to the primary CPU.
+struct Plic {
+    base: usize,
+    primary_hartid: usize,
+}
+
+impl Plic {
+    const PRIORITY: usize = 0;
+    const ENABLE: usize = 0x2000;
+    const ENABLE_STRIDE: usize = 0x80;
+    const CONTEXT: usize = 0x20_0000;
+    const CONTEXT_STRIDE: usize = 0x1000;
+    const THRESHOLD: usize = 0;
+    const CLAIM_COMPLETE: usize = 4;
+
+    /// Returns the supervisor-mode context of the hart, as numbered by QEMU.
+    const fn context(hartid: usize) -> usize {
+        hartid * 2 + 1
+    }
+
+    fn reg(&self, offset: usize) -> *mut u32 {
+        (self.base + offset) as *mut u32
+    }
+
+    fn context_reg(&self, hartid: usize, offset: usize) -> *mut u32 {
+        self.reg(Self::CONTEXT + Self::context(hartid) * Self::CONTEXT_STRIDE + offset)
+    }
+
+    fn set_enable(&mut self, irq_num: usize, enabled: bool) {
+        let context = Self::context(self.primary_hartid);
+        let enable = self.reg(Self::ENABLE + context * Self::ENABLE_STRIDE + irq_num / 32 * 4);
+        let bit = 1 << (irq_num % 32);
+        unsafe {
+            if enabled {
+                self.reg(Self::PRIORITY + irq_num * 4).write_volatile(1);
+                enable.write_volatile(enable.read_volatile() | bit);
+            } else {
+                enable.write_volatile(enable.read_volatile() & !bit);
+            }
+        }
+    }
+
+    fn init_context(&mut self, hartid: usize) {
+        unsafe {
+            self.context_reg(hartid, Self::THRESHOLD).write_volatile(0);
+        }
+    }
+
+    fn claim(&mut self, hartid: usize) -> usize {
+        unsafe {
+            self.context_reg(hartid, Self::CLAIM_COMPLETE)
+                .read_volatile() as usize
+        }
+    }
+
+    fn complete(&mut self, hartid: usize, irq_num: usize) {
+        unsafe {
+            self.context_reg(hartid, Self::CLAIM_COMPLETE)
+                .write_volatile(irq_num as u32);
         }
-    };
+    }
 }
 
 /// Enables or disables the given IRQ.
-pub fn set_enable(scause: usize, _enabled: bool) {
-    if scause == S_EXT {
-        // TODO: set enable in PLIC
+///
+/// The timer IRQ is always enabled.
+pub fn set_enable(irq_num: usize, enabled: bool) {
+    if irq_num & INTC_IRQ_BASE == 0 {
+        if let Some(plic) = PLIC.try_get() {
+            plic.lock().set_enable(irq_num, enabled);
+        }
     }
 }
 
@@ -46,17 +111,22 @@ pub fn set_enable(scause: usize, _enabled: bool) {
 ///
 /// It also enables the IRQ if the registration succeeds. It returns `false` if
 /// the registration failed.
-pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
-    with_cause!(
-        scause,
-        @TIMER => if !TIMER_HANDLER.is_init() {
-            TIMER_HANDLER.init_by(handler);
-            true
-        } else {
+pub fn register_handler(irq_num: usize, handler: IrqHandler) -> bool {
+    match irq_num {
+        S_TIMER => {
+            if !TIMER_HANDLER.is_init() {
+                TIMER_HANDLER.init_by(handler);
+                true
+            } else {
+                false
+            }
+        }
+        _ if irq_num & INTC_IRQ_BASE == 0 => crate::irq::register_handler_common(irq_num, handler),
+        _ => {
+            warn!("register handler for invalid IRQ {:#x}", irq_num);
             false
-        },
-        @EXT => crate::irq::register_handler_common(scause & !INTC_IRQ_BASE, handler),
-    )
+        }
+    }
 }
 
 /// Dispatches the IRQ.
@@ -65,17 +135,44 @@ pub fn register_handler(scause: usize, handler: IrqHandler) -> bool {
 /// up in the IRQ handler table and calls the corresponding handler. If
 /// necessary, it also acknowledges the interrupt controller after handling.
 pub fn dispatch_irq(scause: usize) {
-    with_cause!(
-        scause,
-        @TIMER => {
+    match scause {
+        S_TIMER => {
             trace!("IRQ: timer");
             TIMER_HANDLER();
-        },
-        @EXT => crate::irq::dispatch_irq_common(0), // TODO: get IRQ number from PLIC
-    );
+        }
+        S_EXT => {
+            let Some(plic) = PLIC.try_get() else {
+                return;
+            };
+            let hartid = crate::cpu::this_cpu_id();
+            let irq_num = plic.lock().claim(hartid);
+            if irq_num != 0 {
+                crate::irq::dispatch_irq_common(irq_num);
+                plic.lock().complete(hartid, irq_num);
+            }
+        }
+        _ => panic!("invalid trap cause: {:#x}", scause),
+    }
+}
+
+/// Initializes the PLIC on the primary CPU, whose base address is taken from
+/// the device tree, or [`axconfig::PLIC_PADDR`].
+pub(super) fn init_primary() {
+    let base = crate::fdt::find_devices(crate::fdt::PLIC_COMPATIBLE)
+        .next()
+        .map_or(PhysAddr::from(axconfig::PLIC_PADDR), |dev| dev.paddr);
+    info!("Initialize PLIC at {:#x}...", base);
+    PLIC.init_by(SpinNoIrq::new(Plic {
+        base: phys_to_virt(base).as_usize(),
+        primary_hartid: crate::cpu::this_cpu_id(),
+    }));
+    init_percpu();
 }
 
 pub(super) fn init_percpu() {
+    if let Some(plic) = PLIC.try_get() {
+        plic.lock().init_context(crate::cpu::this_cpu_id());
+    }
     // enable soft interrupts, timer interrupts, and external interrupts
     unsafe {
         sie::set_ssoft();
@@ -22,6 +22,8 @@ unsafe extern "C" fn rust_entry(cpu_id: usize, dtb: usize) {
     crate::mem::clear_bss();
     crate::cpu::init_primary(cpu_id);
     crate::arch::set_trap_vector_base(trap_vector_base as usize);
+    crate::fdt::init(dtb, boot::BOOT_MAPPED_END.into());
+    self::time::init_early();
     rust_main(cpu_id, dtb);
 }
 
@@ -37,7 +39,7 @@ unsafe extern "C" fn rust_entry_secondary(cpu_id: usize) {
 /// For example, the interrupt controller and the timer.
 pub fn platform_init() {
     #[cfg(feature = "irq")]
-    self::irq::init_percpu();
+    self::irq::init_primary();
     self::time::init_percpu();
 }
 
@@ -1,6 +1,8 @@
+use ratio::Ratio;
 use riscv::register::time;
 
-const NANOS_PER_TICK: u64 = crate::time::NANOS_PER_SEC / axconfig::TIMER_FREQUENCY as u64;
+static mut TICKS_TO_NANOS_RATIO: Ratio = Ratio::zero();
+static mut NANOS_TO_TICKS_RATIO: Ratio = Ratio::zero();
 
 /// Returns the current clock time in hardware ticks.
 #[inline]
@@ -10,14 +12,14 @@ pub fn current_ticks() -> u64 {
 
 /// Converts hardware ticks to nanoseconds.
 #[inline]
-pub const fn ticks_to_nanos(ticks: u64) -> u64 {
-    ticks * NANOS_PER_TICK
+pub fn ticks_to_nanos(ticks: u64) -> u64 {
+    unsafe { TICKS_TO_NANOS_RATIO.mul_trunc(ticks) }
 }
 
 /// Converts nanoseconds to hardware ticks.
 #[inline]
-pub const fn nanos_to_ticks(nanos: u64) -> u64 {
-    nanos / NANOS_PER_TICK
+pub fn nanos_to_ticks(nanos: u64) -> u64 {
+    unsafe { NANOS_TO_TICKS_RATIO.mul_trunc(nanos) }
 }
 
 /// Set a one-shot timer.
@@ -28,6 +30,24 @@ pub fn set_oneshot_timer(deadline_ns: u64) {
     sbi_rt::set_timer(nanos_to_ticks(deadline_ns));
 }
 
+/// Early stage initialization: stores the timer frequency, which is the
+/// `timebase-frequency` in the device tree, or [`axconfig::TIMER_FREQUENCY`].
+///
+/// A frequency in the device tree that is zero or does not fit in `u32` (as
+/// required by [`Ratio`]) is ignored.
+pub(super) fn init_early() {
+    let freq = crate::fdt::fdt()
+        .and_then(|fdt| fdt.find_node("/cpus")?.property("timebase-frequency"))
+        .and_then(|prop| prop.as_u64())
+        .and_then(|freq| u32::try_from(freq).ok())
+        .filter(|&freq| freq != 0)
+        .unwrap_or(axconfig::TIMER_FREQUENCY as u32);
+    unsafe {
+        TICKS_TO_NANOS_RATIO = Ratio::new(crate::time::NANOS_PER_SEC as u32, freq);
+        NANOS_TO_TICKS_RATIO = TICKS_TO_NANOS_RATIO.inverse();
+    }
+}
+
 pub(super) fn init_percpu() {
     #[cfg(feature = "irq")]
     sbi_rt::set_timer(0);
@@ -14,6 +14,10 @@ pub use crate::platform::irq::TIMER_IRQ_NUM;
 pub use crate::platform::time::set_oneshot_timer;
 pub use crate::platform::time::{current_ticks, nanos_to_ticks, ticks_to_nanos};
 
+/// Sets the clock of the dummy platform, which does not advance by itself.
+#[cfg(platform = "dummy")]
+pub use crate::platform::time::set_current_ticks;
+
 /// Number of milliseconds in a second.
 pub const MILLIS_PER_SEC: u64 = 1_000;
 /// Number of microseconds in a second.
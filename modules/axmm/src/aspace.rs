@@ -0,0 +1,644 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::fmt;
+
+use axalloc::global_allocator;
+use axerrno::{ax_err, AxError, AxResult};
+use axhal::mem::{memory_regions, phys_to_virt, virt_to_phys, PhysAddr, VirtAddr, PAGE_SIZE_4K};
+use axhal::paging::{MappingFlags, PageSize, PageTable, PagingError};
+use memory_addr::is_aligned_4k;
+
+#[cfg(not(test))]
+use axhal::arch;
+// there is no MMU when testing on the host
+#[cfg(test)]
+use crate::tests::arch;
+
+/// A file that can be mapped into an [`AddrSpace`].
+pub trait MappedFile: Send + Sync {
+    /// Reads the file at `offset` into `buf`. Returns the number of bytes
+    /// read, which is 0 at the end of the file.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> AxResult<usize>;
+
+    /// Writes `buf` into the file at `offset`. Returns the number of bytes
+    /// written.
+    fn write_at(&self, offset: u64, buf: &[u8]) -> AxResult<usize>;
+
+    /// Returns the size of the file in bytes.
+    fn size(&self) -> AxResult<u64>;
+}
+
+/// Where the contents of a [`MemoryArea`] come from.
+#[derive(Clone)]
+pub enum Backend {
+    /// Anonymous memory, which is filled with zeros.
+    Anonymous,
+    /// Memory filled with the contents of `file` starting at `offset`.
+    ///
+    /// If `shared`, the contents are written back to the file when the area
+    /// is unmapped. Other mappings of the same file don't see the changes
+    /// before that.
+    File {
+        /// The mapped file.
+        file: Arc<dyn MappedFile>,
+        /// The file offset of the start of the area.
+        offset: u64,
+        /// Whether changes are carried through to the file.
+        shared: bool,
+    },
+}
+
+impl Backend {
+    /// Returns the backend of the memory `off` bytes after the start of an
+    /// area with this backend.
+    fn advance(&self, off: usize) -> Self {
+        match self {
+            Self::Anonymous => Self::Anonymous,
+            Self::File {
+                file,
+                offset,
+                shared,
+            } => Self::File {
+                file: file.clone(),
+                offset: offset + off as u64,
+                shared: *shared,
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Anonymous => f.write_str("Anonymous"),
+            Self::File { offset, shared, .. } => f
+                .debug_struct("File")
+                .field("offset", offset)
+                .field("shared", shared)
+                .finish(),
+        }
+    }
+}
+
+/// A contiguous range of virtual memory with the same mapping flags and
+/// backend.
+///
+/// Each page in it is backed by a physical frame, which is allocated and
+/// filled when the page is mapped.
+#[derive(Debug)]
+pub struct MemoryArea {
+    start: VirtAddr,
+    size: usize,
+    flags: MappingFlags,
+    backend: Backend,
+}
+
+impl MemoryArea {
+    /// Returns the start address of the area.
+    pub const fn start(&self) -> VirtAddr {
+        self.start
+    }
+
+    /// Returns the end address of the area (exclusive).
+    pub fn end(&self) -> VirtAddr {
+        self.start + self.size
+    }
+
+    /// Returns the size of the area in bytes.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the mapping flags of the area.
+    pub const fn flags(&self) -> MappingFlags {
+        self.flags
+    }
+
+    /// Returns the backend of the area.
+    pub const fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// Shrinks the area to end at `at`, and returns the rest part.
+    fn split(&mut self, at: VirtAddr) -> Self {
+        let off = at.as_usize() - self.start.as_usize();
+        let right = Self {
+            start: at,
+            size: self.size - off,
+            flags: self.flags,
+            backend: self.backend.advance(off),
+        };
+        self.size = off;
+        right
+    }
+}
+
+/// An address space, which consists of a page table and the memory areas
+/// mapped in it.
+///
+/// Memory areas can only be mapped into a dedicated range (the mmap area),
+/// which is out of the linear mapping of the physical memory.
+///
+/// TLB entries are only flushed on the current CPU when pages are unmapped or
+/// changed, so the memory areas must not be used by multiple CPUs.
+pub struct AddrSpace {
+    base: VirtAddr,
+    size: usize,
+    areas: BTreeMap<VirtAddr, MemoryArea>,
+    pt: PageTable,
+}
+
+impl AddrSpace {
+    /// Creates the kernel address space, which maps all physical memory
+    /// regions linearly, and uses the mmap area in the platform configuration.
+    pub(crate) fn new_kernel() -> AxResult<Self> {
+        let mut aspace = Self::new_empty(axconfig::MMAP_BASE_VADDR.into(), axconfig::MMAP_SIZE)?;
+        for r in memory_regions() {
+            aspace
+                .pt
+                .map_region(phys_to_virt(r.paddr), r.paddr, r.size, r.flags.into(), true)
+                .map_err(paging_err)?;
+        }
+        Ok(aspace)
+    }
+
+    /// Creates an address space with an empty page table, and the mmap area
+    /// `[base, base + size)`.
+    pub(crate) fn new_empty(base: VirtAddr, size: usize) -> AxResult<Self> {
+        Ok(Self {
+            base,
+            size,
+            areas: BTreeMap::new(),
+            pt: PageTable::try_new().map_err(paging_err)?,
+        })
+    }
+
+    /// Returns the start address of the mmap area.
+    pub const fn base(&self) -> VirtAddr {
+        self.base
+    }
+
+    /// Returns the end address of the mmap area (exclusive).
+    pub fn end(&self) -> VirtAddr {
+        self.base + self.size
+    }
+
+    /// Returns the size of the mmap area in bytes.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the physical address of the root page table.
+    pub const fn page_table_root(&self) -> PhysAddr {
+        self.pt.root_paddr()
+    }
+
+    /// Returns the physical address of the frame mapped at `vaddr` and the
+    /// mapping flags, or [`None`] if it's not mapped.
+    pub fn query(&self, vaddr: VirtAddr) -> Option<(PhysAddr, MappingFlags)> {
+        let (paddr, flags, _) = self.pt.query(vaddr).ok()?;
+        Some((paddr, flags))
+    }
+
+    /// Returns an iterator over all mapped memory areas, sorted by their start
+    /// addresses.
+    pub fn areas(&self) -> impl Iterator<Item = &MemoryArea> {
+        self.areas.values()
+    }
+
+    /// Whether the range `[start, start + size)` is within the mmap area.
+    pub fn contains_range(&self, start: VirtAddr, size: usize) -> bool {
+        start >= self.base
+            && size <= self.size
+            && start.as_usize() - self.base.as_usize() <= self.size - size
+    }
+
+    /// Finds a free range of `size` bytes in the mmap area, and returns its
+    /// start address.
+    ///
+    /// The search starts at `hint`, and wraps around to the start of the mmap
+    /// area if no range is found after `hint`.
+    pub fn find_free_area(&self, hint: VirtAddr, size: usize) -> Option<VirtAddr> {
+        let hint = hint.align_down_4k().max(self.base);
+        self.find_free_area_from(hint, size)
+            .or_else(|| self.find_free_area_from(self.base, size))
+    }
+
+    /// Maps the range `[start, start + size)` with the given `flags`, and
+    /// fills it from the `backend`.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the range is
+    /// not page aligned or out of the mmap area, and
+    /// [`Err(AlreadyExists)`](AxError::AlreadyExists) if it overlaps with
+    /// mapped areas.
+    pub fn map(
+        &mut self,
+        start: VirtAddr,
+        size: usize,
+        flags: MappingFlags,
+        backend: Backend,
+    ) -> AxResult {
+        self.check_range(start, size)?;
+        if !self.is_free(start, size) {
+            return ax_err!(AlreadyExists, "memory area already mapped");
+        }
+        map_pages(&mut self.pt, start, size, flags, &backend)?;
+        self.areas.insert(
+            start,
+            MemoryArea {
+                start,
+                size,
+                flags,
+                backend,
+            },
+        );
+        Ok(())
+    }
+
+    /// Unmaps the range `[start, start + size)`, which may contain unmapped
+    /// pages, and frees the physical frames.
+    ///
+    /// Pages of shared file mappings are written back to the files first.
+    pub fn unmap(&mut self, start: VirtAddr, size: usize) -> AxResult {
+        self.check_range(start, size)?;
+        let end = start + size;
+        self.split_at(start);
+        self.split_at(end);
+
+        let starts = self
+            .areas
+            .range(start..end)
+            .map(|(&start, _)| start)
+            .collect::<Vec<_>>();
+        for start in starts {
+            let area = self.areas.remove(&start).unwrap();
+            if let Err(e) = self.write_back(&area) {
+                warn!("failed to write back memory area {:#x?}: {:?}", area, e);
+            }
+            unmap_pages(&mut self.pt, area.start, area.size);
+        }
+        Ok(())
+    }
+
+    /// Changes the mapping flags of the range `[start, start + size)`.
+    ///
+    /// Returns [`Err(NoMemory)`](AxError::NoMemory) if some pages in it are
+    /// not mapped, like `mprotect` in Linux.
+    pub fn protect(&mut self, start: VirtAddr, size: usize, flags: MappingFlags) -> AxResult {
+        self.check_range(start, size)?;
+        if !self.is_mapped(start, size) {
+            return ax_err!(NoMemory, "memory area not mapped");
+        }
+        // validate the whole range first, so that nothing is changed on failure
+        for vaddr in pages(start, size) {
+            match self.pt.query(vaddr) {
+                Ok((_, _, PageSize::Size4K)) => {}
+                Ok(_) => return ax_err!(BadState, "memory area mapped to huge pages"),
+                Err(e) => return Err(paging_err(e)),
+            }
+        }
+        let end = start + size;
+        self.split_at(start);
+        self.split_at(end);
+
+        for area in self.areas.range_mut(start..end).map(|(_, area)| area) {
+            area.flags = flags;
+            for vaddr in pages(area.start, area.size) {
+                self.pt
+                    .update(vaddr, None, Some(flags))
+                    .map_err(paging_err)?;
+                flush_tlb(vaddr);
+            }
+        }
+        Ok(())
+    }
+
+    /// Discards the contents of the range `[start, start + size)`, so that
+    /// they are filled from the backends again, like `MADV_DONTNEED` in Linux.
+    ///
+    /// Pages of shared file mappings are written back to the files first.
+    /// Returns [`Err(NoMemory)`](AxError::NoMemory) if some pages in it are
+    /// not mapped.
+    pub fn discard(&mut self, start: VirtAddr, size: usize) -> AxResult {
+        self.check_range(start, size)?;
+        if !self.is_mapped(start, size) {
+            return ax_err!(NoMemory, "memory area not mapped");
+        }
+        let end = start + size;
+        self.split_at(start);
+        self.split_at(end);
+
+        for area in self.areas.range(start..end).map(|(_, area)| area) {
+            self.write_back(area)?;
+            for (i, vaddr) in pages(area.start, area.size).enumerate() {
+                let (paddr, _, _) = self.pt.query(vaddr).map_err(paging_err)?;
+                fill_frame(paddr, &area.backend, i * PAGE_SIZE_4K)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resizes the mapping `[old_start, old_start + old_size)` to `new_size`
+    /// bytes, and returns its new start address.
+    ///
+    /// The range must be in a single memory area. If it can't grow in place,
+    /// it's moved to a new place if `may_move` is true, or
+    /// [`Err(NoMemory)`](AxError::NoMemory) is returned otherwise. The grown
+    /// part is filled from the backend of the area.
+    pub fn remap(
+        &mut self,
+        old_start: VirtAddr,
+        old_size: usize,
+        new_size: usize,
+        may_move: bool,
+    ) -> AxResult<VirtAddr> {
+        self.check_range(old_start, old_size)?;
+        if new_size == 0 || !is_aligned_4k(new_size) {
+            return ax_err!(InvalidInput, "invalid size");
+        }
+        let old_end = old_start + old_size;
+        match self.areas.range(..=old_start).next_back() {
+            Some((_, area)) if area.end() >= old_end => {}
+            _ => return ax_err!(BadAddress, "memory area not mapped"),
+        }
+
+        if new_size <= old_size {
+            if new_size < old_size {
+                self.unmap(old_start + new_size, old_size - new_size)?;
+            }
+            return Ok(old_start);
+        }
+
+        let grown_size = new_size - old_size;
+        let new_start =
+            if self.contains_range(old_start, new_size) && self.is_free(old_end, grown_size) {
+                old_start
+            } else if may_move {
+                self.find_free_area(self.base, new_size)
+                    .ok_or(AxError::NoMemory)?
+            } else {
+                return ax_err!(NoMemory, "no free space to grow in place");
+            };
+
+        self.split_at(old_start);
+        self.split_at(old_end);
+        let mut area = self.areas.remove(&old_start).unwrap();
+        if let Err(e) = self.remap_area(&area, new_start, new_size) {
+            self.areas.insert(old_start, area);
+            return Err(e);
+        }
+        area.start = new_start;
+        area.size = new_size;
+        self.areas.insert(new_start, area);
+        Ok(new_start)
+    }
+}
+
+/// Private methods
+impl AddrSpace {
+    fn check_range(&self, start: VirtAddr, size: usize) -> AxResult {
+        if size == 0 || !start.is_aligned_4k() || !is_aligned_4k(size) {
+            return ax_err!(InvalidInput, "memory area not page aligned");
+        }
+        if !self.contains_range(start, size) {
+            return ax_err!(InvalidInput, "memory area out of the mmap area");
+        }
+        Ok(())
+    }
+
+    fn find_free_area_from(&self, mut start: VirtAddr, size: usize) -> Option<VirtAddr> {
+        // skip the area containing `start`
+        if let Some((_, area)) = self.areas.range(..start).next_back() {
+            start = start.max(area.end());
+        }
+        for area in self.areas.range(start..).map(|(_, area)| area) {
+            if area.start.as_usize() - start.as_usize() >= size {
+                break;
+            }
+            start = area.end();
+        }
+        self.contains_range(start, size).then_some(start)
+    }
+
+    /// Whether the range `[start, start + size)` doesn't overlap with any
+    /// memory area.
+    fn is_free(&self, start: VirtAddr, size: usize) -> bool {
+        self.areas
+            .range(..start + size)
+            .next_back()
+            .map_or(true, |(_, area)| area.end() <= start)
+    }
+
+    /// Whether all pages in the range `[start, start + size)` are mapped.
+    fn is_mapped(&self, start: VirtAddr, size: usize) -> bool {
+        let end = start + size;
+        let mut curr = match self.areas.range(..=start).next_back() {
+            Some((_, area)) if area.end() > start => area.end(),
+            _ => return false,
+        };
+        if curr >= end {
+            return true;
+        }
+        for area in self.areas.range(curr..end).map(|(_, area)| area) {
+            if area.start != curr {
+                return false;
+            }
+            curr = area.end();
+        }
+        curr >= end
+    }
+
+    /// Splits the memory area containing `at`, so that no area crosses it.
+    fn split_at(&mut self, at: VirtAddr) {
+        if let Some((_, area)) = self.areas.range_mut(..at).next_back() {
+            if area.end() > at {
+                let right = area.split(at);
+                self.areas.insert(at, right);
+            }
+        }
+    }
+
+    /// Writes the contents of a shared file mapping back to the file, except
+    /// for those beyond the end of the file.
+    fn write_back(&self, area: &MemoryArea) -> AxResult {
+        if let Backend::File {
+            file,
+            offset,
+            shared: true,
+        } = &area.backend
+        {
+            let file_size = file.size()?;
+            for (i, vaddr) in pages(area.start, area.size).enumerate() {
+                let offset = offset + (i * PAGE_SIZE_4K) as u64;
+                if offset >= file_size {
+                    break;
+                }
+                let len = (file_size - offset).min(PAGE_SIZE_4K as u64) as usize;
+                let (paddr, _, _) = self.pt.query(vaddr).map_err(paging_err)?;
+                let frame = unsafe { frame_slice(paddr) };
+                file.write_at(offset, &frame[..len])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the frames of `area` to `new_start` (if it's different), and maps
+    /// new pages after them to make its size `new_size`.
+    fn remap_area(&mut self, area: &MemoryArea, new_start: VirtAddr, new_size: usize) -> AxResult {
+        let moved = new_start != area.start;
+        if moved {
+            for (i, vaddr) in pages(area.start, area.size).enumerate() {
+                let new_vaddr = new_start + i * PAGE_SIZE_4K;
+                let res = self
+                    .pt
+                    .query(vaddr)
+                    .and_then(|(paddr, ..)| {
+                        self.pt.map(new_vaddr, paddr, PageSize::Size4K, area.flags)
+                    })
+                    .map_err(paging_err);
+                if let Err(e) = res {
+                    for vaddr in pages(new_start, i * PAGE_SIZE_4K) {
+                        self.pt.unmap(vaddr).ok();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let grown_start = new_start + area.size;
+        let grown_size = new_size - area.size;
+        let backend = area.backend.advance(area.size);
+        if let Err(e) = map_pages(&mut self.pt, grown_start, grown_size, area.flags, &backend) {
+            if moved {
+                for vaddr in pages(new_start, area.size) {
+                    self.pt.unmap(vaddr).ok();
+                }
+            }
+            return Err(e);
+        }
+
+        if moved {
+            for vaddr in pages(area.start, area.size) {
+                self.pt.unmap(vaddr).ok();
+                flush_tlb(vaddr);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for AddrSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AddrSpace")
+            .field("va_range", &(self.base.as_usize()..self.end().as_usize()))
+            .field("page_table_root", &self.pt.root_paddr())
+            .field("areas", &self.areas.values().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+fn paging_err(err: PagingError) -> AxError {
+    match err {
+        PagingError::NoMemory => AxError::NoMemory,
+        PagingError::NotAligned => AxError::InvalidInput,
+        PagingError::NotMapped => AxError::BadAddress,
+        PagingError::AlreadyMapped => AxError::AlreadyExists,
+        PagingError::MappedToHugePage => AxError::BadState,
+    }
+}
+
+/// Flushes the TLB entry of the page at `vaddr`.
+///
+/// It's only flushed on the current CPU, as there is no TLB shootdown by
+/// IPIs. Other CPUs could still access the page and even its freed frame
+/// through stale entries, which is why `mmap` is not available with `smp`.
+fn flush_tlb(vaddr: VirtAddr) {
+    arch::flush_tlb(Some(vaddr));
+}
+
+/// Returns an iterator over the start addresses of pages in the range.
+fn pages(start: VirtAddr, size: usize) -> impl Iterator<Item = VirtAddr> {
+    (start.as_usize()..start.as_usize() + size)
+        .step_by(PAGE_SIZE_4K)
+        .map(VirtAddr::from)
+}
+
+/// Returns the contents of the physical frame, accessed through the linear
+/// mapping.
+///
+/// # Safety
+///
+/// The frame must be owned by a memory area.
+#[allow(clippy::mut_from_ref)]
+unsafe fn frame_slice<'a>(paddr: PhysAddr) -> &'a mut [u8] {
+    core::slice::from_raw_parts_mut(phys_to_virt(paddr).as_mut_ptr(), PAGE_SIZE_4K)
+}
+
+/// Fills the frame from the backend, `off` bytes after the start of the area.
+fn fill_frame(paddr: PhysAddr, backend: &Backend, off: usize) -> AxResult {
+    let frame = unsafe { frame_slice(paddr) };
+    frame.fill(0);
+    if let Backend::File { file, offset, .. } = backend {
+        let offset = offset + off as u64;
+        let mut read = 0;
+        while read < PAGE_SIZE_4K {
+            match file.read_at(offset + read as u64, &mut frame[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Allocates a frame and fills it from the backend, `off` bytes after the
+/// start of the area.
+fn alloc_frame(backend: &Backend, off: usize) -> AxResult<PhysAddr> {
+    let vaddr = global_allocator()
+        .alloc_pages(1, PAGE_SIZE_4K)
+        .map_err(|_| AxError::NoMemory)?;
+    let paddr = virt_to_phys(vaddr.into());
+    fill_frame(paddr, backend, off).map_err(|e| {
+        dealloc_frame(paddr);
+        e
+    })?;
+    Ok(paddr)
+}
+
+fn dealloc_frame(paddr: PhysAddr) {
+    global_allocator().dealloc_pages(phys_to_virt(paddr).as_usize(), 1);
+}
+
+/// Maps each page in the range to a newly allocated frame filled from the
+/// backend. Mapped pages are rolled back on failure.
+fn map_pages(
+    pt: &mut PageTable,
+    start: VirtAddr,
+    size: usize,
+    flags: MappingFlags,
+    backend: &Backend,
+) -> AxResult {
+    for (i, vaddr) in pages(start, size).enumerate() {
+        let off = i * PAGE_SIZE_4K;
+        let res = alloc_frame(backend, off).and_then(|paddr| {
+            pt.map(vaddr, paddr, PageSize::Size4K, flags).map_err(|e| {
+                dealloc_frame(paddr);
+                paging_err(e)
+            })
+        });
+        if let Err(e) = res {
+            unmap_pages(pt, start, off);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Unmaps each page in the range and frees its frame.
+fn unmap_pages(pt: &mut PageTable, start: VirtAddr, size: usize) {
+    for vaddr in pages(start, size) {
+        if let Ok((paddr, _)) = pt.unmap(vaddr) {
+            flush_tlb(vaddr);
+            dealloc_frame(paddr);
+        }
+    }
+}
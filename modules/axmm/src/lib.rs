@@ -0,0 +1,53 @@
+//! [ArceOS](https://github.com/rcore-os/arceos) virtual memory management
+//! module.
+//!
+//! It owns the kernel page table, which maps all physical memory regions
+//! linearly, and manages the area for memory mappings (`mmap`) in the kernel
+//! address space. See [`AddrSpace`] for details.
+
+#![cfg_attr(not(test), no_std)]
+
+#[macro_use]
+extern crate log;
+extern crate alloc;
+
+mod aspace;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::aspace::{AddrSpace, Backend, MappedFile, MemoryArea};
+
+use axhal::mem::PhysAddr;
+use axsync::Mutex;
+use lazy_init::LazyInit;
+
+static KERNEL_ASPACE: LazyInit<Mutex<AddrSpace>> = LazyInit::new();
+static KERNEL_PAGE_TABLE_ROOT: LazyInit<PhysAddr> = LazyInit::new();
+
+/// Returns the kernel address space.
+///
+/// # Panics
+///
+/// Panics if it's called before [`init_memory_management`].
+pub fn kernel_aspace() -> &'static Mutex<AddrSpace> {
+    &KERNEL_ASPACE
+}
+
+/// Creates the kernel address space and switches to its page table.
+///
+/// It must be called on the primary CPU, after the global allocator is
+/// initialized.
+pub fn init_memory_management() {
+    info!("Initialize virtual memory management...");
+    let aspace = AddrSpace::new_kernel().expect("failed to initialize kernel address space");
+    debug!("kernel address space init OK: {:#x?}", aspace);
+    KERNEL_PAGE_TABLE_ROOT.init_by(aspace.page_table_root());
+    KERNEL_ASPACE.init_by(Mutex::new(aspace));
+    unsafe { axhal::arch::write_page_table_root(*KERNEL_PAGE_TABLE_ROOT) };
+}
+
+/// Switches to the page table of the kernel address space on secondary CPUs.
+pub fn init_memory_management_secondary() {
+    unsafe { axhal::arch::write_page_table_root(*KERNEL_PAGE_TABLE_ROOT) };
+}
@@ -0,0 +1,273 @@
+use std::sync::{Mutex, Once};
+
+use axerrno::AxError;
+use axhal::mem::{phys_to_virt, VirtAddr, PAGE_SIZE_4K};
+use axhal::paging::MappingFlags;
+
+use crate::{AddrSpace, Backend, MappedFile};
+
+const BASE: usize = 0x1_0000_0000;
+const MMAP_PAGES: usize = 16;
+const HEAP_SIZE: usize = 0x100_0000;
+
+/// Stubs of the architecture-specific operations used by the address space.
+pub(crate) mod arch {
+    use axhal::mem::VirtAddr;
+
+    /// The TLB of the host is not accessible, so it does nothing.
+    pub fn flush_tlb(_vaddr: Option<VirtAddr>) {}
+}
+
+const RW: MappingFlags = MappingFlags::READ.union(MappingFlags::WRITE);
+
+static INIT: Once = Once::new();
+
+fn new_aspace() -> AddrSpace {
+    INIT.call_once(|| {
+        let layout = std::alloc::Layout::from_size_align(HEAP_SIZE, PAGE_SIZE_4K).unwrap();
+        let heap = unsafe { std::alloc::alloc(layout) };
+        axalloc::global_init(heap as usize, HEAP_SIZE);
+    });
+    AddrSpace::new_empty(page(0), MMAP_PAGES * PAGE_SIZE_4K).unwrap()
+}
+
+fn page(i: usize) -> VirtAddr {
+    VirtAddr::from(BASE + i * PAGE_SIZE_4K)
+}
+
+fn pages(n: usize) -> usize {
+    n * PAGE_SIZE_4K
+}
+
+/// Returns the ranges of the memory areas, in pages from the base.
+fn areas(aspace: &AddrSpace) -> Vec<(usize, usize)> {
+    aspace
+        .areas()
+        .map(|area| {
+            let start = (area.start().as_usize() - BASE) / PAGE_SIZE_4K;
+            (start, start + area.size() / PAGE_SIZE_4K)
+        })
+        .collect()
+}
+
+/// Returns the bits of the mapping flags of the page at `vaddr`.
+fn flags_of(aspace: &AddrSpace, vaddr: VirtAddr) -> usize {
+    aspace.query(vaddr).expect("page not mapped").1.bits()
+}
+
+/// Returns the contents of the page at `vaddr` through its frame.
+fn frame(aspace: &AddrSpace, vaddr: VirtAddr) -> &'static mut [u8] {
+    let (paddr, _) = aspace.query(vaddr).expect("page not mapped");
+    unsafe { core::slice::from_raw_parts_mut(phys_to_virt(paddr).as_mut_ptr(), PAGE_SIZE_4K) }
+}
+
+struct MemFile(Mutex<Vec<u8>>);
+
+impl MappedFile for MemFile {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> axerrno::AxResult<usize> {
+        let data = self.0.lock().unwrap();
+        let start = (offset as usize).min(data.len());
+        let len = buf.len().min(data.len() - start);
+        buf[..len].copy_from_slice(&data[start..start + len]);
+        Ok(len)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> axerrno::AxResult<usize> {
+        let mut data = self.0.lock().unwrap();
+        let end = offset as usize + buf.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[offset as usize..end].copy_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn size(&self) -> axerrno::AxResult<u64> {
+        Ok(self.0.lock().unwrap().len() as u64)
+    }
+}
+
+/// A file of `n` pages, where each byte of page `i` is `i + 1`.
+fn page_file(n: usize) -> std::sync::Arc<MemFile> {
+    let data = (0..pages(n))
+        .map(|i| (i / PAGE_SIZE_4K + 1) as u8)
+        .collect();
+    std::sync::Arc::new(MemFile(Mutex::new(data)))
+}
+
+#[test]
+fn test_map_bounds() {
+    let mut aspace = new_aspace();
+    let map = |aspace: &mut AddrSpace, start, size| aspace.map(start, size, RW, Backend::Anonymous);
+
+    assert_eq!(
+        map(&mut aspace, page(0) + 1, pages(1)),
+        Err(AxError::InvalidInput)
+    );
+    assert_eq!(map(&mut aspace, page(0), 1), Err(AxError::InvalidInput));
+    assert_eq!(map(&mut aspace, page(0), 0), Err(AxError::InvalidInput));
+    assert_eq!(
+        map(&mut aspace, page(MMAP_PAGES - 1), pages(2)),
+        Err(AxError::InvalidInput)
+    );
+
+    map(&mut aspace, page(2), pages(2)).unwrap();
+    assert_eq!(
+        map(&mut aspace, page(3), pages(2)),
+        Err(AxError::AlreadyExists)
+    );
+    assert_eq!(
+        map(&mut aspace, page(1), pages(2)),
+        Err(AxError::AlreadyExists)
+    );
+    map(&mut aspace, page(0), pages(2)).unwrap();
+    assert_eq!(areas(&aspace), [(0, 2), (2, 4)]);
+    assert_eq!(aspace.find_free_area(page(0), pages(1)), Some(page(4)));
+    assert_eq!(aspace.find_free_area(page(0), pages(MMAP_PAGES)), None);
+    assert!(frame(&aspace, page(3)).iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_unmap_partial() {
+    let mut aspace = new_aspace();
+    let file = page_file(4);
+    let backend = Backend::File {
+        file: file.clone(),
+        offset: 0,
+        shared: false,
+    };
+    aspace.map(page(0), pages(4), RW, backend).unwrap();
+    assert_eq!(frame(&aspace, page(2))[0], 3);
+
+    // splits the area
+    aspace.unmap(page(1), pages(1)).unwrap();
+    assert_eq!(areas(&aspace), [(0, 1), (2, 4)]);
+    assert!(aspace.query(page(1)).is_none());
+    let right = aspace.areas().nth(1).unwrap();
+    assert!(matches!(right.backend(), Backend::File { offset, .. } if *offset == pages(2) as u64));
+    assert_eq!(frame(&aspace, page(2))[0], 3);
+
+    // overlaps with a hole and part of an area
+    aspace.unmap(page(1), pages(2)).unwrap();
+    assert_eq!(areas(&aspace), [(0, 1), (3, 4)]);
+    assert_eq!(frame(&aspace, page(3))[0], 4);
+
+    // covers all areas
+    aspace.unmap(page(0), pages(MMAP_PAGES)).unwrap();
+    assert!(areas(&aspace).is_empty());
+    assert!(aspace.query(page(0)).is_none() && aspace.query(page(3)).is_none());
+    assert_eq!(
+        aspace.unmap(page(0), pages(MMAP_PAGES + 1)),
+        Err(AxError::InvalidInput)
+    );
+}
+
+#[test]
+fn test_protect() {
+    let mut aspace = new_aspace();
+    aspace
+        .map(page(0), pages(3), RW, Backend::Anonymous)
+        .unwrap();
+
+    aspace
+        .protect(page(1), pages(1), MappingFlags::READ)
+        .unwrap();
+    assert_eq!(areas(&aspace), [(0, 1), (1, 2), (2, 3)]);
+    let flags: Vec<_> = aspace.areas().map(|area| area.flags().bits()).collect();
+    assert_eq!(flags, [RW.bits(), MappingFlags::READ.bits(), RW.bits()]);
+    assert_eq!(flags_of(&aspace, page(1)), MappingFlags::READ.bits());
+    assert_eq!(flags_of(&aspace, page(2)), RW.bits());
+
+    // fails without changes if there are unmapped pages, like `mprotect`
+    assert_eq!(
+        aspace.protect(page(2), pages(2), MappingFlags::READ),
+        Err(AxError::NoMemory)
+    );
+    aspace.unmap(page(1), pages(1)).unwrap();
+    assert_eq!(
+        aspace.protect(page(0), pages(3), MappingFlags::READ),
+        Err(AxError::NoMemory)
+    );
+    assert_eq!(areas(&aspace), [(0, 1), (2, 3)]);
+    assert_eq!(flags_of(&aspace, page(0)), RW.bits());
+    assert_eq!(flags_of(&aspace, page(2)), RW.bits());
+}
+
+#[test]
+fn test_remap() {
+    let mut aspace = new_aspace();
+    aspace
+        .map(page(0), pages(2), RW, Backend::Anonymous)
+        .unwrap();
+    frame(&aspace, page(0)).fill(0xaa);
+
+    // shrink
+    assert_eq!(
+        aspace.remap(page(0), pages(2), pages(1), false),
+        Ok(page(0))
+    );
+    assert_eq!(areas(&aspace), [(0, 1)]);
+    assert!(aspace.query(page(1)).is_none());
+
+    // grow in place
+    assert_eq!(
+        aspace.remap(page(0), pages(1), pages(3), false),
+        Ok(page(0))
+    );
+    assert_eq!(areas(&aspace), [(0, 3)]);
+    assert_eq!(frame(&aspace, page(0))[0], 0xaa);
+    assert!(frame(&aspace, page(2)).iter().all(|&b| b == 0));
+
+    // move if it can't grow in place
+    aspace
+        .map(page(3), pages(1), RW, Backend::Anonymous)
+        .unwrap();
+    assert_eq!(
+        aspace.remap(page(0), pages(3), pages(4), false),
+        Err(AxError::NoMemory)
+    );
+    let (old_paddr, _) = aspace.query(page(0)).unwrap();
+    assert_eq!(aspace.remap(page(0), pages(3), pages(4), true), Ok(page(4)));
+    assert_eq!(areas(&aspace), [(3, 4), (4, 8)]);
+    assert!(aspace.query(page(0)).is_none());
+    assert_eq!(aspace.query(page(4)).unwrap().0, old_paddr);
+    assert_eq!(frame(&aspace, page(4))[0], 0xaa);
+
+    // the range must be in a single area
+    assert_eq!(
+        aspace.remap(page(3), pages(2), pages(3), true),
+        Err(AxError::BadAddress)
+    );
+    assert_eq!(
+        aspace.remap(page(0), pages(1), pages(2), true),
+        Err(AxError::BadAddress)
+    );
+}
+
+#[test]
+fn test_map_fixed_replace() {
+    let mut aspace = new_aspace();
+    let file = page_file(3);
+    let backend = Backend::File {
+        file: file.clone(),
+        offset: 0,
+        shared: true,
+    };
+    aspace.map(page(0), pages(3), RW, backend).unwrap();
+    frame(&aspace, page(1)).fill(0xbb);
+
+    // `MAP_FIXED` unmaps the overlapped part before mapping
+    assert_eq!(
+        aspace.map(page(1), pages(1), RW, Backend::Anonymous),
+        Err(AxError::AlreadyExists)
+    );
+    aspace.unmap(page(1), pages(1)).unwrap();
+    aspace
+        .map(page(1), pages(1), MappingFlags::READ, Backend::Anonymous)
+        .unwrap();
+    assert_eq!(areas(&aspace), [(0, 1), (1, 2), (2, 3)]);
+    assert!(frame(&aspace, page(1)).iter().all(|&b| b == 0));
+    assert_eq!(frame(&aspace, page(2))[0], 3);
+    // the replaced part of the shared mapping is written back
+    assert_eq!(file.0.lock().unwrap()[pages(1)], 0xbb);
+}
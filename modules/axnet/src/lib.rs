@@ -8,12 +8,39 @@
 //!
 //! - [`TcpSocket`]: A TCP socket that provides POSIX-like APIs.
 //! - [`UdpSocket`]: A UDP socket that provides POSIX-like APIs.
+//! - [`IcmpSocket`]: An ICMP socket for sending echo requests and receiving
+//!   echo replies.
+//! - [`RawSocket`]: A raw IP socket that provides POSIX-like APIs.
 //! - [`dns_query`]: Function for DNS query.
+//! - [`iface_list`]: Function to enumerate the network interfaces.
+//! - [`iface_config`] and [`set_iface_config`]: Functions to read and change
+//!   the interface configuration at runtime.
+//!
+//! # Interfaces
+//!
+//! Each NIC device is brought up as an Ethernet interface `eth0..ethN`, in
+//! addition to the loopback interface `lo` with `127.0.0.1` and `::1`. The
+//! egress interface of each destination is selected by the longest prefix
+//! match over the networks and routes of all interfaces.
+//!
+//! # Blocking and Polling
+//!
+//! If both the `multitask` and `irq` features are enabled, a poller task polls
+//! the interfaces when NIC interrupts are raised and when the timers of the
+//! network stack expire. NICs without interrupts are polled periodically.
+//! Blocked socket operations sleep until the socket is ready, and
+//! [`wait_socket_events`] sleeps until any socket is, e.g., for `epoll`.
+//!
+//! Otherwise, blocked operations poll the interfaces and yield the CPU in a
+//! loop.
 //!
 //! # Cargo Features
 //!
 //! - `smoltcp`: Use [smoltcp] as the underlying network stack. This is enabled
 //!   by default.
+//! - `dhcp`: Configure the IPv4 address, the gateway and DNS servers by DHCP.
+//! - `multitask`: Sockets can block tasks on wait queues.
+//! - `irq`: NIC interrupts and timers can be used to drive polling.
 //!
 //! [smoltcp]: https://github.com/smoltcp-rs/smoltcp
 
@@ -36,14 +63,55 @@ pub use self::net_impl::TcpSocket;
 pub use self::net_impl::UdpSocket;
 pub use self::net_impl::{bench_receive, bench_transmit};
 pub use self::net_impl::{dns_query, poll_interfaces};
+pub use self::net_impl::{iface_config, iface_list, set_iface_config};
+pub use self::net_impl::{
+    notify_socket_events, socket_events, socket_events_waker, wait_socket_events, SocketWaiter,
+};
+pub use self::net_impl::{IcmpSocket, RawSocket};
 
+use alloc::{string::String, vec::Vec};
 use axdriver::{prelude::*, AxDeviceContainer};
+use core::net::{IpAddr, Ipv4Addr};
+
+/// The configuration of a network interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfaceConfig {
+    /// Whether the IPv4 address and the gateway are acquired by DHCP.
+    ///
+    /// DHCP is available only if the `dhcp` feature is enabled.
+    pub dhcp: bool,
+    /// The IPv4 address and its prefix length.
+    pub ipv4: Option<(Ipv4Addr, u8)>,
+    /// The default IPv4 gateway.
+    pub gateway: Option<Ipv4Addr>,
+    /// DNS servers used by [`dns_query`], at most 4 servers.
+    pub dns_servers: Vec<IpAddr>,
+}
+
+/// The information of a network interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfaceInfo {
+    /// The interface name, e.g., `lo` or `eth0`.
+    pub name: String,
+    /// The MAC address, or `None` for the loopback interface.
+    pub ether_addr: Option<[u8; 6]>,
+    /// IP addresses and their prefix lengths.
+    pub addrs: Vec<(IpAddr, u8)>,
+    /// Whether it's the loopback interface.
+    pub loopback: bool,
+}
 
 /// Initializes the network subsystem by NIC devices.
 pub fn init_network(mut net_devs: AxDeviceContainer<AxNetDevice>) {
     info!("Initialize network subsystem...");
 
-    let dev = net_devs.take_one().expect("No NIC device found!");
-    info!("  use NIC 0: {:?}", dev.device_name());
-    net_impl::init(dev);
+    let mut devs = Vec::new();
+    while let Some(dev) = net_devs.take_one() {
+        info!("  use NIC {}: {:?}", devs.len(), dev.device_name());
+        devs.push(dev);
+    }
+    if devs.is_empty() {
+        warn!("No NIC device found, only the loopback interface is available");
+    }
+    net_impl::init(devs);
 }
@@ -0,0 +1,71 @@
+//! DHCPv4 client.
+//!
+//! The lease is acquired and renewed by a smoltcp DHCP socket. Every time the
+//! configuration changes, the IPv4 address and the default route of the
+//! interface, and the DNS servers are updated accordingly.
+
+use alloc::vec::Vec;
+
+use smoltcp::iface::{Interface, SocketHandle, SocketSet};
+use smoltcp::socket::dhcpv4::{self, Event};
+use smoltcp::wire::IpAddress;
+
+use super::{set_dns_servers, set_ipv4_config};
+
+/// The DHCP state of an interface.
+pub struct Dhcp {
+    handle: SocketHandle,
+    configured: bool,
+}
+
+impl Dhcp {
+    /// Starts to acquire a lease by adding a DHCP socket to `sockets`.
+    pub fn new(sockets: &mut SocketSet<'_>) -> Self {
+        Self {
+            handle: sockets.add(dhcpv4::Socket::new()),
+            configured: false,
+        }
+    }
+
+    /// Whether a lease is acquired.
+    pub fn is_configured(&self) -> bool {
+        self.configured
+    }
+
+    /// Removes the DHCP socket from `sockets`.
+    pub fn stop(self, sockets: &mut SocketSet<'_>) {
+        sockets.remove(self.handle);
+    }
+
+    /// Applies the configuration changes if any.
+    ///
+    /// It should be called after each [`Interface::poll`].
+    pub fn poll(&mut self, iface: &mut Interface, sockets: &mut SocketSet<'_>) {
+        let socket = sockets.get_mut::<dhcpv4::Socket>(self.handle);
+        match socket.poll() {
+            Some(Event::Configured(config)) => {
+                info!("DHCP: configured {}", config.address);
+                set_ipv4_config(iface, Some(config.address), config.router);
+                if let Some(router) = config.router {
+                    info!("DHCP: default route via {}", router);
+                }
+                if !config.dns_servers.is_empty() {
+                    let servers: Vec<_> = config
+                        .dns_servers
+                        .iter()
+                        .map(|&s| IpAddress::Ipv4(s))
+                        .collect();
+                    info!("DHCP: DNS servers {:?}", servers);
+                    set_dns_servers(&servers);
+                }
+                self.configured = true;
+            }
+            Some(Event::Deconfigured) if self.configured => {
+                warn!("DHCP: lease lost");
+                set_ipv4_config(iface, None, None);
+                self.configured = false;
+            }
+            _ => {}
+        }
+    }
+}
@@ -2,12 +2,11 @@ use alloc::vec::Vec;
 use axerrno::{ax_err_type, AxError, AxResult};
 use core::net::IpAddr;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::dns::{self, GetQueryResultError, StartQueryError};
 use smoltcp::wire::DnsQueryType;
 
 use super::addr::into_core_ipaddr;
-use super::{SocketSetWrapper, ETH0, SOCKET_SET};
+use super::{route, SocketHandle, SocketSetWrapper, SocketWaiter, DNS_SERVERS, IFACES, SOCKET_SET};
 
 /// A DNS socket.
 struct DnsSocket {
@@ -16,10 +15,13 @@ struct DnsSocket {
 
 impl DnsSocket {
     #[allow(clippy::new_without_default)]
-    /// Creates a new DNS socket.
+    /// Creates a new DNS socket on the interface to the first DNS server.
     pub fn new() -> Self {
-        let socket = SocketSetWrapper::new_dns_socket();
-        let handle = Some(SOCKET_SET.add(socket));
+        let server = DNS_SERVERS.lock().first().copied();
+        let handle = server.and_then(route::lookup).map(|iface| {
+            let socket = SocketSetWrapper::new_dns_socket();
+            SOCKET_SET.add(iface, socket)
+        });
         Self { handle }
     }
 
@@ -34,8 +36,10 @@ impl DnsSocket {
     /// Query a address with given DNS query type.
     pub fn query(&self, name: &str, query_type: DnsQueryType) -> AxResult<Vec<IpAddr>> {
         // let local_addr = self.local_addr.unwrap_or_else(f);
-        let handle = self.handle.ok_or_else(|| ax_err_type!(InvalidInput))?;
-        let iface = &ETH0.iface;
+        let handle = self
+            .handle
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "socket query() failed: no route"))?;
+        let iface = &IFACES[handle.iface].iface;
         let query_handle = SOCKET_SET
             .with_socket_mut::<dns::Socket, _, _>(handle, |socket| {
                 socket.start_query(iface.lock().context(), name, query_type)
@@ -51,9 +55,12 @@ impl DnsSocket {
                     ax_err_type!(InvalidInput, "socket query() failed: too long name")
                 }
             })?;
+        let waiter = SocketWaiter::new();
         loop {
             SOCKET_SET.poll_interfaces();
+            let events = waiter.events();
             match SOCKET_SET.with_socket_mut::<dns::Socket, _, _>(handle, |socket| {
+                socket.register_query_waker(query_handle, &waiter.waker());
                 socket.get_query_result(query_handle).map_err(|e| match e {
                     GetQueryResultError::Pending => AxError::WouldBlock,
                     GetQueryResultError::Failed => {
@@ -68,7 +75,7 @@ impl DnsSocket {
                     }
                     return Ok(res);
                 }
-                Err(AxError::WouldBlock) => axtask::yield_now(),
+                Err(AxError::WouldBlock) => waiter.wait(events, None),
                 Err(e) => return Err(e),
             }
         }
@@ -0,0 +1,307 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::net::IpAddr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+use axerrno::{ax_err, ax_err_type, AxError, AxResult};
+use axio::PollState;
+use axsync::Mutex;
+use spin::RwLock;
+
+use smoltcp::socket::icmp::{self, BindError, Endpoint, SendError};
+use smoltcp::wire::IpAddress;
+
+use super::addr::{from_core_ipaddr, into_core_ipaddr, is_unspecified};
+use super::options::SocketOptions;
+use super::{
+    local_ifaces, notify_poller, route, SocketHandle, SocketSetWrapper, SocketWaiter, SOCKET_SET,
+};
+use super::{ICMP_RX_BUF_LEN, ICMP_TX_BUF_LEN};
+
+/// The type of ICMPv4 echo requests.
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+/// The type of ICMPv6 echo requests.
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+
+/// An ICMP socket for sending echo requests and receiving echo replies, like
+/// the "ping socket" (`SOCK_DGRAM` + `IPPROTO_ICMP`) of Linux.
+///
+/// It's bound to an echo identifier on all interfaces. The identifier of each
+/// echo request sent through it is replaced with the bound one, and only echo
+/// replies with that identifier are received.
+pub struct IcmpSocket {
+    /// One socket for each interface.
+    handles: RwLock<Vec<SocketHandle>>,
+    ident: RwLock<Option<u16>>,
+    nonblock: AtomicBool,
+    opts: SocketOptions,
+    waiter: Arc<SocketWaiter>,
+}
+
+impl IcmpSocket {
+    /// Creates a new ICMP socket.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            handles: RwLock::new(Vec::new()),
+            ident: RwLock::new(None),
+            nonblock: AtomicBool::new(false),
+            opts: SocketOptions::new(ICMP_RX_BUF_LEN, ICMP_TX_BUF_LEN),
+            waiter: SocketWaiter::new(),
+        }
+    }
+
+    /// Returns the bound echo identifier, or
+    /// [`Err(NotConnected)`](AxError::NotConnected) if not bound.
+    pub fn ident(&self) -> AxResult<u16> {
+        self.ident.read().ok_or(AxError::NotConnected)
+    }
+
+    /// Returns whether this socket is in nonblocking mode.
+    #[inline]
+    pub fn is_nonblocking(&self) -> bool {
+        self.nonblock.load(Ordering::Acquire)
+    }
+
+    /// Moves this ICMP socket into or out of nonblocking mode.
+    ///
+    /// This will result in `recv_from` and `send_to` operations becoming
+    /// nonblocking, i.e., immediately returning from their calls. If the IO
+    /// operation could not be completed and needs to be retried, an error with
+    /// kind [`Err(WouldBlock)`](AxError::WouldBlock) is returned.
+    #[inline]
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.nonblock.store(nonblocking, Ordering::Release);
+    }
+
+    /// Returns the timeout of receiving operations (`SO_RCVTIMEO`).
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.opts.read_timeout()
+    }
+
+    /// Sets the timeout of receiving operations (`SO_RCVTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_read_timeout(timeout)
+    }
+
+    /// Returns the timeout of sending operations (`SO_SNDTIMEO`).
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.opts.write_timeout()
+    }
+
+    /// Sets the timeout of sending operations (`SO_SNDTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_write_timeout(timeout)
+    }
+
+    /// Binds an unbound socket to the given echo identifier. If it's zero, an
+    /// unused identifier is chosen.
+    ///
+    /// It's called automatically by the first [`send_to`](Self::send_to).
+    pub fn bind(&self, mut ident: u16) -> AxResult {
+        let mut self_ident = self.ident.write();
+        if self_ident.is_some() {
+            return ax_err!(InvalidInput, "socket bind() failed: already bound");
+        }
+        if ident == 0 {
+            ident = get_ephemeral_ident();
+        }
+
+        let mut handles = self.handles.write();
+        for iface in local_ifaces(None) {
+            let socket = SocketSetWrapper::new_icmp_socket(
+                self.opts.recv_buf_size(),
+                self.opts.send_buf_size(),
+            );
+            let handle = SOCKET_SET.add(iface, socket);
+            handles.push(handle);
+            SOCKET_SET.with_socket_mut::<icmp::Socket, _, _>(handle, |socket| {
+                socket.bind(Endpoint::Ident(ident)).or_else(|e| match e {
+                    BindError::InvalidState => ax_err!(AlreadyExists, "socket bind() failed"),
+                    BindError::Unaddressable => ax_err!(InvalidInput, "socket bind() failed"),
+                })
+            })?;
+            debug!("ICMP socket {}: bound on ident {}", handle, ident);
+        }
+
+        *self_ident = Some(ident);
+        Ok(())
+    }
+
+    /// Sends an ICMP message to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// `buf` is a whole ICMP (or ICMPv6 for IPv6 addresses) echo request,
+    /// including the header. Its checksum is calculated by the stack.
+    pub fn send_to(&self, buf: &[u8], remote_addr: IpAddr) -> AxResult<usize> {
+        let remote_addr = from_core_ipaddr(remote_addr);
+        if is_unspecified(remote_addr) {
+            return ax_err!(InvalidInput, "socket send_to() failed: invalid address");
+        }
+        let echo_request = match remote_addr {
+            IpAddress::Ipv4(_) => ICMPV4_ECHO_REQUEST,
+            IpAddress::Ipv6(_) => ICMPV6_ECHO_REQUEST,
+        };
+        if buf.len() < 8 || buf[0] != echo_request || buf[1] != 0 {
+            return ax_err!(InvalidInput, "socket send_to() failed: not an echo request");
+        }
+        if self.ident.read().is_none() {
+            self.bind(0)?;
+        }
+        let ident = self.ident()?;
+
+        let handle = route::lookup(remote_addr)
+            .and_then(|iface| {
+                let handles = self.handles.read();
+                handles.iter().find(|handle| handle.iface == iface).copied()
+            })
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "socket send_to() failed: no route"))?;
+
+        self.block_on(self.write_timeout(), || {
+            SOCKET_SET.with_socket_mut::<icmp::Socket, _, _>(handle, |socket| {
+                let packet = socket.send(buf.len(), remote_addr).map_err(|e| match e {
+                    SendError::BufferFull => AxError::WouldBlock,
+                    SendError::Unaddressable => {
+                        ax_err_type!(ConnectionRefused, "socket send_to() failed")
+                    }
+                })?;
+                packet.copy_from_slice(buf);
+                packet[4..6].copy_from_slice(&ident.to_be_bytes());
+                Ok(buf.len())
+            })
+        })
+    }
+
+    /// Receives a single ICMP message on the socket. On success, returns the
+    /// number of bytes read and the origin.
+    ///
+    /// The message is truncated if `buf` is too small to hold it.
+    pub fn recv_from(&self, buf: &mut [u8]) -> AxResult<(usize, IpAddr)> {
+        if self.ident.read().is_none() {
+            return ax_err!(NotConnected, "socket recv_from() failed");
+        }
+
+        self.block_on(self.read_timeout(), || {
+            for &handle in self.handles.read().iter() {
+                let res = SOCKET_SET.with_socket_mut::<icmp::Socket, _, _>(handle, |socket| {
+                    socket.can_recv().then(|| match socket.recv() {
+                        Ok((packet, addr)) => {
+                            let len = packet.len().min(buf.len());
+                            buf[..len].copy_from_slice(&packet[..len]);
+                            Ok((len, into_core_ipaddr(addr)))
+                        }
+                        Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+                    })
+                });
+                if let Some(res) = res {
+                    return res;
+                }
+            }
+            // no more data
+            Err(AxError::WouldBlock)
+        })
+    }
+
+    /// Close the socket. It no longer receives any message.
+    pub fn shutdown(&self) -> AxResult {
+        for handle in self.handles.write().drain(..) {
+            debug!("ICMP socket {}: shutting down", handle);
+            SOCKET_SET.remove(handle);
+        }
+        Ok(())
+    }
+
+    /// Whether the socket is readable or writable.
+    pub fn poll(&self) -> AxResult<PollState> {
+        self.register_waker();
+        let mut state = PollState {
+            readable: false,
+            writable: self.ident.read().is_none(),
+            hangup: false,
+            error: false,
+        };
+        for &handle in self.handles.read().iter() {
+            SOCKET_SET.with_socket::<icmp::Socket, _, _>(handle, |socket| {
+                state.readable |= socket.can_recv();
+                state.writable |= socket.can_send();
+            });
+        }
+        Ok(state)
+    }
+}
+
+/// Private methods
+impl IcmpSocket {
+    /// Registers the waker of blocked tasks to the underlying sockets.
+    fn register_waker(&self) {
+        let waker = self.waiter.waker();
+        for &handle in self.handles.read().iter() {
+            SOCKET_SET.with_socket_mut::<icmp::Socket, _, _>(handle, |socket| {
+                socket.register_recv_waker(&waker);
+                socket.register_send_waker(&waker);
+            });
+        }
+    }
+
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
+    where
+        F: FnMut() -> AxResult<T>,
+    {
+        if self.is_nonblocking() {
+            f().map(|t| {
+                notify_poller();
+                t
+            })
+        } else {
+            let deadline = timeout.map(|t| axhal::time::current_time() + t);
+            loop {
+                SOCKET_SET.poll_interfaces();
+                let events = self.waiter.events();
+                self.register_waker();
+                match f() {
+                    Ok(t) => {
+                        notify_poller();
+                        return Ok(t);
+                    }
+                    Err(AxError::WouldBlock) => {
+                        if deadline.is_some_and(|d| axhal::time::current_time() >= d) {
+                            return Err(AxError::WouldBlock);
+                        }
+                        self.waiter.wait(events, deadline)
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for IcmpSocket {
+    fn drop(&mut self) {
+        self.shutdown().ok();
+    }
+}
+
+fn get_ephemeral_ident() -> u16 {
+    const IDENT_START: u16 = 0x1000;
+    static CURR: Mutex<u16> = Mutex::new(IDENT_START);
+    let mut curr = CURR.lock();
+
+    let ident = *curr;
+    *curr = if *curr == u16::MAX {
+        IDENT_START
+    } else {
+        *curr + 1
+    };
+    ident
+}
@@ -1,25 +1,37 @@
-use alloc::{boxed::Box, collections::VecDeque};
+use alloc::{boxed::Box, collections::VecDeque, sync::Arc};
 use core::ops::{Deref, DerefMut};
 
 use axerrno::{ax_err, AxError, AxResult};
 use axsync::Mutex;
-use smoltcp::iface::{SocketHandle, SocketSet};
+use smoltcp::iface::SocketSet;
 use smoltcp::socket::tcp::{self, State};
 use smoltcp::wire::{IpAddress, IpEndpoint, IpListenEndpoint};
 
-use super::{SocketSetWrapper, LISTEN_QUEUE_SIZE, SOCKET_SET};
+use super::options::SocketOptions;
+use super::{SocketHandle, SocketSetWrapper, SocketWaiter, LISTEN_QUEUE_SIZE, SOCKET_SET};
 
 const PORT_NUM: usize = 65536;
 
 struct ListenTableEntry {
     listen_endpoint: IpListenEndpoint,
+    /// Options of the listening socket, read when new connections arrive.
+    opts: Arc<SocketOptions>,
+    /// The waiter of the listening socket, woken when new connections are
+    /// established.
+    waiter: Arc<SocketWaiter>,
     syn_queue: VecDeque<SocketHandle>,
 }
 
 impl ListenTableEntry {
-    pub fn new(listen_endpoint: IpListenEndpoint) -> Self {
+    pub fn new(
+        listen_endpoint: IpListenEndpoint,
+        opts: Arc<SocketOptions>,
+        waiter: Arc<SocketWaiter>,
+    ) -> Self {
         Self {
             listen_endpoint,
+            opts,
+            waiter,
             syn_queue: VecDeque::with_capacity(LISTEN_QUEUE_SIZE),
         }
     }
@@ -61,12 +73,21 @@ impl ListenTable {
         self.tcp[port as usize].lock().is_none()
     }
 
-    pub fn listen(&self, listen_endpoint: IpListenEndpoint) -> AxResult {
+    pub fn listen(
+        &self,
+        listen_endpoint: IpListenEndpoint,
+        opts: Arc<SocketOptions>,
+        waiter: Arc<SocketWaiter>,
+    ) -> AxResult {
         let port = listen_endpoint.port;
         assert_ne!(port, 0);
         let mut entry = self.tcp[port as usize].lock();
         if entry.is_none() {
-            *entry = Some(Box::new(ListenTableEntry::new(listen_endpoint)));
+            *entry = Some(Box::new(ListenTableEntry::new(
+                listen_endpoint,
+                opts,
+                waiter,
+            )));
             Ok(())
         } else {
             ax_err!(AddrInUse, "socket listen() failed")
@@ -112,6 +133,7 @@ impl ListenTable {
 
     pub fn incoming_tcp_packet(
         &self,
+        iface: usize,
         src: IpEndpoint,
         dst: IpEndpoint,
         sockets: &mut SocketSet<'_>,
@@ -126,9 +148,17 @@ impl ListenTable {
                 warn!("SYN queue overflow!");
                 return;
             }
-            let mut socket = SocketSetWrapper::new_tcp_socket();
+            let opts = &entry.opts;
+            let mut socket =
+                SocketSetWrapper::new_tcp_socket(opts.recv_buf_size(), opts.send_buf_size());
+            opts.apply_tcp(&mut socket);
             if socket.listen(entry.listen_endpoint).is_ok() {
-                let handle = sockets.add(socket);
+                // wake up the listening socket when the connection is established
+                socket.register_recv_waker(&entry.waiter.waker());
+                let handle = SocketHandle {
+                    iface,
+                    inner: sockets.add(socket),
+                };
                 debug!(
                     "TCP socket {}: prepare for connection {} -> {}",
                     handle, src, entry.listen_endpoint
@@ -0,0 +1,83 @@
+//! The loopback device, on which every transmitted packet is received by
+//! itself.
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use smoltcp::iface::SocketSet;
+use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
+use smoltcp::time::Instant;
+
+use super::snoop_ip_packet;
+
+/// The MTU of the loopback device, the same as Linux.
+const LOOPBACK_MTU: usize = 65536;
+
+pub struct LoopbackDev {
+    iface: usize,
+    queue: VecDeque<Vec<u8>>,
+}
+
+impl LoopbackDev {
+    /// Creates a loopback device for the interface at index `iface`.
+    pub const fn new(iface: usize) -> Self {
+        Self {
+            iface,
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl Device for LoopbackDev {
+    type RxToken<'a> = LoopbackRxToken where Self: 'a;
+    type TxToken<'a> = LoopbackTxToken<'a> where Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let buf = self.queue.pop_front()?;
+        Some((
+            LoopbackRxToken(self.iface, buf),
+            LoopbackTxToken(&mut self.queue),
+        ))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(LoopbackTxToken(&mut self.queue))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.max_transmission_unit = LOOPBACK_MTU;
+        caps.max_burst_size = None;
+        caps.medium = Medium::Ip;
+        caps
+    }
+}
+
+pub struct LoopbackRxToken(usize, Vec<u8>);
+pub struct LoopbackTxToken<'a>(&'a mut VecDeque<Vec<u8>>);
+
+impl RxToken for LoopbackRxToken {
+    fn preprocess(&self, sockets: &mut SocketSet<'_>) {
+        snoop_ip_packet(self.0, &self.1, sockets).ok();
+    }
+
+    fn consume<R, F>(mut self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        trace!("LOOPBACK RECV {} bytes: {:02X?}", self.1.len(), self.1);
+        f(&mut self.1)
+    }
+}
+
+impl<'a> TxToken for LoopbackTxToken<'a> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut buf = vec![0; len];
+        let ret = f(&mut buf);
+        trace!("LOOPBACK SEND {} bytes: {:02X?}", len, buf);
+        self.0.push_back(buf);
+        ret
+    }
+}
@@ -1,30 +1,56 @@
 mod addr;
 mod bench;
+#[cfg(feature = "dhcp")]
+mod dhcp;
 mod dns;
+mod icmp;
 mod listen_table;
+mod loopback;
+mod options;
+#[cfg(all(feature = "multitask", feature = "irq"))]
+mod poller;
+mod raw;
+mod route;
+mod slaac;
 mod tcp;
 mod udp;
+mod waiter;
 
-use alloc::vec;
+use alloc::{format, string::String, vec, vec::Vec};
 use core::cell::RefCell;
-use core::ops::DerefMut;
+use core::fmt;
 
 use axdriver::prelude::*;
+use axerrno::{ax_err, ax_err_type, AxResult};
 use axhal::time::{current_time_nanos, NANOS_PER_MICROS};
 use axsync::Mutex;
 use driver_net::{DevError, NetBufPtr};
 use lazy_init::LazyInit;
-use smoltcp::iface::{Config, Interface, SocketHandle, SocketSet};
+use smoltcp::iface::{Config, Interface, SocketSet};
 use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
 use smoltcp::socket::{self, AnySocket};
 use smoltcp::time::Instant;
-use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr};
-
+use smoltcp::wire::{
+    EthernetAddress, HardwareAddress, IpAddress, IpCidr, IpProtocol, IpVersion, Ipv4Address,
+    Ipv4Cidr, Ipv6Address,
+};
+
+use self::addr::{from_core_ipaddr, into_core_ipaddr};
+#[cfg(feature = "dhcp")]
+use self::dhcp::Dhcp;
 use self::listen_table::ListenTable;
+use self::loopback::LoopbackDev;
+use self::slaac::Slaac;
+use crate::{IfaceConfig, IfaceInfo};
 
 pub use self::dns::dns_query;
+pub use self::icmp::IcmpSocket;
+pub use self::raw::RawSocket;
 pub use self::tcp::TcpSocket;
 pub use self::udp::UdpSocket;
+pub use self::waiter::{
+    notify_socket_events, socket_events, socket_events_waker, wait_socket_events, SocketWaiter,
+};
 
 macro_rules! env_or_default {
     ($key:literal) => {
@@ -37,8 +63,17 @@ macro_rules! env_or_default {
 
 const IP: &str = env_or_default!("AX_IP");
 const GATEWAY: &str = env_or_default!("AX_GW");
+const IP6: &str = env_or_default!("AX_IP6");
+const GATEWAY6: &str = env_or_default!("AX_GW6");
 const DNS_SEVER: &str = "8.8.8.8";
 const IP_PREFIX: u8 = 24;
+const IP6_PREFIX: u8 = 64;
+
+/// The maximum number of DNS servers, the same as the `dns-max-server-count-*`
+/// feature of smoltcp.
+const MAX_DNS_SERVERS: usize = 4;
+#[cfg(feature = "dhcp")]
+const DHCP_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(10);
 
 const STANDARD_MTU: usize = 1500;
 
@@ -48,55 +83,123 @@ const TCP_RX_BUF_LEN: usize = 64 * 1024;
 const TCP_TX_BUF_LEN: usize = 64 * 1024;
 const UDP_RX_BUF_LEN: usize = 64 * 1024;
 const UDP_TX_BUF_LEN: usize = 64 * 1024;
+const ICMP_RX_BUF_LEN: usize = 16 * 1024;
+const ICMP_TX_BUF_LEN: usize = 16 * 1024;
+const RAW_RX_BUF_LEN: usize = 64 * 1024;
+const RAW_TX_BUF_LEN: usize = 64 * 1024;
+/// The range of socket buffer sizes that can be set by users.
+const MIN_BUF_LEN: usize = 1024;
+const MAX_BUF_LEN: usize = 4 * 1024 * 1024;
 const LISTEN_QUEUE_SIZE: usize = 512;
 
 static LISTEN_TABLE: LazyInit<ListenTable> = LazyInit::new();
 static SOCKET_SET: LazyInit<SocketSetWrapper> = LazyInit::new();
-static ETH0: LazyInit<InterfaceWrapper> = LazyInit::new();
+/// All interfaces, the loopback interface comes first, then `eth0..ethN`.
+static IFACES: LazyInit<Vec<InterfaceWrapper>> = LazyInit::new();
+static DNS_SERVERS: Mutex<Vec<IpAddress>> = Mutex::new(Vec::new());
 
-struct SocketSetWrapper<'a>(Mutex<SocketSet<'a>>);
+/// Socket sets of all interfaces, indexed the same as [`IFACES`].
+///
+/// A socket only sends and receives packets through the interface of its set.
+struct SocketSetWrapper<'a>(Vec<Mutex<SocketSet<'a>>>);
+
+/// A handle to a socket in the socket set of an interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SocketHandle {
+    iface: usize,
+    inner: smoltcp::iface::SocketHandle,
+}
 
 struct DeviceWrapper {
+    iface: usize,
     inner: RefCell<AxNetDevice>, // use `RefCell` is enough since it's wrapped in `Mutex` in `InterfaceWrapper`.
 }
 
+enum NetDevice {
+    Ethernet(DeviceWrapper),
+    Loopback(LoopbackDev),
+}
+
 struct InterfaceWrapper {
-    name: &'static str,
-    ether_addr: EthernetAddress,
-    dev: Mutex<DeviceWrapper>,
+    index: usize,
+    name: String,
+    ether_addr: Option<EthernetAddress>,
+    dev: Mutex<NetDevice>,
     iface: Mutex<Interface>,
+    slaac: Mutex<Option<Slaac>>,
+    #[cfg(feature = "dhcp")]
+    dhcp: Mutex<Option<Dhcp>>,
 }
 
 impl<'a> SocketSetWrapper<'a> {
-    fn new() -> Self {
-        Self(Mutex::new(SocketSet::new(vec![])))
+    fn new(num_ifaces: usize) -> Self {
+        Self(
+            (0..num_ifaces)
+                .map(|_| Mutex::new(SocketSet::new(vec![])))
+                .collect(),
+        )
+    }
+
+    /// Returns the socket set of the interface at index `iface`.
+    fn sockets(&self, iface: usize) -> &Mutex<SocketSet<'a>> {
+        &self.0[iface]
     }
 
-    pub fn new_tcp_socket() -> socket::tcp::Socket<'a> {
-        let tcp_rx_buffer = socket::tcp::SocketBuffer::new(vec![0; TCP_RX_BUF_LEN]);
-        let tcp_tx_buffer = socket::tcp::SocketBuffer::new(vec![0; TCP_TX_BUF_LEN]);
+    pub fn new_tcp_socket(rx_buf_len: usize, tx_buf_len: usize) -> socket::tcp::Socket<'a> {
+        let tcp_rx_buffer = socket::tcp::SocketBuffer::new(vec![0; rx_buf_len]);
+        let tcp_tx_buffer = socket::tcp::SocketBuffer::new(vec![0; tx_buf_len]);
         socket::tcp::Socket::new(tcp_rx_buffer, tcp_tx_buffer)
     }
 
-    pub fn new_udp_socket() -> socket::udp::Socket<'a> {
+    pub fn new_udp_socket(rx_buf_len: usize, tx_buf_len: usize) -> socket::udp::Socket<'a> {
         let udp_rx_buffer = socket::udp::PacketBuffer::new(
             vec![socket::udp::PacketMetadata::EMPTY; 8],
-            vec![0; UDP_RX_BUF_LEN],
+            vec![0; rx_buf_len],
         );
         let udp_tx_buffer = socket::udp::PacketBuffer::new(
             vec![socket::udp::PacketMetadata::EMPTY; 8],
-            vec![0; UDP_TX_BUF_LEN],
+            vec![0; tx_buf_len],
         );
         socket::udp::Socket::new(udp_rx_buffer, udp_tx_buffer)
     }
 
+    pub fn new_icmp_socket(rx_buf_len: usize, tx_buf_len: usize) -> socket::icmp::Socket<'a> {
+        let icmp_rx_buffer = socket::icmp::PacketBuffer::new(
+            vec![socket::icmp::PacketMetadata::EMPTY; 8],
+            vec![0; rx_buf_len],
+        );
+        let icmp_tx_buffer = socket::icmp::PacketBuffer::new(
+            vec![socket::icmp::PacketMetadata::EMPTY; 8],
+            vec![0; tx_buf_len],
+        );
+        socket::icmp::Socket::new(icmp_rx_buffer, icmp_tx_buffer)
+    }
+
+    pub fn new_raw_socket(
+        ip_version: IpVersion,
+        protocol: IpProtocol,
+        rx_buf_len: usize,
+        tx_buf_len: usize,
+    ) -> socket::raw::Socket<'a> {
+        let raw_rx_buffer = socket::raw::PacketBuffer::new(
+            vec![socket::raw::PacketMetadata::EMPTY; 16],
+            vec![0; rx_buf_len],
+        );
+        let raw_tx_buffer = socket::raw::PacketBuffer::new(
+            vec![socket::raw::PacketMetadata::EMPTY; 16],
+            vec![0; tx_buf_len],
+        );
+        socket::raw::Socket::new(ip_version, protocol, raw_rx_buffer, raw_tx_buffer)
+    }
+
     pub fn new_dns_socket() -> socket::dns::Socket<'a> {
-        let server_addr = DNS_SEVER.parse().expect("invalid DNS server address");
-        socket::dns::Socket::new(&[server_addr], vec![])
+        socket::dns::Socket::new(&DNS_SERVERS.lock(), vec![])
     }
 
-    pub fn add<T: AnySocket<'a>>(&self, socket: T) -> SocketHandle {
-        let handle = self.0.lock().add(socket);
+    /// Adds a socket to the interface at index `iface`.
+    pub fn add<T: AnySocket<'a>>(&self, iface: usize, socket: T) -> SocketHandle {
+        let inner = self.0[iface].lock().add(socket);
+        let handle = SocketHandle { iface, inner };
         debug!("socket {}: created", handle);
         handle
     }
@@ -105,8 +208,8 @@ impl<'a> SocketSetWrapper<'a> {
     where
         F: FnOnce(&T) -> R,
     {
-        let set = self.0.lock();
-        let socket = set.get(handle);
+        let set = self.0[handle.iface].lock();
+        let socket = set.get(handle.inner);
         f(socket)
     }
 
@@ -114,33 +217,101 @@ impl<'a> SocketSetWrapper<'a> {
     where
         F: FnOnce(&mut T) -> R,
     {
-        let mut set = self.0.lock();
-        let socket = set.get_mut(handle);
+        let mut set = self.0[handle.iface].lock();
+        let socket = set.get_mut(handle.inner);
         f(socket)
     }
 
     pub fn poll_interfaces(&self) {
-        ETH0.poll(&self.0);
+        for iface in IFACES.iter() {
+            iface.poll(&self.0[iface.index]);
+        }
+    }
+
+    /// Returns how long to wait before polling interfaces again, or `None` if
+    /// it's not needed until new packets arrive.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    pub fn poll_delay(&self) -> Option<core::time::Duration> {
+        IFACES
+            .iter()
+            .filter_map(|iface| iface.poll_delay(&self.0[iface.index]))
+            .min()
     }
 
     pub fn remove(&self, handle: SocketHandle) {
-        self.0.lock().remove(handle);
+        self.0[handle.iface].lock().remove(handle.inner);
         debug!("socket {}: destroyed", handle);
     }
+
+    /// Returns whether an open TCP or UDP socket (depending on `protocol`)
+    /// uses the local `port` on any interface.
+    ///
+    /// Listening TCP sockets are not included, as they are in the
+    /// [`ListenTable`].
+    pub fn port_in_use(&self, protocol: IpProtocol, port: u16) -> bool {
+        self.0.iter().any(|set| {
+            set.lock()
+                .iter()
+                .any(|(_, socket)| match (protocol, socket) {
+                    (IpProtocol::Tcp, socket::Socket::Tcp(socket)) => {
+                        socket.local_endpoint().is_some_and(|e| e.port == port)
+                    }
+                    (IpProtocol::Udp, socket::Socket::Udp(socket)) => {
+                        socket.is_open() && socket.endpoint().port == port
+                    }
+                    _ => false,
+                })
+        })
+    }
+}
+
+impl fmt::Display for SocketHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", IFACES[self.iface].name(), self.inner)
+    }
 }
 
 impl InterfaceWrapper {
-    fn new(name: &'static str, dev: AxNetDevice, ether_addr: EthernetAddress) -> Self {
+    fn new(index: usize, name: String, dev: AxNetDevice, ether_addr: EthernetAddress) -> Self {
         let mut config = Config::new(HardwareAddress::Ethernet(ether_addr));
         config.random_seed = RANDOM_SEED;
 
-        let mut dev = DeviceWrapper::new(dev);
-        let iface = Mutex::new(Interface::new(config, &mut dev, Self::current_time()));
+        let mut dev = DeviceWrapper::new(index, dev);
+        let iface = Interface::new(config, &mut dev, Self::current_time());
+        Self::from_parts(
+            index,
+            name,
+            Some(ether_addr),
+            NetDevice::Ethernet(dev),
+            iface,
+        )
+    }
+
+    fn new_loopback(index: usize) -> Self {
+        let mut config = Config::new(HardwareAddress::Ip);
+        config.random_seed = RANDOM_SEED;
+
+        let mut dev = LoopbackDev::new(index);
+        let iface = Interface::new(config, &mut dev, Self::current_time());
+        Self::from_parts(index, "lo".into(), None, NetDevice::Loopback(dev), iface)
+    }
+
+    fn from_parts(
+        index: usize,
+        name: String,
+        ether_addr: Option<EthernetAddress>,
+        dev: NetDevice,
+        iface: Interface,
+    ) -> Self {
         Self {
+            index,
             name,
             ether_addr,
             dev: Mutex::new(dev),
-            iface,
+            iface: Mutex::new(iface),
+            slaac: Mutex::new(None),
+            #[cfg(feature = "dhcp")]
+            dhcp: Mutex::new(None),
         }
     }
 
@@ -149,13 +320,21 @@ impl InterfaceWrapper {
     }
 
     pub fn name(&self) -> &str {
-        self.name
+        &self.name
     }
 
-    pub fn ethernet_address(&self) -> EthernetAddress {
+    pub fn ethernet_address(&self) -> Option<EthernetAddress> {
         self.ether_addr
     }
 
+    pub fn is_loopback(&self) -> bool {
+        self.ether_addr.is_none()
+    }
+
+    pub fn has_ip_addr(&self, ip: IpAddress) -> bool {
+        self.iface.lock().has_ip_addr(ip)
+    }
+
     pub fn setup_ip_addr(&self, ip: IpAddress, prefix_len: u8) {
         let mut iface = self.iface.lock();
         iface.update_ip_addrs(|ip_addrs| {
@@ -167,21 +346,194 @@ impl InterfaceWrapper {
         let mut iface = self.iface.lock();
         match gateway {
             IpAddress::Ipv4(v4) => iface.routes_mut().add_default_ipv4_route(v4).unwrap(),
+            IpAddress::Ipv6(v6) => iface.routes_mut().add_default_ipv6_route(v6).unwrap(),
         };
     }
 
+    /// Configures the global IPv6 address and the default route by SLAAC.
+    pub fn start_slaac(&self, sockets: &Mutex<SocketSet>) {
+        if let Some(ether_addr) = self.ether_addr {
+            let slaac = Slaac::new(&mut sockets.lock(), ether_addr);
+            *self.slaac.lock() = Some(slaac);
+        }
+    }
+
+    /// Acquires the IPv4 address, the default gateway and DNS servers by
+    /// DHCP. The current configuration is kept until a lease is acquired.
+    #[cfg(feature = "dhcp")]
+    pub fn start_dhcp(&self, sockets: &Mutex<SocketSet>) {
+        let mut sockets = sockets.lock();
+        let mut dhcp = self.dhcp.lock();
+        if dhcp.is_none() {
+            *dhcp = Some(Dhcp::new(&mut sockets));
+        }
+    }
+
+    /// Stops the DHCP client, the acquired configuration is kept.
+    #[cfg(feature = "dhcp")]
+    pub fn stop_dhcp(&self, sockets: &Mutex<SocketSet>) {
+        let mut sockets = sockets.lock();
+        if let Some(dhcp) = self.dhcp.lock().take() {
+            dhcp.stop(&mut sockets);
+        }
+    }
+
+    /// Whether the DHCP client is running but no lease is acquired yet.
+    #[cfg(feature = "dhcp")]
+    pub fn dhcp_pending(&self) -> bool {
+        self.dhcp
+            .lock()
+            .as_ref()
+            .is_some_and(|dhcp| !dhcp.is_configured())
+    }
+
+    /// Returns the information of the interface.
+    pub fn info(&self) -> IfaceInfo {
+        IfaceInfo {
+            name: self.name.clone(),
+            ether_addr: self.ether_addr.map(|addr| addr.0),
+            addrs: self
+                .iface
+                .lock()
+                .ip_addrs()
+                .iter()
+                .map(|cidr| (into_core_ipaddr(cidr.address()), cidr.prefix_len()))
+                .collect(),
+            loopback: self.is_loopback(),
+        }
+    }
+
+    /// Returns the current configuration of the interface.
+    pub fn config(&self) -> IfaceConfig {
+        let mut iface = self.iface.lock();
+        let ipv4 = iface.ip_addrs().iter().find_map(|cidr| match cidr {
+            IpCidr::Ipv4(v4) => Some((v4.address().0.into(), v4.prefix_len())),
+            _ => None,
+        });
+        let mut gateway = None;
+        iface.routes_mut().update(|routes| {
+            gateway = routes.iter().find_map(|route| match route.via_router {
+                IpAddress::Ipv4(v4) if route.cidr.prefix_len() == 0 => Some(v4.0.into()),
+                _ => None,
+            });
+        });
+        IfaceConfig {
+            #[cfg(feature = "dhcp")]
+            dhcp: self.dhcp.lock().is_some(),
+            #[cfg(not(feature = "dhcp"))]
+            dhcp: false,
+            ipv4,
+            gateway,
+            dns_servers: DNS_SERVERS
+                .lock()
+                .iter()
+                .map(|&ip| into_core_ipaddr(ip))
+                .collect(),
+        }
+    }
+
+    /// Changes the configuration of the interface.
+    #[cfg_attr(not(feature = "dhcp"), allow(unused_variables))]
+    pub fn set_config(&self, sockets: &Mutex<SocketSet>, config: &IfaceConfig) -> AxResult {
+        if config.dhcp && cfg!(not(feature = "dhcp")) {
+            return ax_err!(Unsupported, "DHCP is not enabled");
+        }
+        if config.dhcp && self.is_loopback() {
+            return ax_err!(
+                Unsupported,
+                "DHCP is not available on the loopback interface"
+            );
+        }
+        if config.dns_servers.len() > MAX_DNS_SERVERS {
+            return ax_err!(InvalidInput, "too many DNS servers");
+        }
+        if config.ipv4.is_some_and(|(_, prefix_len)| prefix_len > 32) {
+            return ax_err!(InvalidInput, "invalid IPv4 prefix length");
+        }
+        let dns_servers: Vec<_> = config
+            .dns_servers
+            .iter()
+            .map(|&ip| from_core_ipaddr(ip))
+            .collect();
+
+        #[cfg(feature = "dhcp")]
+        if config.dhcp {
+            self.start_dhcp(sockets);
+        } else {
+            self.stop_dhcp(sockets);
+        }
+        if !config.dhcp {
+            let cidr = config
+                .ipv4
+                .map(|(ip, prefix_len)| Ipv4Cidr::new(Ipv4Address(ip.octets()), prefix_len));
+            let gateway = config.gateway.map(|gw| Ipv4Address(gw.octets()));
+            set_ipv4_config(&mut self.iface.lock(), cidr, gateway);
+        }
+        set_dns_servers(&dns_servers);
+        Ok(())
+    }
+
     pub fn poll(&self, sockets: &Mutex<SocketSet>) {
         let mut dev = self.dev.lock();
         let mut iface = self.iface.lock();
         let mut sockets = sockets.lock();
         let timestamp = Self::current_time();
-        iface.poll(timestamp, dev.deref_mut(), &mut sockets);
+        match &mut *dev {
+            NetDevice::Ethernet(dev) => iface.poll(timestamp, dev, &mut sockets),
+            NetDevice::Loopback(dev) => iface.poll(timestamp, dev, &mut sockets),
+        };
+        if let Some(slaac) = self.slaac.lock().as_mut() {
+            slaac.poll(timestamp, &mut iface, &mut sockets);
+        }
+        #[cfg(feature = "dhcp")]
+        if let Some(dhcp) = self.dhcp.lock().as_mut() {
+            dhcp.poll(&mut iface, &mut sockets);
+        }
+    }
+
+    /// Returns how long to wait before polling the interface again, or `None`
+    /// if it's not needed until new packets arrive.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    pub fn poll_delay(&self, sockets: &Mutex<SocketSet>) -> Option<core::time::Duration> {
+        let mut iface = self.iface.lock();
+        let sockets = sockets.lock();
+        let timestamp = Self::current_time();
+        let slaac_delay = self
+            .slaac
+            .lock()
+            .as_ref()
+            .and_then(Slaac::poll_at)
+            .map(|t| t.max(timestamp) - timestamp);
+        let delay = match (iface.poll_delay(timestamp, &sockets), slaac_delay) {
+            (Some(d1), Some(d2)) => Some(d1.min(d2)),
+            (d1, d2) => d1.or(d2),
+        };
+        delay.map(Into::into)
+    }
+
+    /// The IRQ number of the NIC, or `None` if it can only be polled.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    pub fn irq_num(&self) -> Option<usize> {
+        match &*self.dev.lock() {
+            NetDevice::Ethernet(dev) => dev.inner.borrow().irq_num(),
+            NetDevice::Loopback(_) => None,
+        }
+    }
+
+    /// Acknowledges the interrupt of the NIC.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    pub fn ack_interrupt(&self) -> bool {
+        match &*self.dev.lock() {
+            NetDevice::Ethernet(dev) => dev.inner.borrow_mut().ack_interrupt(),
+            NetDevice::Loopback(_) => false,
+        }
     }
 }
 
 impl DeviceWrapper {
-    fn new(inner: AxNetDevice) -> Self {
+    fn new(iface: usize, inner: AxNetDevice) -> Self {
         Self {
+            iface,
             inner: RefCell::new(inner),
         }
     }
@@ -210,7 +562,10 @@ impl Device for DeviceWrapper {
                 return None;
             }
         };
-        Some((AxNetRxToken(&self.inner, rx_buf), AxNetTxToken(&self.inner)))
+        Some((
+            AxNetRxToken(self.iface, &self.inner, rx_buf),
+            AxNetTxToken(&self.inner),
+        ))
     }
 
     fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
@@ -235,26 +590,26 @@ impl Device for DeviceWrapper {
     }
 }
 
-struct AxNetRxToken<'a>(&'a RefCell<AxNetDevice>, NetBufPtr);
+struct AxNetRxToken<'a>(usize, &'a RefCell<AxNetDevice>, NetBufPtr);
 struct AxNetTxToken<'a>(&'a RefCell<AxNetDevice>);
 
 impl<'a> RxToken for AxNetRxToken<'a> {
     fn preprocess(&self, sockets: &mut SocketSet<'_>) {
-        snoop_tcp_packet(self.1.packet(), sockets).ok();
+        snoop_tcp_packet(self.0, self.2.packet(), sockets).ok();
     }
 
     fn consume<R, F>(self, f: F) -> R
     where
         F: FnOnce(&mut [u8]) -> R,
     {
-        let mut rx_buf = self.1;
+        let mut rx_buf = self.2;
         trace!(
             "RECV {} bytes: {:02X?}",
             rx_buf.packet_len(),
             rx_buf.packet()
         );
         let result = f(rx_buf.packet_mut());
-        self.0.borrow_mut().recycle_rx_buffer(rx_buf).unwrap();
+        self.1.borrow_mut().recycle_rx_buffer(rx_buf).unwrap();
         result
     }
 }
@@ -273,25 +628,119 @@ impl<'a> TxToken for AxNetTxToken<'a> {
     }
 }
 
-fn snoop_tcp_packet(buf: &[u8], sockets: &mut SocketSet<'_>) -> Result<(), smoltcp::wire::Error> {
-    use smoltcp::wire::{EthernetFrame, IpProtocol, Ipv4Packet, TcpPacket};
+fn snoop_tcp_packet(
+    iface: usize,
+    buf: &[u8],
+    sockets: &mut SocketSet<'_>,
+) -> Result<(), smoltcp::wire::Error> {
+    use smoltcp::wire::{EthernetFrame, EthernetProtocol};
 
     let ether_frame = EthernetFrame::new_checked(buf)?;
-    let ipv4_packet = Ipv4Packet::new_checked(ether_frame.payload())?;
+    match ether_frame.ethertype() {
+        EthernetProtocol::Ipv4 | EthernetProtocol::Ipv6 => {
+            snoop_ip_packet(iface, ether_frame.payload(), sockets)
+        }
+        _ => Ok(()),
+    }
+}
 
-    if ipv4_packet.next_header() == IpProtocol::Tcp {
-        let tcp_packet = TcpPacket::new_checked(ipv4_packet.payload())?;
-        let src_addr = (ipv4_packet.src_addr(), tcp_packet.src_port()).into();
-        let dst_addr = (ipv4_packet.dst_addr(), tcp_packet.dst_port()).into();
+fn snoop_ip_packet(
+    iface: usize,
+    buf: &[u8],
+    sockets: &mut SocketSet<'_>,
+) -> Result<(), smoltcp::wire::Error> {
+    use smoltcp::wire::{IpProtocol, IpVersion, Ipv4Packet, Ipv6Packet, TcpPacket};
+
+    let (src_ip, dst_ip, protocol, payload): (IpAddress, IpAddress, _, _) =
+        match IpVersion::of_packet(buf)? {
+            IpVersion::Ipv4 => {
+                let packet = Ipv4Packet::new_checked(buf)?;
+                let (src, dst) = (packet.src_addr().into(), packet.dst_addr().into());
+                (src, dst, packet.next_header(), packet.payload())
+            }
+            IpVersion::Ipv6 => {
+                let packet = Ipv6Packet::new_checked(buf)?;
+                let (src, dst) = (packet.src_addr().into(), packet.dst_addr().into());
+                (src, dst, packet.next_header(), packet.payload())
+            }
+        };
+
+    if protocol == IpProtocol::Tcp {
+        let tcp_packet = TcpPacket::new_checked(payload)?;
+        let src_addr = (src_ip, tcp_packet.src_port()).into();
+        let dst_addr = (dst_ip, tcp_packet.dst_port()).into();
         let is_first = tcp_packet.syn() && !tcp_packet.ack();
         if is_first {
             // create a socket for the first incoming TCP packet, as the later accept() returns.
-            LISTEN_TABLE.incoming_tcp_packet(src_addr, dst_addr, sockets);
+            LISTEN_TABLE.incoming_tcp_packet(iface, src_addr, dst_addr, sockets);
         }
     }
     Ok(())
 }
 
+/// Replaces the IPv4 address and the default IPv4 route of the interface.
+fn set_ipv4_config(iface: &mut Interface, cidr: Option<Ipv4Cidr>, gateway: Option<Ipv4Address>) {
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.retain(|cidr| !matches!(cidr, IpCidr::Ipv4(_)));
+        if let Some(cidr) = cidr {
+            ip_addrs.push(IpCidr::Ipv4(cidr)).unwrap();
+        }
+    });
+    match gateway {
+        Some(gateway) => {
+            iface.routes_mut().add_default_ipv4_route(gateway).unwrap();
+        }
+        None => {
+            iface.routes_mut().remove_default_ipv4_route();
+        }
+    }
+}
+
+/// Replaces the DNS servers used by [`dns_query`].
+fn set_dns_servers(servers: &[IpAddress]) {
+    let mut dns_servers = DNS_SERVERS.lock();
+    dns_servers.clear();
+    dns_servers.extend(servers.iter().take(MAX_DNS_SERVERS));
+}
+
+/// Returns the interface with the given name.
+fn find_iface(name: &str) -> AxResult<&'static InterfaceWrapper> {
+    IFACES
+        .iter()
+        .find(|iface| iface.name() == name)
+        .ok_or_else(|| ax_err_type!(NotFound, "no such network interface"))
+}
+
+/// Returns the indices of interfaces that own the address `ip`, or all
+/// interfaces if `ip` is `None`.
+fn local_ifaces(ip: Option<IpAddress>) -> Vec<usize> {
+    IFACES
+        .iter()
+        .filter(|iface| ip.map_or(true, |ip| iface.has_ip_addr(ip)))
+        .map(|iface| iface.index)
+        .collect()
+}
+
+/// Returns the information of all network interfaces.
+pub fn iface_list() -> Vec<IfaceInfo> {
+    IFACES.iter().map(InterfaceWrapper::info).collect()
+}
+
+/// Returns the current configuration of the network interface.
+pub fn iface_config(name: &str) -> AxResult<IfaceConfig> {
+    Ok(find_iface(name)?.config())
+}
+
+/// Changes the configuration of the network interface.
+///
+/// If [`IfaceConfig::dhcp`] is set, the IPv4 address and gateway are acquired
+/// by DHCP, and the given ones are ignored. DNS servers are shared by all
+/// interfaces.
+pub fn set_iface_config(name: &str, config: &IfaceConfig) -> AxResult {
+    let iface = find_iface(name)?;
+    iface.set_config(SOCKET_SET.sockets(iface.index), config)
+}
+
 /// Poll the network stack.
 ///
 /// It may receive packets from the NIC and process them, and transmit queued
@@ -300,31 +749,120 @@ pub fn poll_interfaces() {
     SOCKET_SET.poll_interfaces();
 }
 
+/// Wakes up the poller task to send packets queued by sockets.
+///
+/// Without the poller task, interfaces are polled by blocked sockets or by
+/// [`poll_interfaces`] instead.
+fn notify_poller() {
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    poller::notify_poller();
+}
+
 /// Benchmark raw socket transmit bandwidth.
 pub fn bench_transmit() {
-    ETH0.dev.lock().bench_transmit_bandwidth();
+    let eth0 = find_iface("eth0").expect("no NIC device");
+    if let NetDevice::Ethernet(dev) = &mut *eth0.dev.lock() {
+        dev.bench_transmit_bandwidth();
+    }
 }
 
 /// Benchmark raw socket receive bandwidth.
 pub fn bench_receive() {
-    ETH0.dev.lock().bench_receive_bandwidth();
+    let eth0 = find_iface("eth0").expect("no NIC device");
+    if let NetDevice::Ethernet(dev) = &mut *eth0.dev.lock() {
+        dev.bench_receive_bandwidth();
+    }
 }
 
-pub(crate) fn init(net_dev: AxNetDevice) {
-    let ether_addr = EthernetAddress(net_dev.mac_address().0);
-    let eth0 = InterfaceWrapper::new("eth0", net_dev, ether_addr);
-
-    let ip = IP.parse().expect("invalid IP address");
-    let gateway = GATEWAY.parse().expect("invalid gateway IP address");
-    eth0.setup_ip_addr(ip, IP_PREFIX);
-    eth0.setup_gateway(gateway);
+pub(crate) fn init(net_devs: Vec<AxNetDevice>) {
+    let mut ifaces = Vec::with_capacity(net_devs.len() + 1);
+    let lo = InterfaceWrapper::new_loopback(0);
+    lo.setup_ip_addr(IpAddress::v4(127, 0, 0, 1), 8);
+    lo.setup_ip_addr(IpAddress::Ipv6(Ipv6Address::LOOPBACK), 128);
+    ifaces.push(lo);
+
+    for (i, net_dev) in net_devs.into_iter().enumerate() {
+        let ether_addr = EthernetAddress(net_dev.mac_address().0);
+        let eth = InterfaceWrapper::new(ifaces.len(), format!("eth{}", i), net_dev, ether_addr);
+        let link_local = IpAddress::Ipv6(slaac::link_local_addr(ether_addr));
+        eth.setup_ip_addr(link_local, IP6_PREFIX);
+        if i == 0 {
+            // the static configuration only applies to `eth0`
+            eth.setup_ip_addr(IP.parse().expect("invalid IP address"), IP_PREFIX);
+            eth.setup_gateway(GATEWAY.parse().expect("invalid gateway IP address"));
+            if !IP6.is_empty() {
+                eth.setup_ip_addr(IP6.parse().expect("invalid IPv6 address"), IP6_PREFIX);
+                if !GATEWAY6.is_empty() {
+                    eth.setup_gateway(GATEWAY6.parse().expect("invalid IPv6 gateway address"));
+                }
+            }
+        }
+        ifaces.push(eth);
+    }
+    set_dns_servers(&[DNS_SEVER.parse().expect("invalid DNS server address")]);
 
-    ETH0.init_by(eth0);
-    SOCKET_SET.init_by(SocketSetWrapper::new());
+    SOCKET_SET.init_by(SocketSetWrapper::new(ifaces.len()));
+    IFACES.init_by(ifaces);
     LISTEN_TABLE.init_by(ListenTable::new());
 
-    info!("created net interface {:?}:", ETH0.name());
-    info!("  ether:    {}", ETH0.ethernet_address());
-    info!("  ip:       {}/{}", ip, IP_PREFIX);
-    info!("  gateway:  {}", gateway);
+    for iface in IFACES.iter() {
+        let sockets = SOCKET_SET.sockets(iface.index);
+        info!("created net interface {:?}:", iface.name());
+        if let Some(ether_addr) = iface.ethernet_address() {
+            info!("  ether:    {}", ether_addr);
+        }
+        let mut has_ip6 = false;
+        for cidr in iface.iface.lock().ip_addrs() {
+            match cidr {
+                IpCidr::Ipv4(_) => info!("  ip:       {}", cidr),
+                IpCidr::Ipv6(v6) => {
+                    info!("  ip6:      {}", cidr);
+                    has_ip6 |= !v6.address().is_link_local();
+                }
+            }
+        }
+        iface.iface.lock().routes_mut().update(|routes| {
+            for route in routes.iter() {
+                match route.via_router {
+                    IpAddress::Ipv4(gw) => info!("  gateway:  {}", gw),
+                    IpAddress::Ipv6(gw) => info!("  gateway6: {}", gw),
+                }
+            }
+        });
+        if iface.is_loopback() {
+            continue;
+        }
+        if !has_ip6 {
+            info!("  ip6:      SLAAC");
+            iface.start_slaac(sockets);
+        }
+        #[cfg(feature = "dhcp")]
+        {
+            // the static address is used until a lease is acquired
+            info!("  ip:       DHCP");
+            iface.start_dhcp(sockets);
+        }
+    }
+
+    #[cfg(feature = "dhcp")]
+    {
+        let deadline = axhal::time::current_time() + DHCP_TIMEOUT;
+        while IFACES.iter().any(InterfaceWrapper::dhcp_pending)
+            && axhal::time::current_time() < deadline
+        {
+            SOCKET_SET.poll_interfaces();
+            axtask::yield_now();
+        }
+        for iface in IFACES.iter().filter(|iface| iface.dhcp_pending()) {
+            warn!(
+                "DHCP: no lease acquired on {} in {:?}, keep trying",
+                iface.name(),
+                DHCP_TIMEOUT
+            );
+        }
+    }
+    SOCKET_SET.poll_interfaces();
+
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    poller::init();
 }
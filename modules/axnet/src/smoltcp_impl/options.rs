@@ -0,0 +1,166 @@
+//! Socket options shared by TCP and UDP sockets.
+
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axerrno::{ax_err, AxResult};
+use smoltcp::socket::tcp;
+
+use super::{MAX_BUF_LEN, MIN_BUF_LEN};
+
+/// The interval of TCP keep-alive packets, the same as the default
+/// `TCP_KEEPINTVL` of Linux.
+const TCP_KEEP_ALIVE_INTERVAL: smoltcp::time::Duration = smoltcp::time::Duration::from_secs(75);
+
+/// Means no timeout or no linger in the atomic fields.
+const NONE_NANOS: u64 = u64::MAX;
+
+fn from_nanos(nanos: u64) -> Option<Duration> {
+    (nanos != NONE_NANOS).then(|| Duration::from_nanos(nanos))
+}
+
+fn into_nanos(dur: Option<Duration>) -> u64 {
+    dur.map_or(NONE_NANOS, |d| {
+        d.as_nanos().min(NONE_NANOS as u128 - 1) as u64
+    })
+}
+
+/// Options of a socket.
+///
+/// The buffer sizes take effect when the underlying smoltcp socket is
+/// created, i.e., when a TCP socket connects or a UDP socket is bound. The TCP
+/// options are ignored by UDP sockets.
+pub struct SocketOptions {
+    recv_buf_size: AtomicUsize,
+    send_buf_size: AtomicUsize,
+    reuse_addr: AtomicBool,
+    read_timeout: AtomicU64,
+    write_timeout: AtomicU64,
+    nodelay: AtomicBool,
+    keepalive: AtomicBool,
+    linger: AtomicU64,
+}
+
+impl SocketOptions {
+    pub const fn new(recv_buf_size: usize, send_buf_size: usize) -> Self {
+        Self {
+            recv_buf_size: AtomicUsize::new(recv_buf_size),
+            send_buf_size: AtomicUsize::new(send_buf_size),
+            reuse_addr: AtomicBool::new(false),
+            read_timeout: AtomicU64::new(NONE_NANOS),
+            write_timeout: AtomicU64::new(NONE_NANOS),
+            nodelay: AtomicBool::new(false),
+            keepalive: AtomicBool::new(false),
+            linger: AtomicU64::new(NONE_NANOS),
+        }
+    }
+
+    /// Returns a copy of all options, e.g., for a socket accepted by a
+    /// listening socket.
+    pub fn inherit(&self) -> Self {
+        let opts = Self::new(self.recv_buf_size(), self.send_buf_size());
+        opts.set_reuse_addr(self.reuse_addr());
+        opts.read_timeout
+            .store(self.read_timeout.load(Ordering::Relaxed), Ordering::Relaxed);
+        opts.write_timeout.store(
+            self.write_timeout.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        opts.set_nodelay(self.nodelay());
+        opts.set_keepalive(self.keepalive());
+        opts.set_linger(self.linger());
+        opts
+    }
+
+    pub fn recv_buf_size(&self) -> usize {
+        self.recv_buf_size.load(Ordering::Relaxed)
+    }
+
+    /// Sets the receive buffer size, which is clamped to the valid range.
+    pub fn set_recv_buf_size(&self, size: usize) {
+        let size = size.clamp(MIN_BUF_LEN, MAX_BUF_LEN);
+        self.recv_buf_size.store(size, Ordering::Relaxed)
+    }
+
+    pub fn send_buf_size(&self) -> usize {
+        self.send_buf_size.load(Ordering::Relaxed)
+    }
+
+    /// Sets the send buffer size, which is clamped to the valid range.
+    pub fn set_send_buf_size(&self, size: usize) {
+        let size = size.clamp(MIN_BUF_LEN, MAX_BUF_LEN);
+        self.send_buf_size.store(size, Ordering::Relaxed)
+    }
+
+    pub fn reuse_addr(&self) -> bool {
+        self.reuse_addr.load(Ordering::Relaxed)
+    }
+
+    pub fn set_reuse_addr(&self, reuse_addr: bool) {
+        self.reuse_addr.store(reuse_addr, Ordering::Relaxed)
+    }
+
+    pub fn read_timeout(&self) -> Option<Duration> {
+        from_nanos(self.read_timeout.load(Ordering::Relaxed))
+    }
+
+    /// Sets the read timeout, [`None`] means blocking forever.
+    ///
+    /// Returns [`Err(InvalidInput)`](axerrno::AxError::InvalidInput) if the
+    /// timeout is zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        if timeout == Some(Duration::ZERO) {
+            return ax_err!(InvalidInput, "zero timeout");
+        }
+        self.read_timeout
+            .store(into_nanos(timeout), Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn write_timeout(&self) -> Option<Duration> {
+        from_nanos(self.write_timeout.load(Ordering::Relaxed))
+    }
+
+    /// Sets the write timeout, [`None`] means blocking forever.
+    ///
+    /// Returns [`Err(InvalidInput)`](axerrno::AxError::InvalidInput) if the
+    /// timeout is zero.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        if timeout == Some(Duration::ZERO) {
+            return ax_err!(InvalidInput, "zero timeout");
+        }
+        self.write_timeout
+            .store(into_nanos(timeout), Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn nodelay(&self) -> bool {
+        self.nodelay.load(Ordering::Relaxed)
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) {
+        self.nodelay.store(nodelay, Ordering::Relaxed)
+    }
+
+    pub fn keepalive(&self) -> bool {
+        self.keepalive.load(Ordering::Relaxed)
+    }
+
+    pub fn set_keepalive(&self, keepalive: bool) {
+        self.keepalive.store(keepalive, Ordering::Relaxed)
+    }
+
+    pub fn linger(&self) -> Option<Duration> {
+        from_nanos(self.linger.load(Ordering::Relaxed))
+    }
+
+    pub fn set_linger(&self, linger: Option<Duration>) {
+        self.linger.store(into_nanos(linger), Ordering::Relaxed)
+    }
+
+    /// Applies `TCP_NODELAY` and `SO_KEEPALIVE` to a smoltcp TCP socket.
+    pub fn apply_tcp(&self, socket: &mut tcp::Socket) {
+        socket.set_nagle_enabled(!self.nodelay());
+        socket.set_keep_alive(self.keepalive().then_some(TCP_KEEP_ALIVE_INTERVAL));
+    }
+}
@@ -0,0 +1,94 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+use axtask::WaitQueue;
+use lazy_init::LazyInit;
+
+use super::{IFACES, SOCKET_SET};
+
+/// The polling interval if some NIC doesn't raise interrupts.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// IRQ numbers of all NICs that raise interrupts.
+static NIC_IRQS: LazyInit<Vec<usize>> = LazyInit::new();
+/// Whether all NICs raise interrupts, so they are not polled periodically.
+static ALL_IRQ: AtomicBool = AtomicBool::new(false);
+
+static POLLER_WQ: WaitQueue = WaitQueue::new();
+static POLL_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Wakes up the poller task to poll all interfaces, e.g., when packets are
+/// queued by sockets, or NIC interrupts are raised.
+pub fn notify_poller() {
+    POLL_PENDING.store(true, Ordering::Release);
+    POLLER_WQ.notify_one(false);
+}
+
+/// The handler of all NIC interrupts.
+///
+/// The interrupts are disabled until they are acknowledged by the poller task,
+/// as the NIC can't be accessed in the interrupt context.
+fn nic_irq_handler() {
+    for &irq_num in NIC_IRQS.iter() {
+        axhal::irq::set_enable(irq_num, false);
+    }
+    notify_poller();
+}
+
+/// Acknowledges NIC interrupts and enables them again.
+fn ack_nic_interrupts() {
+    for iface in IFACES.iter() {
+        iface.ack_interrupt();
+    }
+    for &irq_num in NIC_IRQS.iter() {
+        axhal::irq::set_enable(irq_num, true);
+    }
+}
+
+/// The poller task: polls all interfaces when NIC interrupts are raised, and
+/// when the timers of smoltcp (e.g., TCP retransmission) expire.
+fn poller_loop() {
+    loop {
+        POLL_PENDING.store(false, Ordering::Release);
+        ack_nic_interrupts();
+        SOCKET_SET.poll_interfaces();
+
+        let mut delay = SOCKET_SET.poll_delay();
+        if !ALL_IRQ.load(Ordering::Acquire) {
+            delay = Some(delay.map_or(POLL_INTERVAL, |d| d.min(POLL_INTERVAL)));
+        }
+        let pending = || POLL_PENDING.load(Ordering::Acquire);
+        match delay {
+            Some(delay) if delay.is_zero() => axtask::yield_now(),
+            Some(delay) => {
+                POLLER_WQ.wait_timeout_until(delay, pending);
+            }
+            None => POLLER_WQ.wait_until(pending),
+        }
+    }
+}
+
+/// Registers NIC interrupt handlers and starts the poller task.
+pub fn init() {
+    let mut irqs = Vec::new();
+    let mut all_irq = true;
+    for iface in IFACES.iter().filter(|iface| !iface.is_loopback()) {
+        match iface.irq_num() {
+            Some(irq_num) if !irqs.contains(&irq_num) => irqs.push(irq_num),
+            Some(_) => {}
+            None => all_irq = false,
+        }
+    }
+    NIC_IRQS.init_by(irqs);
+
+    for &irq_num in NIC_IRQS.iter() {
+        info!("  use NIC IRQ {}", irq_num);
+        all_irq &= axhal::irq::register_handler(irq_num, nic_irq_handler);
+    }
+    if !all_irq {
+        info!("  poll NICs every {:?}", POLL_INTERVAL);
+    }
+    ALL_IRQ.store(all_irq, Ordering::Release);
+    axtask::spawn(poller_loop);
+}
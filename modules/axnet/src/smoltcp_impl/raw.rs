@@ -0,0 +1,350 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::net::IpAddr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+use axerrno::{ax_err, ax_err_type, AxError, AxResult};
+use axio::PollState;
+
+use smoltcp::phy::ChecksumCapabilities;
+use smoltcp::socket::raw::{self, SendError};
+use smoltcp::wire::{
+    Icmpv6Packet, IpAddress, IpProtocol, IpVersion, Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr,
+};
+
+use super::addr::{from_core_ipaddr, into_core_ipaddr, is_unspecified};
+use super::options::SocketOptions;
+use super::{
+    local_ifaces, notify_poller, route, SocketHandle, SocketSetWrapper, SocketWaiter, IFACES,
+    SOCKET_SET,
+};
+use super::{RAW_RX_BUF_LEN, RAW_TX_BUF_LEN};
+
+/// The hop limit of packets sent through raw sockets.
+const RAW_HOP_LIMIT: u8 = 64;
+
+/// A raw IP socket that provides POSIX-like APIs.
+///
+/// It receives a copy of every IP packet of its IP version and protocol on
+/// all interfaces. Outgoing payloads are wrapped in an IP header whose
+/// source address is taken from the interface selected by the routing table.
+pub struct RawSocket {
+    /// One socket for each interface.
+    handles: Vec<SocketHandle>,
+    ip_version: IpVersion,
+    protocol: IpProtocol,
+    nonblock: AtomicBool,
+    opts: SocketOptions,
+    waiter: Arc<SocketWaiter>,
+}
+
+impl RawSocket {
+    /// Creates a new raw socket for IPv4 (or IPv6 if `ipv6` is true) packets
+    /// of the given IP protocol number.
+    pub fn new(ipv6: bool, protocol: u8) -> Self {
+        let ip_version = if ipv6 {
+            IpVersion::Ipv6
+        } else {
+            IpVersion::Ipv4
+        };
+        let protocol = IpProtocol::from(protocol);
+        let opts = SocketOptions::new(RAW_RX_BUF_LEN, RAW_TX_BUF_LEN);
+        let handles = local_ifaces(None)
+            .into_iter()
+            .map(|iface| {
+                let socket = SocketSetWrapper::new_raw_socket(
+                    ip_version,
+                    protocol,
+                    opts.recv_buf_size(),
+                    opts.send_buf_size(),
+                );
+                let handle = SOCKET_SET.add(iface, socket);
+                debug!(
+                    "raw socket {}: created for {} {}",
+                    handle, ip_version, protocol
+                );
+                handle
+            })
+            .collect();
+        Self {
+            handles,
+            ip_version,
+            protocol,
+            nonblock: AtomicBool::new(false),
+            opts,
+            waiter: SocketWaiter::new(),
+        }
+    }
+
+    /// Returns whether this socket is for IPv6 packets.
+    pub fn is_ipv6(&self) -> bool {
+        self.ip_version == IpVersion::Ipv6
+    }
+
+    /// Returns the IP protocol number of this socket.
+    pub fn protocol(&self) -> u8 {
+        self.protocol.into()
+    }
+
+    /// Returns whether this socket is in nonblocking mode.
+    #[inline]
+    pub fn is_nonblocking(&self) -> bool {
+        self.nonblock.load(Ordering::Acquire)
+    }
+
+    /// Moves this raw socket into or out of nonblocking mode.
+    ///
+    /// This will result in `recv_from` and `send_to` operations becoming
+    /// nonblocking, i.e., immediately returning from their calls. If the IO
+    /// operation could not be completed and needs to be retried, an error with
+    /// kind [`Err(WouldBlock)`](AxError::WouldBlock) is returned.
+    #[inline]
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.nonblock.store(nonblocking, Ordering::Release);
+    }
+
+    /// Returns the timeout of receiving operations (`SO_RCVTIMEO`).
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.opts.read_timeout()
+    }
+
+    /// Sets the timeout of receiving operations (`SO_RCVTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_read_timeout(timeout)
+    }
+
+    /// Returns the timeout of sending operations (`SO_SNDTIMEO`).
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.opts.write_timeout()
+    }
+
+    /// Sets the timeout of sending operations (`SO_SNDTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_write_timeout(timeout)
+    }
+
+    /// Sends an IP packet with the given payload to the given address. On
+    /// success, returns the number of payload bytes written.
+    ///
+    /// The IP header is built by the stack. For IPv4, the payload is sent as
+    /// is, so its checksum must be filled by the caller. For ICMPv6, the
+    /// checksum is calculated by the stack.
+    pub fn send_to(&self, buf: &[u8], remote_addr: IpAddr) -> AxResult<usize> {
+        let dst_addr = from_core_ipaddr(remote_addr);
+        if is_unspecified(dst_addr) || dst_addr.version() != self.ip_version {
+            return ax_err!(InvalidInput, "socket send_to() failed: invalid address");
+        }
+        let iface = route::lookup(dst_addr)
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "socket send_to() failed: no route"))?;
+        let handle = self
+            .handles
+            .iter()
+            .find(|handle| handle.iface == iface)
+            .copied()
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "socket send_to() failed: no route"))?;
+        let src_addr = source_addr(iface, dst_addr).ok_or_else(|| {
+            ax_err_type!(
+                ConnectionRefused,
+                "socket send_to() failed: no source address"
+            )
+        })?;
+
+        self.block_on(self.write_timeout(), || {
+            SOCKET_SET.with_socket_mut::<raw::Socket, _, _>(handle, |socket| {
+                self.emit_packet(socket, src_addr, dst_addr, buf)
+                    .map_err(|e| match e {
+                        SendError::BufferFull => AxError::WouldBlock,
+                    })?;
+                Ok(buf.len())
+            })
+        })
+    }
+
+    /// Receives a single IP packet on the socket. On success, returns the
+    /// number of bytes read and the origin.
+    ///
+    /// The packet includes its IP header, and is truncated if `buf` is too
+    /// small to hold it.
+    pub fn recv_from(&self, buf: &mut [u8]) -> AxResult<(usize, IpAddr)> {
+        self.block_on(self.read_timeout(), || {
+            for &handle in self.handles.iter() {
+                let res = SOCKET_SET.with_socket_mut::<raw::Socket, _, _>(handle, |socket| {
+                    socket.can_recv().then(|| match socket.recv() {
+                        Ok(packet) => {
+                            let src_addr = packet_source(packet).ok_or_else(|| {
+                                ax_err_type!(BadState, "socket recv_from() failed")
+                            })?;
+                            let len = packet.len().min(buf.len());
+                            buf[..len].copy_from_slice(&packet[..len]);
+                            Ok((len, into_core_ipaddr(src_addr)))
+                        }
+                        Err(_) => ax_err!(BadState, "socket recv_from() failed"),
+                    })
+                });
+                if let Some(res) = res {
+                    return res;
+                }
+            }
+            // no more data
+            Err(AxError::WouldBlock)
+        })
+    }
+
+    /// Whether the socket is readable or writable.
+    pub fn poll(&self) -> AxResult<PollState> {
+        self.register_waker();
+        let mut state = PollState {
+            readable: false,
+            writable: false,
+            hangup: false,
+            error: false,
+        };
+        for &handle in self.handles.iter() {
+            SOCKET_SET.with_socket::<raw::Socket, _, _>(handle, |socket| {
+                state.readable |= socket.can_recv();
+                state.writable |= socket.can_send();
+            });
+        }
+        Ok(state)
+    }
+}
+
+/// Private methods
+impl RawSocket {
+    fn emit_packet(
+        &self,
+        socket: &mut raw::Socket,
+        src_addr: IpAddress,
+        dst_addr: IpAddress,
+        payload: &[u8],
+    ) -> Result<(), SendError> {
+        match (src_addr, dst_addr) {
+            (IpAddress::Ipv4(src_addr), IpAddress::Ipv4(dst_addr)) => {
+                let repr = Ipv4Repr {
+                    src_addr,
+                    dst_addr,
+                    next_header: self.protocol,
+                    payload_len: payload.len(),
+                    hop_limit: RAW_HOP_LIMIT,
+                };
+                let packet = socket.send(repr.buffer_len() + payload.len())?;
+                let mut packet = Ipv4Packet::new_unchecked(packet);
+                repr.emit(&mut packet, &ChecksumCapabilities::default());
+                packet.payload_mut().copy_from_slice(payload);
+            }
+            (IpAddress::Ipv6(src_addr), IpAddress::Ipv6(dst_addr)) => {
+                let repr = Ipv6Repr {
+                    src_addr,
+                    dst_addr,
+                    next_header: self.protocol,
+                    payload_len: payload.len(),
+                    hop_limit: RAW_HOP_LIMIT,
+                };
+                let packet = socket.send(repr.buffer_len() + payload.len())?;
+                let mut packet = Ipv6Packet::new_unchecked(packet);
+                repr.emit(&mut packet);
+                let payload_mut = packet.payload_mut();
+                payload_mut.copy_from_slice(payload);
+                if self.protocol == IpProtocol::Icmpv6 && payload.len() >= 4 {
+                    Icmpv6Packet::new_unchecked(payload_mut)
+                        .fill_checksum(&src_addr.into(), &dst_addr.into());
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Registers the waker of blocked tasks to the underlying sockets.
+    fn register_waker(&self) {
+        let waker = self.waiter.waker();
+        for &handle in self.handles.iter() {
+            SOCKET_SET.with_socket_mut::<raw::Socket, _, _>(handle, |socket| {
+                socket.register_recv_waker(&waker);
+                socket.register_send_waker(&waker);
+            });
+        }
+    }
+
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
+    where
+        F: FnMut() -> AxResult<T>,
+    {
+        if self.is_nonblocking() {
+            f().map(|t| {
+                notify_poller();
+                t
+            })
+        } else {
+            let deadline = timeout.map(|t| axhal::time::current_time() + t);
+            loop {
+                SOCKET_SET.poll_interfaces();
+                let events = self.waiter.events();
+                self.register_waker();
+                match f() {
+                    Ok(t) => {
+                        notify_poller();
+                        return Ok(t);
+                    }
+                    Err(AxError::WouldBlock) => {
+                        if deadline.is_some_and(|d| axhal::time::current_time() >= d) {
+                            return Err(AxError::WouldBlock);
+                        }
+                        self.waiter.wait(events, deadline)
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RawSocket {
+    fn drop(&mut self) {
+        for &handle in self.handles.iter() {
+            SOCKET_SET.remove(handle);
+        }
+    }
+}
+
+/// Selects the source address of packets to `dst` sent through the interface
+/// at index `iface`.
+///
+/// An address in the same network as `dst` is preferred. Otherwise, IPv6
+/// link-local destinations get a link-local address, and other destinations
+/// get a non-link-local one if any.
+fn source_addr(iface: usize, dst: IpAddress) -> Option<IpAddress> {
+    let iface = IFACES[iface].iface.lock();
+    let addrs = iface
+        .ip_addrs()
+        .iter()
+        .filter(|cidr| cidr.address().version() == dst.version());
+    if let Some(cidr) = addrs.clone().find(|cidr| cidr.contains_addr(&dst)) {
+        return Some(cidr.address());
+    }
+    let is_link_local =
+        |addr: IpAddress| matches!(addr, IpAddress::Ipv6(addr) if addr.is_link_local());
+    addrs
+        .clone()
+        .find(|cidr| is_link_local(cidr.address()) == is_link_local(dst))
+        .or_else(|| addrs.clone().next())
+        .map(|cidr| cidr.address())
+}
+
+/// Returns the source address of an IP packet.
+fn packet_source(packet: &[u8]) -> Option<IpAddress> {
+    match IpVersion::of_packet(packet).ok()? {
+        IpVersion::Ipv4 => Some(Ipv4Packet::new_checked(packet).ok()?.src_addr().into()),
+        IpVersion::Ipv6 => Some(Ipv6Packet::new_checked(packet).ok()?.src_addr().into()),
+    }
+}
@@ -0,0 +1,62 @@
+//! The routing table that selects the egress interface of each destination.
+//!
+//! The table is built from the interfaces: each address adds a route to its
+//! directly connected network, and the routes of each interface (e.g., the
+//! default route via the gateway) are added as well. A destination is routed
+//! by the longest prefix match, and the first interface wins if several routes
+//! have the same prefix length.
+
+use alloc::vec::Vec;
+
+use smoltcp::wire::{IpAddress, IpCidr};
+
+use super::IFACES;
+
+/// A route entry.
+#[derive(Debug, Clone, Copy)]
+pub struct Route {
+    /// The destination network. Host bits are not cleared for directly
+    /// connected networks.
+    pub cidr: IpCidr,
+    /// The index of the egress interface.
+    pub iface: usize,
+}
+
+/// Returns all routes of all interfaces.
+pub fn route_table() -> Vec<Route> {
+    let mut table = Vec::new();
+    for iface in IFACES.iter() {
+        let mut inner = iface.iface.lock();
+        for cidr in inner.ip_addrs() {
+            table.push(Route {
+                cidr: *cidr,
+                iface: iface.index,
+            });
+        }
+        inner.routes_mut().update(|routes| {
+            for route in routes.iter() {
+                table.push(Route {
+                    cidr: route.cidr,
+                    iface: iface.index,
+                });
+            }
+        });
+    }
+    table
+}
+
+/// Returns the index of the egress interface to `dst`, or `None` if it's
+/// unreachable.
+pub fn lookup(dst: IpAddress) -> Option<usize> {
+    let mut best: Option<Route> = None;
+    for route in route_table() {
+        if route.cidr.contains_addr(&dst)
+            && best.map_or(true, |best| {
+                route.cidr.prefix_len() > best.cidr.prefix_len()
+            })
+        {
+            best = Some(route);
+        }
+    }
+    best.map(|route| route.iface)
+}
@@ -0,0 +1,204 @@
+//! IPv6 stateless address autoconfiguration ([RFC 4862]).
+//!
+//! Router Solicitations are sent to all routers through a raw ICMPv6 socket,
+//! and the first Router Advertisement with an autonomous /64 prefix is used to
+//! configure a global address (the prefix plus the EUI-64 interface ID) and
+//! the default route.
+//!
+//! [RFC 4862]: https://datatracker.ietf.org/doc/html/rfc4862
+
+use alloc::vec;
+
+use smoltcp::iface::{Interface, SocketHandle, SocketSet};
+use smoltcp::phy::ChecksumCapabilities;
+use smoltcp::socket::raw;
+use smoltcp::time::{Duration, Instant};
+use smoltcp::wire::{
+    EthernetAddress, Icmpv6Packet, Icmpv6Repr, IpAddress, IpCidr, IpProtocol, IpVersion,
+    Ipv6Address, Ipv6Packet, Ipv6Repr, NdiscPrefixInfoFlags, NdiscRepr, RawHardwareAddress,
+};
+
+const MAX_RTR_SOLICITATIONS: usize = 3;
+const RTR_SOLICITATION_INTERVAL: Duration = Duration::from_secs(4);
+const SLAAC_PREFIX_LEN: u8 = 64;
+
+const RAW_RX_BUF_LEN: usize = 4 * 1024;
+const RAW_TX_BUF_LEN: usize = 1024;
+
+/// Returns the address with the given /64 prefix and the EUI-64 interface ID
+/// derived from the MAC address.
+pub fn eui64_addr(prefix: Ipv6Address, ether_addr: EthernetAddress) -> Ipv6Address {
+    let mac = ether_addr.0;
+    let mut bytes = prefix.0;
+    bytes[8..].copy_from_slice(&[
+        mac[0] ^ 0x02,
+        mac[1],
+        mac[2],
+        0xff,
+        0xfe,
+        mac[3],
+        mac[4],
+        mac[5],
+    ]);
+    Ipv6Address(bytes)
+}
+
+/// Returns the link-local address (`fe80::/64`) of the interface.
+pub fn link_local_addr(ether_addr: EthernetAddress) -> Ipv6Address {
+    eui64_addr(Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), ether_addr)
+}
+
+/// The SLAAC state of an interface.
+pub struct Slaac {
+    handle: SocketHandle,
+    ether_addr: EthernetAddress,
+    solicitations: usize,
+    next_solicit: Instant,
+    configured: bool,
+}
+
+impl Slaac {
+    /// Starts autoconfiguration by adding a raw ICMPv6 socket to `sockets`.
+    pub fn new(sockets: &mut SocketSet<'_>, ether_addr: EthernetAddress) -> Self {
+        let rx_buffer =
+            raw::PacketBuffer::new(vec![raw::PacketMetadata::EMPTY; 4], vec![0; RAW_RX_BUF_LEN]);
+        let tx_buffer =
+            raw::PacketBuffer::new(vec![raw::PacketMetadata::EMPTY; 1], vec![0; RAW_TX_BUF_LEN]);
+        let socket = raw::Socket::new(IpVersion::Ipv6, IpProtocol::Icmpv6, rx_buffer, tx_buffer);
+        Self {
+            handle: sockets.add(socket),
+            ether_addr,
+            solicitations: 0,
+            next_solicit: Instant::ZERO,
+            configured: false,
+        }
+    }
+
+    /// Handles received Router Advertisements, and sends a Router Solicitation
+    /// if the interface is not configured yet.
+    ///
+    /// It should be called after each [`Interface::poll`].
+    pub fn poll(&mut self, timestamp: Instant, iface: &mut Interface, sockets: &mut SocketSet<'_>) {
+        let socket = sockets.get_mut::<raw::Socket>(self.handle);
+        while let Ok(packet) = socket.recv() {
+            if let Some((router, prefix)) = parse_router_advert(packet) {
+                if !self.configured {
+                    self.configure(iface, router, prefix);
+                }
+            }
+        }
+
+        if self.configured
+            || self.solicitations >= MAX_RTR_SOLICITATIONS
+            || timestamp < self.next_solicit
+        {
+            return;
+        }
+        let src_addr = link_local_addr(self.ether_addr);
+        if send_router_solicit(socket, src_addr, self.ether_addr) {
+            debug!("SLAAC: router solicitation sent");
+            self.solicitations += 1;
+            self.next_solicit = timestamp + RTR_SOLICITATION_INTERVAL;
+        }
+    }
+
+    /// Returns when the next Router Solicitation should be sent, or `None` if
+    /// no more solicitations are needed.
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    pub fn poll_at(&self) -> Option<Instant> {
+        (!self.configured && self.solicitations < MAX_RTR_SOLICITATIONS)
+            .then_some(self.next_solicit)
+    }
+
+    fn configure(
+        &mut self,
+        iface: &mut Interface,
+        router: Ipv6Address,
+        prefix: Option<Ipv6Address>,
+    ) {
+        if let Some(prefix) = prefix {
+            let addr = eui64_addr(prefix, self.ether_addr);
+            let mut res = Ok(());
+            iface.update_ip_addrs(|ip_addrs| {
+                let cidr = IpCidr::new(IpAddress::Ipv6(addr), SLAAC_PREFIX_LEN);
+                if !ip_addrs.contains(&cidr) {
+                    res = ip_addrs.push(cidr);
+                }
+            });
+            if res.is_err() {
+                warn!("SLAAC: too many addresses, {} is not added", addr);
+                return;
+            }
+            info!("SLAAC: configured {}/{}", addr, SLAAC_PREFIX_LEN);
+        }
+        if iface.routes_mut().add_default_ipv6_route(router).is_ok() {
+            info!("SLAAC: default route via {}", router);
+        }
+        self.configured = true;
+    }
+}
+
+/// Parses a Router Advertisement packet (with the IPv6 header), returns the
+/// router address and the autonomous /64 prefix if any.
+///
+/// Advertisements from routers that are not default routers are ignored.
+fn parse_router_advert(packet: &[u8]) -> Option<(Ipv6Address, Option<Ipv6Address>)> {
+    let ipv6_packet = Ipv6Packet::new_checked(packet).ok()?;
+    let ipv6_repr = Ipv6Repr::parse(&ipv6_packet).ok()?;
+    let icmp_packet = Icmpv6Packet::new_checked(ipv6_packet.payload()).ok()?;
+    let icmp_repr = Icmpv6Repr::parse(
+        &IpAddress::Ipv6(ipv6_repr.src_addr),
+        &IpAddress::Ipv6(ipv6_repr.dst_addr),
+        &icmp_packet,
+        &ChecksumCapabilities::default(),
+    )
+    .ok()?;
+    match icmp_repr {
+        Icmpv6Repr::Ndisc(NdiscRepr::RouterAdvert {
+            router_lifetime,
+            prefix_info,
+            ..
+        }) if router_lifetime != Duration::ZERO && ipv6_repr.src_addr.is_link_local() => {
+            let prefix = prefix_info
+                .filter(|info| {
+                    info.flags.contains(NdiscPrefixInfoFlags::ADDRCONF)
+                        && info.prefix_len == SLAAC_PREFIX_LEN
+                        && info.valid_lifetime != Duration::ZERO
+                })
+                .map(|info| info.prefix);
+            Some((ipv6_repr.src_addr, prefix))
+        }
+        _ => None,
+    }
+}
+
+/// Enqueues a Router Solicitation to all routers, returns `false` if the
+/// socket buffer is full.
+fn send_router_solicit(
+    socket: &mut raw::Socket<'_>,
+    src_addr: Ipv6Address,
+    ether_addr: EthernetAddress,
+) -> bool {
+    let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::RouterSolicit {
+        lladdr: Some(RawHardwareAddress::from(ether_addr)),
+    });
+    let ipv6_repr = Ipv6Repr {
+        src_addr,
+        dst_addr: Ipv6Address::LINK_LOCAL_ALL_ROUTERS,
+        next_header: IpProtocol::Icmpv6,
+        payload_len: icmp_repr.buffer_len(),
+        hop_limit: 255,
+    };
+    let Ok(buf) = socket.send(ipv6_repr.buffer_len() + ipv6_repr.payload_len) else {
+        return false;
+    };
+    let mut ipv6_packet = Ipv6Packet::new_unchecked(buf);
+    ipv6_repr.emit(&mut ipv6_packet);
+    icmp_repr.emit(
+        &IpAddress::Ipv6(src_addr),
+        &IpAddress::Ipv6(Ipv6Address::LINK_LOCAL_ALL_ROUTERS),
+        &mut Icmpv6Packet::new_unchecked(ipv6_packet.payload_mut()),
+        &ChecksumCapabilities::default(),
+    );
+    true
+}
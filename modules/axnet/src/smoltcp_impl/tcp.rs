@@ -1,17 +1,21 @@
+use alloc::sync::Arc;
 use core::cell::UnsafeCell;
 use core::net::SocketAddr;
 use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use core::time::Duration;
 
 use axerrno::{ax_err, ax_err_type, AxError, AxResult};
 use axio::PollState;
 use axsync::Mutex;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::tcp::{self, ConnectError, State};
-use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
+use smoltcp::wire::{IpEndpoint, IpListenEndpoint, IpProtocol};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, ETH0, LISTEN_TABLE, SOCKET_SET};
+use super::options::SocketOptions;
+use super::{notify_poller, route, SocketHandle, SocketSetWrapper, SocketWaiter};
+use super::{IFACES, LISTEN_TABLE, SOCKET_SET};
+use super::{TCP_RX_BUF_LEN, TCP_TX_BUF_LEN};
 
 // State transitions:
 // CLOSED -(connect)-> BUSY -> CONNECTING -> CONNECTED -(shutdown)-> BUSY -> CLOSED
@@ -41,27 +45,36 @@ pub struct TcpSocket {
     local_addr: UnsafeCell<IpEndpoint>,
     peer_addr: UnsafeCell<IpEndpoint>,
     nonblock: AtomicBool,
+    /// Shared with the listen table when listening, so that the options set
+    /// after [`listen`](Self::listen) apply to new connections.
+    opts: Arc<SocketOptions>,
+    /// Also woken by new connections of a listening socket.
+    waiter: Arc<SocketWaiter>,
 }
 
 unsafe impl Sync for TcpSocket {}
 
 impl TcpSocket {
     /// Creates a new TCP socket.
-    pub const fn new() -> Self {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
         Self {
             state: AtomicU8::new(STATE_CLOSED),
             handle: UnsafeCell::new(None),
             local_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             peer_addr: UnsafeCell::new(UNSPECIFIED_ENDPOINT),
             nonblock: AtomicBool::new(false),
+            opts: Arc::new(SocketOptions::new(TCP_RX_BUF_LEN, TCP_TX_BUF_LEN)),
+            waiter: SocketWaiter::new(),
         }
     }
 
     /// Creates a new TCP socket that is already connected.
-    const fn new_connected(
+    fn new_connected(
         handle: SocketHandle,
         local_addr: IpEndpoint,
         peer_addr: IpEndpoint,
+        opts: SocketOptions,
     ) -> Self {
         Self {
             state: AtomicU8::new(STATE_CONNECTED),
@@ -69,6 +82,8 @@ impl TcpSocket {
             local_addr: UnsafeCell::new(local_addr),
             peer_addr: UnsafeCell::new(peer_addr),
             nonblock: AtomicBool::new(false),
+            opts: Arc::new(opts),
+            waiter: SocketWaiter::new(),
         }
     }
 
@@ -115,21 +130,150 @@ impl TcpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns whether the Nagle's algorithm is disabled (`TCP_NODELAY`).
+    pub fn nodelay(&self) -> bool {
+        self.opts.nodelay()
+    }
+
+    /// Disables or enables the Nagle's algorithm (`TCP_NODELAY`).
+    pub fn set_nodelay(&self, nodelay: bool) {
+        self.opts.set_nodelay(nodelay);
+        self.apply_options();
+    }
+
+    /// Returns whether keep-alive packets are sent (`SO_KEEPALIVE`).
+    pub fn keepalive(&self) -> bool {
+        self.opts.keepalive()
+    }
+
+    /// Enables or disables sending keep-alive packets on an idle connection
+    /// (`SO_KEEPALIVE`).
+    pub fn set_keepalive(&self, keepalive: bool) {
+        self.opts.set_keepalive(keepalive);
+        self.apply_options();
+    }
+
+    /// Returns the size of the receive buffer (`SO_RCVBUF`).
+    pub fn recv_buffer_size(&self) -> usize {
+        self.opts.recv_buf_size()
+    }
+
+    /// Sets the size of the receive buffer (`SO_RCVBUF`).
+    ///
+    /// It takes effect on the next [`connect`](Self::connect), or on the
+    /// connections accepted after [`listen`](Self::listen). The size is
+    /// clamped to a valid range.
+    pub fn set_recv_buffer_size(&self, size: usize) {
+        self.opts.set_recv_buf_size(size);
+    }
+
+    /// Returns the size of the send buffer (`SO_SNDBUF`).
+    pub fn send_buffer_size(&self) -> usize {
+        self.opts.send_buf_size()
+    }
+
+    /// Sets the size of the send buffer (`SO_SNDBUF`).
+    ///
+    /// Like [`set_recv_buffer_size`](Self::set_recv_buffer_size), it takes
+    /// effect on new connections.
+    pub fn set_send_buffer_size(&self, size: usize) {
+        self.opts.set_send_buf_size(size);
+    }
+
+    /// Returns whether the local address can be reused (`SO_REUSEADDR`).
+    pub fn reuse_address(&self) -> bool {
+        self.opts.reuse_addr()
+    }
+
+    /// Allows or disallows reusing the local address (`SO_REUSEADDR`).
+    ///
+    /// If disallowed, [`listen`](Self::listen) fails with
+    /// [`AddrInUse`](AxError::AddrInUse) while a connection on the same port is
+    /// open, e.g., one accepted by a closed listening socket.
+    pub fn set_reuse_address(&self, reuse: bool) {
+        self.opts.set_reuse_addr(reuse);
+    }
+
+    /// Returns the timeout of [`recv`](Self::recv) and [`accept`](Self::accept)
+    /// (`SO_RCVTIMEO`).
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.opts.read_timeout()
+    }
+
+    /// Sets the timeout of [`recv`](Self::recv) and [`accept`](Self::accept)
+    /// (`SO_RCVTIMEO`), after which they fail with
+    /// [`Err(WouldBlock)`](AxError::WouldBlock). [`None`] means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_read_timeout(timeout)
+    }
+
+    /// Returns the timeout of [`send`](Self::send) (`SO_SNDTIMEO`).
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.opts.write_timeout()
+    }
+
+    /// Sets the timeout of [`send`](Self::send) (`SO_SNDTIMEO`), after which
+    /// it fails with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`] means
+    /// no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_write_timeout(timeout)
+    }
+
+    /// Returns the linger timeout (`SO_LINGER`).
+    pub fn linger(&self) -> Option<Duration> {
+        self.opts.linger()
+    }
+
+    /// Sets the linger timeout (`SO_LINGER`).
+    ///
+    /// If it's set, [`shutdown`](Self::shutdown) blocks until the connection
+    /// is closed gracefully or the timeout expires. A zero timeout resets the
+    /// connection immediately. If it's [`None`], the connection is closed in
+    /// the background.
+    pub fn set_linger(&self, linger: Option<Duration>) {
+        self.opts.set_linger(linger);
+    }
+
     /// Connects to the given address and port.
     ///
-    /// The local port is generated automatically.
+    /// The local port is generated automatically. The socket is connected
+    /// through the interface selected by the routing table.
     pub fn connect(&self, remote_addr: SocketAddr) -> AxResult {
         self.update_state(STATE_CLOSED, STATE_CONNECTING, || {
+            let remote_endpoint = from_core_sockaddr(remote_addr);
+            let iface_idx = route::lookup(remote_endpoint.addr).ok_or_else(|| {
+                ax_err_type!(ConnectionRefused, "socket connect() failed: no route")
+            })?;
+
             // SAFETY: no other threads can read or write these fields.
-            let handle = unsafe { self.handle.get().read() }
-                .unwrap_or_else(|| SOCKET_SET.add(SocketSetWrapper::new_tcp_socket()));
+            let handle = match unsafe { self.handle.get().read() } {
+                Some(handle) if handle.iface == iface_idx => handle,
+                old => {
+                    // a socket can't be moved to another interface, create a new one
+                    if let Some(old) = old {
+                        SOCKET_SET.remove(old);
+                    }
+                    let socket = SocketSetWrapper::new_tcp_socket(
+                        self.opts.recv_buf_size(),
+                        self.opts.send_buf_size(),
+                    );
+                    let handle = SOCKET_SET.add(iface_idx, socket);
+                    unsafe { self.handle.get().write(Some(handle)) };
+                    handle
+                }
+            };
 
-            // TODO: check remote addr unreachable
-            let remote_endpoint = from_core_sockaddr(remote_addr);
             let bound_endpoint = self.bound_endpoint()?;
-            let iface = &ETH0.iface;
+            let iface = &IFACES[iface_idx].iface;
             let (local_endpoint, remote_endpoint) = SOCKET_SET
                 .with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                    self.opts.apply_tcp(socket);
                     socket
                         .connect(iface.lock().context(), remote_endpoint, bound_endpoint)
                         .or_else(|e| match e {
@@ -160,7 +304,7 @@ impl TcpSocket {
         if self.is_nonblocking() {
             Err(AxError::WouldBlock)
         } else {
-            self.block_on(|| {
+            self.block_on(None, || {
                 let PollState { writable, .. } = self.poll_connect()?;
                 if !writable {
                     Err(AxError::WouldBlock)
@@ -206,10 +350,14 @@ impl TcpSocket {
     pub fn listen(&self) -> AxResult {
         self.update_state(STATE_CLOSED, STATE_LISTENING, || {
             let bound_endpoint = self.bound_endpoint()?;
+            if !self.reuse_address() && SOCKET_SET.port_in_use(IpProtocol::Tcp, bound_endpoint.port)
+            {
+                return ax_err!(AddrInUse, "socket listen() failed");
+            }
             unsafe {
                 (*self.local_addr.get()).port = bound_endpoint.port;
             }
-            LISTEN_TABLE.listen(bound_endpoint)?;
+            LISTEN_TABLE.listen(bound_endpoint, self.opts.clone(), self.waiter.clone())?;
             debug!("TCP socket listening on {}", bound_endpoint);
             Ok(())
         })
@@ -229,26 +377,53 @@ impl TcpSocket {
 
         // SAFETY: `self.local_addr` should be initialized after `bind()`.
         let local_port = unsafe { self.local_addr.get().read().port };
-        self.block_on(|| {
+        self.block_on(self.read_timeout(), || {
             let (handle, (local_addr, peer_addr)) = LISTEN_TABLE.accept(local_port)?;
             debug!("TCP socket accepted a new connection {}", peer_addr);
-            Ok(TcpSocket::new_connected(handle, local_addr, peer_addr))
+            // the options may be changed after the connection was received
+            let socket =
+                TcpSocket::new_connected(handle, local_addr, peer_addr, self.opts.inherit());
+            socket.apply_options();
+            Ok(socket)
         })
     }
 
     /// Close the connection.
+    ///
+    /// See [`set_linger`](Self::set_linger) for whether it blocks.
     pub fn shutdown(&self) -> AxResult {
         // stream
         self.update_state(STATE_CONNECTED, STATE_CLOSED, || {
             // SAFETY: `self.handle` should be initialized in a connected socket, and
             // no other threads can read or write it.
             let handle = unsafe { self.handle.get().read().unwrap() };
+            let linger = self.linger();
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 debug!("TCP socket {}: shutting down", handle);
-                socket.close();
+                if linger == Some(Duration::ZERO) {
+                    socket.abort();
+                } else {
+                    socket.close();
+                }
             });
             unsafe { self.local_addr.get().write(UNSPECIFIED_ENDPOINT) }; // clear bound address
             SOCKET_SET.poll_interfaces();
+            if let Some(timeout) = linger.filter(|t| !t.is_zero()) {
+                let deadline = axhal::time::current_time() + timeout;
+                while axhal::time::current_time() < deadline {
+                    let events = self.waiter.events();
+                    let closed =
+                        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                            socket.register_recv_waker(&self.waiter.waker());
+                            matches!(socket.state(), State::Closed | State::TimeWait)
+                        });
+                    if closed {
+                        break;
+                    }
+                    self.waiter.wait(events, Some(deadline));
+                    SOCKET_SET.poll_interfaces();
+                }
+            }
             Ok(())
         })
         .unwrap_or(Ok(()))?;
@@ -279,7 +454,7 @@ impl TcpSocket {
 
         // SAFETY: `self.handle` should be initialized in a connected socket.
         let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
+        self.block_on(self.read_timeout(), || {
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 if !socket.is_active() {
                     // not open
@@ -312,7 +487,7 @@ impl TcpSocket {
 
         // SAFETY: `self.handle` should be initialized in a connected socket.
         let handle = unsafe { self.handle.get().read().unwrap() };
-        self.block_on(|| {
+        self.block_on(self.write_timeout(), || {
             SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
                 if !socket.is_active() || !socket.may_send() {
                     // closed by remote
@@ -334,6 +509,7 @@ impl TcpSocket {
 
     /// Whether the socket is readable or writable.
     pub fn poll(&self) -> AxResult<PollState> {
+        self.register_waker();
         match self.get_state() {
             STATE_CONNECTING => self.poll_connect(),
             STATE_CONNECTED => self.poll_stream(),
@@ -341,6 +517,8 @@ impl TcpSocket {
             _ => Ok(PollState {
                 readable: false,
                 writable: false,
+                hangup: true,
+                error: false,
             }),
         }
     }
@@ -402,6 +580,18 @@ impl TcpSocket {
         self.get_state() == STATE_LISTENING
     }
 
+    /// Applies the options to the smoltcp socket if it's connecting or
+    /// connected, otherwise they are applied on [`connect`](Self::connect).
+    fn apply_options(&self) {
+        if self.is_connecting() || self.is_connected() {
+            // SAFETY: `self.handle` should be initialized in a connecting or
+            // connected socket.
+            let handle = unsafe { self.handle.get().read().unwrap() };
+            SOCKET_SET
+                .with_socket_mut::<tcp::Socket, _, _>(handle, |socket| self.opts.apply_tcp(socket));
+        }
+    }
+
     fn bound_endpoint(&self) -> AxResult<IpListenEndpoint> {
         // SAFETY: no other threads can read or write `self.local_addr`.
         let local_addr = unsafe { self.local_addr.get().read() };
@@ -422,9 +612,9 @@ impl TcpSocket {
     fn poll_connect(&self) -> AxResult<PollState> {
         // SAFETY: `self.handle` should be initialized above.
         let handle = unsafe { self.handle.get().read().unwrap() };
-        let writable =
+        let (writable, error) =
             SOCKET_SET.with_socket::<tcp::Socket, _, _>(handle, |socket| match socket.state() {
-                State::SynSent => false, // wait for connection
+                State::SynSent => (false, false), // wait for connection
                 State::Established => {
                     self.set_state(STATE_CONNECTED); // connected
                     debug!(
@@ -432,7 +622,7 @@ impl TcpSocket {
                         handle,
                         socket.remote_endpoint().unwrap(),
                     );
-                    true
+                    (true, false)
                 }
                 _ => {
                     unsafe {
@@ -440,12 +630,14 @@ impl TcpSocket {
                         self.peer_addr.get().write(UNSPECIFIED_ENDPOINT);
                     }
                     self.set_state(STATE_CLOSED); // connection failed
-                    true
+                    (true, true)
                 }
             });
         Ok(PollState {
             readable: false,
             writable,
+            hangup: error,
+            error,
         })
     }
 
@@ -456,6 +648,9 @@ impl TcpSocket {
             Ok(PollState {
                 readable: !socket.may_recv() || socket.can_recv(),
                 writable: !socket.may_send() || socket.can_send(),
+                hangup: !socket.may_recv() && !socket.may_send(),
+                // It's closed before `shutdown`, so it must be reset or timed out.
+                error: socket.state() == State::Closed,
             })
         })
     }
@@ -466,26 +661,60 @@ impl TcpSocket {
         Ok(PollState {
             readable: LISTEN_TABLE.can_accept(local_addr.port)?,
             writable: false,
+            hangup: false,
+            error: false,
         })
     }
 
+    /// Registers the waker of blocked tasks to the underlying socket.
+    ///
+    /// New connections of a listening socket are registered when they are
+    /// created by the listen table.
+    fn register_waker(&self) {
+        if self.is_connecting() || self.is_connected() {
+            // SAFETY: `self.handle` should be initialized in a connecting or
+            // connected socket.
+            let handle = unsafe { self.handle.get().read().unwrap() };
+            SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+                let waker = self.waiter.waker();
+                socket.register_recv_waker(&waker);
+                socket.register_send_waker(&waker);
+            });
+        }
+    }
+
     /// Block the current thread until the given function completes or fails.
     ///
     /// If the socket is non-blocking, it calls the function once and returns
     /// immediately. Otherwise, it may call the function multiple times if it
-    /// returns [`Err(WouldBlock)`](AxError::WouldBlock).
-    fn block_on<F, T>(&self, mut f: F) -> AxResult<T>
+    /// returns [`Err(WouldBlock)`](AxError::WouldBlock), until the `timeout`
+    /// expires. Between the calls, it sleeps until the socket state changes.
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
     where
         F: FnMut() -> AxResult<T>,
     {
         if self.is_nonblocking() {
-            f()
+            f().map(|t| {
+                notify_poller();
+                t
+            })
         } else {
+            let deadline = timeout.map(|t| axhal::time::current_time() + t);
             loop {
                 SOCKET_SET.poll_interfaces();
+                let events = self.waiter.events();
+                self.register_waker();
                 match f() {
-                    Ok(t) => return Ok(t),
-                    Err(AxError::WouldBlock) => axtask::yield_now(),
+                    Ok(t) => {
+                        notify_poller();
+                        return Ok(t);
+                    }
+                    Err(AxError::WouldBlock) => {
+                        if deadline.is_some_and(|d| axhal::time::current_time() >= d) {
+                            return Err(AxError::WouldBlock);
+                        }
+                        self.waiter.wait(events, deadline)
+                    }
                     Err(e) => return Err(e),
                 }
             }
@@ -1,37 +1,49 @@
+use alloc::{sync::Arc, vec::Vec};
 use core::net::SocketAddr;
 use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
 
 use axerrno::{ax_err, ax_err_type, AxError, AxResult};
 use axio::PollState;
 use axsync::Mutex;
 use spin::RwLock;
 
-use smoltcp::iface::SocketHandle;
 use smoltcp::socket::udp::{self, BindError, SendError};
-use smoltcp::wire::{IpEndpoint, IpListenEndpoint};
+use smoltcp::wire::{IpEndpoint, IpListenEndpoint, IpProtocol};
 
 use super::addr::{from_core_sockaddr, into_core_sockaddr, is_unspecified, UNSPECIFIED_ENDPOINT};
-use super::{SocketSetWrapper, SOCKET_SET};
+use super::options::SocketOptions;
+use super::{
+    local_ifaces, notify_poller, route, SocketHandle, SocketSetWrapper, SocketWaiter, SOCKET_SET,
+};
+use super::{UDP_RX_BUF_LEN, UDP_TX_BUF_LEN};
 
 /// A UDP socket that provides POSIX-like APIs.
+///
+/// It's bound on every interface that owns the local address (or all
+/// interfaces for the unspecified address), and datagrams are sent through
+/// the interface selected by the routing table.
 pub struct UdpSocket {
-    handle: SocketHandle,
+    /// One socket for each interface it's bound on.
+    handles: RwLock<Vec<SocketHandle>>,
     local_addr: RwLock<Option<IpEndpoint>>,
     peer_addr: RwLock<Option<IpEndpoint>>,
     nonblock: AtomicBool,
+    opts: SocketOptions,
+    waiter: Arc<SocketWaiter>,
 }
 
 impl UdpSocket {
     /// Creates a new UDP socket.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        let socket = SocketSetWrapper::new_udp_socket();
-        let handle = SOCKET_SET.add(socket);
         Self {
-            handle,
+            handles: RwLock::new(Vec::new()),
             local_addr: RwLock::new(None),
             peer_addr: RwLock::new(None),
             nonblock: AtomicBool::new(false),
+            opts: SocketOptions::new(UDP_RX_BUF_LEN, UDP_TX_BUF_LEN),
+            waiter: SocketWaiter::new(),
         }
     }
 
@@ -69,6 +81,76 @@ impl UdpSocket {
         self.nonblock.store(nonblocking, Ordering::Release);
     }
 
+    /// Returns the size of the receive buffer (`SO_RCVBUF`).
+    pub fn recv_buffer_size(&self) -> usize {
+        self.opts.recv_buf_size()
+    }
+
+    /// Sets the size of the receive buffer (`SO_RCVBUF`).
+    ///
+    /// It takes effect on the next [`bind`](Self::bind). The size is clamped
+    /// to a valid range.
+    pub fn set_recv_buffer_size(&self, size: usize) {
+        self.opts.set_recv_buf_size(size);
+    }
+
+    /// Returns the size of the send buffer (`SO_SNDBUF`).
+    pub fn send_buffer_size(&self) -> usize {
+        self.opts.send_buf_size()
+    }
+
+    /// Sets the size of the send buffer (`SO_SNDBUF`).
+    ///
+    /// It takes effect on the next [`bind`](Self::bind). The size is clamped
+    /// to a valid range.
+    pub fn set_send_buffer_size(&self, size: usize) {
+        self.opts.set_send_buf_size(size);
+    }
+
+    /// Returns whether the local address can be reused (`SO_REUSEADDR`).
+    pub fn reuse_address(&self) -> bool {
+        self.opts.reuse_addr()
+    }
+
+    /// Allows or disallows reusing the local address (`SO_REUSEADDR`).
+    ///
+    /// If disallowed, [`bind`](Self::bind) fails with
+    /// [`AddrInUse`](axerrno::AxError::AddrInUse) when another UDP socket is
+    /// bound to the same port.
+    pub fn set_reuse_address(&self, reuse: bool) {
+        self.opts.set_reuse_addr(reuse);
+    }
+
+    /// Returns the timeout of receiving operations (`SO_RCVTIMEO`).
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.opts.read_timeout()
+    }
+
+    /// Sets the timeout of receiving operations (`SO_RCVTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_read_timeout(timeout)
+    }
+
+    /// Returns the timeout of sending operations (`SO_SNDTIMEO`).
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.opts.write_timeout()
+    }
+
+    /// Sets the timeout of sending operations (`SO_SNDTIMEO`), after which
+    /// they fail with [`Err(WouldBlock)`](AxError::WouldBlock). [`None`]
+    /// means no timeout.
+    ///
+    /// Returns [`Err(InvalidInput)`](AxError::InvalidInput) if the timeout is
+    /// zero.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> AxResult {
+        self.opts.set_write_timeout(timeout)
+    }
+
     /// Binds an unbound socket to the given address and port.
     ///
     /// It's must be called before [`send_to`](Self::send_to) and
@@ -88,15 +170,40 @@ impl UdpSocket {
             addr: (!is_unspecified(local_endpoint.addr)).then_some(local_endpoint.addr),
             port: local_endpoint.port,
         };
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            socket.bind(endpoint).or_else(|e| match e {
-                BindError::InvalidState => ax_err!(AlreadyExists, "socket bind() failed"),
-                BindError::Unaddressable => ax_err!(InvalidInput, "socket bind() failed"),
-            })
-        })?;
+        let ifaces = local_ifaces(endpoint.addr);
+        if ifaces.is_empty() {
+            return ax_err!(InvalidInput, "socket bind() failed: address not available");
+        }
+        if !self.reuse_address() && SOCKET_SET.port_in_use(IpProtocol::Udp, endpoint.port) {
+            return ax_err!(AddrInUse, "socket bind() failed");
+        }
+
+        let mut handles = self.handles.write();
+        for iface in ifaces {
+            let socket = SocketSetWrapper::new_udp_socket(
+                self.opts.recv_buf_size(),
+                self.opts.send_buf_size(),
+            );
+            let handle = SOCKET_SET.add(iface, socket);
+            handles.push(handle);
+            let res = SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                socket.bind(endpoint).or_else(|e| match e {
+                    BindError::InvalidState => ax_err!(AlreadyExists, "socket bind() failed"),
+                    BindError::Unaddressable => ax_err!(InvalidInput, "socket bind() failed"),
+                })
+            });
+            if let Err(e) = res {
+                // remove the sockets bound on the previous interfaces, so
+                // that the socket stays unbound
+                for handle in handles.drain(..) {
+                    SOCKET_SET.remove(handle);
+                }
+                return Err(e);
+            }
+            debug!("UDP socket {}: bound on {}", handle, endpoint);
+        }
 
         *self_local_addr = Some(local_endpoint);
-        debug!("UDP socket {}: bound on {}", self.handle, endpoint);
         Ok(())
     }
 
@@ -142,7 +249,7 @@ impl UdpSocket {
         }
 
         *self_peer_addr = Some(from_core_sockaddr(addr));
-        debug!("UDP socket {}: connected to {}", self.handle, addr);
+        debug!("UDP socket: connected to {}", addr);
         Ok(())
     }
 
@@ -172,28 +279,40 @@ impl UdpSocket {
 
     /// Close the socket.
     pub fn shutdown(&self) -> AxResult {
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            debug!("UDP socket {}: shutting down", self.handle);
-            socket.close();
-        });
+        for &handle in self.handles.read().iter() {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                debug!("UDP socket {}: shutting down", handle);
+                socket.close();
+            });
+        }
         SOCKET_SET.poll_interfaces();
         Ok(())
     }
 
     /// Whether the socket is readable or writable.
     pub fn poll(&self) -> AxResult<PollState> {
+        self.register_waker();
         if self.local_addr.read().is_none() {
             return Ok(PollState {
                 readable: false,
                 writable: false,
+                hangup: false,
+                error: false,
             });
         }
-        SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-            Ok(PollState {
-                readable: socket.can_recv(),
-                writable: socket.can_send(),
-            })
-        })
+        let mut state = PollState {
+            readable: false,
+            writable: false,
+            hangup: false,
+            error: false,
+        };
+        for &handle in self.handles.read().iter() {
+            SOCKET_SET.with_socket::<udp::Socket, _, _>(handle, |socket| {
+                state.readable |= socket.can_recv();
+                state.writable |= socket.can_send();
+            });
+        }
+        Ok(state)
     }
 }
 
@@ -210,9 +329,15 @@ impl UdpSocket {
         if self.local_addr.read().is_none() {
             return ax_err!(NotConnected, "socket send() failed");
         }
+        let handle = route::lookup(remote_endpoint.addr)
+            .and_then(|iface| {
+                let handles = self.handles.read();
+                handles.iter().find(|handle| handle.iface == iface).copied()
+            })
+            .ok_or_else(|| ax_err_type!(ConnectionRefused, "socket send() failed: no route"))?;
 
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
+        self.block_on(self.write_timeout(), || {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
                 if socket.can_send() {
                     socket
                         .send_slice(buf, remote_endpoint)
@@ -239,31 +364,58 @@ impl UdpSocket {
             return ax_err!(NotConnected, "socket send() failed");
         }
 
-        self.block_on(|| {
-            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(self.handle, |socket| {
-                if socket.can_recv() {
+        self.block_on(self.read_timeout(), || {
+            for &handle in self.handles.read().iter() {
+                let res = SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
                     // data available
-                    op(socket)
-                } else {
-                    // no more data
-                    Err(AxError::WouldBlock)
+                    socket.can_recv().then(|| op(socket))
+                });
+                if let Some(res) = res {
+                    return res;
                 }
-            })
+            }
+            // no more data
+            Err(AxError::WouldBlock)
         })
     }
 
-    fn block_on<F, T>(&self, mut f: F) -> AxResult<T>
+    /// Registers the waker of blocked tasks to the underlying sockets.
+    fn register_waker(&self) {
+        let waker = self.waiter.waker();
+        for &handle in self.handles.read().iter() {
+            SOCKET_SET.with_socket_mut::<udp::Socket, _, _>(handle, |socket| {
+                socket.register_recv_waker(&waker);
+                socket.register_send_waker(&waker);
+            });
+        }
+    }
+
+    fn block_on<F, T>(&self, timeout: Option<Duration>, mut f: F) -> AxResult<T>
     where
         F: FnMut() -> AxResult<T>,
     {
         if self.is_nonblocking() {
-            f()
+            f().map(|t| {
+                notify_poller();
+                t
+            })
         } else {
+            let deadline = timeout.map(|t| axhal::time::current_time() + t);
             loop {
                 SOCKET_SET.poll_interfaces();
+                let events = self.waiter.events();
+                self.register_waker();
                 match f() {
-                    Ok(t) => return Ok(t),
-                    Err(AxError::WouldBlock) => axtask::yield_now(),
+                    Ok(t) => {
+                        notify_poller();
+                        return Ok(t);
+                    }
+                    Err(AxError::WouldBlock) => {
+                        if deadline.is_some_and(|d| axhal::time::current_time() >= d) {
+                            return Err(AxError::WouldBlock);
+                        }
+                        self.waiter.wait(events, deadline)
+                    }
                     Err(e) => return Err(e),
                 }
             }
@@ -274,7 +426,9 @@ impl UdpSocket {
 impl Drop for UdpSocket {
     fn drop(&mut self) {
         self.shutdown().ok();
-        SOCKET_SET.remove(self.handle);
+        for &handle in self.handles.get_mut().iter() {
+            SOCKET_SET.remove(handle);
+        }
     }
 }
 
@@ -284,11 +438,16 @@ fn get_ephemeral_port() -> AxResult<u16> {
     static CURR: Mutex<u16> = Mutex::new(PORT_START);
     let mut curr = CURR.lock();
 
-    let port = *curr;
-    if *curr == PORT_END {
-        *curr = PORT_START;
-    } else {
-        *curr += 1;
+    for _ in PORT_START..=PORT_END {
+        let port = *curr;
+        if *curr == PORT_END {
+            *curr = PORT_START;
+        } else {
+            *curr += 1;
+        }
+        if !SOCKET_SET.port_in_use(IpProtocol::Udp, port) {
+            return Ok(port);
+        }
     }
-    Ok(port)
+    ax_err!(AddrInUse, "no avaliable ports!")
 }
@@ -0,0 +1,152 @@
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::Waker;
+
+use axhal::time::TimeValue;
+
+/// The number of events of all sockets.
+static SOCKET_EVENTS: AtomicUsize = AtomicUsize::new(0);
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "multitask", feature = "irq"))] {
+        use axhal::time::current_time;
+        use axtask::WaitQueue;
+
+        /// Tasks waiting for events of any socket, e.g., in `epoll_wait`.
+        static SOCKET_EVENTS_WQ: WaitQueue = WaitQueue::new();
+
+        /// Blocks the current task until `events` differs from `seen`, or the
+        /// `deadline` has passed.
+        fn wait_events(wq: &WaitQueue, events: &AtomicUsize, seen: usize, deadline: Option<TimeValue>) {
+            let condition = || events.load(Ordering::Acquire) != seen;
+            match deadline {
+                Some(deadline) => {
+                    let now = current_time();
+                    if now < deadline {
+                        wq.wait_timeout_until(deadline - now, condition);
+                    }
+                }
+                None => wq.wait_until(condition),
+            }
+        }
+    }
+}
+
+/// Tasks blocked on a socket, waiting for its state to change.
+///
+/// Its [`Waker`] is registered to the smoltcp sockets, which is woken when
+/// data or connections arrive, buffer space is freed, or the connection is
+/// closed. Sockets not backed by smoltcp call [`notify`](Self::notify)
+/// themselves on such changes.
+///
+/// If the `multitask` and `irq` features are not both enabled, there is no
+/// wait queue to sleep on, so waiting only yields the CPU.
+pub struct SocketWaiter {
+    events: AtomicUsize,
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    wq: WaitQueue,
+}
+
+impl SocketWaiter {
+    /// Creates a new waiter.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            events: AtomicUsize::new(0),
+            #[cfg(all(feature = "multitask", feature = "irq"))]
+            wq: WaitQueue::new(),
+        })
+    }
+
+    /// Returns the waker to be registered to smoltcp sockets.
+    pub fn waker(self: &Arc<Self>) -> Waker {
+        Waker::from(self.clone())
+    }
+
+    /// Returns the number of events so far.
+    ///
+    /// It must be read before checking the socket, and be passed to the
+    /// following [`wait`](Self::wait) so that events in between are not lost.
+    pub fn events(&self) -> usize {
+        self.events.load(Ordering::Acquire)
+    }
+
+    /// Blocks the current task until an event happens after `seen` is read
+    /// by [`events`](Self::events), or the `deadline` has passed.
+    #[cfg_attr(
+        not(all(feature = "multitask", feature = "irq")),
+        allow(unused_variables)
+    )]
+    pub fn wait(&self, seen: usize, deadline: Option<TimeValue>) {
+        #[cfg(all(feature = "multitask", feature = "irq"))]
+        wait_events(&self.wq, &self.events, seen, deadline);
+        #[cfg(not(all(feature = "multitask", feature = "irq")))]
+        axtask::yield_now();
+    }
+
+    /// Notifies an event of the socket, which wakes up the tasks blocked on
+    /// it and those waiting for events of any socket.
+    pub fn notify(&self) {
+        self.events.fetch_add(1, Ordering::Release);
+        #[cfg(all(feature = "multitask", feature = "irq"))]
+        self.wq.notify_all(false);
+        notify_socket_events();
+    }
+}
+
+impl Wake for SocketWaiter {
+    fn wake(self: Arc<Self>) {
+        self.notify();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.notify();
+    }
+}
+
+/// Returns the number of events of all sockets so far.
+///
+/// It must be read before polling the sockets, and be passed to the following
+/// [`wait_socket_events`] so that events in between are not lost.
+pub fn socket_events() -> usize {
+    SOCKET_EVENTS.load(Ordering::Acquire)
+}
+
+/// Notifies an event not of a particular socket, which wakes up the tasks
+/// waiting for events of any socket, e.g., to interrupt
+/// [`wait_socket_events`] when a signal arrives.
+pub fn notify_socket_events() {
+    SOCKET_EVENTS.fetch_add(1, Ordering::Release);
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    SOCKET_EVENTS_WQ.notify_all(false);
+}
+
+/// Returns a waker that calls [`notify_socket_events`] when woken.
+pub fn socket_events_waker() -> Waker {
+    struct EventsWaker;
+
+    impl Wake for EventsWaker {
+        fn wake(self: Arc<Self>) {
+            notify_socket_events();
+        }
+    }
+
+    Waker::from(Arc::new(EventsWaker))
+}
+
+/// Blocks the current task until an event of any socket happens after `seen`
+/// is read by [`socket_events`], or the `deadline` has passed.
+///
+/// Events are reported by the sockets that have been blocked on or polled by
+/// their `poll` methods. If the `multitask` and `irq` features are not both
+/// enabled, it only yields the CPU.
+#[cfg_attr(
+    not(all(feature = "multitask", feature = "irq")),
+    allow(unused_variables)
+)]
+pub fn wait_socket_events(seen: usize, deadline: Option<TimeValue>) {
+    #[cfg(all(feature = "multitask", feature = "irq"))]
+    wait_events(&SOCKET_EVENTS_WQ, &SOCKET_EVENTS, seen, deadline);
+    #[cfg(not(all(feature = "multitask", feature = "irq")))]
+    axtask::yield_now();
+}
@@ -6,7 +6,7 @@
 //! # Cargo Features
 //!
 //! - `alloc`: Enable global memory allocator.
-//! - `paging`: Enable page table manipulation support.
+//! - `paging`: Enable page table manipulation and memory mapping support.
 //! - `irq`: Enable interrupt handling support.
 //! - `multitask`: Enable multi-threading support.
 //! - `smp`: Enable SMP (symmetric multiprocessing) support.
@@ -89,7 +89,7 @@ use core::sync::atomic::{AtomicUsize, Ordering};
 static INITED_CPUS: AtomicUsize = AtomicUsize::new(0);
 
 fn is_init_ok() -> bool {
-    INITED_CPUS.load(Ordering::Acquire) == axconfig::SMP
+    INITED_CPUS.load(Ordering::Acquire) == axhal::cpu::cpu_count()
 }
 
 /// The main entry point of the ArceOS runtime.
@@ -125,6 +125,10 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
     axlog::set_max_level(option_env!("AX_LOG").unwrap_or("")); // no effect if set `log-level-*` features
     info!("Logging is enabled.");
     info!("Primary CPU {} started, dtb = {:#x}.", cpu_id, dtb);
+    match axhal::fdt::fdt() {
+        Some(fdt) => info!("Found device tree: {} bytes.", fdt.total_size()),
+        None => info!("No device tree, use the static platform configuration."),
+    }
 
     info!("Found physcial memory regions:");
     for r in axhal::mem::memory_regions() {
@@ -141,10 +145,7 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
     init_allocator();
 
     #[cfg(feature = "paging")]
-    {
-        info!("Initialize kernel page table...");
-        remap_kernel_memory().expect("remap kernel memoy failed");
-    }
+    axmm::init_memory_management();
 
     info!("Initialize platform devices...");
     axhal::platform_init();
@@ -157,8 +158,18 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
         #[allow(unused_variables)]
         let all_devices = axdriver::init_drivers();
 
+        #[cfg(feature = "serial")]
+        axfs::init_serial_ports(all_devices.char);
+        #[cfg(feature = "gpio")]
+        axfs::init_gpio(all_devices.gpio);
+
         #[cfg(feature = "fs")]
-        axfs::init_filesystems(all_devices.block);
+        {
+            axfs::init_filesystems(all_devices.block);
+            // the block caches are write-back, so flush them on all paths
+            // that terminate the system (e.g., `exit` or fatal signals).
+            axhal::misc::register_shutdown_hook(sync_filesystems);
+        }
 
         #[cfg(feature = "net")]
         axnet::init_network(all_devices.net);
@@ -200,6 +211,15 @@ pub extern "C" fn rust_main(cpu_id: usize, dtb: usize) -> ! {
     }
 }
 
+#[cfg(feature = "fs")]
+fn sync_filesystems() {
+    // it may be called from the panic handler with the caches locked, so
+    // they are not waited for.
+    if let Err(e) = axfs::api::try_sync() {
+        warn!("failed to sync filesystems: {:?}", e);
+    }
+}
+
 #[cfg(feature = "alloc")]
 fn init_allocator() {
     use axhal::mem::{memory_regions, phys_to_virt, MemRegionFlags};
@@ -229,32 +249,6 @@ fn init_allocator() {
     }
 }
 
-#[cfg(feature = "paging")]
-fn remap_kernel_memory() -> Result<(), axhal::paging::PagingError> {
-    use axhal::mem::{memory_regions, phys_to_virt};
-    use axhal::paging::PageTable;
-    use lazy_init::LazyInit;
-
-    static KERNEL_PAGE_TABLE: LazyInit<PageTable> = LazyInit::new();
-
-    if axhal::cpu::this_cpu_is_bsp() {
-        let mut kernel_page_table = PageTable::try_new()?;
-        for r in memory_regions() {
-            kernel_page_table.map_region(
-                phys_to_virt(r.paddr),
-                r.paddr,
-                r.size,
-                r.flags.into(),
-                true,
-            )?;
-        }
-        KERNEL_PAGE_TABLE.init_by(kernel_page_table);
-    }
-
-    unsafe { axhal::arch::write_page_table_root(KERNEL_PAGE_TABLE.root_paddr()) };
-    Ok(())
-}
-
 #[cfg(feature = "irq")]
 fn init_interrupt() {
     use axhal::time::TIMER_IRQ_NUM;
@@ -9,7 +9,7 @@ static ENTERED_CPUS: AtomicUsize = AtomicUsize::new(1);
 
 pub fn start_secondary_cpus(primary_cpu_id: usize) {
     let mut logic_cpu_id = 0;
-    for i in 0..SMP {
+    for i in 0..axhal::cpu::cpu_count() {
         if i != primary_cpu_id {
             let stack_top = virt_to_phys(VirtAddr::from(unsafe {
                 SECONDARY_BOOT_STACK[logic_cpu_id].as_ptr_range().end as usize
@@ -35,7 +35,7 @@ pub extern "C" fn rust_main_secondary(cpu_id: usize) -> ! {
     info!("Secondary CPU {:x} started.", cpu_id);
 
     #[cfg(feature = "paging")]
-    super::remap_kernel_memory().unwrap();
+    axmm::init_memory_management_secondary();
 
     axhal::platform_init_secondary();
 
@@ -3,26 +3,83 @@
 //! Currently supported primitives:
 //!
 //! - [`Mutex`]: A mutual exclusion primitive.
+//! - [`Condvar`]: A condition variable.
+//! - [`RwLock`]: A readers-writer lock.
+//! - [`Semaphore`]: A counting semaphore.
+//! - [`Barrier`]: Enables multiple tasks to synchronize the beginning of some
+//!   computation.
+//! - [`Once`]: A synchronization primitive which can be used to run a one-time
+//!   initialization.
 //! - mod [`spin`](spinlock): spin-locks.
 //!
 //! # Cargo Features
 //!
 //! - `multitask`: For use in the multi-threaded environments. If the feature is
-//!   not enabled, [`Mutex`] will be an alias of [`spin::SpinNoIrq`]. This
-//!   feature is enabled by default.
+//!   not enabled, [`Mutex`] will be an alias of [`spin::SpinNoIrq`], and other
+//!   blocking primitives are unavailable. This feature is enabled by default.
+//! - `irq`: Interrupts are enabled. Timed waits (e.g.,
+//!   [`Condvar::wait_timeout`]) will sleep on timers, otherwise they fall back
+//!   to busy-waiting.
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
 
 pub use spinlock as spin;
 
-#[cfg(feature = "multitask")]
-mod mutex;
+cfg_if::cfg_if! {
+    if #[cfg(feature = "multitask")] {
+        mod barrier;
+        mod condvar;
+        mod mutex;
+        mod once;
+        mod rwlock;
+        mod semaphore;
 
-#[cfg(feature = "multitask")]
-#[doc(cfg(feature = "multitask"))]
-pub use self::mutex::{Mutex, MutexGuard};
+        #[cfg(test)]
+        mod tests;
 
-#[cfg(not(feature = "multitask"))]
-#[doc(cfg(not(feature = "multitask")))]
-pub use spinlock::{SpinNoIrq as Mutex, SpinNoIrqGuard as MutexGuard};
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::barrier::{Barrier, BarrierWaitResult};
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::condvar::{Condvar, WaitTimeoutResult};
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::mutex::{Mutex, MutexGuard};
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::once::Once;
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+        #[doc(cfg(feature = "multitask"))]
+        pub use self::semaphore::Semaphore;
+
+        /// Blocks the current task on `wq` until `condition` becomes true or
+        /// `dur` has elapsed. Returns whether it timed out.
+        fn wait_timeout_until<F>(
+            wq: &axtask::WaitQueue,
+            dur: core::time::Duration,
+            condition: F,
+        ) -> bool
+        where
+            F: Fn() -> bool,
+        {
+            #[cfg(feature = "irq")]
+            {
+                wq.wait_timeout_until(dur, condition)
+            }
+            #[cfg(not(feature = "irq"))]
+            {
+                let _ = wq;
+                let deadline = axhal::time::current_time() + dur;
+                while !condition() {
+                    if axhal::time::current_time() >= deadline {
+                        return true;
+                    }
+                    axtask::yield_now();
+                }
+                false
+            }
+        }
+    } else {
+        #[doc(cfg(not(feature = "multitask")))]
+        pub use spinlock::{SpinNoIrq as Mutex, SpinNoIrqGuard as MutexGuard};
+    }
+}
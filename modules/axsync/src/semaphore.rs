@@ -0,0 +1,70 @@
+//! A counting semaphore.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axtask::WaitQueue;
+
+/// A counting semaphore.
+///
+/// It maintains a count of available permits. [`Semaphore::acquire`] blocks
+/// the current task until a permit is available and takes it, and
+/// [`Semaphore::release`] returns a permit and wakes up a waiting task.
+pub struct Semaphore {
+    wq: WaitQueue,
+    count: AtomicUsize,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the initial number of permits.
+    pub const fn new(count: usize) -> Self {
+        Self {
+            wq: WaitQueue::new(),
+            count: AtomicUsize::new(count),
+        }
+    }
+
+    /// Returns the number of available permits.
+    pub fn value(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Acquires a permit, blocking the current task until one is available.
+    pub fn acquire(&self) {
+        while !self.try_acquire() {
+            self.wq.wait_until(|| self.value() > 0);
+        }
+    }
+
+    /// Acquires a permit, blocking the current task until one is available or
+    /// the given duration has elapsed.
+    ///
+    /// Returns `true` if a permit was acquired.
+    pub fn acquire_timeout(&self, dur: Duration) -> bool {
+        let deadline = axhal::time::current_time() + dur;
+        while !self.try_acquire() {
+            let now = axhal::time::current_time();
+            if now >= deadline
+                || crate::wait_timeout_until(&self.wq, deadline - now, || self.value() > 0)
+            {
+                return self.try_acquire();
+            }
+        }
+        true
+    }
+
+    /// Tries to acquire a permit without blocking. Returns `true` on success.
+    pub fn try_acquire(&self) -> bool {
+        self.count
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Releases a permit, waking up a task waiting for it.
+    pub fn release(&self) {
+        self.count.fetch_add(1, Ordering::Release);
+        self.wq.notify_one(true);
+    }
+}
@@ -0,0 +1,203 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use axtask as thread;
+
+use crate::{Barrier, Condvar, Mutex, Once, RwLock, Semaphore};
+
+/// All tests share the same scheduler, so they must run one by one.
+pub(crate) static INIT: std::sync::Once = std::sync::Once::new();
+pub(crate) static SERIAL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn wait_for(counter: &AtomicUsize, n: usize) {
+    while counter.load(Ordering::Acquire) < n {
+        thread::yield_now();
+    }
+}
+
+#[test]
+fn test_condvar() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    const NUM_TASKS: usize = 5;
+    static QUEUE: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+    static CV: Condvar = Condvar::new();
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    for _ in 0..NUM_TASKS {
+        thread::spawn(|| {
+            let mut queue = CV.wait_while(QUEUE.lock(), |q| q.is_empty());
+            queue.pop().unwrap();
+            FINISHED.fetch_add(1, Ordering::Release);
+        });
+    }
+    for i in 0..NUM_TASKS {
+        thread::yield_now();
+        QUEUE.lock().push(i);
+        CV.notify_one();
+    }
+    wait_for(&FINISHED, NUM_TASKS);
+    assert!(QUEUE.lock().is_empty());
+
+    // notified before the timeout
+    thread::spawn(|| {
+        *QUEUE.lock() = vec![42];
+        CV.notify_all();
+    });
+    let (queue, res) =
+        CV.wait_timeout_while(QUEUE.lock(), Duration::from_secs(60), |q| q.is_empty());
+    assert!(!res.timed_out());
+    assert_eq!(*queue, [42]);
+}
+
+#[test]
+fn test_rwlock() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    const NUM_TASKS: usize = 10;
+    const NUM_ITERS: usize = 100;
+    static LOCK: RwLock<(usize, usize)> = RwLock::new((0, 0));
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    for i in 0..NUM_TASKS {
+        thread::spawn(move || {
+            for _ in 0..NUM_ITERS {
+                if i % 2 == 0 {
+                    let mut val = LOCK.write();
+                    val.0 += 1;
+                    thread::yield_now();
+                    val.1 += 1;
+                } else {
+                    let val = LOCK.read();
+                    thread::yield_now();
+                    assert_eq!(val.0, val.1);
+                }
+            }
+            FINISHED.fetch_add(1, Ordering::Release);
+        });
+    }
+    wait_for(&FINISHED, NUM_TASKS);
+
+    let val = LOCK.read();
+    assert!(LOCK.try_read().is_some());
+    assert!(LOCK.try_write().is_none());
+    assert_eq!(*val, (NUM_TASKS / 2 * NUM_ITERS, NUM_TASKS / 2 * NUM_ITERS));
+}
+
+#[test]
+fn test_rwlock_writer_preferred() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    static LOCK: RwLock<()> = RwLock::new(());
+    static ORDER: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+    static STARTED: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    let val = LOCK.read();
+    thread::spawn(|| {
+        STARTED.fetch_add(1, Ordering::Release);
+        let _val = LOCK.write();
+        ORDER.lock().push("writer");
+        FINISHED.fetch_add(1, Ordering::Release);
+    });
+    wait_for(&STARTED, 1);
+
+    // the writer is blocked, so new readers must wait for it
+    assert!(LOCK.try_read().is_none());
+    thread::spawn(|| {
+        STARTED.fetch_add(1, Ordering::Release);
+        let _val = LOCK.read();
+        ORDER.lock().push("reader");
+        FINISHED.fetch_add(1, Ordering::Release);
+    });
+    wait_for(&STARTED, 2);
+    assert!(ORDER.lock().is_empty());
+
+    drop(val);
+    wait_for(&FINISHED, 2);
+    assert_eq!(*ORDER.lock(), ["writer", "reader"]);
+    assert!(LOCK.try_read().is_some());
+}
+
+#[test]
+fn test_semaphore() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    const NUM_TASKS: usize = 10;
+    const PERMITS: usize = 3;
+    static SEM: Semaphore = Semaphore::new(PERMITS);
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    for _ in 0..NUM_TASKS {
+        thread::spawn(|| {
+            SEM.acquire();
+            assert!(ACTIVE.fetch_add(1, Ordering::Relaxed) < PERMITS);
+            thread::yield_now();
+            ACTIVE.fetch_sub(1, Ordering::Relaxed);
+            SEM.release();
+            FINISHED.fetch_add(1, Ordering::Release);
+        });
+    }
+    wait_for(&FINISHED, NUM_TASKS);
+    assert_eq!(SEM.value(), PERMITS);
+    assert!(SEM.acquire_timeout(Duration::from_secs(60)));
+    assert_eq!(SEM.value(), PERMITS - 1);
+    SEM.release();
+}
+
+#[test]
+fn test_barrier() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    const NUM_TASKS: usize = 5;
+    const NUM_ROUNDS: usize = 3;
+    static BARRIER: Barrier = Barrier::new(NUM_TASKS);
+    static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+    static LEADERS: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    for _ in 0..NUM_TASKS {
+        thread::spawn(|| {
+            for round in 1..=NUM_ROUNDS {
+                ARRIVED.fetch_add(1, Ordering::Relaxed);
+                if BARRIER.wait().is_leader() {
+                    LEADERS.fetch_add(1, Ordering::Relaxed);
+                }
+                assert!(ARRIVED.load(Ordering::Relaxed) >= round * NUM_TASKS);
+            }
+            FINISHED.fetch_add(1, Ordering::Release);
+        });
+    }
+    wait_for(&FINISHED, NUM_TASKS);
+    assert_eq!(LEADERS.load(Ordering::Relaxed), NUM_ROUNDS);
+}
+
+#[test]
+fn test_once() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(thread::init_scheduler);
+
+    const NUM_TASKS: usize = 5;
+    static ONCE: Once = Once::new();
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    for _ in 0..NUM_TASKS {
+        thread::spawn(|| {
+            ONCE.call_once(|| {
+                thread::yield_now(); // let others wait for the initialization
+                CALLS.fetch_add(1, Ordering::Relaxed);
+            });
+            assert!(ONCE.is_completed());
+            assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+            FINISHED.fetch_add(1, Ordering::Release);
+        });
+    }
+    wait_for(&FINISHED, NUM_TASKS);
+}
@@ -2,10 +2,10 @@
 
 use alloc::{string::String, sync::Arc};
 
-pub(crate) use crate::run_queue::{AxRunQueue, RUN_QUEUE};
+pub(crate) use crate::run_queue::current_run_queue;
 
 #[doc(cfg(feature = "multitask"))]
-pub use crate::task::{CurrentTask, TaskId, TaskInner};
+pub use crate::task::{CpuMask, CurrentTask, TaskId, TaskInner, TaskState};
 #[doc(cfg(feature = "multitask"))]
 pub use crate::wait_queue::WaitQueue;
 
@@ -20,6 +20,10 @@ cfg_if::cfg_if! {
     } else if #[cfg(feature = "sched_cfs")] {
         pub(crate) type AxTask = scheduler::CFSTask<TaskInner>;
         pub(crate) type Scheduler = scheduler::CFScheduler<TaskInner>;
+    } else if #[cfg(feature = "sched_edf")] {
+        const TICK_NANOS: u64 = axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+        pub(crate) type AxTask = scheduler::EDFTask<TaskInner>;
+        pub(crate) type Scheduler = scheduler::EDFScheduler<TaskInner, TICK_NANOS>;
     } else {
         // If no scheduler features are set, use FIFO as the default.
         pub(crate) type AxTask = scheduler::FifoTask<TaskInner>;
@@ -75,6 +79,8 @@ pub fn init_scheduler() {
 /// Initializes the task scheduler for secondary CPUs.
 pub fn init_scheduler_secondary() {
     crate::run_queue::init_secondary();
+    #[cfg(feature = "irq")]
+    crate::timers::init();
 }
 
 /// Handles periodic timer ticks for the task manager.
@@ -84,18 +90,24 @@ pub fn init_scheduler_secondary() {
 #[doc(cfg(feature = "irq"))]
 pub fn on_timer_tick() {
     crate::timers::check_events();
-    RUN_QUEUE.lock().scheduler_timer_tick();
+    current_run_queue().scheduler_timer_tick();
 }
 
 /// Spawns a new task with the given parameters.
 ///
+/// The new task inherits the CPU affinity and blocked signals of the current
+/// task, and is put on the least loaded CPU it is allowed to run on.
+///
 /// Returns the task reference.
 pub fn spawn_raw<F>(f: F, name: String, stack_size: usize) -> AxTaskRef
 where
     F: FnOnce() + Send + 'static,
 {
     let task = TaskInner::new(f, name, stack_size);
-    RUN_QUEUE.lock().add_task(task.clone());
+    let curr = current();
+    task.set_cpumask(curr.cpumask());
+    task.signals().set_blocked(curr.signals().blocked());
+    crate::run_queue::spawn_task(task.clone());
     task
 }
 
@@ -112,6 +124,13 @@ where
     spawn_raw(f, "".into(), axconfig::TASK_STACK_SIZE)
 }
 
+/// Returns all tasks that have not been dropped, sorted by their IDs.
+///
+/// Exited tasks are included until all references to them are dropped.
+pub fn all_tasks() -> alloc::vec::Vec<AxTaskRef> {
+    crate::task::all_tasks()
+}
+
 /// Set the priority for current task.
 ///
 /// The range of the priority is dependent on the underlying scheduler. For
@@ -122,13 +141,26 @@ where
 ///
 /// [CFS]: https://en.wikipedia.org/wiki/Completely_Fair_Scheduler
 pub fn set_priority(prio: isize) -> bool {
-    RUN_QUEUE.lock().set_current_priority(prio)
+    current_run_queue().set_current_priority(prio)
+}
+
+/// Sets the CPU affinity of the given task, i.e., the CPUs it is allowed to
+/// run on.
+///
+/// If the task is on a CPU not in `cpumask`, it is moved to an allowed one:
+/// immediately if it is the current task or waiting in a run queue, and at
+/// its next reschedule or wakeup otherwise.
+///
+/// Returns `false` if `cpumask` contains no online CPU, or the task is a
+/// periodic real-time task, and the affinity is not changed.
+pub fn set_affinity(task: &AxTaskRef, cpumask: CpuMask) -> bool {
+    crate::run_queue::set_affinity(task, cpumask)
 }
 
 /// Current task gives up the CPU time voluntarily, and switches to another
 /// ready task.
 pub fn yield_now() {
-    RUN_QUEUE.lock().yield_current();
+    current_run_queue().yield_current();
 }
 
 /// Current task is going to sleep for the given duration.
@@ -143,14 +175,16 @@ pub fn sleep(dur: core::time::Duration) {
 /// If the feature `irq` is not enabled, it uses busy-wait instead.
 pub fn sleep_until(deadline: axhal::time::TimeValue) {
     #[cfg(feature = "irq")]
-    RUN_QUEUE.lock().sleep_until(deadline);
+    current_run_queue().sleep_until(deadline);
     #[cfg(not(feature = "irq"))]
     axhal::time::busy_wait_until(deadline);
 }
 
 /// Exits the current task.
 pub fn exit(exit_code: i32) -> ! {
-    RUN_QUEUE.lock().exit_current(exit_code)
+    #[cfg(feature = "irq")]
+    crate::timers::cancel_signal_timer(current().as_task_ref());
+    current_run_queue().exit_current(exit_code)
 }
 
 /// The idle task routine.
@@ -4,14 +4,20 @@
 //! creation, scheduling, sleeping, termination, etc. The scheduler algorithm
 //! is configurable by cargo features.
 //!
+//! Each CPU has its own run queue. New and woken tasks are put in the least
+//! loaded CPU allowed by their affinity (see [`set_affinity`]), and a CPU with
+//! nothing to run steals ready tasks from the most loaded one.
+//!
+//! Tasks can also send POSIX-like [signals](signal) to each other.
+//!
 //! # Cargo Features
 //!
 //! - `multitask`: Enable multi-task support. If it's enabled, complex task
 //!   management and scheduling is used, as well as more task-related APIs.
 //!   Otherwise, only a few APIs with naive implementation is available.
 //! - `irq`: Interrupts are enabled. If this feature is enabled, timer-based
-//!    APIs can be used, such as [`sleep`], [`sleep_until`], and
-//!    [`WaitQueue::wait_timeout`].
+//!    APIs can be used, such as [`sleep`], [`sleep_until`],
+//!    [`WaitQueue::wait_timeout`], and [`signal::set_real_timer`].
 //! - `preempt`: Enable preemptive scheduling.
 //! - `sched_fifo`: Use the [FIFO cooperative scheduler][1]. It also enables the
 //!   `multitask` feature if it is enabled. This feature is enabled by default,
@@ -20,10 +26,14 @@
 //!   the `multitask` and `preempt` features if it is enabled.
 //! - `sched_cfs`: Use the [Completely Fair Scheduler][3]. It also enables the
 //!   the `multitask` and `preempt` features if it is enabled.
+//! - `sched_edf`: Use the [Earliest Deadline First scheduler][4] for periodic
+//!   real-time tasks (see [`spawn_periodic`]). It also enables the `multitask`
+//!   and `preempt` features if it is enabled.
 //!
 //! [1]: scheduler::FifoScheduler
 //! [2]: scheduler::RRScheduler
 //! [3]: scheduler::CFScheduler
+//! [4]: scheduler::EDFScheduler
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
@@ -40,12 +50,23 @@ cfg_if::cfg_if! {
         mod api;
         mod wait_queue;
 
+        #[doc(cfg(feature = "multitask"))]
+        pub mod signal;
+
         #[cfg(feature = "irq")]
         mod timers;
+        #[cfg(feature = "sched_edf")]
+        mod periodic;
+        // CFS is not tested on the host
+        #[cfg(all(test, not(feature = "sched_cfs")))]
+        mod tests;
 
         #[doc(cfg(feature = "multitask"))]
         pub use self::api::*;
         pub use self::api::{sleep, sleep_until, yield_now};
+        #[cfg(feature = "sched_edf")]
+        #[doc(cfg(feature = "sched_edf"))]
+        pub use self::periodic::{rt_utilization, spawn_periodic, RtParams};
     } else {
         mod api_s;
         pub use self::api_s::{sleep, sleep_until, yield_now};
@@ -0,0 +1,111 @@
+//! Periodic real-time tasks for the EDF scheduler.
+
+use alloc::{string::String, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use crate::{current, AxTaskRef, CpuMask, TaskInner};
+
+pub use scheduler::RtParams;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_UTILIZATION: AtomicU64 = AtomicU64::new(0);
+
+/// Utilization of the periodic tasks admitted on each CPU.
+static RT_UTILIZATION: [AtomicU64; axconfig::SMP] = [NO_UTILIZATION; axconfig::SMP];
+
+fn now_nanos() -> u64 {
+    axhal::time::current_time().as_nanos() as u64
+}
+
+/// Reserves the utilization for a new periodic task on the least utilized
+/// online CPU in `cpumask` that still fits it. Returns the CPU ID.
+fn admit(util: u64, cpumask: CpuMask) -> Option<usize> {
+    let mut cpus: Vec<_> = crate::run_queue::online_cpus()
+        .filter(|&cpu_id| cpumask.contains(cpu_id))
+        .collect();
+    cpus.sort_by_key(|&cpu_id| RT_UTILIZATION[cpu_id].load(Ordering::Acquire));
+    cpus.into_iter().find(|&cpu_id| {
+        RT_UTILIZATION[cpu_id]
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |total| {
+                Some(total + util).filter(|&new| new <= RtParams::FULL_UTILIZATION)
+            })
+            .is_ok()
+    })
+}
+
+/// Returns the utilization reserved by the periodic tasks on the CPU, where
+/// [`RtParams::FULL_UTILIZATION`] stands for the whole CPU.
+pub fn rt_utilization(cpu_id: usize) -> u64 {
+    RT_UTILIZATION
+        .get(cpu_id)
+        .map_or(0, |util| util.load(Ordering::Acquire))
+}
+
+/// Spawns a periodic real-time task.
+///
+/// The times in `params` are in nanoseconds. The first job is released
+/// immediately, and `f` is called once per period until it returns `false`.
+/// Each job is scheduled by its absolute deadline, and runs at most `budget`
+/// nanoseconds before the deadline is postponed by a period.
+///
+/// The task is admitted only if `params` is valid and it fits in a CPU
+/// allowed by the affinity of the current task, i.e., the total utilization
+/// of periodic tasks on that CPU does not exceed 1. Returns [`None`]
+/// otherwise. The task is pinned to the CPU, and its affinity cannot be
+/// changed. The reserved utilization is released when `f` returns `false`.
+///
+/// Jobs that complete after their deadlines are counted in
+/// [`deadline_misses`](scheduler::EDFTask::deadline_misses).
+pub fn spawn_periodic<F>(
+    mut f: F,
+    name: String,
+    stack_size: usize,
+    params: RtParams,
+) -> Option<AxTaskRef>
+where
+    F: FnMut() -> bool + Send + 'static,
+{
+    let util = params.utilization();
+    let curr = current();
+    let admitted = if params.is_valid() {
+        admit(util, curr.cpumask())
+    } else {
+        None
+    };
+    let Some(cpu_id) = admitted else {
+        warn!("periodic task {:?} is not admitted: {:?}", name, params);
+        return None;
+    };
+
+    let start = now_nanos();
+    let task = TaskInner::new(
+        move || {
+            let curr = current();
+            let task = curr.as_task_ref();
+            let mut release = start;
+            loop {
+                let more = f();
+                if !task.complete_job(now_nanos()) {
+                    warn!("task {} missed its deadline", curr.id_name());
+                }
+                if !more {
+                    break;
+                }
+                release += params.period;
+                // the next job must be released before it is enqueued again
+                task.release_job(release);
+                crate::sleep_until(Duration::from_nanos(release));
+            }
+            RT_UTILIZATION[cpu_id].fetch_sub(util, Ordering::AcqRel);
+        },
+        name,
+        stack_size,
+    );
+    task.set_rt_params(Some(params));
+    task.release_job(start);
+    task.set_cpumask(CpuMask::one(cpu_id));
+    task.signals().set_blocked(curr.signals().blocked());
+    crate::run_queue::spawn_task(task.clone());
+    Some(task)
+}
@@ -1,72 +1,154 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use kernel_guard::NoPreemptIrqSave;
 use lazy_init::LazyInit;
 use scheduler::BaseScheduler;
-use spinlock::SpinNoIrq;
+use spinlock::{SpinNoIrq, SpinRaw};
 
 use crate::task::{CurrentTask, TaskState};
-use crate::{AxTaskRef, Scheduler, TaskInner, WaitQueue};
+use crate::{AxTaskRef, CpuMask, Scheduler, TaskInner, WaitQueue};
 
-// TODO: per-CPU
-pub(crate) static RUN_QUEUE: LazyInit<SpinNoIrq<AxRunQueue>> = LazyInit::new();
+#[percpu::def_percpu]
+static RUN_QUEUE: LazyInit<AxRunQueue> = LazyInit::new();
 
-// TODO: per-CPU
-static EXITED_TASKS: SpinNoIrq<VecDeque<AxTaskRef>> = SpinNoIrq::new(VecDeque::new());
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_RUN_QUEUE: LazyInit<&'static AxRunQueue> = LazyInit::new();
 
-static WAIT_FOR_EXIT: WaitQueue = WaitQueue::new();
+/// Run queues of all CPUs indexed by CPU IDs, for the accesses from other
+/// CPUs (remote wakeups, load balancing and work stealing).
+static RUN_QUEUES: [LazyInit<&'static AxRunQueue>; axconfig::SMP] = [NO_RUN_QUEUE; axconfig::SMP];
 
 #[percpu::def_percpu]
 static IDLE_TASK: LazyInit<AxTaskRef> = LazyInit::new();
 
+/// The task that is switching out of this CPU, and whether it should be
+/// migrated to another CPU once switched out.
+#[percpu::def_percpu]
+static PREV_TASK: Option<(AxTaskRef, bool)> = None;
+
 pub(crate) struct AxRunQueue {
-    scheduler: Scheduler,
+    cpu_id: usize,
+    /// Number of runnable tasks on this CPU, including the running one but
+    /// not the idle task.
+    nr_tasks: AtomicUsize,
+    scheduler: SpinRaw<Scheduler>, // IRQs are always disabled when it's locked
+    /// Tasks exited on this CPU, to be dropped by its gc task.
+    exited_tasks: SpinNoIrq<VecDeque<AxTaskRef>>,
+    wait_for_exit: WaitQueue,
+}
+
+/// The run queue of the current CPU.
+///
+/// IRQs and preemption are disabled while it is held, so the current task
+/// stays on this CPU.
+pub(crate) struct CurrentRunQueueRef {
+    inner: &'static AxRunQueue,
+    _guard: NoPreemptIrqSave,
+}
+
+impl Deref for CurrentRunQueueRef {
+    type Target = AxRunQueue;
+    fn deref(&self) -> &Self::Target {
+        self.inner
+    }
+}
+
+/// Gets the run queue of the current CPU.
+pub(crate) fn current_run_queue() -> CurrentRunQueueRef {
+    let guard = NoPreemptIrqSave::new();
+    CurrentRunQueueRef {
+        // Safety: IRQs and preemption are disabled at this time.
+        inner: unsafe { RUN_QUEUE.current_ref_raw().get_unchecked() },
+        _guard: guard,
+    }
+}
+
+/// Returns the run queues of all online CPUs.
+fn online_run_queues() -> impl Iterator<Item = &'static AxRunQueue> {
+    RUN_QUEUES.iter().filter_map(|rq| rq.try_get().copied())
+}
+
+/// Returns the IDs of all online CPUs.
+#[cfg(feature = "sched_edf")]
+pub(crate) fn online_cpus() -> impl Iterator<Item = usize> {
+    online_run_queues().map(|rq| rq.cpu_id)
+}
+
+/// Selects the run queue to put a ready task in: the least loaded one among
+/// the CPUs allowed by its affinity, preferring the current CPU on ties.
+fn select_run_queue(task: &AxTaskRef) -> &'static AxRunQueue {
+    let this_cpu = axhal::cpu::this_cpu_id();
+    let cpumask = task.cpumask();
+    online_run_queues()
+        .filter(|rq| cpumask.contains(rq.cpu_id))
+        .min_by_key(|rq| (rq.load(), rq.cpu_id != this_cpu))
+        .unwrap_or(*RUN_QUEUES[this_cpu])
 }
 
 impl AxRunQueue {
-    pub fn new() -> SpinNoIrq<Self> {
-        let gc_task = TaskInner::new(gc_entry, "gc".into(), axconfig::TASK_STACK_SIZE);
-        let mut scheduler = Scheduler::new();
-        scheduler.add_task(gc_task);
-        SpinNoIrq::new(Self { scheduler })
+    fn new(cpu_id: usize) -> Self {
+        Self {
+            cpu_id,
+            nr_tasks: AtomicUsize::new(0),
+            scheduler: SpinRaw::new(Scheduler::new()),
+            exited_tasks: SpinNoIrq::new(VecDeque::new()),
+            wait_for_exit: WaitQueue::new(),
+        }
     }
 
-    pub fn add_task(&mut self, task: AxTaskRef) {
-        debug!("task spawn: {}", task.id_name());
-        assert!(task.is_ready());
-        self.scheduler.add_task(task);
+    fn load(&self) -> usize {
+        self.nr_tasks.load(Ordering::Relaxed)
+    }
+
+    /// Puts a ready task that is not running on any CPU in this run queue.
+    fn enqueue(&self, task: AxTaskRef) {
+        let mut scheduler = self.scheduler.lock();
+        task.set_rq_cpu(Some(self.cpu_id));
+        scheduler.add_task(task); // TODO: priority
+        self.nr_tasks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn pick_next_task(&self) -> Option<AxTaskRef> {
+        let mut scheduler = self.scheduler.lock();
+        let task = scheduler.pick_next_task()?;
+        task.set_rq_cpu(None);
+        Some(task)
     }
 
     #[cfg(feature = "irq")]
-    pub fn scheduler_timer_tick(&mut self) {
+    pub fn scheduler_timer_tick(&self) {
         let curr = crate::current();
-        if !curr.is_idle() && self.scheduler.task_tick(curr.as_task_ref()) {
+        if !curr.is_idle() && self.scheduler.lock().task_tick(curr.as_task_ref()) {
             #[cfg(feature = "preempt")]
             curr.set_preempt_pending(true);
         }
     }
 
-    pub fn yield_current(&mut self) {
+    pub fn yield_current(&self) {
         let curr = crate::current();
         trace!("task yield: {}", curr.id_name());
         assert!(curr.is_running());
         self.resched(false);
     }
 
-    pub fn set_current_priority(&mut self, prio: isize) -> bool {
+    pub fn set_current_priority(&self, prio: isize) -> bool {
         self.scheduler
+            .lock()
             .set_priority(crate::current().as_task_ref(), prio)
     }
 
     #[cfg(feature = "preempt")]
-    pub fn preempt_resched(&mut self) {
+    pub fn preempt_resched(&self) {
         let curr = crate::current();
         assert!(curr.is_running());
 
-        // When we get the mutable reference of the run queue, we must
-        // have held the `SpinNoIrq` lock with both IRQs and preemption
-        // disabled. So we need to set `current_disable_count` to 1 in
-        // `can_preempt()` to obtain the preemption permission before
-        //  locking the run queue.
+        // When we get the current run queue, we must have both IRQs and
+        // preemption disabled. So we need to set `current_disable_count` to
+        // 1 in `can_preempt()` to obtain the preemption permission before
+        // getting the run queue.
         let can_preempt = curr.can_preempt(1);
 
         debug!(
@@ -81,25 +163,27 @@ impl AxRunQueue {
         }
     }
 
-    pub fn exit_current(&mut self, exit_code: i32) -> ! {
+    pub fn exit_current(&self, exit_code: i32) -> ! {
         let curr = crate::current();
         debug!("task exit: {}, exit_code={}", curr.id_name(), exit_code);
         assert!(curr.is_running());
         assert!(!curr.is_idle());
         if curr.is_init() {
-            EXITED_TASKS.lock().clear();
+            self.exited_tasks.lock().clear();
             axhal::misc::terminate();
         } else {
             curr.set_state(TaskState::Exited);
-            curr.notify_exit(exit_code, self);
-            EXITED_TASKS.lock().push_back(curr.clone());
-            WAIT_FOR_EXIT.notify_one_locked(false, self);
+            curr.notify_exit(exit_code);
+            self.exited_tasks.lock().push_back(curr.clone());
+            self.wait_for_exit.notify_one(false);
             self.resched(false);
         }
         unreachable!("task exited!");
     }
 
-    pub fn block_current<F>(&mut self, wait_queue_push: F)
+    /// Blocks the current task, `wait_queue_push` is called after it is
+    /// marked as blocked, so any wakeup after that will not be lost.
+    pub fn block_current<F>(&self, wait_queue_push: F)
     where
         F: FnOnce(AxTaskRef),
     {
@@ -108,29 +192,53 @@ impl AxRunQueue {
         assert!(curr.is_running());
         assert!(!curr.is_idle());
 
+        curr.set_state(TaskState::Blocked);
+        wait_queue_push(curr.clone());
+
         // we must not block current task with preemption disabled.
         #[cfg(feature = "preempt")]
         assert!(curr.can_preempt(1));
 
-        curr.set_state(TaskState::Blocked);
-        wait_queue_push(curr.clone());
         self.resched(false);
     }
 
-    pub fn unblock_task(&mut self, task: AxTaskRef, resched: bool) {
-        debug!("task unblock: {}", task.id_name());
-        if task.is_blocked() {
-            task.set_state(TaskState::Ready);
-            self.scheduler.add_task(task); // TODO: priority
-            if resched {
-                #[cfg(feature = "preempt")]
-                crate::current().set_preempt_pending(true);
+    /// Blocks the current task unless `condition` is true, returns the result
+    /// of `condition`.
+    ///
+    /// The current task is marked blocked and put into a wait queue by
+    /// `wait_queue_push` before `condition` is checked, so a notification
+    /// after the check wakes it up, and no lock is needed around the check.
+    pub fn block_current_unless<F, C>(&self, wait_queue_push: F, condition: C) -> bool
+    where
+        F: FnOnce(AxTaskRef),
+        C: FnOnce() -> bool,
+    {
+        let curr = crate::current();
+        assert!(curr.is_running());
+        assert!(!curr.is_idle());
+
+        curr.set_state(TaskState::Blocked);
+        wait_queue_push(curr.clone());
+        if condition() {
+            // If the task has been woken up meanwhile, it's being put in a run
+            // queue, so it must be switched out first.
+            if !curr.transition_state(TaskState::Blocked, TaskState::Running) {
+                self.resched(false);
             }
+            return true;
         }
+        debug!("task block: {}", curr.id_name());
+
+        // we must not block current task with preemption disabled.
+        #[cfg(feature = "preempt")]
+        assert!(curr.can_preempt(1));
+
+        self.resched(false);
+        false
     }
 
     #[cfg(feature = "irq")]
-    pub fn sleep_until(&mut self, deadline: axhal::time::TimeValue) {
+    pub fn sleep_until(&self, deadline: axhal::time::TimeValue) {
         let curr = crate::current();
         debug!("task sleep: {}, deadline={:?}", curr.id_name(), deadline);
         assert!(curr.is_running());
@@ -138,8 +246,8 @@ impl AxRunQueue {
 
         let now = axhal::time::current_time();
         if now < deadline {
-            crate::timers::set_alarm_wakeup(deadline, curr.clone());
             curr.set_state(TaskState::Blocked);
+            crate::timers::set_alarm_wakeup(deadline, curr.clone());
             self.resched(false);
         }
     }
@@ -148,22 +256,67 @@ impl AxRunQueue {
 impl AxRunQueue {
     /// Common reschedule subroutine. If `preempt`, keep current task's time
     /// slice, otherwise reset it.
-    fn resched(&mut self, preempt: bool) {
+    fn resched(&self, preempt: bool) {
         let prev = crate::current();
+        let mut migrate = false;
         if prev.is_running() {
             prev.set_state(TaskState::Ready);
             if !prev.is_idle() {
-                self.scheduler.put_prev_task(prev.clone(), preempt);
+                if prev.cpumask().contains(self.cpu_id) {
+                    let mut scheduler = self.scheduler.lock();
+                    prev.set_rq_cpu(Some(self.cpu_id));
+                    scheduler.put_prev_task(prev.clone(), preempt);
+                } else {
+                    // The affinity has been changed, but the task can only
+                    // be put in another run queue after it is switched out.
+                    self.nr_tasks.fetch_sub(1, Ordering::Relaxed);
+                    migrate = true;
+                }
             }
+        } else if !prev.is_idle() {
+            // blocked or exited
+            self.nr_tasks.fetch_sub(1, Ordering::Relaxed);
         }
-        let next = self.scheduler.pick_next_task().unwrap_or_else(|| unsafe {
-            // Safety: IRQs must be disabled at this time.
-            IDLE_TASK.current_ref_raw().get_unchecked().clone()
-        });
-        self.switch_to(prev, next);
+        let next = self
+            .pick_next_task()
+            .or_else(|| self.steal_task())
+            .unwrap_or_else(|| unsafe {
+                // Safety: IRQs must be disabled at this time.
+                IDLE_TASK.current_ref_raw().get_unchecked().clone()
+            });
+        self.switch_to(prev, next, migrate);
+    }
+
+    /// Takes a ready task that is allowed to run on this CPU from the most
+    /// loaded CPU, when there is nothing else to run on this CPU.
+    fn steal_task(&self) -> Option<AxTaskRef> {
+        let victim = online_run_queues()
+            .filter(|rq| rq.cpu_id != self.cpu_id)
+            .max_by_key(|rq| rq.load())?;
+        if victim.load() < 2 {
+            return None; // leave the running task alone
+        }
+        let mut scheduler = victim.scheduler.lock();
+        let task = scheduler.pick_next_task()?;
+        if !task.cpumask().contains(self.cpu_id) {
+            scheduler.put_prev_task(task, true);
+            return None;
+        }
+        task.set_rq_cpu(None);
+        victim.nr_tasks.fetch_sub(1, Ordering::Relaxed);
+        drop(scheduler);
+
+        debug!(
+            "task steal: {} from CPU {} to CPU {}",
+            task.id_name(),
+            victim.cpu_id,
+            self.cpu_id
+        );
+        self.nr_tasks.fetch_add(1, Ordering::Relaxed);
+        Some(task)
     }
 
-    fn switch_to(&mut self, prev_task: CurrentTask, next_task: AxTaskRef) {
+    fn switch_to(&self, prev_task: CurrentTask, next_task: AxTaskRef, migrate: bool) {
         trace!(
             "context switch: {} -> {}",
             prev_task.id_name(),
@@ -176,6 +329,12 @@ impl AxRunQueue {
             return;
         }
 
+        // A task stolen from another CPU may still be switching out of it.
+        while next_task.on_cpu() {
+            core::hint::spin_loop();
+        }
+        next_task.set_on_cpu(true);
+
         unsafe {
             let prev_ctx_ptr = prev_task.ctx_mut_ptr();
             let next_ctx_ptr = next_task.ctx_mut_ptr();
@@ -185,19 +344,116 @@ impl AxRunQueue {
             assert!(Arc::strong_count(prev_task.as_task_ref()) > 1);
             assert!(Arc::strong_count(&next_task) >= 1);
 
+            *PREV_TASK.current_ref_mut_raw() = Some((prev_task.clone(), migrate));
             CurrentTask::set_current(prev_task, next_task);
             (*prev_ctx_ptr).switch_to(&*next_ctx_ptr);
+            finish_switch();
         }
     }
 }
 
-fn gc_entry() {
+/// Completes the context switch on the next task, after the previous task
+/// is switched out and its context is saved.
+///
+/// # Safety
+///
+/// IRQs must be disabled.
+pub(crate) unsafe fn finish_switch() {
+    if let Some((prev, migrate)) = PREV_TASK.current_ref_mut_raw().take() {
+        prev.set_on_cpu(false);
+        if migrate {
+            let rq = select_run_queue(&prev);
+            debug!("task migrate: {} to CPU {}", prev.id_name(), rq.cpu_id);
+            rq.enqueue(prev);
+        }
+    }
+}
+
+/// Puts a newly spawned task in a run queue.
+pub(crate) fn spawn_task(task: AxTaskRef) {
+    let _guard = NoPreemptIrqSave::new();
+    let rq = select_run_queue(&task);
+    debug!("task spawn: {} on CPU {}", task.id_name(), rq.cpu_id);
+    assert!(task.is_ready());
+    rq.enqueue(task);
+}
+
+/// Wakes up a blocked task, and puts it in a run queue.
+///
+/// If `resched` is true and the task is put in the current CPU, the current
+/// task will be preempted when the preemption is enabled.
+///
+/// IRQs must be disabled.
+pub(crate) fn unblock_task(task: AxTaskRef, resched: bool) {
+    if task.transition_state(TaskState::Blocked, TaskState::Ready) {
+        debug!("task unblock: {}", task.id_name());
+        // The task can only be run after it is switched out.
+        while task.on_cpu() {
+            core::hint::spin_loop();
+        }
+        let rq = select_run_queue(&task);
+        rq.enqueue(task);
+        if resched && rq.cpu_id == axhal::cpu::this_cpu_id() {
+            #[cfg(feature = "preempt")]
+            crate::current().set_preempt_pending(true);
+        }
+    }
+}
+
+/// Sets the CPU affinity of a task, and moves it to an allowed CPU if it is
+/// ready in the run queue of another CPU.
+///
+/// A task running on another CPU is moved at its next reschedule, and a
+/// blocked task is moved when it is woken up.
+pub(crate) fn set_affinity(task: &AxTaskRef, cpumask: CpuMask) -> bool {
+    if !online_run_queues().any(|rq| cpumask.contains(rq.cpu_id)) {
+        return false;
+    }
+    // periodic tasks are pinned to the CPUs they are admitted on
+    #[cfg(feature = "sched_edf")]
+    if task.rt_params().is_some() {
+        return false;
+    }
+    let rq = current_run_queue();
+    task.set_cpumask(cpumask);
+    if Arc::ptr_eq(task, crate::current().as_task_ref()) {
+        if !cpumask.contains(rq.cpu_id) {
+            rq.resched(false);
+        }
+        return true;
+    }
+
+    let Some(cpu_id) = task.rq_cpu() else {
+        return true;
+    };
+    if cpumask.contains(cpu_id) {
+        return true;
+    }
+    let src = *RUN_QUEUES[cpu_id];
+    let mut scheduler = src.scheduler.lock();
+    // Check again with the run queue locked, and skip the task switching out
+    // of a CPU, which will be migrated at its next reschedule.
+    if task.rq_cpu() != Some(cpu_id) || task.on_cpu() {
+        return true;
+    }
+    if let Some(task) = scheduler.remove_task(task) {
+        task.set_rq_cpu(None);
+        src.nr_tasks.fetch_sub(1, Ordering::Relaxed);
+        drop(scheduler);
+        let dst = select_run_queue(&task);
+        debug!("task migrate: {} to CPU {}", task.id_name(), dst.cpu_id);
+        dst.enqueue(task);
+    }
+    true
+}
+
+fn gc_entry(rq: &'static AxRunQueue) {
     loop {
         // Drop all exited tasks and recycle resources.
-        let n = EXITED_TASKS.lock().len();
+        let n = rq.exited_tasks.lock().len();
         for _ in 0..n {
             // Do not do the slow drops in the critical section.
-            let task = EXITED_TASKS.lock().pop_front();
+            let task = rq.exited_tasks.lock().pop_front();
             if let Some(task) = task {
                 if Arc::strong_count(&task) == 1 {
                     // If I'm the last holder of the task, drop it immediately.
@@ -205,14 +461,31 @@ fn gc_entry() {
                 } else {
                     // Otherwise (e.g, `switch_to` is not compeleted, held by the
                     // joiner, etc), push it back and wait for them to drop first.
-                    EXITED_TASKS.lock().push_back(task);
+                    rq.exited_tasks.lock().push_back(task);
                 }
             }
         }
-        WAIT_FOR_EXIT.wait();
+        rq.wait_for_exit.wait();
     }
 }
 
+/// Spawns the gc task of the current CPU, which is pinned to it and drops
+/// the tasks exited on it.
+fn spawn_gc_task(rq: &'static AxRunQueue) {
+    let gc_task = TaskInner::new(move || gc_entry(rq), "gc".into(), axconfig::TASK_STACK_SIZE);
+    gc_task.set_cpumask(CpuMask::one(rq.cpu_id));
+    current_run_queue().enqueue(gc_task);
+}
+
+fn init_run_queue() -> &'static AxRunQueue {
+    let cpu_id = axhal::cpu::this_cpu_id();
+    RUN_QUEUE.with_current(|rq| rq.init_by(AxRunQueue::new(cpu_id)));
+    // Safety: the run queue is initialized above and will never be moved.
+    let rq = unsafe { RUN_QUEUE.current_ref_raw().get_unchecked() };
+    RUN_QUEUES[cpu_id].init_by(rq);
+    rq
+}
+
 pub(crate) fn init() {
     const IDLE_TASK_STACK_SIZE: usize = 4096;
     let idle_task = TaskInner::new(|| crate::run_idle(), "idle".into(), IDLE_TASK_STACK_SIZE);
@@ -220,14 +493,21 @@ pub(crate) fn init() {
 
     let main_task = TaskInner::new_init("main".into());
     main_task.set_state(TaskState::Running);
+    main_task.set_on_cpu(true);
 
-    RUN_QUEUE.init_by(AxRunQueue::new());
+    let rq = init_run_queue();
+    rq.nr_tasks.fetch_add(1, Ordering::Relaxed); // the main task
     unsafe { CurrentTask::init_current(main_task) }
+    spawn_gc_task(rq);
 }
 
 pub(crate) fn init_secondary() {
     let idle_task = TaskInner::new_init("idle".into());
     idle_task.set_state(TaskState::Running);
+    idle_task.set_on_cpu(true);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
+
+    let rq = init_run_queue();
     unsafe { CurrentTask::init_current(idle_task) }
+    spawn_gc_task(rq);
 }
@@ -0,0 +1,546 @@
+//! POSIX-like signals of tasks.
+//!
+//! Each task has its own pending and blocked signals, while the actions of
+//! signals are shared by all tasks, like threads of a process.
+//!
+//! Signals are not delivered asynchronously. Pending signals that are not
+//! blocked are delivered to the current task when it calls
+//! [`handle_signals`], which should be done at the points where no kernel
+//! locks are held, such as on return from system calls and at yield points.
+
+use core::ffi::{c_int, c_void};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::task::Waker;
+use core::time::Duration;
+
+use axhal::time::{current_time, TimeValue};
+use spinlock::SpinNoIrq;
+
+use crate::{AxTaskRef, WaitQueue};
+
+/// The number of signals, which are numbered from 1 to `NSIG`.
+pub const NSIG: usize = 64;
+
+/// Hangup.
+pub const SIGHUP: usize = 1;
+/// Interrupt from keyboard.
+pub const SIGINT: usize = 2;
+/// Abort.
+pub const SIGABRT: usize = 6;
+/// Kill, which can't be caught, blocked or ignored.
+pub const SIGKILL: usize = 9;
+/// User-defined signal 1.
+pub const SIGUSR1: usize = 10;
+/// User-defined signal 2.
+pub const SIGUSR2: usize = 12;
+/// Broken pipe.
+pub const SIGPIPE: usize = 13;
+/// Timer signal from [`set_real_timer`].
+pub const SIGALRM: usize = 14;
+/// Termination.
+pub const SIGTERM: usize = 15;
+/// Child stopped or terminated.
+pub const SIGCHLD: usize = 17;
+/// Continue if stopped.
+pub const SIGCONT: usize = 18;
+/// Stop, which can't be caught, blocked or ignored.
+pub const SIGSTOP: usize = 19;
+/// Stop typed at terminal.
+pub const SIGTSTP: usize = 20;
+/// Terminal input for background process.
+pub const SIGTTIN: usize = 21;
+/// Terminal output for background process.
+pub const SIGTTOU: usize = 22;
+/// Urgent condition on socket.
+pub const SIGURG: usize = 23;
+/// Window resize.
+pub const SIGWINCH: usize = 28;
+
+/// Sent by a task (`kill`).
+pub const SI_USER: i32 = 0;
+/// Sent by the kernel, e.g., by timers.
+pub const SI_KERNEL: i32 = 128;
+/// Sent to a specific thread (`pthread_kill`).
+pub const SI_TKILL: i32 = -6;
+
+/// Don't block the signal in its own handler.
+pub const SA_NODEFER: u32 = 0x4000_0000;
+/// Reset the action to the default one before calling the handler.
+pub const SA_RESETHAND: u32 = 0x8000_0000;
+
+/// A set of signals, where signal `i` is in the set if bit `i - 1` is set.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SignalSet(u64);
+
+/// What to do when a signal is delivered.
+#[derive(Debug, Clone, Copy)]
+pub enum SignalHandler {
+    /// Take the default action, which terminates the system (as the only
+    /// process) for most signals, and ignores the others.
+    Default,
+    /// Ignore the signal.
+    Ignore,
+    /// Call the function with the signal number.
+    Handler(unsafe extern "C" fn(c_int)),
+    /// Call the function with the signal number and the [`SignalInfo`]
+    /// (`SA_SIGINFO`). The third argument is always null.
+    SigAction(unsafe extern "C" fn(c_int, *mut SignalInfo, *mut c_void)),
+}
+
+/// The action of a signal.
+#[derive(Debug, Clone, Copy)]
+pub struct SignalAction {
+    /// What to do when the signal is delivered.
+    pub handler: SignalHandler,
+    /// Signals that are blocked during the handler, in addition to the
+    /// signal itself (unless [`SA_NODEFER`] is set).
+    pub mask: SignalSet,
+    /// Flags like `sa_flags` of `struct sigaction`, where only
+    /// [`SA_NODEFER`] and [`SA_RESETHAND`] have effects.
+    pub flags: u32,
+}
+
+/// Information about a delivered signal, which has the same layout as
+/// `siginfo_t` on 64-bit Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SignalInfo {
+    /// The signal number.
+    pub signo: i32,
+    /// Always 0.
+    pub errno: i32,
+    /// Where the signal comes from, e.g., [`SI_USER`].
+    pub code: i32,
+    _pad0: i32,
+    /// ID of the sending task, or 0 if sent by the kernel.
+    pub pid: i32,
+    /// Always 0.
+    pub uid: u32,
+    _pad1: [u64; 13],
+}
+
+/// The sender of a pending signal.
+#[derive(Clone, Copy)]
+struct Sender {
+    code: i32,
+    pid: i32,
+}
+
+/// The real-time interval timer of a task, which sends [`SIGALRM`].
+#[cfg(feature = "irq")]
+struct RealTimer {
+    deadline: Option<TimeValue>,
+    interval: Duration,
+}
+
+/// Signal states of a task.
+pub(crate) struct TaskSignals {
+    pending: AtomicU64,
+    blocked: AtomicU64,
+    senders: SpinNoIrq<[Sender; NSIG]>,
+    /// Woken when a signal is sent to the task.
+    wq: WaitQueue,
+    /// Also woken when a signal is sent to the task, see [`set_signal_waker`].
+    waker: SpinNoIrq<Option<Waker>>,
+    #[cfg(feature = "irq")]
+    timer: SpinNoIrq<RealTimer>,
+}
+
+/// Actions of all signals, shared by all tasks.
+static SIGNAL_ACTIONS: SpinNoIrq<[SignalAction; NSIG]> =
+    SpinNoIrq::new([SignalAction::DEFAULT; NSIG]);
+
+impl SignalSet {
+    /// Signals that can't be blocked.
+    const UNBLOCKABLE: Self = Self(1 << (SIGKILL - 1) | 1 << (SIGSTOP - 1));
+
+    /// Creates an empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Creates a set from the raw bits.
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits of the set.
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Whether the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether the signal `sig` is in the set.
+    pub const fn contains(&self, sig: usize) -> bool {
+        is_valid(sig) && self.0 & (1 << (sig - 1)) != 0
+    }
+
+    /// Adds the signal `sig` to the set. Invalid signals are ignored.
+    pub fn add(&mut self, sig: usize) {
+        if is_valid(sig) {
+            self.0 |= 1 << (sig - 1);
+        }
+    }
+
+    /// Removes the signal `sig` from the set.
+    pub fn remove(&mut self, sig: usize) {
+        if is_valid(sig) {
+            self.0 &= !(1 << (sig - 1));
+        }
+    }
+}
+
+impl SignalAction {
+    /// The default action.
+    pub const DEFAULT: Self = Self {
+        handler: SignalHandler::Default,
+        mask: SignalSet::empty(),
+        flags: 0,
+    };
+}
+
+impl SignalInfo {
+    fn new(sig: usize, sender: Sender) -> Self {
+        Self {
+            signo: sig as i32,
+            errno: 0,
+            code: sender.code,
+            _pad0: 0,
+            pid: sender.pid,
+            uid: 0,
+            _pad1: [0; 13],
+        }
+    }
+}
+
+impl TaskSignals {
+    pub fn new() -> Self {
+        Self {
+            pending: AtomicU64::new(0),
+            blocked: AtomicU64::new(0),
+            senders: SpinNoIrq::new([Sender { code: 0, pid: 0 }; NSIG]),
+            wq: WaitQueue::new(),
+            waker: SpinNoIrq::new(None),
+            #[cfg(feature = "irq")]
+            timer: SpinNoIrq::new(RealTimer {
+                deadline: None,
+                interval: Duration::ZERO,
+            }),
+        }
+    }
+
+    pub fn blocked(&self) -> SignalSet {
+        SignalSet(self.blocked.load(Ordering::Acquire))
+    }
+
+    /// Sets the blocked signals, and returns the old ones.
+    pub fn set_blocked(&self, mask: SignalSet) -> SignalSet {
+        let bits = mask.0 & !SignalSet::UNBLOCKABLE.0;
+        SignalSet(self.blocked.swap(bits, Ordering::AcqRel))
+    }
+
+    /// Whether there are pending signals that are not blocked.
+    fn has_deliverable(&self) -> bool {
+        self.pending.load(Ordering::Acquire) & !self.blocked.load(Ordering::Acquire) != 0
+    }
+
+    /// Makes the signal pending. A signal that is already pending is not
+    /// queued again.
+    fn send(&self, sig: usize, sender: Sender) {
+        let bit = 1 << (sig - 1);
+        let mut senders = self.senders.lock();
+        if self.pending.load(Ordering::Acquire) & bit == 0 {
+            senders[sig - 1] = sender;
+            self.pending.fetch_or(bit, Ordering::AcqRel);
+        }
+        drop(senders);
+        self.wq.notify_all(false);
+        if let Some(waker) = self.waker.lock().as_ref() {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Takes the lowest pending signal that is not blocked.
+    fn take_deliverable(&self) -> Option<SignalInfo> {
+        let senders = self.senders.lock();
+        let ready = self.pending.load(Ordering::Acquire) & !self.blocked.load(Ordering::Acquire);
+        if ready == 0 {
+            return None;
+        }
+        let sig = ready.trailing_zeros() as usize + 1;
+        self.pending.fetch_and(!(1 << (sig - 1)), Ordering::AcqRel);
+        Some(SignalInfo::new(sig, senders[sig - 1]))
+    }
+}
+
+/// Whether `sig` is a valid signal number.
+pub const fn is_valid(sig: usize) -> bool {
+    sig.wrapping_sub(1) < NSIG
+}
+
+/// Whether the default action of the signal is to ignore it.
+///
+/// Stopping tasks is not supported, so stop signals are ignored as well.
+fn ignored_by_default(sig: usize) -> bool {
+    matches!(
+        sig,
+        SIGCHLD | SIGCONT | SIGURG | SIGWINCH | SIGSTOP | SIGTSTP | SIGTTIN | SIGTTOU
+    )
+}
+
+/// Returns the action of the signal `sig`.
+///
+/// # Panics
+///
+/// Panics if `sig` is not a valid signal number.
+pub fn signal_action(sig: usize) -> SignalAction {
+    assert!(is_valid(sig), "invalid signal {}", sig);
+    SIGNAL_ACTIONS.lock()[sig - 1]
+}
+
+/// Sets the action of the signal `sig` for all tasks, and returns the old
+/// one.
+///
+/// It's up to the caller to reject changing the actions of [`SIGKILL`] and
+/// [`SIGSTOP`].
+///
+/// # Panics
+///
+/// Panics if `sig` is not a valid signal number.
+pub fn set_signal_action(sig: usize, action: SignalAction) -> SignalAction {
+    assert!(is_valid(sig), "invalid signal {}", sig);
+    core::mem::replace(&mut SIGNAL_ACTIONS.lock()[sig - 1], action)
+}
+
+/// Returns the signals blocked by the current task.
+pub fn signal_mask() -> SignalSet {
+    crate::current().signals().blocked()
+}
+
+/// Sets the signals blocked by the current task, and returns the old ones.
+///
+/// [`SIGKILL`] and [`SIGSTOP`] can't be blocked, and are removed from the
+/// set silently.
+pub fn set_signal_mask(mask: SignalSet) -> SignalSet {
+    crate::current().signals().set_blocked(mask)
+}
+
+/// Returns the pending signals of the current task.
+pub fn pending_signals() -> SignalSet {
+    SignalSet(crate::current().signals().pending.load(Ordering::Acquire))
+}
+
+/// Whether the current task has pending signals that are not blocked, which
+/// should interrupt its blocking operations.
+pub fn signal_pending() -> bool {
+    crate::current().signals().has_deliverable()
+}
+
+/// Sets the waker that is woken when a signal is sent to the current task,
+/// and returns the old one.
+///
+/// It's for the tasks that are blocked on something other than signals, and
+/// need to be interrupted by them.
+pub fn set_signal_waker(waker: Option<Waker>) -> Option<Waker> {
+    core::mem::replace(&mut *crate::current().signals().waker.lock(), waker)
+}
+
+/// Sends the signal `sig` to the task, with `code` as the origin (e.g.,
+/// [`SI_USER`]).
+///
+/// The signal is delivered when the task calls [`handle_signals`], and the
+/// task is woken up if it's waiting for signals by [`suspend`] or
+/// [`interruptible_sleep_until`]. It can be called in interrupt contexts.
+///
+/// Returns `false` if `sig` is not a valid signal number.
+pub fn send_signal(task: &AxTaskRef, sig: usize, code: i32) -> bool {
+    if !is_valid(sig) {
+        return false;
+    }
+    let pid = match code {
+        SI_KERNEL => 0,
+        _ => crate::current_may_uninit().map_or(0, |curr| curr.id().as_u64() as i32),
+    };
+    debug!("send signal {} to {}", sig, task.id_name());
+    task.signals().send(sig, Sender { code, pid });
+    true
+}
+
+/// Delivers the pending signals of the current task that are not blocked.
+///
+/// Signals with handlers are handled by calling the handlers on the current
+/// stack, with the signals in the action masks blocked. Signals with the
+/// default actions terminate the system, except those ignored by default.
+pub fn handle_signals() {
+    let Some(curr) = crate::current_may_uninit() else {
+        return;
+    };
+    let signals = curr.signals();
+    if !signals.has_deliverable() {
+        return;
+    }
+    while let Some(mut info) = signals.take_deliverable() {
+        let sig = info.signo as usize;
+        let action = {
+            let mut actions = SIGNAL_ACTIONS.lock();
+            let action = actions[sig - 1];
+            if action.flags & SA_RESETHAND != 0 {
+                actions[sig - 1] = SignalAction::DEFAULT;
+            }
+            action
+        };
+        debug!("deliver signal {} to {}", sig, curr.id_name());
+
+        match action.handler {
+            SignalHandler::Ignore => {}
+            SignalHandler::Default if ignored_by_default(sig) => {}
+            SignalHandler::Default => {
+                warn!("{} is terminated by signal {}", curr.id_name(), sig);
+                axhal::misc::terminate();
+            }
+            SignalHandler::Handler(f) => {
+                with_action_mask(signals, sig, &action, || unsafe { f(sig as c_int) })
+            }
+            SignalHandler::SigAction(f) => with_action_mask(signals, sig, &action, || unsafe {
+                f(sig as c_int, &mut info, core::ptr::null_mut())
+            }),
+        }
+    }
+}
+
+/// Calls the signal handler `f` with the signals in the action mask blocked.
+fn with_action_mask<F: FnOnce()>(signals: &TaskSignals, sig: usize, action: &SignalAction, f: F) {
+    let old_mask = signals.blocked();
+    let mut mask = SignalSet(old_mask.0 | action.mask.0);
+    if action.flags & SA_NODEFER == 0 {
+        mask.add(sig);
+    }
+    signals.set_blocked(mask);
+    f();
+    signals.set_blocked(old_mask);
+}
+
+/// Replaces the blocked signals of the current task with `mask`, blocks
+/// until a signal that is not blocked is pending, handles the signals, and
+/// restores the blocked signals (like `sigsuspend`).
+pub fn suspend(mask: SignalSet) {
+    let curr = crate::current();
+    let signals = curr.signals();
+    let old_mask = signals.set_blocked(mask);
+    signals.wq.wait_until(|| signals.has_deliverable());
+    handle_signals();
+    signals.set_blocked(old_mask);
+}
+
+/// Current task is going to sleep until the given deadline, or a signal that
+/// is not blocked is pending.
+///
+/// Returns `true` if the sleep is interrupted by a signal. The signal is not
+/// handled until [`handle_signals`] is called.
+///
+/// If the feature `irq` is not enabled, it uses busy-wait instead.
+pub fn interruptible_sleep_until(deadline: TimeValue) -> bool {
+    let curr = crate::current();
+    let signals = curr.signals();
+    #[cfg(feature = "irq")]
+    {
+        let now = current_time();
+        if now < deadline {
+            signals
+                .wq
+                .wait_timeout_until(deadline - now, || signals.has_deliverable());
+        }
+        signals.has_deliverable()
+    }
+    #[cfg(not(feature = "irq"))]
+    {
+        while current_time() < deadline {
+            if signals.has_deliverable() {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+}
+
+/// Returns the time remaining until the real-time interval timer of the
+/// current task expires (zero if disarmed), and its interval.
+///
+/// Returns [`None`] if timers are not supported, i.e., the feature `irq` is
+/// not enabled.
+pub fn real_timer() -> Option<(Duration, Duration)> {
+    #[cfg(feature = "irq")]
+    {
+        let curr = crate::current();
+        let timer = curr.signals().timer.lock();
+        let remaining = timer
+            .deadline
+            .map_or(Duration::ZERO, |d| d.saturating_sub(current_time()));
+        Some((remaining, timer.interval))
+    }
+    #[cfg(not(feature = "irq"))]
+    None
+}
+
+/// Sets the real-time interval timer of the current task (like `setitimer`
+/// with `ITIMER_REAL`), and returns the old one like [`real_timer`].
+///
+/// The timer sends [`SIGALRM`] to the current task after `value`, and then
+/// every `interval` if it's not zero. A zero `value` disarms the timer.
+///
+/// Returns [`None`] if timers are not supported, i.e., the feature `irq` is
+/// not enabled.
+#[cfg_attr(not(feature = "irq"), allow(unused_variables))]
+pub fn set_real_timer(value: Duration, interval: Duration) -> Option<(Duration, Duration)> {
+    #[cfg(feature = "irq")]
+    {
+        let curr = crate::current();
+        let mut timer = curr.signals().timer.lock();
+        let now = current_time();
+        let old_remaining = timer
+            .deadline
+            .map_or(Duration::ZERO, |d| d.saturating_sub(now));
+        let old = (old_remaining, timer.interval);
+
+        crate::timers::cancel_signal_timer(curr.as_task_ref());
+        timer.interval = interval;
+        timer.deadline = if value.is_zero() {
+            None
+        } else {
+            crate::timers::set_signal_timer(now + value, curr.clone());
+            Some(now + value)
+        };
+        Some(old)
+    }
+    #[cfg(not(feature = "irq"))]
+    None
+}
+
+/// Called by the timer list when the real-time interval timer of the task
+/// expires.
+#[cfg(feature = "irq")]
+pub(crate) fn real_timer_expired(task: AxTaskRef, now: TimeValue) {
+    let mut timer = task.signals().timer.lock();
+    // The timer may have been reset after the event is taken out.
+    let Some(deadline) = timer.deadline.filter(|&d| d <= now) else {
+        return;
+    };
+    timer.deadline = if timer.interval.is_zero() {
+        None
+    } else {
+        let mut next = deadline + timer.interval;
+        if next <= now {
+            // skip the missed periods
+            next = now + timer.interval;
+        }
+        crate::timers::set_signal_timer(next, task.clone());
+        Some(next)
+    };
+    drop(timer);
+    send_signal(&task, SIGALRM, SI_KERNEL);
+}
@@ -1,33 +1,59 @@
-use alloc::{boxed::Box, string::String, sync::Arc};
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::ops::Deref;
-use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use core::{alloc::Layout, cell::UnsafeCell, fmt, ptr::NonNull};
 
-#[cfg(feature = "preempt")]
-use core::sync::atomic::AtomicUsize;
-
 #[cfg(feature = "tls")]
 use axhal::tls::TlsArea;
 
 use axhal::arch::TaskContext;
 use memory_addr::{align_up_4k, VirtAddr};
+use spinlock::SpinNoIrq;
 
-use crate::{AxRunQueue, AxTask, AxTaskRef, WaitQueue};
+use crate::signal::TaskSignals;
+use crate::{AxTask, AxTaskRef, WaitQueue};
 
 /// A unique identifier for a thread.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct TaskId(u64);
 
+/// A set of CPUs, where CPU `i` is in the set if bit `i` is set.
+///
+/// It is used as the CPU affinity of tasks, i.e., the CPUs that a task is
+/// allowed to run on. CPUs that do not exist are never in the set.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CpuMask(usize);
+
+const _: () = assert!(axconfig::SMP <= usize::BITS as usize, "too many CPUs");
+
+/// The value of [`TaskInner::rq_cpu`] if the task is not in any run queue.
+const NO_CPU: usize = usize::MAX;
+
 /// The possible states of a task.
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub(crate) enum TaskState {
+pub enum TaskState {
+    /// The task is running on a CPU.
     Running = 1,
+    /// The task is in the run queue, waiting to be scheduled.
     Ready = 2,
+    /// The task is waiting for an event (e.g., in a wait queue or sleeping).
     Blocked = 3,
+    /// The task has exited, but has not been dropped.
     Exited = 4,
 }
 
+/// All tasks that have not been dropped, indexed by their IDs.
+static TASK_TABLE: SpinNoIrq<BTreeMap<u64, Weak<AxTask>>> = SpinNoIrq::new(BTreeMap::new());
+
+/// Returns all tasks that have not been dropped, sorted by their IDs.
+pub(crate) fn all_tasks() -> Vec<AxTaskRef> {
+    let table = TASK_TABLE.lock();
+    table.values().filter_map(Weak::upgrade).collect()
+}
+
 /// The inner task structure.
 pub struct TaskInner {
     id: TaskId,
@@ -42,6 +68,13 @@ pub struct TaskInner {
     #[cfg(feature = "irq")]
     in_timer_list: AtomicBool,
 
+    /// The CPUs that the task is allowed to run on.
+    cpumask: AtomicUsize,
+    /// The CPU whose run queue the task is in, or [`NO_CPU`].
+    rq_cpu: AtomicUsize,
+    /// Whether the task is running on a CPU, or still switching out of it.
+    on_cpu: AtomicBool,
+
     #[cfg(feature = "preempt")]
     need_resched: AtomicBool,
     #[cfg(feature = "preempt")]
@@ -50,6 +83,8 @@ pub struct TaskInner {
     exit_code: AtomicI32,
     wait_for_exit: WaitQueue,
 
+    signals: TaskSignals,
+
     kstack: Option<TaskStack>,
     ctx: UnsafeCell<TaskContext>,
 
@@ -69,6 +104,42 @@ impl TaskId {
     }
 }
 
+impl CpuMask {
+    /// Creates a set from the raw bits, ignoring CPUs that do not exist.
+    pub const fn from_bits(bits: usize) -> Self {
+        Self(bits & Self::full().0)
+    }
+
+    /// Creates a set of all CPUs.
+    pub const fn full() -> Self {
+        Self(usize::MAX >> (usize::BITS as usize - axconfig::SMP))
+    }
+
+    /// Creates a set of only the given CPU.
+    pub const fn one(cpu_id: usize) -> Self {
+        if cpu_id < axconfig::SMP {
+            Self(1 << cpu_id)
+        } else {
+            Self(0)
+        }
+    }
+
+    /// Returns the raw bits of the set.
+    pub const fn bits(&self) -> usize {
+        self.0
+    }
+
+    /// Whether the given CPU is in the set.
+    pub const fn contains(&self, cpu_id: usize) -> bool {
+        cpu_id < axconfig::SMP && self.0 & (1 << cpu_id) != 0
+    }
+
+    /// Whether the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
 impl From<u8> for TaskState {
     #[inline]
     fn from(state: u8) -> Self {
@@ -96,6 +167,24 @@ impl TaskInner {
         self.name.as_str()
     }
 
+    /// Gets the current state of the task.
+    #[inline]
+    pub fn state(&self) -> TaskState {
+        self.state.load(Ordering::Acquire).into()
+    }
+
+    /// Whether the task is an idle task.
+    #[inline]
+    pub const fn is_idle(&self) -> bool {
+        self.is_idle
+    }
+
+    /// Gets the CPUs that the task is allowed to run on.
+    #[inline]
+    pub fn cpumask(&self) -> CpuMask {
+        CpuMask(self.cpumask.load(Ordering::Acquire))
+    }
+
     /// Get a combined string of the task ID and name.
     pub fn id_name(&self) -> alloc::string::String {
         alloc::format!("Task({}, {:?})", self.id.as_u64(), self.name)
@@ -124,12 +213,16 @@ impl TaskInner {
             in_wait_queue: AtomicBool::new(false),
             #[cfg(feature = "irq")]
             in_timer_list: AtomicBool::new(false),
+            cpumask: AtomicUsize::new(CpuMask::full().0),
+            rq_cpu: AtomicUsize::new(NO_CPU),
+            on_cpu: AtomicBool::new(false),
             #[cfg(feature = "preempt")]
             need_resched: AtomicBool::new(false),
             #[cfg(feature = "preempt")]
             preempt_disable_count: AtomicUsize::new(0),
             exit_code: AtomicI32::new(0),
             wait_for_exit: WaitQueue::new(),
+            signals: TaskSignals::new(),
             kstack: None,
             ctx: UnsafeCell::new(TaskContext::new()),
             #[cfg(feature = "tls")]
@@ -157,7 +250,7 @@ impl TaskInner {
         if t.name == "idle" {
             t.is_idle = true;
         }
-        Arc::new(AxTask::new(t))
+        Self::register(Arc::new(AxTask::new(t)))
     }
 
     /// Creates an "init task" using the current CPU states, to use as the
@@ -174,12 +267,14 @@ impl TaskInner {
         if t.name == "idle" {
             t.is_idle = true;
         }
-        Arc::new(AxTask::new(t))
+        Self::register(Arc::new(AxTask::new(t)))
     }
 
-    #[inline]
-    pub(crate) fn state(&self) -> TaskState {
-        self.state.load(Ordering::Acquire).into()
+    fn register(task: AxTaskRef) -> AxTaskRef {
+        TASK_TABLE
+            .lock()
+            .insert(task.id().as_u64(), Arc::downgrade(&task));
+        task
     }
 
     #[inline]
@@ -187,6 +282,15 @@ impl TaskInner {
         self.state.store(state as u8, Ordering::Release)
     }
 
+    /// Changes the state from `from` to `to` atomically, returns `false` if
+    /// the task is not in the `from` state.
+    #[inline]
+    pub(crate) fn transition_state(&self, from: TaskState, to: TaskState) -> bool {
+        self.state
+            .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
     #[inline]
     pub(crate) fn is_running(&self) -> bool {
         matches!(self.state(), TaskState::Running)
@@ -197,21 +301,11 @@ impl TaskInner {
         matches!(self.state(), TaskState::Ready)
     }
 
-    #[inline]
-    pub(crate) fn is_blocked(&self) -> bool {
-        matches!(self.state(), TaskState::Blocked)
-    }
-
     #[inline]
     pub(crate) const fn is_init(&self) -> bool {
         self.is_init
     }
 
-    #[inline]
-    pub(crate) const fn is_idle(&self) -> bool {
-        self.is_idle
-    }
-
     #[inline]
     pub(crate) fn in_wait_queue(&self) -> bool {
         self.in_wait_queue.load(Ordering::Acquire)
@@ -234,6 +328,37 @@ impl TaskInner {
         self.in_timer_list.store(in_timer_list, Ordering::Release);
     }
 
+    #[inline]
+    pub(crate) fn set_cpumask(&self, cpumask: CpuMask) {
+        self.cpumask.store(cpumask.0, Ordering::Release);
+    }
+
+    /// Returns the CPU whose run queue the task is in. It must be called with
+    /// the run queue locked to get a stable result.
+    #[inline]
+    pub(crate) fn rq_cpu(&self) -> Option<usize> {
+        match self.rq_cpu.load(Ordering::Acquire) {
+            NO_CPU => None,
+            cpu_id => Some(cpu_id),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_rq_cpu(&self, cpu_id: Option<usize>) {
+        self.rq_cpu
+            .store(cpu_id.unwrap_or(NO_CPU), Ordering::Release);
+    }
+
+    #[inline]
+    pub(crate) fn on_cpu(&self) -> bool {
+        self.on_cpu.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_on_cpu(&self, on_cpu: bool) {
+        self.on_cpu.store(on_cpu, Ordering::Release);
+    }
+
     #[inline]
     #[cfg(feature = "preempt")]
     pub(crate) fn set_preempt_pending(&self, pending: bool) {
@@ -265,16 +390,21 @@ impl TaskInner {
     fn current_check_preempt_pending() {
         let curr = crate::current();
         if curr.need_resched.load(Ordering::Acquire) && curr.can_preempt(0) {
-            let mut rq = crate::RUN_QUEUE.lock();
+            let rq = crate::current_run_queue();
             if curr.need_resched.load(Ordering::Acquire) {
                 rq.preempt_resched();
             }
         }
     }
 
-    pub(crate) fn notify_exit(&self, exit_code: i32, rq: &mut AxRunQueue) {
+    #[inline]
+    pub(crate) fn signals(&self) -> &TaskSignals {
+        &self.signals
+    }
+
+    pub(crate) fn notify_exit(&self, exit_code: i32) {
         self.exit_code.store(exit_code, Ordering::Release);
-        self.wait_for_exit.notify_all_locked(false, rq);
+        self.wait_for_exit.notify_all(false);
     }
 
     #[inline]
@@ -296,6 +426,7 @@ impl fmt::Debug for TaskInner {
 impl Drop for TaskInner {
     fn drop(&mut self) {
         debug!("task drop: {}", self.id_name());
+        TASK_TABLE.lock().remove(&self.id.as_u64());
     }
 }
 
@@ -379,8 +510,8 @@ impl Deref for CurrentTask {
 }
 
 extern "C" fn task_entry() -> ! {
-    // release the lock that was implicitly held across the reschedule
-    unsafe { crate::RUN_QUEUE.force_unlock() };
+    // finish the context switch started by the previous task
+    unsafe { crate::run_queue::finish_switch() };
     #[cfg(feature = "irq")]
     axhal::arch::enable_irqs();
     let task = crate::current();
@@ -1,7 +1,10 @@
 use core::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Mutex, Once};
 
-use crate::{self as axtask, current, WaitQueue};
+use spinlock::SpinNoIrq;
+
+use crate::signal::{self, SignalAction, SignalHandler, SignalSet};
+use crate::{self as axtask, current, CpuMask, WaitQueue};
 
 static INIT: Once = Once::new();
 static SERIAL: Mutex<()> = Mutex::new(());
@@ -39,9 +42,9 @@ fn test_fp_state_switch() {
 
     const NUM_TASKS: usize = 5;
     const FLOATS: [f64; NUM_TASKS] = [
-        3.141592653589793,
-        2.718281828459045,
-        -1.4142135623730951,
+        core::f64::consts::PI,
+        core::f64::consts::E,
+        -core::f64::consts::SQRT_2,
         0.0,
         0.618033988749895,
     ];
@@ -104,6 +107,42 @@ fn test_wait_queue() {
     assert!(!current().in_wait_queue());
 }
 
+#[test]
+fn test_wait_until_locked_condition() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_TASKS: usize = 10;
+
+    static WQ: WaitQueue = WaitQueue::new();
+    // the condition takes the lock that is held while notifying
+    static ITEMS: SpinNoIrq<usize> = SpinNoIrq::new(0);
+
+    let take_item = || {
+        let mut items = ITEMS.lock();
+        *items > 0 && {
+            *items -= 1;
+            true
+        }
+    };
+    let tasks: Vec<_> = (0..NUM_TASKS)
+        .map(|_| axtask::spawn(move || WQ.wait_until(take_item)))
+        .collect();
+    for i in 0..NUM_TASKS {
+        let mut items = ITEMS.lock();
+        *items += 1;
+        WQ.notify_one(true);
+        drop(items);
+        if i % 2 == 0 {
+            axtask::yield_now();
+        }
+    }
+    for task in tasks {
+        assert_eq!(task.join(), Some(0));
+    }
+    assert_eq!(*ITEMS.lock(), 0);
+}
+
 #[test]
 fn test_task_join() {
     let _lock = SERIAL.lock();
@@ -124,7 +163,259 @@ fn test_task_join() {
         ));
     }
 
-    for i in 0..NUM_TASKS {
-        assert_eq!(tasks[i].join(), Some(i as _));
+    for (i, task) in tasks.iter().enumerate() {
+        assert_eq!(task.join(), Some(i as _));
+    }
+}
+
+#[test]
+fn test_all_tasks() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let task = axtask::spawn_raw(|| axtask::exit(0), "all_tasks".into(), 0x1000);
+    let id = task.id();
+    let find = || axtask::all_tasks().into_iter().find(|t| t.id() == id);
+    assert_eq!(
+        find().map(|t| t.name().to_string()),
+        Some("all_tasks".into())
+    );
+    assert!(axtask::all_tasks().iter().any(|t| t.id() == current().id()));
+
+    assert_eq!(task.join(), Some(0));
+    assert_eq!(task.state(), axtask::TaskState::Exited);
+    drop(task);
+    while find().is_some() {
+        axtask::yield_now(); // wait for the exited task to be dropped
     }
 }
+
+#[test]
+fn test_affinity() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let curr = current();
+    assert_eq!(curr.cpumask(), CpuMask::full());
+    assert!(CpuMask::one(axconfig::SMP).is_empty());
+    assert!(!axtask::set_affinity(
+        curr.as_task_ref(),
+        CpuMask::one(axconfig::SMP)
+    ));
+    assert_eq!(curr.cpumask(), CpuMask::full());
+
+    assert!(axtask::set_affinity(curr.as_task_ref(), CpuMask::one(0)));
+    assert_eq!(curr.cpumask(), CpuMask::one(0));
+    // spawned tasks inherit the affinity
+    let task = axtask::spawn(|| {
+        assert!(current().cpumask().contains(0));
+        axtask::yield_now();
+    });
+    assert_eq!(task.cpumask(), CpuMask::one(0));
+    assert!(axtask::set_affinity(&task, CpuMask::full()));
+    assert_eq!(task.cpumask(), CpuMask::full());
+    assert_eq!(task.join(), Some(0));
+
+    assert!(axtask::set_affinity(curr.as_task_ref(), CpuMask::full()));
+}
+
+static HANDLED_SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn count_signal(_sig: core::ffi::c_int) {
+    HANDLED_SIGNALS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn set_handler(sig: usize, handler: SignalHandler) {
+    signal::set_signal_action(
+        sig,
+        SignalAction {
+            handler,
+            ..SignalAction::DEFAULT
+        },
+    );
+}
+
+#[test]
+fn test_signal_mask_pending() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let mut mask = SignalSet::empty();
+    mask.add(signal::SIGUSR1);
+    mask.add(signal::SIGKILL);
+    assert_eq!(signal::set_signal_mask(mask), SignalSet::empty());
+    // SIGKILL can't be blocked
+    assert!(signal::signal_mask().contains(signal::SIGUSR1));
+    assert!(!signal::signal_mask().contains(signal::SIGKILL));
+
+    set_handler(signal::SIGUSR1, SignalHandler::Handler(count_signal));
+    let handled = HANDLED_SIGNALS.load(Ordering::Relaxed);
+    assert!(signal::send_signal(
+        current().as_task_ref(),
+        signal::SIGUSR1,
+        signal::SI_USER
+    ));
+    assert!(!signal::send_signal(
+        current().as_task_ref(),
+        0,
+        signal::SI_USER
+    ));
+
+    // blocked signals stay pending
+    assert!(signal::pending_signals().contains(signal::SIGUSR1));
+    assert!(!signal::signal_pending());
+    signal::handle_signals();
+    assert_eq!(HANDLED_SIGNALS.load(Ordering::Relaxed), handled);
+
+    signal::set_signal_mask(SignalSet::empty());
+    assert!(signal::signal_pending());
+    signal::handle_signals();
+    assert_eq!(HANDLED_SIGNALS.load(Ordering::Relaxed), handled + 1);
+    assert!(signal::pending_signals().is_empty());
+
+    set_handler(signal::SIGUSR1, SignalHandler::Default);
+}
+
+#[test]
+fn test_sigsuspend_wakeup() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    set_handler(signal::SIGUSR2, SignalHandler::Handler(count_signal));
+    let mut mask = SignalSet::empty();
+    mask.add(signal::SIGUSR2);
+    signal::set_signal_mask(mask);
+
+    let handled = HANDLED_SIGNALS.load(Ordering::Relaxed);
+    let main_task = current().as_task_ref().clone();
+    let task = axtask::spawn(move || {
+        axtask::yield_now();
+        signal::send_signal(&main_task, signal::SIGUSR2, signal::SI_TKILL);
+    });
+    // blocks until the signal is sent, as it's not blocked while suspended
+    signal::suspend(SignalSet::empty());
+    assert_eq!(HANDLED_SIGNALS.load(Ordering::Relaxed), handled + 1);
+    assert_eq!(signal::signal_mask(), mask);
+    assert_eq!(task.join(), Some(0));
+
+    signal::set_signal_mask(SignalSet::empty());
+    set_handler(signal::SIGUSR2, SignalHandler::Default);
+}
+
+#[cfg(feature = "irq")]
+#[test]
+fn test_real_timer() {
+    use core::time::Duration;
+
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    let mut mask = SignalSet::empty();
+    mask.add(signal::SIGALRM);
+    signal::set_signal_mask(mask);
+
+    let ms = Duration::from_millis;
+    let zero = (Duration::ZERO, Duration::ZERO);
+    assert_eq!(signal::real_timer(), Some(zero));
+    assert_eq!(signal::set_real_timer(ms(10), ms(5)), Some(zero));
+    let (remaining, interval) = signal::real_timer().unwrap();
+    assert!(!remaining.is_zero() && remaining <= ms(10));
+    assert_eq!(interval, ms(5));
+
+    // There are no timer interrupts on the host, so expire it by hand.
+    let curr = current().as_task_ref().clone();
+    let now = axhal::time::current_time();
+    signal::real_timer_expired(curr.clone(), now);
+    assert!(signal::pending_signals().is_empty());
+    signal::real_timer_expired(curr, now + ms(10));
+    assert!(signal::pending_signals().contains(signal::SIGALRM));
+    // rearmed for the next period
+    let (remaining, interval) = signal::real_timer().unwrap();
+    assert!(remaining > ms(10) && remaining <= ms(15));
+    assert_eq!(interval, ms(5));
+
+    let (remaining, interval) = signal::set_real_timer(Duration::ZERO, Duration::ZERO).unwrap();
+    assert!(remaining > ms(10) && interval == ms(5));
+    assert_eq!(signal::real_timer(), Some(zero));
+
+    set_handler(signal::SIGALRM, SignalHandler::Ignore);
+    signal::set_signal_mask(SignalSet::empty());
+    signal::handle_signals();
+    assert!(signal::pending_signals().is_empty());
+    set_handler(signal::SIGALRM, SignalHandler::Default);
+}
+
+#[cfg(feature = "sched_edf")]
+#[test]
+fn test_periodic_admission() {
+    use crate::{rt_utilization, RtParams};
+
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const MS: u64 = 1_000_000;
+    let spawn = |params| axtask::spawn_periodic(|| false, "periodic".into(), 0x1000, params);
+
+    // the budget exceeds the deadline
+    assert!(spawn(RtParams::new(10 * MS, 5 * MS).with_deadline(4 * MS)).is_none());
+    assert_eq!(rt_utilization(0), 0);
+
+    let t1 = spawn(RtParams::new(10 * MS, 6 * MS)).unwrap();
+    assert_eq!(rt_utilization(0), RtParams::FULL_UTILIZATION * 6 / 10);
+    // 60% + 50% of the only CPU
+    assert!(spawn(RtParams::new(10 * MS, 5 * MS)).is_none());
+    let t2 = spawn(RtParams::new(20 * MS, 8 * MS)).unwrap();
+    assert_eq!(rt_utilization(0), RtParams::FULL_UTILIZATION);
+
+    // the utilization is released when the tasks exit
+    assert_eq!(t1.join(), Some(0));
+    assert_eq!(t2.join(), Some(0));
+    assert_eq!(rt_utilization(0), 0);
+}
+
+#[cfg(feature = "sched_edf")]
+#[test]
+fn test_periodic_deadline_miss() {
+    use crate::RtParams;
+    use axhal::time::{current_ticks, set_current_ticks};
+
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const MS: u64 = 1_000_000;
+    static JOBS: AtomicUsize = AtomicUsize::new(0);
+
+    // the clock of the dummy platform is in nanoseconds
+    let start = current_ticks();
+    let params = RtParams::new(10 * MS, 2 * MS).with_deadline(5 * MS);
+    let task = axtask::spawn_periodic(
+        move || match JOBS.fetch_add(1, Ordering::Relaxed) {
+            0 => {
+                set_current_ticks(start + MS);
+                true
+            }
+            _ => {
+                // the second job is released at 10 ms, with a deadline at 15 ms
+                set_current_ticks(start + 16 * MS);
+                false
+            }
+        },
+        "periodic".into(),
+        0x1000,
+        params,
+    )
+    .unwrap();
+
+    // the task runs until it sleeps for the next period
+    while JOBS.load(Ordering::Relaxed) == 0 {
+        axtask::yield_now();
+    }
+    assert_eq!(task.deadline_misses(), 0);
+    set_current_ticks(start + 10 * MS);
+    axtask::on_timer_tick();
+
+    assert_eq!(task.join(), Some(0));
+    assert_eq!(JOBS.load(Ordering::Relaxed), 2);
+    assert_eq!(task.deadline_misses(), 1);
+    set_current_ticks(start);
+}
@@ -4,37 +4,76 @@ use lazy_init::LazyInit;
 use spinlock::SpinNoIrq;
 use timer_list::{TimeValue, TimerEvent, TimerList};
 
-use crate::{AxTaskRef, RUN_QUEUE};
+use crate::AxTaskRef;
 
-// TODO: per-CPU
-static TIMER_LIST: LazyInit<SpinNoIrq<TimerList<TaskWakeupEvent>>> = LazyInit::new();
+type TaskTimerList = SpinNoIrq<TimerList<TaskTimerEvent>>;
 
-struct TaskWakeupEvent(AxTaskRef);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_TIMER_LIST: LazyInit<TaskTimerList> = LazyInit::new();
 
-impl TimerEvent for TaskWakeupEvent {
-    fn callback(self, _now: TimeValue) {
-        let mut rq = RUN_QUEUE.lock();
-        self.0.set_in_timer_list(false);
-        rq.unblock_task(self.0, true);
+/// Timer lists of all CPUs indexed by CPU IDs. Timers are set on the list of
+/// the current CPU and expired by its timer interrupts, but a task may have
+/// migrated when canceling them, so all lists are searched then.
+static TIMER_LISTS: [LazyInit<TaskTimerList>; axconfig::SMP] = [NO_TIMER_LIST; axconfig::SMP];
+
+fn local_timer_list() -> &'static TaskTimerList {
+    &TIMER_LISTS[axhal::cpu::this_cpu_id()]
+}
+
+fn online_timer_lists() -> impl Iterator<Item = &'static TaskTimerList> {
+    TIMER_LISTS.iter().filter_map(|timers| timers.try_get())
+}
+
+enum TaskTimerEvent {
+    /// Wakes up the sleeping task.
+    Wakeup(AxTaskRef),
+    /// The real-time interval timer of the task expires.
+    Signal(AxTaskRef),
+}
+
+impl TimerEvent for TaskTimerEvent {
+    fn callback(self, now: TimeValue) {
+        match self {
+            Self::Wakeup(task) => {
+                task.set_in_timer_list(false);
+                crate::run_queue::unblock_task(task, true);
+            }
+            Self::Signal(task) => crate::signal::real_timer_expired(task, now),
+        }
     }
 }
 
 pub fn set_alarm_wakeup(deadline: TimeValue, task: AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
+    let mut timers = local_timer_list().lock();
     task.set_in_timer_list(true);
-    timers.set(deadline, TaskWakeupEvent(task));
+    timers.set(deadline, TaskTimerEvent::Wakeup(task));
 }
 
 pub fn cancel_alarm(task: &AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
     task.set_in_timer_list(false);
-    timers.cancel(|t| Arc::ptr_eq(&t.0, task));
+    for timers in online_timer_lists() {
+        let mut timers = timers.lock();
+        timers.cancel(|e| matches!(e, TaskTimerEvent::Wakeup(t) if Arc::ptr_eq(t, task)));
+    }
+}
+
+pub fn set_signal_timer(deadline: TimeValue, task: AxTaskRef) {
+    local_timer_list()
+        .lock()
+        .set(deadline, TaskTimerEvent::Signal(task));
+}
+
+pub fn cancel_signal_timer(task: &AxTaskRef) {
+    for timers in online_timer_lists() {
+        let mut timers = timers.lock();
+        timers.cancel(|e| matches!(e, TaskTimerEvent::Signal(t) if Arc::ptr_eq(t, task)));
+    }
 }
 
 pub fn check_events() {
     loop {
         let now = current_time();
-        let event = TIMER_LIST.lock().expire_one(now);
+        let event = local_timer_list().lock().expire_one(now);
         if let Some((_deadline, event)) = event {
             event.callback(now);
         } else {
@@ -43,6 +82,7 @@ pub fn check_events() {
     }
 }
 
+/// Initializes the timer list of the current CPU.
 pub fn init() {
-    TIMER_LIST.init_by(SpinNoIrq::new(TimerList::new()));
+    TIMER_LISTS[axhal::cpu::this_cpu_id()].init_by(SpinNoIrq::new(TimerList::new()));
 }
@@ -1,8 +1,9 @@
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
-use spinlock::SpinRaw;
+use spinlock::SpinNoIrq;
 
-use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
+use crate::run_queue::{current_run_queue, unblock_task};
+use crate::{AxTaskRef, CurrentTask};
 
 /// A queue to store sleeping tasks.
 ///
@@ -27,21 +28,21 @@ use crate::{AxRunQueue, AxTaskRef, CurrentTask, RUN_QUEUE};
 /// assert_eq!(VALUE.load(Ordering::Relaxed), 1);
 /// ```
 pub struct WaitQueue {
-    queue: SpinRaw<VecDeque<AxTaskRef>>, // we already disabled IRQs when lock the `RUN_QUEUE`
+    queue: SpinNoIrq<VecDeque<AxTaskRef>>,
 }
 
 impl WaitQueue {
     /// Creates an empty wait queue.
     pub const fn new() -> Self {
         Self {
-            queue: SpinRaw::new(VecDeque::new()),
+            queue: SpinNoIrq::new(VecDeque::new()),
         }
     }
 
     /// Creates an empty wait queue with space for at least `capacity` elements.
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            queue: SpinRaw::new(VecDeque::with_capacity(capacity)),
+            queue: SpinNoIrq::new(VecDeque::with_capacity(capacity)),
         }
     }
 
@@ -50,8 +51,6 @@ impl WaitQueue {
         // the event from another queue.
         if curr.in_wait_queue() {
             // wake up by timer (timeout).
-            // `RUN_QUEUE` is not locked here, so disable IRQs.
-            let _guard = kernel_guard::IrqSave::new();
             self.queue.lock().retain(|t| !curr.ptr_eq(t));
             curr.set_in_wait_queue(false);
         }
@@ -62,10 +61,43 @@ impl WaitQueue {
         }
     }
 
+    /// Blocks the current task and puts it into the wait queue, unless
+    /// `condition` is true. Returns whether `condition` is true.
+    ///
+    /// `on_push` is called with the task after it's put into the queue.
+    fn block_current_unless<F, P>(&self, condition: &F, on_push: P) -> bool
+    where
+        F: Fn() -> bool,
+        P: FnOnce(&AxTaskRef),
+    {
+        let ready = current_run_queue().block_current_unless(
+            |task| {
+                task.set_in_wait_queue(true);
+                self.queue.lock().push_back(task.clone());
+                on_push(&task);
+            },
+            condition,
+        );
+        if ready {
+            let curr = crate::current();
+            let mut wq = self.queue.lock();
+            if curr.in_wait_queue() {
+                wq.retain(|t| !curr.ptr_eq(t));
+                curr.set_in_wait_queue(false);
+            } else {
+                // The task was notified after checking the condition, pass
+                // the notification on as it's not needed.
+                drop(wq);
+                self.notify_one(false);
+            }
+        }
+        ready
+    }
+
     /// Blocks the current task and put it into the wait queue, until other task
     /// notifies it.
     pub fn wait(&self) {
-        RUN_QUEUE.lock().block_current(|task| {
+        current_run_queue().block_current(|task| {
             task.set_in_wait_queue(true);
             self.queue.lock().push_back(task)
         });
@@ -77,20 +109,15 @@ impl WaitQueue {
     ///
     /// Note that even other tasks notify this task, it will not wake up until
     /// the condition becomes true.
+    ///
+    /// The condition is checked with IRQs and preemption disabled, but not
+    /// with the wait queue locked, so it can take the (spin) locks that are
+    /// held while notifying the queue.
     pub fn wait_until<F>(&self, condition: F)
     where
         F: Fn() -> bool,
     {
-        loop {
-            let mut rq = RUN_QUEUE.lock();
-            if condition() {
-                break;
-            }
-            rq.block_current(|task| {
-                task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
-            });
-        }
+        while !self.block_current_unless(&condition, |_| {}) {}
         self.cancel_events(crate::current());
     }
 
@@ -105,11 +132,10 @@ impl WaitQueue {
             curr.id_name(),
             deadline
         );
-        crate::timers::set_alarm_wakeup(deadline, curr.clone());
-
-        RUN_QUEUE.lock().block_current(|task| {
+        current_run_queue().block_current(|task| {
             task.set_in_wait_queue(true);
-            self.queue.lock().push_back(task)
+            self.queue.lock().push_back(task.clone());
+            crate::timers::set_alarm_wakeup(deadline, task);
         });
         let timeout = curr.in_wait_queue(); // still in the wait queue, must have timed out
         self.cancel_events(curr);
@@ -120,7 +146,8 @@ impl WaitQueue {
     /// `condition` becomes true, or the given duration has elapsed.
     ///
     /// Note that even other tasks notify this task, it will not wake up until
-    /// the above conditions are met.
+    /// the above conditions are met. The condition is checked as in
+    /// [`WaitQueue::wait_until`].
     #[cfg(feature = "irq")]
     pub fn wait_timeout_until<F>(&self, dur: core::time::Duration, condition: F) -> bool
     where
@@ -133,19 +160,18 @@ impl WaitQueue {
             curr.id_name(),
             deadline
         );
-        crate::timers::set_alarm_wakeup(deadline, curr.clone());
-
         let mut timeout = true;
         while axhal::time::current_time() < deadline {
-            let mut rq = RUN_QUEUE.lock();
-            if condition() {
+            let ready = self.block_current_unless(&condition, |task| {
+                // the previous alarm may have expired before the task blocks
+                if !task.in_timer_list() {
+                    crate::timers::set_alarm_wakeup(deadline, task.clone());
+                }
+            });
+            if ready {
                 timeout = false;
                 break;
             }
-            rq.block_current(|task| {
-                task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
-            });
         }
         self.cancel_events(curr);
         timeout
@@ -156,9 +182,11 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_one(&self, resched: bool) -> bool {
-        let mut rq = RUN_QUEUE.lock();
-        if !self.queue.lock().is_empty() {
-            self.notify_one_locked(resched, &mut rq)
+        let mut wq = self.queue.lock();
+        if let Some(task) = wq.pop_front() {
+            task.set_in_wait_queue(false);
+            unblock_task(task, resched);
+            true
         } else {
             false
         }
@@ -169,15 +197,10 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_all(&self, resched: bool) {
-        loop {
-            let mut rq = RUN_QUEUE.lock();
-            if let Some(task) = self.queue.lock().pop_front() {
-                task.set_in_wait_queue(false);
-                rq.unblock_task(task, resched);
-            } else {
-                break;
-            }
-            drop(rq); // we must unlock `RUN_QUEUE` after unlocking `self.queue`.
+        let mut wq = self.queue.lock();
+        while let Some(task) = wq.pop_front() {
+            task.set_in_wait_queue(false);
+            unblock_task(task, resched);
         }
     }
 
@@ -186,31 +209,13 @@ impl WaitQueue {
     /// If `resched` is true, the current task will be preempted when the
     /// preemption is enabled.
     pub fn notify_task(&mut self, resched: bool, task: &AxTaskRef) -> bool {
-        let mut rq = RUN_QUEUE.lock();
         let mut wq = self.queue.lock();
         if let Some(index) = wq.iter().position(|t| Arc::ptr_eq(t, task)) {
             task.set_in_wait_queue(false);
-            rq.unblock_task(wq.remove(index).unwrap(), resched);
+            unblock_task(wq.remove(index).unwrap(), resched);
             true
         } else {
             false
         }
     }
-
-    pub(crate) fn notify_one_locked(&self, resched: bool, rq: &mut AxRunQueue) -> bool {
-        if let Some(task) = self.queue.lock().pop_front() {
-            task.set_in_wait_queue(false);
-            rq.unblock_task(task, resched);
-            true
-        } else {
-            false
-        }
-    }
-
-    pub(crate) fn notify_all_locked(&self, resched: bool, rq: &mut AxRunQueue) {
-        while let Some(task) = self.queue.lock().pop_front() {
-            task.set_in_wait_queue(false);
-            rq.unblock_task(task, resched);
-        }
-    }
 }
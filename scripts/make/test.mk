@@ -3,6 +3,9 @@
 define unit_test
   $(call run_cmd,cargo test,-p percpu $(1) -- --nocapture)
   $(call run_cmd,cargo test,-p axfs $(1) --features "myfs" -- --nocapture)
+  $(call run_cmd,cargo test,-p axfs $(1) --features "ext2" -- --nocapture)
+  $(call run_cmd,cargo test,-p arceos_posix_api $(1) --features "multitask,poll,pipe" -- --nocapture)
+  $(call run_cmd,cargo test,-p axtask $(1) --features "sched_edf" -- --nocapture)
   $(call run_cmd,cargo test,--workspace --exclude "arceos-*" $(1) -- --nocapture)
 endef
 
@@ -1,7 +1,30 @@
+#include <stdarg.h>
 #include <stddef.h>
 #include <stdio.h>
 #include <sys/mman.h>
 
+#ifdef AX_CONFIG_MMAP
+
+void *ax_mremap(void *old_address, size_t old_size, size_t new_size, int flags,
+                void *new_address);
+
+void *mremap(void *old_address, size_t old_size, size_t new_size, int flags,
+             ... /* void *new_address */)
+{
+    void *new_address = NULL;
+
+    if (flags & MREMAP_FIXED) {
+        va_list ap;
+        va_start(ap, flags);
+        new_address = va_arg(ap, void *);
+        va_end(ap);
+    }
+
+    return ax_mremap(old_address, old_size, new_size, flags, new_address);
+}
+
+#else // AX_CONFIG_MMAP
+
 // TODO:
 void *mmap(void *addr, size_t len, int prot, int flags, int fildes, off_t off)
 {
@@ -37,3 +60,5 @@ int madvise(void *addr, size_t len, int advice)
     unimplemented();
     return 0;
 }
+
+#endif // AX_CONFIG_MMAP
@@ -46,38 +46,6 @@ int pthread_setname_np(pthread_t thread, const char *name)
     return 0;
 }
 
-int pthread_cond_init(pthread_cond_t *restrict c, const pthread_condattr_t *restrict a)
-{
-    *c = (pthread_cond_t){0};
-    if (a) {
-        c->_c_clock = a->__attr & 0x7fffffff;
-        if (a->__attr >> 31)
-            c->_c_shared = (void *)-1;
-    }
-    return 0;
-}
-
-// TODO
-int pthread_cond_signal(pthread_cond_t *__cond)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int pthread_cond_wait(pthread_cond_t *__restrict__ __cond, pthread_mutex_t *__restrict__ __mutex)
-{
-    unimplemented();
-    return 0;
-}
-
-// TODO
-int pthread_cond_broadcast(pthread_cond_t *c)
-{
-    unimplemented();
-    return 0;
-}
-
 #define DEFAULT_STACK_SIZE 131072
 #define DEFAULT_GUARD_SIZE 8192
 
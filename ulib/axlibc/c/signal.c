@@ -2,35 +2,48 @@
 #include <signal.h>
 #include <stddef.h>
 #include <stdio.h>
+#include <sys/time.h>
+#include <unistd.h>
 
-int sigaction_helper(int signum, const struct sigaction *act, struct sigaction *oldact,
-                     size_t sigsetsize)
-{
-    if (signum == SIGKILL || signum == SIGSTOP)
-        return -EINVAL;
+#ifdef AX_CONFIG_MULTITASK
 
-    if (oldact)
-        *oldact = (struct sigaction){0};
+// `sigaction`, `kill`, `pthread_sigmask`, `sigprocmask`, `sigpending`,
+// `sigsuspend` and `pthread_kill` are implemented in Rust.
 
-    return 0;
+int raise(int sig)
+{
+    return kill(getpid(), sig);
 }
 
-void (*signal(int signum, void (*handler)(int)))(int)
+unsigned alarm(unsigned seconds)
 {
-    struct sigaction old;
-    struct sigaction act = {
-        .sa_handler = handler, .sa_flags = SA_RESTART, /* BSD signal semantics */
-    };
-
-    if (sigaction_helper(signum, &act, &old, sizeof(sigset_t)) < 0)
-        return SIG_ERR;
+    struct itimerval it = {.it_value.tv_sec = seconds}, old = {0};
+    setitimer(ITIMER_REAL, &it, &old);
+    return old.it_value.tv_sec + !!old.it_value.tv_usec;
+}
 
-    return (old.sa_flags & SA_SIGINFO) ? NULL : old.sa_handler;
+int pause(void)
+{
+    sigset_t mask;
+    sigprocmask(SIG_BLOCK, NULL, &mask);
+    return sigsuspend(&mask);
 }
 
+#else // AX_CONFIG_MULTITASK
+
+// Signals are not supported without multitasking.
+
 int sigaction(int sig, const struct sigaction *restrict act, struct sigaction *restrict oact)
 {
-    return sigaction_helper(sig, act, oact, sizeof(sigset_t));
+    if (sig == SIGKILL || sig == SIGSTOP) {
+        errno = EINVAL;
+        return -1;
+    }
+
+    if (oact)
+        *oact = (struct sigaction){0};
+
+    return 0;
 }
 
 // TODO
@@ -40,6 +53,35 @@ int kill(pid_t __pid, int __sig)
     return 0;
 }
 
+// TODO
+int raise(int __sig)
+{
+    unimplemented();
+    return 0;
+}
+
+// TODO
+int pthread_sigmask(int __how, const sigset_t *restrict __newmask, sigset_t *restrict __oldmask)
+{
+    unimplemented();
+    return 0;
+}
+
+#endif // AX_CONFIG_MULTITASK
+
+void (*signal(int signum, void (*handler)(int)))(int)
+{
+    struct sigaction old;
+    struct sigaction act = {
+        .sa_handler = handler, .sa_flags = SA_RESTART, /* BSD signal semantics */
+    };
+
+    if (sigaction(signum, &act, &old) < 0)
+        return SIG_ERR;
+
+    return (old.sa_flags & SA_SIGINFO) ? NULL : old.sa_handler;
+}
+
 int sigemptyset(sigset_t *set)
 {
     set->__bits[0] = 0;
@@ -52,10 +94,13 @@ int sigemptyset(sigset_t *set)
     return 0;
 }
 
-// TODO
-int raise(int __sig)
+int sigfillset(sigset_t *set)
 {
-    unimplemented();
+    sigemptyset(set);
+    for (int sig = 1; sig < _NSIG; sig++) {
+        unsigned s = sig - 1;
+        set->__bits[s / 8 / sizeof *set->__bits] |= 1UL << (s & (8 * sizeof *set->__bits - 1));
+    }
     return 0;
 }
 
@@ -70,18 +115,21 @@ int sigaddset(sigset_t *set, int sig)
     return 0;
 }
 
-// TODO
-int pthread_sigmask(int __how, const sigset_t *restrict __newmask, sigset_t *restrict __oldmask)
+int sigdelset(sigset_t *set, int sig)
 {
-    unimplemented();
+    unsigned s = sig - 1;
+    if (s >= _NSIG - 1 || sig - 32U < 3) {
+        errno = EINVAL;
+        return -1;
+    }
+    set->__bits[s / 8 / sizeof *set->__bits] &= ~(1UL << (s & (8 * sizeof *set->__bits - 1)));
     return 0;
 }
 
-#ifdef AX_CONFIG_MULTITASK
-// TODO
-int pthread_kill(pthread_t t, int sig)
+int sigismember(const sigset_t *set, int sig)
 {
-    unimplemented();
-    return 0;
+    unsigned s = sig - 1;
+    if (s >= _NSIG - 1)
+        return 0;
+    return !!(set->__bits[s / 8 / sizeof *set->__bits] & (1UL << (s & (8 * sizeof *set->__bits - 1))));
 }
-#endif
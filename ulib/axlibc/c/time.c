@@ -174,12 +174,14 @@ void tzset()
     return;
 }
 
+#ifndef AX_CONFIG_MULTITASK
 // TODO
 int setitimer(int _which, const struct itimerval *restrict _new, struct itimerval *restrict _old)
 {
     unimplemented();
     return 0;
 }
+#endif
 
 // TODO
 char *ctime_r(const time_t *t, char *buf)
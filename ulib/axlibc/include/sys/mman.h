@@ -42,6 +42,13 @@
 #define MREMAP_FIXED     2
 #define MREMAP_DONTUNMAP 4
 
+/* Advice to madvise.  */
+#define MADV_NORMAL     0 /* No further special treatment.  */
+#define MADV_RANDOM     1 /* Expect random page references.  */
+#define MADV_SEQUENTIAL 2 /* Expect sequential page references.  */
+#define MADV_WILLNEED   3 /* Will need these pages.  */
+#define MADV_DONTNEED   4 /* Don't need these pages.  */
+
 void *mmap(void *addr, size_t len, int prot, int flags, int fildes, off_t off);
 int munmap(void *addr, size_t length);
 void *mremap(void *old_address, size_t old_size, size_t new_size, int flags,
@@ -1,7 +1,8 @@
 use core::ffi::{c_char, c_int};
 
 use arceos_posix_api::{
-    sys_fstat, sys_getcwd, sys_lseek, sys_lstat, sys_open, sys_rename, sys_stat,
+    sys_fstat, sys_fsync, sys_getcwd, sys_link, sys_lseek, sys_lstat, sys_open, sys_readlink,
+    sys_rename, sys_stat, sys_symlink, sys_sync,
 };
 
 use crate::{ctypes, utils::e};
@@ -51,6 +52,34 @@ pub unsafe extern "C" fn lstat(path: *const c_char, buf: *mut ctypes::stat) -> c
     e(sys_lstat(path, buf) as _)
 }
 
+/// Read the target of the symbolic link `path` into `buf`.
+///
+/// Return the number of bytes placed in `buf`.
+#[no_mangle]
+pub unsafe extern "C" fn readlink(
+    path: *const c_char,
+    buf: *mut c_char,
+    bufsize: usize,
+) -> ctypes::ssize_t {
+    e(sys_readlink(path, buf, bufsize) as _) as _
+}
+
+/// Create a symbolic link `linkpath` which contains the string `target`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn symlink(target: *const c_char, linkpath: *const c_char) -> c_int {
+    e(sys_symlink(target, linkpath))
+}
+
+/// Create a hard link `newpath` to the existing file `oldpath`.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn link(oldpath: *const c_char, newpath: *const c_char) -> c_int {
+    e(sys_link(oldpath, newpath))
+}
+
 /// Get the path of the current directory.
 #[no_mangle]
 pub unsafe extern "C" fn getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
@@ -65,3 +94,26 @@ pub unsafe extern "C" fn getcwd(buf: *mut c_char, size: usize) -> *mut c_char {
 pub unsafe extern "C" fn rename(old: *const c_char, new: *const c_char) -> c_int {
     e(sys_rename(old, new))
 }
+
+/// Synchronize the data of the file `fd` to the disk.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fsync(fd: c_int) -> c_int {
+    e(sys_fsync(fd))
+}
+
+/// Synchronize the data of the file `fd` to the disk, the same as [`fsync`]
+/// since metadata is not distinguished.
+///
+/// Return 0 if success.
+#[no_mangle]
+pub unsafe extern "C" fn fdatasync(fd: c_int) -> c_int {
+    e(sys_fsync(fd))
+}
+
+/// Write all cached data of the filesystems to the disks.
+#[no_mangle]
+pub unsafe extern "C" fn sync() {
+    sys_sync()
+}
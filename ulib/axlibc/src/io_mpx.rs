@@ -1,11 +1,13 @@
 use crate::{ctypes, utils::e};
 
-use core::ffi::c_int;
+use core::ffi::{c_int, c_void};
 
 #[cfg(feature = "select")]
 use arceos_posix_api::sys_select;
 #[cfg(feature = "epoll")]
 use arceos_posix_api::{sys_epoll_create, sys_epoll_ctl, sys_epoll_wait};
+#[cfg(feature = "poll")]
+use arceos_posix_api::{sys_poll, sys_ppoll};
 
 /// Creates a new epoll instance.
 ///
@@ -52,3 +54,26 @@ pub unsafe extern "C" fn select(
 ) -> c_int {
     e(sys_select(nfds, readfds, writefds, exceptfds, timeout))
 }
+
+/// Wait for some event on the file descriptors, with a timeout in milliseconds.
+#[cfg(feature = "poll")]
+#[no_mangle]
+pub unsafe extern "C" fn poll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    timeout: c_int,
+) -> c_int {
+    e(sys_poll(fds, nfds, timeout))
+}
+
+/// Wait for some event on the file descriptors, with a `timespec` timeout.
+#[cfg(feature = "poll")]
+#[no_mangle]
+pub unsafe extern "C" fn ppoll(
+    fds: *mut ctypes::pollfd,
+    nfds: ctypes::nfds_t,
+    timeout: *const ctypes::timespec,
+    sigmask: *const c_void,
+) -> c_int {
+    e(sys_ppoll(fds, nfds, timeout, sigmask))
+}
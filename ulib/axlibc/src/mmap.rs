@@ -0,0 +1,71 @@
+use core::ffi::{c_int, c_void};
+
+use arceos_posix_api::{sys_madvise, sys_mmap, sys_mprotect, sys_mremap, sys_munmap};
+
+use crate::{ctypes, utils::e};
+
+/// The value returned by `mmap` and `mremap` on failure.
+const MAP_FAILED: *mut c_void = usize::MAX as _;
+
+/// Converts the pointer returned by `sys_mmap` or `sys_mremap` to the return
+/// value of `mmap` or `mremap`, and sets `errno` on failure.
+///
+/// Errors are returned as `-errno`, which are never page aligned addresses.
+fn e_ptr(ret: *mut c_void) -> *mut c_void {
+    let ret = ret as isize;
+    if (-4095..0).contains(&ret) {
+        e(ret as c_int);
+        MAP_FAILED
+    } else {
+        ret as _
+    }
+}
+
+/// Map files or devices into memory.
+#[no_mangle]
+pub unsafe extern "C" fn mmap(
+    addr: *mut c_void,
+    len: ctypes::size_t,
+    prot: c_int,
+    flags: c_int,
+    fd: c_int,
+    off: ctypes::off_t,
+) -> *mut c_void {
+    e_ptr(sys_mmap(addr, len, prot, flags, fd, off))
+}
+
+/// Unmap files or devices from memory.
+#[no_mangle]
+pub unsafe extern "C" fn munmap(addr: *mut c_void, len: ctypes::size_t) -> c_int {
+    e(sys_munmap(addr, len))
+}
+
+/// Set the protection on a region of memory.
+#[no_mangle]
+pub unsafe extern "C" fn mprotect(addr: *mut c_void, len: ctypes::size_t, prot: c_int) -> c_int {
+    e(sys_mprotect(addr, len, prot))
+}
+
+/// Remap a virtual memory address, called by the variadic `mremap` in C.
+#[no_mangle]
+pub unsafe extern "C" fn ax_mremap(
+    old_address: *mut c_void,
+    old_size: ctypes::size_t,
+    new_size: ctypes::size_t,
+    flags: c_int,
+    new_address: *mut c_void,
+) -> *mut c_void {
+    e_ptr(sys_mremap(
+        old_address,
+        old_size,
+        new_size,
+        flags,
+        new_address,
+    ))
+}
+
+/// Give advice about use of memory.
+#[no_mangle]
+pub unsafe extern "C" fn madvise(addr: *mut c_void, len: ctypes::size_t, advice: c_int) -> c_int {
+    e(sys_madvise(addr, len, advice))
+}
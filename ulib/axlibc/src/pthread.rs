@@ -57,3 +57,98 @@ pub unsafe extern "C" fn pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t)
 pub unsafe extern "C" fn pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     e(api::sys_pthread_mutex_unlock(mutex))
 }
+
+// Unlike most libc functions, the following return the error number directly
+// instead of setting `errno`, as `ETIMEDOUT` or `EBUSY` is expected by callers.
+
+/// Initialize a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    -api::sys_pthread_cond_init(cond, attr)
+}
+
+/// Destroy a condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    -api::sys_pthread_cond_destroy(cond)
+}
+
+/// Atomically unlock the mutex and wait on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    -api::sys_pthread_cond_wait(cond, mutex)
+}
+
+/// Like `pthread_cond_wait`, but returns `ETIMEDOUT` if not signaled before
+/// the absolute time `abstime`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    -api::sys_pthread_cond_timedwait(cond, mutex, abstime)
+}
+
+/// Wake up one thread waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    -api::sys_pthread_cond_signal(cond)
+}
+
+/// Wake up all threads waiting on the condition variable.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    -api::sys_pthread_cond_broadcast(cond)
+}
+
+/// Initialize a readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    -api::sys_pthread_rwlock_init(rwlock, attr)
+}
+
+/// Destroy a readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_destroy(rwlock)
+}
+
+/// Lock the readers-writer lock for reading.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_rdlock(rwlock)
+}
+
+/// Try to lock the readers-writer lock for reading, returns `EBUSY` on failure.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_tryrdlock(rwlock)
+}
+
+/// Lock the readers-writer lock for writing.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_wrlock(rwlock)
+}
+
+/// Try to lock the readers-writer lock for writing, returns `EBUSY` on failure.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_trywrlock(rwlock)
+}
+
+/// Unlock the readers-writer lock.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    -api::sys_pthread_rwlock_unlock(rwlock)
+}
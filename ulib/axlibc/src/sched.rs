@@ -0,0 +1,23 @@
+use crate::{ctypes, utils::e};
+use arceos_posix_api::{sys_sched_getaffinity, sys_sched_setaffinity};
+use core::ffi::c_int;
+
+/// Set the CPU affinity of the thread `pid` (0 for the current thread).
+#[no_mangle]
+pub unsafe extern "C" fn sched_setaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *const ctypes::cpu_set_t,
+) -> c_int {
+    e(sys_sched_setaffinity(pid, cpusetsize, mask))
+}
+
+/// Get the CPU affinity of the thread `pid` (0 for the current thread).
+#[no_mangle]
+pub unsafe extern "C" fn sched_getaffinity(
+    pid: c_int,
+    cpusetsize: usize,
+    mask: *mut ctypes::cpu_set_t,
+) -> c_int {
+    e(sys_sched_getaffinity(pid, cpusetsize, mask))
+}
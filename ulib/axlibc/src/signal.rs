@@ -0,0 +1,82 @@
+use crate::{ctypes, utils::e};
+use arceos_posix_api::{
+    sys_getitimer, sys_kill, sys_pthread_kill, sys_setitimer, sys_sigaction, sys_sigpending,
+    sys_sigprocmask, sys_sigsuspend,
+};
+use core::ffi::c_int;
+
+/// Examine and change the action of a signal.
+#[no_mangle]
+pub unsafe extern "C" fn sigaction(
+    signum: c_int,
+    act: *const ctypes::sigaction,
+    oldact: *mut ctypes::sigaction,
+) -> c_int {
+    e(sys_sigaction(signum, act, oldact))
+}
+
+/// Examine and change the blocked signals of the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn sigprocmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    e(sys_sigprocmask(how, set, oldset))
+}
+
+/// Examine and change the blocked signals of the current thread.
+///
+/// Like other pthread functions, it returns the error number directly instead
+/// of setting `errno`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_sigmask(
+    how: c_int,
+    set: *const ctypes::sigset_t,
+    oldset: *mut ctypes::sigset_t,
+) -> c_int {
+    -sys_sigprocmask(how, set, oldset)
+}
+
+/// Get the pending signals of the current thread.
+#[no_mangle]
+pub unsafe extern "C" fn sigpending(set: *mut ctypes::sigset_t) -> c_int {
+    e(sys_sigpending(set))
+}
+
+/// Wait for a signal with the blocked signals replaced by `mask`.
+#[no_mangle]
+pub unsafe extern "C" fn sigsuspend(mask: *const ctypes::sigset_t) -> c_int {
+    e(sys_sigsuspend(mask))
+}
+
+/// Send a signal to a thread (0 for the current thread).
+#[no_mangle]
+pub unsafe extern "C" fn kill(pid: c_int, sig: c_int) -> c_int {
+    e(sys_kill(pid, sig))
+}
+
+/// Send a signal to a thread.
+///
+/// Like other pthread functions, it returns the error number directly instead
+/// of setting `errno`.
+#[no_mangle]
+pub unsafe extern "C" fn pthread_kill(thread: ctypes::pthread_t, sig: c_int) -> c_int {
+    -sys_pthread_kill(thread, sig)
+}
+
+/// Get the value of an interval timer.
+#[no_mangle]
+pub unsafe extern "C" fn getitimer(which: c_int, curr_value: *mut ctypes::itimerval) -> c_int {
+    e(sys_getitimer(which, curr_value))
+}
+
+/// Set the value of an interval timer.
+#[no_mangle]
+pub unsafe extern "C" fn setitimer(
+    which: c_int,
+    new_value: *const ctypes::itimerval,
+    old_value: *mut ctypes::itimerval,
+) -> c_int {
+    e(sys_setitimer(which, new_value, old_value))
+}
@@ -11,6 +11,20 @@ use alloc::{string::String, vec::Vec};
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
 
+/// Statistics of the block caches returned by [`block_cache_stats`].
+pub use arceos_api::fs::AxBlockCacheStats as BlockCacheStats;
+
+/// Line configuration of serial ports, see [`set_serial_config`].
+#[cfg(feature = "serial")]
+pub use arceos_api::fs::{
+    AxParity as Parity, AxSerialConfig as SerialConfig, AxStopBits as StopBits,
+};
+
+/// Pin functions, pull resistors and edges of GPIO controllers, see
+/// [`gpio_set_function`].
+#[cfg(feature = "gpio")]
+pub use arceos_api::gpio::{AxEdge as Edge, AxPinFunction as PinFunction, AxPull as Pull};
+
 /// Read the entire contents of a file into a bytes vector.
 #[cfg(feature = "alloc")]
 pub fn read(path: &str) -> io::Result<Vec<u8>> {
@@ -42,6 +56,32 @@ pub fn metadata(path: &str) -> io::Result<Metadata> {
     File::open(path)?.metadata()
 }
 
+/// Query the metadata about a file without following symlinks.
+pub fn symlink_metadata(path: &str) -> io::Result<Metadata> {
+    arceos_api::fs::ax_symlink_attr(path).map(Metadata)
+}
+
+/// Reads a symbolic link, returning the file that the link points to.
+#[cfg(feature = "alloc")]
+pub fn read_link(path: &str) -> io::Result<String> {
+    arceos_api::fs::ax_read_link(path)
+}
+
+/// Creates a new symbolic link on the filesystem.
+///
+/// The `link` path will be a symbolic link pointing to the `original` path.
+pub fn symlink(original: &str, link: &str) -> io::Result<()> {
+    arceos_api::fs::ax_symlink(original, link)
+}
+
+/// Creates a new hard link on the filesystem.
+///
+/// The `link` path will be a link pointing to the `original` path. Note that
+/// both paths must be in the same mounted filesystem.
+pub fn hard_link(original: &str, link: &str) -> io::Result<()> {
+    arceos_api::fs::ax_hard_link(original, link)
+}
+
 /// Returns an iterator over the entries within a directory.
 pub fn read_dir(path: &str) -> io::Result<ReadDir> {
     ReadDir::new(path)
@@ -75,3 +115,96 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     arceos_api::fs::ax_rename(old, new)
 }
+
+/// Mounts the filesystem of type `fstype` from `source` on the directory
+/// `target`.
+///
+/// FAT (`vfat`) and ext2 (`ext2`, `ext3` or `ext4`) filesystems are mounted
+/// from the additional block devices named `/dev/sdb`, `/dev/sdc`, etc. Other
+/// types (`ramfs`, `devfs`, `procfs` and `sysfs`) ignore the `source`.
+pub fn mount(source: &str, target: &str, fstype: &str) -> io::Result<()> {
+    arceos_api::fs::ax_mount(source, target, fstype)
+}
+
+/// Unmounts the filesystem mounted on the directory `target`.
+pub fn umount(target: &str) -> io::Result<()> {
+    arceos_api::fs::ax_umount(target)
+}
+
+/// Writes the cached data of all block devices back to the devices.
+pub fn sync() -> io::Result<()> {
+    arceos_api::fs::ax_sync()
+}
+
+/// Returns the hit and miss counters of the block caches, e.g., to measure
+/// the I/O performance of a benchmark.
+pub fn block_cache_stats() -> BlockCacheStats {
+    arceos_api::fs::ax_block_cache_stats()
+}
+
+/// Returns the line configuration (baud rate, data bits, parity and stop
+/// bits) of the serial port at `path`, e.g., `/dev/ttyS0`.
+#[cfg(feature = "serial")]
+pub fn serial_config(path: &str) -> io::Result<SerialConfig> {
+    arceos_api::fs::ax_serial_config(path)
+}
+
+/// Sets the line configuration of the serial port at `path`, e.g.,
+/// `/dev/ttyS0`.
+#[cfg(feature = "serial")]
+pub fn set_serial_config(path: &str, config: &SerialConfig) -> io::Result<()> {
+    arceos_api::fs::ax_set_serial_config(path, config)
+}
+
+/// Returns the function of the pin of the GPIO controller at `path`, e.g.,
+/// `/dev/gpiochip0`.
+#[cfg(feature = "gpio")]
+pub fn gpio_function(path: &str, pin: usize) -> io::Result<PinFunction> {
+    arceos_api::gpio::ax_gpio_function(path, pin)
+}
+
+/// Sets the function of the pin of the GPIO controller at `path`, i.e.,
+/// makes it an input, an output, or selects an alternative function.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_function(path: &str, pin: usize, func: PinFunction) -> io::Result<()> {
+    arceos_api::gpio::ax_gpio_set_function(path, pin, func)
+}
+
+/// Returns the level of the pin of the GPIO controller at `path`, `true` for
+/// high.
+#[cfg(feature = "gpio")]
+pub fn gpio_level(path: &str, pin: usize) -> io::Result<bool> {
+    arceos_api::gpio::ax_gpio_level(path, pin)
+}
+
+/// Drives the output pin of the GPIO controller at `path` to high or low.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_level(path: &str, pin: usize, high: bool) -> io::Result<()> {
+    arceos_api::gpio::ax_gpio_set_level(path, pin, high)
+}
+
+/// Returns the pull resistor of the pin of the GPIO controller at `path`.
+#[cfg(feature = "gpio")]
+pub fn gpio_pull(path: &str, pin: usize) -> io::Result<Pull> {
+    arceos_api::gpio::ax_gpio_pull(path, pin)
+}
+
+/// Sets the pull resistor of the pin of the GPIO controller at `path`.
+#[cfg(feature = "gpio")]
+pub fn gpio_set_pull(path: &str, pin: usize, pull: Pull) -> io::Result<()> {
+    arceos_api::gpio::ax_gpio_set_pull(path, pin, pull)
+}
+
+/// Selects the edges of the pin of the GPIO controller at `path` that are
+/// reported by [`gpio_wait_event`].
+#[cfg(feature = "gpio")]
+pub fn gpio_set_edge_interrupt(path: &str, pin: usize, edge: Edge) -> io::Result<()> {
+    arceos_api::gpio::ax_gpio_set_edge_interrupt(path, pin, edge)
+}
+
+/// Blocks until a selected edge is detected on the GPIO controller at `path`,
+/// and returns its pin.
+#[cfg(feature = "gpio")]
+pub fn gpio_wait_event(path: &str) -> io::Result<usize> {
+    arceos_api::gpio::ax_gpio_wait_event(path)
+}
@@ -0,0 +1,79 @@
+use super::IpAddr;
+use crate::io;
+use core::time::Duration;
+
+use arceos_api::net::{self as api, AxIcmpSocketHandle};
+
+/// An ICMP socket for sending echo requests and receiving echo replies, e.g.,
+/// to implement `ping`.
+///
+/// This is an ArceOS extension which is not in the Rust standard library. It
+/// works like the unprivileged "ping socket" of Linux: the identifier of the
+/// echo requests is replaced with the one that the socket is bound to, and
+/// only the echo replies with that identifier are received.
+pub struct IcmpSocket(AxIcmpSocketHandle);
+
+impl IcmpSocket {
+    /// Creates an ICMP socket bound to the given echo identifier.
+    ///
+    /// If `ident` is zero, an unused identifier is chosen. It can be queried
+    /// with [`IcmpSocket::ident`].
+    pub fn bind(ident: u16) -> io::Result<IcmpSocket> {
+        let socket = api::ax_icmp_socket();
+        api::ax_icmp_bind(&socket, ident)?;
+        Ok(IcmpSocket(socket))
+    }
+
+    /// Returns the echo identifier that this socket is bound to.
+    pub fn ident(&self) -> io::Result<u16> {
+        api::ax_icmp_ident(&self.0)
+    }
+
+    /// Sends an echo request to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// `buf` is a whole ICMP echo request (ICMPv6 for IPv6 addresses)
+    /// including its header. The checksum is calculated by the network stack.
+    pub fn send_to(&self, buf: &[u8], addr: IpAddr) -> io::Result<usize> {
+        api::ax_icmp_send_to(&self.0, buf, addr)
+    }
+
+    /// Receives a single ICMP message on the socket. On success, returns the
+    /// number of bytes read and the origin.
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, IpAddr)> {
+        api::ax_icmp_recv_from(&self.0, buf)
+    }
+
+    /// Moves this ICMP socket into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        api::ax_icmp_set_nonblocking(&self.0, nonblocking)
+    }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then receiving calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is passed
+    /// to this method.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_icmp_set_read_timeout(&self.0, timeout)
+    }
+
+    /// Returns the read timeout of this socket.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_icmp_read_timeout(&self.0)
+    }
+
+    /// Sets the write timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then sending calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is passed
+    /// to this method.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_icmp_set_write_timeout(&self.0, timeout)
+    }
+
+    /// Returns the write timeout of this socket.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_icmp_write_timeout(&self.0)
+    }
+}
@@ -1,5 +1,6 @@
 use super::{SocketAddr, ToSocketAddrs};
 use crate::io::{self, prelude::*};
+use core::time::Duration;
 
 use arceos_api::net::{self as api, AxTcpSocketHandle};
 
@@ -40,9 +41,89 @@ impl TcpStream {
     }
 
     /// Shuts down the connection.
+    ///
+    /// It blocks if a linger timeout is set by [`set_linger`](Self::set_linger).
     pub fn shutdown(&self) -> io::Result<()> {
         api::ax_tcp_shutdown(&self.0)
     }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        api::ax_tcp_set_nodelay(&self.0, nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        api::ax_tcp_nodelay(&self.0)
+    }
+
+    /// Sets the value of the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// If set, keep-alive packets are sent when the connection is idle.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        api::ax_tcp_set_keepalive(&self.0, keepalive)
+    }
+
+    /// Gets the value of the `SO_KEEPALIVE` option on this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        api::ax_tcp_keepalive(&self.0)
+    }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`read`](Read::read) calls
+    /// will block indefinitely. An [`Err`] is returned if the zero
+    /// [`Duration`] is passed to this method.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_tcp_set_read_timeout(&self.0, timeout)
+    }
+
+    /// Returns the read timeout of this socket.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_read_timeout(&self.0)
+    }
+
+    /// Sets the write timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`write`](Write::write) calls
+    /// will block indefinitely. An [`Err`] is returned if the zero
+    /// [`Duration`] is passed to this method.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_tcp_set_write_timeout(&self.0, timeout)
+    }
+
+    /// Returns the write timeout of this socket.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_write_timeout(&self.0)
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// If set, [`shutdown`](Self::shutdown) blocks until the connection is
+    /// closed or the timeout expires, and a zero timeout resets the
+    /// connection immediately.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        api::ax_tcp_set_linger(&self.0, linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        api::ax_tcp_linger(&self.0)
+    }
+
+    /// Gets the size of the receive buffer (`SO_RCVBUF`) of this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        api::ax_tcp_recv_buffer_size(&self.0)
+    }
+
+    /// Gets the size of the send buffer (`SO_SNDBUF`) of this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        api::ax_tcp_send_buffer_size(&self.0)
+    }
 }
 
 impl Read for TcpStream {
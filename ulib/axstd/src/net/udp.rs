@@ -1,5 +1,6 @@
 use super::{SocketAddr, ToSocketAddrs};
 use crate::io;
+use core::time::Duration;
 
 use arceos_api::net::{self as api, AxUdpSocketHandle};
 
@@ -93,4 +94,42 @@ impl UdpSocket {
     pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
         api::ax_udp_recv(&self.0, buf)
     }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then receiving calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is passed
+    /// to this method.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_udp_set_read_timeout(&self.0, timeout)
+    }
+
+    /// Returns the read timeout of this socket.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_udp_read_timeout(&self.0)
+    }
+
+    /// Sets the write timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then sending calls will block
+    /// indefinitely. An [`Err`] is returned if the zero [`Duration`] is passed
+    /// to this method.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        api::ax_udp_set_write_timeout(&self.0, timeout)
+    }
+
+    /// Returns the write timeout of this socket.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        api::ax_udp_write_timeout(&self.0)
+    }
+
+    /// Gets the size of the receive buffer (`SO_RCVBUF`) of this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        api::ax_udp_recv_buffer_size(&self.0)
+    }
+
+    /// Gets the size of the send buffer (`SO_SNDBUF`) of this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        api::ax_udp_send_buffer_size(&self.0)
+    }
 }
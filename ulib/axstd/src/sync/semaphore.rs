@@ -0,0 +1,73 @@
+//! A counting semaphore.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use arceos_api::task::{self as api, AxWaitQueueHandle};
+
+use crate::time::Instant;
+
+/// A counting semaphore.
+///
+/// It maintains a count of available permits. [`Semaphore::acquire`] blocks
+/// the current task until a permit is available and takes it, and
+/// [`Semaphore::release`] returns a permit and wakes up a waiting task.
+pub struct Semaphore {
+    wq: AxWaitQueueHandle,
+    count: AtomicUsize,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the initial number of permits.
+    pub const fn new(count: usize) -> Self {
+        Self {
+            wq: AxWaitQueueHandle::new(),
+            count: AtomicUsize::new(count),
+        }
+    }
+
+    /// Returns the number of available permits.
+    pub fn value(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Acquires a permit, blocking the current task until one is available.
+    pub fn acquire(&self) {
+        while !self.try_acquire() {
+            api::ax_wait_queue_wait(&self.wq, || self.value() > 0, None);
+        }
+    }
+
+    /// Acquires a permit, blocking the current task until one is available or
+    /// the given duration has elapsed.
+    ///
+    /// Returns `true` if a permit was acquired. The timeout is ignored if the
+    /// `irq` feature is not enabled.
+    pub fn acquire_timeout(&self, dur: Duration) -> bool {
+        let start = Instant::now();
+        while !self.try_acquire() {
+            let elapsed = start.elapsed();
+            if elapsed >= dur
+                || api::ax_wait_queue_wait(&self.wq, || self.value() > 0, Some(dur - elapsed))
+            {
+                return self.try_acquire();
+            }
+        }
+        true
+    }
+
+    /// Tries to acquire a permit without blocking. Returns `true` on success.
+    pub fn try_acquire(&self) -> bool {
+        self.count
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Releases a permit, waking up a task waiting for it.
+    pub fn release(&self) {
+        self.count.fetch_add(1, Ordering::Release);
+        api::ax_wait_queue_wake(&self.wq, 1);
+    }
+}
@@ -9,6 +9,10 @@ use core::{cell::UnsafeCell, num::NonZeroU64};
 use arceos_api::task::{self as api, AxTaskHandle};
 use axerrno::ax_err_type;
 
+/// Real-time parameters of periodic threads, see [`spawn_periodic`].
+#[cfg(feature = "sched_edf")]
+pub use arceos_api::task::AxRtParams as RtParams;
+
 /// A unique identifier for a running thread.
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub struct ThreadId(NonZeroU64);
@@ -122,6 +126,35 @@ impl Builder {
             packet: my_packet,
         })
     }
+
+    /// Spawns a new periodic real-time thread by taking ownership of the
+    /// `Builder`, and returns an [`io::Result`] to its [`JoinHandle`].
+    ///
+    /// See [`spawn_periodic`] for the details.
+    #[cfg(feature = "sched_edf")]
+    pub fn spawn_periodic<F>(self, params: RtParams, f: F) -> io::Result<JoinHandle<()>>
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        if !params.is_valid() {
+            return Err(ax_err_type!(InvalidInput, "invalid real-time parameters"));
+        }
+        let name = self.name.unwrap_or_default();
+        let stack_size = self
+            .stack_size
+            .unwrap_or(arceos_api::config::TASK_STACK_SIZE);
+
+        let task = api::ax_spawn_periodic(f, name, stack_size, params)
+            .ok_or_else(|| ax_err_type!(ResourceBusy, "not enough CPU time"))?;
+        Ok(JoinHandle {
+            thread: Thread::from_id(task.id()),
+            native: task,
+            // there's nothing to return but `()`
+            packet: Arc::new(Packet {
+                result: UnsafeCell::new(Some(())),
+            }),
+        })
+    }
 }
 
 /// Gets a handle to the thread that invokes it.
@@ -147,6 +180,28 @@ where
     Builder::new().spawn(f).expect("failed to spawn thread")
 }
 
+/// Spawns a new periodic real-time thread, returning a [`JoinHandle`] for it.
+///
+/// The thread calls `f` once per period until it returns `false`, and each
+/// call is a job scheduled by the Earliest Deadline First (EDF) scheduler. The
+/// times in `params` are in nanoseconds.
+///
+/// It fails with [`InvalidInput`] if `params` is invalid, or with
+/// [`ResourceBusy`] if the thread does not fit in any CPU allowed by the
+/// affinity of the current thread, i.e., the utilization of the periodic
+/// threads on that CPU would exceed 1. The number of jobs that missed their
+/// deadlines is returned by [`JoinHandle::deadline_misses`].
+///
+/// [`InvalidInput`]: io::Error::InvalidInput
+/// [`ResourceBusy`]: io::Error::ResourceBusy
+#[cfg(feature = "sched_edf")]
+pub fn spawn_periodic<F>(params: RtParams, f: F) -> io::Result<JoinHandle<()>>
+where
+    F: FnMut() -> bool + Send + 'static,
+{
+    Builder::new().spawn_periodic(params, f)
+}
+
 struct Packet<T> {
     result: UnsafeCell<Option<T>>,
 }
@@ -173,6 +228,13 @@ impl<T> JoinHandle<T> {
         &self.thread
     }
 
+    /// Returns the number of jobs of the periodic thread that completed after
+    /// their deadlines, or 0 if it's not a periodic thread.
+    #[cfg(feature = "sched_edf")]
+    pub fn deadline_misses(&self) -> usize {
+        api::ax_deadline_misses(&self.native)
+    }
+
     /// Waits for the associated thread to finish.
     ///
     /// This function will return immediately if the associated thread has